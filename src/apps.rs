@@ -0,0 +1,549 @@
+//! Locating application data directories and the caches inside them
+
+use crate::browsers::{join_relative, BaseDirs, Os};
+use crate::cleaner::get_dir_size;
+use crate::cleanup_items::{
+    self, AppCheck, BreakdownEntry, Category, CleanTarget, CleanupItem, CleanupResult, CleanupType, ItemContext,
+    ItemStatus, RiskLevel,
+};
+use crate::util::format_size;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Cache directories inside a VS Code user data directory
+pub const VSCODE_CACHE_DIRS: &[&str] = &[
+    "Cache",
+    "CachedData",
+    "CachedExtensionVSIXs",
+    "Code Cache",
+    "GPUCache",
+    "logs",
+];
+
+/// Settings, keybindings, snippets and installed extensions; never cleaned
+pub const VSCODE_PROTECTED: &[&str] = &["User", "extensions"];
+
+/// A VS Code build and the name of its user data directory
+#[derive(Debug, Clone, Copy)]
+pub struct VsCodeSpec {
+    /// Prefix of the generated item id (`vscode` -> `vscode_cache`)
+    pub key: &'static str,
+    pub name: &'static str,
+    /// Directory under `%APPDATA%`, `~/.config` or `~/Library/Application Support`
+    pub dir_name: &'static str,
+    /// Process names on any OS; matched case-insensitively
+    pub process_names: &'static [&'static str],
+}
+
+pub const VSCODE: VsCodeSpec = VsCodeSpec {
+    key: "vscode",
+    name: "VS Code",
+    dir_name: "Code",
+    process_names: &["code", "Code Helper"],
+};
+
+pub const VSCODE_INSIDERS: VsCodeSpec = VsCodeSpec {
+    key: "vscode_insiders",
+    name: "VS Code Insiders",
+    dir_name: "Code - Insiders",
+    process_names: &["code-insiders", "Code - Insiders", "Code - Insiders Helper"],
+};
+
+pub const VSCODIUM: VsCodeSpec = VsCodeSpec {
+    key: "vscodium",
+    name: "VSCodium",
+    dir_name: "VSCodium",
+    process_names: &["codium", "VSCodium", "VSCodium Helper"],
+};
+
+/// Cache items for VS Code, plus Insiders and VSCodium when they are installed
+pub fn detect_vscode(os: Os, base: &BaseDirs) -> Vec<CleanupItem> {
+    let mut items = vec![VSCODE.cache_item(&VSCODE.user_data(os, base))];
+    for spec in [VSCODE_INSIDERS, VSCODIUM] {
+        let user_data = spec.user_data(os, base);
+        if user_data.is_dir() {
+            items.push(spec.cache_item(&user_data));
+        }
+    }
+    items
+}
+
+/// Existing cache directories of a VS Code user data directory
+pub fn vscode_cache_dirs(user_data: &Path) -> Vec<PathBuf> {
+    VSCODE_CACHE_DIRS
+        .iter()
+        .map(|name| user_data.join(name))
+        .filter(|path| path.is_dir())
+        .collect()
+}
+
+impl VsCodeSpec {
+    /// User data directory of this build (`%APPDATA%\Code` on Windows)
+    pub fn user_data(&self, os: Os, base: &BaseDirs) -> PathBuf {
+        app_data_root(os, base).join(self.dir_name)
+    }
+
+    /// Item cleaning only the cache directories; `User` and `extensions` are never touched.
+    /// Disabled by default.
+    pub fn cache_item(&self, user_data: &Path) -> CleanupItem {
+        CleanupItem {
+            id: format!("{}_cache", self.key),
+            name: format!("{} 缓存", self.name),
+            description: format!("{} 缓存文件 ({}), 不包括设置和扩展", self.name, user_data.display()),
+            cleanup_type: CleanupType::Directories(vscode_cache_dirs(user_data)),
+            category: Category::DevTools,
+            risk: RiskLevel::Moderate,
+            priority: 20,
+            enabled: false,
+            app_check: Some(AppCheck {
+                app_name: self.name.to_string(),
+                process_names: self.process_names.iter().map(|name| name.to_string()).collect(),
+                lock_files: vec![user_data.join("code.lock")],
+                pid_file_dirs: Vec::new(),
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// Per-user root under which desktop apps keep their data:
+/// `%APPDATA%`, `~/.config` or `~/Library/Application Support`
+fn app_data_root(os: Os, base: &BaseDirs) -> &Path {
+    match os {
+        Os::Windows | Os::Linux => &base.config,
+        Os::MacOs => &base.data,
+    }
+}
+
+/// Cache directories of an Electron app, relative to its data directory
+pub const ELECTRON_CACHE_DIRS: &[&str] = &["Cache", "Code Cache", "GPUCache", "Service Worker/CacheStorage"];
+
+/// Files Electron keeps in the data directory only while the app runs
+const ELECTRON_LOCK_FILES: &[&str] = &["SingletonLock", "SingletonSocket", "lockfile"];
+
+/// An Electron app whose caches can be cleaned.
+///
+/// The built-in table can be extended with `electron_apps` entries in the state file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ElectronApp {
+    /// Prefix of the generated item id (`discord` -> `discord_cache`)
+    pub key: String,
+    pub name: String,
+    /// Candidate data directories under the app-data root, `/`-separated; the first existing one is used
+    pub dir_names: Vec<String>,
+    /// Process names on any OS; matched case-insensitively
+    #[serde(default)]
+    pub process_names: Vec<String>,
+}
+
+/// Built-in Electron apps: (key, name, data directory candidates, process names)
+const ELECTRON_APPS: &[(&str, &str, &[&str], &[&str])] = &[
+    ("discord", "Discord", &["discord"], &["Discord"]),
+    ("slack", "Slack", &["Slack"], &["slack"]),
+    ("teams", "Microsoft Teams", &["Microsoft/Teams", "Microsoft Teams"], &["Teams", "Microsoft Teams"]),
+    ("postman", "Postman", &["Postman"], &["Postman"]),
+    ("signal", "Signal", &["Signal"], &["Signal"]),
+    ("obsidian", "Obsidian", &["obsidian"], &["Obsidian"]),
+];
+
+/// The built-in Electron app table
+pub fn builtin_electron_apps() -> Vec<ElectronApp> {
+    ELECTRON_APPS
+        .iter()
+        .map(|(key, name, dir_names, process_names)| ElectronApp {
+            key: key.to_string(),
+            name: name.to_string(),
+            dir_names: dir_names.iter().map(|dir| dir.to_string()).collect(),
+            process_names: process_names.iter().map(|process| process.to_string()).collect(),
+        })
+        .collect()
+}
+
+/// Cache items for the built-in and `extra` Electron apps that are installed.
+///
+/// An `extra` entry with the key of a built-in app replaces it.
+pub fn detect_electron_apps(os: Os, base: &BaseDirs, extra: &[ElectronApp]) -> Vec<CleanupItem> {
+    let mut apps: Vec<ElectronApp> = builtin_electron_apps()
+        .into_iter()
+        .filter(|app| !extra.iter().any(|other| other.key == app.key))
+        .collect();
+    apps.extend(extra.iter().cloned());
+
+    apps.iter()
+        .filter_map(|app| app.data_dir(os, base).map(|dir| app.cache_item(&dir)))
+        .collect()
+}
+
+impl ElectronApp {
+    /// The app's data directory, `None` if the app is not installed
+    pub fn data_dir(&self, os: Os, base: &BaseDirs) -> Option<PathBuf> {
+        let root = app_data_root(os, base);
+        self.dir_names
+            .iter()
+            .map(|dir| join_relative(root, dir))
+            .find(|path| path.is_dir())
+    }
+
+    /// Item cleaning only the Electron cache directories. Disabled by default.
+    pub fn cache_item(&self, data_dir: &Path) -> CleanupItem {
+        let cache_dirs = ELECTRON_CACHE_DIRS
+            .iter()
+            .map(|relative| join_relative(data_dir, relative))
+            .filter(|path| path.is_dir())
+            .collect();
+
+        CleanupItem {
+            id: format!("{}_cache", self.key),
+            name: format!("{} 缓存", self.name),
+            description: format!("{} 缓存文件 ({})", self.name, data_dir.display()),
+            cleanup_type: CleanupType::Directories(cache_dirs),
+            category: Category::Applications,
+            risk: RiskLevel::Safe,
+            priority: 20,
+            enabled: false,
+            app_check: Some(AppCheck {
+                app_name: self.name.clone(),
+                process_names: self.process_names.clone(),
+                lock_files: ELECTRON_LOCK_FILES.iter().map(|name| data_dir.join(name)).collect(),
+                pid_file_dirs: Vec::new(),
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// Streamed chunks younger than this are kept so Spotify does not download them again
+const SPOTIFY_MIN_AGE_DAYS: u32 = 7;
+
+/// Spotify's offline and streaming cache directories
+pub fn spotify_cache_dirs(os: Os, base: &BaseDirs) -> Vec<PathBuf> {
+    match os {
+        Os::Windows => vec![
+            join_relative(&base.data_local, "Spotify/Data"),
+            join_relative(&base.data_local, "Spotify/Storage"),
+        ],
+        Os::Linux => vec![base.cache.join("spotify")],
+        Os::MacOs => vec![base.cache.join("com.spotify.client")],
+    }
+}
+
+/// Spotify cache item. Disabled by default; the description lists the size of each directory.
+pub fn spotify_cache_item(os: Os, base: &BaseDirs) -> CleanupItem {
+    let dirs = spotify_cache_dirs(os, base);
+    let breakdown: Vec<String> = dirs
+        .iter()
+        .filter(|dir| dir.is_dir())
+        .map(|dir| {
+            let size = get_dir_size(dir).unwrap_or(0);
+            format!("{} {}", dir.display(), format_size(size))
+        })
+        .collect();
+    let description = if breakdown.is_empty() {
+        format!("Spotify 离线和流媒体缓存 ({})", dirs[0].display())
+    } else {
+        format!(
+            "Spotify 离线和流媒体缓存, 保留 {} 天内的文件: {}",
+            SPOTIFY_MIN_AGE_DAYS,
+            breakdown.join(", ")
+        )
+    };
+
+    CleanupItem {
+        id: "spotify_cache".to_string(),
+        name: "Spotify 缓存".to_string(),
+        description,
+        cleanup_type: CleanupType::Directories(dirs),
+        category: Category::Applications,
+        risk: RiskLevel::Safe,
+        priority: 20,
+        min_age_days: SPOTIFY_MIN_AGE_DAYS,
+        enabled: false,
+        app_check: Some(AppCheck {
+            app_name: "Spotify".to_string(),
+            process_names: vec!["Spotify".to_string()],
+            lock_files: Vec::new(),
+            pid_file_dirs: Vec::new(),
+        }),
+        ..Default::default()
+    }
+}
+
+/// Media Telegram downloaded within this many days is kept, so recent chats open without
+/// downloading it again
+pub const TELEGRAM_MIN_AGE_DAYS: u32 = 3;
+
+/// Subdirectories of each account's `tdata/user_data*` folder holding downloaded media
+pub const TELEGRAM_CACHE_DIRS: &[&str] = &["cache", "media_cache"];
+
+/// Telegram Desktop's data directory: `%APPDATA%\Telegram Desktop`,
+/// `~/.local/share/TelegramDesktop` or `~/Library/Application Support/Telegram Desktop`
+pub fn telegram_data_dir(os: Os, base: &BaseDirs) -> PathBuf {
+    match os {
+        Os::Windows | Os::MacOs => base.data.join("Telegram Desktop"),
+        Os::Linux => base.data.join("TelegramDesktop"),
+    }
+}
+
+/// Media cache directories of every account, `tdata/user_data/cache` for the first and
+/// `tdata/user_data#2/cache` and so on for the others. Nothing else in `tdata` is returned:
+/// it holds the login keys.
+pub fn telegram_cache_dirs(data_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(data_dir.join("tdata")) else {
+        return Vec::new();
+    };
+    let mut accounts: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("user_data"))
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .map(|entry| entry.path())
+        .collect();
+    accounts.sort();
+    accounts
+        .iter()
+        .flat_map(|account| TELEGRAM_CACHE_DIRS.iter().map(move |dir| account.join(dir)))
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// Telegram Desktop media cache item, keeping media from the last 3 days. Skipped while
+/// Telegram runs and disabled by default.
+pub fn telegram_cache_item(os: Os, base: &BaseDirs) -> CleanupItem {
+    let data_dir = telegram_data_dir(os, base);
+    CleanupItem {
+        id: "telegram_cache".to_string(),
+        name: "Telegram 媒体缓存".to_string(),
+        description: format!(
+            "Telegram Desktop 下载的图片和视频, 保留 {} 天内的文件, 不会触及登录数据 ({})",
+            TELEGRAM_MIN_AGE_DAYS,
+            data_dir.join("tdata").display()
+        ),
+        cleanup_type: CleanupType::Directories(telegram_cache_dirs(&data_dir)),
+        category: Category::Applications,
+        risk: RiskLevel::Safe,
+        priority: 20,
+        min_age_days: TELEGRAM_MIN_AGE_DAYS,
+        enabled: false,
+        app_check: Some(AppCheck {
+            app_name: "Telegram".to_string(),
+            process_names: vec!["Telegram".to_string(), "telegram-desktop".to_string()],
+            lock_files: Vec::new(),
+            pid_file_dirs: Vec::new(),
+        }),
+        ..Default::default()
+    }
+}
+
+/// Package folder prefix of the Microsoft Store app below `%LOCALAPPDATA%\Packages`
+pub const STORE_PACKAGE_PREFIX: &str = "Microsoft.WindowsStore_";
+
+/// Subdirectories of the Store's package folder that are cleaned; `Settings`,
+/// `SystemAppData` and the rest are never touched
+pub const STORE_CACHE_SUBDIRS: &[&str] = &["LocalCache", "TempState"];
+
+/// Cache directories of every Microsoft Store package family below `packages`, each family once
+pub fn store_cache_dirs(packages: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(packages) else {
+        return Vec::new();
+    };
+    let mut packages: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .get(..STORE_PACKAGE_PREFIX.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(STORE_PACKAGE_PREFIX))
+        })
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    // Package folder names are case-insensitive; keep one folder per family
+    packages.sort_by_cached_key(|path| (path.to_string_lossy().to_lowercase(), path.clone()));
+    packages.dedup_by_key(|path| path.to_string_lossy().to_lowercase());
+    packages
+        .into_iter()
+        .flat_map(|package| STORE_CACHE_SUBDIRS.iter().map(move |dir| package.join(dir)))
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// Microsoft Store cache item, emptying what `wsreset.exe` clears. Disabled by default.
+pub fn store_cache_item(base: &BaseDirs) -> CleanupItem {
+    let packages = base.data_local.join("Packages");
+    let dirs = store_cache_dirs(&packages);
+    CleanupItem {
+        id: "store_cache".to_string(),
+        name: "Microsoft Store 缓存".to_string(),
+        description: format!(
+            "Microsoft Store 的 LocalCache 和 TempState, 不会触及 Settings 和 SystemAppData: {}",
+            packages.join(format!("{}*", STORE_PACKAGE_PREFIX)).display()
+        ),
+        cleanup_type: CleanupType::Directories(dirs),
+        category: Category::Applications,
+        risk: RiskLevel::Safe,
+        enabled: false,
+        app_check: Some(AppCheck {
+            app_name: "Microsoft Store".to_string(),
+            process_names: vec!["WinStore.App".to_string()],
+            lock_files: Vec::new(),
+            pid_file_dirs: Vec::new(),
+        }),
+        ..Default::default()
+    }
+}
+
+/// Subdirectories of the newest version's cache that are cleaned; the rest is kept warm
+pub const JETBRAINS_CURRENT_DIRS: &[&str] = &["log", "index"];
+
+/// Process names of JetBrains IDEs on any OS; matched case-insensitively
+const JETBRAINS_PROCESSES: &[&str] = &[
+    "idea", "idea64", "pycharm", "pycharm64", "clion", "clion64", "webstorm", "webstorm64", "goland", "goland64",
+    "rider", "rider64", "phpstorm", "phpstorm64", "rubymine", "rubymine64", "datagrip", "datagrip64",
+];
+
+/// Root of the per-version JetBrains caches: `%LOCALAPPDATA%\JetBrains`, `~/.cache/JetBrains`
+/// or `~/Library/Caches/JetBrains`. Settings live elsewhere and are never cleaned.
+pub fn jetbrains_cache_root(os: Os, base: &BaseDirs) -> PathBuf {
+    match os {
+        Os::Windows => base.data_local.join("JetBrains"),
+        Os::Linux | Os::MacOs => base.cache.join("JetBrains"),
+    }
+}
+
+/// One `<Product><Version>` cache directory, such as `PyCharm2024.1`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JetBrainsVersion {
+    pub product: String,
+    pub version: Vec<u32>,
+    pub dir: PathBuf,
+}
+
+impl JetBrainsVersion {
+    /// `None` for directories that are not named `<Product><Version>`
+    pub fn parse(dir: PathBuf) -> Option<Self> {
+        let name = dir.file_name()?.to_string_lossy().into_owned();
+        let split = name.find(|c: char| c.is_ascii_digit()).filter(|&index| index > 0)?;
+        let version = name[split..]
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<Vec<u32>>>()?;
+        Some(Self {
+            product: name[..split].to_string(),
+            version,
+            dir,
+        })
+    }
+
+    /// `PyCharm 2024.1`
+    pub fn label(&self) -> String {
+        let version: Vec<String> = self.version.iter().map(u32::to_string).collect();
+        format!("{} {}", self.product, version.join("."))
+    }
+}
+
+/// JetBrains IDE caches of one user: whole caches of superseded versions, plus logs and
+/// indexes of the newest version of each product
+#[derive(Debug, Clone)]
+pub struct JetBrainsCaches {
+    root: PathBuf,
+}
+
+impl JetBrainsCaches {
+    pub fn new(root: &Path) -> Self {
+        Self { root: root.to_path_buf() }
+    }
+
+    /// Every version's cache, each paired with whether a newer version of the product exists
+    pub fn versions(&self) -> Vec<(JetBrainsVersion, bool)> {
+        let mut versions: Vec<JetBrainsVersion> = fs::read_dir(&self.root)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+                    .filter_map(|entry| JetBrainsVersion::parse(entry.path()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        versions.sort_by(|a, b| a.product.cmp(&b.product).then_with(|| a.version.cmp(&b.version)));
+
+        let mut result = Vec::with_capacity(versions.len());
+        for (index, version) in versions.iter().enumerate() {
+            let superseded = versions.get(index + 1).is_some_and(|next| next.product == version.product);
+            result.push((version.clone(), superseded));
+        }
+        result
+    }
+
+    /// Item for the JetBrains caches. Disabled by default.
+    pub fn cache_item(self) -> CleanupItem {
+        CleanupItem {
+            id: "jetbrains_cache".to_string(),
+            name: "JetBrains IDE 缓存".to_string(),
+            description: format!(
+                "旧版本 IntelliJ / PyCharm / CLion 等的缓存, 以及当前版本的日志和索引 ({}), 不包括设置",
+                self.root.display()
+            ),
+            category: Category::DevTools,
+            risk: RiskLevel::Safe,
+            priority: 20,
+            enabled: false,
+            app_check: Some(AppCheck {
+                app_name: "JetBrains IDE".to_string(),
+                process_names: JETBRAINS_PROCESSES.iter().map(|name| name.to_string()).collect(),
+                lock_files: Vec::new(),
+                pid_file_dirs: Vec::new(),
+            }),
+            cleanup_type: CleanupType::Custom(Arc::new(self)),
+            ..Default::default()
+        }
+    }
+
+    /// Run `whole` on the cache of each superseded version and `current` on the log and index
+    /// directories of each newest version, one breakdown entry per version
+    fn for_each_version(
+        &self,
+        whole: impl Fn(&Path) -> CleanupResult,
+        current: impl Fn(&Path) -> CleanupResult,
+    ) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for (version, superseded) in self.versions() {
+            let (mut part, note) = if superseded {
+                (whole(&version.dir), "旧版本, 整个删除")
+            } else {
+                let mut part = CleanupResult::new();
+                for name in JETBRAINS_CURRENT_DIRS {
+                    part.merge(current(&version.dir.join(name)));
+                }
+                (part, "当前版本, 只清理 log 和 index")
+            };
+            part.breakdown = vec![BreakdownEntry {
+                label: version.label(),
+                size_bytes: part.size_bytes,
+                note: note.to_string(),
+            }];
+            result.merge(part);
+        }
+        result
+    }
+}
+
+impl CleanTarget for JetBrainsCaches {
+    fn scan_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        self.for_each_version(|dir| cx.scan_path(dir), |dir| cx.scan_dir(dir))
+    }
+
+    fn clean_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let remove = |dir: &Path| {
+            let mut result = CleanupResult::new();
+            cx.remove_path(dir, &mut result);
+            result
+        };
+        self.for_each_version(remove, |dir| cx.clean_dir(dir))
+    }
+
+    fn validate_in(&self, cx: &ItemContext<'_>) -> ItemStatus {
+        cleanup_items::validate_roots(cx.fs(), std::slice::from_ref(&self.root))
+    }
+}
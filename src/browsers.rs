@@ -0,0 +1,649 @@
+//! Locating browser profiles and the cache directories inside them
+
+use crate::cleanup_items::{
+    AppCheck, Category, CleanErrorEntry, CleanTarget, CleanupItem, CleanupResult, CleanupType, ItemContext, ItemStatus,
+    RiskLevel,
+};
+use crate::history::{HistoryDb, HistoryDbKind, DEFAULT_HISTORY_MAX_AGE_DAYS};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Cache directories inside a Chromium profile, relative to the profile directory
+pub const CHROMIUM_CACHE_DIRS: &[&str] = &[
+    "Cache",
+    "Code Cache",
+    "GPUCache",
+    "Media Cache",
+    "Service Worker/CacheStorage",
+];
+
+/// Profile files holding user data. Never part of a cleanup target.
+pub const CHROMIUM_PROTECTED: &[&str] = &["History", "Bookmarks", "Login Data", "Preferences"];
+
+/// Profile directories (`Default`, `Profile 1`, ...) under a Chromium `User Data` directory.
+///
+/// A directory counts as a profile when it contains a `Preferences` file. Browsers with a
+/// single profile (Opera) keep it in `user_data` itself, which is then the only profile.
+/// Sorted by path.
+pub fn chromium_profiles(user_data: &Path) -> Vec<PathBuf> {
+    if user_data.join("Preferences").is_file() {
+        return vec![user_data.to_path_buf()];
+    }
+    let Ok(entries) = fs::read_dir(user_data) else {
+        return Vec::new();
+    };
+    let mut profiles: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join("Preferences").is_file())
+        .collect();
+    profiles.sort();
+    profiles
+}
+
+/// Existing cache directories of the given profiles
+pub fn chromium_cache_dirs(profiles: &[PathBuf]) -> Vec<PathBuf> {
+    profiles
+        .iter()
+        .flat_map(|profile| CHROMIUM_CACHE_DIRS.iter().map(move |relative| join_relative(profile, relative)))
+        .filter(|path| path.is_dir() && !is_protected(path))
+        .collect()
+}
+
+/// Operating systems with their own browser data layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Os {
+    Windows,
+    Linux,
+    MacOs,
+}
+
+impl Os {
+    pub fn current() -> Self {
+        if cfg!(windows) {
+            Os::Windows
+        } else if cfg!(target_os = "macos") {
+            Os::MacOs
+        } else {
+            Os::Linux
+        }
+    }
+}
+
+/// Per-user base directories that browser locations are relative to
+#[derive(Debug, Clone, Default)]
+pub struct BaseDirs {
+    pub home: PathBuf,
+    /// `%LOCALAPPDATA%` on Windows
+    pub data_local: PathBuf,
+    /// `%APPDATA%` on Windows, `~/.config` on Linux
+    pub config: PathBuf,
+    /// `~/.cache` on Linux, `~/Library/Caches` on macOS
+    pub cache: PathBuf,
+    /// `~/Library/Application Support` on macOS
+    pub data: PathBuf,
+}
+
+impl BaseDirs {
+    /// Directories of the current user, or `None` if they cannot be determined
+    pub fn current() -> Option<Self> {
+        Some(Self {
+            home: dirs::home_dir()?,
+            data_local: dirs::data_local_dir()?,
+            config: dirs::config_dir()?,
+            cache: dirs::cache_dir()?,
+            data: dirs::data_dir()?,
+        })
+    }
+}
+
+/// Where a Chromium-based browser keeps its data on each OS, relative to `BaseDirs`.
+///
+/// An empty path means the browser does not exist on that OS.
+#[derive(Debug, Clone, Copy)]
+pub struct ChromiumSpec {
+    /// Prefix of the generated item id (`chrome` -> `chrome_cache`)
+    pub key: &'static str,
+    pub name: &'static str,
+    /// Process names on any OS; matched case-insensitively
+    pub process_names: &'static [&'static str],
+    /// `User Data` directory under `data_local`
+    pub windows: &'static str,
+    /// Profile data lives under `config` (`%APPDATA%`) and only caches under `data_local`
+    pub windows_roaming: bool,
+    /// Profile directory under `config`; disk caches mirror it under `cache`
+    pub linux: &'static str,
+    /// Profile directory under `data`; disk caches mirror it under `cache`
+    pub macos: &'static str,
+}
+
+pub const CHROME: ChromiumSpec = ChromiumSpec {
+    key: "chrome",
+    name: "Chrome",
+    process_names: &["chrome", "Google Chrome"],
+    windows: "Google/Chrome/User Data",
+    windows_roaming: false,
+    linux: "google-chrome",
+    macos: "Google/Chrome",
+};
+
+pub const CHROMIUM: ChromiumSpec = ChromiumSpec {
+    key: "chromium",
+    name: "Chromium",
+    process_names: &["chromium", "chromium-browse", "Chromium"],
+    windows: "Chromium/User Data",
+    windows_roaming: false,
+    linux: "chromium",
+    macos: "Chromium",
+};
+
+pub const EDGE: ChromiumSpec = ChromiumSpec {
+    key: "edge",
+    name: "Microsoft Edge",
+    process_names: &["msedge", "Microsoft Edge"],
+    windows: "Microsoft/Edge/User Data",
+    windows_roaming: false,
+    linux: "microsoft-edge",
+    macos: "Microsoft Edge",
+};
+
+/// Chromium-based browsers that get an item only when installed.
+///
+/// Add new browsers here; ids are derived from `key`.
+pub const CHROMIUM_FAMILY: &[ChromiumSpec] = &[
+    ChromiumSpec {
+        key: "brave",
+        name: "Brave",
+        process_names: &["brave", "Brave Browser"],
+        windows: "BraveSoftware/Brave-Browser/User Data",
+        windows_roaming: false,
+        linux: "BraveSoftware/Brave-Browser",
+        macos: "BraveSoftware/Brave-Browser",
+    },
+    ChromiumSpec {
+        key: "opera",
+        name: "Opera",
+        process_names: &["opera", "Opera"],
+        windows: "Opera Software/Opera Stable",
+        windows_roaming: true,
+        linux: "opera",
+        macos: "com.operasoftware.Opera",
+    },
+    ChromiumSpec {
+        key: "vivaldi",
+        name: "Vivaldi",
+        process_names: &["vivaldi", "vivaldi-bin", "Vivaldi"],
+        windows: "Vivaldi/User Data",
+        windows_roaming: false,
+        linux: "vivaldi",
+        macos: "Vivaldi",
+    },
+    ChromiumSpec {
+        key: "arc",
+        name: "Arc",
+        process_names: &["Arc"],
+        // The Windows build lives in a versioned MSIX package directory
+        windows: "",
+        windows_roaming: false,
+        linux: "",
+        macos: "Arc/User Data",
+    },
+    ChromiumSpec {
+        key: "yandex",
+        name: "Yandex",
+        process_names: &["yandex_browser", "Yandex"],
+        windows: "Yandex/YandexBrowser/User Data",
+        windows_roaming: false,
+        linux: "yandex-browser",
+        macos: "Yandex/YandexBrowser",
+    },
+];
+
+/// Cache items for the `CHROMIUM_FAMILY` browsers whose data directory exists
+pub fn detect_chromium_browsers(os: Os, base: &BaseDirs) -> Vec<CleanupItem> {
+    CHROMIUM_FAMILY
+        .iter()
+        .filter_map(|spec| spec.install(os, base).map(|install| (spec, install)))
+        .filter(|(_, install)| install.user_data.is_dir())
+        .map(|(spec, install)| spec.cache_item(&install))
+        .collect()
+}
+
+/// Resolved data locations of a Chromium-based browser
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChromiumInstall {
+    /// Directory holding the profiles (`User Data` on Windows)
+    pub user_data: PathBuf,
+    /// Separate root where profile disk caches live (Linux and macOS)
+    pub cache_root: Option<PathBuf>,
+}
+
+impl ChromiumSpec {
+    /// Data locations of this browser for the given OS and user directories.
+    ///
+    /// `None` if the browser is not available on `os`.
+    pub fn install(&self, os: Os, base: &BaseDirs) -> Option<ChromiumInstall> {
+        let install = match os {
+            Os::Windows if self.windows_roaming => ChromiumInstall {
+                user_data: join_relative(&base.config, self.windows),
+                cache_root: Some(join_relative(&base.data_local, self.windows)),
+            },
+            Os::Windows => ChromiumInstall {
+                user_data: join_relative(&base.data_local, self.windows),
+                cache_root: None,
+            },
+            Os::Linux => ChromiumInstall {
+                user_data: join_relative(&base.config, self.linux),
+                cache_root: Some(join_relative(&base.cache, self.linux)),
+            },
+            Os::MacOs => ChromiumInstall {
+                user_data: join_relative(&base.data, self.macos),
+                cache_root: Some(join_relative(&base.cache, self.macos)),
+            },
+        };
+        let relative = match os {
+            Os::Windows => self.windows,
+            Os::Linux => self.linux,
+            Os::MacOs => self.macos,
+        };
+        (!relative.is_empty()).then_some(install)
+    }
+
+    /// Cache item covering every profile of the browser. Disabled by default.
+    pub fn cache_item(&self, install: &ChromiumInstall) -> CleanupItem {
+        let profiles = chromium_profiles(&install.user_data);
+        let mut cache_dirs = chromium_cache_dirs(&profiles);
+        if let Some(cache_root) = &install.cache_root {
+            let mirrored: Vec<PathBuf> = profiles
+                .iter()
+                .map(|profile| match profile.strip_prefix(&install.user_data) {
+                    Ok(relative) => cache_root.join(relative),
+                    Err(_) => profile.clone(),
+                })
+                .collect();
+            cache_dirs.extend(chromium_cache_dirs(&mirrored));
+        }
+
+        CleanupItem {
+            id: format!("{}_cache", self.key),
+            name: format!("{} 缓存", self.name),
+            description: format!(
+                "{} 浏览器缓存文件 ({} 个配置文件, {})",
+                self.name,
+                profiles.len(),
+                install.user_data.display()
+            ),
+            cleanup_type: CleanupType::Directories(cache_dirs),
+            category: Category::Browsers,
+            risk: RiskLevel::Safe,
+            priority: 20,
+            enabled: false,
+            app_check: Some(self.app_check(install)),
+            ..Default::default()
+        }
+    }
+
+    /// Opt-in item trimming old history and cookies of every profile. Disabled by default.
+    pub fn history_item(&self, install: &ChromiumInstall) -> CleanupItem {
+        let databases = chromium_profiles(&install.user_data)
+            .iter()
+            .flat_map(|profile| {
+                [
+                    HistoryDb::new(profile.join("History"), HistoryDbKind::ChromiumHistory),
+                    HistoryDb::new(profile.join("Cookies"), HistoryDbKind::ChromiumCookies),
+                    HistoryDb::new(profile.join("Network").join("Cookies"), HistoryDbKind::ChromiumCookies),
+                ]
+            })
+            .collect();
+
+        CleanupItem {
+            id: format!("{}_history", self.key),
+            name: format!("{} 浏览历史和 Cookie", self.name),
+            description: history_description(self.name),
+            cleanup_type: CleanupType::BrowserHistory(databases),
+            category: Category::Browsers,
+            risk: RiskLevel::Aggressive,
+            priority: 90,
+            enabled: false,
+            app_check: Some(self.app_check(install)),
+            ..Default::default()
+        }
+    }
+
+    fn app_check(&self, install: &ChromiumInstall) -> AppCheck {
+        // Windows keeps a `lockfile`, Linux and macOS a `SingletonLock` symlink while running
+        let lock_files = ["lockfile", "SingletonLock"]
+            .iter()
+            .map(|name| install.user_data.join(name))
+            .collect();
+        AppCheck {
+            app_name: self.name.to_string(),
+            process_names: self.process_names.iter().map(|name| name.to_string()).collect(),
+            lock_files,
+            pid_file_dirs: Vec::new(),
+        }
+    }
+}
+
+/// Description of a browser history item, spelling out that data is deleted for good
+fn history_description(browser: &str) -> String {
+    format!(
+        "⚠ 永久删除 {} 中超过 {} 天 (可通过 max_age_days 修改) 的浏览历史和 Cookie, 书签会保留",
+        browser, DEFAULT_HISTORY_MAX_AGE_DAYS
+    )
+}
+
+/// Join a `/`-separated relative path onto `base` using native separators
+pub(crate) fn join_relative(base: &Path, relative: &str) -> PathBuf {
+    relative.split('/').fold(base.to_path_buf(), |path, part| path.join(part))
+}
+
+/// Whether a path is one of the profile files that must never be deleted
+fn is_protected(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| CHROMIUM_PROTECTED.iter().any(|protected| name == *protected))
+}
+
+/// A `[ProfileN]` entry of Firefox's `profiles.ini`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirefoxProfile {
+    pub name: String,
+    /// Profile directory, relative to the Firefox root when `is_relative` is set
+    pub path: String,
+    pub is_relative: bool,
+}
+
+/// Parse the `[ProfileN]` sections of a `profiles.ini`.
+///
+/// `[General]` and `[InstallXXXX]` sections only point at profiles that are already listed
+/// and are ignored.
+pub fn parse_profiles_ini(contents: &str) -> Vec<FirefoxProfile> {
+    let mut profiles = Vec::new();
+    let mut current: Option<FirefoxProfile> = None;
+
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('[') && line.ends_with(']') {
+            profiles.extend(current.take().filter(|profile| !profile.path.is_empty()));
+            if line[1..line.len() - 1].starts_with("Profile") {
+                current = Some(FirefoxProfile {
+                    name: String::new(),
+                    path: String::new(),
+                    // Firefox treats a missing IsRelative as relative
+                    is_relative: true,
+                });
+            }
+            continue;
+        }
+
+        let (Some(profile), Some((key, value))) = (current.as_mut(), line.split_once('=')) else {
+            continue;
+        };
+        match key.trim() {
+            "Name" => profile.name = value.trim().to_string(),
+            "Path" => profile.path = value.trim().to_string(),
+            "IsRelative" => profile.is_relative = value.trim() != "0",
+            _ => {}
+        }
+    }
+    profiles.extend(current.filter(|profile| !profile.path.is_empty()));
+    profiles
+}
+
+/// Resolved Firefox locations: profiles.ini lives under `root`, disk caches under `cache_root`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirefoxInstall {
+    pub root: PathBuf,
+    pub cache_root: PathBuf,
+}
+
+/// Cache directories inside a Firefox profile's local (cache) directory
+pub const FIREFOX_CACHE_DIRS: &[&str] = &["cache2", "startupCache"];
+
+/// Firefox locations for the given OS and user directories
+pub fn firefox_install(os: Os, base: &BaseDirs) -> FirefoxInstall {
+    match os {
+        Os::Windows => FirefoxInstall {
+            root: join_relative(&base.config, "Mozilla/Firefox"),
+            cache_root: join_relative(&base.data_local, "Mozilla/Firefox"),
+        },
+        Os::Linux => FirefoxInstall {
+            root: join_relative(&base.home, ".mozilla/firefox"),
+            cache_root: join_relative(&base.cache, "mozilla/firefox"),
+        },
+        Os::MacOs => FirefoxInstall {
+            root: join_relative(&base.data, "Firefox"),
+            cache_root: join_relative(&base.cache, "Firefox"),
+        },
+    }
+}
+
+impl FirefoxInstall {
+    /// Profiles listed in `profiles.ini`; empty if Firefox is not installed
+    pub fn profiles(&self) -> Vec<FirefoxProfile> {
+        fs::read_to_string(self.root.join("profiles.ini"))
+            .map(|contents| parse_profiles_ini(&contents))
+            .unwrap_or_default()
+    }
+
+    /// Directory holding a profile's disk caches.
+    ///
+    /// Relative profiles keep caches in a mirror of their path under `cache_root`; profiles
+    /// stored elsewhere keep them in the profile directory itself.
+    pub fn local_dir(&self, profile: &FirefoxProfile) -> PathBuf {
+        if profile.is_relative {
+            join_relative(&self.cache_root, &profile.path)
+        } else {
+            PathBuf::from(&profile.path)
+        }
+    }
+
+    /// Existing `cache2`/`startupCache` directories of every profile
+    pub fn cache_dirs(&self, profiles: &[FirefoxProfile]) -> Vec<PathBuf> {
+        profiles
+            .iter()
+            .flat_map(|profile| {
+                let local = self.local_dir(profile);
+                FIREFOX_CACHE_DIRS.iter().map(move |name| local.join(name))
+            })
+            .filter(|path| path.is_dir())
+            .collect()
+    }
+
+    /// Directory holding a profile's databases and settings
+    pub fn profile_dir(&self, profile: &FirefoxProfile) -> PathBuf {
+        if profile.is_relative {
+            join_relative(&self.root, &profile.path)
+        } else {
+            PathBuf::from(&profile.path)
+        }
+    }
+
+    /// Cache item covering every Firefox profile. Disabled by default.
+    pub fn cache_item(&self) -> CleanupItem {
+        let profiles = self.profiles();
+
+        CleanupItem {
+            id: "firefox_cache".to_string(),
+            name: "Firefox 缓存".to_string(),
+            description: format!("Firefox 浏览器缓存文件 ({} 个配置文件)", profiles.len()),
+            cleanup_type: CleanupType::Directories(self.cache_dirs(&profiles)),
+            category: Category::Browsers,
+            risk: RiskLevel::Safe,
+            priority: 20,
+            enabled: false,
+            app_check: Some(self.app_check(&profiles)),
+            ..Default::default()
+        }
+    }
+
+    /// Opt-in item trimming old history and cookies of every profile. Disabled by default.
+    pub fn history_item(&self) -> CleanupItem {
+        let profiles = self.profiles();
+        let databases = profiles
+            .iter()
+            .flat_map(|profile| {
+                let dir = self.profile_dir(profile);
+                [
+                    HistoryDb::new(dir.join("places.sqlite"), HistoryDbKind::FirefoxPlaces),
+                    HistoryDb::new(dir.join("cookies.sqlite"), HistoryDbKind::FirefoxCookies),
+                ]
+            })
+            .collect();
+
+        CleanupItem {
+            id: "firefox_history".to_string(),
+            name: "Firefox 浏览历史和 Cookie".to_string(),
+            description: history_description("Firefox"),
+            cleanup_type: CleanupType::BrowserHistory(databases),
+            category: Category::Browsers,
+            risk: RiskLevel::Aggressive,
+            priority: 90,
+            enabled: false,
+            app_check: Some(self.app_check(&profiles)),
+            ..Default::default()
+        }
+    }
+
+    fn app_check(&self, profiles: &[FirefoxProfile]) -> AppCheck {
+        // Linux keeps a `lock` symlink in the profile only while Firefox runs
+        let lock_files = profiles.iter().map(|profile| self.profile_dir(profile).join("lock")).collect();
+        AppCheck {
+            app_name: "Firefox".to_string(),
+            process_names: vec!["firefox".to_string(), "firefox-bin".to_string()],
+            lock_files,
+            pid_file_dirs: Vec::new(),
+        }
+    }
+}
+
+/// Safari's sandbox container caches, relative to the home directory
+const SAFARI_CONTAINER_CACHE: &str = "Library/Containers/com.apple.Safari/Data/Library/Caches";
+
+/// Caches of Safari versions that predate the sandbox container
+const SAFARI_LEGACY_CACHE: &str = "Library/Caches/com.apple.Safari";
+
+/// Bookmarks, history and the reading list; never cleaned
+const SAFARI_DATA: &str = "Library/Safari";
+
+/// Shown when macOS privacy protection blocks a directory
+pub const FULL_DISK_ACCESS_HINT: &str = "请在 系统设置 → 隐私与安全性 → 完全磁盘访问权限 中授权终端";
+
+/// Where Safari keeps its caches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SafariLayout {
+    /// Sandboxed Safari under `~/Library/Containers`
+    Container,
+    /// Older Safari writing straight to `~/Library/Caches`
+    Legacy,
+}
+
+/// Safari caches of one user. macOS protects the container with Full Disk Access,
+/// so unreadable directories are reported instead of being counted as empty.
+#[derive(Debug, Clone)]
+pub struct SafariCache {
+    home: PathBuf,
+}
+
+impl SafariCache {
+    pub fn new(home: &Path) -> Self {
+        Self { home: home.to_path_buf() }
+    }
+
+    /// Layout of the installed Safari, `None` if it has never run
+    pub fn layout(&self) -> Option<SafariLayout> {
+        if self.home.join("Library/Containers/com.apple.Safari").is_dir() {
+            Some(SafariLayout::Container)
+        } else if join_relative(&self.home, SAFARI_LEGACY_CACHE).is_dir() {
+            Some(SafariLayout::Legacy)
+        } else {
+            None
+        }
+    }
+
+    /// Existing cache directories; anything inside `~/Library/Safari` is left out
+    pub fn cache_dirs(&self) -> Vec<PathBuf> {
+        let data = join_relative(&self.home, SAFARI_DATA);
+        [SAFARI_CONTAINER_CACHE, SAFARI_LEGACY_CACHE]
+            .iter()
+            .map(|relative| join_relative(&self.home, relative))
+            .filter(|path| path.is_dir() && !path.starts_with(&data))
+            .collect()
+    }
+
+    /// Cache item for Safari. Disabled by default.
+    pub fn cache_item(self) -> CleanupItem {
+        let layout = match self.layout() {
+            Some(SafariLayout::Container) => "沙盒容器",
+            Some(SafariLayout::Legacy) => "旧版路径",
+            None => "未找到",
+        };
+        CleanupItem {
+            id: "safari_cache".to_string(),
+            name: "Safari 缓存".to_string(),
+            description: format!("Safari 浏览器缓存文件 ({}), 不包括书签和历史记录", layout),
+            cleanup_type: CleanupType::Custom(Arc::new(self)),
+            category: Category::Browsers,
+            risk: RiskLevel::Safe,
+            priority: 20,
+            enabled: false,
+            app_check: Some(AppCheck {
+                app_name: "Safari".to_string(),
+                process_names: vec!["Safari".to_string()],
+                lock_files: Vec::new(),
+                pid_file_dirs: Vec::new(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Run `op` on every readable cache directory and record the unreadable ones
+    fn for_each_dir(&self, cx: &ItemContext<'_>, op: impl Fn(&Path) -> CleanupResult) -> CleanupResult {
+        let dirs = self.cache_dirs();
+        let mut result = CleanupResult::new();
+        let mut denied = 0;
+        for dir in &dirs {
+            if let Err(e) = cx.fs().read_dir(dir) {
+                if e.kind() == io::ErrorKind::PermissionDenied {
+                    denied += 1;
+                    result.errors.push(CleanErrorEntry {
+                        message: FULL_DISK_ACCESS_HINT.to_string(),
+                        ..CleanErrorEntry::new(dir, &e)
+                    });
+                }
+                continue;
+            }
+            result.merge(op(dir));
+        }
+        if denied > 0 && denied == dirs.len() {
+            return CleanupResult::skipped(FULL_DISK_ACCESS_HINT);
+        }
+        result
+    }
+}
+
+impl CleanTarget for SafariCache {
+    fn scan_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        self.for_each_dir(cx, |dir| cx.scan_dir(dir))
+    }
+
+    fn clean_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        self.for_each_dir(cx, |dir| cx.clean_dir(dir))
+    }
+
+    fn validate_in(&self, cx: &ItemContext<'_>) -> ItemStatus {
+        let dirs = self.cache_dirs();
+        if dirs.is_empty() {
+            return ItemStatus::PathMissing;
+        }
+        let denied = dirs.iter().all(|dir| {
+            matches!(cx.fs().read_dir(dir), Err(e) if e.kind() == io::ErrorKind::PermissionDenied)
+        });
+        if denied {
+            ItemStatus::NeedsFullDiskAccess
+        } else {
+            ItemStatus::Ready
+        }
+    }
+}
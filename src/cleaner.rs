@@ -1,40 +1,68 @@
-use crate::error::Result;
-use std::fs;
+use crate::cleanup_items::{
+    self, CleanErrorEntry, CleanupItem, CleanupResult, Report, DEFAULT_SCAN_PARALLELISM,
+};
+use crate::error::{self, CleanError, EntryKind, ErrorClass, Result};
+use crate::events::CleanObserver;
+use crate::options::CleanOptions;
+use crate::platform::is_cloud_placeholder;
+use crate::util::format_size;
+use crate::vfs::{EntryType, Fs, RealFs};
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
 use tracing::{debug, error, info, warn};
 
-/// Calculate the total size of a directory recursively
+/// Calculate the total size of a directory recursively.
+///
+/// Symlinks and junctions below `path` are not followed and add nothing, and neither do
+/// online-only cloud files, whose size is not on disk.
 pub fn get_dir_size(path: &Path) -> Result<u64> {
+    get_dir_size_in(&RealFs, path)
+}
+
+/// Like `get_dir_size`, reading through `fs`
+pub fn get_dir_size_in(fs: &dyn Fs, path: &Path) -> Result<u64> {
     let mut size = 0u64;
     
-    if path.is_dir() {
-        let entries = fs::read_dir(path)?;
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
-            
-            if path.is_dir() {
-                size += get_dir_size(&path).unwrap_or(0);
-            } else if path.is_file() {
-                if let Ok(metadata) = fs::metadata(&path) {
-                    size += metadata.len();
+    match fs.metadata(path).map(|metadata| metadata.entry_type) {
+        Ok(EntryType::Dir) => {
+            let names = fs.read_dir(path).map_err(|e| CleanError::read_dir(path, e))?;
+            for name in names {
+                let path = path.join(&name);
+                let Ok(metadata) = fs.symlink_metadata(&path) else {
+                    continue;
+                };
+                
+                if metadata.entry_type == EntryType::Link || is_cloud_placeholder(&name, &metadata) {
+                    continue;
+                } else if metadata.entry_type == EntryType::Dir {
+                    size += get_dir_size_in(fs, &path).unwrap_or(0);
+                } else {
+                    size += metadata.len;
                 }
             }
         }
-    } else if path.is_file() {
-        if let Ok(metadata) = fs::metadata(path) {
-            size = metadata.len();
-        }
+        Ok(_) => size = fs.metadata(path).map_or(0, |metadata| metadata.len),
+        Err(_) => {}
     }
     
     Ok(size)
 }
 
-/// Clean a directory by removing all files and subdirectories
+/// Clean a directory by removing all files and subdirectories.
+///
+/// Entries that cannot be deleted for lack of rights or because they are in use are recorded
+/// in the result's errors and the clean goes on. Any other removal failure stops it; once
+/// entries were deleted that is a `CleanError::PartialFailure` carrying the counts so far.
 pub fn clean_directory(path: &Path, dry_run: bool) -> Result<CleanResult> {
+    clean_directory_in(&RealFs, path, dry_run)
+}
+
+/// Like `clean_directory`, reading and deleting through `fs`
+pub fn clean_directory_in(fs: &dyn Fs, path: &Path, dry_run: bool) -> Result<CleanResult> {
     info!("Cleaning directory: {}", path.display());
     
-    if !path.exists() {
+    if fs.metadata(path).is_err() {
         warn!("Directory does not exist: {}", path.display());
         return Ok(CleanResult {
             files_deleted: 0,
@@ -44,7 +72,7 @@ pub fn clean_directory(path: &Path, dry_run: bool) -> Result<CleanResult> {
         });
     }
 
-    let before_size = get_dir_size(path)?;
+    let before_size = get_dir_size_in(fs, path)?;
     let mut result = CleanResult {
         files_deleted: 0,
         dirs_deleted: 0,
@@ -52,50 +80,72 @@ pub fn clean_directory(path: &Path, dry_run: bool) -> Result<CleanResult> {
         errors: Vec::new(),
     };
 
-    let entries = fs::read_dir(path)?;
-    for entry in entries {
-        let entry = entry?;
-        let entry_path = entry.path();
+    let names = fs.read_dir(path).map_err(|e| CleanError::read_dir(path, e))?;
+    for name in names {
+        let entry_path = path.join(&name);
+        let Ok(metadata) = fs.symlink_metadata(&entry_path) else {
+            continue;
+        };
+        if is_cloud_placeholder(&name, &metadata) {
+            debug!("Skipping online-only file: {}", entry_path.display());
+            continue;
+        }
         
-        if entry_path.is_file() {
+        // Links and junctions are removed themselves, leaving their target alone
+        if metadata.entry_type != EntryType::Dir {
             if dry_run {
                 debug!("[DRY RUN] Would delete file: {}", entry_path.display());
                 result.files_deleted += 1;
             } else {
-                match fs::remove_file(&entry_path) {
+                match fs.remove_file(&entry_path) {
                     Ok(()) => {
                         debug!("Deleted file: {}", entry_path.display());
                         result.files_deleted += 1;
                     }
                     Err(e) => {
-                        let err_msg = format!("Failed to delete file {}: {}", entry_path.display(), e);
-                        error!("{}", err_msg);
-                        result.errors.push(err_msg);
+                        let kind = match metadata.entry_type {
+                            EntryType::Link => EntryKind::Link,
+                            _ => EntryKind::File,
+                        };
+                        let error = CleanError::remove(&entry_path, kind, e);
+                        error!("{}", error);
+                        if error.classification() == ErrorClass::Fatal {
+                            return Err(stop_clean(fs, path, before_size, result, error));
+                        }
+                        result.errors.push(CleanErrorEntry::from_error(&entry_path, &error));
                     }
                 }
             }
-        } else if entry_path.is_dir() {
-            if dry_run {
-                debug!("[DRY RUN] Would delete directory: {}", entry_path.display());
-                result.dirs_deleted += 1;
-            } else {
-                match fs::remove_dir_all(&entry_path) {
-                    Ok(()) => {
-                        debug!("Deleted directory: {}", entry_path.display());
-                        result.dirs_deleted += 1;
-                    }
-                    Err(e) => {
-                        let err_msg = format!("Failed to delete directory {}: {}", entry_path.display(), e);
-                        error!("{}", err_msg);
-                        result.errors.push(err_msg);
+        } else if dry_run {
+            debug!("[DRY RUN] Would delete directory: {}", entry_path.display());
+            result.dirs_deleted += 1;
+        } else {
+            match fs.remove_dir_all(&entry_path) {
+                Ok(()) => {
+                    debug!("Deleted directory: {}", entry_path.display());
+                    result.dirs_deleted += 1;
+                }
+                Err(e) => {
+                    let error = CleanError::remove(&entry_path, EntryKind::Directory, e);
+                    error!("{}", error);
+                    if error.classification() == ErrorClass::Fatal {
+                        return Err(stop_clean(fs, path, before_size, result, error));
                     }
+                    result.errors.push(CleanErrorEntry::from_error(&entry_path, &error));
                 }
             }
         }
     }
 
-    let after_size = get_dir_size(path)?;
-    result.bytes_cleaned = before_size.saturating_sub(after_size);
+    if dry_run {
+        result.bytes_cleaned = before_size;
+    } else {
+        let after_size = match get_dir_size_in(fs, path) {
+            Ok(size) => size,
+            Err(e) => return Err(CleanError::partial(result, e)),
+        };
+        result.bytes_cleaned = before_size.saturating_sub(after_size);
+    }
     
     info!("Cleaned {} files, {} directories, {} bytes", 
           result.files_deleted, result.dirs_deleted, result.bytes_cleaned);
@@ -103,16 +153,30 @@ pub fn clean_directory(path: &Path, dry_run: bool) -> Result<CleanResult> {
     Ok(result)
 }
 
+/// `cause` stopping the clean of `path`, which measured `before_size` when it started. The
+/// bytes freed so far are counted when the directory can still be measured.
+fn stop_clean(fs: &dyn Fs, path: &Path, before_size: u64, mut result: CleanResult, cause: CleanError) -> CleanError {
+    if let Ok(after_size) = get_dir_size_in(fs, path) {
+        result.bytes_cleaned = before_size.saturating_sub(after_size);
+    }
+    CleanError::partial(result, cause)
+}
+
 /// Result of a cleaning operation
 #[derive(Debug, Clone)]
 pub struct CleanResult {
     pub files_deleted: u64,
     pub dirs_deleted: u64,
     pub bytes_cleaned: u64,
-    pub errors: Vec<String>,
+    pub errors: Vec<CleanErrorEntry>,
 }
 
 impl CleanResult {
+    /// `bytes_cleaned` in the largest fitting unit, such as "3.50 GB"
+    pub fn bytes_human(&self) -> String {
+        format_size(self.bytes_cleaned)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.files_deleted == 0 && self.dirs_deleted == 0
     }
@@ -125,13 +189,152 @@ impl CleanResult {
         let mut status = vec![
             format!("Files deleted: {}", self.files_deleted),
             format!("Directories deleted: {}", self.dirs_deleted),
-            format!("Space freed: {:.2} MB", self.bytes_cleaned as f64 / (1024.0 * 1024.0)),
+            format!("Space freed: {}", self.bytes_human()),
         ];
         
         if self.has_errors() {
-            status.push(format!("Errors encountered: {}", self.errors.len()));
+            let classes = error::summarize_classes(self.errors.iter().map(CleanErrorEntry::class));
+            status.push(format!("Errors encountered: {} ({})", self.errors.len(), classes));
         }
         
         status.join("\n")
     }
-}
\ No newline at end of file
+}
+
+/// Scans and cleans a set of items with shared options, the way the CLI and the TUI do.
+///
+/// ```
+/// use clean_rs::Cleaner;
+///
+/// let mut cleaner = Cleaner::with_default_items();
+/// assert!(cleaner.enable("temp_files"));
+/// // Unknown ids enable nothing
+/// assert!(!cleaner.enable("no_such_item"));
+/// ```
+///
+/// A dry run then reports what cleaning the enabled items would free:
+///
+/// ```no_run
+/// # let cleaner = clean_rs::Cleaner::with_default_items();
+/// let report = cleaner.clean_enabled(true);
+/// println!("{} files, {} bytes", report.total_files(), report.total_bytes());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cleaner {
+    items: Vec<CleanupItem>,
+    options: CleanOptions,
+    parallelism: usize,
+}
+
+impl Cleaner {
+    pub fn new(items: Vec<CleanupItem>) -> Self {
+        Self {
+            items,
+            options: CleanOptions::default(),
+            parallelism: DEFAULT_SCAN_PARALLELISM,
+        }
+    }
+
+    /// Every built-in item, with the safe ones enabled
+    pub fn with_default_items() -> Self {
+        Self::new(cleanup_items::get_all_cleanup_items())
+    }
+
+    pub fn items(&self) -> &[CleanupItem] {
+        &self.items
+    }
+
+    pub fn items_mut(&mut self) -> &mut [CleanupItem] {
+        &mut self.items
+    }
+
+    pub fn into_items(self) -> Vec<CleanupItem> {
+        self.items
+    }
+
+    pub fn options(&self) -> &CleanOptions {
+        &self.options
+    }
+
+    /// Replace the options, and with them the observers registered so far
+    pub fn set_options(&mut self, options: CleanOptions) {
+        self.options = options;
+    }
+
+    /// Tell `observer` about every scan and clean from now on
+    pub fn add_observer(&mut self, observer: Arc<dyn CleanObserver>) {
+        self.options.observers.push(observer);
+    }
+
+    /// Most items `scan_all` scans at once
+    pub fn set_parallelism(&mut self, parallelism: usize) {
+        self.parallelism = parallelism;
+    }
+
+    /// Select the item `id`. Returns false when there is no such item or it is unavailable
+    /// here, which leaves it unselected.
+    pub fn enable(&mut self, id: &str) -> bool {
+        match self.items.iter_mut().find(|item| item.id == id) {
+            Some(item) if item.status.is_ready() => {
+                item.enabled = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Deselect the item `id`; returns false when there is no such item
+    pub fn disable(&mut self, id: &str) -> bool {
+        self.items.iter_mut().find(|item| item.id == id).map(|item| item.enabled = false).is_some()
+    }
+
+    /// Scan the enabled items, several at once, in priority order
+    pub fn scan_all(&self) -> Report {
+        self.scan_all_with(|_, _| {})
+    }
+
+    /// Like `scan_all`, passing each item's index and result to `on_result` as it finishes
+    pub fn scan_all_with(&self, mut on_result: impl FnMut(usize, &CleanupResult)) -> Report {
+        let started = Instant::now();
+        let mut results = vec![None; self.items.len()];
+        cleanup_items::scan_enabled_parallel_with(&self.items, self.parallelism, &self.options, |index, result| {
+            on_result(index, &result);
+            results[index] = Some(result);
+        });
+        let mut report = Report::default();
+        for index in cleanup_items::priority_order(&self.items) {
+            if let Some(result) = results[index].take() {
+                report.push(self.items[index].id.clone(), Ok(result));
+            }
+        }
+        report.duration = started.elapsed();
+        report
+    }
+
+    /// Clean the enabled items one after another in priority order, or with `dry_run` scan
+    /// them to show what would be cleaned. Items refused outright, such as one emptying a
+    /// volume root, fail in the report.
+    pub fn clean_enabled(&self, dry_run: bool) -> Report {
+        self.clean_enabled_with(dry_run, |_, _| {})
+    }
+
+    /// Like `clean_enabled`, passing each item's index and outcome to `on_result` as it finishes
+    pub fn clean_enabled_with(
+        &self,
+        dry_run: bool,
+        mut on_result: impl FnMut(usize, &std::result::Result<CleanupResult, CleanError>),
+    ) -> Report {
+        let options = &self.options;
+        cleanup_items::run_enabled(&self.items, options, |index, item| {
+            info!("Cleaning: {}", item.name);
+            let outcome = if dry_run {
+                // A volume root is not even scanned, which could take very long
+                item.check_mount_roots(options).map(|()| item.scan_with(options))
+            } else {
+                item.outcome(item.clean_with(options), options)
+            };
+            on_result(index, &outcome);
+            outcome
+        })
+    }
+}
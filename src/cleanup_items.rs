@@ -1,15 +1,368 @@
+use crate::apps;
+use crate::browsers;
+use crate::devtools::{self, CondaPackages, MavenVersion, PackageStore};
+use crate::docker;
+use crate::duplicates;
+use crate::error::{self, CleanError, EntryKind, ErrorClass};
+use crate::events::Progress;
+use crate::history::{self, HistoryDb};
+use crate::large_files;
+use crate::linux;
+#[cfg(target_os = "macos")]
+use crate::macos;
+use crate::options::{CleanOptions, ItemOptions};
+use crate::patterns::{self, FilePattern, TempPattern};
+use crate::platform::{self, FileHolder, TrashBackend, VolumeKind};
+use crate::state;
+use crate::symlinks;
+#[cfg(any(windows, all(unix, not(target_os = "macos"))))]
+use crate::system;
+use crate::util::format_size;
+use crate::vfs::{self, EntryMetadata, EntryType, Fs};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fmt;
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 use tracing::{debug, info};
 
 /// Represents a cleanup item that can be scanned and cleaned
-#[derive(Debug, Clone)]
+///
+/// Only descriptive metadata is serialized; targets and runtime state are rebuilt
+/// by `get_all_cleanup_items` on every run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CleanupItem {
+    /// Stable identifier persisted in state files. Never rename an existing id.
     pub id: String,
     pub name: String,
     pub description: String,
+    #[serde(skip)]
     pub cleanup_type: CleanupType,
+    pub category: Category,
+    pub risk: RiskLevel,
+    /// Processing order; lower values are scanned and cleaned first
+    #[serde(default = "default_priority")]
+    pub priority: u8,
+    /// Files modified within this many days are left alone; 0 cleans everything
+    #[serde(default)]
+    pub min_age_days: u32,
+    /// Age files by their last access as well as their last modification
+    #[serde(skip)]
+    pub age_by_access: bool,
+    /// Delete only files and leave every directory in place
+    #[serde(skip)]
+    pub files_only: bool,
     pub enabled: bool,
+    /// Application that must not be running while this item is cleaned
+    #[serde(skip)]
+    pub app_check: Option<AppCheck>,
+    /// Windows service keeping the item's files open, stopped while cleaning when the
+    /// `stop_service` option is set and the process is elevated
+    #[serde(skip)]
+    pub service: Option<String>,
+    /// Explorer keeps the item's files open; with the `restart_explorer` option it is closed
+    /// while cleaning and started again afterwards
+    #[serde(skip)]
+    pub explorer_lock: bool,
+    /// Files another program has open are left alone without reporting an error, such as
+    /// shaders a running game has loaded
+    #[serde(skip)]
+    pub skip_in_use: bool,
+    /// `Files` items list one breakdown entry per pattern instead of one per file
+    #[serde(skip)]
+    pub group_files: bool,
+    /// Cleaning needs administrator (root) rights; without them the item can still be
+    /// scanned but cleaning is skipped with a hint to elevate
+    #[serde(skip)]
+    pub requires_admin: bool,
+    /// Lock files another program holds while it uses the item's files, such as apt's
+    /// `lock-frontend`, dnf's `metadata_lock.pid` or pacman's `db.lck`; cleaning is skipped while
+    /// any of them is held
+    #[serde(skip)]
+    pub busy_locks: Vec<PathBuf>,
+    /// `Files` items move their files to the trash (Recycle Bin) instead of deleting them,
+    /// so they can still be restored; space is only freed once the trash is emptied
+    #[serde(skip)]
+    pub use_trash: bool,
+    /// Whether the item's target can currently be cleaned
+    #[serde(skip)]
+    pub status: ItemStatus,
+    /// Per-item overrides of default behaviour
+    #[serde(default)]
+    pub options: ItemOptions,
+}
+
+/// Breakdown note of projects the user left out of a `RustTargets` or `NodeModules` item
+pub const EXCLUDED_NOTE: &str = "已排除";
+
+/// Breakdown note of duplicate groups the user has not chosen to clean
+pub const NOT_SELECTED_NOTE: &str = "未选择";
+
+/// Breakdown label counting the directories a `LargeFiles` report could not read
+pub const UNREADABLE_DIRS_LABEL: &str = "无法读取的目录";
+
+/// Breakdown note of files left in place because another program has them open
+pub const IN_USE_NOTE: &str = "正在使用, 已跳过";
+
+/// Breakdown note of a file skipped because `holders` have it open, naming them when known
+pub fn in_use_note(holders: &[FileHolder]) -> String {
+    if holders.is_empty() {
+        return IN_USE_NOTE.to_string();
+    }
+    let holders: Vec<String> = holders.iter().map(FileHolder::to_string).collect();
+    format!("被 {} 占用, 已跳过", holders.join(", "))
+}
+
+/// Skip reason of items cleaned only with administrator rights
+#[cfg(windows)]
+pub const ADMIN_REQUIRED_NOTE: &str = "需要管理员权限, 请以管理员身份运行";
+/// Skip reason of items cleaned only with administrator rights
+#[cfg(not(windows))]
+pub const ADMIN_REQUIRED_NOTE: &str = "需要 root 权限, 请使用 sudo 运行";
+
+/// Skip reason of items that wanted a restore point that could not be created
+pub const RESTORE_POINT_FAILED_NOTE: &str = "未能创建系统还原点";
+
+/// Priority of items that do not need to run early or late
+pub const DEFAULT_PRIORITY: u8 = 50;
+
+fn default_priority() -> u8 {
+    DEFAULT_PRIORITY
+}
+
+/// Availability of a cleanup item on this machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ItemStatus {
+    #[default]
+    Ready,
+    /// None of the item's target paths exist
+    PathMissing,
+    /// The target exists but cannot be read by the current user
+    PermissionDenied,
+    /// macOS privacy protection blocks the target until Full Disk Access is granted
+    NeedsFullDiskAccess,
+    /// A background service the item works through, such as the Docker daemon, is not running
+    ServiceUnavailable,
+    /// Cleaning needs administrator (root) rights the process does not have. The item can
+    /// still be selected and scanned; cleaning it is skipped.
+    NeedsElevation,
+    /// The item would empty a volume root or mount point. It stays unselected, and cleaning
+    /// it is refused unless `CleanOptions::allow_mount_roots` is set.
+    MountRoot,
+}
+
+impl ItemStatus {
+    /// Whether the item can be selected
+    pub fn is_ready(&self) -> bool {
+        matches!(self, ItemStatus::Ready | ItemStatus::NeedsElevation | ItemStatus::MountRoot)
+    }
+
+    /// Status of a target the current user cannot read: on macOS that is privacy protection
+    /// asking for Full Disk Access, elsewhere missing rights
+    pub fn denied() -> ItemStatus {
+        if cfg!(target_os = "macos") {
+            ItemStatus::NeedsFullDiskAccess
+        } else {
+            ItemStatus::PermissionDenied
+        }
+    }
+
+    /// Reason shown next to unavailable items
+    pub fn reason(&self) -> &'static str {
+        match self {
+            ItemStatus::Ready => "",
+            ItemStatus::PathMissing => "路径不存在",
+            ItemStatus::PermissionDenied => "无访问权限, 以管理员身份运行可启用",
+            ItemStatus::NeedsFullDiskAccess => "需要完全磁盘访问权限",
+            ItemStatus::ServiceUnavailable => "所需的后台服务未运行",
+            ItemStatus::NeedsElevation => ADMIN_REQUIRED_NOTE,
+            ItemStatus::MountRoot => "目标是磁盘根目录或挂载点",
+        }
+    }
+}
+
+impl Default for CleanupType {
+    fn default() -> Self {
+        CleanupType::Directories(Vec::new())
+    }
+}
+
+impl Default for CleanupItem {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            name: String::new(),
+            description: String::new(),
+            cleanup_type: CleanupType::default(),
+            category: Category::System,
+            risk: RiskLevel::Safe,
+            priority: DEFAULT_PRIORITY,
+            min_age_days: 0,
+            age_by_access: false,
+            files_only: false,
+            enabled: false,
+            app_check: None,
+            service: None,
+            explorer_lock: false,
+            skip_in_use: false,
+            group_files: false,
+            requires_admin: false,
+            busy_locks: Vec::new(),
+            use_trash: false,
+            status: ItemStatus::Ready,
+            options: ItemOptions::default(),
+        }
+    }
+}
+
+/// Detects whether the application owning an item's files is running
+#[derive(Debug, Clone, Default)]
+pub struct AppCheck {
+    /// Name shown in skip messages
+    pub app_name: String,
+    /// Process names matched case-insensitively, ignoring a trailing `.exe`
+    pub process_names: Vec<String>,
+    /// Files that only exist while the application is running
+    pub lock_files: Vec<PathBuf>,
+    /// Directories of `<name>-<pid>.<ext>` files the application leaves behind, such as
+    /// Gradle's `daemon-1234.out.log`; it is running while one of those pids is alive
+    pub pid_file_dirs: Vec<PathBuf>,
+}
+
+impl AppCheck {
+    pub fn is_running(&self) -> bool {
+        // symlink_metadata: Chrome's SingletonLock is a symlink to a non-existent target
+        if self.lock_files.iter().any(|lock| fs::symlink_metadata(lock).is_ok()) {
+            return true;
+        }
+        if self.pid_file_dirs.iter().flat_map(|dir| pids_in_file_names(dir)).any(platform::process_exists) {
+            return true;
+        }
+        if self.process_names.is_empty() {
+            return false;
+        }
+
+        let wanted: Vec<String> = self.process_names.iter().map(|name| name.to_lowercase()).collect();
+        platform::running_process_names().iter().any(|name| {
+            let name = name.to_lowercase();
+            let name = name.strip_suffix(".exe").unwrap_or(&name);
+            wanted.iter().any(|w| w == name)
+        })
+    }
+}
+
+/// Pids embedded in file names like `daemon-1234.out.log` directly inside `dir`
+fn pids_in_file_names(dir: &Path) -> Vec<u32> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let stem = name.split('.').next()?;
+            stem.rsplit_once('-')?.1.parse().ok()
+        })
+        .collect()
+}
+
+/// Group a cleanup item belongs to, used to organize the item list
+///
+/// Serialized as the same stable strings returned by `as_str`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    System,
+    Browsers,
+    DevTools,
+    Applications,
+    Logs,
+}
+
+impl Category {
+    /// All categories in display order
+    pub const ALL: [Category; 5] = [
+        Category::System,
+        Category::Browsers,
+        Category::DevTools,
+        Category::Applications,
+        Category::Logs,
+    ];
+
+    /// Stable identifier used in config files and JSON output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Category::System => "system",
+            Category::Browsers => "browsers",
+            Category::DevTools => "dev_tools",
+            Category::Applications => "applications",
+            Category::Logs => "logs",
+        }
+    }
+
+    /// Header shown above the category in the TUI
+    pub fn label(&self) -> &'static str {
+        match self {
+            Category::System => "系统",
+            Category::Browsers => "浏览器",
+            Category::DevTools => "开发工具",
+            Category::Applications => "应用程序",
+            Category::Logs => "日志",
+        }
+    }
+}
+
+impl std::str::FromStr for Category {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Category::ALL
+            .into_iter()
+            .find(|category| category.as_str() == s)
+            .ok_or_else(|| format!("unknown category: {}", s))
+    }
+}
+
+/// How likely cleaning an item is to have user-visible consequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    /// Regenerated transparently (temp files, thumbnail cache)
+    Safe,
+    /// Noticeable side effects such as slower first launch or lost MRU lists
+    Moderate,
+    /// May remove data the user cares about; disabled by default
+    Aggressive,
+}
+
+impl RiskLevel {
+    /// Short label shown next to items in the TUI
+    pub fn label(&self) -> &'static str {
+        match self {
+            RiskLevel::Safe => "安全",
+            RiskLevel::Moderate => "中等",
+            RiskLevel::Aggressive => "激进",
+        }
+    }
+}
+
+impl std::str::FromStr for RiskLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "safe" => Ok(RiskLevel::Safe),
+            "moderate" => Ok(RiskLevel::Moderate),
+            "aggressive" => Ok(RiskLevel::Aggressive),
+            other => Err(format!("unknown risk level: {}", other)),
+        }
+    }
 }
 
 /// Type of cleanup operation
@@ -21,10 +374,184 @@ pub enum CleanupType {
     Directories(Vec<PathBuf>),
     /// Clean temp files in a directory
     TempFiles(PathBuf),
+    /// Delete old rows from browser history and cookie databases, then `VACUUM` them
+    BrowserHistory(Vec<HistoryDb>),
+    /// `target/` directories of idle Rust projects found below these roots
+    RustTargets(Vec<PathBuf>),
+    /// `node_modules/` directories of idle JavaScript projects found below these roots
+    NodeModules(Vec<PathBuf>),
+    /// `__pycache__` directories and orphaned `.pyc` files found below these roots
+    PythonBytecode(Vec<PathBuf>),
+    /// A package manager's global store, reported per version directory
+    PackageStore(PackageStore),
+    /// Superseded snapshot builds (and optionally unused versions) in a Maven repository
+    MavenRepo(PathBuf),
+    /// Cached conda packages that no environment uses
+    CondaPkgs(CondaPackages),
+    /// Single files; a last component with one `*`, such as `*.dmp` or `iconcache_*.db`,
+    /// stands for every matching file in the directory
+    Files(Vec<PathBuf>),
+    /// IIS log root such as `C:\inetpub\logs\LogFiles`; old `*.log` files in its `W3SVC*`
+    /// site folders are deleted, or compressed with the `compress_until_days` option
+    IisLogs(PathBuf),
+    /// Directories exactly `depth` levels below each of `roots`, each deleted whole once
+    /// nothing in it was used within the item's minimum age
+    UnusedDirs { roots: Vec<PathBuf>, depth: usize },
+    /// Files named exactly one of the pattern's names, such as `.DS_Store`, anywhere below its roots
+    FilePattern(FilePattern),
+    /// Delegate scanning and cleaning to a custom implementation
+    Custom(Arc<dyn CleanTarget>),
+}
+
+/// Scan/clean behaviour for items that are not plain directory deletions
+pub trait CleanTarget: fmt::Debug + Send + Sync {
+    /// Report what would be cleaned without deleting anything. Targets that need their item
+    /// override `scan_in` instead and leave this empty.
+    fn scan(&self) -> CleanupResult {
+        CleanupResult::new()
+    }
+    /// Perform the cleanup and report what was actually removed. Targets that need their item
+    /// override `clean_in` instead and leave this empty.
+    fn clean(&self) -> CleanupResult {
+        CleanupResult::new()
+    }
+    /// Quick look at whether there is anything to clean. Override when `scan` is slow.
+    fn estimate(&self) -> CleanupResult {
+        self.scan()
+    }
+    /// Whether the breakdown lists parts, labelled with their path, that can be excluded one by
+    /// one through `excluded_paths`
+    fn has_parts(&self) -> bool {
+        false
+    }
+    /// Whether the breakdown entry labelled `label` is one of those parts, rather than detail
+    /// listed below one
+    fn is_part(&self, _label: &Path) -> bool {
+        self.has_parts()
+    }
+    /// `scan` with the parts in `excluded` still listed but not counted. Override with `has_parts`.
+    fn scan_excluding(&self, _excluded: &[PathBuf]) -> CleanupResult {
+        self.scan()
+    }
+    /// `clean` leaving the parts in `excluded` alone. Override with `has_parts`.
+    fn clean_excluding(&self, _excluded: &[PathBuf]) -> CleanupResult {
+        self.clean()
+    }
+    /// Check whether the target is usable on this machine
+    fn validate(&self) -> ItemStatus {
+        ItemStatus::Ready
+    }
+    /// `scan_excluding` for the item in `cx`. Override instead when the target reads the
+    /// item's options or measures files as the item's own scan does.
+    fn scan_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        self.scan_excluding(cx.excluded_paths())
+    }
+    /// `clean_excluding` for the item in `cx`. Override instead when the target reads the
+    /// item's options or deletes files, so deletions are reported and go through the run's `Fs`.
+    fn clean_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        self.clean_excluding(cx.excluded_paths())
+    }
+    /// `estimate` for the item in `cx`
+    fn estimate_in(&self, _cx: &ItemContext<'_>) -> CleanupResult {
+        self.estimate()
+    }
+    /// `validate` for the item in `cx`
+    fn validate_in(&self, _cx: &ItemContext<'_>) -> ItemStatus {
+        self.validate()
+    }
+    /// Directories the target cleans below, for the permission and remote volume checks
+    fn root_paths(&self) -> Vec<&Path> {
+        Vec::new()
+    }
+    /// Whether the breakdown lists groups, labelled with a path, that are only cleaned once
+    /// the user selects them with `toggle_selected`
+    fn has_groups(&self) -> bool {
+        false
+    }
+}
+
+/// The item a `CleanTarget` runs for: the user's options for it, and the item's own way of
+/// measuring and deleting files, which reports progress and goes through the run's `Fs`
+pub struct ItemContext<'a> {
+    item: &'a CleanupItem,
+    progress: &'a Progress<'a>,
+}
+
+impl<'a> ItemContext<'a> {
+    pub(crate) fn new(item: &'a CleanupItem, progress: &'a Progress<'a>) -> Self {
+        Self { item, progress }
+    }
+
+    pub fn options(&self) -> &'a ItemOptions {
+        &self.item.options
+    }
+
+    /// Minimum file age in days after applying the user's override
+    pub fn min_age_days(&self) -> u32 {
+        self.item.effective_min_age_days()
+    }
+
+    /// Whether a file is younger than the item's minimum age and must be kept
+    pub fn is_too_recent(&self, metadata: &impl EntryMetadata) -> bool {
+        self.item.is_too_recent(metadata)
+    }
+
+    /// Roots to search: the user's `project_roots`, else `defaults`
+    pub fn roots(&self, defaults: &'a [PathBuf]) -> &'a [PathBuf] {
+        self.item.project_roots(defaults)
+    }
+
+    /// Paths the user left out
+    pub fn excluded_paths(&self) -> &'a [PathBuf] {
+        self.item.excluded_paths()
+    }
+
+    /// Whether the user selected the group whose kept copy is `kept`
+    pub fn is_selected(&self, kept: &Path) -> bool {
+        self.item.is_selected(kept)
+    }
+
+    /// Count a file of `bytes` found while scanning
+    pub fn found(&self, bytes: u64) {
+        self.progress.file(bytes);
+    }
+
+    /// Measure a file, or a directory and everything below it, as deleting it would free
+    pub fn scan_path(&self, path: &Path) -> CleanupResult {
+        self.item.scan_entry(path, self.progress)
+    }
+
+    /// Delete a file, or a directory and everything below it
+    pub fn remove_path(&self, path: &Path, result: &mut CleanupResult) {
+        self.item.remove_path(path, result, self.progress);
+    }
+
+    /// Measure everything below the directory `dir`, leaving `dir` itself out
+    pub fn scan_dir(&self, dir: &Path) -> CleanupResult {
+        self.item.scan_directory(dir, self.progress)
+    }
+
+    /// Delete everything below the directory `dir`, keeping `dir` itself
+    pub fn clean_dir(&self, dir: &Path) -> CleanupResult {
+        self.item.clean_directory(dir, false, self.progress)
+    }
+
+    /// Delete a single file of `size` bytes
+    pub fn remove_file(&self, path: &Path, size: u64, result: &mut CleanupResult) {
+        CleanupItem::remove_file_counted(path, size, result, self.progress);
+    }
+
+    /// The file system the run reads and deletes through
+    pub fn fs(&self) -> &'a dyn Fs {
+        self.progress.file_system()
+    }
 }
 
+/// Default number of items scanned at the same time
+pub const DEFAULT_SCAN_PARALLELISM: usize = 4;
+
 /// Result of scanning/cleaning a cleanup item
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CleanupResult {
     pub files: u64,
     pub directories: u64,
@@ -35,6 +562,123 @@ pub struct CleanupResult {
     pub files_remaining: u64,
     /// Size remaining after cleanup attempt
     pub size_remaining: u64,
+    /// Entries that could not be deleted
+    pub errors: Vec<CleanErrorEntry>,
+    /// Why the item was not cleaned, if it was skipped
+    pub skip_reason: Option<String>,
+    /// Counts come from a shallow sample and `size_bytes` was not measured
+    pub is_estimate: bool,
+    /// Per-part sizes for items that cover several independent targets
+    pub breakdown: Vec<BreakdownEntry>,
+    /// The item only lists what it found; nothing was or will be deleted, so the size is not
+    /// space that can be freed
+    pub report_only: bool,
+    /// What moved files to the trash, for items that trash instead of deleting
+    pub trashed_with: Vec<TrashBackend>,
+    /// Online-only cloud files that were neither measured nor deleted, which is why the size
+    /// can be below what Explorer shows
+    pub cloud_placeholders: u64,
+    /// Space the files counted in `size_bytes` take on disk, which is what deleting them frees:
+    /// less for NTFS-compressed and sparse files, more for many small ones. 0 when the item
+    /// does not measure it.
+    pub disk_bytes: u64,
+    /// Files left alone because running processes hold them open, found with in-use detection
+    pub skipped_in_use: Vec<InUseEntry>,
+}
+
+/// A file skipped because running processes hold it open
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InUseEntry {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// The processes holding it, empty when they could not be identified
+    pub holders: Vec<FileHolder>,
+}
+
+/// One part of an item's result, listed when the item is expanded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BreakdownEntry {
+    pub label: String,
+    pub size_bytes: u64,
+    /// Extra information, such as why the part was left alone
+    pub note: String,
+}
+
+/// A single entry that failed to be deleted
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanErrorEntry {
+    pub path: PathBuf,
+    /// Stable code from `error::ERROR_CODES`, for scripts reading `--json` output
+    pub code: &'static str,
+    #[serde(serialize_with = "serialize_error_kind")]
+    pub kind: io::ErrorKind,
+    pub message: String,
+    /// Another program had the file open
+    pub in_use: bool,
+}
+
+/// Error kinds are written as their description, such as "permission denied"
+fn serialize_error_kind<S: serde::Serializer>(kind: &io::ErrorKind, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(kind)
+}
+
+/// Replace the permission errors below each of `roots` with one entry for the root, noting
+/// how many files it stands for. Used without administrator rights, where a protected system
+/// folder would otherwise fail once per file.
+pub fn collapse_permission_errors(errors: Vec<CleanErrorEntry>, roots: &[&Path]) -> Vec<CleanErrorEntry> {
+    let mut denied = vec![0u64; roots.len()];
+    let mut kept = Vec::new();
+    for error in errors {
+        let root = roots.iter().position(|root| error.path.starts_with(root));
+        match root {
+            Some(index) if error.kind == io::ErrorKind::PermissionDenied => denied[index] += 1,
+            _ => kept.push(error),
+        }
+    }
+    for (root, count) in roots.iter().zip(denied).filter(|(_, count)| *count > 0) {
+        kept.push(CleanErrorEntry {
+            path: root.to_path_buf(),
+            code: error::io_code(io::ErrorKind::PermissionDenied, false),
+            kind: io::ErrorKind::PermissionDenied,
+            message: format!("{} 个文件无法删除, {}", count, ADMIN_REQUIRED_NOTE),
+            in_use: false,
+        });
+    }
+    kept
+}
+
+impl CleanErrorEntry {
+    pub fn new(path: &Path, error: &io::Error) -> Self {
+        let in_use = platform::is_in_use(error);
+        Self {
+            path: path.to_path_buf(),
+            code: error::io_code(error.kind(), in_use),
+            kind: error.kind(),
+            message: error.to_string(),
+            in_use,
+        }
+    }
+
+    /// How the failure should be handled
+    pub fn class(&self) -> ErrorClass {
+        ErrorClass::of_io(self.kind, self.in_use)
+    }
+
+    /// Entry for `error`, at the path it records or else at `fallback`
+    pub fn from_error(fallback: &Path, error: &CleanError) -> Self {
+        let in_use = error.io_error().is_some_and(platform::is_in_use);
+        Self {
+            path: error.path().unwrap_or(fallback).to_path_buf(),
+            // I/O failures are classed like any other entry, the rest by their variant
+            code: match error.io_error() {
+                Some(io_error) => error::io_code(io_error.kind(), in_use),
+                None => error.code(),
+            },
+            kind: error.kind(),
+            message: error.to_string(),
+            in_use,
+        }
+    }
 }
 
 impl CleanupResult {
@@ -47,11 +691,83 @@ impl CleanupResult {
             has_data: false,
             files_remaining: 0,
             size_remaining: 0,
+            errors: Vec::new(),
+            skip_reason: None,
+            is_estimate: false,
+            breakdown: Vec::new(),
+            report_only: false,
+            trashed_with: Vec::new(),
+            cloud_placeholders: 0,
+            disk_bytes: 0,
+            skipped_in_use: Vec::new(),
+        }
+    }
+
+    /// Result for an item that was skipped without touching anything
+    pub fn skipped(reason: impl Into<String>) -> Self {
+        Self {
+            skip_reason: Some(reason.into()),
+            ..Self::new()
+        }
+    }
+
+    /// Accumulate another result into this one
+    pub fn merge(&mut self, other: CleanupResult) {
+        self.files += other.files;
+        self.directories += other.directories;
+        self.size_bytes += other.size_bytes;
+        self.entries += other.entries;
+        self.has_data = self.has_data || other.has_data;
+        self.files_remaining += other.files_remaining;
+        self.size_remaining += other.size_remaining;
+        self.errors.extend(other.errors);
+        if self.skip_reason.is_none() {
+            self.skip_reason = other.skip_reason;
+        }
+        self.is_estimate = self.is_estimate || other.is_estimate;
+        self.breakdown.extend(other.breakdown);
+        self.report_only = self.report_only || other.report_only;
+        self.cloud_placeholders += other.cloud_placeholders;
+        self.disk_bytes += other.disk_bytes;
+        self.skipped_in_use.extend(other.skipped_in_use);
+        for backend in other.trashed_with {
+            if !self.trashed_with.contains(&backend) {
+                self.trashed_with.push(backend);
+            }
         }
     }
 
-    pub fn size_mb(&self) -> f64 {
-        self.size_bytes as f64 / (1024.0 * 1024.0)
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Record `path` as left alone because `holders` have it open
+    fn skip_in_use(&mut self, path: &Path, size_bytes: u64, holders: Vec<FileHolder>) {
+        self.breakdown.push(BreakdownEntry {
+            label: path.display().to_string(),
+            size_bytes,
+            note: in_use_note(&holders),
+        });
+        self.skipped_in_use.push(InUseEntry {
+            path: path.to_path_buf(),
+            size_bytes,
+            holders,
+        });
+    }
+
+    /// `size_bytes` in the largest fitting unit, such as "3.50 GB"
+    pub fn size_human(&self) -> String {
+        format_size(self.size_bytes)
+    }
+
+    /// Bytes deleting the files frees: the measured on-disk size, or `size_bytes` when the
+    /// item does not measure it
+    pub fn physical_bytes(&self) -> u64 {
+        if self.disk_bytes > 0 {
+            self.disk_bytes
+        } else {
+            self.size_bytes
+        }
     }
 
     pub fn total_items(&self) -> u64 {
@@ -66,302 +782,2047 @@ impl Default for CleanupResult {
 }
 
 impl CleanupItem {
-    /// Scan the cleanup item without deleting anything
-    pub fn scan(&self) -> CleanupResult {
+    /// Priority after applying the user's override
+    pub fn effective_priority(&self) -> u8 {
+        self.options.priority.unwrap_or(self.priority)
+    }
+
+    /// Minimum file age in days after applying the user's override
+    pub fn effective_min_age_days(&self) -> u32 {
+        self.options.min_age_days.unwrap_or(self.min_age_days)
+    }
+
+    /// Whether a file is younger than the item's minimum age and must be kept.
+    ///
+    /// Files dated in the future are kept as well.
+    fn is_too_recent(&self, metadata: &impl EntryMetadata) -> bool {
+        let days = self.effective_min_age_days();
+        if days == 0 {
+            return false;
+        }
+        let min_age = Duration::from_secs(u64::from(days) * 24 * 60 * 60);
+        let last_used = metadata.modified_time().map(|modified| match metadata.accessed_time() {
+            Ok(accessed) if self.age_by_access => modified.max(accessed),
+            _ => modified,
+        });
+        match last_used.map(|time| time.elapsed()) {
+            Ok(Ok(age)) => age < min_age,
+            Ok(Err(_)) => true,
+            Err(_) => false,
+        }
+    }
+
+    /// Whether `entry` is an online-only cloud file to leave alone: not measured, since that
+    /// could download it, and not deleted unless the `delete_cloud_placeholders` option is set
+    fn skips_cloud_placeholder(&self, file_name: &OsStr, metadata: &impl EntryMetadata) -> bool {
+        !self.options.delete_cloud_placeholders.unwrap_or(false) && platform::is_cloud_placeholder(file_name, metadata)
+    }
+
+    /// Whether a System Restore point is created before the item is cleaned: when its
+    /// `restore_point` option asks for one, or for aggressive items with `options.restore_point`
+    pub fn wants_restore_point(&self, options: &CleanOptions) -> bool {
+        self.options.restore_point.unwrap_or(options.restore_point && self.risk == RiskLevel::Aggressive)
+    }
+
+    /// Roots searched by `RustTargets`, `NodeModules`, `PythonBytecode` and `FilePattern`
+    /// items and by custom targets, after applying the user's override
+    fn project_roots<'a>(&'a self, defaults: &'a [PathBuf]) -> &'a [PathBuf] {
+        self.options.project_roots.as_deref().unwrap_or(defaults)
+    }
+
+    /// Projects of a `RustTargets` or `NodeModules` item as `(root, build dir, idle days)`
+    fn project_builds(&self) -> Vec<(PathBuf, PathBuf, u32)> {
         match &self.cleanup_type {
-            CleanupType::Directory(path) => self.scan_directory(path),
-            CleanupType::Directories(paths) => {
-                let mut result = CleanupResult::new();
-                for path in paths {
-                    let item_result = self.scan_directory(path);
-                    result.files += item_result.files;
-                    result.directories += item_result.directories;
-                    result.size_bytes += item_result.size_bytes;
-                    result.has_data = result.has_data || item_result.has_data;
-                }
-                result
+            CleanupType::RustTargets(roots) => devtools::find_rust_projects(self.project_roots(roots))
+                .into_iter()
+                .map(|project| (project.root.clone(), project.target_dir(), project.idle_days()))
+                .collect(),
+            CleanupType::NodeModules(roots) => devtools::find_node_projects(self.project_roots(roots))
+                .into_iter()
+                .map(|project| (project.root.clone(), project.node_modules(), project.idle_days()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Files matched by a `FilePattern` item, below the roots and outside the exclusions the
+    /// user configured
+    fn pattern_files(&self, fs: &dyn Fs, pattern: &FilePattern) -> Vec<PathBuf> {
+        let mut excluded = pattern.excluded.clone();
+        excluded.extend(self.options.excluded_paths.iter().flatten().cloned());
+        let same_filesystem = self.options.same_filesystem.unwrap_or(pattern.same_filesystem);
+        pattern.find(fs, self.project_roots(&pattern.roots), &excluded, same_filesystem)
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        self.options.excluded_paths.as_ref().is_some_and(|excluded| excluded.iter().any(|p| p == path))
+    }
+
+    fn excluded_paths(&self) -> &[PathBuf] {
+        self.options.excluded_paths.as_deref().unwrap_or_default()
+    }
+
+    /// Whether the item's breakdown lists projects, or parts of a custom target such as the
+    /// drives of the Recycle Bin, that can be excluded one by one
+    pub fn has_projects(&self) -> bool {
+        match &self.cleanup_type {
+            CleanupType::RustTargets(_) | CleanupType::NodeModules(_) => true,
+            CleanupType::Custom(target) => target.has_parts(),
+            _ => false,
+        }
+    }
+
+    /// Whether the item's breakdown lists duplicate groups that can be selected one by one
+    pub fn has_duplicate_groups(&self) -> bool {
+        matches!(&self.cleanup_type, CleanupType::Custom(target) if target.has_groups())
+    }
+
+    /// Whether the breakdown entry labelled `label` can be excluded with `toggle_excluded`
+    pub fn can_exclude(&self, label: &Path) -> bool {
+        match &self.cleanup_type {
+            CleanupType::Custom(target) => target.is_part(label),
+            _ => self.has_projects(),
+        }
+    }
+
+    /// Whether the duplicate group whose kept copy is `kept` is cleaned
+    fn is_selected(&self, kept: &Path) -> bool {
+        self.options.selected_paths.as_ref().is_some_and(|selected| selected.iter().any(|p| p == kept))
+    }
+
+    /// Select the duplicate group whose kept copy is `kept` for cleaning, or deselect it if it
+    /// was selected.
+    ///
+    /// Returns whether the group is now selected.
+    pub fn toggle_selected(&mut self, kept: &Path) -> bool {
+        let selected = self.options.selected_paths.get_or_insert_with(Vec::new);
+        if let Some(index) = selected.iter().position(|path| path == kept) {
+            selected.remove(index);
+            if selected.is_empty() {
+                self.options.selected_paths = None;
             }
-            CleanupType::TempFiles(path) => self.scan_temp_files(path),
+            false
+        } else {
+            selected.push(kept.to_path_buf());
+            true
         }
     }
 
-    /// Clean the cleanup item (delete files)
-    pub fn clean(&self) -> CleanupResult {
-        debug!("Starting cleanup for: {}", self.name);
-        let mut result = match &self.cleanup_type {
-            CleanupType::Directory(path) => self.clean_directory(path, false),
-            CleanupType::Directories(paths) => {
-                let mut result = CleanupResult::new();
-                for path in paths {
-                    let item_result = self.clean_directory(path, false);
-                    result.files += item_result.files;
-                    result.directories += item_result.directories;
-                    result.size_bytes += item_result.size_bytes;
-                    result.has_data = result.has_data || item_result.has_data;
-                }
-                result
+    /// Exclude the project at `root`, or include it again if it was excluded.
+    ///
+    /// Returns whether the project is now excluded.
+    pub fn toggle_excluded(&mut self, root: &Path) -> bool {
+        let excluded = self.options.excluded_paths.get_or_insert_with(Vec::new);
+        if let Some(index) = excluded.iter().position(|path| path == root) {
+            excluded.remove(index);
+            if excluded.is_empty() {
+                self.options.excluded_paths = None;
             }
-            CleanupType::TempFiles(path) => self.clean_temp_files(path, false),
-        };
-        
-        // Verify cleanup by scanning again
-        debug!("Verifying cleanup for: {}", self.name);
-        let verify_result = self.scan();
-        
-        if verify_result.has_data {
-            info!("Warning: {} still has {} files after cleanup", 
-                  self.name, verify_result.files);
-            // Update result to show what remains
-            result = verify_result;
+            false
+        } else {
+            excluded.push(root.to_path_buf());
+            true
         }
-        
-        info!("Cleanup complete for {}: {} files, {:.2} MB", 
-              self.name, result.files, result.size_mb());
-        result
     }
 
-    fn scan_directory(&self, path: &Path) -> CleanupResult {
+    /// Build directories of projects that were not touched within the item's minimum age and
+    /// were not excluded by the user
+    fn idle_project_builds(&self) -> impl Iterator<Item = (PathBuf, PathBuf, u32)> + '_ {
+        let min_idle_days = self.effective_min_age_days();
+        self.project_builds()
+            .into_iter()
+            .filter(move |(root, _, idle_days)| *idle_days >= min_idle_days && !self.is_excluded(root))
+    }
+
+    /// Measure every idle project's build directory, one breakdown entry per project.
+    ///
+    /// Excluded projects are still measured so they can be included again, but do not count
+    /// towards the item's size.
+    fn scan_projects(&self, progress: &Progress) -> CleanupResult {
+        let min_idle_days = self.effective_min_age_days();
         let mut result = CleanupResult::new();
+        for (root, build, idle_days) in self.project_builds() {
+            let label = root.display().to_string();
+            let excluded = if self.is_excluded(&root) {
+                format!(", {}", EXCLUDED_NOTE)
+            } else {
+                String::new()
+            };
+            if idle_days < min_idle_days {
+                result.breakdown.push(BreakdownEntry {
+                    label,
+                    size_bytes: 0,
+                    note: format!("{} 天前修改, 跳过{}", idle_days, excluded),
+                });
+                continue;
+            }
+            let mut part = self.scan_directory(&build, progress);
+            part.directories += 1;
+            if !excluded.is_empty() {
+                result.breakdown.push(BreakdownEntry {
+                    label,
+                    size_bytes: part.size_bytes,
+                    note: format!("{} 天前修改{}", idle_days, excluded),
+                });
+                continue;
+            }
+            part.breakdown.push(BreakdownEntry {
+                label,
+                size_bytes: part.size_bytes,
+                note: format!("{} 天前修改", idle_days),
+            });
+            result.merge(part);
+        }
+        result
+    }
 
-        if !path.exists() {
-            return result;
+    /// Delete the whole build directory of every idle project; sources are never touched
+    fn clean_projects(&self, progress: &Progress) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for (root, build, idle_days) in self.idle_project_builds() {
+            let freed_before = result.size_bytes;
+            self.remove_tree(&build, &mut result, progress);
+            result.breakdown.push(BreakdownEntry {
+                label: root.display().to_string(),
+                size_bytes: result.size_bytes - freed_before,
+                note: format!("{} 天前修改", idle_days),
+            });
         }
+        result
+    }
 
-        if let Ok(entries) = fs::read_dir(path) {
-            for entry in entries.flatten() {
-                let entry_path = entry.path();
-                
-                if entry_path.is_file() {
-                    if let Ok(metadata) = fs::metadata(&entry_path) {
-                        result.files += 1;
-                        result.size_bytes += metadata.len();
-                        result.has_data = true;
-                    }
-                } else if entry_path.is_dir() {
-                    let subdir_result = self.scan_directory(&entry_path);
-                    result.files += subdir_result.files;
-                    result.directories += 1 + subdir_result.directories;
-                    result.size_bytes += subdir_result.size_bytes;
-                    result.has_data = result.has_data || subdir_result.has_data;
+    fn scan_package_store(&self, store: &PackageStore, progress: &Progress) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        let fs = progress.file_system();
+        let Ok(names) = fs.read_dir(&store.dir) else {
+            return result;
+        };
+        let mut loose = CleanupResult::new();
+        for name in names {
+            let path = store.dir.join(&name);
+            let Ok(metadata) = fs.symlink_metadata(&path) else {
+                continue;
+            };
+            if !is_real_dir(&metadata) {
+                if !self.is_too_recent(&metadata) {
+                    loose.files += 1;
+                    loose.size_bytes += metadata.len;
+                    loose.has_data = true;
+                    progress.file(metadata.len);
                 }
+                continue;
             }
+            let mut version = self.scan_directory(&path, progress);
+            version.directories += 1;
+            version.breakdown.push(BreakdownEntry {
+                label: name.to_string_lossy().into_owned(),
+                size_bytes: version.size_bytes,
+                note: String::new(),
+            });
+            result.merge(version);
+        }
+        // Stores without version directories keep their archives at the top level
+        if loose.has_data {
+            loose.breakdown.push(BreakdownEntry {
+                label: store.dir.display().to_string(),
+                size_bytes: loose.size_bytes,
+                note: String::new(),
+            });
+            result.merge(loose);
         }
-
-        debug!("Scanned {}: {} files, {} dirs, {:.2} MB", 
-               self.name, result.files, result.directories, result.size_mb());
         result
     }
 
-    fn clean_directory(&self, path: &Path, dry_run: bool) -> CleanupResult {
-        let mut result = CleanupResult::new();
+    /// Let the package manager prune its store when it can, else delete the store's contents
+    fn clean_package_store(&self, store: &PackageStore, progress: &Progress) -> CleanupResult {
+        let Some(prune) = &store.prune else {
+            return self.clean_directory(&store.dir, false, progress);
+        };
+        info!("Running {} {}...", prune.program.display(), prune.args.join(" "));
+        let before = self.scan_directory(&store.dir, &Progress::silent());
+        if let Err(e) = prune.run() {
+            let mut result = CleanupResult::new();
+            result.errors.push(CleanErrorEntry::new(&store.dir, &e));
+            return result;
+        }
+        let after = self.scan_directory(&store.dir, &Progress::silent());
+        let freed = before.size_bytes.saturating_sub(after.size_bytes);
+        progress.file(freed);
+        CleanupResult {
+            files: before.files.saturating_sub(after.files),
+            directories: before.directories.saturating_sub(after.directories),
+            size_bytes: freed,
+            has_data: freed > 0,
+            ..CleanupResult::new()
+        }
+    }
+
+    /// What to delete from one Maven artifact version: the whole directory when it has not
+    /// been used for `unused_days`, else its stale snapshot files
+    fn maven_plan(&self, version: &MavenVersion) -> (bool, Vec<PathBuf>) {
+        let unused = self.options.unused_days.is_some_and(|days| version.unused_days() >= days);
+        if unused {
+            (true, Vec::new())
+        } else {
+            (false, version.stale_snapshot_files())
+        }
+    }
+
+    fn scan_maven_repo(&self, repository: &Path, progress: &Progress) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for version in devtools::find_maven_versions(repository) {
+            let (whole, stale) = self.maven_plan(&version);
+            let mut part = if whole {
+                let mut part = self.scan_directory(&version.dir, progress);
+                part.directories += 1;
+                part
+            } else {
+                let mut part = CleanupResult::new();
+                for metadata in stale.iter().filter_map(|path| progress.file_system().metadata(path).ok()) {
+                    part.files += 1;
+                    part.size_bytes += metadata.len;
+                    part.has_data = true;
+                    progress.file(metadata.len);
+                }
+                part
+            };
+            if !part.has_data {
+                continue;
+            }
+            part.breakdown.push(BreakdownEntry {
+                label: version.dir.strip_prefix(repository).unwrap_or(&version.dir).display().to_string(),
+                size_bytes: part.size_bytes,
+                note: if whole {
+                    format!("{} 天未使用", version.unused_days())
+                } else {
+                    format!("{} 个旧快照文件", stale.len())
+                },
+            });
+            result.merge(part);
+        }
+        result
+    }
+
+    fn clean_maven_repo(&self, repository: &Path, progress: &Progress) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for version in devtools::find_maven_versions(repository) {
+            let (whole, stale) = self.maven_plan(&version);
+            if whole {
+                self.remove_tree(&version.dir, &mut result, progress);
+            }
+            for path in stale {
+                let size = progress.file_system().metadata(&path).map_or(0, |metadata| metadata.len);
+                Self::remove_file_counted(&path, size, &mut result, progress);
+            }
+        }
+        result
+    }
+
+    /// What happens to an IIS log file: `None` keeps it, `Some(true)` compresses it and
+    /// `Some(false)` deletes it
+    fn iis_log_action(&self, metadata: &vfs::Metadata) -> Option<bool> {
+        let age = metadata.modified_time().map_or(0, devtools::days_since);
+        // Today's log is still being written, whatever the minimum age says
+        if age == 0 || self.is_too_recent(metadata) {
+            return None;
+        }
+        match self.options.compress_until_days {
+            Some(days) if age < days => (!metadata.compressed).then_some(true),
+            _ => Some(false),
+        }
+    }
+
+    /// Size of the old IIS logs, one breakdown entry per site folder
+    fn scan_iis_logs(&self, root: &Path, progress: &Progress) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        let fs = progress.file_system();
+        for (site, logs) in iis_site_logs(fs, root) {
+            let mut part = CleanupResult::new();
+            let mut compress = 0;
+            for path in logs {
+                let Ok(metadata) = fs.metadata(&path) else {
+                    continue;
+                };
+                match self.iis_log_action(&metadata) {
+                    Some(false) => {
+                        part.files += 1;
+                        part.size_bytes += metadata.len;
+                        progress.file(metadata.len);
+                    }
+                    Some(true) => compress += 1,
+                    None => {}
+                }
+            }
+            part.has_data = part.files > 0 || compress > 0;
+            if part.has_data {
+                result.breakdown.push(BreakdownEntry {
+                    label: site.display().to_string(),
+                    size_bytes: part.size_bytes,
+                    note: if compress > 0 { format!("{} 个文件将压缩", compress) } else { String::new() },
+                });
+            }
+            result.merge(part);
+        }
+        result
+    }
+
+    /// Delete or compress the old IIS logs; site folders are never removed
+    fn clean_iis_logs(&self, root: &Path, progress: &Progress) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        let fs = progress.file_system();
+        for path in iis_site_logs(fs, root).into_iter().flat_map(|(_, logs)| logs) {
+            let Ok(metadata) = fs.metadata(&path) else {
+                continue;
+            };
+            match self.iis_log_action(&metadata) {
+                Some(false) => Self::remove_file_counted(&path, metadata.len, &mut result, progress),
+                Some(true) => match platform::compress_file(&path) {
+                    Ok(saved) => {
+                        result.size_bytes += saved;
+                        result.has_data = true;
+                        progress.file(saved);
+                    }
+                    Err(e) => {
+                        debug!("Failed to compress {}: {}", path.display(), e);
+                        result.errors.push(CleanErrorEntry::new(&path, &e));
+                    }
+                },
+                None => {}
+            }
+        }
+        result
+    }
+
+    /// Size of every unused conda package, one breakdown entry per package
+    fn scan_conda_packages(&self, conda: &CondaPackages, progress: &Progress) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for package in conda.unused_packages() {
+            let mut part = CleanupResult::new();
+            for path in &package.paths {
+                let Ok(metadata) = progress.file_system().metadata(path) else {
+                    continue;
+                };
+                if is_real_dir(&metadata) {
+                    part.merge(self.scan_directory(path, progress));
+                    part.directories += 1;
+                } else {
+                    part.files += 1;
+                    part.size_bytes += metadata.len;
+                    progress.file(metadata.len);
+                }
+            }
+            part.has_data = true;
+            part.breakdown.push(BreakdownEntry {
+                label: package.name,
+                size_bytes: part.size_bytes,
+                note: "未被任何环境使用".to_string(),
+            });
+            result.merge(part);
+        }
+        result
+    }
+
+    /// Bytecode found below each root of a `PythonBytecode` item
+    fn python_bytecode<'a>(&'a self, roots: &'a [PathBuf]) -> Vec<(&'a Path, Vec<PathBuf>)> {
+        let include_envs = self.options.include_envs.unwrap_or(false);
+        self.project_roots(roots)
+            .iter()
+            .map(|root| (root.as_path(), devtools::find_python_bytecode(root, include_envs)))
+            .collect()
+    }
+
+    /// Size of the bytecode below each root, one breakdown entry per root
+    fn scan_python_bytecode(&self, roots: &[PathBuf], progress: &Progress) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for (root, found) in self.python_bytecode(roots) {
+            if found.is_empty() {
+                continue;
+            }
+            let mut part = CleanupResult::new();
+            let mut dirs = 0;
+            for path in &found {
+                let Ok(metadata) = progress.file_system().metadata(path) else {
+                    continue;
+                };
+                if is_real_dir(&metadata) {
+                    part.merge(self.scan_directory(path, progress));
+                    part.directories += 1;
+                    dirs += 1;
+                } else {
+                    part.files += 1;
+                    part.size_bytes += metadata.len;
+                    progress.file(metadata.len);
+                }
+            }
+            part.has_data = true;
+            part.breakdown.push(BreakdownEntry {
+                label: root.display().to_string(),
+                size_bytes: part.size_bytes,
+                note: format!("{} 个 __pycache__ 目录, {} 个 .pyc 文件", dirs, found.len() - dirs),
+            });
+            result.merge(part);
+        }
+        result
+    }
+
+    /// Count and size of the files matched by a `FilePattern` item, one breakdown entry per name
+    fn scan_file_pattern(&self, pattern: &FilePattern, progress: &Progress) -> CleanupResult {
+        let mut by_name: BTreeMap<String, CleanupResult> = BTreeMap::new();
+        let fs = progress.file_system();
+        for path in self.pattern_files(fs, pattern) {
+            let Ok(metadata) = fs.metadata(&path) else {
+                continue;
+            };
+            let part = by_name.entry(path.file_name().unwrap_or_default().to_string_lossy().into_owned()).or_default();
+            part.files += 1;
+            part.size_bytes += metadata.len;
+            part.has_data = true;
+            progress.file(metadata.len);
+        }
+        let mut result = CleanupResult::new();
+        for (name, mut part) in by_name {
+            part.breakdown.push(BreakdownEntry {
+                label: name,
+                size_bytes: part.size_bytes,
+                note: format!("{} 个文件", part.files),
+            });
+            result.merge(part);
+        }
+        result
+    }
+
+    /// Files of a `Files` item, narrowed to installers and archives with the `installers_only` option
+    fn item_files(&self, fs: &dyn Fs, patterns: &[PathBuf]) -> Vec<PathBuf> {
+        let mut files = expand_files(fs, patterns);
+        if self.options.installers_only.unwrap_or(false) {
+            let installers = patterns::installer_patterns();
+            files.retain(|path| {
+                path.file_name().is_some_and(|name| patterns::matches_any(&installers, &name.to_string_lossy()))
+            });
+        }
+        files
+    }
+
+    /// Size and age of every file, one breakdown entry per file. Files younger than the
+    /// item's minimum age are listed with their size but not counted.
+    fn scan_files(&self, patterns: &[PathBuf], progress: &Progress) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        let fs = progress.file_system();
+        // Say why unreadable paths are not counted instead of silently reporting zero
+        let denied = denied_files(fs, patterns);
+        result.breakdown.extend(denied.iter().map(|pattern| denied_entry(fs, pattern)));
+        if self.group_files {
+            for pattern in patterns.iter().filter(|pattern| !denied.contains(pattern)) {
+                let mut part = CleanupResult::new();
+                let mut kept = 0;
+                for path in self.item_files(fs, std::slice::from_ref(pattern)) {
+                    let Ok(metadata) = fs.metadata(&path) else {
+                        continue;
+                    };
+                    if self.is_too_recent(&metadata) {
+                        kept += 1;
+                        continue;
+                    }
+                    part.files += 1;
+                    part.size_bytes += metadata.len;
+                    part.has_data = true;
+                    progress.file(metadata.len);
+                }
+                if part.has_data || kept > 0 {
+                    result.breakdown.push(BreakdownEntry {
+                        label: pattern.display().to_string(),
+                        size_bytes: part.size_bytes,
+                        note: if self.effective_min_age_days() > 0 {
+                            format!("{} / {} 个文件已过期", part.files, part.files + kept)
+                        } else {
+                            String::new()
+                        },
+                    });
+                }
+                result.merge(part);
+            }
+            return result;
+        }
+        for path in self.item_files(fs, patterns).into_iter().filter(|path| !denied.contains(&path)) {
+            let Ok(metadata) = fs.metadata(&path) else {
+                continue;
+            };
+            let age = metadata.modified_time().map_or(0, devtools::days_since);
+            let label = path.display().to_string();
+            if self.is_too_recent(&metadata) {
+                result.breakdown.push(BreakdownEntry {
+                    label,
+                    size_bytes: metadata.len,
+                    note: format!("{} 天前, 保留", age),
+                });
+                continue;
+            }
+            result.files += 1;
+            result.size_bytes += metadata.len;
+            result.has_data = true;
+            progress.file(metadata.len);
+            result.breakdown.push(BreakdownEntry {
+                label,
+                size_bytes: metadata.len,
+                note: format!("{} 天前", age),
+            });
+        }
+        result
+    }
+
+    /// Delete the files of a `Files` item, or move them to the trash. Files another program
+    /// has open are listed as skipped rather than reported as errors.
+    fn clean_files(&self, patterns: &[PathBuf], progress: &Progress) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        let fs = progress.file_system();
+        // Without admin rights only the readable part is cleaned; list what was skipped
+        let denied = denied_files(fs, patterns);
+        result.breakdown.extend(denied.iter().map(|pattern| denied_entry(fs, pattern)));
+        let (paths, sizes): (Vec<PathBuf>, Vec<u64>) = self
+            .item_files(fs, patterns)
+            .into_iter()
+            .filter(|path| !denied.contains(&path))
+            .filter_map(|path| {
+                let metadata = fs.metadata(&path).ok().filter(|m| !self.is_too_recent(m))?;
+                // Files running processes hold open are left out of the batch
+                let holders = progress.holders_of(&path);
+                if !holders.is_empty() {
+                    debug!("Skipping {}: held open by {:?}", path.display(), holders);
+                    result.skip_in_use(&path, metadata.len, holders);
+                    return None;
+                }
+                Some((path, metadata.len))
+            })
+            .unzip();
+        // Trashing goes in one batch, so a trash program is started once
+        let removed: Vec<io::Result<()>> = if self.options.use_trash.unwrap_or(self.use_trash) {
+            let trashed = platform::trash_files(&paths);
+            for backend in trashed.iter().flatten() {
+                if !result.trashed_with.contains(backend) {
+                    result.trashed_with.push(*backend);
+                }
+            }
+            trashed.into_iter().map(|trashed| trashed.map(drop)).collect()
+        } else {
+            paths.iter().map(|path| remove_read_only(fs, path, |path| fs.remove_file(path))).collect()
+        };
+        for ((path, size), removed) in paths.into_iter().zip(sizes).zip(removed) {
+            match removed {
+                Ok(()) => {
+                    result.files += 1;
+                    result.size_bytes += size;
+                    result.has_data = true;
+                    progress.deleted(&path, size);
+                }
+                Err(e) if platform::is_in_use(&e) => {
+                    debug!("Skipping {}: in use", path.display());
+                    result.breakdown.push(BreakdownEntry {
+                        label: path.display().to_string(),
+                        size_bytes: size,
+                        note: IN_USE_NOTE.to_string(),
+                    });
+                }
+                Err(e) => {
+                    let error = CleanErrorEntry::new(&path, &e);
+                    progress.failed(&error);
+                    result.errors.push(error);
+                }
+            }
+        }
+        result
+    }
+
+    /// Delete a whole directory or a single file
+    fn remove_path(&self, path: &Path, result: &mut CleanupResult, progress: &Progress) {
+        let metadata = progress.file_system().symlink_metadata(path).ok();
+        if metadata.as_ref().is_some_and(is_real_dir) {
+            self.remove_tree(path, result, progress);
+        } else {
+            let size = metadata.as_ref().map_or(0, entry_size);
+            Self::remove_file_counted(path, size, result, progress);
+        }
+    }
+
+    /// Directories `depth` levels below `root` unused for the item's minimum age, with their idle days
+    fn unused_dirs(&self, fs: &dyn Fs, root: &Path, depth: usize) -> Vec<(PathBuf, u32)> {
+        let min_age_days = self.effective_min_age_days();
+        let mut level = vec![root.to_path_buf()];
+        for _ in 0..depth {
+            level = level
+                .iter()
+                .filter_map(|dir| Some(fs.read_dir(dir).ok()?.into_iter().map(move |name| dir.join(name))))
+                .flatten()
+                .filter(|path| fs.symlink_metadata(path).is_ok_and(|metadata| is_real_dir(&metadata)))
+                .collect();
+        }
+        level.sort();
+        level
+            .into_iter()
+            .map(|dir| {
+                let idle_days = devtools::days_since(devtools::last_used(&dir));
+                (dir, idle_days)
+            })
+            .filter(|(_, idle_days)| *idle_days >= min_age_days)
+            .collect()
+    }
+
+    /// Age in days after which `BrowserHistory` rows are deleted
+    pub fn history_max_age_days(&self) -> u32 {
+        self.options.max_age_days.unwrap_or(history::DEFAULT_HISTORY_MAX_AGE_DAYS)
+    }
+
+    /// Directories or files the item cleans below, for the item types with fixed targets
+    pub fn root_paths(&self) -> Vec<&Path> {
+        match &self.cleanup_type {
+            CleanupType::Directory(path) | CleanupType::TempFiles(path) => vec![path],
+            CleanupType::Directories(paths) => paths.iter().map(PathBuf::as_path).collect(),
+            CleanupType::BrowserHistory(databases) => databases.iter().map(|db| db.path.as_path()).collect(),
+            CleanupType::PackageStore(store) => vec![&store.dir],
+            CleanupType::MavenRepo(repository) | CleanupType::IisLogs(repository) => vec![repository],
+            CleanupType::CondaPkgs(conda) => conda.pkgs_dirs.iter().map(PathBuf::as_path).collect(),
+            CleanupType::UnusedDirs { roots, .. } => roots.iter().map(PathBuf::as_path).collect(),
+            CleanupType::Custom(target) => target.root_paths(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Check whether the item's target paths exist and are accessible
+    pub fn validate(&self) -> ItemStatus {
+        self.validate_with(&CleanOptions::default())
+    }
+
+    /// Like [`validate`](Self::validate), reading directories through `options.file_system()`
+    pub fn validate_with(&self, options: &CleanOptions) -> ItemStatus {
+        let progress = Progress::silent().fs(options.file_system());
+        let fs = progress.file_system();
+        let paths: Vec<&Path> = match &self.cleanup_type {
+            CleanupType::Files(patterns) => return validate_files(fs, patterns),
+            CleanupType::RustTargets(roots) | CleanupType::NodeModules(roots) | CleanupType::PythonBytecode(roots) => {
+                return validate_roots(fs, self.project_roots(roots));
+            }
+            CleanupType::FilePattern(pattern) => return validate_roots(fs, self.project_roots(&pattern.roots)),
+            CleanupType::Custom(target) => return target.validate_in(&ItemContext::new(self, &progress)),
+            _ => self.root_paths(),
+        };
+
+        match validate_paths(fs, &paths) {
+            ItemStatus::Ready if self.mount_root().is_some() => ItemStatus::MountRoot,
+            status => status,
+        }
+    }
+
+    /// The first directory the item empties that is a volume root or a mount point
+    pub fn mount_root(&self) -> Option<&Path> {
+        self.mount_root_with(platform::is_mount_point)
+    }
+
+    /// Like `mount_root`, with `is_mount` deciding which directories are mount points. Paths
+    /// without a parent, such as `/` or `C:\`, are always volume roots. `TempFiles` items are
+    /// not checked: they only delete files named like temp files, so a tmpfs `/tmp` is fine.
+    pub fn mount_root_with(&self, is_mount: impl Fn(&Path) -> bool) -> Option<&Path> {
+        let emptied: Vec<&Path> = match &self.cleanup_type {
+            CleanupType::Directory(path) => vec![path],
+            CleanupType::Directories(paths) => paths.iter().map(PathBuf::as_path).collect(),
+            _ => Vec::new(),
+        };
+        emptied.into_iter().find(|path| path.parent().is_none() || is_mount(path))
+    }
+
+    /// The item's outcome in a `Report` given its `result`: the error when cleaning it was
+    /// refused outright, as for a volume root
+    pub fn outcome(&self, result: CleanupResult, options: &CleanOptions) -> Result<CleanupResult, CleanError> {
+        self.check_mount_roots(options).map(|()| result)
+    }
+
+    /// Fails with `CleanError::RefusedMountPoint` when the item would empty a volume root or
+    /// mount point, unless `options.allow_mount_roots` is set
+    pub fn check_mount_roots(&self, options: &CleanOptions) -> crate::error::Result<()> {
+        self.check_mount_roots_with(options, platform::is_mount_point)
+    }
+
+    /// Like `check_mount_roots`, with `is_mount` deciding which directories are mount points
+    pub fn check_mount_roots_with(
+        &self,
+        options: &CleanOptions,
+        is_mount: impl Fn(&Path) -> bool,
+    ) -> crate::error::Result<()> {
+        if options.allow_mount_roots {
+            return Ok(());
+        }
+        match self.mount_root_with(is_mount) {
+            Some(root) => Err(CleanError::RefusedMountPoint(root.to_path_buf())),
+            None => Ok(()),
+        }
+    }
+
+    /// Scan the cleanup item without deleting anything
+    pub fn scan(&self) -> CleanupResult {
+        self.scan_with(&CleanOptions::default())
+    }
+
+    /// Scan the cleanup item, reporting progress to `options.observers`
+    pub fn scan_with(&self, options: &CleanOptions) -> CleanupResult {
+        self.with_progress(options, |progress| match self.skip_remote(options) {
+            Some(skipped) => skipped,
+            None => self.scan_paths(progress),
+        })
+    }
+
+    /// The first of the item's existing roots on a network share or removable drive
+    pub fn remote_root(&self) -> Option<(&Path, VolumeKind)> {
+        self.root_paths()
+            .into_iter()
+            .filter(|root| root.exists())
+            .map(|root| (root, platform::volume_kind(root)))
+            .find(|(_, kind)| kind.is_remote())
+    }
+
+    /// With `options.skip_remote`, the skipped result for an item with files on a network
+    /// share or removable drive
+    fn skip_remote(&self, options: &CleanOptions) -> Option<CleanupResult> {
+        if !options.skip_remote {
+            return None;
+        }
+        let (root, kind) = self.remote_root()?;
+        info!("Skipping {}: {} is on a {:?} volume", self.name, root.display(), kind);
+        Some(CleanupResult::skipped(format!("{} 位于{}", root.display(), kind.label())))
+    }
+
+    fn scan_paths(&self, progress: &Progress) -> CleanupResult {
+        match &self.cleanup_type {
+            CleanupType::Directory(path) => self.scan_directory(path, progress),
+            CleanupType::Directories(paths) => {
+                let mut result = CleanupResult::new();
+                for path in paths {
+                    // Say why an unreadable location is not counted, such as the system temp without admin rights
+                    if is_dir_denied(progress.file_system(), path) {
+                        result.breakdown.push(denied_entry(progress.file_system(), path));
+                        continue;
+                    }
+                    let mut part = self.scan_directory(path, progress);
+                    if paths.len() > 1 && part.has_data {
+                        part.breakdown.push(BreakdownEntry {
+                            label: path.display().to_string(),
+                            size_bytes: part.size_bytes,
+                            note: String::new(),
+                        });
+                    }
+                    result.merge(part);
+                }
+                result
+            }
+            CleanupType::TempFiles(path) => self.scan_temp_files(path, progress),
+            CleanupType::BrowserHistory(databases) => history::scan_history(databases, self.history_max_age_days()),
+            CleanupType::RustTargets(_) | CleanupType::NodeModules(_) => self.scan_projects(progress),
+            CleanupType::PackageStore(store) => self.scan_package_store(store, progress),
+            CleanupType::MavenRepo(repository) => self.scan_maven_repo(repository, progress),
+            CleanupType::CondaPkgs(conda) => self.scan_conda_packages(conda, progress),
+            CleanupType::PythonBytecode(roots) => self.scan_python_bytecode(roots, progress),
+            CleanupType::FilePattern(pattern) => self.scan_file_pattern(pattern, progress),
+            CleanupType::Files(patterns) => self.scan_files(patterns, progress),
+            CleanupType::IisLogs(root) => self.scan_iis_logs(root, progress),
+            CleanupType::UnusedDirs { roots, depth } => {
+                let mut result = CleanupResult::new();
+                for root in roots {
+                    let mut part = CleanupResult::new();
+                    for (dir, _) in self.unused_dirs(progress.file_system(), root, *depth) {
+                        let mut unused = self.scan_directory(&dir, progress);
+                        unused.directories += 1;
+                        unused.has_data = true;
+                        part.merge(unused);
+                    }
+                    if roots.len() > 1 {
+                        let denied = is_dir_denied(progress.file_system(), root);
+                        if part.has_data || denied {
+                            part.breakdown.push(BreakdownEntry {
+                                label: root.display().to_string(),
+                                size_bytes: part.size_bytes,
+                                note: if denied { ItemStatus::denied().reason().to_string() } else { String::new() },
+                            });
+                        }
+                    }
+                    result.merge(part);
+                }
+                result
+            }
+            CleanupType::Custom(target) => target.scan_in(&ItemContext::new(self, progress)),
+        }
+    }
+
+    /// Measure a file, or a directory and everything below it, as `remove_path` would free
+    fn scan_entry(&self, path: &Path, progress: &Progress) -> CleanupResult {
+        let Ok(metadata) = progress.file_system().symlink_metadata(path) else {
+            return CleanupResult::new();
+        };
+        if is_real_dir(&metadata) {
+            let mut result = self.scan_directory(path, progress);
+            result.directories += 1;
+            result.has_data = true;
+            return result;
+        }
+        let size = entry_size(&metadata);
+        progress.file(size);
+        CleanupResult {
+            files: 1,
+            size_bytes: size,
+            disk_bytes: entry_disk_size(path, &metadata),
+            has_data: true,
+            ..CleanupResult::new()
+        }
+    }
+
+    /// Run `work` between `ItemStarted` and `ItemFinished` events
+    fn with_progress(&self, options: &CleanOptions, work: impl FnOnce(&Progress) -> CleanupResult) -> CleanupResult {
+        let progress = Progress::new(&self.id, &options.observers)
+            .detect_in_use(options.detect_in_use)
+            .fs(options.file_system());
+        progress.started(&self.name);
+        let result = work(&progress);
+        progress.finished(&result);
+        result
+    }
+
+    /// Cheaply estimate the item by looking only at the first two directory levels.
+    ///
+    /// Entry counts are approximate and `size_bytes` is always 0; use [`scan`](Self::scan)
+    /// for real numbers.
+    pub fn estimate(&self) -> CleanupResult {
+        self.estimate_with(&CleanOptions::default())
+    }
+
+    /// Like [`estimate`](Self::estimate), reading directories through `options.file_system()`
+    pub fn estimate_with(&self, options: &CleanOptions) -> CleanupResult {
+        let progress = Progress::silent().fs(options.file_system());
+        let fs = progress.file_system();
+        let mut result = match &self.cleanup_type {
+            CleanupType::Directory(path) => self.estimate_directory(path, false, fs),
+            CleanupType::Directories(paths) => {
+                let mut result = CleanupResult::new();
+                for path in paths {
+                    result.merge(self.estimate_directory(path, false, fs));
+                }
+                result
+            }
+            CleanupType::TempFiles(path) => self.estimate_directory(path, true, fs),
+            CleanupType::PackageStore(store) => self.estimate_directory(&store.dir, false, fs),
+            CleanupType::UnusedDirs { roots, depth } => {
+                let unused = roots.iter().map(|root| self.unused_dirs(fs, root, *depth).len() as u64).sum();
+                CleanupResult {
+                    directories: unused,
+                    has_data: unused > 0,
+                    ..CleanupResult::new()
+                }
+            }
+            // Finding stale snapshots only needs directory listings, no file sizes
+            CleanupType::MavenRepo(repository) => {
+                let stale: u64 = devtools::find_maven_versions(repository)
+                    .iter()
+                    .map(|version| version.stale_snapshot_files().len() as u64)
+                    .sum();
+                CleanupResult {
+                    files: stale,
+                    has_data: stale > 0,
+                    ..CleanupResult::new()
+                }
+            }
+            CleanupType::Files(patterns) => {
+                let found = self.item_files(fs, patterns).len() as u64;
+                CleanupResult {
+                    files: found,
+                    has_data: found > 0,
+                    ..CleanupResult::new()
+                }
+            }
+            CleanupType::PythonBytecode(roots) => {
+                let found = self.python_bytecode(roots).iter().map(|(_, found)| found.len() as u64).sum();
+                CleanupResult {
+                    entries: found,
+                    has_data: found > 0,
+                    ..CleanupResult::new()
+                }
+            }
+            CleanupType::FilePattern(pattern) => {
+                let found = self.pattern_files(fs, pattern).len() as u64;
+                CleanupResult {
+                    files: found,
+                    has_data: found > 0,
+                    ..CleanupResult::new()
+                }
+            }
+            CleanupType::IisLogs(root) => {
+                let found = iis_site_logs(fs, root).iter().map(|(_, logs)| logs.len() as u64).sum();
+                CleanupResult {
+                    files: found,
+                    has_data: found > 0,
+                    ..CleanupResult::new()
+                }
+            }
+            CleanupType::CondaPkgs(conda) => {
+                let unused = conda.unused_packages().len() as u64;
+                CleanupResult {
+                    entries: unused,
+                    has_data: unused > 0,
+                    ..CleanupResult::new()
+                }
+            }
+            // Counting old rows means copying the database; only report which ones exist
+            CleanupType::BrowserHistory(databases) => {
+                let existing = databases.iter().filter(|db| is_file(fs, &db.path)).count() as u64;
+                CleanupResult {
+                    files: existing,
+                    has_data: existing > 0,
+                    ..CleanupResult::new()
+                }
+            }
+            CleanupType::RustTargets(_) | CleanupType::NodeModules(_) => {
+                let idle = self.idle_project_builds().count() as u64;
+                CleanupResult {
+                    directories: idle,
+                    has_data: idle > 0,
+                    ..CleanupResult::new()
+                }
+            }
+            CleanupType::Custom(target) => target.estimate_in(&ItemContext::new(self, &progress)),
+        };
+        result.is_estimate = true;
+        result
+    }
+
+    /// Clean the cleanup item (delete files) with default options
+    pub fn clean(&self) -> CleanupResult {
+        self.clean_with(&CleanOptions::default())
+    }
+
+    /// Clean the cleanup item (delete files)
+    pub fn clean_with(&self, options: &CleanOptions) -> CleanupResult {
+        self.with_progress(options, |progress| self.clean_paths(options, progress))
+    }
+
+    fn clean_paths(&self, options: &CleanOptions, progress: &Progress) -> CleanupResult {
+        if let Some(skipped) = self.skip_before_clean(options) {
+            return skipped;
+        }
+        self.with_locks_released(|| self.clean_all(progress))
+    }
+
+    /// Clean every target path of the item, without the running-app check
+    fn clean_all(&self, progress: &Progress) -> CleanupResult {
+        debug!("Starting cleanup for: {}", self.name);
+        let mut result = match &self.cleanup_type {
+            CleanupType::Directory(path) => self.clean_directory(path, false, progress),
+            CleanupType::Directories(paths) => {
+                let mut result = CleanupResult::new();
+                for path in paths {
+                    // Skip unreadable locations with a note instead of an error
+                    if is_dir_denied(progress.file_system(), path) {
+                        result.breakdown.push(denied_entry(progress.file_system(), path));
+                        continue;
+                    }
+                    result.merge(self.clean_directory(path, false, progress));
+                }
+                result
+            }
+            CleanupType::TempFiles(path) => self.clean_temp_files(path, false, progress),
+            CleanupType::BrowserHistory(databases) => history::clean_history(databases, self.history_max_age_days()),
+            CleanupType::RustTargets(_) | CleanupType::NodeModules(_) => self.clean_projects(progress),
+            CleanupType::PackageStore(store) => self.clean_package_store(store, progress),
+            CleanupType::MavenRepo(repository) => self.clean_maven_repo(repository, progress),
+            CleanupType::Files(patterns) => self.clean_files(patterns, progress),
+            CleanupType::PythonBytecode(roots) => {
+                let mut result = CleanupResult::new();
+                for (_, found) in self.python_bytecode(roots) {
+                    for path in found {
+                        self.remove_path(&path, &mut result, progress);
+                    }
+                }
+                result
+            }
+            CleanupType::FilePattern(pattern) => {
+                let mut result = CleanupResult::new();
+                for path in self.pattern_files(progress.file_system(), pattern) {
+                    self.remove_path(&path, &mut result, progress);
+                }
+                result
+            }
+            CleanupType::IisLogs(root) => self.clean_iis_logs(root, progress),
+            CleanupType::CondaPkgs(conda) => {
+                let mut result = CleanupResult::new();
+                for package in conda.unused_packages() {
+                    for path in &package.paths {
+                        self.remove_path(path, &mut result, progress);
+                    }
+                }
+                result
+            }
+            CleanupType::UnusedDirs { roots, depth } => {
+                let mut result = CleanupResult::new();
+                for root in roots {
+                    for (dir, _) in self.unused_dirs(progress.file_system(), root, *depth) {
+                        self.remove_tree(&dir, &mut result, progress);
+                    }
+                }
+                result
+            }
+            CleanupType::Custom(target) => target.clean_in(&ItemContext::new(self, progress)),
+        };
+        self.drop_in_use_errors(&mut result, progress);
+        self.collapse_denied_errors(&mut result);
+        self.verify_clean(&mut result, progress);
+        result
+    }
+
+    /// Clean the oldest files first, stopping as soon as `max_bytes` have been freed.
+    ///
+    /// Browser history, Rust targets, node_modules, Python bytecode, pruned package stores, Maven
+    /// repositories, conda packages, unused directories and custom targets cannot be cleaned
+    /// partially and are cleaned in full.
+    pub fn clean_at_most(&self, max_bytes: u64, options: &CleanOptions) -> CleanupResult {
+        self.with_progress(options, |progress| self.clean_oldest(max_bytes, options, progress))
+    }
+
+    fn clean_oldest(&self, max_bytes: u64, options: &CleanOptions, progress: &Progress) -> CleanupResult {
+        if let Some(skipped) = self.skip_before_clean(options) {
+            return skipped;
+        }
+        self.with_locks_released(|| self.clean_oldest_files(max_bytes, progress))
+    }
+
+    fn clean_oldest_files(&self, max_bytes: u64, progress: &Progress) -> CleanupResult {
+        let roots: Vec<(&Path, bool)> = match &self.cleanup_type {
+            CleanupType::Directory(path) => vec![(path, false)],
+            CleanupType::Directories(paths) => paths.iter().map(|path| (path.as_path(), false)).collect(),
+            CleanupType::TempFiles(path) => vec![(path, true)],
+            CleanupType::PackageStore(store) if store.prune.is_none() => vec![(store.dir.as_path(), false)],
+            CleanupType::BrowserHistory(_)
+            | CleanupType::RustTargets(_)
+            | CleanupType::NodeModules(_)
+            | CleanupType::PackageStore(_)
+            | CleanupType::MavenRepo(_)
+            | CleanupType::CondaPkgs(_)
+            | CleanupType::PythonBytecode(_)
+            | CleanupType::FilePattern(_)
+            | CleanupType::Files(_)
+            | CleanupType::IisLogs(_)
+            | CleanupType::UnusedDirs { .. }
+            | CleanupType::Custom(_) => {
+                return self.clean_all(progress);
+            }
+        };
+
+        let mut files = Vec::new();
+        let mut dirs = Vec::new();
+        for (root, temp_only) in roots {
+            self.collect_candidates(progress.file_system(), root, temp_only, &mut files, &mut dirs);
+        }
+        // Oldest first; the path breaks ties so repeated runs delete the same files
+        files.sort();
+
+        info!("Cleaning {} until {} bytes are freed...", self.name, max_bytes);
+        let mut result = CleanupResult::new();
+        let mut remaining = files.into_iter();
+        for (_, path, size) in remaining.by_ref() {
+            Self::remove_file_counted(&path, size, &mut result, progress);
+            if result.size_bytes >= max_bytes {
+                break;
+            }
+        }
+
+        // Only tidy up emptied directories when the whole item was processed
+        if remaining.next().is_none() && !self.files_only {
+            let fs = progress.file_system();
+            for dir in dirs {
+                if remove_read_only(fs, &dir, |dir| fs.remove_dir(dir)).is_ok() {
+                    result.directories += 1;
+                    result.has_data = true;
+                }
+            }
+        }
+
+        self.drop_in_use_errors(&mut result, progress);
+        self.collapse_denied_errors(&mut result);
+        self.verify_clean(&mut result, progress);
+        result
+    }
+
+    /// Run `clean` with the item's service stopped and Explorer closed, when that is enabled
+    /// and possible.
+    ///
+    /// Without administrator rights the service keeps running and files it holds open are
+    /// reported as errors like any other locked file.
+    fn with_locks_released(&self, clean: impl FnOnce() -> CleanupResult) -> CleanupResult {
+        let service = self
+            .service
+            .as_deref()
+            .filter(|_| self.options.stop_service.unwrap_or(false));
+        let stopped = match service {
+            Some(service) if platform::is_elevated() => platform::stop_service(service),
+            Some(service) => {
+                info!("Not stopping {} for {}: administrator rights are required", service, self.name);
+                false
+            }
+            None => false,
+        };
+        let result = if self.explorer_lock && self.options.restart_explorer.unwrap_or(false) {
+            // Without Explorer stopped the clean still runs, skipping the files it has open
+            let mut clean = Some(clean);
+            let stopped = platform::with_explorer_stopped(|| clean.take().map(|clean| clean()));
+            if let Err(e) = &stopped {
+                info!("Cleaning {} with Explorer running: {}", self.name, e);
+            }
+            stopped.ok().flatten().or_else(|| clean.map(|clean| clean())).unwrap_or_default()
+        } else {
+            clean()
+        };
+        if let Some(service) = service.filter(|_| stopped) {
+            platform::start_service(service);
+        }
+        result
+    }
+
+    /// Result to return instead of cleaning without the required rights, while another
+    /// program holds one of the item's locks or while the item's application is running
+    fn skip_before_clean(&self, options: &CleanOptions) -> Option<CleanupResult> {
+        if let Err(CleanError::RefusedMountPoint(root)) = self.check_mount_roots(options) {
+            info!("Skipping {}: {} is a volume root or mount point", self.name, root.display());
+            return Some(CleanupResult::skipped(format!("{} 是磁盘根目录或挂载点, 已拒绝清理", root.display())));
+        }
+        if let Some(skipped) = self.skip_remote(options) {
+            return Some(skipped);
+        }
+        if self.requires_admin && !platform::is_elevated() {
+            info!("Skipping {}: administrator rights are required", self.name);
+            return Some(CleanupResult::skipped(ADMIN_REQUIRED_NOTE));
+        }
+        if let Some(lock) = self.busy_locks.iter().find(|lock| is_lock_held(lock)) {
+            info!("Skipping {}: {} is locked", self.name, lock.display());
+            return Some(CleanupResult::skipped(format!("{} 被其他程序锁定, 请稍后再试", lock.display())));
+        }
+        if !options.ignore_running_apps {
+            if let Some(app) = self.app_check.as_ref().filter(|app| app.is_running()) {
+                info!("Skipping {}: {} is running", self.name, app.app_name);
+                return Some(CleanupResult::skipped(format!("{} 正在运行, 请先关闭", app.app_name)));
+            }
+        }
+        self.skip_without_restore_point(options)
+    }
+
+    /// Create the restore point the item wants, once per run. Without it the item is skipped
+    /// unless the user acknowledged the failure.
+    fn skip_without_restore_point(&self, options: &CleanOptions) -> Option<CleanupResult> {
+        if !self.wants_restore_point(options) {
+            return None;
+        }
+        let error = options.create_restore_point().err()?;
+        if options.restore_point_acknowledged {
+            return None;
+        }
+        info!("Skipping {}: no restore point ({})", self.name, error);
+        Some(CleanupResult::skipped(format!("{}: {}", RESTORE_POINT_FAILED_NOTE, error)))
+    }
+
+    /// Collect deletable files (with modification time) and the directories that would be emptied.
+    ///
+    /// Directories are pushed after their contents so they can be removed in order.
+    fn collect_candidates(
+        &self,
+        fs: &dyn Fs,
+        dir: &Path,
+        temp_only: bool,
+        files: &mut Vec<(SystemTime, PathBuf, u64)>,
+        dirs: &mut Vec<PathBuf>,
+    ) {
+        let Ok(names) = fs.read_dir(dir) else {
+            return;
+        };
+
+        for name in names {
+            let entry_path = dir.join(&name);
+            let Ok(metadata) = fs.symlink_metadata(&entry_path) else {
+                continue;
+            };
+            if self.skips_cloud_placeholder(&name, &metadata) {
+                continue;
+            }
+            let matched = !temp_only || self.is_temp_name(&name.to_string_lossy());
+
+            if is_real_dir(&metadata) {
+                if matched {
+                    self.collect_candidates(fs, &entry_path, false, files, dirs);
+                    dirs.push(entry_path);
+                } else {
+                    self.collect_candidates(fs, &entry_path, true, files, dirs);
+                }
+            } else if matched {
+                if self.is_too_recent(&metadata) {
+                    continue;
+                }
+                let modified = metadata.modified_time().unwrap_or(SystemTime::UNIX_EPOCH);
+                files.push((modified, entry_path, entry_size(&metadata)));
+            }
+        }
+    }
+
+    /// When the item skips files in use, or with in-use detection, list them in the breakdown
+    /// instead of as errors; detection also names the processes holding them
+    fn drop_in_use_errors(&self, result: &mut CleanupResult, progress: &Progress) {
+        if !self.skip_in_use && !progress.detects_in_use() {
+            return;
+        }
+        let (in_use, errors): (Vec<_>, Vec<_>) = std::mem::take(&mut result.errors).into_iter().partition(|e| e.in_use);
+        result.errors = errors;
+        let paths: Vec<PathBuf> = in_use.into_iter().map(|error| error.path).collect();
+        let holders = if progress.detects_in_use() {
+            platform::file_holders(&paths)
+        } else {
+            vec![Vec::new(); paths.len()]
+        };
+        for (path, holders) in paths.iter().zip(holders) {
+            let size = progress.file_system().metadata(path).map_or(0, |metadata| metadata.len);
+            if progress.detects_in_use() {
+                result.skip_in_use(path, size, holders);
+            } else {
+                result.breakdown.push(BreakdownEntry {
+                    label: path.display().to_string(),
+                    size_bytes: size,
+                    note: IN_USE_NOTE.to_string(),
+                });
+            }
+        }
+    }
+
+    /// For items that need administrator rights, report each protected root once instead of
+    /// every file when running without them
+    fn collapse_denied_errors(&self, result: &mut CleanupResult) {
+        if !self.requires_admin || platform::is_elevated() {
+            return;
+        }
+        let errors = std::mem::take(&mut result.errors);
+        result.errors = collapse_permission_errors(errors, &self.root_paths());
+    }
+
+    fn verify_clean(&self, result: &mut CleanupResult, progress: &Progress) {
+        debug!("Verifying cleanup for: {}", self.name);
+        let verify_result = self.scan_paths(&Progress::silent().fs(progress.file_system()));
+        
+        if verify_result.has_data {
+            info!("Warning: {} still has {} files after cleanup", 
+                  self.name, verify_result.files);
+        }
+        result.files_remaining = verify_result.files;
+        result.size_remaining = verify_result.size_bytes;
+        
+        info!("Cleanup complete for {}: {} files, {}, {} errors", 
+              self.name, result.files, result.size_human(), result.errors.len());
+    }
+
+    fn scan_directory(&self, path: &Path, progress: &Progress) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        let fs = progress.file_system();
+
+        if fs.metadata(path).is_err() {
+            return result;
+        }
+
+        if let Ok(names) = fs.read_dir(path) {
+            for name in names {
+                let entry_path = path.join(&name);
+                let Ok(metadata) = fs.symlink_metadata(&entry_path) else {
+                    continue;
+                };
+
+                if self.skips_cloud_placeholder(&name, &metadata) {
+                    result.cloud_placeholders += 1;
+                } else if is_real_dir(&metadata) {
+                    let subdir_result = self.scan_directory(&entry_path, progress);
+                    result.files += subdir_result.files;
+                    result.directories += u64::from(!self.files_only) + subdir_result.directories;
+                    result.size_bytes += subdir_result.size_bytes;
+                    result.disk_bytes += subdir_result.disk_bytes;
+                    result.has_data = result.has_data || subdir_result.has_data;
+                    result.cloud_placeholders += subdir_result.cloud_placeholders;
+                } else if !self.is_too_recent(&metadata) {
+                    // Links and junctions are counted as themselves, never as their target
+                    let size = entry_size(&metadata);
+                    result.files += 1;
+                    result.size_bytes += size;
+                    result.disk_bytes += entry_disk_size(&entry_path, &metadata);
+                    result.has_data = true;
+                    progress.file(size);
+                }
+            }
+        }
+
+        debug!("Scanned {}: {} files, {} dirs, {}", 
+               self.name, result.files, result.directories, result.size_human());
+        result
+    }
+
+    fn estimate_directory(&self, path: &Path, temp_only: bool, fs: &dyn Fs) -> CleanupResult {
+        /// Levels below the item's root that are sampled
+        const ESTIMATE_DEPTH: usize = 2;
+
+        let mut result = CleanupResult::new();
+        let mut pending = vec![(path.to_path_buf(), 1)];
+        while let Some((dir, depth)) = pending.pop() {
+            let Ok(names) = fs.read_dir(&dir) else {
+                continue;
+            };
+            for name in names {
+                let entry_path = dir.join(&name);
+                let Ok(metadata) = fs.symlink_metadata(&entry_path) else {
+                    continue;
+                };
+                if self.skips_cloud_placeholder(&name, &metadata) {
+                    continue;
+                }
+                let counted = !temp_only || self.is_temp_name(&name.to_string_lossy());
+
+                if is_real_dir(&metadata) {
+                    if counted && !self.files_only {
+                        result.directories += 1;
+                        result.has_data = true;
+                    }
+                    // Matching temp directories are removed whole, so their contents need no sampling
+                    if depth < ESTIMATE_DEPTH && !(temp_only && counted) {
+                        pending.push((entry_path, depth + 1));
+                    }
+                } else if counted {
+                    result.files += 1;
+                    result.has_data = true;
+                }
+            }
+        }
+
+        debug!("Estimated {}: {} files, {} dirs (sampled)", self.name, result.files, result.directories);
+        result
+    }
+
+    fn clean_directory(&self, path: &Path, dry_run: bool, progress: &Progress) -> CleanupResult {
+        if progress.file_system().symlink_metadata(path).is_err() {
+            return CleanupResult::new();
+        }
+
+        if dry_run {
+            return self.scan_directory(path, progress);
+        }
+
+        info!("Cleaning {}...", self.name);
+        let mut result = CleanupResult::new();
+        self.remove_contents(path, &mut result, progress);
+        result
+    }
+
+    /// Delete everything inside `dir`, counting only entries that were actually removed.
+    ///
+    /// Returns whether files were kept for being younger than the item's minimum age.
+    fn remove_contents(&self, dir: &Path, result: &mut CleanupResult, progress: &Progress) -> bool {
+        let fs = progress.file_system();
+        let names = match fs.read_dir(dir) {
+            Ok(names) => names,
+            Err(e) => {
+                let error = CleanErrorEntry::from_error(dir, &CleanError::read_dir(dir, e));
+                progress.failed(&error);
+                result.errors.push(error);
+                return false;
+            }
+        };
+
+        let mut kept = false;
+        for name in names {
+            let entry_path = dir.join(&name);
+            // Links are not followed, so linked directories and junctions are unlinked, not emptied
+            let Ok(metadata) = fs.symlink_metadata(&entry_path) else {
+                continue;
+            };
+
+            if self.skips_cloud_placeholder(&name, &metadata) {
+                result.cloud_placeholders += 1;
+                kept = true;
+            } else if is_real_dir(&metadata) {
+                kept |= self.remove_tree(&entry_path, result, progress);
+            } else {
+                if self.is_too_recent(&metadata) {
+                    kept = true;
+                    continue;
+                }
+                Self::remove_file_counted(&entry_path, entry_size(&metadata), result, progress);
+            }
+        }
+        kept
+    }
+
+    /// Delete a directory and everything below it. Returns whether recent files were kept.
+    ///
+    /// Files-only items empty the directory but keep it.
+    fn remove_tree(&self, dir: &Path, result: &mut CleanupResult, progress: &Progress) -> bool {
+        let errors_before = result.errors.len();
+        let kept = self.remove_contents(dir, result, progress);
+        if self.files_only || kept || result.errors.len() > errors_before {
+            // Something inside survived, so the directory cannot be removed
+            return kept;
+        }
+        let fs = progress.file_system();
+        match remove_read_only(fs, dir, |dir| fs.remove_dir(dir)) {
+            Ok(()) => {
+                result.directories += 1;
+                result.has_data = true;
+                progress.deleted(dir, 0);
+            }
+            Err(e) => {
+                let error = CleanErrorEntry::from_error(dir, &CleanError::remove(dir, EntryKind::Directory, e));
+                progress.failed(&error);
+                result.errors.push(error);
+            }
+        }
+        false
+    }
+
+    /// Delete a single file, recording either its size or the failure. Files running processes
+    /// hold open are skipped when in-use detection is on.
+    fn remove_file_counted(path: &Path, size: u64, result: &mut CleanupResult, progress: &Progress) {
+        let holders = progress.holders_of(path);
+        if !holders.is_empty() {
+            debug!("Skipping {}: held open by {:?}", path.display(), holders);
+            result.skip_in_use(path, size, holders);
+            return;
+        }
+        let fs = progress.file_system();
+        match remove_read_only(fs, path, |path| fs.remove_file(path)) {
+            Ok(()) => {
+                result.files += 1;
+                result.size_bytes += size;
+                result.has_data = true;
+                progress.deleted(path, size);
+            }
+            Err(e) => {
+                let error = CleanErrorEntry::from_error(path, &CleanError::remove(path, EntryKind::File, e));
+                progress.failed(&error);
+                result.errors.push(error);
+            }
+        }
+    }
+
+    /// Whether a file name matches this item's temp patterns (or the defaults)
+    fn is_temp_name(&self, file_name: &str) -> bool {
+        static DEFAULT_PATTERNS: OnceLock<Vec<TempPattern>> = OnceLock::new();
+        let patterns = self
+            .options
+            .temp_patterns
+            .as_deref()
+            .unwrap_or_else(|| DEFAULT_PATTERNS.get_or_init(patterns::default_temp_patterns));
+        patterns::matches_any(patterns, file_name)
+    }
+
+    fn scan_temp_files(&self, path: &Path, progress: &Progress) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        let fs = progress.file_system();
+
+        if fs.metadata(path).is_err() {
+            return result;
+        }
+
+        if let Ok(names) = fs.read_dir(path) {
+            for name in names {
+                let entry_path = path.join(&name);
+                let Ok(metadata) = fs.symlink_metadata(&entry_path) else {
+                    continue;
+                };
+                
+                // Look for temp file patterns
+                let is_temp = self.is_temp_name(&name.to_string_lossy());
+                
+                if self.skips_cloud_placeholder(&name, &metadata) {
+                    result.cloud_placeholders += u64::from(is_temp);
+                } else if is_real_dir(&metadata) {
+                    if is_temp {
+                        // A matching directory is removed as a whole, contents included
+                        let subdir_result = self.scan_directory(&entry_path, progress);
+                        result.files += subdir_result.files;
+                        result.directories += 1 + subdir_result.directories;
+                        result.size_bytes += subdir_result.size_bytes;
+                        result.disk_bytes += subdir_result.disk_bytes;
+                        result.has_data = true;
+                        result.cloud_placeholders += subdir_result.cloud_placeholders;
+                    } else {
+                        let subdir_result = self.scan_temp_files(&entry_path, progress);
+                        result.files += subdir_result.files;
+                        result.directories += subdir_result.directories;
+                        result.size_bytes += subdir_result.size_bytes;
+                        result.disk_bytes += subdir_result.disk_bytes;
+                        result.has_data = result.has_data || subdir_result.has_data;
+                        result.cloud_placeholders += subdir_result.cloud_placeholders;
+                    }
+                } else if is_temp && !self.is_too_recent(&metadata) {
+                    let size = entry_size(&metadata);
+                    result.files += 1;
+                    result.size_bytes += size;
+                    result.disk_bytes += entry_disk_size(&entry_path, &metadata);
+                    result.has_data = true;
+                    progress.file(size);
+                }
+            }
+        }
+
+        debug!("Scanned temp files in {}: {} files, {} dirs, {}", 
+               self.name, result.files, result.directories, result.size_human());
+        result
+    }
+
+    fn clean_temp_files(&self, path: &Path, dry_run: bool, progress: &Progress) -> CleanupResult {
+        if progress.file_system().symlink_metadata(path).is_err() {
+            return CleanupResult::new();
+        }
+
+        if dry_run {
+            return self.scan_temp_files(path, progress);
+        }
+
+        info!("Cleaning temp files in {}...", self.name);
+        let mut result = CleanupResult::new();
+        self.remove_temp_entries(path, &mut result, progress);
+        result
+    }
+
+    /// Delete matching entries below `dir`, counting only what was actually removed
+    fn remove_temp_entries(&self, dir: &Path, result: &mut CleanupResult, progress: &Progress) {
+        let fs = progress.file_system();
+        let names = match fs.read_dir(dir) {
+            Ok(names) => names,
+            Err(e) => {
+                let error = CleanErrorEntry::from_error(dir, &CleanError::read_dir(dir, e));
+                progress.failed(&error);
+                result.errors.push(error);
+                return;
+            }
+        };
+
+        for name in names {
+            let entry_path = dir.join(&name);
+            let Ok(metadata) = fs.symlink_metadata(&entry_path) else {
+                continue;
+            };
+
+            let is_temp = self.is_temp_name(&name.to_string_lossy());
+
+            if self.skips_cloud_placeholder(&name, &metadata) {
+                result.cloud_placeholders += u64::from(is_temp);
+            } else if is_real_dir(&metadata) {
+                if is_temp {
+                    self.remove_tree(&entry_path, result, progress);
+                } else {
+                    self.remove_temp_entries(&entry_path, result, progress);
+                }
+            } else if is_temp && !self.is_too_recent(&metadata) {
+                Self::remove_file_counted(&entry_path, entry_size(&metadata), result, progress);
+            }
+        }
+    }
+}
+
+/// Whether another program holds `lock`: `.pid` files name the process holding them, `.lck`
+/// files are held while they exist, other lock files are held with a POSIX lock
+fn is_lock_held(lock: &Path) -> bool {
+    if lock.extension().is_some_and(|ext| ext == "lck") {
+        return lock.exists();
+    }
+    if lock.extension().is_some_and(|ext| ext == "pid") {
+        return fs::read_to_string(lock)
+            .ok()
+            .and_then(|pid| pid.trim().parse().ok())
+            .is_some_and(platform::process_exists);
+    }
+    platform::is_locked(lock)
+}
+
+/// Run `remove` on `path`, retrying once after `fs` made it deletable if it was read-only.
+/// On Windows the extended-length form is used, so paths past `MAX_PATH` can be deleted.
+///
+/// Go's module cache, for one, is written read-only on purpose.
+fn remove_read_only(fs: &dyn Fs, path: &Path, remove: impl Fn(&Path) -> io::Result<()>) -> io::Result<()> {
+    match remove(path) {
+        Err(e) if ErrorClass::of(&e) == ErrorClass::Permission && fs.make_deletable(path) => {
+            debug!("Cleared read-only permission for {}", path.display());
+            remove(path)
+        }
+        other => other,
+    }
+}
+
+/// Whether `metadata`, read without following links, is a directory to descend into.
+/// Symlinks, junctions and other reparse points never are, so a link can neither loop nor
+/// expose its target to deletion; they are removed as themselves.
+fn is_real_dir(metadata: &impl EntryMetadata) -> bool {
+    metadata.is_real_dir()
+}
+
+/// Bytes freed by deleting an entry: its length, or nothing for a link
+fn entry_size(metadata: &impl EntryMetadata) -> u64 {
+    if metadata.is_link() {
+        0
+    } else {
+        metadata.size()
+    }
+}
+
+/// Bytes the entry at `path` occupies on disk, or nothing for a link
+fn entry_disk_size(path: &Path, metadata: &impl EntryMetadata) -> u64 {
+    if metadata.is_link() {
+        0
+    } else {
+        metadata.disk_size(path)
+    }
+}
+
+/// Text before and after the single `*` of a `Files` pattern's last component, such as
+/// `("iconcache_", ".db")` for `iconcache_*.db`
+fn wildcard(pattern: &Path) -> Option<(&str, &str)> {
+    let name = pattern.file_name()?.to_str()?;
+    name.split_once('*').filter(|(_, suffix)| !suffix.contains('*'))
+}
+
+/// Whether `name` matches a wildcard split by [`wildcard`], ignoring ASCII case
+fn matches_wildcard(name: &str, (prefix, suffix): (&str, &str)) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.len() >= prefix.len() + suffix.len()
+        && name.starts_with(&prefix.to_ascii_lowercase())
+        && name.ends_with(&suffix.to_ascii_lowercase())
+}
+
+/// Files named by a `Files` item, with `*` in last components expanded, sorted
+fn expand_files(fs: &dyn Fs, patterns: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for pattern in patterns {
+        match (wildcard(pattern), pattern.parent()) {
+            (Some(wildcard), Some(dir)) => {
+                let Ok(names) = fs.read_dir(dir) else {
+                    continue;
+                };
+                files.extend(
+                    names
+                        .into_iter()
+                        .filter(|name| matches_wildcard(&name.to_string_lossy(), wildcard))
+                        .map(|name| dir.join(name))
+                        .filter(|path| is_file(fs, path)),
+                );
+            }
+            _ if is_file(fs, pattern) => files.push(pattern.clone()),
+            _ => {}
+        }
+    }
+    files.sort();
+    files
+}
+
+/// `*.log` files directly inside each `W3SVC*` site folder of an IIS log root, by site
+fn iis_site_logs(fs: &dyn Fs, root: &Path) -> Vec<(PathBuf, Vec<PathBuf>)> {
+    let Ok(names) = fs.read_dir(root) else {
+        return Vec::new();
+    };
+    let sites: Vec<PathBuf> = names
+        .into_iter()
+        .filter(|name| name.to_string_lossy().to_ascii_uppercase().starts_with("W3SVC"))
+        .map(|name| root.join(name))
+        .filter(|path| fs.symlink_metadata(path).is_ok_and(|metadata| is_real_dir(&metadata)))
+        .collect();
+    sites
+        .into_iter()
+        .map(|site| {
+            let logs = expand_files(fs, &[site.join("*.log")]);
+            (site, logs)
+        })
+        .collect()
+}
+
+/// Try to read a `Files` pattern: open the file, or list the directory of a wildcard pattern.
+/// `None` when it does not exist.
+fn probe_files(fs: &dyn Fs, pattern: &Path) -> Option<io::Result<()>> {
+    let is_pattern = wildcard(pattern).is_some();
+    match pattern.parent() {
+        Some(dir) if is_pattern && is_dir(fs, dir) => Some(fs.read_dir(dir).map(drop)),
+        _ if !is_pattern && is_file(fs, pattern) => Some(fs.open(pattern)),
+        _ => None,
+    }
+}
+
+/// Whether `path` is a directory, or a link to one
+fn is_dir(fs: &dyn Fs, path: &Path) -> bool {
+    fs.metadata(path).is_ok_and(|metadata| metadata.entry_type == EntryType::Dir)
+}
+
+/// Whether `path` is a file, or a link to one
+fn is_file(fs: &dyn Fs, path: &Path) -> bool {
+    fs.metadata(path).is_ok_and(|metadata| metadata.entry_type == EntryType::File)
+}
+
+fn is_permission_denied(probe: &io::Result<()>) -> bool {
+    matches!(probe, Err(e) if e.kind() == io::ErrorKind::PermissionDenied)
+}
+
+/// Patterns of a `Files` item whose file or directory cannot be read
+fn denied_files<'a>(fs: &dyn Fs, patterns: &'a [PathBuf]) -> Vec<&'a PathBuf> {
+    patterns
+        .iter()
+        .filter(|pattern| probe_files(fs, pattern).is_some_and(|probe| is_permission_denied(&probe)))
+        .collect()
+}
+
+/// Whether listing a directory through `fs` is refused
+fn is_dir_denied(fs: &dyn Fs, dir: &Path) -> bool {
+    matches!(fs.read_dir(dir), Err(e) if e.kind() == io::ErrorKind::PermissionDenied)
+}
+
+/// Breakdown entry saying why an unreadable pattern or directory was left out
+fn denied_entry(fs: &dyn Fs, pattern: &Path) -> BreakdownEntry {
+    let metadata = fs.metadata(pattern).ok().filter(|metadata| metadata.entry_type == EntryType::File);
+    BreakdownEntry {
+        label: pattern.display().to_string(),
+        size_bytes: metadata.map_or(0, |metadata| metadata.len),
+        note: ItemStatus::denied().reason().to_string(),
+    }
+}
+
+/// Status of an item cleaning below `paths`: ready when one of them exists and can be listed
+pub(crate) fn validate_paths(fs: &dyn Fs, paths: &[&Path]) -> ItemStatus {
+    let existing: Vec<&&Path> = paths.iter().filter(|path| fs.metadata(path).is_ok()).collect();
+    if existing.is_empty() {
+        return ItemStatus::PathMissing;
+    }
+
+    // Listing a directory is a cheap probe for read access
+    let denied = existing.iter().all(|path| is_dir_denied(fs, path));
+    if denied {
+        ItemStatus::denied()
+    } else {
+        ItemStatus::Ready
+    }
+}
+
+/// Status of an item searching below `roots`: ready when one of them is a directory
+pub(crate) fn validate_roots(fs: &dyn Fs, roots: &[PathBuf]) -> ItemStatus {
+    if roots.iter().any(|root| is_dir(fs, root)) {
+        ItemStatus::Ready
+    } else {
+        ItemStatus::PathMissing
+    }
+}
+
+/// Availability of a `Files` item: missing when nothing matches, denied when every file (or
+/// every pattern's directory) is unreadable, such as system crash dumps without admin rights
+fn validate_files(fs: &dyn Fs, patterns: &[PathBuf]) -> ItemStatus {
+    let probes: Vec<io::Result<()>> = patterns.iter().filter_map(|pattern| probe_files(fs, pattern)).collect();
+    if probes.is_empty() {
+        ItemStatus::PathMissing
+    } else if probes.iter().all(is_permission_denied) {
+        ItemStatus::denied()
+    } else {
+        ItemStatus::Ready
+    }
+}
+
+/// Files in temporary directories younger than this are kept, as programs may still use them
+pub const TEMP_MIN_AGE_DAYS: u32 = 1;
+
+/// Item emptying the temporary directories, sized per directory when there are several
+pub fn temp_files_item(dirs: Vec<PathBuf>) -> CleanupItem {
+    let list: Vec<String> = dirs.iter().map(|dir| dir.display().to_string()).collect();
+    CleanupItem {
+        id: "temp_files".to_string(),
+        name: "临时文件目录".to_string(),
+        description: format!("系统临时文件目录: {}", list.join(", ")),
+        cleanup_type: CleanupType::Directories(dirs),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        priority: 60,
+        min_age_days: TEMP_MIN_AGE_DAYS,
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+/// Get all available cleanup items for the current platform
+pub fn get_all_cleanup_items() -> Vec<CleanupItem> {
+    let mut items = Vec::new();
 
-        if !path.exists() {
-            return result;
-        }
+    let temp_dir = std::env::temp_dir();
 
-        // Scan first to get the result
-        let scan_result = self.scan_directory(path);
-        result.files = scan_result.files;
-        result.directories = scan_result.directories;
-        result.size_bytes = scan_result.size_bytes;
-        result.has_data = scan_result.has_data;
+    // 1. Temporary files directories
+    #[cfg(windows)]
+    let temp_dirs = system::temp_dirs(
+        &temp_dir,
+        &system::windows_dir(),
+        dirs::home_dir().map(|home| home.join("AppData").join("LocalLow").join("Temp")).as_deref(),
+    );
+    #[cfg(target_os = "macos")]
+    let temp_dirs = vec![temp_dir.clone()];
+    #[cfg(any(windows, target_os = "macos"))]
+    items.push(temp_files_item(temp_dirs));
 
-        if dry_run {
-            return result;
+    // /tmp is shared by every user and session, so only old entries of the current user go
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let tmp = linux::SharedTemp::for_current_user(&temp_dir);
+        items.push(linux::shared_temp_item("temp_files", tmp, linux::TMP_MIN_AGE_DAYS));
+        if temp_dir != Path::new(linux::VAR_TMP) {
+            let var_tmp = linux::SharedTemp::for_current_user(linux::VAR_TMP);
+            items.push(linux::shared_temp_item("var_tmp", var_tmp, linux::VAR_TMP_MIN_AGE_DAYS));
         }
+    }
 
-        // Now actually clean
-        info!("Cleaning {}...", self.name);
-        if let Ok(entries) = fs::read_dir(path) {
-            for entry in entries.flatten() {
-                let entry_path = entry.path();
-                
-                if entry_path.is_file() {
-                    // Silent deletion - no error on failure
-                    let _ = fs::remove_file(&entry_path);
-                } else if entry_path.is_dir() {
-                    // Silent deletion - no error on failure
-                    let _ = fs::remove_dir_all(&entry_path);
-                }
+    // Other users' temp and thumbnail caches, only when running as administrator
+    #[cfg(windows)]
+    if platform::is_elevated() {
+        if let Some(home) = dirs::home_dir() {
+            let users_dir = home.parent().map_or_else(|| PathBuf::from("C:\\Users"), Path::to_path_buf);
+            for (user, profile) in system::other_user_profiles(&users_dir, Some(&home)) {
+                items.extend(system::user_profile_items(&user, &profile));
             }
         }
-
-        result
     }
 
-    fn scan_temp_files(&self, path: &Path) -> CleanupResult {
-        let mut result = CleanupResult::new();
+    // 2. Windows Prefetch (Windows only)
+    #[cfg(windows)]
+    items.push(system::prefetch_item(&system::windows_dir()));
 
-        if !path.exists() {
-            return result;
+    // 3. Browser caches (Chrome, Chromium, Edge, Firefox), covering every profile
+    if let Some(base) = browsers::BaseDirs::current() {
+        let os = browsers::Os::current();
+        for spec in [browsers::CHROME, browsers::CHROMIUM, browsers::EDGE] {
+            if let Some(install) = spec.install(os, &base) {
+                items.push(spec.cache_item(&install));
+            }
         }
+        items.push(browsers::firefox_install(os, &base).cache_item());
+    }
 
-        if let Ok(entries) = fs::read_dir(path) {
-            for entry in entries.flatten() {
-                let entry_path = entry.path();
-                
-                // Look for temp file patterns
-                let file_name = entry.file_name();
-                let name_str = file_name.to_string_lossy();
-                
-                let is_temp = name_str.contains(".tmp") 
-                    || name_str.contains(".temp")
-                    || name_str.starts_with("~")
-                    || name_str.ends_with("~")
-                    || name_str.contains("temp")
-                    || name_str.contains("cache");
-                
-                if is_temp && entry_path.is_file() {
-                    if let Ok(metadata) = fs::metadata(&entry_path) {
-                        result.files += 1;
-                        result.size_bytes += metadata.len();
-                        result.has_data = true;
-                    }
-                }
-                
-                if entry_path.is_dir() {
-                    let subdir_result = self.scan_temp_files(&entry_path);
-                    result.files += subdir_result.files;
-                    result.directories += subdir_result.directories;
-                    result.size_bytes += subdir_result.size_bytes;
-                    result.has_data = result.has_data || subdir_result.has_data;
-                }
+    // Browser history and cookies (SQLite), opt-in only
+    if let Some(base) = browsers::BaseDirs::current() {
+        let os = browsers::Os::current();
+        for spec in [browsers::CHROME, browsers::EDGE] {
+            if let Some(install) = spec.install(os, &base) {
+                items.push(spec.history_item(&install));
             }
         }
+        items.push(browsers::firefox_install(os, &base).history_item());
+    }
 
-        debug!("Scanned temp files in {}: {} files, {:.2} MB", 
-               self.name, result.files, result.size_mb());
-        result
+    // Safari, macOS only
+    #[cfg(target_os = "macos")]
+    if let Some(home) = dirs::home_dir() {
+        items.push(browsers::SafariCache::new(&home).cache_item());
     }
 
-    fn clean_temp_files(&self, path: &Path, dry_run: bool) -> CleanupResult {
-        let mut result = CleanupResult::new();
+    // App caches and logs below ~/Library (macOS)
+    #[cfg(target_os = "macos")]
+    if let Some(home) = dirs::home_dir() {
+        items.push(macos::user_caches_item(&home));
+        items.push(macos::logs_item(&home, Path::new(macos::SYSTEM_DIAGNOSTIC_REPORTS)));
+    }
 
-        if !path.exists() {
-            return result;
-        }
+    // Xcode build products, device symbols and simulators (macOS)
+    #[cfg(target_os = "macos")]
+    if let Some(home) = dirs::home_dir() {
+        items.extend(macos::xcode_items(&home, devtools::find_program("xcrun")));
+    }
 
-        // Scan first
-        let scan_result = self.scan_temp_files(path);
-        result.files = scan_result.files;
-        result.directories = scan_result.directories;
-        result.size_bytes = scan_result.size_bytes;
-        result.has_data = scan_result.has_data;
+    // 4. VS Code caches (plus Insiders and VSCodium when installed)
+    if let Some(base) = browsers::BaseDirs::current() {
+        items.extend(apps::detect_vscode(browsers::Os::current(), &base));
+    }
 
-        if dry_run {
-            return result;
-        }
+    // JetBrains IDE caches, superseded versions first of all
+    if let Some(base) = browsers::BaseDirs::current() {
+        let root = apps::jetbrains_cache_root(browsers::Os::current(), &base);
+        items.push(apps::JetBrainsCaches::new(&root).cache_item());
+    }
 
-        // Now clean
-        info!("Cleaning temp files in {}...", self.name);
-        if let Ok(entries) = fs::read_dir(path) {
-            for entry in entries.flatten() {
-                let entry_path = entry.path();
-                
-                let file_name = entry.file_name();
-                let name_str = file_name.to_string_lossy();
-                
-                let is_temp = name_str.contains(".tmp") 
-                    || name_str.contains(".temp")
-                    || name_str.starts_with("~")
-                    || name_str.ends_with("~")
-                    || name_str.contains("temp")
-                    || name_str.contains("cache");
-                
-                if is_temp && entry_path.is_file() {
-                    let _ = fs::remove_file(&entry_path);
-                }
-                
-                if entry_path.is_dir() {
-                    let _ = self.clean_temp_files(&entry_path, false);
-                }
-            }
-        }
+    // 5. Package manager caches (cargo for Rust, one item per cache directory)
+    if let Some(home) = dirs::home_dir() {
+        items.extend(devtools::cargo_items(&devtools::current_cargo_home(&home)));
+    }
 
-        result
+    // Build output of idle Rust projects
+    if let Some(home) = dirs::home_dir() {
+        items.push(devtools::rust_targets_item(&home));
     }
-}
 
-/// Get all available cleanup items for the current platform
-pub fn get_all_cleanup_items() -> Vec<CleanupItem> {
-    let mut items = Vec::new();
+    // node_modules of idle JavaScript projects
+    if let Some(home) = dirs::home_dir() {
+        items.push(devtools::node_modules_item(&home));
+    }
 
-    let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("/"));
-    let temp_dir = std::env::temp_dir();
-    let appdata_local = dirs::data_local_dir();
-    let appdata_roaming = dirs::config_dir();
+    // npm cache, wherever .npmrc or npm itself puts it
+    if let Some(base) = browsers::BaseDirs::current() {
+        let npmrc = devtools::user_npmrc(&base.home);
+        let cache = devtools::resolve_npm_cache(browsers::Os::current(), &base, &npmrc, devtools::npm_config_get_cache);
+        items.push(devtools::npm_cache_item(&cache));
+    }
 
-    // 1. Temporary files directory
-    items.push(CleanupItem {
-        id: "temp_files".to_string(),
-        name: "临时文件目录".to_string(),
-        description: format!("系统临时文件目录: {}", temp_dir.display()),
-        cleanup_type: CleanupType::Directory(temp_dir.clone()),
-        enabled: true,
-    });
+    // yarn and pnpm stores that exist
+    if let Some(base) = browsers::BaseDirs::current() {
+        items.extend(devtools::node_store_items(browsers::Os::current(), &base, devtools::find_program("pnpm")));
+    }
 
-    // 2. Windows Prefetch (Windows only)
-    #[cfg(windows)]
-    {
-        let prefetch_dir = PathBuf::from("C:\\Windows\\Prefetch");
-        items.push(CleanupItem {
-            id: "prefetch".to_string(),
-            name: "Windows Prefetch".to_string(),
-            description: "Windows 预读文件缓存".to_string(),
-            cleanup_type: CleanupType::Directory(prefetch_dir),
-            enabled: true,
-        });
+    // pip download and wheel caches
+    if let Some(base) = browsers::BaseDirs::current() {
+        let os = browsers::Os::current();
+        items.push(devtools::pip_cache_item(&devtools::current_pip_cache_dir(os, &base)));
     }
 
-    // 3. Browser cache (Chrome)
-    #[cfg(target_os = "windows")]
-    if let Some(appdata) = dirs::data_local_dir() {
-        let chrome_cache = appdata.join("Google\\Chrome\\User Data\\Default\\Cache");
-        items.push(CleanupItem {
-            id: "chrome_cache".to_string(),
-            name: "Chrome 缓存".to_string(),
-            description: "Chrome 浏览器缓存文件".to_string(),
-            cleanup_type: CleanupType::Directory(chrome_cache),
-            enabled: false,
-        });
+    // Composer caches in any of their historical locations
+    if let Some(base) = browsers::BaseDirs::current() {
+        let os = browsers::Os::current();
+        items.push(devtools::composer_cache_item(&devtools::current_composer_caches(os, &base)));
     }
 
-    // 4. VS Code cache
-    if let Some(appdata) = dirs::cache_dir() {
-        let vscode_cache = appdata.join("Code");
-        items.push(CleanupItem {
-            id: "vscode_cache".to_string(),
-            name: "VS Code 缓存".to_string(),
-            description: "Visual Studio Code 缓存文件".to_string(),
-            cleanup_type: CleanupType::Directory(vscode_cache),
-            enabled: false,
-        });
+    // Homebrew downloads and, with brew installed, old kegs (macOS and Linux)
+    #[cfg(unix)]
+    if let Some(base) = browsers::BaseDirs::current() {
+        let cache_dir = devtools::current_homebrew_cache_dir(&base);
+        items.extend(devtools::homebrew_items(&cache_dir, devtools::find_program("brew")));
     }
 
-    // 5. Package manager cache (cargo for Rust)
-    #[cfg(target_os = "windows")]
+    // Go module download and build caches
+    if let Some(base) = browsers::BaseDirs::current() {
+        items.push(devtools::go_cache_item(&devtools::current_go_env(browsers::Os::current(), &base)));
+    }
+
+    // Gradle caches and superseded Maven snapshots
     if let Some(home) = dirs::home_dir() {
-        let cargo_cache = home.join(".cargo\\registry\\cache");
-        items.push(CleanupItem {
-            id: "cargo_cache".to_string(),
-            name: "Cargo 缓存".to_string(),
-            description: "Rust Cargo 包管理器缓存".to_string(),
-            cleanup_type: CleanupType::Directory(cargo_cache),
-            enabled: false,
-        });
+        items.push(devtools::gradle_cache_item(&devtools::current_gradle_user_home(&home)));
+        items.push(devtools::maven_repo_item(&devtools::maven_repository(&home)));
     }
 
-    #[cfg(target_os = "windows")]
+    // Python bytecode in project directories
     if let Some(home) = dirs::home_dir() {
-        let npm_cache = home.join("AppData\\Roaming\\npm-cache");
-        items.push(CleanupItem {
-            id: "npm_cache".to_string(),
-            name: "NPM 缓存".to_string(),
-            description: "Node.js NPM 包管理器缓存".to_string(),
-            cleanup_type: CleanupType::Directory(npm_cache),
-            enabled: false,
-        });
+        items.push(devtools::python_bytecode_item(&home));
+    }
+
+    // Conda packages no environment uses
+    if let Some(home) = dirs::home_dir() {
+        items.push(devtools::conda_cache_item(&devtools::current_conda(&home)));
+    }
+
+    // NuGet caches, at the locations dotnet reports when it is installed
+    if let Some(base) = browsers::BaseDirs::current() {
+        items.extend(devtools::nuget_items(&devtools::current_nuget_locals(browsers::Os::current(), &base)));
+    }
+
+    // Docker images, containers, build cache and volumes, through the docker CLI
+    items.extend(docker::docker_items(devtools::find_program("docker")));
+
+    // Flatpak app caches and, when flatpak is installed, unused runtimes
+    #[cfg(target_os = "linux")]
+    if let Some(home) = dirs::home_dir() {
+        items.push(linux::flatpak_cache_item(&home));
     }
+    #[cfg(target_os = "linux")]
+    if let Some(flatpak) = devtools::find_program("flatpak") {
+        items.push(linux::FlatpakRuntimes::new(flatpak).item());
+    }
+
+    // Disabled snap revisions and snapd's download cache
+    #[cfg(target_os = "linux")]
+    items.push(linux::snap_item(devtools::find_program("snap"), Path::new(linux::SNAPD_DIR)));
 
     // 6. Log files in temp directories
     items.push(CleanupItem {
@@ -369,33 +2830,98 @@ pub fn get_all_cleanup_items() -> Vec<CleanupItem> {
         name: "日志文件".to_string(),
         description: "临时目录中的日志文件".to_string(),
         cleanup_type: CleanupType::TempFiles(temp_dir.clone()),
+        category: Category::Logs,
+        risk: RiskLevel::Safe,
         enabled: true,
+        ..Default::default()
     });
 
-    // 7. Thumbnail cache (Windows)
+    // 7. Thumbnail and icon caches (Windows)
     #[cfg(windows)]
     if let Some(appdata) = dirs::data_local_dir() {
-        let thumbnail_cache = appdata.join("Microsoft\\Windows\\Explorer");
-        items.push(CleanupItem {
-            id: "thumbnail_cache".to_string(),
-            name: "缩略图缓存".to_string(),
-            description: "Windows 文件缩略图缓存".to_string(),
-            cleanup_type: CleanupType::Directory(thumbnail_cache),
-            enabled: false,
-        });
+        items.push(system::thumbnail_cache_item(&appdata));
+        items.push(system::icon_cache_item(&appdata));
+    }
+
+    // Freedesktop thumbnails (Linux and other Unix desktops)
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if let (Some(cache), Some(home)) = (dirs::cache_dir(), dirs::home_dir()) {
+        items.push(system::thumbnails_item(&cache, &home));
+    }
+
+    // Recently opened files recorded by GTK applications
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if let (Some(data), Some(cache)) = (dirs::data_dir(), dirs::cache_dir()) {
+        items.push(system::RecentlyUsed::new(system::recently_used_files(&data, &cache)).item());
+    }
+
+    // .DS_Store, Thumbs.db and desktop.ini scattered through the home directory
+    if let Some(home) = dirs::home_dir() {
+        items.push(system::desktop_metadata_item(&home));
+    }
+
+    // Empty directory trees in the temp and cache directories
+    items.push(system::empty_dirs_item(
+        std::iter::once(temp_dir.clone()).chain(dirs::cache_dir()).collect(),
+    ));
+
+    // Identical large files in the home directory, cleaned only for the groups the user selects
+    if let Some(home) = dirs::home_dir() {
+        items.push(duplicates::duplicates_item(&home));
+    }
+
+    // Dangling symlinks left by uninstalled programs
+    #[cfg(unix)]
+    if let Some(home) = dirs::home_dir() {
+        items.push(symlinks::broken_symlinks_item(vec![
+            home.join(".local/bin"),
+            home.join(".config"),
+            temp_dir.clone(),
+        ]));
+    }
+
+    // Report of the largest files in the home directory; never cleaned
+    if let Some(home) = dirs::home_dir() {
+        items.push(large_files::large_files_item(&home));
     }
 
+    // Old files in the Downloads folder, moved to the trash
+    if let Some(downloads) = dirs::download_dir() {
+        items.push(system::downloads_item(&downloads));
+    }
+
+    // Downloaded packages of apt (Debian, Ubuntu)
+    #[cfg(target_os = "linux")]
+    if Path::new(linux::APT_ARCHIVES).is_dir() {
+        items.push(linux::apt_cache_item(
+            linux::AptArchives::new(linux::APT_ARCHIVES, linux::DPKG_STATUS),
+            linux::APT_LOCKS,
+        ));
+    }
+
+    // Downloaded packages and metadata of dnf / yum (Fedora, RHEL)
+    #[cfg(target_os = "linux")]
+    if let Some(root) = linux::rpm_cache_root(linux::RPM_CACHE_ROOTS) {
+        items.push(linux::dnf_cache_item(&root, linux::RPM_LOCKS));
+    }
+
+    // Old package versions in pacman's cache (Arch Linux)
+    #[cfg(target_os = "linux")]
+    if Path::new(linux::PACMAN_CACHE).is_dir() {
+        items.push(linux::pacman_cache_item(
+            linux::PacmanCache::new(linux::PACMAN_CACHE, linux::PACMAN_LOCAL_DB),
+            linux::PACMAN_LOCK,
+        ));
+    }
+
+    // systemd journal, vacuumed through journalctl
+    #[cfg(target_os = "linux")]
+    items.push(linux::journal_item(linux::Journal::new("journalctl")));
+
     // 8. Recent documents (Windows)
     #[cfg(windows)]
-    if let Some(appdata) = dirs::data_local_dir() {
-        let recent_docs = appdata.join("Microsoft\\Windows\\Recent");
-        items.push(CleanupItem {
-            id: "recent_docs".to_string(),
-            name: "最近文档".to_string(),
-            description: "Windows 最近访问的文档列表".to_string(),
-            cleanup_type: CleanupType::Directory(recent_docs),
-            enabled: false,
-        });
+    if let Some(roaming) = dirs::data_dir() {
+        items.push(system::recent_docs_item(&roaming));
     }
 
     // 9. Windows Update Cache (Windows)
@@ -407,37 +2933,52 @@ pub fn get_all_cleanup_items() -> Vec<CleanupItem> {
             name: "Windows 更新缓存".to_string(),
             description: "Windows 更新下载的临时文件".to_string(),
             cleanup_type: CleanupType::Directory(windows_update_cache),
+            category: Category::System,
+            risk: RiskLevel::Moderate,
             enabled: false,
+            requires_admin: true,
+            ..Default::default()
         });
     }
 
-    // 10. Windows Error Reporting (Windows)
+    // 10. Windows Error Reporting, per user and machine-wide (Windows)
     #[cfg(windows)]
     if let Some(appdata) = dirs::data_local_dir() {
-        let wer_cache = appdata.join("Microsoft\\Windows\\WER");
-        items.push(CleanupItem {
-            id: "windows_error_reporting".to_string(),
-            name: "Windows 错误报告".to_string(),
-            description: "Windows 错误报告和诊断文件".to_string(),
-            cleanup_type: CleanupType::Directory(wer_cache),
-            enabled: false,
-        });
+        items.push(system::wer_item(&appdata, &system::program_data()));
     }
 
-    // 11. Microsoft Edge Cache (Windows)
-    #[cfg(target_os = "windows")]
+    // Blue screen memory dumps (Windows)
+    #[cfg(windows)]
+    items.push(system::memory_dump_item(&system::windows_dir()));
+
+    // Font cache (Windows)
+    #[cfg(windows)]
     if let Some(appdata) = dirs::data_local_dir() {
-        let edge_cache = appdata.join("Microsoft\\Edge\\User Data\\Default\\Cache");
-        items.push(CleanupItem {
-            id: "edge_cache".to_string(),
-            name: "Microsoft Edge 缓存".to_string(),
-            description: "Edge 浏览器缓存文件".to_string(),
-            cleanup_type: CleanupType::Directory(edge_cache),
-            enabled: false,
-        });
+        items.push(system::font_cache_item(&system::windows_dir(), &appdata));
+    }
+
+    // WinINET cache and cookies (Windows)
+    #[cfg(windows)]
+    if let (Some(local), Some(roaming)) = (dirs::data_local_dir(), dirs::data_dir()) {
+        items.push(system::inet_cache_item(&local));
+        items.push(system::inet_cookies_item(&local, &roaming));
+    }
+
+    // DirectX and GPU driver shader caches (Windows)
+    #[cfg(windows)]
+    if let Some(appdata) = dirs::data_local_dir() {
+        items.push(system::shader_cache_item(&appdata));
     }
 
-    // 12. Windows Search Index (Windows)
+    // Servicing and setup logs (Windows)
+    #[cfg(windows)]
+    items.push(system::setup_logs_item(&system::windows_dir()));
+
+    // IIS site logs (Windows servers)
+    #[cfg(windows)]
+    items.push(system::iis_logs_item(&system::iis_log_root()));
+
+    // 11. Windows Search Index (Windows)
     #[cfg(windows)]
     {
         let search_index = PathBuf::from("C:\\ProgramData\\Microsoft\\Windows Search");
@@ -446,11 +2987,15 @@ pub fn get_all_cleanup_items() -> Vec<CleanupItem> {
             name: "Windows 搜索索引".to_string(),
             description: "Windows 搜索索引缓存".to_string(),
             cleanup_type: CleanupType::Directory(search_index),
+            category: Category::System,
+            risk: RiskLevel::Aggressive,
+            priority: 90,
             enabled: false,
+            ..Default::default()
         });
     }
 
-    // 13. Download Folder (Optional - user must be careful)
+    // 12. Download Folder (Optional - user must be careful)
     #[cfg(any(target_os = "windows", target_os = "macos"))]
     if let Some(download_dir) = dirs::download_dir() {
         items.push(CleanupItem {
@@ -458,24 +3003,19 @@ pub fn get_all_cleanup_items() -> Vec<CleanupItem> {
             name: "下载文件夹 (谨慎使用)".to_string(),
             description: format!("下载文件夹: {}", download_dir.display()),
             cleanup_type: CleanupType::Directory(download_dir),
+            category: Category::Applications,
+            risk: RiskLevel::Aggressive,
+            priority: 90,
             enabled: false,
+            ..Default::default()
         });
     }
 
-    // 14. Windows Delivery Optimization (Windows 10/11)
+    // 13. Windows Delivery Optimization (Windows 10/11)
     #[cfg(windows)]
-    if let Some(appdata) = dirs::data_local_dir() {
-        let delivery_opt = appdata.join("Microsoft\\Windows\\DeliveryOptimization");
-        items.push(CleanupItem {
-            id: "delivery_optimization".to_string(),
-            name: "Windows 传递优化缓存".to_string(),
-            description: "Windows 更新传递优化文件".to_string(),
-            cleanup_type: CleanupType::Directory(delivery_opt),
-            enabled: false,
-        });
-    }
+    items.push(system::delivery_optimization_item(&system::current_delivery_optimization_cache()));
 
-    // 15. Adobe PDF Cache (Common)
+    // 14. Adobe PDF Cache (Common)
     #[cfg(target_os = "windows")]
     if let Some(appdata_local_dir) = dirs::data_local_dir() {
         let adobe_cache = appdata_local_dir.join("Adobe\\Acrobat\\DC");
@@ -484,22 +3024,328 @@ pub fn get_all_cleanup_items() -> Vec<CleanupItem> {
             name: "Adobe PDF 缓存".to_string(),
             description: "Adobe Acrobat 临时文件".to_string(),
             cleanup_type: CleanupType::Directory(adobe_cache),
+            category: Category::Applications,
+            risk: RiskLevel::Moderate,
+            priority: 20,
             enabled: false,
+            ..Default::default()
         });
     }
 
-    // 16. Windows Recycle Bin
+    // 15. Recycle Bin (Windows), Trash (macOS) or FreeDesktop trash
+    items.push(system::RecycleBin::new().item());
+
+    // DNS resolver cache, where one is running
+    items.push(system::DnsCache::new().item());
+
+    // Other Chromium-based browsers are only listed when installed
+    if let Some(base) = browsers::BaseDirs::current() {
+        items.extend(browsers::detect_chromium_browsers(browsers::Os::current(), &base));
+    }
+
+    // Spotify streaming cache
+    if let Some(base) = browsers::BaseDirs::current() {
+        items.push(apps::spotify_cache_item(browsers::Os::current(), &base));
+    }
+
+    // Telegram Desktop media cache
+    if let Some(base) = browsers::BaseDirs::current() {
+        items.push(apps::telegram_cache_item(browsers::Os::current(), &base));
+    }
+
+    // Microsoft Store cache (Windows)
     #[cfg(windows)]
-    {
-        // This is a placeholder - actual recycle bin cleaning needs special handling
-        items.push(CleanupItem {
-            id: "recycle_bin".to_string(),
-            name: "回收站".to_string(),
-            description: "Windows 回收站 (需要管理员权限)".to_string(),
-            cleanup_type: CleanupType::Directory(PathBuf::from("C:\\$Recycle.Bin")),
-            enabled: false,
-        });
+    if let Some(base) = browsers::BaseDirs::current() {
+        items.push(apps::store_cache_item(&base));
+    }
+
+    // Electron apps (Discord, Slack, Teams, ...) are only listed when installed
+    if let Some(base) = browsers::BaseDirs::current() {
+        let extra = state::default_state_path()
+            .and_then(|path| state::load_electron_apps(&path).ok())
+            .unwrap_or_default();
+        items.extend(apps::detect_electron_apps(browsers::Os::current(), &base, &extra));
+    }
+
+    for item in &mut items {
+        // Aggressive items must always be opted into explicitly
+        if item.risk == RiskLevel::Aggressive {
+            item.enabled = false;
+        }
+
+        item.status = item.validate();
+        if !item.status.is_ready() {
+            debug!("Disabling {}: {:?}", item.id, item.status);
+            item.enabled = false;
+        } else if item.status == ItemStatus::MountRoot {
+            // Emptying a whole drive is never preselected
+            item.enabled = false;
+        } else if item.requires_admin && !platform::is_elevated() {
+            item.status = ItemStatus::NeedsElevation;
+        }
     }
 
     items
 }
+
+/// Indices of `items` in processing order: by priority, then name, then id.
+///
+/// The full key keeps the order identical between runs even when priorities tie.
+pub fn priority_order(items: &[CleanupItem]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&items[a], &items[b]);
+        (a.effective_priority(), &a.name, &a.id).cmp(&(b.effective_priority(), &b.name, &b.id))
+    });
+    order
+}
+
+/// Get the cleanup items whose risk does not exceed `max_risk`
+pub fn get_cleanup_items_up_to(max_risk: RiskLevel) -> Vec<CleanupItem> {
+    get_all_cleanup_items()
+        .into_iter()
+        .filter(|item| item.risk <= max_risk)
+        .collect()
+}
+
+/// Scan all enabled items concurrently, running at most `max_parallel` scans at once.
+///
+/// `on_result` is invoked on the calling thread with the item's index as soon as
+/// each scan finishes, so results can be stored in completion order.
+pub fn scan_enabled_parallel<F>(items: &[CleanupItem], max_parallel: usize, on_result: F)
+where
+    F: FnMut(usize, CleanupResult),
+{
+    scan_enabled_parallel_with(items, max_parallel, &CleanOptions::default(), on_result);
+}
+
+/// Like [`scan_enabled_parallel`], also reporting to `options.observers` and ending with `on_run_done`
+pub fn scan_enabled_parallel_with<F>(items: &[CleanupItem], max_parallel: usize, options: &CleanOptions, on_result: F)
+where
+    F: FnMut(usize, CleanupResult),
+{
+    let _background = options.enter_background();
+    let enabled: Vec<usize> = priority_order(items).into_iter().filter(|&i| items[i].enabled).collect();
+    run_parallel(items, &enabled, max_parallel, |item| item.scan_with(options), on_result);
+    options.run_done();
+}
+
+/// Whether any enabled item in `items` wants a restore point before it is cleaned
+pub fn needs_restore_point(items: &[CleanupItem], options: &CleanOptions) -> bool {
+    items.iter().any(|item| item.enabled && item.wants_restore_point(options))
+}
+
+/// Clean enabled items one after another in priority order, ending with `on_run_done`.
+///
+/// `on_result` receives each item's index and result as soon as it is cleaned. The returned
+/// report holds every item's outcome, in the same order.
+pub fn clean_enabled<F>(items: &[CleanupItem], options: &CleanOptions, mut on_result: F) -> Report
+where
+    F: FnMut(usize, CleanupResult),
+{
+    run_enabled(items, options, |index, item| {
+        let result = item.clean_with(options);
+        on_result(index, result.clone());
+        item.outcome(result, options)
+    })
+}
+
+/// Run `op` on the enabled items one after another in priority order, ending with
+/// `on_run_done`, and collect what it returns
+pub(crate) fn run_enabled(
+    items: &[CleanupItem],
+    options: &CleanOptions,
+    mut op: impl FnMut(usize, &CleanupItem) -> Result<CleanupResult, CleanError>,
+) -> Report {
+    let started = Instant::now();
+    let _background = options.enter_background();
+    let mut report = Report::default();
+    for index in priority_order(items).into_iter().filter(|&i| items[i].enabled) {
+        let item = &items[index];
+        report.push(item.id.clone(), op(index, item));
+    }
+    options.run_done();
+    report.duration = started.elapsed();
+    report
+}
+
+/// Outcome of an operation on several items: each item's result, or the error that kept the
+/// item from being cleaned at all, by item id
+#[derive(Debug, Default)]
+pub struct Report {
+    pub items: Vec<(String, Result<CleanupResult, CleanError>)>,
+    /// Time the whole operation took; zero for a report assembled from earlier results
+    pub duration: Duration,
+}
+
+impl Report {
+    pub fn push(&mut self, id: String, outcome: Result<CleanupResult, CleanError>) {
+        self.items.push((id, outcome));
+    }
+
+    /// Results that count towards the totals: not skipped, and not reports of files that are
+    /// never deleted
+    fn counted(&self) -> impl Iterator<Item = &CleanupResult> {
+        self.results().map(|(_, result)| result).filter(|result| result.skip_reason.is_none() && !result.report_only)
+    }
+
+    /// Bytes freed, or found by a scan, over every item
+    pub fn total_bytes(&self) -> u64 {
+        self.counted().map(|result| result.size_bytes).sum()
+    }
+
+    pub fn total_files(&self) -> u64 {
+        self.counted().map(|result| result.files).sum()
+    }
+
+    /// Entries that could not be deleted, over every item
+    pub fn errors(&self) -> impl Iterator<Item = &CleanErrorEntry> {
+        self.results().flat_map(|(_, result)| &result.errors)
+    }
+
+    /// Items that failed, with their errors
+    pub fn failures(&self) -> impl Iterator<Item = (&str, &CleanError)> {
+        self.items.iter().filter_map(|(id, outcome)| Some((id.as_str(), outcome.as_ref().err()?)))
+    }
+
+    pub fn failed(&self) -> usize {
+        self.failures().count()
+    }
+
+    /// Results of the items that did not fail
+    pub fn results(&self) -> impl Iterator<Item = (&str, &CleanupResult)> {
+        self.items.iter().filter_map(|(id, outcome)| Some((id.as_str(), outcome.as_ref().ok()?)))
+    }
+
+    /// Every item's result, or `CleanError::Multiple` with the errors of those that failed
+    pub fn into_result(self) -> crate::error::Result<Vec<(String, CleanupResult)>> {
+        let mut results = Vec::new();
+        let mut failures = Vec::new();
+        for (id, outcome) in self.items {
+            match outcome {
+                Ok(result) => results.push((id, result)),
+                Err(e) => failures.push((id, e)),
+            }
+        }
+        if failures.is_empty() {
+            Ok(results)
+        } else {
+            Err(CleanError::Multiple(failures))
+        }
+    }
+}
+
+impl FromIterator<(String, Result<CleanupResult, CleanError>)> for Report {
+    fn from_iter<I: IntoIterator<Item = (String, Result<CleanupResult, CleanError>)>>(iter: I) -> Self {
+        Report {
+            items: iter.into_iter().collect(),
+            duration: Duration::ZERO,
+        }
+    }
+}
+
+/// One item of a serialized `Report`: its result, or the error that stopped it
+#[derive(Serialize)]
+struct ItemOutcome<'a> {
+    id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<&'a CleanupResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a CleanError>,
+}
+
+impl Serialize for Report {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let items: Vec<ItemOutcome> = self
+            .items
+            .iter()
+            .map(|(id, outcome)| ItemOutcome { id, result: outcome.as_ref().ok(), error: outcome.as_ref().err() })
+            .collect();
+        let mut report = serializer.serialize_struct("Report", 6)?;
+        report.serialize_field("items", &items)?;
+        report.serialize_field("total_files", &self.total_files())?;
+        report.serialize_field("total_bytes", &self.total_bytes())?;
+        report.serialize_field("failed_items", &self.failed())?;
+        report.serialize_field("failed_entries", &self.errors().count())?;
+        report.serialize_field("duration_ms", &(self.duration.as_millis() as u64))?;
+        report.end()
+    }
+}
+
+/// "2 of 5 items failed", followed by one indented line per failure
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} items failed{}",
+            self.failed(),
+            self.items.len(),
+            error::indented_failures(self.failures())
+        )
+    }
+}
+
+/// Estimate every available item concurrently, selected or not.
+///
+/// Used to show which items have data before the user starts a full scan.
+pub fn estimate_ready_parallel<F>(items: &[CleanupItem], max_parallel: usize, on_result: F)
+where
+    F: FnMut(usize, CleanupResult),
+{
+    let ready: Vec<usize> = (0..items.len()).filter(|&i| items[i].status.is_ready()).collect();
+    run_parallel(items, &ready, max_parallel, CleanupItem::estimate, on_result);
+}
+
+/// Run `op` on the items at `indices` with at most `max_parallel` worker threads
+fn run_parallel<F>(
+    items: &[CleanupItem],
+    indices: &[usize],
+    max_parallel: usize,
+    op: impl Fn(&CleanupItem) -> CleanupResult + Sync,
+    mut on_result: F,
+) where
+    F: FnMut(usize, CleanupResult),
+{
+    let workers = max_parallel.max(1).min(indices.len());
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let tx = tx.clone();
+            let (next, op) = (&next, &op);
+            scope.spawn(move || {
+                while let Some(&index) = indices.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    if tx.send((index, op(&items[index]))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        for (index, result) in rx {
+            debug!("Finished item {}: {:?}", index, result);
+            on_result(index, result);
+        }
+    });
+}
+
+/// Group items by category, preserving their relative order within each group
+pub fn items_by_category(items: &[CleanupItem]) -> BTreeMap<Category, Vec<&CleanupItem>> {
+    let mut groups: BTreeMap<Category, Vec<&CleanupItem>> = BTreeMap::new();
+    for item in items {
+        groups.entry(item.category).or_default().push(item);
+    }
+    groups
+}
+
+/// Enable or disable every item in a category. Unavailable items stay disabled.
+pub fn toggle_category(items: &mut [CleanupItem], category: Category, enabled: bool) {
+    for item in items.iter_mut().filter(|item| item.category == category) {
+        item.enabled = enabled && item.status.is_ready();
+    }
+    debug!("Set category {} enabled={}", category.as_str(), enabled);
+}
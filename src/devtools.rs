@@ -0,0 +1,1405 @@
+//! Caches of development tools and package managers
+
+use crate::browsers::{join_relative, BaseDirs, Os};
+use crate::cleanup_items::{
+    AppCheck, Category, CleanErrorEntry, CleanTarget, CleanupItem, CleanupResult, CleanupType, ItemStatus, RiskLevel,
+};
+use crate::free_space;
+use std::collections::HashSet;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::io;
+use std::process::Command;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tracing::{debug, info};
+
+/// Cargo's home directory: `CARGO_HOME` if set, else `~/.cargo`
+pub fn cargo_home(cargo_home_var: Option<PathBuf>, home: &Path) -> PathBuf {
+    cargo_home_var
+        .filter(|path| !path.as_os_str().is_empty())
+        .unwrap_or_else(|| home.join(".cargo"))
+}
+
+/// Cargo's home directory for the current user
+pub fn current_cargo_home(home: &Path) -> PathBuf {
+    cargo_home(env::var_os("CARGO_HOME").map(PathBuf::from), home)
+}
+
+/// Cache directories below `CARGO_HOME`: (id, name, description, relative path).
+///
+/// `bin/` and `config.toml` are never part of it.
+const CARGO_SUBTARGETS: &[(&str, &str, &str, &[&str])] = &[
+    ("cargo_cache", "Cargo 下载缓存", "下载的 .crate 压缩包", &["registry", "cache"]),
+    ("cargo_registry_src", "Cargo 解压源码", "从 .crate 解压的源码, 需要时会重新解压", &["registry", "src"]),
+    ("cargo_git_db", "Cargo Git 仓库", "git 依赖的裸仓库", &["git", "db"]),
+    ("cargo_git_checkouts", "Cargo Git 检出", "git 依赖的检出副本", &["git", "checkouts"]),
+];
+
+/// One item per Cargo cache directory so each can be selected and sized on its own.
+/// Disabled by default.
+pub fn cargo_items(cargo_home: &Path) -> Vec<CleanupItem> {
+    CARGO_SUBTARGETS
+        .iter()
+        .map(|(id, name, description, relative)| {
+            let path = relative.iter().fold(cargo_home.to_path_buf(), |path, part| path.join(part));
+            CleanupItem {
+                id: id.to_string(),
+                name: name.to_string(),
+                description: format!("Rust Cargo {} ({})", description, path.display()),
+                cleanup_type: CleanupType::Directory(path),
+                category: Category::DevTools,
+                risk: RiskLevel::Safe,
+                priority: 20,
+                enabled: false,
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Project roots searched by default, relative to the home directory
+pub const DEFAULT_PROJECT_ROOTS: &[&str] = &["projects", "src"];
+
+/// Projects modified within this many days keep their `target/` by default
+pub const DEFAULT_PROJECT_IDLE_DAYS: u32 = 30;
+
+/// Directory levels below a project root that are searched for `Cargo.toml`
+const PROJECT_SEARCH_DEPTH: usize = 4;
+
+/// A Cargo project with a build directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RustProject {
+    pub root: PathBuf,
+    /// Newest modification time of anything in the project outside `target/`
+    pub last_modified: SystemTime,
+}
+
+impl RustProject {
+    pub fn target_dir(&self) -> PathBuf {
+        self.root.join("target")
+    }
+
+    /// Whole days since the project was last modified
+    pub fn idle_days(&self) -> u32 {
+        days_since(self.last_modified)
+    }
+}
+
+/// Directories below `roots` that contain both a `Cargo.toml` and a `target/` directory.
+///
+/// Hidden directories and build output are not searched.
+pub fn find_rust_projects(roots: &[PathBuf]) -> Vec<RustProject> {
+    find_projects(roots, "Cargo.toml", "target")
+        .into_iter()
+        .map(|(root, last_modified)| RustProject { root, last_modified })
+        .collect()
+}
+
+/// Directories below `roots` containing `manifest` and a `build_dir` directory, with the
+/// newest modification time of their sources
+fn find_projects(roots: &[PathBuf], manifest: &str, build_dir: &str) -> Vec<(PathBuf, SystemTime)> {
+    let mut projects = Vec::new();
+    let mut pending: Vec<(PathBuf, usize)> = roots.iter().map(|root| (root.clone(), 0)).collect();
+    while let Some((dir, depth)) = pending.pop() {
+        if dir.join(manifest).is_file() && dir.join(build_dir).is_dir() {
+            projects.push((dir.clone(), newest_modification(&dir)));
+        }
+        if depth >= PROJECT_SEARCH_DEPTH {
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) && !is_skipped_dir(&entry.file_name()) {
+                pending.push((entry.path(), depth + 1));
+            }
+        }
+    }
+    projects.sort_by(|a, b| a.0.cmp(&b.0));
+    projects.dedup_by(|a, b| a.0 == b.0);
+    projects
+}
+
+/// Whole days between `time` and now; 0 for times in the future
+pub(crate) fn days_since(time: SystemTime) -> u32 {
+    let elapsed = time.elapsed().unwrap_or(Duration::ZERO);
+    (elapsed.as_secs() / (24 * 60 * 60)) as u32
+}
+
+/// Newest access or modification time of anything below `dir`
+pub fn last_used(dir: &Path) -> SystemTime {
+    let mut newest = SystemTime::UNIX_EPOCH;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                for time in [metadata.modified(), metadata.accessed()].into_iter().flatten() {
+                    newest = newest.max(time);
+                }
+            }
+        }
+    }
+    newest
+}
+
+/// Directories that never contain projects or project sources worth dating
+fn is_skipped_dir(name: &std::ffi::OsStr) -> bool {
+    let name = name.to_string_lossy();
+    name.starts_with('.') || name == "target" || name == "node_modules"
+}
+
+/// Newest modification time of `dir` and everything below it, skipping build output
+fn newest_modification(dir: &Path) -> SystemTime {
+    let mut newest = SystemTime::UNIX_EPOCH;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                if !is_skipped_dir(&entry.file_name()) {
+                    pending.push(entry.path());
+                }
+            } else if let Ok(modified) = metadata.modified() {
+                newest = newest.max(modified);
+            }
+        }
+    }
+    newest
+}
+
+/// Item deleting `target/` of Rust projects idle for 30 days below `~/projects` and `~/src`.
+///
+/// Roots and the idle window can be changed with the `project_roots` and `min_age_days`
+/// options. Disabled by default.
+pub fn rust_targets_item(home: &Path) -> CleanupItem {
+    let roots: Vec<PathBuf> = DEFAULT_PROJECT_ROOTS.iter().map(|root| home.join(root)).collect();
+    CleanupItem {
+        id: "rust_targets".to_string(),
+        name: "Rust target 目录".to_string(),
+        description: format!(
+            "闲置 Rust 项目的编译产物 (在 {} 中查找), 源码不受影响",
+            roots.iter().map(|root| root.display().to_string()).collect::<Vec<_>>().join(", ")
+        ),
+        cleanup_type: CleanupType::RustTargets(roots),
+        category: Category::DevTools,
+        risk: RiskLevel::Moderate,
+        min_age_days: DEFAULT_PROJECT_IDLE_DAYS,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// JavaScript projects modified within this many days keep their `node_modules/` by default
+pub const DEFAULT_NODE_IDLE_DAYS: u32 = 60;
+
+/// A JavaScript project with installed dependencies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeProject {
+    pub root: PathBuf,
+    /// Newest modification time of anything in the project outside `node_modules/`
+    pub last_modified: SystemTime,
+}
+
+impl NodeProject {
+    pub fn node_modules(&self) -> PathBuf {
+        self.root.join("node_modules")
+    }
+
+    /// Whole days since the project was last modified
+    pub fn idle_days(&self) -> u32 {
+        days_since(self.last_modified)
+    }
+}
+
+/// Directories below `roots` that contain both a `package.json` and a `node_modules/` directory.
+///
+/// `node_modules` itself is never searched, so packages inside it are not reported again.
+pub fn find_node_projects(roots: &[PathBuf]) -> Vec<NodeProject> {
+    find_projects(roots, "package.json", "node_modules")
+        .into_iter()
+        .map(|(root, last_modified)| NodeProject { root, last_modified })
+        .collect()
+}
+
+/// Item deleting `node_modules/` of JavaScript projects idle for 60 days below `~/projects`
+/// and `~/src`.
+///
+/// Roots and the idle window can be changed with the `project_roots` and `min_age_days`
+/// options, and single projects left out with `excluded_paths`. Disabled by default.
+pub fn node_modules_item(home: &Path) -> CleanupItem {
+    let roots: Vec<PathBuf> = DEFAULT_PROJECT_ROOTS.iter().map(|root| home.join(root)).collect();
+    CleanupItem {
+        id: "node_modules".to_string(),
+        name: "node_modules 目录".to_string(),
+        description: format!(
+            "闲置 JavaScript 项目安装的依赖 (在 {} 中查找), 源码不受影响",
+            roots.iter().map(|root| root.display().to_string()).collect::<Vec<_>>().join(", ")
+        ),
+        cleanup_type: CleanupType::NodeModules(roots),
+        category: Category::DevTools,
+        risk: RiskLevel::Moderate,
+        min_age_days: DEFAULT_NODE_IDLE_DAYS,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// Directories inside the npm cache that are cleaned; `_npx` and `_update-notifier-last-checked`
+/// are left alone
+pub const NPM_CACHE_SUBDIRS: &[&str] = &["_cacache", "_logs"];
+
+/// How the npm cache directory was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NpmCacheSource {
+    /// A `cache=` entry in this `.npmrc`
+    Npmrc(PathBuf),
+    /// The output of `npm config get cache`
+    NpmConfig,
+    /// The OS default, used when npmrc and npm gave no answer
+    Default,
+}
+
+/// The npm cache directory and where its location came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NpmCache {
+    pub dir: PathBuf,
+    pub source: NpmCacheSource,
+}
+
+/// Value of the last `cache=` entry in `.npmrc` contents, with a leading `~` expanded
+pub fn npmrc_cache(contents: &str, home: &Path) -> Option<PathBuf> {
+    // The last entry wins
+    let value = contents
+        .lines()
+        .rev()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#') && !line.starts_with(';'))
+        .filter_map(|line| line.split_once('='))
+        .find(|(key, _)| key.trim() == "cache")
+        .map(|(_, value)| value.trim().trim_matches('"').trim_matches('\''))
+        .filter(|value| !value.is_empty())?;
+
+    Some(match value.strip_prefix('~') {
+        Some(rest) => home.join(rest.trim_start_matches(['/', '\\'])),
+        None => PathBuf::from(value),
+    })
+}
+
+/// The user's `.npmrc`: `NPM_CONFIG_USERCONFIG` if set, else `~/.npmrc`
+pub fn user_npmrc(home: &Path) -> PathBuf {
+    env::var_os("NPM_CONFIG_USERCONFIG")
+        .filter(|path| !path.is_empty())
+        .map_or_else(|| home.join(".npmrc"), PathBuf::from)
+}
+
+/// Ask npm for its cache directory; `None` if npm is not on `PATH` or fails
+pub fn npm_config_get_cache() -> Option<PathBuf> {
+    let program = if cfg!(windows) { "npm.cmd" } else { "npm" };
+    let output = match Command::new(program).args(["config", "get", "cache"]).output() {
+        Ok(output) if output.status.success() => output,
+        Ok(output) => {
+            debug!("npm config get cache exited with {}", output.status);
+            return None;
+        }
+        Err(e) => {
+            debug!("Failed to run npm: {}", e);
+            return None;
+        }
+    };
+    let dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!dir.is_empty()).then(|| PathBuf::from(dir))
+}
+
+/// Find the npm cache: `cache=` in `npmrc`, then `npm_config` (normally
+/// [`npm_config_get_cache`]), then `%LOCALAPPDATA%\npm-cache` on Windows or `~/.npm` elsewhere
+pub fn resolve_npm_cache(
+    os: Os,
+    base: &BaseDirs,
+    npmrc: &Path,
+    npm_config: impl FnOnce() -> Option<PathBuf>,
+) -> NpmCache {
+    if let Some(dir) = fs::read_to_string(npmrc)
+        .ok()
+        .and_then(|contents| npmrc_cache(&contents, &base.home))
+    {
+        return NpmCache {
+            dir,
+            source: NpmCacheSource::Npmrc(npmrc.to_path_buf()),
+        };
+    }
+    if let Some(dir) = npm_config() {
+        return NpmCache {
+            dir,
+            source: NpmCacheSource::NpmConfig,
+        };
+    }
+    let dir = match os {
+        Os::Windows => base.data_local.join("npm-cache"),
+        Os::Linux | Os::MacOs => base.home.join(".npm"),
+    };
+    NpmCache {
+        dir,
+        source: NpmCacheSource::Default,
+    }
+}
+
+/// Item cleaning the content cache and logs of npm. Disabled by default.
+pub fn npm_cache_item(cache: &NpmCache) -> CleanupItem {
+    let source = match &cache.source {
+        NpmCacheSource::Npmrc(npmrc) => format!("来自 {}", npmrc.display()),
+        NpmCacheSource::NpmConfig => "来自 npm config get cache".to_string(),
+        NpmCacheSource::Default => "默认位置".to_string(),
+    };
+    CleanupItem {
+        id: "npm_cache".to_string(),
+        name: "NPM 缓存".to_string(),
+        description: format!("Node.js NPM 包管理器缓存 ({}, {})", cache.dir.display(), source),
+        cleanup_type: CleanupType::Directories(NPM_CACHE_SUBDIRS.iter().map(|name| cache.dir.join(name)).collect()),
+        category: Category::DevTools,
+        risk: RiskLevel::Safe,
+        priority: 20,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// Files in the pnpm store younger than this are kept when `pnpm` itself cannot prune it
+pub const PNPM_STORE_MIN_AGE_DAYS: u32 = 30;
+
+/// A package manager's global store; each subdirectory is usually one store layout version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageStore {
+    pub dir: PathBuf,
+    /// Removes unreferenced packages; used instead of deleting files when set
+    pub prune: Option<PruneCommand>,
+}
+
+/// A package manager command that cleans its own store
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PruneCommand {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+}
+
+impl PruneCommand {
+    pub fn run(&self) -> io::Result<()> {
+        let output = Command::new(&self.program).args(&self.args).output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "{} {} exited with {}: {}",
+                self.program.display(),
+                self.args.join(" "),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+}
+
+/// Full path of `name` in `PATH`, trying `.cmd` and `.exe` on Windows
+pub fn find_program(name: &str) -> Option<PathBuf> {
+    let extensions: &[&str] = if cfg!(windows) { &["cmd", "exe"] } else { &[""] };
+    env::split_paths(&env::var_os("PATH")?)
+        .flat_map(|dir| extensions.iter().map(move |extension| dir.join(name).with_extension(extension)))
+        .find(|path| path.is_file())
+}
+
+/// Yarn classic's cache: `%LOCALAPPDATA%\Yarn\Cache`, `~/.cache/yarn` or `~/Library/Caches/Yarn`
+pub fn yarn_cache_dir(os: Os, base: &BaseDirs) -> PathBuf {
+    match os {
+        Os::Windows => base.data_local.join("Yarn").join("Cache"),
+        Os::Linux => base.cache.join("yarn"),
+        Os::MacOs => base.cache.join("Yarn"),
+    }
+}
+
+/// Yarn berry's global mirror of package archives
+pub fn yarn_berry_cache_dir(os: Os, base: &BaseDirs) -> PathBuf {
+    match os {
+        Os::Windows => base.data_local.join("Yarn").join("Berry").join("cache"),
+        Os::Linux | Os::MacOs => base.home.join(".yarn").join("berry").join("cache"),
+    }
+}
+
+/// pnpm's content-addressable store: `%LOCALAPPDATA%\pnpm\store`, `~/.local/share/pnpm/store`
+/// or `~/Library/pnpm/store`
+pub fn pnpm_store_dir(os: Os, base: &BaseDirs) -> PathBuf {
+    match os {
+        Os::Windows => base.data_local.join("pnpm").join("store"),
+        Os::Linux => base.data.join("pnpm").join("store"),
+        Os::MacOs => base.home.join("Library").join("pnpm").join("store"),
+    }
+}
+
+/// One item for each yarn and pnpm store that exists. Disabled by default.
+///
+/// With `pnpm` (the binary's path) the pnpm store is cleaned by `pnpm store prune`;
+/// without it only files older than [`PNPM_STORE_MIN_AGE_DAYS`] are deleted.
+pub fn node_store_items(os: Os, base: &BaseDirs, pnpm: Option<PathBuf>) -> Vec<CleanupItem> {
+    let mut items = Vec::new();
+
+    let yarn = yarn_cache_dir(os, base);
+    if yarn.is_dir() {
+        items.push(store_item("yarn_cache", "Yarn 缓存", "Yarn classic 包缓存", yarn, None));
+    }
+    let berry = yarn_berry_cache_dir(os, base);
+    if berry.is_dir() {
+        items.push(store_item("yarn_berry_cache", "Yarn Berry 缓存", "Yarn berry 全局镜像", berry, None));
+    }
+    let store = pnpm_store_dir(os, base);
+    if store.is_dir() {
+        let prune = pnpm.map(|program| PruneCommand {
+            program,
+            args: vec!["store".to_string(), "prune".to_string()],
+        });
+        let mut item = store_item("pnpm_store", "pnpm 存储", "pnpm 内容寻址存储", store, prune.clone());
+        item.risk = RiskLevel::Moderate;
+        if prune.is_some() {
+            item.description.push_str(", 通过 pnpm store prune 清理未引用的包");
+        } else {
+            item.min_age_days = PNPM_STORE_MIN_AGE_DAYS;
+            item.description.push_str(&format!(", 未找到 pnpm, 只删除 {} 天前的文件", PNPM_STORE_MIN_AGE_DAYS));
+        }
+        items.push(item);
+    }
+    items
+}
+
+fn store_item(id: &str, name: &str, description: &str, dir: PathBuf, prune: Option<PruneCommand>) -> CleanupItem {
+    CleanupItem {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: format!("{} ({})", description, dir.display()),
+        cleanup_type: CleanupType::PackageStore(PackageStore { dir, prune }),
+        category: Category::DevTools,
+        risk: RiskLevel::Safe,
+        priority: 20,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// pip's cache directory: `PIP_CACHE_DIR` if set, else `%LOCALAPPDATA%\pip\Cache`,
+/// `~/.cache/pip` or `~/Library/Caches/pip`
+pub fn pip_cache_dir(pip_cache_var: Option<PathBuf>, os: Os, base: &BaseDirs) -> PathBuf {
+    pip_cache_var
+        .filter(|path| !path.as_os_str().is_empty())
+        .unwrap_or_else(|| match os {
+            Os::Windows => base.data_local.join("pip").join("Cache"),
+            Os::Linux | Os::MacOs => base.cache.join("pip"),
+        })
+}
+
+/// pip's cache directory for the current user
+pub fn current_pip_cache_dir(os: Os, base: &BaseDirs) -> PathBuf {
+    pip_cache_dir(env::var_os("PIP_CACHE_DIR").map(PathBuf::from), os, base)
+}
+
+/// Cache subdirectories sized separately: downloaded archives (`http`, `http-v2`) and built wheels
+pub const PIP_CACHE_SUBDIRS: &[&str] = &["http", "http-v2", "wheels"];
+
+/// Item deleting the files in pip's download and wheel caches. The directories themselves are
+/// kept so pip finds the layout it expects. Disabled by default.
+pub fn pip_cache_item(cache_dir: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "pip_cache".to_string(),
+        name: "pip 缓存".to_string(),
+        description: format!("Python pip 下载缓存和构建的 wheel ({})", cache_dir.display()),
+        cleanup_type: CleanupType::Directories(PIP_CACHE_SUBDIRS.iter().map(|name| cache_dir.join(name)).collect()),
+        category: Category::DevTools,
+        risk: RiskLevel::Safe,
+        priority: 20,
+        files_only: true,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// Whether `dir` holds installed packages rather than project sources: `site-packages`,
+/// `dist-packages`, a virtualenv (`pyvenv.cfg`) or a conda environment (`conda-meta`)
+fn is_python_env(dir: &Path) -> bool {
+    dir.file_name().is_some_and(|name| name == "site-packages" || name == "dist-packages")
+        || dir.join("pyvenv.cfg").is_file()
+        || dir.join("conda-meta").is_dir()
+}
+
+/// `__pycache__` directories and orphaned `.pyc`/`.pyo` files (without a `.py` next to them)
+/// below `root`, sorted.
+///
+/// Virtualenvs and `site-packages` are left alone unless `include_envs` is set, so installed
+/// libraries keep their compiled bytecode.
+pub fn find_python_bytecode(root: &Path, include_envs: bool) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            // file_type() does not follow symlinks, so linked trees are not searched twice
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if name == "__pycache__" {
+                    found.push(path);
+                } else if name != ".git" && name != "node_modules" && (include_envs || !is_python_env(&path)) {
+                    pending.push(path);
+                }
+            } else if file_type.is_file()
+                && path.extension().is_some_and(|extension| extension == "pyc" || extension == "pyo")
+                && !path.with_extension("py").exists()
+            {
+                found.push(path);
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Item deleting Python bytecode below `~/projects` and `~/src`.
+///
+/// Roots can be changed with the `project_roots` option, and virtualenvs searched as well
+/// with `include_envs`. Disabled by default.
+pub fn python_bytecode_item(home: &Path) -> CleanupItem {
+    let roots: Vec<PathBuf> = DEFAULT_PROJECT_ROOTS.iter().map(|root| home.join(root)).collect();
+    CleanupItem {
+        id: "python_bytecode".to_string(),
+        name: "Python 字节码".to_string(),
+        description: format!(
+            "__pycache__ 目录和孤立的 .pyc / .pyo 文件 (在 {} 中查找), 不包括虚拟环境和 site-packages",
+            roots.iter().map(|root| root.display().to_string()).collect::<Vec<_>>().join(", ")
+        ),
+        cleanup_type: CleanupType::PythonBytecode(roots),
+        category: Category::DevTools,
+        risk: RiskLevel::Safe,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// Subdirectories of the Composer cache that are cleaned; the cache root may double as the
+/// Composer home, whose `auth.json` and `config.json` are left alone
+pub const COMPOSER_CACHE_SUBDIRS: &[&str] = &["files", "repo"];
+
+/// Which of Composer's cache locations a cache directory is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComposerCacheSource {
+    /// `COMPOSER_CACHE_DIR`
+    EnvVar,
+    /// `%LOCALAPPDATA%\Composer` on Windows
+    LocalAppData,
+    /// `~/.cache/composer`, used by newer versions on Linux
+    XdgCache,
+    /// `~/.composer/cache`, used by older versions and when `~/.composer` exists
+    LegacyHome,
+}
+
+impl ComposerCacheSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ComposerCacheSource::EnvVar => "来自 COMPOSER_CACHE_DIR",
+            ComposerCacheSource::LocalAppData => "%LOCALAPPDATA%\\Composer",
+            ComposerCacheSource::XdgCache => "~/.cache/composer",
+            ComposerCacheSource::LegacyHome => "~/.composer/cache",
+        }
+    }
+}
+
+/// A Composer cache directory and which location it is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComposerCache {
+    pub dir: PathBuf,
+    pub source: ComposerCacheSource,
+}
+
+/// Composer caches: `COMPOSER_CACHE_DIR` if set, else every historical location that exists.
+///
+/// When none exists the location current Composer versions use is returned, so the item
+/// shows up as missing.
+pub fn composer_caches(cache_dir_var: Option<PathBuf>, os: Os, base: &BaseDirs) -> Vec<ComposerCache> {
+    if let Some(dir) = cache_dir_var.filter(|dir| !dir.as_os_str().is_empty()) {
+        return vec![ComposerCache {
+            dir,
+            source: ComposerCacheSource::EnvVar,
+        }];
+    }
+    let mut candidates = Vec::new();
+    match os {
+        Os::Windows => candidates.push(ComposerCache {
+            dir: base.data_local.join("Composer"),
+            source: ComposerCacheSource::LocalAppData,
+        }),
+        Os::Linux => candidates.push(ComposerCache {
+            dir: base.cache.join("composer"),
+            source: ComposerCacheSource::XdgCache,
+        }),
+        Os::MacOs => {}
+    }
+    candidates.push(ComposerCache {
+        dir: base.home.join(".composer").join("cache"),
+        source: ComposerCacheSource::LegacyHome,
+    });
+    let existing: Vec<ComposerCache> = candidates.iter().filter(|cache| cache.dir.is_dir()).cloned().collect();
+    if existing.is_empty() {
+        candidates.truncate(1);
+        candidates
+    } else {
+        existing
+    }
+}
+
+/// Composer caches for the current user
+pub fn current_composer_caches(os: Os, base: &BaseDirs) -> Vec<ComposerCache> {
+    composer_caches(env::var_os("COMPOSER_CACHE_DIR").map(PathBuf::from), os, base)
+}
+
+/// Item deleting the downloaded packages and repository metadata in every Composer cache.
+/// Disabled by default.
+pub fn composer_cache_item(caches: &[ComposerCache]) -> CleanupItem {
+    let locations: Vec<String> = caches
+        .iter()
+        .map(|cache| format!("{}: {}", cache.source.label(), cache.dir.display()))
+        .collect();
+    CleanupItem {
+        id: "composer_cache".to_string(),
+        name: "Composer 缓存".to_string(),
+        description: format!("PHP Composer 下载的包和仓库元数据 ({})", locations.join("; ")),
+        cleanup_type: CleanupType::Directories(
+            caches
+                .iter()
+                .flat_map(|cache| COMPOSER_CACHE_SUBDIRS.iter().map(|name| cache.dir.join(name)))
+                .collect(),
+        ),
+        category: Category::DevTools,
+        risk: RiskLevel::Safe,
+        priority: 20,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// Go's module path and build cache
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoEnv {
+    pub gopath: PathBuf,
+    /// `None` when the build cache is turned off (`GOCACHE=off`)
+    pub gocache: Option<PathBuf>,
+}
+
+impl GoEnv {
+    /// Downloaded module archives; extracted sources next to it are left alone
+    pub fn download_cache(&self) -> PathBuf {
+        self.gopath.join("pkg").join("mod").join("cache").join("download")
+    }
+}
+
+/// Resolve `GOPATH` and `GOCACHE` from the environment variables, then from `go_env`
+/// (normally [`go_env_output`]), then from Go's defaults: `~/go` and `go-build` in the user
+/// cache directory. Only the first entry of a `GOPATH` list is used.
+pub fn resolve_go_env(
+    gopath_var: Option<OsString>,
+    gocache_var: Option<OsString>,
+    os: Os,
+    base: &BaseDirs,
+    go_env: impl FnOnce() -> Option<(String, String)>,
+) -> GoEnv {
+    let non_empty = |value: Option<OsString>| value.filter(|value| !value.is_empty());
+    let (mut gopath, mut gocache) = (non_empty(gopath_var), non_empty(gocache_var));
+    if gopath.is_none() || gocache.is_none() {
+        if let Some((go_gopath, go_gocache)) = go_env() {
+            gopath = gopath.or(non_empty(Some(go_gopath.into())));
+            gocache = gocache.or(non_empty(Some(go_gocache.into())));
+        }
+    }
+
+    let gopath = gopath
+        .and_then(|list| env::split_paths(&list).find(|path| !path.as_os_str().is_empty()))
+        .unwrap_or_else(|| base.home.join("go"));
+    let gocache = match gocache {
+        Some(value) if value == "off" => None,
+        Some(value) => Some(PathBuf::from(value)),
+        None => Some(
+            match os {
+                Os::Windows => &base.data_local,
+                Os::Linux | Os::MacOs => &base.cache,
+            }
+            .join("go-build"),
+        ),
+    };
+    GoEnv { gopath, gocache }
+}
+
+/// `GOPATH` and `GOCACHE` as reported by `go env`; `None` if Go is not installed
+pub fn go_env_output() -> Option<(String, String)> {
+    let output = match Command::new("go").args(["env", "GOPATH", "GOCACHE"]).output() {
+        Ok(output) if output.status.success() => output,
+        Ok(output) => {
+            debug!("go env exited with {}", output.status);
+            return None;
+        }
+        Err(e) => {
+            debug!("Failed to run go: {}", e);
+            return None;
+        }
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines().map(|line| line.trim().to_string());
+    Some((lines.next()?, lines.next().unwrap_or_default()))
+}
+
+/// Go's environment for the current user
+pub fn current_go_env(os: Os, base: &BaseDirs) -> GoEnv {
+    resolve_go_env(env::var_os("GOPATH"), env::var_os("GOCACHE"), os, base, go_env_output)
+}
+
+/// Item cleaning Go's module download cache and build cache. Disabled by default.
+pub fn go_cache_item(go: &GoEnv) -> CleanupItem {
+    let mut dirs = vec![go.download_cache()];
+    dirs.extend(go.gocache.clone());
+    CleanupItem {
+        id: "go_cache".to_string(),
+        name: "Go 缓存".to_string(),
+        description: format!(
+            "Go 模块下载缓存和构建缓存 ({})",
+            dirs.iter().map(|dir| dir.display().to_string()).collect::<Vec<_>>().join(", ")
+        ),
+        cleanup_type: CleanupType::Directories(dirs),
+        category: Category::DevTools,
+        risk: RiskLevel::Safe,
+        priority: 20,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// Gradle's user home: `GRADLE_USER_HOME` if set, else `~/.gradle`
+pub fn gradle_user_home(gradle_user_home_var: Option<PathBuf>, home: &Path) -> PathBuf {
+    gradle_user_home_var
+        .filter(|path| !path.as_os_str().is_empty())
+        .unwrap_or_else(|| home.join(".gradle"))
+}
+
+/// Gradle's user home for the current user
+pub fn current_gradle_user_home(home: &Path) -> PathBuf {
+    gradle_user_home(env::var_os("GRADLE_USER_HOME").map(PathBuf::from), home)
+}
+
+/// Directories below the Gradle user home that are cleaned: dependency jars, the build cache,
+/// downloaded Gradle distributions and daemon registries and logs
+pub const GRADLE_CACHE_DIRS: &[&str] = &["caches/modules-2", "caches/build-cache-1", "wrapper/dists", "daemon"];
+
+/// Files used by a build within this many days are kept so they are not downloaded again
+pub const GRADLE_MIN_AGE_DAYS: u32 = 30;
+
+/// Item cleaning Gradle's caches, keeping files accessed within [`GRADLE_MIN_AGE_DAYS`].
+/// Skipped while a Gradle daemon runs. Disabled by default.
+pub fn gradle_cache_item(gradle_home: &Path) -> CleanupItem {
+    let daemon_dirs = fs::read_dir(gradle_home.join("daemon"))
+        .map(|entries| entries.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()).collect())
+        .unwrap_or_default();
+    CleanupItem {
+        id: "gradle_cache".to_string(),
+        name: "Gradle 缓存".to_string(),
+        description: format!(
+            "Gradle 依赖、构建缓存、下载的发行版和守护进程日志 ({}), 保留 {} 天内用过的文件",
+            gradle_home.display(),
+            GRADLE_MIN_AGE_DAYS
+        ),
+        cleanup_type: CleanupType::Directories(
+            GRADLE_CACHE_DIRS.iter().map(|relative| join_relative(gradle_home, relative)).collect(),
+        ),
+        category: Category::DevTools,
+        risk: RiskLevel::Safe,
+        priority: 20,
+        min_age_days: GRADLE_MIN_AGE_DAYS,
+        age_by_access: true,
+        enabled: false,
+        app_check: Some(AppCheck {
+            app_name: "Gradle 守护进程".to_string(),
+            process_names: Vec::new(),
+            lock_files: Vec::new(),
+            pid_file_dirs: daemon_dirs,
+        }),
+        ..Default::default()
+    }
+}
+
+/// The local Maven repository
+pub fn maven_repository(home: &Path) -> PathBuf {
+    home.join(".m2").join("repository")
+}
+
+/// One version directory of an artifact in a Maven repository, e.g. `org/foo/bar/1.0-SNAPSHOT`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MavenVersion {
+    pub dir: PathBuf,
+    /// Newest access or modification time of the files in the directory
+    pub last_used: SystemTime,
+}
+
+impl MavenVersion {
+    pub fn is_snapshot(&self) -> bool {
+        self.dir.to_string_lossy().ends_with("-SNAPSHOT")
+    }
+
+    /// Whole days since a file of this version was last read or written
+    pub fn unused_days(&self) -> u32 {
+        days_since(self.last_used)
+    }
+
+    /// Files of timestamped snapshot builds older than the newest build; empty for releases.
+    ///
+    /// `bar-1.0-20240101.120000-3.jar` belongs to build `20240101.120000-3`; every file of
+    /// the newest build is kept, as are the non-timestamped `bar-1.0-SNAPSHOT.*` files.
+    pub fn stale_snapshot_files(&self) -> Vec<PathBuf> {
+        let (Some(artifact), Some(version)) = (
+            self.dir.parent().and_then(Path::file_name),
+            self.dir.file_name(),
+        ) else {
+            return Vec::new();
+        };
+        let Some(base) = version.to_string_lossy().strip_suffix("-SNAPSHOT").map(str::to_string) else {
+            return Vec::new();
+        };
+        let prefix = format!("{}-{}-", artifact.to_string_lossy(), base);
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let builds: Vec<((String, u32), PathBuf)> = entries
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_file()))
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let build = snapshot_build(name.strip_prefix(&prefix)?)?;
+                Some((build, entry.path()))
+            })
+            .collect();
+        let Some(newest) = builds.iter().map(|(build, _)| build).max().cloned() else {
+            return Vec::new();
+        };
+        let mut stale: Vec<PathBuf> = builds
+            .into_iter()
+            .filter(|(build, _)| *build != newest)
+            .map(|(_, path)| path)
+            .collect();
+        stale.sort();
+        stale
+    }
+}
+
+/// Timestamp and build number at the start of `20240101.120000-3.jar`
+fn snapshot_build(rest: &str) -> Option<(String, u32)> {
+    let (timestamp, rest) = rest.split_at_checked(15)?;
+    let (date, time) = timestamp.split_once('.')?;
+    if date.len() != 8 || !date.bytes().chain(time.bytes()).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let digits: String = rest.strip_prefix('-')?.chars().take_while(char::is_ascii_digit).collect();
+    Some((timestamp.to_string(), digits.parse().ok()?))
+}
+
+/// Artifact version directories below `repository`, recognized by the `.pom` they contain
+pub fn find_maven_versions(repository: &Path) -> Vec<MavenVersion> {
+    let mut versions = Vec::new();
+    let mut pending = vec![repository.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut subdirs = Vec::new();
+        let mut is_version = false;
+        let mut last_used = SystemTime::UNIX_EPOCH;
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                subdirs.push(entry.path());
+                continue;
+            }
+            is_version |= entry.file_name().to_string_lossy().ends_with(".pom");
+            for time in [metadata.modified(), metadata.accessed()].into_iter().flatten() {
+                last_used = last_used.max(time);
+            }
+        }
+        if is_version {
+            versions.push(MavenVersion { dir, last_used });
+        } else {
+            pending.extend(subdirs);
+        }
+    }
+    versions.sort_by(|a, b| a.dir.cmp(&b.dir));
+    versions
+}
+
+/// Item deleting superseded snapshot builds from the local Maven repository. Releases are
+/// only touched when the `unused_days` option is set. Disabled by default.
+pub fn maven_repo_item(repository: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "maven_repo".to_string(),
+        name: "Maven 旧快照".to_string(),
+        description: format!("Maven 本地仓库中被新构建取代的 SNAPSHOT 文件 ({})", repository.display()),
+        cleanup_type: CleanupType::MavenRepo(repository.to_path_buf()),
+        category: Category::DevTools,
+        risk: RiskLevel::Safe,
+        priority: 20,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// Where NuGet keeps its caches, named as `dotnet nuget locals` names them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NuGetLocals {
+    pub http_cache: PathBuf,
+    pub global_packages: PathBuf,
+    pub temp: PathBuf,
+    pub plugins_cache: PathBuf,
+    /// The paths were reported by the `dotnet` CLI
+    pub from_dotnet: bool,
+}
+
+/// Packages not used by a restore or build within this many days may be deleted
+pub const NUGET_PACKAGES_MIN_AGE_DAYS: u32 = 30;
+
+impl NuGetLocals {
+    /// NuGet's defaults: `~/.nuget/packages` plus `%LOCALAPPDATA%\NuGet` on Windows or
+    /// `~/.local/share/NuGet` elsewhere; the scratch folder is `NuGetScratch<user>` outside Windows
+    pub fn defaults(os: Os, base: &BaseDirs, temp_dir: &Path, user: &str) -> Self {
+        let (root, temp) = match os {
+            Os::Windows => (base.data_local.join("NuGet"), temp_dir.join("NuGetScratch")),
+            Os::Linux | Os::MacOs => (
+                base.home.join(".local").join("share").join("NuGet"),
+                temp_dir.join(format!("NuGetScratch{}", user)),
+            ),
+        };
+        Self {
+            http_cache: root.join("v3-cache"),
+            global_packages: base.home.join(".nuget").join("packages"),
+            temp,
+            plugins_cache: root.join("plugins-cache"),
+            from_dotnet: false,
+        }
+    }
+
+    /// Apply `NUGET_PACKAGES`, then the output of `dotnet nuget locals all --list`, which wins
+    pub fn resolve(mut self, packages_var: Option<OsString>, dotnet_output: Option<&str>) -> Self {
+        if let Some(packages) = packages_var.filter(|value| !value.is_empty()) {
+            self.global_packages = PathBuf::from(packages);
+        }
+        for line in dotnet_output.unwrap_or_default().lines() {
+            // Newer SDKs prefix lines with `info : `
+            let line = line.trim().trim_start_matches("info :").trim();
+            let Some((name, path)) = line.split_once(": ") else {
+                continue;
+            };
+            let field = match name.trim() {
+                "http-cache" => &mut self.http_cache,
+                "global-packages" => &mut self.global_packages,
+                "temp" => &mut self.temp,
+                "plugins-cache" => &mut self.plugins_cache,
+                _ => continue,
+            };
+            *field = PathBuf::from(path.trim());
+            self.from_dotnet = true;
+        }
+        self
+    }
+}
+
+/// Output of `dotnet nuget locals all --list`; `None` if the .NET SDK is not installed
+pub fn dotnet_nuget_locals() -> Option<String> {
+    match Command::new("dotnet").args(["nuget", "locals", "all", "--list"]).output() {
+        Ok(output) if output.status.success() => Some(String::from_utf8_lossy(&output.stdout).into_owned()),
+        Ok(output) => {
+            debug!("dotnet nuget locals exited with {}", output.status);
+            None
+        }
+        Err(e) => {
+            debug!("Failed to run dotnet: {}", e);
+            None
+        }
+    }
+}
+
+/// NuGet's locations for the current user
+pub fn current_nuget_locals(os: Os, base: &BaseDirs) -> NuGetLocals {
+    let user = env::var("USER").unwrap_or_default();
+    NuGetLocals::defaults(os, base, &env::temp_dir(), &user)
+        .resolve(env::var_os("NUGET_PACKAGES"), dotnet_nuget_locals().as_deref())
+}
+
+/// `nuget_cache` for the http, scratch and plugin caches, and the aggressive `nuget_packages`
+/// deleting package versions unused for [`NUGET_PACKAGES_MIN_AGE_DAYS`]. Disabled by default.
+pub fn nuget_items(locals: &NuGetLocals) -> Vec<CleanupItem> {
+    let source = if locals.from_dotnet { ", 路径来自 dotnet nuget locals" } else { "" };
+    vec![
+        CleanupItem {
+            id: "nuget_cache".to_string(),
+            name: "NuGet 缓存".to_string(),
+            description: format!("NuGet HTTP 缓存、临时目录和插件缓存 ({}{})", locals.http_cache.display(), source),
+            cleanup_type: CleanupType::Directories(vec![
+                locals.http_cache.clone(),
+                locals.temp.clone(),
+                locals.plugins_cache.clone(),
+            ]),
+            category: Category::DevTools,
+            risk: RiskLevel::Safe,
+            priority: 20,
+            enabled: false,
+            ..Default::default()
+        },
+        CleanupItem {
+            id: "nuget_packages".to_string(),
+            name: "NuGet 全局包".to_string(),
+            description: format!(
+                "NuGet 全局包目录中 {} 天未使用的包版本, 下次还原时重新下载 ({}{})",
+                NUGET_PACKAGES_MIN_AGE_DAYS,
+                locals.global_packages.display(),
+                source
+            ),
+            // `<id>/<version>` is the unit NuGet restores, so it is deleted as a whole
+            cleanup_type: CleanupType::UnusedDirs {
+                roots: vec![locals.global_packages.clone()],
+                depth: 2,
+            },
+            category: Category::DevTools,
+            risk: RiskLevel::Aggressive,
+            priority: 20,
+            min_age_days: NUGET_PACKAGES_MIN_AGE_DAYS,
+            age_by_access: true,
+            enabled: false,
+            ..Default::default()
+        },
+    ]
+}
+
+/// Conda installs looked for in the home directory
+pub const CONDA_ROOT_NAMES: &[&str] = &["miniconda3", "anaconda3", "miniforge3", "mambaforge"];
+
+/// Suffixes of downloaded conda package archives
+const CONDA_ARCHIVE_SUFFIXES: &[&str] = &[".tar.bz2", ".conda"];
+
+/// Conda package caches and the environments that use them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CondaPackages {
+    /// `pkgs` directories holding archives and extracted packages
+    pub pkgs_dirs: Vec<PathBuf>,
+    /// Environment prefixes whose `conda-meta` lists the packages in use
+    pub envs: Vec<PathBuf>,
+}
+
+/// A cached package no environment uses: its archive, its extracted directory, or both
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CondaPackage {
+    /// `name-version-build`, as in `numpy-1.26.4-py311h64a7726_0`
+    pub name: String,
+    pub paths: Vec<PathBuf>,
+}
+
+impl CondaPackages {
+    /// Packages installed in any environment, by `name-version-build`
+    pub fn referenced(&self) -> HashSet<String> {
+        self.envs
+            .iter()
+            .filter_map(|env| fs::read_dir(env.join("conda-meta")).ok())
+            .flat_map(|entries| entries.flatten())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                name.strip_suffix(".json").map(str::to_string)
+            })
+            .collect()
+    }
+
+    /// Archives and extracted packages in the `pkgs` directories that no environment uses.
+    ///
+    /// Nothing is reported when no environment was found, as every package would look unused.
+    pub fn unused_packages(&self) -> Vec<CondaPackage> {
+        let referenced = self.referenced();
+        if referenced.is_empty() {
+            return Vec::new();
+        }
+        let mut packages: Vec<CondaPackage> = Vec::new();
+        for pkgs_dir in &self.pkgs_dirs {
+            let Ok(entries) = fs::read_dir(pkgs_dir) else {
+                continue;
+            };
+            let mut found: Vec<(String, PathBuf)> = entries
+                .flatten()
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    let file_name = entry.file_name().to_string_lossy().into_owned();
+                    let name = if entry.file_type().ok()?.is_dir() {
+                        // Extracted packages have an `info` directory; `cache` does not
+                        path.join("info").is_dir().then_some(file_name)?
+                    } else {
+                        CONDA_ARCHIVE_SUFFIXES
+                            .iter()
+                            .find_map(|suffix| file_name.strip_suffix(suffix))?
+                            .to_string()
+                    };
+                    Some((name, path))
+                })
+                .filter(|(name, _)| !referenced.contains(name))
+                .collect();
+            found.sort();
+            let first = packages.len();
+            for (name, path) in found {
+                match packages[first..].last_mut() {
+                    Some(last) if last.name == name => last.paths.push(path),
+                    _ => packages.push(CondaPackage { name, paths: vec![path] }),
+                }
+            }
+        }
+        packages
+    }
+}
+
+/// `pkgs_dirs` listed in a `.condarc`, with `~` expanded
+pub fn condarc_pkgs_dirs(contents: &str, home: &Path) -> Vec<PathBuf> {
+    let expand = |value: &str| {
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+        match value.strip_prefix("~/") {
+            Some(rest) => home.join(rest),
+            None => PathBuf::from(value),
+        }
+    };
+    let mut dirs = Vec::new();
+    let mut in_list = false;
+    for line in contents.lines() {
+        let line = line.split('#').next().unwrap_or("").trim_end();
+        if let Some(value) = line.strip_prefix("pkgs_dirs:") {
+            let value = value.trim();
+            // Flow style: `pkgs_dirs: [/a, /b]`
+            if let Some(list) = value.strip_prefix('[').and_then(|value| value.strip_suffix(']')) {
+                dirs.extend(list.split(',').filter(|item| !item.trim().is_empty()).map(expand));
+            }
+            in_list = value.is_empty();
+        } else if in_list {
+            match line.trim_start().strip_prefix("- ") {
+                Some(item) => dirs.push(expand(item)),
+                None if line.trim().is_empty() => {}
+                None => in_list = false,
+            }
+        }
+    }
+    dirs
+}
+
+/// Install directory of a conda prefix: `<root>/envs/<name>` belongs to `<root>`
+fn conda_root_of(prefix: &Path) -> &Path {
+    match prefix.parent() {
+        Some(envs) if envs.file_name().is_some_and(|name| name == "envs") => envs.parent().unwrap_or(prefix),
+        _ => prefix,
+    }
+}
+
+/// Find conda installs in the home directory and at `conda_prefix`, their package caches
+/// (plus those set with `pkgs_dirs` in `condarc`) and their environments
+pub fn find_conda(home: &Path, conda_prefix: Option<PathBuf>, condarc: &str) -> CondaPackages {
+    let mut roots: Vec<PathBuf> = CONDA_ROOT_NAMES.iter().map(|name| home.join(name)).collect();
+    if let Some(prefix) = conda_prefix.filter(|prefix| !prefix.as_os_str().is_empty()) {
+        roots.push(conda_root_of(&prefix).to_path_buf());
+    }
+    roots.retain(|root| root.is_dir());
+
+    let mut pkgs_dirs = condarc_pkgs_dirs(condarc, home);
+    pkgs_dirs.extend(roots.iter().map(|root| root.join("pkgs")));
+    pkgs_dirs.push(home.join(".conda").join("pkgs"));
+
+    // Environments created with `--prefix` are only listed in `environments.txt`
+    let mut envs = roots.clone();
+    for root in &roots {
+        if let Ok(entries) = fs::read_dir(root.join("envs")) {
+            envs.extend(entries.flatten().map(|entry| entry.path()));
+        }
+    }
+    if let Ok(listed) = fs::read_to_string(home.join(".conda").join("environments.txt")) {
+        envs.extend(listed.lines().map(str::trim).filter(|line| !line.is_empty()).map(PathBuf::from));
+    }
+
+    CondaPackages {
+        pkgs_dirs: unique_existing(pkgs_dirs, Path::is_dir),
+        envs: unique_existing(envs, |env| env.join("conda-meta").is_dir()),
+    }
+}
+
+/// `paths` that pass `keep`, without duplicates, in their original order
+fn unique_existing(paths: Vec<PathBuf>, keep: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+    let mut unique: Vec<PathBuf> = Vec::new();
+    for path in paths {
+        if keep(&path) && !unique.contains(&path) {
+            unique.push(path);
+        }
+    }
+    unique
+}
+
+/// Conda package caches for the current user, from `CONDA_PREFIX` and `~/.condarc`
+pub fn current_conda(home: &Path) -> CondaPackages {
+    let condarc = fs::read_to_string(home.join(".condarc")).unwrap_or_default();
+    find_conda(home, env::var_os("CONDA_PREFIX").map(PathBuf::from), &condarc)
+}
+
+/// Item deleting cached conda packages that no environment uses, like
+/// `conda clean --packages --tarballs`. Disabled by default.
+pub fn conda_cache_item(conda: &CondaPackages) -> CleanupItem {
+    let dirs: Vec<String> = conda.pkgs_dirs.iter().map(|dir| dir.display().to_string()).collect();
+    CleanupItem {
+        id: "conda_cache".to_string(),
+        name: "Conda 包缓存".to_string(),
+        description: format!("未被任何环境使用的 conda 压缩包和解压目录 ({})", dirs.join(", ")),
+        cleanup_type: CleanupType::CondaPkgs(conda.clone()),
+        category: Category::DevTools,
+        risk: RiskLevel::Safe,
+        priority: 20,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// Homebrew's download cache: `HOMEBREW_CACHE` if set, else `~/Library/Caches/Homebrew` or
+/// `~/.cache/Homebrew`
+pub fn homebrew_cache_dir(homebrew_cache_var: Option<PathBuf>, base: &BaseDirs) -> PathBuf {
+    homebrew_cache_var
+        .filter(|path| !path.as_os_str().is_empty())
+        .unwrap_or_else(|| base.cache.join("Homebrew"))
+}
+
+/// Homebrew's download cache for the current user
+pub fn current_homebrew_cache_dir(base: &BaseDirs) -> PathBuf {
+    homebrew_cache_dir(env::var_os("HOMEBREW_CACHE").map(PathBuf::from), base)
+}
+
+/// Item deleting downloaded bottles and source archives in `downloads/`.
+///
+/// The `name--version` links next to it are left for `brew cleanup`, which drops dangling ones.
+pub fn homebrew_cache_item(cache_dir: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "homebrew_cache".to_string(),
+        name: "Homebrew 缓存".to_string(),
+        description: format!("Homebrew 下载的 bottle 和源码包: {}", cache_dir.join("downloads").display()),
+        cleanup_type: CleanupType::Directory(cache_dir.join("downloads")),
+        category: Category::DevTools,
+        risk: RiskLevel::Safe,
+        enabled: cache_dir.is_dir(),
+        files_only: true,
+        ..Default::default()
+    }
+}
+
+/// Bytes in the `This operation would free approximately 1.2GB of disk space.` line printed
+/// by `brew cleanup`, with or without `--dry-run`. Homebrew uses binary units.
+pub fn parse_brew_freed(output: &str) -> Option<u64> {
+    output.lines().find_map(|line| {
+        let size = line.split_once("approximately ")?.1.split_whitespace().next()?;
+        free_space::parse_size(size).ok()
+    })
+}
+
+/// Old kegs and stale downloads removed by `brew cleanup --prune=all`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrewCleanup {
+    pub program: PathBuf,
+}
+
+impl BrewCleanup {
+    pub fn new(program: PathBuf) -> Self {
+        Self { program }
+    }
+
+    /// Run `brew cleanup --prune=all` with `extra` arguments and return the bytes it reports
+    fn cleanup(&self, extra: &[&str]) -> io::Result<u64> {
+        let mut args = vec!["cleanup", "--prune=all"];
+        args.extend_from_slice(extra);
+        let output = Command::new(&self.program).args(&args).output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "{} {} exited with {}: {}",
+                self.program.display(),
+                args.join(" "),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        // Nothing to remove prints no summary line at all
+        Ok(parse_brew_freed(&String::from_utf8_lossy(&output.stdout)).unwrap_or(0))
+    }
+
+    fn result(&self, freed: io::Result<u64>) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        match freed {
+            Ok(freed) => {
+                result.size_bytes = freed;
+                result.has_data = freed > 0;
+            }
+            Err(e) => result.errors.push(CleanErrorEntry::new(&self.program, &e)),
+        }
+        result
+    }
+
+    /// Opt-in item: pruning removes older versions of installed formulae
+    pub fn item(self) -> CleanupItem {
+        CleanupItem {
+            id: "homebrew_cleanup".to_string(),
+            name: "Homebrew 旧版本".to_string(),
+            description: "已安装软件的旧版本和过期下载 (brew cleanup --prune=all)".to_string(),
+            category: Category::DevTools,
+            risk: RiskLevel::Moderate,
+            enabled: false,
+            cleanup_type: CleanupType::Custom(Arc::new(self)),
+            ..Default::default()
+        }
+    }
+}
+
+impl CleanTarget for BrewCleanup {
+    fn scan(&self) -> CleanupResult {
+        self.result(self.cleanup(&["--dry-run"]))
+    }
+
+    fn clean(&self) -> CleanupResult {
+        let result = self.result(self.cleanup(&[]));
+        info!("brew cleanup freed {} bytes", result.size_bytes);
+        result
+    }
+
+    fn validate(&self) -> ItemStatus {
+        if self.program.is_file() {
+            ItemStatus::Ready
+        } else {
+            ItemStatus::PathMissing
+        }
+    }
+}
+
+/// The Homebrew cache item, plus keg pruning when `brew` is installed
+pub fn homebrew_items(cache_dir: &Path, brew: Option<PathBuf>) -> Vec<CleanupItem> {
+    let mut items = vec![homebrew_cache_item(cache_dir)];
+    items.extend(brew.map(|brew| BrewCleanup::new(brew).item()));
+    items
+}
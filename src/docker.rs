@@ -0,0 +1,206 @@
+//! Docker images, containers, build cache and volumes, cleaned through the docker CLI.
+//!
+//! Docker's data lives in the daemon's storage, so sizes come from `docker system df` and
+//! cleaning is left to `docker system prune` instead of deleting files.
+
+use crate::cleanup_items::{
+    BreakdownEntry, Category, CleanErrorEntry, CleanTarget, CleanupItem, CleanupResult, CleanupType, ItemStatus,
+    RiskLevel,
+};
+use serde::Deserialize;
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+use tracing::info;
+
+/// `docker system df` types removed by `docker system prune`
+const SYSTEM_TYPES: [&str; 3] = ["Images", "Containers", "Build Cache"];
+
+/// `docker system df` type removed by `docker volume prune`
+const VOLUMES_TYPE: &str = "Local Volumes";
+
+/// One line of `docker system df --format '{{json .}}'`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DiskUsage {
+    #[serde(rename = "Type")]
+    pub kind: String,
+    /// Such as `1.2GB (50%)`
+    #[serde(rename = "Reclaimable")]
+    pub reclaimable: String,
+}
+
+impl DiskUsage {
+    pub fn reclaimable_bytes(&self) -> Option<u64> {
+        parse_size(self.reclaimable.split_whitespace().next()?)
+    }
+}
+
+/// Parse `docker system df` output, one JSON object per line; unreadable lines are ignored
+pub fn parse_disk_usage(output: &str) -> Vec<DiskUsage> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str(line.trim()).ok())
+        .collect()
+}
+
+/// Bytes in a size printed by docker, such as `512B`, `1.5kB` or `2.3GB`.
+///
+/// Docker uses decimal units.
+pub fn parse_size(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 1e0,
+        "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        "PB" => 1e15,
+        _ => return None,
+    };
+    Some((number * multiplier).round() as u64)
+}
+
+/// Bytes in the `Total reclaimed space: 1.2GB` line printed by `docker ... prune`
+pub fn parse_reclaimed(output: &str) -> Option<u64> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Total reclaimed space:"))
+        .and_then(parse_size)
+}
+
+/// Unused Docker data, measured and pruned with the docker CLI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DockerTarget {
+    pub program: PathBuf,
+    /// Prune unused volumes instead of images, containers and build cache
+    pub volumes: bool,
+}
+
+impl DockerTarget {
+    pub fn new(program: PathBuf, volumes: bool) -> Self {
+        Self { program, volumes }
+    }
+
+    /// `docker system df` types this target reports
+    fn types(&self) -> &'static [&'static str] {
+        if self.volumes {
+            &[VOLUMES_TYPE]
+        } else {
+            &SYSTEM_TYPES
+        }
+    }
+
+    fn prune_args(&self) -> &'static [&'static str] {
+        if self.volumes {
+            &["volume", "prune", "-f"]
+        } else {
+            &["system", "prune", "-f"]
+        }
+    }
+
+    /// Run docker and return its stdout, or an error carrying its stderr
+    fn run(&self, args: &[&str]) -> io::Result<String> {
+        let output = Command::new(&self.program).args(args).output()?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            Err(io::Error::other(format!(
+                "{} {} exited with {}: {}",
+                self.program.display(),
+                args.join(" "),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+
+    fn failed(&self, error: &io::Error) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        result.errors.push(CleanErrorEntry::new(&self.program, error));
+        result
+    }
+
+    pub fn item(self) -> CleanupItem {
+        let (id, name, description, risk) = if self.volumes {
+            ("docker_volumes", "Docker 卷", "未被任何容器使用的 Docker 卷, 其中的数据会丢失", RiskLevel::Aggressive)
+        } else {
+            (
+                "docker",
+                "Docker 缓存",
+                "已停止的容器、悬空镜像和构建缓存 (docker system prune)",
+                RiskLevel::Moderate,
+            )
+        };
+        CleanupItem {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            category: Category::DevTools,
+            risk,
+            enabled: false,
+            cleanup_type: CleanupType::Custom(Arc::new(self)),
+            ..Default::default()
+        }
+    }
+}
+
+impl CleanTarget for DockerTarget {
+    fn scan(&self) -> CleanupResult {
+        // `{{json .}}` rather than `json`, which older docker versions print literally
+        let output = match self.run(&["system", "df", "--format", "{{json .}}"]) {
+            Ok(output) => output,
+            Err(e) => return self.failed(&e),
+        };
+        let mut result = CleanupResult::new();
+        for usage in parse_disk_usage(&output) {
+            if !self.types().contains(&usage.kind.as_str()) {
+                continue;
+            }
+            let size = usage.reclaimable_bytes().unwrap_or(0);
+            result.size_bytes += size;
+            if size > 0 {
+                result.breakdown.push(BreakdownEntry {
+                    label: usage.kind,
+                    size_bytes: size,
+                    note: String::new(),
+                });
+            }
+        }
+        result.has_data = result.size_bytes > 0;
+        result
+    }
+
+    fn clean(&self) -> CleanupResult {
+        let output = match self.run(self.prune_args()) {
+            Ok(output) => output,
+            Err(e) => return self.failed(&e),
+        };
+        let mut result = CleanupResult::new();
+        result.size_bytes = parse_reclaimed(&output).unwrap_or(0);
+        result.has_data = result.size_bytes > 0;
+        info!("docker {}: reclaimed {} bytes", self.prune_args().join(" "), result.size_bytes);
+        result
+    }
+
+    fn validate(&self) -> ItemStatus {
+        match Command::new(&self.program).args(["info", "--format", "{{.ServerVersion}}"]).output() {
+            Ok(output) if output.status.success() => ItemStatus::Ready,
+            Ok(_) => ItemStatus::ServiceUnavailable,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => ItemStatus::PathMissing,
+            Err(_) => ItemStatus::ServiceUnavailable,
+        }
+    }
+}
+
+/// Docker cache item and, for aggressive cleaning, the unused volumes item
+pub fn docker_items(program: Option<PathBuf>) -> Vec<CleanupItem> {
+    let program = program.unwrap_or_else(|| PathBuf::from("docker"));
+    vec![
+        DockerTarget::new(program.clone(), false).item(),
+        DockerTarget::new(program, true).item(),
+    ]
+}
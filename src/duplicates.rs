@@ -0,0 +1,306 @@
+//! Finding files with identical contents.
+//!
+//! Files are grouped by size first, then by a hash of their first and last 64 KiB, and only
+//! files still sharing a group are hashed in full. Hashing runs on several threads.
+
+use crate::cleanup_items::{
+    self, BreakdownEntry, Category, CleanTarget, CleanupItem, CleanupResult, CleanupType, ItemContext, ItemStatus,
+    RiskLevel, NOT_SELECTED_NOTE,
+};
+use crate::platform;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use xxhash_rust::xxh3::{xxh3_64, Xxh3};
+
+/// Files smaller than this are ignored; duplicates worth cleaning up are large
+pub const DUPLICATE_MIN_SIZE: u64 = 1024 * 1024;
+
+/// Folders below the home directory holding application data rather than the user's files
+pub const DUPLICATE_EXCLUDED: &[&str] = &["Library", "AppData"];
+
+/// Bytes hashed at each end of a file before it is hashed in full
+const SAMPLE_SIZE: usize = 64 * 1024;
+
+/// Files with identical contents. The first path is the copy that is kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// Size of each copy
+    pub size: u64,
+    /// Sorted paths, each a separate file rather than a hard link to another one
+    pub paths: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    /// The copy that is kept when the group is cleaned
+    pub fn kept(&self) -> &Path {
+        &self.paths[0]
+    }
+
+    /// The copies deleted when the group is cleaned
+    pub fn extra_copies(&self) -> &[PathBuf] {
+        &self.paths[1..]
+    }
+
+    /// Bytes freed by deleting every copy but one
+    pub fn reclaimable(&self) -> u64 {
+        self.size * self.extra_copies().len() as u64
+    }
+}
+
+/// Files of at least `min_size` bytes below `roots` that share their size with another file.
+///
+/// Symlinks are not followed, and directories in `excluded` or whose name starts with a dot
+/// are not searched.
+pub fn same_size_files(roots: &[PathBuf], excluded: &[PathBuf], min_size: u64) -> Vec<(u64, Vec<PathBuf>)> {
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    let mut pending = roots.to_vec();
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                if !entry.file_name().to_string_lossy().starts_with('.') && !excluded.contains(&path) {
+                    pending.push(path);
+                }
+            } else if file_type.is_file() {
+                let size = entry.metadata().map_or(0, |metadata| metadata.len());
+                if size >= min_size {
+                    by_size.entry(size).or_default().push(path);
+                }
+            }
+        }
+    }
+    let mut groups: Vec<(u64, Vec<PathBuf>)> = by_size
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|(size, mut paths)| {
+            paths.sort();
+            paths.dedup();
+            (size, paths)
+        })
+        .filter(|(_, paths)| paths.len() > 1)
+        .collect();
+    groups.sort();
+    groups
+}
+
+/// Groups of identical files of at least `min_size` bytes below `roots`, largest reclaimable
+/// space first.
+///
+/// Hard links to one file share its storage, so only one of them is listed.
+pub fn find_duplicates(roots: &[PathBuf], excluded: &[PathBuf], min_size: u64) -> Vec<DuplicateGroup> {
+    let candidates: Vec<(u64, Vec<PathBuf>)> = same_size_files(roots, excluded, min_size)
+        .into_iter()
+        .map(|(size, paths)| (size, without_hard_links(paths)))
+        .filter(|(_, paths)| paths.len() > 1)
+        .collect();
+
+    let sampled = regroup(candidates, sample_hash);
+    let confirmed = regroup(sampled, full_hash);
+
+    let mut groups: Vec<DuplicateGroup> =
+        confirmed.into_iter().map(|(size, paths)| DuplicateGroup { size, paths }).collect();
+    groups.sort_by(|a, b| b.reclaimable().cmp(&a.reclaimable()).then_with(|| a.paths.cmp(&b.paths)));
+    groups
+}
+
+/// `paths` with only the first of several hard links to the same file
+fn without_hard_links(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen = Vec::new();
+    paths
+        .into_iter()
+        .filter(|path| match platform::file_id(path) {
+            Some(id) if seen.contains(&id) => false,
+            Some(id) => {
+                seen.push(id);
+                true
+            }
+            None => true,
+        })
+        .collect()
+}
+
+/// Split each group by the hash of its files, dropping files that cannot be read and groups
+/// left with a single file
+fn regroup(groups: Vec<(u64, Vec<PathBuf>)>, hash: fn(&Path, u64) -> io::Result<u128>) -> Vec<(u64, Vec<PathBuf>)> {
+    let files: Vec<(u64, &Path)> = groups
+        .iter()
+        .flat_map(|(size, paths)| paths.iter().map(move |path| (*size, path.as_path())))
+        .collect();
+    let hashes = hash_parallel(&files, hash);
+
+    let mut by_hash: HashMap<(u64, u128), Vec<PathBuf>> = HashMap::new();
+    for ((size, path), hash) in files.into_iter().zip(hashes) {
+        if let Some(hash) = hash {
+            by_hash.entry((size, hash)).or_default().push(path.to_path_buf());
+        }
+    }
+    let mut regrouped: Vec<(u64, Vec<PathBuf>)> = by_hash
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|((size, _), mut paths)| {
+            paths.sort();
+            (size, paths)
+        })
+        .collect();
+    regrouped.sort();
+    regrouped
+}
+
+/// Hash every file on one thread per CPU, `None` for files that cannot be read
+fn hash_parallel(files: &[(u64, &Path)], hash: fn(&Path, u64) -> io::Result<u128>) -> Vec<Option<u128>> {
+    let workers = thread::available_parallelism().map_or(1, |n| n.get()).min(files.len()).max(1);
+    let next = AtomicUsize::new(0);
+    let hashes = Mutex::new(vec![None; files.len()]);
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some((size, path)) = files.get(index) else {
+                    break;
+                };
+                let hash = hash(path, *size).ok();
+                hashes.lock().unwrap_or_else(|e| e.into_inner())[index] = hash;
+            });
+        }
+    });
+    hashes.into_inner().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Hash of the first and last 64 KiB of a file of `size` bytes
+fn sample_hash(path: &Path, size: u64) -> io::Result<u128> {
+    let mut file = File::open(path)?;
+    let mut buffer = vec![0; SAMPLE_SIZE.min(size as usize)];
+    file.read_exact(&mut buffer)?;
+    let head = xxh3_64(&buffer);
+    file.seek(SeekFrom::Start(size.saturating_sub(buffer.len() as u64)))?;
+    file.read_exact(&mut buffer)?;
+    Ok((u128::from(head) << 64) | u128::from(xxh3_64(&buffer)))
+}
+
+/// 128-bit hash of a whole file
+fn full_hash(path: &Path, _size: u64) -> io::Result<u128> {
+    let mut file = File::open(path)?;
+    let mut hasher = Xxh3::new();
+    let mut buffer = vec![0; 1024 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(hasher.digest128());
+        }
+        hasher.update(&buffer[..read]);
+    }
+}
+
+/// Identical files below `roots`, outside `excluded`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Duplicates {
+    pub roots: Vec<PathBuf>,
+    pub excluded: Vec<PathBuf>,
+}
+
+impl Duplicates {
+    /// Folders left out: the target's own and the user's
+    fn excluded(&self, cx: &ItemContext<'_>) -> Vec<PathBuf> {
+        let mut excluded = self.excluded.clone();
+        excluded.extend(cx.excluded_paths().iter().cloned());
+        excluded
+    }
+
+    fn groups(&self, cx: &ItemContext<'_>) -> Vec<DuplicateGroup> {
+        find_duplicates(cx.roots(&self.roots), &self.excluded(cx), DUPLICATE_MIN_SIZE)
+    }
+}
+
+impl CleanTarget for Duplicates {
+    /// One breakdown entry per duplicate group, labelled with the copy that is kept. Only the
+    /// selected groups count towards the item's size.
+    fn scan_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for group in self.groups(cx) {
+            let others: Vec<String> = group.extra_copies().iter().map(|path| path.display().to_string()).collect();
+            let mut note = format!("{} 个相同的文件, 另有 {}", group.paths.len(), others.join(", "));
+            if cx.is_selected(group.kept()) {
+                result.files += others.len() as u64;
+                result.size_bytes += group.reclaimable();
+            } else {
+                note.push_str(&format!(", {}", NOT_SELECTED_NOTE));
+            }
+            result.has_data = true;
+            result.breakdown.push(BreakdownEntry {
+                label: group.kept().display().to_string(),
+                size_bytes: group.reclaimable(),
+                note,
+            });
+        }
+        result
+    }
+
+    /// Delete every copy but the kept one in each selected group. Contents are hashed again
+    /// first, so files changed since the scan are left alone.
+    fn clean_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for group in self.groups(cx) {
+            if !cx.is_selected(group.kept()) {
+                continue;
+            }
+            for path in group.extra_copies() {
+                cx.remove_file(path, group.size, &mut result);
+            }
+        }
+        result
+    }
+
+    /// Hashing is slow; files sharing their size with another one are only an upper bound
+    fn estimate_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let same_size = same_size_files(cx.roots(&self.roots), &self.excluded(cx), DUPLICATE_MIN_SIZE);
+        let extra: u64 = same_size.iter().map(|(_, paths)| paths.len() as u64 - 1).sum();
+        CleanupResult {
+            files: extra,
+            has_data: extra > 0,
+            ..CleanupResult::new()
+        }
+    }
+
+    fn validate_in(&self, cx: &ItemContext<'_>) -> ItemStatus {
+        cleanup_items::validate_roots(cx.fs(), cx.roots(&self.roots))
+    }
+
+    fn has_groups(&self) -> bool {
+        true
+    }
+}
+
+/// Item reporting identical files of 1 MiB or more in the home directory, outside `Library`,
+/// `AppData` and hidden directories.
+///
+/// Nothing is deleted until the user selects groups in the details, which stores them in the
+/// `selected_paths` option. Roots can be changed with `project_roots`. Disabled by default.
+pub fn duplicates_item(home: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "duplicate_files".to_string(),
+        name: "重复文件".to_string(),
+        description: format!(
+            "{} 中内容相同的大文件 (1 MB 以上), 在明细中选择要清理的组, 每组保留一份",
+            home.display()
+        ),
+        cleanup_type: CleanupType::Custom(Arc::new(Duplicates {
+            roots: vec![home.to_path_buf()],
+            excluded: DUPLICATE_EXCLUDED.iter().map(|dir| home.join(dir)).collect(),
+        })),
+        category: Category::System,
+        risk: RiskLevel::Moderate,
+        enabled: false,
+        ..Default::default()
+    }
+}
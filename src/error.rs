@@ -1,12 +1,171 @@
+use crate::cleaner::CleanResult;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::io;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+/// What a failed removal was deleting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Directory,
+    /// A symlink, junction or other reparse point, removed without touching its target
+    Link,
+}
+
+impl fmt::Display for EntryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EntryKind::File => "file",
+            EntryKind::Directory => "directory",
+            EntryKind::Link => "link",
+        })
+    }
+}
+
+/// Every error code with what it means, as `clean-rs errors` lists them. The codes are
+/// stable: scripts reading `--json` output branch on them, so they are never renamed.
+pub const ERROR_CODES: &[(&str, &str)] = &[
+    ("E_IO", "An I/O error without a more specific class"),
+    ("E_PERMISSION", "Access was denied; running as administrator (root) may help"),
+    ("E_NOT_FOUND", "The file or directory was already gone"),
+    ("E_IN_USE", "Another program has the file open"),
+    ("E_READ_DIR", "A directory could not be listed"),
+    ("E_METADATA", "The metadata of a file could not be read"),
+    ("E_REMOVE", "A file, directory or link could not be deleted"),
+    ("E_DELETE_FAILED", "A file could not be deleted"),
+    ("E_CANCELLED", "The operation was cancelled"),
+    ("E_PARTIAL", "A clean stopped partway after deleting some entries"),
+    ("E_MULTIPLE", "Several items of one operation failed"),
+    ("E_NOT_SUPPORTED", "The operation is not supported on this platform or setup"),
+    ("E_WINDOWS_API", "A Windows API call failed"),
+    ("E_PROTECTED_PATH", "The target is a volume root or mount point and was refused"),
+    ("E_INVALID_STATE", "The saved state file could not be parsed"),
+];
+
+/// All error codes with their descriptions, in the order of `ERROR_CODES`
+pub fn error_codes() -> &'static [(&'static str, &'static str)] {
+    ERROR_CODES
+}
+
+/// Code of a per-entry I/O failure: in use, permission or not found when the error says so,
+/// `E_IO` otherwise
+pub fn io_code(kind: io::ErrorKind, in_use: bool) -> &'static str {
+    match kind {
+        _ if in_use => "E_IN_USE",
+        io::ErrorKind::PermissionDenied => "E_PERMISSION",
+        io::ErrorKind::NotFound => "E_NOT_FOUND",
+        _ => "E_IO",
+    }
+}
+
+/// How a failure should be handled, whatever operation hit it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorClass {
+    /// Access was denied; administrator rights or clearing a read-only flag may help
+    Permission,
+    /// Another program has the file open, or the call was interrupted; trying again later may work
+    Transient,
+    /// The entry was already gone, which is harmless for a clean
+    Missing,
+    /// Anything else
+    Fatal,
+}
+
+impl ErrorClass {
+    /// Class of an I/O failure of `kind`, `in_use` meaning another program holds the file
+    pub fn of_io(kind: io::ErrorKind, in_use: bool) -> Self {
+        match kind {
+            _ if in_use => ErrorClass::Transient,
+            io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem => ErrorClass::Permission,
+            io::ErrorKind::NotFound => ErrorClass::Missing,
+            io::ErrorKind::Interrupted
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::ResourceBusy => ErrorClass::Transient,
+            _ => ErrorClass::Fatal,
+        }
+    }
+
+    /// Class of `error` as returned by a file system call
+    pub fn of(error: &io::Error) -> Self {
+        Self::of_io(error.kind(), crate::platform::is_in_use(error))
+    }
+
+    /// Word used in summaries, such as "3 permission, 1 transient"
+    pub fn label(&self) -> &'static str {
+        match self {
+            ErrorClass::Permission => "permission",
+            ErrorClass::Transient => "transient",
+            ErrorClass::Missing => "missing",
+            ErrorClass::Fatal => "fatal",
+        }
+    }
+}
+
+/// Counts of `classes` grouped by class, such as "3 permission, 1 transient", in the order
+/// the classes are declared
+pub fn summarize_classes(classes: impl IntoIterator<Item = ErrorClass>) -> String {
+    let mut counts: BTreeMap<ErrorClass, usize> = BTreeMap::new();
+    for class in classes {
+        *counts.entry(class).or_default() += 1;
+    }
+    let groups: Vec<String> = counts.iter().map(|(class, count)| format!("{} {}", count, class.label())).collect();
+    groups.join(", ")
+}
+
 /// Custom error types for the clean-rs application
 #[derive(Error, Debug)]
 pub enum CleanError {
+    /// An I/O error without a path, for the cases where none is known
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
 
+    #[error("Failed to read directory {}: {source}", .path.display())]
+    ReadDir {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Failed to read metadata of {}: {source}", .path.display())]
+    Metadata {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Failed to delete {kind} {}: {source}", .path.display())]
+    Remove {
+        path: PathBuf,
+        kind: EntryKind,
+        #[source]
+        source: io::Error,
+    },
+
+    /// The user stopped the operation before it finished
+    #[error("Cancelled")]
+    Cancelled,
+
+    /// Items of a multi-item operation that failed, by item id
+    #[error("{} items failed:{}", .0.len(), indented_failures(.0.iter().map(|(id, e)| (id.as_str(), e))))]
+    Multiple(Vec<(String, CleanError)>),
+
+    /// A clean stopped partway, after deleting what `result` counts
+    #[error(
+        "{cause} (stopped after deleting {} files and {} directories, {} bytes freed)",
+        .result.files_deleted,
+        .result.dirs_deleted,
+        .result.bytes_cleaned
+    )]
+    PartialFailure {
+        result: Box<CleanResult>,
+        #[source]
+        cause: Box<CleanError>,
+    },
+
     #[error("Failed to delete file: {path}")]
     DeleteFailed { path: String },
 
@@ -15,7 +174,139 @@ pub enum CleanError {
 
     #[error("Windows API error: {0}")]
     WindowsError(String),
+
+    #[error("Refusing to clean a volume root or mount point: {}", .0.display())]
+    RefusedMountPoint(PathBuf),
+
+    #[error("Invalid state file: {0}")]
+    InvalidState(#[from] serde_json::Error),
+}
+
+impl CleanError {
+    /// Stable code of the error's variant, listed in `ERROR_CODES`
+    pub fn code(&self) -> &'static str {
+        match self {
+            CleanError::Io(_) => "E_IO",
+            CleanError::ReadDir { .. } => "E_READ_DIR",
+            CleanError::Metadata { .. } => "E_METADATA",
+            CleanError::Remove { .. } => "E_REMOVE",
+            CleanError::Cancelled => "E_CANCELLED",
+            CleanError::PartialFailure { .. } => "E_PARTIAL",
+            CleanError::Multiple(_) => "E_MULTIPLE",
+            CleanError::DeleteFailed { .. } => "E_DELETE_FAILED",
+            CleanError::NotSupported(_) => "E_NOT_SUPPORTED",
+            CleanError::WindowsError(_) => "E_WINDOWS_API",
+            CleanError::RefusedMountPoint(_) => "E_PROTECTED_PATH",
+            CleanError::InvalidState(_) => "E_INVALID_STATE",
+        }
+    }
+
+    /// Listing the directory `path` failed
+    pub fn read_dir(path: &Path, source: io::Error) -> Self {
+        CleanError::ReadDir {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    /// Reading the metadata of `path` failed
+    pub fn metadata(path: &Path, source: io::Error) -> Self {
+        CleanError::Metadata {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    /// Deleting the `kind` of entry at `path` failed
+    pub fn remove(path: &Path, kind: EntryKind, source: io::Error) -> Self {
+        CleanError::Remove {
+            path: path.to_path_buf(),
+            kind,
+            source,
+        }
+    }
+
+    /// `cause` stopping a clean that already did what `result` counts. A clean that had not
+    /// deleted anything yet fails with the plain `cause`.
+    pub fn partial(result: CleanResult, cause: CleanError) -> Self {
+        if result.is_empty() {
+            return cause;
+        }
+        CleanError::PartialFailure {
+            result: Box::new(result),
+            cause: Box::new(cause),
+        }
+    }
+
+    /// Split off what a stopped clean had already done, for callers that want to report it.
+    /// Errors other than `PartialFailure` come back unchanged without a result.
+    pub fn into_partial_result(self) -> (Option<CleanResult>, CleanError) {
+        match self {
+            CleanError::PartialFailure { result, cause } => (Some(*result), *cause),
+            error => (None, error),
+        }
+    }
+
+    /// The path the failure is about, when the error records one
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            CleanError::PartialFailure { cause, .. } => cause.path(),
+            CleanError::ReadDir { path, .. }
+            | CleanError::Metadata { path, .. }
+            | CleanError::Remove { path, .. }
+            | CleanError::RefusedMountPoint(path) => Some(path),
+            CleanError::DeleteFailed { path } => Some(Path::new(path)),
+            _ => None,
+        }
+    }
+
+    /// The I/O error underneath, if the failure came from the file system
+    pub fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            CleanError::PartialFailure { cause, .. } => cause.io_error(),
+            CleanError::Io(source)
+            | CleanError::ReadDir { source, .. }
+            | CleanError::Metadata { source, .. }
+            | CleanError::Remove { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+
+    /// Kind of the underlying I/O error, if the failure came from the file system
+    pub fn io_kind(&self) -> Option<io::ErrorKind> {
+        self.io_error().map(io::Error::kind)
+    }
+
+    /// Kind of the underlying I/O error, `Other` for failures that are not I/O errors
+    pub fn kind(&self) -> io::ErrorKind {
+        self.io_kind().unwrap_or(io::ErrorKind::Other)
+    }
+
+    /// How the failure should be handled. Errors that are not I/O errors are fatal, except
+    /// that a stopped clean is classed by what stopped it.
+    pub fn classification(&self) -> ErrorClass {
+        match self {
+            CleanError::PartialFailure { cause, .. } => cause.classification(),
+            error => error.io_error().map_or(ErrorClass::Fatal, ErrorClass::of),
+        }
+    }
+}
+
+/// One line per failed item, `id: error`, each on a new indented line
+pub(crate) fn indented_failures<'a>(failures: impl Iterator<Item = (&'a str, &'a CleanError)>) -> String {
+    failures.map(|(id, error)| format!("\n  {}: {}", id, error)).collect()
+}
+
+/// Written as its code and message, such as
+/// `{"code": "E_NOT_SUPPORTED", "message": "Platform not supported: ..."}`
+impl Serialize for CleanError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("CleanError", 2)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.end()
+    }
 }
 
 /// Result type alias for cleaner error handling
-pub type Result<T> = std::result::Result<T, CleanError>;
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, CleanError>;
@@ -0,0 +1,222 @@
+//! Progress events emitted while items are scanned or cleaned, and the observers receiving them
+
+use crate::cleanup_items::{CleanErrorEntry, CleanupResult};
+use crate::platform::{self, FileHolder, OpenFiles};
+use crate::util::format_size;
+use crate::vfs::{Fs, RealFs};
+use std::cell::{Cell, OnceCell};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+/// Files processed between two `Progress` events for the same item
+const PROGRESS_INTERVAL: u64 = 256;
+
+/// Notification a `ChannelObserver` sends during a scan or clean
+#[derive(Debug, Clone)]
+pub enum CleanEvent {
+    /// Work on an item has begun
+    ItemStarted { id: String },
+    /// Running totals for the item currently being processed
+    Progress { id: String, files_done: u64, bytes_done: u64 },
+    /// The item is finished; `result` is what its scan or clean returned
+    ItemFinished { id: String, result: CleanupResult },
+    /// Every item of the batch has finished
+    AllDone,
+}
+
+/// Callbacks for programs following a scan or clean, registered with `CleanOptions::observe`.
+/// Every method does nothing by default. Items run on worker threads, so callbacks can come
+/// from several threads at once.
+///
+/// A callback that panics is caught and logged; the scan or clean goes on without it.
+pub trait CleanObserver: fmt::Debug + Send + Sync {
+    /// Work on the item `id`, called `name` in the UI, has begun
+    fn on_item_start(&self, _id: &str, _name: &str) {}
+
+    /// Running totals of the item, every few hundred files
+    fn on_progress(&self, _id: &str, _files_done: u64, _bytes_done: u64) {}
+
+    /// The item deleted `path`, freeing `bytes`; 0 for a directory
+    fn on_entry_deleted(&self, _id: &str, _path: &Path, _bytes: u64) {}
+
+    /// The item could not delete `error.path`
+    fn on_entry_failed(&self, _id: &str, _error: &CleanErrorEntry) {}
+
+    /// The item is finished; `result` is what its scan or clean returned
+    fn on_item_done(&self, _id: &str, _result: &CleanupResult) {}
+
+    /// Every item of the batch has finished
+    fn on_run_done(&self) {}
+}
+
+/// Run `callback` on every observer, logging instead of unwinding when one panics
+pub(crate) fn notify(observers: &[Arc<dyn CleanObserver>], name: &str, callback: impl Fn(&dyn CleanObserver)) {
+    for observer in observers {
+        if panic::catch_unwind(AssertUnwindSafe(|| callback(observer.as_ref()))).is_err() {
+            warn!("Observer {:?} panicked in {}; ignoring it", observer, name);
+        }
+    }
+}
+
+/// Writes every event to the `tracing` log: item and entry events at debug level, the end of
+/// the run at info level
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingObserver;
+
+impl CleanObserver for TracingObserver {
+    fn on_item_start(&self, id: &str, name: &str) {
+        debug!("Started {} ({})", name, id);
+    }
+
+    fn on_progress(&self, id: &str, files_done: u64, bytes_done: u64) {
+        debug!("{}: {} files, {} so far", id, files_done, format_size(bytes_done));
+    }
+
+    fn on_entry_deleted(&self, _id: &str, path: &Path, _bytes: u64) {
+        debug!("Deleted: {}", path.display());
+    }
+
+    fn on_entry_failed(&self, _id: &str, error: &CleanErrorEntry) {
+        debug!("{}", error.message);
+    }
+
+    fn on_item_done(&self, id: &str, result: &CleanupResult) {
+        debug!("Finished {}: {} files, {}, {} errors", id, result.files, result.size_human(), result.errors.len());
+    }
+
+    fn on_run_done(&self) {
+        info!("All items finished");
+    }
+}
+
+/// Forwards item events to a channel as `CleanEvent`s, which is how the TUI follows a job
+/// running on another thread. Entry events are not forwarded.
+#[derive(Debug, Clone)]
+pub struct ChannelObserver {
+    sender: Sender<CleanEvent>,
+}
+
+impl ChannelObserver {
+    pub fn new(sender: Sender<CleanEvent>) -> Self {
+        Self { sender }
+    }
+
+    fn send(&self, event: CleanEvent) {
+        // A receiver that hung up just means nobody is listening any more
+        let _ = self.sender.send(event);
+    }
+}
+
+impl CleanObserver for ChannelObserver {
+    fn on_item_start(&self, id: &str, _name: &str) {
+        self.send(CleanEvent::ItemStarted { id: id.to_string() });
+    }
+
+    fn on_progress(&self, id: &str, files_done: u64, bytes_done: u64) {
+        self.send(CleanEvent::Progress { id: id.to_string(), files_done, bytes_done });
+    }
+
+    fn on_item_done(&self, id: &str, result: &CleanupResult) {
+        self.send(CleanEvent::ItemFinished { id: id.to_string(), result: result.clone() });
+    }
+
+    fn on_run_done(&self) {
+        self.send(CleanEvent::AllDone);
+    }
+}
+
+/// Tracks one item's running totals and passes them and the item's other events to the
+/// observers. With in-use detection it also knows which files running processes hold open.
+pub(crate) struct Progress<'a> {
+    id: &'a str,
+    observers: &'a [Arc<dyn CleanObserver>],
+    files_done: Cell<u64>,
+    bytes_done: Cell<u64>,
+    detect_in_use: bool,
+    open_files: OnceCell<OpenFiles>,
+    fs: &'a dyn Fs,
+}
+
+impl<'a> Progress<'a> {
+    pub(crate) fn new(id: &'a str, observers: &'a [Arc<dyn CleanObserver>]) -> Self {
+        Self {
+            id,
+            observers,
+            files_done: Cell::new(0),
+            bytes_done: Cell::new(0),
+            detect_in_use: false,
+            open_files: OnceCell::new(),
+            fs: &RealFs,
+        }
+    }
+
+    /// Scan and delete through `fs` instead of the real file system
+    pub(crate) fn fs(mut self, fs: &'a dyn Fs) -> Self {
+        self.fs = fs;
+        self
+    }
+
+    pub(crate) fn file_system(&self) -> &'a dyn Fs {
+        self.fs
+    }
+
+    /// Look up the processes holding files before they are deleted
+    pub(crate) fn detect_in_use(mut self, detect: bool) -> Self {
+        self.detect_in_use = detect;
+        self
+    }
+
+    pub(crate) fn detects_in_use(&self) -> bool {
+        self.detect_in_use
+    }
+
+    /// Processes holding `path` open, when in-use detection is on. Open files are read once,
+    /// on the first call.
+    pub(crate) fn holders_of(&self, path: &Path) -> Vec<FileHolder> {
+        if !self.detect_in_use {
+            return Vec::new();
+        }
+        self.open_files.get_or_init(platform::open_files).holders_of(path)
+    }
+
+    /// Progress that is not reported anywhere
+    pub(crate) fn silent() -> Self {
+        Self::new("", &[])
+    }
+
+    /// Count one processed file of `bytes` bytes
+    pub(crate) fn file(&self, bytes: u64) {
+        if self.observers.is_empty() {
+            return;
+        }
+        let files_done = self.files_done.get() + 1;
+        self.files_done.set(files_done);
+        let bytes_done = self.bytes_done.get() + bytes;
+        self.bytes_done.set(bytes_done);
+        if files_done.is_multiple_of(PROGRESS_INTERVAL) {
+            notify(self.observers, "on_progress", |observer| observer.on_progress(self.id, files_done, bytes_done));
+        }
+    }
+
+    /// Count `path` as deleted, freeing `bytes`
+    pub(crate) fn deleted(&self, path: &Path, bytes: u64) {
+        notify(self.observers, "on_entry_deleted", |observer| observer.on_entry_deleted(self.id, path, bytes));
+        self.file(bytes);
+    }
+
+    pub(crate) fn failed(&self, error: &CleanErrorEntry) {
+        notify(self.observers, "on_entry_failed", |observer| observer.on_entry_failed(self.id, error));
+    }
+
+    pub(crate) fn started(&self, name: &str) {
+        notify(self.observers, "on_item_start", |observer| observer.on_item_start(self.id, name));
+    }
+
+    pub(crate) fn finished(&self, result: &CleanupResult) {
+        notify(self.observers, "on_item_done", |observer| observer.on_item_done(self.id, result));
+    }
+}
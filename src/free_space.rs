@@ -0,0 +1,97 @@
+//! Cleaning just enough to free a requested amount of space
+
+use crate::cleanup_items::{
+    priority_order, scan_enabled_parallel, CleanupItem, CleanupResult, DEFAULT_SCAN_PARALLELISM,
+};
+use crate::options::{CleanOptions, FreeOrder};
+use tracing::info;
+
+/// What a single item contributed to a `clean_until_freed` run
+#[derive(Debug, Clone)]
+pub struct ItemContribution {
+    pub id: String,
+    pub name: String,
+    pub result: CleanupResult,
+}
+
+/// Outcome of `clean_until_freed`
+#[derive(Debug, Clone, Default)]
+pub struct FreeReport {
+    pub target_bytes: u64,
+    pub freed_bytes: u64,
+    /// Items that were cleaned, in the order they were processed
+    pub contributions: Vec<ItemContribution>,
+    /// Ids of enabled items left untouched because the target was already reached
+    pub untouched: Vec<String>,
+}
+
+impl FreeReport {
+    pub fn target_reached(&self) -> bool {
+        self.freed_bytes >= self.target_bytes
+    }
+}
+
+/// Clean enabled items until at least `target_bytes` have been freed, then stop.
+///
+/// Items are visited in `options.free_order`; within an item the oldest files go first.
+pub fn clean_until_freed(items: &[CleanupItem], target_bytes: u64, options: &CleanOptions) -> FreeReport {
+    let _background = options.enter_background();
+    let enabled = |&i: &usize| items[i].enabled;
+    let mut order: Vec<usize> = match options.free_order {
+        FreeOrder::AsGiven => (0..items.len()).filter(enabled).collect(),
+        FreeOrder::LargestFirst => priority_order(items).into_iter().filter(enabled).collect(),
+    };
+    if options.free_order == FreeOrder::LargestFirst {
+        let mut sizes = vec![0u64; items.len()];
+        scan_enabled_parallel(items, DEFAULT_SCAN_PARALLELISM, |i, result| sizes[i] = result.size_bytes);
+        // Stable sort keeps priority order for items of equal size
+        order.sort_by_key(|&i| std::cmp::Reverse(sizes[i]));
+    }
+
+    let mut report = FreeReport {
+        target_bytes,
+        ..Default::default()
+    };
+    for index in order {
+        let item = &items[index];
+        if report.target_reached() {
+            report.untouched.push(item.id.clone());
+            continue;
+        }
+
+        let result = item.clean_at_most(target_bytes - report.freed_bytes, options);
+        // A report lists files without deleting them
+        if !result.report_only {
+            report.freed_bytes += result.size_bytes;
+        }
+        report.contributions.push(ItemContribution {
+            id: item.id.clone(),
+            name: item.name.clone(),
+            result,
+        });
+    }
+
+    options.run_done();
+    info!("Freed {} of {} requested bytes", report.freed_bytes, target_bytes);
+    report
+}
+
+/// Parse a human-readable size such as `5G`, `500MB`, `1.5GiB` or `4096`.
+///
+/// Units are binary (1K = 1024 bytes) and case-insensitive.
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number.parse().map_err(|_| format!("invalid size: {}", s))?;
+
+    let shift = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 0,
+        "k" | "kb" | "kib" => 10,
+        "m" | "mb" | "mib" => 20,
+        "g" | "gb" | "gib" => 30,
+        "t" | "tb" | "tib" => 40,
+        _ => return Err(format!("unknown size unit in: {}", s)),
+    };
+    Ok((number * (1u64 << shift) as f64) as u64)
+}
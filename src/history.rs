@@ -0,0 +1,206 @@
+//! Deleting old rows from browser history and cookie databases.
+//!
+//! History and cookies live in SQLite databases, so they are trimmed with `DELETE` and the
+//! file is shrunk with `VACUUM` instead of being removed.
+
+use crate::cleanup_items::{CleanErrorEntry, CleanupResult};
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info};
+
+/// Rows older than this many days are deleted unless the item's `max_age_days` says otherwise
+pub const DEFAULT_HISTORY_MAX_AGE_DAYS: u32 = 90;
+
+/// Microseconds between 1601-01-01 (Chromium's epoch) and 1970-01-01
+const CHROMIUM_EPOCH_OFFSET_US: i64 = 11_644_473_600_000_000;
+
+/// Shown when a database is locked by its browser
+const LOCKED_REASON: &str = "浏览器正在使用历史记录数据库, 请先关闭浏览器";
+
+/// Which browser database a file is, which decides the tables that are trimmed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryDbKind {
+    /// Chromium `History`
+    ChromiumHistory,
+    /// Chromium `Cookies` (`Network/Cookies` in newer versions)
+    ChromiumCookies,
+    /// Firefox `places.sqlite`; bookmarked places are kept
+    FirefoxPlaces,
+    /// Firefox `cookies.sqlite`
+    FirefoxCookies,
+}
+
+impl HistoryDbKind {
+    /// Statements run in order; `?1` is the cutoff timestamp in the database's own unit
+    fn statements(&self) -> &'static [&'static str] {
+        match self {
+            HistoryDbKind::ChromiumHistory => &[
+                "DELETE FROM visits WHERE visit_time < ?1",
+                "DELETE FROM urls WHERE last_visit_time < ?1 AND id NOT IN (SELECT url FROM visits)",
+                "DELETE FROM keyword_search_terms WHERE url_id NOT IN (SELECT id FROM urls)",
+            ],
+            HistoryDbKind::ChromiumCookies => &["DELETE FROM cookies WHERE last_access_utc < ?1"],
+            HistoryDbKind::FirefoxPlaces => &[
+                "DELETE FROM moz_historyvisits WHERE visit_date < ?1",
+                "DELETE FROM moz_places WHERE last_visit_date < ?1 \
+                 AND id NOT IN (SELECT fk FROM moz_bookmarks WHERE fk IS NOT NULL) \
+                 AND id NOT IN (SELECT place_id FROM moz_historyvisits)",
+            ],
+            HistoryDbKind::FirefoxCookies => &["DELETE FROM moz_cookies WHERE lastAccessed < ?1"],
+        }
+    }
+
+    /// Timestamp `max_age_days` before `now`, in the unit the database stores
+    pub fn cutoff(&self, now: SystemTime, max_age_days: u32) -> i64 {
+        let age = Duration::from_secs(u64::from(max_age_days) * 24 * 60 * 60);
+        let unix_us = now
+            .checked_sub(age)
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_micros() as i64);
+        match self {
+            HistoryDbKind::ChromiumHistory | HistoryDbKind::ChromiumCookies => unix_us + CHROMIUM_EPOCH_OFFSET_US,
+            HistoryDbKind::FirefoxPlaces | HistoryDbKind::FirefoxCookies => unix_us,
+        }
+    }
+}
+
+/// One history or cookie database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryDb {
+    pub path: PathBuf,
+    pub kind: HistoryDbKind,
+}
+
+impl HistoryDb {
+    pub fn new(path: PathBuf, kind: HistoryDbKind) -> Self {
+        Self { path, kind }
+    }
+}
+
+/// Report rows older than `max_age_days` and the bytes a `VACUUM` would reclaim.
+///
+/// Works on an in-memory copy of each database, so nothing on disk is modified.
+pub fn scan_history(databases: &[HistoryDb], max_age_days: u32) -> CleanupResult {
+    let now = SystemTime::now();
+    let mut result = CleanupResult::new();
+    for db in databases.iter().filter(|db| db.path.is_file()) {
+        match scan_database(db, now, max_age_days) {
+            Ok((rows, bytes)) => {
+                result.entries += rows;
+                result.size_bytes += bytes;
+                result.has_data = result.has_data || rows > 0 || bytes > 0;
+            }
+            Err(e) if is_locked(&e) => return CleanupResult::skipped(LOCKED_REASON),
+            Err(e) => result.errors.push(sqlite_error(&db.path, e)),
+        }
+    }
+    result
+}
+
+/// Rows deleted and bytes freed by trimming a copy of `db`
+fn scan_database(db: &HistoryDb, now: SystemTime, max_age_days: u32) -> rusqlite::Result<(u64, u64)> {
+    let source = Connection::open_with_flags(&db.path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut copy = Connection::open_in_memory()?;
+    // Copy every page in a single step
+    Backup::new(&source, &mut copy)?.run_to_completion(i32::MAX, Duration::ZERO, None)?;
+    drop(source);
+
+    let tx = copy.transaction()?;
+    let rows = delete_old_rows(&tx, db.kind, now, max_age_days)?;
+    // Pages on the freelist are what `VACUUM` gives back to the file system
+    let free_pages: i64 = tx.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+    let page_size: i64 = tx.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+    tx.rollback()?;
+    Ok((rows, (free_pages * page_size) as u64))
+}
+
+/// Delete rows older than `max_age_days` and `VACUUM` every database.
+///
+/// Refuses to touch any database if one of them is locked by a running browser.
+pub fn clean_history(databases: &[HistoryDb], max_age_days: u32) -> CleanupResult {
+    let now = SystemTime::now();
+    let mut result = CleanupResult::new();
+
+    // Take the write lock on every database first so a running browser stops the whole item
+    let mut locked = Vec::new();
+    for db in databases.iter().filter(|db| db.path.is_file()) {
+        match lock_database(&db.path) {
+            Ok(conn) => locked.push((db, conn)),
+            Err(e) if is_locked(&e) => return CleanupResult::skipped(LOCKED_REASON),
+            Err(e) => result.errors.push(sqlite_error(&db.path, e)),
+        }
+    }
+
+    for (db, conn) in locked {
+        match trim_database(db, conn, now, max_age_days) {
+            Ok((rows, bytes)) => {
+                info!("Trimmed {}: {} rows, {} bytes", db.path.display(), rows, bytes);
+                result.entries += rows;
+                result.size_bytes += bytes;
+                result.has_data = result.has_data || rows > 0 || bytes > 0;
+            }
+            Err(e) => result.errors.push(sqlite_error(&db.path, e)),
+        }
+    }
+    result
+}
+
+/// Open `path` and hold its write lock; fails at once if another process has it
+fn lock_database(path: &Path) -> rusqlite::Result<Connection> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+    conn.busy_timeout(Duration::ZERO)?;
+    conn.execute_batch("BEGIN IMMEDIATE")?;
+    Ok(conn)
+}
+
+/// Delete old rows from a locked database, then `VACUUM` it. Returns rows and bytes freed.
+fn trim_database(db: &HistoryDb, conn: Connection, now: SystemTime, max_age_days: u32) -> rusqlite::Result<(u64, u64)> {
+    let pages_before = page_count(&conn)?;
+    let rows = match delete_old_rows(&conn, db.kind, now, max_age_days) {
+        Ok(rows) => rows,
+        Err(e) => {
+            conn.execute_batch("ROLLBACK")?;
+            return Err(e);
+        }
+    };
+    conn.execute_batch("COMMIT")?;
+    conn.execute_batch("VACUUM")?;
+
+    let pages_after = page_count(&conn)?;
+    let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+    debug!("{}: {} -> {} pages", db.path.display(), pages_before, pages_after);
+    Ok((rows, (pages_before - pages_after).max(0) as u64 * page_size as u64))
+}
+
+fn page_count(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.query_row("PRAGMA page_count", [], |row| row.get(0))
+}
+
+/// Run the kind's `DELETE` statements and return the number of rows removed
+fn delete_old_rows(conn: &Connection, kind: HistoryDbKind, now: SystemTime, max_age_days: u32) -> rusqlite::Result<u64> {
+    let cutoff = kind.cutoff(now, max_age_days);
+    let mut rows = 0;
+    for sql in kind.statements() {
+        let mut statement = conn.prepare(sql)?;
+        rows += if statement.parameter_count() == 0 {
+            statement.execute([])?
+        } else {
+            statement.execute([cutoff])?
+        } as u64;
+    }
+    Ok(rows)
+}
+
+fn is_locked(error: &rusqlite::Error) -> bool {
+    matches!(
+        error.sqlite_error_code(),
+        Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked)
+    )
+}
+
+fn sqlite_error(path: &Path, error: rusqlite::Error) -> CleanErrorEntry {
+    CleanErrorEntry::new(path, &io::Error::other(error))
+}
@@ -0,0 +1,173 @@
+//! Finding the largest files, for a report that never deletes anything.
+
+use crate::cleanup_items::{
+    self, BreakdownEntry, Category, CleanTarget, CleanupItem, CleanupResult, CleanupType, ItemContext, ItemStatus,
+    RiskLevel, UNREADABLE_DIRS_LABEL,
+};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Number of files listed by the report
+pub const LARGE_FILES_LIMIT: usize = 50;
+
+/// A file listed by the report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LargeFile {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Last access, if the filesystem records it
+    pub accessed: Option<SystemTime>,
+}
+
+impl LargeFile {
+    /// Whole days since the file was last accessed
+    pub fn idle_days(&self) -> Option<u64> {
+        let idle = self.accessed?.elapsed().ok()?;
+        Some(idle.as_secs() / (24 * 60 * 60))
+    }
+}
+
+/// The largest files below some roots
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LargeFileReport {
+    /// Largest first
+    pub files: Vec<LargeFile>,
+    /// Directories that could not be listed and were skipped with everything below them
+    pub unreadable_dirs: u64,
+}
+
+/// The `limit` largest files below `roots`, outside the directories in `excluded`.
+///
+/// Symlinks are not followed. Roots that do not exist are ignored.
+pub fn largest_files(roots: &[PathBuf], excluded: &[PathBuf], limit: usize) -> LargeFileReport {
+    let mut report = LargeFileReport::default();
+    // Smallest of the files kept so far on top, so it is the one dropped
+    let mut largest: BinaryHeap<Reverse<(u64, PathBuf)>> = BinaryHeap::new();
+    let mut pending = roots.to_vec();
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(_) => {
+                report.unreadable_dirs += 1;
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                if !excluded.contains(&path) {
+                    pending.push(path);
+                }
+            } else if file_type.is_file() {
+                let size = entry.metadata().map_or(0, |metadata| metadata.len());
+                if largest.len() < limit {
+                    largest.push(Reverse((size, path)));
+                } else if largest.peek().is_some_and(|Reverse((smallest, _))| size > *smallest) {
+                    largest.pop();
+                    largest.push(Reverse((size, path)));
+                }
+            }
+        }
+    }
+
+    // Ascending order of `Reverse` is descending size
+    report.files = largest
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((size, path))| LargeFile {
+            accessed: fs::symlink_metadata(&path).and_then(|metadata| metadata.accessed()).ok(),
+            path,
+            size,
+        })
+        .collect();
+    report
+}
+
+/// The largest files below `roots`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LargeFiles {
+    pub roots: Vec<PathBuf>,
+}
+
+impl CleanTarget for LargeFiles {
+    /// The largest files below the roots, outside the user's exclusions, largest first.
+    /// Directories that cannot be read are skipped and counted in a last breakdown entry.
+    fn scan_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let report = largest_files(cx.roots(&self.roots), cx.excluded_paths(), LARGE_FILES_LIMIT);
+        let mut result = CleanupResult {
+            report_only: true,
+            ..CleanupResult::new()
+        };
+        for file in &report.files {
+            result.files += 1;
+            result.size_bytes += file.size;
+            result.has_data = true;
+            result.breakdown.push(BreakdownEntry {
+                label: file.path.display().to_string(),
+                size_bytes: file.size,
+                note: match file.idle_days() {
+                    Some(days) => format!("{} 天前访问", days),
+                    None => "访问时间未知".to_string(),
+                },
+            });
+        }
+        if report.unreadable_dirs > 0 {
+            result.breakdown.push(BreakdownEntry {
+                label: UNREADABLE_DIRS_LABEL.to_string(),
+                size_bytes: 0,
+                note: format!("跳过 {} 个目录", report.unreadable_dirs),
+            });
+        }
+        result
+    }
+
+    /// Nothing is deleted; the report is returned as is
+    fn clean_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        self.scan_in(cx)
+    }
+
+    /// Finding the largest files means walking every root; only report whether one exists
+    fn estimate_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        CleanupResult {
+            has_data: cx.roots(&self.roots).iter().any(|root| root.is_dir()),
+            report_only: true,
+            ..CleanupResult::new()
+        }
+    }
+
+    fn validate_in(&self, cx: &ItemContext<'_>) -> ItemStatus {
+        cleanup_items::validate_roots(cx.fs(), cx.roots(&self.roots))
+    }
+}
+
+/// Report of the 50 largest files in the home directory, with their last access.
+///
+/// Nothing is ever deleted: cleaning returns the scan unchanged. Roots can be changed with
+/// `project_roots` and folders left out with `excluded_paths`. Disabled by default.
+pub fn large_files_item(home: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "large_files".to_string(),
+        name: "大文件报告".to_string(),
+        description: format!(
+            "列出 {} 中最大的 {} 个文件及最后访问时间, 只报告不删除",
+            home.display(),
+            LARGE_FILES_LIMIT
+        ),
+        cleanup_type: CleanupType::Custom(Arc::new(LargeFiles {
+            roots: vec![home.to_path_buf()],
+        })),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        enabled: false,
+        ..Default::default()
+    }
+}
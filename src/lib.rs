@@ -2,9 +2,42 @@
 //!
 //! This library provides functionality for cleaning system files and directories.
 
+pub mod apps;
+pub mod browsers;
 pub mod cleaner;
+pub mod cleanup_items;
+pub mod devtools;
+pub mod docker;
+pub mod duplicates;
 pub mod error;
+pub mod events;
+pub mod free_space;
+pub mod history;
+pub mod large_files;
+pub mod linux;
+pub mod macos;
+pub mod options;
+pub mod patterns;
 pub mod platform;
+pub mod state;
+pub mod symlinks;
+pub mod system;
+pub mod util;
+pub mod vfs;
 
-pub use cleaner::{clean_directory, get_dir_size, CleanResult};
-pub use error::{CleanError, Result};
\ No newline at end of file
+pub use cleaner::{clean_directory, clean_directory_in, get_dir_size, get_dir_size_in, CleanResult, Cleaner};
+pub use cleanup_items::{
+    clean_enabled, estimate_ready_parallel, get_all_cleanup_items, get_cleanup_items_up_to,
+    items_by_category, needs_restore_point, priority_order, scan_enabled_parallel, scan_enabled_parallel_with,
+    toggle_category,
+    AppCheck, BreakdownEntry, Category, CleanErrorEntry, CleanTarget, CleanupItem, CleanupResult, CleanupType,
+    InUseEntry, ItemContext, ItemStatus, Report, RiskLevel,
+};
+pub use events::{ChannelObserver, CleanEvent, CleanObserver, TracingObserver};
+pub use free_space::{clean_until_freed, FreeReport, ItemContribution};
+pub use options::{CleanOptions, FreeOrder, ItemOptions};
+pub use patterns::TempPattern;
+pub use state::{apply_item_options, apply_item_state, load_item_state, save_item_state, ItemState};
+pub use error::{CleanError, EntryKind, ErrorClass, Result};
+pub use util::{format_size, format_size_si};
+pub use vfs::{Fs, MockFs, RealFs};
\ No newline at end of file
@@ -0,0 +1,1139 @@
+//! Package manager caches and system logs of Linux distributions
+
+use crate::cleanup_items::{
+    self, BreakdownEntry, Category, CleanErrorEntry, CleanTarget, CleanupItem, CleanupResult, CleanupType,
+    ItemContext, ItemStatus, RiskLevel,
+};
+use crate::devtools;
+use crate::docker;
+use crate::free_space;
+use crate::platform;
+use crate::util::format_size;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tracing::info;
+
+/// Where apt keeps downloaded packages
+pub const APT_ARCHIVES: &str = "/var/cache/apt/archives";
+
+/// dpkg's database of installed packages
+pub const DPKG_STATUS: &str = "/var/lib/dpkg/status";
+
+/// Lock files apt and dpkg hold while they install or download packages
+pub const APT_LOCKS: &[&str] = &["/var/lib/dpkg/lock-frontend", "/var/lib/dpkg/lock", "/var/cache/apt/archives/lock"];
+
+/// Package caches of dnf5, dnf and yum, newest first
+pub const RPM_CACHE_ROOTS: &[&str] = &["/var/cache/libdnf5", "/var/cache/dnf", "/var/cache/yum"];
+
+/// rpm's database lock and the pid files dnf and yum hold while they run
+pub const RPM_LOCKS: &[&str] = &[
+    "/var/lib/rpm/.rpm.lock",
+    "/var/cache/dnf/metadata_lock.pid",
+    "/var/cache/dnf/download_lock.pid",
+    "/var/lib/dnf/rpmdb_lock.pid",
+    "/var/run/yum.pid",
+];
+
+/// Where pacman keeps downloaded packages
+pub const PACMAN_CACHE: &str = "/var/cache/pacman/pkg";
+
+/// pacman's database of installed packages, one `name-version` directory each
+pub const PACMAN_LOCAL_DB: &str = "/var/lib/pacman/local";
+
+/// Lock file pacman creates while it runs
+pub const PACMAN_LOCK: &str = "/var/lib/pacman/db.lck";
+
+/// Versions of each package kept by the pacman item, like `paccache -rk2`
+pub const DEFAULT_PACMAN_KEEP_VERSIONS: u32 = 2;
+
+/// Size the journal is vacuumed down to unless the item's `vacuum_size` option says otherwise
+pub const DEFAULT_JOURNAL_MAX_SIZE: &str = "500M";
+
+/// snapd's state directory holding `snaps/` and `cache/`
+pub const SNAPD_DIR: &str = "/var/lib/snapd";
+
+/// Shared temporary directory cleaned after `TMP_MIN_AGE_DAYS`, like systemd-tmpfiles does
+pub const VAR_TMP: &str = "/var/tmp";
+
+/// Entries in `/tmp` untouched for this many days are deleted
+pub const TMP_MIN_AGE_DAYS: u32 = 2;
+
+/// Entries in `/var/tmp` untouched for this many days are deleted; it is meant to survive reboots
+pub const VAR_TMP_MIN_AGE_DAYS: u32 = 14;
+
+/// Entries of a shared temp directory that sessions and services rely on
+const SHARED_TEMP_KEEP: &[&str] = &[".X11-unix", ".ICE-unix", ".XIM-unix", ".font-unix", ".Test-unix"];
+
+/// File name apt gives a downloaded package, with the epoch colon escaped as `%3a`
+pub fn deb_file_name(package: &str, version: &str, architecture: &str) -> String {
+    format!("{}_{}_{}.deb", package, version.replace(':', "%3a"), architecture)
+}
+
+/// `.deb` file names of the package versions marked installed in a dpkg status file
+pub fn installed_deb_names(status: &str) -> HashSet<String> {
+    status
+        .split("\n\n")
+        .filter_map(|stanza| {
+            let field = |name: &str| {
+                stanza
+                    .lines()
+                    .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+                    .map(str::trim)
+            };
+            field("Status")?
+                .ends_with(" installed")
+                .then(|| Some(deb_file_name(field("Package")?, field("Version")?, field("Architecture")?)))?
+        })
+        .collect()
+}
+
+/// apt's download cache
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AptArchives {
+    pub dir: PathBuf,
+    pub dpkg_status: PathBuf,
+}
+
+impl AptArchives {
+    pub fn new(dir: impl Into<PathBuf>, dpkg_status: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            dpkg_status: dpkg_status.into(),
+        }
+    }
+
+    /// Downloaded `.deb` files, leaving out the installed versions when `keep_installed` is set
+    pub fn packages(&self, keep_installed: bool) -> Vec<PathBuf> {
+        let installed = if keep_installed {
+            installed_deb_names(&fs::read_to_string(&self.dpkg_status).unwrap_or_default())
+        } else {
+            HashSet::new()
+        };
+        files_in(&self.dir)
+            .into_iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "deb"))
+            .filter(|path| !path.file_name().is_some_and(|name| installed.contains(&*name.to_string_lossy())))
+            .collect()
+    }
+
+    /// Unfinished downloads in `partial/`
+    pub fn partial(&self) -> Vec<PathBuf> {
+        files_in(&self.dir.join("partial"))
+    }
+}
+
+/// Whether the APT item keeps the packages of installed versions
+fn keep_installed(cx: &ItemContext<'_>) -> bool {
+    cx.options().keep_installed.unwrap_or(false)
+}
+
+impl CleanTarget for AptArchives {
+    fn scan_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let packages = self.packages(keep_installed(cx));
+        let mut result = scan_files(cx, self.dir.join("*.deb").display().to_string(), &packages);
+        result.merge(scan_files(cx, self.dir.join("partial").display().to_string(), &self.partial()));
+        result
+    }
+
+    fn clean_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for path in self.packages(keep_installed(cx)).into_iter().chain(self.partial()) {
+            cx.remove_path(&path, &mut result);
+        }
+        result
+    }
+
+    fn estimate_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let found = (self.packages(keep_installed(cx)).len() + self.partial().len()) as u64;
+        CleanupResult {
+            files: found,
+            has_data: found > 0,
+            ..CleanupResult::new()
+        }
+    }
+
+    fn validate_in(&self, cx: &ItemContext<'_>) -> ItemStatus {
+        cleanup_items::validate_paths(cx.fs(), &[&self.dir])
+    }
+
+    fn root_paths(&self) -> Vec<&Path> {
+        vec![&self.dir]
+    }
+}
+
+/// First existing directory of `roots`
+pub fn rpm_cache_root(roots: &[&str]) -> Option<PathBuf> {
+    roots.iter().map(PathBuf::from).find(|root| root.is_dir())
+}
+
+/// Repository directories in a dnf or yum cache, sorted.
+///
+/// dnf keeps one directory per repository directly in the cache (`fedora-<hash>`), yum nests
+/// them below the architecture and release (`x86_64/7/base`); either way a repository holds
+/// `packages/` or `repodata/`.
+pub fn rpm_cache_repos(root: &Path) -> Vec<PathBuf> {
+    let mut repos = Vec::new();
+    collect_repos(root, 3, &mut repos);
+    repos.sort();
+    repos
+}
+
+fn collect_repos(dir: &Path, depth: usize, repos: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if !entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            continue;
+        }
+        let path = entry.path();
+        if path.join("packages").is_dir() || path.join("repodata").is_dir() {
+            repos.push(path);
+        } else if depth > 1 {
+            collect_repos(&path, depth - 1, repos);
+        }
+    }
+}
+
+/// A package file in pacman's cache, such as `linux-firmware-20240312.3f1c8b7c-1-any.pkg.tar.zst`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PacmanPackage {
+    pub name: String,
+    /// `[epoch:]pkgver-pkgrel`
+    pub version: String,
+    pub arch: String,
+}
+
+impl PacmanPackage {
+    /// Name of the package's directory in pacman's local database when this version is installed
+    pub fn local_name(&self) -> String {
+        format!("{}-{}", self.name, self.version)
+    }
+}
+
+/// Split a pacman package file name into name, version and architecture.
+///
+/// Package names may contain dashes, so the last three dash-separated fields are taken as
+/// version, release and architecture. Signatures and other files return `None`.
+pub fn parse_pacman_file_name(file_name: &str) -> Option<PacmanPackage> {
+    let (stem, compression) = file_name.split_once(".pkg.tar")?;
+    let compressed = |ext: &str| ext.bytes().all(|b| b.is_ascii_alphanumeric());
+    if !compression.is_empty() && !compression.strip_prefix('.').is_some_and(compressed) {
+        return None;
+    }
+    let mut fields = stem.rsplitn(4, '-');
+    let (arch, release, version, name) = (fields.next()?, fields.next()?, fields.next()?, fields.next()?);
+    if name.is_empty() || version.is_empty() || release.is_empty() || arch.is_empty() {
+        return None;
+    }
+    Some(PacmanPackage {
+        name: name.to_string(),
+        version: format!("{}-{}", version, release),
+        arch: arch.to_string(),
+    })
+}
+
+/// Compare two `[epoch:]pkgver-pkgrel` versions the way pacman's `vercmp` does
+pub fn pacman_vercmp(a: &str, b: &str) -> Ordering {
+    fn split(version: &str) -> (u64, &str, &str) {
+        let (epoch, rest) = match version.split_once(':') {
+            Some((epoch, rest)) => (epoch.parse().unwrap_or(0), rest),
+            None => (0, version),
+        };
+        let (version, release) = rest.rsplit_once('-').unwrap_or((rest, ""));
+        (epoch, version, release)
+    }
+    let (epoch_a, version_a, release_a) = split(a);
+    let (epoch_b, version_b, release_b) = split(b);
+    epoch_a
+        .cmp(&epoch_b)
+        .then_with(|| rpm_vercmp(version_a, version_b))
+        .then_with(|| rpm_vercmp(release_a, release_b))
+}
+
+/// rpm's segment-wise comparison: digits compare as numbers and beat letters, separators only split
+fn rpm_vercmp(mut a: &str, mut b: &str) -> Ordering {
+    let separator = |c: char| !c.is_ascii_alphanumeric();
+    loop {
+        a = a.trim_start_matches(separator);
+        b = b.trim_start_matches(separator);
+        if a.is_empty() || b.is_empty() {
+            break;
+        }
+        let numeric = a.starts_with(|c: char| c.is_ascii_digit());
+        let end = |text: &str| {
+            text.find(|c: char| if numeric { !c.is_ascii_digit() } else { !c.is_ascii_alphabetic() })
+                .unwrap_or(text.len())
+        };
+        let (segment_a, rest_a) = a.split_at(end(a));
+        let (segment_b, rest_b) = b.split_at(end(b));
+        if segment_b.is_empty() {
+            // Segments of different kinds: a number is newer than letters
+            return if numeric { Ordering::Greater } else { Ordering::Less };
+        }
+        let order = if numeric {
+            let (segment_a, segment_b) = (segment_a.trim_start_matches('0'), segment_b.trim_start_matches('0'));
+            segment_a.len().cmp(&segment_b.len()).then(segment_a.cmp(segment_b))
+        } else {
+            segment_a.cmp(segment_b)
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+        a = rest_a;
+        b = rest_b;
+    }
+    // `1.0` is older than `1.0.1` but newer than `1.0a`
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => Ordering::Equal,
+        (true, false) if b.starts_with(|c: char| c.is_ascii_alphabetic()) => Ordering::Greater,
+        (true, false) => Ordering::Less,
+        _ if a.starts_with(|c: char| c.is_ascii_alphabetic()) => Ordering::Less,
+        _ => Ordering::Greater,
+    }
+}
+
+/// pacman's download cache
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PacmanCache {
+    pub dir: PathBuf,
+    pub local_db: PathBuf,
+}
+
+impl PacmanCache {
+    pub fn new(dir: impl Into<PathBuf>, local_db: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            local_db: local_db.into(),
+        }
+    }
+
+    /// Package files to delete per package name, keeping the newest `keep` versions of each
+    /// package and the installed version when the local database is readable.
+    ///
+    /// Each package file comes with its `.sig` signature when there is one.
+    pub fn removable(&self, keep: u32) -> BTreeMap<String, Vec<PathBuf>> {
+        let installed: HashSet<String> = fs::read_dir(&self.local_db)
+            .map(|entries| entries.flatten().map(|entry| entry.file_name().to_string_lossy().into_owned()).collect())
+            .unwrap_or_default();
+
+        // Every architecture of a version counts as one version
+        let mut versions: BTreeMap<String, BTreeMap<String, Vec<PathBuf>>> = BTreeMap::new();
+        for path in files_in(&self.dir) {
+            let Some(package) = path.file_name().and_then(|name| parse_pacman_file_name(&name.to_string_lossy())) else {
+                continue;
+            };
+            let files = versions.entry(package.name.clone()).or_default().entry(package.version.clone()).or_default();
+            if !installed.contains(&package.local_name()) {
+                files.push(path);
+            }
+        }
+
+        let mut removable = BTreeMap::new();
+        for (name, by_version) in versions {
+            let mut by_version: Vec<_> = by_version.into_iter().collect();
+            by_version.sort_by(|(a, _), (b, _)| pacman_vercmp(b, a));
+            let old: Vec<PathBuf> = by_version
+                .into_iter()
+                .skip(keep as usize)
+                .flat_map(|(_, files)| files)
+                .flat_map(|path| {
+                    let mut signature = path.clone().into_os_string();
+                    signature.push(".sig");
+                    let signature = PathBuf::from(signature);
+                    let signature = signature.is_file().then_some(signature);
+                    std::iter::once(path).chain(signature)
+                })
+                .collect();
+            if !old.is_empty() {
+                removable.insert(name, old);
+            }
+        }
+        removable
+    }
+}
+
+/// Versions of each package the pacman item keeps
+fn keep_versions(cx: &ItemContext<'_>) -> u32 {
+    cx.options().keep_versions.unwrap_or(DEFAULT_PACMAN_KEEP_VERSIONS)
+}
+
+impl CleanTarget for PacmanCache {
+    /// Old files per package, one breakdown entry each
+    fn scan_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for (name, old) in self.removable(keep_versions(cx)) {
+            let mut part = scan_files(cx, name, &old);
+            if let Some(entry) = part.breakdown.last_mut() {
+                entry.note = format!("{} 个旧文件", part.files);
+            }
+            result.merge(part);
+        }
+        result
+    }
+
+    fn clean_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for path in self.removable(keep_versions(cx)).into_values().flatten() {
+            cx.remove_path(&path, &mut result);
+        }
+        result
+    }
+
+    fn estimate_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let found = self.removable(keep_versions(cx)).values().map(|old| old.len() as u64).sum();
+        CleanupResult {
+            files: found,
+            has_data: found > 0,
+            ..CleanupResult::new()
+        }
+    }
+
+    fn validate_in(&self, cx: &ItemContext<'_>) -> ItemStatus {
+        cleanup_items::validate_paths(cx.fs(), &[&self.dir])
+    }
+
+    fn root_paths(&self) -> Vec<&Path> {
+        vec![&self.dir]
+    }
+}
+
+/// Bytes in the `Archived and active journals take up 1.2G in the file system.` line printed
+/// by `journalctl --disk-usage`
+pub fn parse_journal_usage(output: &str) -> Option<u64> {
+    output.lines().find_map(|line| {
+        let size = line.split_once("take up ")?.1.split_whitespace().next()?;
+        free_space::parse_size(size).ok()
+    })
+}
+
+/// Bytes freed according to the `Vacuuming done, freed 1.2G of archived journals from ...`
+/// lines `journalctl --vacuum-*` prints, one per journal directory
+pub fn parse_vacuumed(output: &str) -> u64 {
+    output
+        .lines()
+        .filter_map(|line| line.split_once("freed ")?.1.split_whitespace().next())
+        .filter_map(|size| free_space::parse_size(size).ok())
+        .sum()
+}
+
+/// The systemd journal, measured and vacuumed with `journalctl`.
+///
+/// Journal files are never deleted directly: journald keeps the active ones open and only
+/// `journalctl --vacuum-*` knows which archived files are safe to remove.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Journal {
+    pub program: PathBuf,
+}
+
+impl Journal {
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self { program: program.into() }
+    }
+
+    /// Run journalctl and return everything it printed, as vacuuming reports on stderr
+    fn run(&self, args: &[String]) -> io::Result<String> {
+        let output = Command::new(&self.program).args(args).output()?;
+        let printed = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        if output.status.success() {
+            Ok(printed)
+        } else {
+            Err(io::Error::other(format!(
+                "{} {} exited with {}: {}",
+                self.program.display(),
+                args.join(" "),
+                output.status,
+                printed.trim()
+            )))
+        }
+    }
+
+    /// Bytes taken by archived and active journal files
+    pub fn disk_usage(&self) -> io::Result<u64> {
+        let output = self.run(&["--disk-usage".to_string()])?;
+        parse_journal_usage(&output)
+            .ok_or_else(|| io::Error::other(format!("unexpected journalctl output: {}", output.trim())))
+    }
+
+    /// Vacuum archived journal files down to `max_size` and, when set, `max_age_days`,
+    /// returning the bytes freed
+    pub fn vacuum(&self, max_size: &str, max_age_days: Option<u32>) -> io::Result<u64> {
+        let mut args = vec![format!("--vacuum-size={}", max_size)];
+        args.extend(max_age_days.map(|days| format!("--vacuum-time={}d", days)));
+        self.run(&args).map(|output| parse_vacuumed(&output))
+    }
+
+    /// Size limit to vacuum down to, from the item's `vacuum_size` option
+    fn max_size<'a>(&self, cx: &ItemContext<'a>) -> &'a str {
+        cx.options().vacuum_size.as_deref().unwrap_or(DEFAULT_JOURNAL_MAX_SIZE)
+    }
+}
+
+impl CleanTarget for Journal {
+    /// The journal's size and how much of it lies above the size limit
+    fn scan_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        let usage = match self.disk_usage() {
+            Ok(usage) => usage,
+            Err(e) => {
+                result.errors.push(CleanErrorEntry::new(&self.program, &e));
+                return result;
+            }
+        };
+        let limit = free_space::parse_size(self.max_size(cx)).unwrap_or(u64::MAX);
+        result.size_bytes = usage.saturating_sub(limit);
+        result.has_data = result.size_bytes > 0;
+        result.breakdown.push(BreakdownEntry {
+            label: "journal".to_string(),
+            size_bytes: result.size_bytes,
+            note: format!("共 {}, 保留 {}", format_size(usage), self.max_size(cx)),
+        });
+        result
+    }
+
+    fn clean_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        match self.vacuum(self.max_size(cx), cx.options().max_age_days) {
+            Ok(freed) => {
+                info!("journalctl vacuum freed {} bytes", freed);
+                result.size_bytes = freed;
+                result.has_data = freed > 0;
+            }
+            Err(e) => result.errors.push(CleanErrorEntry::new(&self.program, &e)),
+        }
+        result
+    }
+
+    fn estimate_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let scan = self.scan_in(cx);
+        CleanupResult {
+            has_data: scan.has_data,
+            errors: scan.errors,
+            ..CleanupResult::new()
+        }
+    }
+
+    fn validate(&self) -> ItemStatus {
+        match self.run(&["--disk-usage".to_string()]) {
+            Ok(_) => ItemStatus::Ready,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => ItemStatus::PathMissing,
+            Err(_) => ItemStatus::PermissionDenied,
+        }
+    }
+}
+
+/// A temporary directory every user writes to, such as `/tmp`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedTemp {
+    pub dir: PathBuf,
+    /// Only entries of this uid are deleted; `None` deletes entries of every user
+    pub owner: Option<u32>,
+}
+
+impl SharedTemp {
+    pub fn new(dir: impl Into<PathBuf>, owner: Option<u32>) -> Self {
+        Self { dir: dir.into(), owner }
+    }
+
+    /// The current user's entries, or everyone's when running as root
+    pub fn for_current_user(dir: impl Into<PathBuf>) -> Self {
+        let owner = if platform::is_elevated() { None } else { platform::current_uid() };
+        Self::new(dir, owner)
+    }
+
+    /// Top-level entries untouched for `min_age_days`, sorted.
+    ///
+    /// Entries of other users, sockets and FIFOs, X11 and ICE socket directories,
+    /// `systemd-private-*` directories and `*.lock` files are left alone, as are directories
+    /// that contain a socket, FIFO or lock file.
+    pub fn stale_entries(&self, min_age_days: u32) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut stale: Vec<PathBuf> = entries
+            .flatten()
+            .filter(|entry| !is_kept_temp_name(&entry.file_name().to_string_lossy()))
+            .filter(|entry| {
+                let Ok(metadata) = entry.path().symlink_metadata() else {
+                    return false;
+                };
+                self.owner.is_none_or(|uid| platform::file_owner(&metadata) == Some(uid))
+                    && newest_temp_use(&entry.path(), &metadata)
+                        .is_some_and(|newest| devtools::days_since(newest) >= min_age_days)
+            })
+            .map(|entry| entry.path())
+            .collect();
+        stale.sort();
+        stale
+    }
+}
+
+impl CleanTarget for SharedTemp {
+    fn scan_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for path in self.stale_entries(cx.min_age_days()) {
+            result.merge(cx.scan_path(&path));
+        }
+        result
+    }
+
+    fn clean_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for path in self.stale_entries(cx.min_age_days()) {
+            cx.remove_path(&path, &mut result);
+        }
+        result
+    }
+
+    fn estimate_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let stale = self.stale_entries(cx.min_age_days()).len() as u64;
+        CleanupResult {
+            entries: stale,
+            has_data: stale > 0,
+            ..CleanupResult::new()
+        }
+    }
+
+    fn validate_in(&self, cx: &ItemContext<'_>) -> ItemStatus {
+        cleanup_items::validate_paths(cx.fs(), &[&self.dir])
+    }
+
+    fn root_paths(&self) -> Vec<&Path> {
+        vec![&self.dir]
+    }
+}
+
+fn is_kept_temp_name(name: &str) -> bool {
+    SHARED_TEMP_KEEP.contains(&name) || name.starts_with("systemd-private-") || name.ends_with(".lock")
+}
+
+/// Newest modification time of a temp entry and everything below it, or `None` when the
+/// entry holds something that must not be deleted
+fn newest_temp_use(path: &Path, metadata: &fs::Metadata) -> Option<SystemTime> {
+    if platform::is_special_file(&metadata.file_type()) {
+        return None;
+    }
+    let mut newest = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    if !metadata.is_dir() {
+        return Some(newest);
+    }
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir).ok()?.flatten() {
+            let metadata = entry.path().symlink_metadata().ok()?;
+            let lock = entry.file_name().to_string_lossy().ends_with(".lock");
+            if lock || platform::is_special_file(&metadata.file_type()) {
+                return None;
+            }
+            newest = newest.max(metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH));
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            }
+        }
+    }
+    Some(newest)
+}
+
+/// Size of `files`, counted as found by `cx`, in one breakdown entry labelled `label`
+fn scan_files(cx: &ItemContext<'_>, label: String, files: &[PathBuf]) -> CleanupResult {
+    let mut result = CleanupResult::new();
+    for path in files {
+        if let Ok(metadata) = fs::metadata(path) {
+            result.files += 1;
+            result.size_bytes += metadata.len();
+            result.has_data = true;
+            cx.found(metadata.len());
+        }
+    }
+    if result.has_data {
+        result.breakdown.push(BreakdownEntry {
+            label,
+            size_bytes: result.size_bytes,
+            note: String::new(),
+        });
+    }
+    result
+}
+
+/// Files directly inside `dir`, sorted
+fn files_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_file()))
+        .map(|entry| entry.path())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Item deleting apt's downloaded packages, like `apt-get clean`.
+///
+/// The cache is world-readable, so it can be scanned without root; cleaning needs root and
+/// waits until no apt or dpkg operation holds the lock. With `keep_installed` the packages
+/// of installed versions are kept, like a safer `apt-get autoclean`.
+pub fn apt_cache_item(archives: AptArchives, locks: &[&str]) -> CleanupItem {
+    CleanupItem {
+        id: "apt_cache".to_string(),
+        name: "APT 软件包缓存".to_string(),
+        description: format!("apt 下载的 .deb 软件包 (清理需要 root 权限): {}", archives.dir.display()),
+        cleanup_type: CleanupType::Custom(Arc::new(archives)),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        enabled: true,
+        requires_admin: true,
+        busy_locks: locks.iter().map(PathBuf::from).collect(),
+        ..Default::default()
+    }
+}
+
+/// Item deleting the downloaded packages and metadata of dnf or yum, per repository.
+///
+/// Only files are deleted so the repository layout stays in place. Like the apt item it can
+/// be scanned without root, needs root to clean and waits while rpm, dnf or yum is running.
+pub fn dnf_cache_item(root: &Path, locks: &[&str]) -> CleanupItem {
+    CleanupItem {
+        id: "dnf_cache".to_string(),
+        name: "DNF / YUM 软件包缓存".to_string(),
+        description: format!("dnf 或 yum 下载的软件包和仓库元数据 (清理需要 root 权限): {}", root.display()),
+        cleanup_type: CleanupType::Directories(rpm_cache_repos(root)),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        enabled: true,
+        files_only: true,
+        requires_admin: true,
+        busy_locks: locks.iter().map(PathBuf::from).collect(),
+        ..Default::default()
+    }
+}
+
+/// Item deleting old package versions from pacman's cache, like `paccache -rk2`.
+///
+/// The newest versions of each package (two unless the `keep_versions` option says otherwise)
+/// and the installed versions are kept. Cleaning needs root and waits while pacman runs.
+pub fn pacman_cache_item(cache: PacmanCache, lock: &str) -> CleanupItem {
+    CleanupItem {
+        id: "pacman_cache".to_string(),
+        name: "Pacman 软件包缓存".to_string(),
+        description: format!("pacman 下载的旧版本软件包 (清理需要 root 权限): {}", cache.dir.display()),
+        cleanup_type: CleanupType::Custom(Arc::new(cache)),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        enabled: true,
+        requires_admin: true,
+        busy_locks: vec![PathBuf::from(lock)],
+        ..Default::default()
+    }
+}
+
+/// Item vacuuming the systemd journal down to a size limit (500M unless the `vacuum_size`
+/// option says otherwise) and optionally an age limit from `max_age_days`.
+///
+/// Scanning reports how far the journal is above the size limit; cleaning needs root.
+pub fn journal_item(journal: Journal) -> CleanupItem {
+    CleanupItem {
+        id: "journal".to_string(),
+        name: "systemd 日志".to_string(),
+        description: "用 journalctl --vacuum-size 清理旧的系统日志 (清理需要 root 权限)".to_string(),
+        cleanup_type: CleanupType::Custom(Arc::new(journal)),
+        category: Category::System,
+        risk: RiskLevel::Moderate,
+        enabled: false,
+        requires_admin: true,
+        ..Default::default()
+    }
+}
+
+/// Item deleting old entries of a shared temp directory such as `/tmp` or `/var/tmp`.
+///
+/// Unlike emptying a private temp directory this keeps sockets, session directories and
+/// other users' files; see [`SharedTemp::stale_entries`].
+pub fn shared_temp_item(id: &str, temp: SharedTemp, min_age_days: u32) -> CleanupItem {
+    let owner = if temp.owner.is_some() { "当前用户" } else { "所有用户" };
+    CleanupItem {
+        id: id.to_string(),
+        name: format!("临时文件目录 ({})", temp.dir.display()),
+        description: format!(
+            "{} 中{}超过 {} 天未修改的文件, 保留套接字和会话目录",
+            temp.dir.display(),
+            owner,
+            min_age_days
+        ),
+        cleanup_type: CleanupType::Custom(Arc::new(temp)),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        priority: 60,
+        min_age_days,
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+/// Cache directories of Flatpak apps, `~/.var/app/<app id>/cache`, sorted
+pub fn flatpak_cache_dirs(home: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(home.join(".var").join("app")) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path().join("cache"))
+        .filter(|cache| cache.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Item emptying the cache directory of every Flatpak app, sized per app
+pub fn flatpak_cache_item(home: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "flatpak_cache".to_string(),
+        name: "Flatpak 应用缓存".to_string(),
+        description: format!("Flatpak 应用的缓存目录: {}", home.join(".var/app/*/cache").display()),
+        cleanup_type: CleanupType::Directories(flatpak_cache_dirs(home)),
+        category: Category::Applications,
+        risk: RiskLevel::Safe,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// Bytes in a size printed by flatpak, such as `1.2 GB`, `650.3 MB` or `512 bytes`.
+///
+/// Flatpak uses decimal units.
+pub fn parse_flatpak_size(text: &str) -> Option<u64> {
+    let compact: String = text.split_whitespace().collect();
+    docker::parse_size(compact.strip_suffix("bytes").map_or(&compact, |number| number))
+}
+
+/// `(ref, size)` lines of `flatpak list --runtime --columns=ref,size`, or the runtime refs of
+/// `flatpak list --app --columns=runtime` with a size of 0
+pub fn parse_flatpak_list(output: &str) -> Vec<(String, u64)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split('\t');
+            let reference = columns.next()?.trim();
+            let size = columns.next().and_then(parse_flatpak_size).unwrap_or(0);
+            (!reference.is_empty()).then(|| (reference.to_string(), size))
+        })
+        .collect()
+}
+
+/// Installed runtimes no app needs, roughly as `flatpak uninstall --unused` finds them.
+///
+/// A runtime is used when an app names it as its runtime; its extensions such as `.Locale`
+/// share its id as prefix and are used as well. Graphics drivers (`.GL.` and `.GL32.`) serve
+/// every runtime and are never reported.
+pub fn unused_flatpak_runtimes(runtimes: &[(String, u64)], app_runtimes: &[String]) -> Vec<(String, u64)> {
+    let used_ids: Vec<&str> = app_runtimes.iter().filter_map(|reference| reference.split('/').next()).collect();
+    runtimes
+        .iter()
+        .filter(|(reference, _)| !app_runtimes.contains(reference))
+        .filter(|(reference, _)| {
+            let id = reference.split('/').next().unwrap_or_default();
+            let extends_used = used_ids
+                .iter()
+                .any(|used| id.strip_prefix(used).is_some_and(|rest| rest.starts_with('.')));
+            !extends_used && !id.contains(".GL.") && !id.contains(".GL32.")
+        })
+        .cloned()
+        .collect()
+}
+
+/// Flatpak runtimes no installed app needs, removed with `flatpak uninstall --unused`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlatpakRuntimes {
+    pub program: PathBuf,
+}
+
+impl FlatpakRuntimes {
+    pub fn new(program: PathBuf) -> Self {
+        Self { program }
+    }
+
+    /// Run flatpak and return its stdout, or an error carrying its stderr
+    fn run(&self, args: &[&str]) -> io::Result<String> {
+        let output = Command::new(&self.program).args(args).output()?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            Err(io::Error::other(format!(
+                "{} {} exited with {}: {}",
+                self.program.display(),
+                args.join(" "),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+
+    /// Installed runtimes with their sizes
+    fn runtimes(&self) -> io::Result<Vec<(String, u64)>> {
+        self.run(&["list", "--runtime", "--columns=ref,size"]).map(|output| parse_flatpak_list(&output))
+    }
+
+    pub fn unused(&self) -> io::Result<Vec<(String, u64)>> {
+        let apps = self.run(&["list", "--app", "--columns=runtime"])?;
+        let app_runtimes: Vec<String> = parse_flatpak_list(&apps).into_iter().map(|(reference, _)| reference).collect();
+        Ok(unused_flatpak_runtimes(&self.runtimes()?, &app_runtimes))
+    }
+
+    fn failed(&self, error: &io::Error) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        result.errors.push(CleanErrorEntry::new(&self.program, error));
+        result
+    }
+
+    /// Aggressive item: removed runtimes must be downloaded again by the next app that needs them
+    pub fn item(self) -> CleanupItem {
+        CleanupItem {
+            id: "flatpak_runtimes".to_string(),
+            name: "Flatpak 未使用的运行时".to_string(),
+            description: "没有应用使用的 Flatpak 运行时 (flatpak uninstall --unused)".to_string(),
+            category: Category::Applications,
+            risk: RiskLevel::Aggressive,
+            enabled: false,
+            cleanup_type: CleanupType::Custom(Arc::new(self)),
+            ..Default::default()
+        }
+    }
+}
+
+impl CleanTarget for FlatpakRuntimes {
+    fn scan(&self) -> CleanupResult {
+        let unused = match self.unused() {
+            Ok(unused) => unused,
+            Err(e) => return self.failed(&e),
+        };
+        let mut result = CleanupResult::new();
+        for (reference, size) in unused {
+            result.size_bytes += size;
+            result.entries += 1;
+            result.breakdown.push(BreakdownEntry {
+                label: reference,
+                size_bytes: size,
+                note: String::new(),
+            });
+        }
+        result.has_data = result.entries > 0;
+        result
+    }
+
+    /// Uninstall the unused runtimes; the freed size is that of the runtimes that disappeared
+    fn clean(&self) -> CleanupResult {
+        let before = match self.runtimes() {
+            Ok(runtimes) => runtimes,
+            Err(e) => return self.failed(&e),
+        };
+        if let Err(e) = self.run(&["uninstall", "--unused", "--noninteractive", "-y"]) {
+            return self.failed(&e);
+        }
+        let after: Vec<String> = match self.runtimes() {
+            Ok(runtimes) => runtimes.into_iter().map(|(reference, _)| reference).collect(),
+            Err(e) => return self.failed(&e),
+        };
+        let mut result = CleanupResult::new();
+        for (_, size) in before.iter().filter(|(reference, _)| !after.contains(reference)) {
+            result.entries += 1;
+            result.size_bytes += size;
+        }
+        result.has_data = result.entries > 0;
+        info!("flatpak uninstall --unused removed {} runtimes", result.entries);
+        result
+    }
+
+    fn validate(&self) -> ItemStatus {
+        match self.runtimes() {
+            Ok(_) => ItemStatus::Ready,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => ItemStatus::PathMissing,
+            Err(_) => ItemStatus::ServiceUnavailable,
+        }
+    }
+}
+
+/// A snap revision listed by `snap list --all`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapRevision {
+    pub name: String,
+    pub revision: String,
+    pub disabled: bool,
+}
+
+impl SnapRevision {
+    /// File snapd mounts the revision from, such as `core_16928.snap`
+    pub fn file_name(&self) -> String {
+        format!("{}_{}.snap", self.name, self.revision)
+    }
+}
+
+/// Parse `snap list --all`: the header, then name, version, revision, tracking, publisher and
+/// notes columns; `disabled` in the notes marks a revision kept only for rollback
+pub fn parse_snap_list(output: &str) -> Vec<SnapRevision> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            if columns.len() < 4 {
+                return None;
+            }
+            Some(SnapRevision {
+                name: columns[0].to_string(),
+                revision: columns[2].to_string(),
+                disabled: columns[columns.len() - 1].split(',').any(|note| note == "disabled"),
+            })
+        })
+        .collect()
+}
+
+/// Disabled snap revisions removed with `snap remove --revision`, plus snapd's download cache
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapRevisions {
+    pub program: PathBuf,
+    /// snapd's state directory, normally `/var/lib/snapd`
+    pub snapd_dir: PathBuf,
+}
+
+impl SnapRevisions {
+    pub fn new(program: PathBuf, snapd_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            program,
+            snapd_dir: snapd_dir.into(),
+        }
+    }
+
+    /// Run snap and return its stdout, or an error carrying its stderr
+    fn run(&self, args: &[&str]) -> io::Result<String> {
+        let output = Command::new(&self.program).args(args).output()?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            Err(io::Error::other(format!(
+                "{} {} exited with {}: {}",
+                self.program.display(),
+                args.join(" "),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+
+    /// Revisions kept only for rollback
+    pub fn disabled(&self) -> io::Result<Vec<SnapRevision>> {
+        let output = self.run(&["list", "--all"])?;
+        Ok(parse_snap_list(&output).into_iter().filter(|revision| revision.disabled).collect())
+    }
+
+    fn snap_size(&self, revision: &SnapRevision) -> u64 {
+        fs::metadata(self.snapd_dir.join("snaps").join(revision.file_name())).map_or(0, |metadata| metadata.len())
+    }
+
+    /// Item removing disabled revisions; disabled when snap is not installed
+    pub fn item(self, installed: bool) -> CleanupItem {
+        CleanupItem {
+            id: "snap_revisions".to_string(),
+            name: "Snap 旧版本".to_string(),
+            description: "snapd 保留用于回滚的已禁用版本和下载缓存 (清理需要 root 权限)".to_string(),
+            category: Category::System,
+            risk: RiskLevel::Moderate,
+            enabled: installed,
+            requires_admin: true,
+            cleanup_type: CleanupType::Custom(Arc::new(self)),
+            ..Default::default()
+        }
+    }
+}
+
+impl CleanTarget for SnapRevisions {
+    fn scan(&self) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        match self.disabled() {
+            Ok(disabled) => {
+                for revision in disabled {
+                    let size = self.snap_size(&revision);
+                    result.files += 1;
+                    result.size_bytes += size;
+                    result.breakdown.push(BreakdownEntry {
+                        label: format!("{} ({})", revision.name, revision.revision),
+                        size_bytes: size,
+                        note: "已禁用".to_string(),
+                    });
+                }
+            }
+            Err(e) => result.errors.push(CleanErrorEntry::new(&self.program, &e)),
+        }
+        let cache = self.snapd_dir.join("cache");
+        let cached = files_in(&cache);
+        let cache_size: u64 = cached.iter().filter_map(|path| fs::metadata(path).ok()).map(|m| m.len()).sum();
+        if !cached.is_empty() {
+            result.files += cached.len() as u64;
+            result.size_bytes += cache_size;
+            result.breakdown.push(BreakdownEntry {
+                label: cache.display().to_string(),
+                size_bytes: cache_size,
+                note: String::new(),
+            });
+        }
+        result.has_data = result.files > 0;
+        result
+    }
+
+    fn clean(&self) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        match self.disabled() {
+            Ok(disabled) => {
+                for revision in disabled {
+                    let size = self.snap_size(&revision);
+                    match self.run(&["remove", &revision.name, "--revision", &revision.revision]) {
+                        Ok(_) => {
+                            info!("Removed snap {} revision {}", revision.name, revision.revision);
+                            result.files += 1;
+                            result.size_bytes += size;
+                        }
+                        Err(e) => result.errors.push(CleanErrorEntry::new(&self.program, &e)),
+                    }
+                }
+            }
+            Err(e) => result.errors.push(CleanErrorEntry::new(&self.program, &e)),
+        }
+        for path in files_in(&self.snapd_dir.join("cache")) {
+            let size = fs::metadata(&path).map_or(0, |metadata| metadata.len());
+            match fs::remove_file(&path) {
+                Ok(()) => {
+                    result.files += 1;
+                    result.size_bytes += size;
+                }
+                Err(e) => result.errors.push(CleanErrorEntry::new(&path, &e)),
+            }
+        }
+        result.has_data = result.files > 0;
+        result
+    }
+
+    fn validate(&self) -> ItemStatus {
+        match self.run(&["version"]) {
+            Ok(_) => ItemStatus::Ready,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => ItemStatus::PathMissing,
+            Err(_) => ItemStatus::ServiceUnavailable,
+        }
+    }
+}
+
+/// Snap old revision item; without a `snap` program it is listed disabled and unavailable
+pub fn snap_item(program: Option<PathBuf>, snapd_dir: &Path) -> CleanupItem {
+    let installed = program.is_some();
+    let program = program.unwrap_or_else(|| PathBuf::from("snap"));
+    SnapRevisions::new(program, snapd_dir).item(installed)
+}
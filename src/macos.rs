@@ -0,0 +1,347 @@
+//! Caches, logs and developer data kept below `~/Library` on macOS
+
+use crate::cleanup_items::{
+    BreakdownEntry, Category, CleanErrorEntry, CleanTarget, CleanupItem, CleanupResult, CleanupType, ItemStatus,
+    RiskLevel,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use tracing::info;
+
+/// Crash and hang reports of system processes, readable by admin users
+pub const SYSTEM_DIAGNOSTIC_REPORTS: &str = "/Library/Logs/DiagnosticReports";
+
+/// Caches in `~/Library/Caches` left alone: Safari has its own item, the others hold sync
+/// or account state that apps do not rebuild cleanly
+pub const EXCLUDED_CACHES: &[&str] = &[
+    "com.apple.Safari",
+    "CloudKit",
+    "com.apple.bird",
+    "com.apple.akd",
+    "com.apple.HomeKit",
+    "com.apple.containermanagerd",
+    "FamilyCircle",
+];
+
+/// Files in `~/Library/Caches` younger than this are kept, as running apps may still use them
+pub const CACHES_MIN_AGE_DAYS: u32 = 1;
+
+/// Logs younger than this are kept for troubleshooting recent problems
+pub const LOGS_MIN_AGE_DAYS: u32 = 7;
+
+pub fn library_dir(home: &Path) -> PathBuf {
+    home.join("Library")
+}
+
+/// One directory per app or bundle id in `~/Library/Caches`, without the excluded ones, sorted
+pub fn user_cache_dirs(home: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(library_dir(home).join("Caches")) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .filter(|entry| !EXCLUDED_CACHES.contains(&&*entry.file_name().to_string_lossy()))
+        .map(|entry| entry.path())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Item emptying the app caches in `~/Library/Caches`, sized per bundle.
+///
+/// Directories protected by privacy settings are listed as needing Full Disk Access.
+pub fn user_caches_item(home: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "macos_caches".to_string(),
+        name: "用户缓存".to_string(),
+        description: format!("应用缓存: {}", library_dir(home).join("Caches").display()),
+        cleanup_type: CleanupType::Directories(user_cache_dirs(home)),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        min_age_days: CACHES_MIN_AGE_DAYS,
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+/// `~/Library/Logs`, plus the system diagnostic reports when they can be read
+pub fn log_dirs(home: &Path, system_reports: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![library_dir(home).join("Logs")];
+    if fs::read_dir(system_reports).is_ok() {
+        dirs.push(system_reports.to_path_buf());
+    }
+    dirs
+}
+
+/// Item deleting logs and crash reports older than a week
+pub fn logs_item(home: &Path, system_reports: &Path) -> CleanupItem {
+    let dirs = log_dirs(home, system_reports);
+    let list: Vec<String> = dirs.iter().map(|dir| dir.display().to_string()).collect();
+    CleanupItem {
+        id: "macos_logs".to_string(),
+        name: "日志和诊断报告".to_string(),
+        description: format!("应用日志和崩溃报告: {}", list.join(", ")),
+        cleanup_type: CleanupType::Directories(dirs),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        min_age_days: LOGS_MIN_AGE_DAYS,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// `~/Library/Developer`, home of Xcode's and the simulators' data
+pub fn developer_dir(home: &Path) -> PathBuf {
+    library_dir(home).join("Developer")
+}
+
+/// Subdirectories of `dir`, sorted
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .map(|entry| entry.path())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Item deleting Xcode's build products and indexes, sized per project.
+///
+/// `Xcode/Archives` is never touched: it holds the dSYMs of shipped releases.
+pub fn derived_data_item(home: &Path) -> CleanupItem {
+    let derived_data = developer_dir(home).join("Xcode").join("DerivedData");
+    CleanupItem {
+        id: "xcode_derived_data".to_string(),
+        name: "Xcode DerivedData".to_string(),
+        description: format!("Xcode 构建产物和索引, 会在下次构建时重新生成: {}", derived_data.display()),
+        cleanup_type: CleanupType::Directories(subdirs(&derived_data)),
+        category: Category::DevTools,
+        risk: RiskLevel::Safe,
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+/// OS version of a DeviceSupport directory such as `17.4 (21E219)` or `iPhone15,2 17.4 (21E219)`,
+/// as numbers for comparing
+pub fn device_support_version(name: &str) -> Vec<u32> {
+    let before_build = name.split(" (").next().unwrap_or(name);
+    let version = before_build.split_whitespace().last().unwrap_or_default();
+    version.split('.').map(|part| part.parse().unwrap_or(0)).collect()
+}
+
+/// DeviceSupport directories of OS versions older than the newest one of each platform
+/// (iOS, watchOS, tvOS), which devices that have since updated no longer need
+pub fn stale_device_support(home: &Path) -> Vec<PathBuf> {
+    let xcode = developer_dir(home).join("Xcode");
+    let mut stale = Vec::new();
+    for platform in ["iOS DeviceSupport", "watchOS DeviceSupport", "tvOS DeviceSupport"] {
+        let versions: Vec<(Vec<u32>, PathBuf)> = subdirs(&xcode.join(platform))
+            .into_iter()
+            .map(|dir| (device_support_version(&dir.file_name().unwrap_or_default().to_string_lossy()), dir))
+            .collect();
+        let newest = versions.iter().map(|(version, _)| version).max().cloned();
+        stale.extend(versions.into_iter().filter(|(version, _)| Some(version) != newest.as_ref()).map(|(_, dir)| dir));
+    }
+    stale
+}
+
+/// Item deleting debug symbols copied from devices for OS versions they no longer run, sized
+/// per version
+pub fn device_support_item(home: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "xcode_device_support".to_string(),
+        name: "Xcode 旧设备支持文件".to_string(),
+        description: "iOS / watchOS / tvOS DeviceSupport 中较旧系统版本的调试符号, 再次连接该版本的设备时会重新复制"
+            .to_string(),
+        cleanup_type: CleanupType::Directories(stale_device_support(home)),
+        category: Category::DevTools,
+        risk: RiskLevel::Moderate,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// Simulator cache directories, with `dyld` split into one entry per runtime build
+pub fn simulator_cache_dirs(home: &Path) -> Vec<PathBuf> {
+    let caches = developer_dir(home).join("CoreSimulator").join("Caches");
+    let mut dirs = Vec::new();
+    for dir in subdirs(&caches) {
+        if dir.file_name().is_some_and(|name| name == "dyld") {
+            dirs.extend(subdirs(&dir));
+        } else {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+/// Item emptying the simulators' shared caches, which they rebuild on next boot
+pub fn simulator_caches_item(home: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "simulator_caches".to_string(),
+        name: "模拟器缓存".to_string(),
+        description: format!(
+            "iOS 模拟器的 dyld 共享缓存等, 下次启动时重新生成: {}",
+            developer_dir(home).join("CoreSimulator").join("Caches").display()
+        ),
+        cleanup_type: CleanupType::Directories(simulator_cache_dirs(home)),
+        category: Category::DevTools,
+        risk: RiskLevel::Safe,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// A simulator device from `xcrun simctl list devices -j`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Simulator {
+    pub udid: String,
+    pub name: String,
+    #[serde(default)]
+    pub is_available: bool,
+    /// `.../Devices/<udid>/data`
+    #[serde(default)]
+    pub data_path: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct SimctlDevices {
+    devices: BTreeMap<String, Vec<Simulator>>,
+}
+
+/// Unavailable devices in `xcrun simctl list devices -j` output, whose runtime is no longer installed
+pub fn parse_unavailable_simulators(json: &str) -> Vec<Simulator> {
+    serde_json::from_str::<SimctlDevices>(json)
+        .map(|list| list.devices.into_values().flatten().filter(|device| !device.is_available).collect())
+        .unwrap_or_default()
+}
+
+/// Simulator devices whose runtime is gone, deleted with `xcrun simctl delete unavailable`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnavailableSimulators {
+    pub program: PathBuf,
+}
+
+impl UnavailableSimulators {
+    pub fn new(program: PathBuf) -> Self {
+        Self { program }
+    }
+
+    /// Run xcrun and return its stdout, or an error carrying its stderr
+    fn run(&self, args: &[&str]) -> io::Result<String> {
+        let output = Command::new(&self.program).args(args).output()?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            Err(io::Error::other(format!(
+                "{} {} exited with {}: {}",
+                self.program.display(),
+                args.join(" "),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+
+    /// Unavailable devices with the size of their device directory
+    fn devices(&self) -> io::Result<Vec<(Simulator, u64)>> {
+        let output = self.run(&["simctl", "list", "devices", "unavailable", "-j"])?;
+        Ok(parse_unavailable_simulators(&output)
+            .into_iter()
+            .map(|device| {
+                let size = device.data_path.as_deref().and_then(Path::parent).map_or(0, |dir| {
+                    CleanupItem {
+                        cleanup_type: CleanupType::Directory(dir.to_path_buf()),
+                        ..Default::default()
+                    }
+                    .scan()
+                    .size_bytes
+                });
+                (device, size)
+            })
+            .collect())
+    }
+
+    fn failed(&self, error: &io::Error) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        result.errors.push(CleanErrorEntry::new(&self.program, error));
+        result
+    }
+
+    pub fn item(self) -> CleanupItem {
+        CleanupItem {
+            id: "unavailable_simulators".to_string(),
+            name: "不可用的模拟器".to_string(),
+            description: "运行时已卸载的模拟器设备及其数据 (xcrun simctl delete unavailable)".to_string(),
+            category: Category::DevTools,
+            risk: RiskLevel::Moderate,
+            enabled: false,
+            cleanup_type: CleanupType::Custom(Arc::new(self)),
+            ..Default::default()
+        }
+    }
+}
+
+impl CleanTarget for UnavailableSimulators {
+    fn scan(&self) -> CleanupResult {
+        let devices = match self.devices() {
+            Ok(devices) => devices,
+            Err(e) => return self.failed(&e),
+        };
+        let mut result = CleanupResult::new();
+        for (device, size) in devices {
+            result.entries += 1;
+            result.size_bytes += size;
+            result.breakdown.push(BreakdownEntry {
+                label: device.name,
+                size_bytes: size,
+                note: device.udid,
+            });
+        }
+        result.has_data = result.entries > 0;
+        result
+    }
+
+    fn clean(&self) -> CleanupResult {
+        let devices = match self.devices() {
+            Ok(devices) => devices,
+            Err(e) => return self.failed(&e),
+        };
+        if let Err(e) = self.run(&["simctl", "delete", "unavailable"]) {
+            return self.failed(&e);
+        }
+        let mut result = CleanupResult::new();
+        result.entries = devices.len() as u64;
+        result.size_bytes = devices.iter().map(|(_, size)| size).sum();
+        result.has_data = result.entries > 0;
+        info!("Deleted {} unavailable simulators", result.entries);
+        result
+    }
+
+    fn validate(&self) -> ItemStatus {
+        match self.run(&["simctl", "help"]) {
+            Ok(_) => ItemStatus::Ready,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => ItemStatus::PathMissing,
+            Err(_) => ItemStatus::ServiceUnavailable,
+        }
+    }
+}
+
+/// Xcode and simulator items; unavailable simulators only when the developer tools are installed
+pub fn xcode_items(home: &Path, xcrun: Option<PathBuf>) -> Vec<CleanupItem> {
+    let mut items = vec![derived_data_item(home), device_support_item(home), simulator_caches_item(home)];
+    items.extend(xcrun.map(|xcrun| UnavailableSimulators::new(xcrun).item()));
+    items
+}
@@ -1,15 +1,21 @@
-mod cleanup_items;
-mod error;
-mod platform;
 mod tui;
 
 use anyhow::Result;
-use clap::Parser;
-use cleanup_items::CleanupType;
-use error::CleanError;
-use platform::clean_recycle_bin;
+use clap::{Parser, Subcommand};
+use clean_rs::cleaner::Cleaner;
+use clean_rs::cleanup_items::{self, Category, CleanErrorEntry, CleanupResult, CleanupType, RiskLevel};
+use clean_rs::error::{self, CleanError};
+use clean_rs::events::TracingObserver;
+use clean_rs::free_space::{self, clean_until_freed};
+use clean_rs::options::CleanOptions;
+use clean_rs::platform::{self, clean_recycle_bin};
+use clean_rs::state::{apply_item_options, default_state_path, load_item_state};
+use clean_rs::system;
+use clean_rs::util::format_size;
+use serde::Serialize;
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tracing::{error, info, Level};
 
 /// Clean Tools of Rust - A system cleaning tool with TUI interface
@@ -24,18 +30,67 @@ struct Cli {
     #[arg(short, long)]
     temp: bool,
 
-    /// Clean recycle bin only (Windows)
+    /// Clean recycle bin only (Windows Recycle Bin, macOS Trash, FreeDesktop trash on Linux)
     #[arg(short, long)]
     recycle: bool,
 
+    /// List every cleanup item with its status, such as whether it needs administrator rights,
+    /// then what the recycle bin holds, per drive, with each item's original path, deletion
+    /// date and size (Windows), without deleting anything
+    #[arg(long)]
+    list: bool,
+
     /// Custom directory to clean
     #[arg(short = 'd', long, value_name = "DIR")]
     directory: Option<PathBuf>,
 
+    /// Clean all default-enabled items up to this risk level (safe, moderate, aggressive).
+    /// Aggressive items, such as the downloads folder, are never enabled by default, so
+    /// `aggressive` cleans the same items as `moderate`; select those in the TUI instead
+    #[arg(long, value_name = "LEVEL")]
+    risk: Option<RiskLevel>,
+
+    /// Clean default-enabled items, oldest files first, until SIZE is freed (e.g. 5G, 500M)
+    #[arg(long, value_name = "SIZE", value_parser = free_space::parse_size)]
+    free: Option<u64>,
+
+    /// Maximum number of cleanup items scanned concurrently in TUI mode
+    #[arg(long, value_name = "N", default_value_t = cleanup_items::DEFAULT_SCAN_PARALLELISM)]
+    scan_jobs: usize,
+
+    /// Clean application caches even if the application appears to be running
+    #[arg(long)]
+    ignore_running_apps: bool,
+
+    /// Skip items whose files are on a network share or a removable drive
+    #[arg(long)]
+    skip_remote: bool,
+
+    /// Skip files that running processes hold open and name those processes
+    #[arg(long)]
+    detect_in_use: bool,
+
+    /// Run at low CPU and I/O priority so other programs stay responsive; cleaning takes longer
+    #[arg(long)]
+    background: bool,
+
+    /// Create a System Restore point before cleaning aggressive items (Windows, needs admin)
+    #[arg(long)]
+    restore_point: bool,
+
+    /// Allow cleaning a target that is a volume root or a mount point
+    #[arg(long)]
+    force_roots: bool,
+
     /// Dry run - show what would be deleted without actually deleting
     #[arg(long)]
     dry_run: bool,
 
+    /// Print JSON instead of text: the per-item results of --risk, the recycle bin with --list
+    /// and the error codes of the `errors` command. The summary is left out and logs go to stderr
+    #[arg(long, global = true)]
+    json: bool,
+
     /// Verbose output
     #[arg(short, long)]
     verbose: bool,
@@ -47,6 +102,15 @@ struct Cli {
     /// Pause before exit (useful when running from .exe on Windows)
     #[arg(long)]
     pause: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum Command {
+    /// List the error codes used in --json output and what each means
+    Errors,
 }
 
 impl Cli {
@@ -60,20 +124,37 @@ impl Cli {
             Level::INFO
         }
     }
+
+    /// Options every clean started from the command line or the TUI runs with
+    fn clean_options(&self) -> CleanOptions {
+        CleanOptions::new()
+            .ignore_running_apps(self.ignore_running_apps)
+            .skip_remote(self.skip_remote)
+            .detect_in_use(self.detect_in_use)
+            .background(self.background)
+            .restore_point(self.restore_point)
+            .allow_mount_roots(self.force_roots)
+            .observe(Arc::new(TracingObserver))
+    }
 }
 
 /// Initialize logging system (silent for TUI to avoid interfering with output)
-fn init_logging(level: Level, silent: bool) {
+fn init_logging(level: Level, silent: bool, to_stderr: bool) {
     if silent {
         // Don't initialize logging for TUI mode
         return;
     }
     
-    let subscriber = tracing_subscriber::FmtSubscriber::builder()
-        .with_max_level(level)
-        .finish();
+    let builder = tracing_subscriber::FmtSubscriber::builder()
+        .with_max_level(level);
+    // Keep stdout clean for the JSON report
+    let result = if to_stderr {
+        tracing::subscriber::set_global_default(builder.with_writer(std::io::stderr).finish())
+    } else {
+        tracing::subscriber::set_global_default(builder.finish())
+    };
 
-    if let Err(e) = tracing::subscriber::set_global_default(subscriber) {
+    if let Err(e) = result {
         eprintln!("Warning: Failed to set tracing subscriber: {}", e);
     }
 }
@@ -87,60 +168,383 @@ fn pause_if_needed(should_pause: bool) {
     }
 }
 
-/// Clean with new cleanup_items module
-fn clean_with_items(items: Vec<cleanup_items::CleanupItem>, dry_run: bool) -> (u64, u64) {
-    let mut total_bytes = 0u64;
-    let mut total_files = 0u64;
+/// Result of one item in the JSON report
+#[derive(Serialize)]
+struct ItemReport<'a> {
+    id: &'a str,
+    name: &'a str,
+    result: CleanupResult,
+}
 
-    for item in items {
-        if !item.enabled {
-            continue;
+/// Space on a volume holding files of the cleaned items, before and after cleaning
+#[derive(Serialize)]
+struct VolumeReport {
+    path: PathBuf,
+    total: u64,
+    /// Space the user can write to, as `DiskUsage::available`
+    free_before: u64,
+    /// Not measured in a dry run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    free_after: Option<u64>,
+}
+
+/// Everything printed by `--json`
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    dry_run: bool,
+    /// Ran at low priority, which explains a slower run
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    background: bool,
+    items: Vec<ItemReport<'a>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    volumes: Vec<VolumeReport>,
+}
+
+/// Volumes holding the enabled items' files, with their space before cleaning
+fn measure_volumes(items: &[cleanup_items::CleanupItem]) -> Vec<VolumeReport> {
+    let mut roots: Vec<PathBuf> = items
+        .iter()
+        .filter(|item| item.enabled)
+        .flat_map(|item| item.root_paths())
+        .filter_map(platform::volume_root)
+        .collect();
+    roots.sort();
+    roots.dedup();
+    roots
+        .into_iter()
+        .filter_map(|path| {
+            let usage = platform::disk_usage(&path).ok()?;
+            Some(VolumeReport { path, total: usage.total, free_before: usage.available, free_after: None })
+        })
+        .collect()
+}
+
+/// Print the files listed by a report-only item such as the large file report
+fn print_report(result: &CleanupResult) {
+    println!("  Report: {} files ({}), nothing deleted", result.files, result.size_human());
+    for entry in &result.breakdown {
+        println!("    {:>10}  {}  {}", format_size(entry.size_bytes), entry.label, entry.note);
+    }
+}
+
+/// Print every cleanup item with its id and, when it is not simply ready, its status
+fn list_items() {
+    for item in cleanup_items::get_all_cleanup_items() {
+        let status = item.status.reason();
+        if status.is_empty() {
+            println!("  {:<28} {}", item.id, item.name);
+        } else {
+            println!("  {:<28} {} ({})", item.id, item.name, status);
         }
+    }
+    println!();
+}
 
-        info!("Cleaning: {}", item.name);
-        let result = item.scan();
-        
-        if result.has_data {
-            if dry_run {
-                println!("  [DRY RUN] Would clean: {} files ({:.2} MB)", 
-                        result.files, result.size_mb());
+/// Print every error code with its meaning, or with `json` as a JSON array
+fn list_error_codes(json: bool) {
+    if json {
+        let codes: Vec<serde_json::Value> = clean_rs::error::error_codes()
+            .iter()
+            .map(|(code, description)| serde_json::json!({ "code": code, "description": description }))
+            .collect();
+        match serde_json::to_string_pretty(&codes) {
+            Ok(text) => println!("{}", text),
+            Err(e) => error!("Failed to write JSON report: {}", e),
+        }
+        return;
+    }
+    for (code, description) in clean_rs::error::error_codes() {
+        println!("{:<18} {}", code, description);
+    }
+}
+
+/// Print each bin of the recycle bin and the items in it, as text or with `json` as a JSON
+/// report; nothing is deleted
+fn list_recycle_bin(json: bool) {
+    let item = system::RecycleBin::new().item();
+    let result = item.scan();
+    if json {
+        let report = JsonReport {
+            dry_run: true,
+            background: false,
+            items: vec![ItemReport { id: &item.id, name: &item.name, result }],
+            volumes: Vec::new(),
+        };
+        match serde_json::to_string_pretty(&report) {
+            Ok(text) => println!("{}", text),
+            Err(e) => error!("Failed to write JSON report: {}", e),
+        }
+        return;
+    }
+
+    println!("{}: {} items ({})", item.name, result.entries, result.size_human());
+    if let Some(reason) = &result.skip_reason {
+        println!("  Skipped: {}", reason);
+    }
+    for entry in &result.breakdown {
+        // Bins first, then the items in each one below it
+        let indent = if item.can_exclude(Path::new(&entry.label)) { "  " } else { "      " };
+        println!("{}{:>10}  {}  {}", indent, format_size(entry.size_bytes), entry.label, entry.note);
+    }
+}
+
+/// Create the restore point enabled items want before any of them is cleaned, warning when
+/// that fails; those items then report why they were skipped
+fn prepare_restore_point(items: &[cleanup_items::CleanupItem], options: &CleanOptions, dry_run: bool) {
+    if dry_run || !cleanup_items::needs_restore_point(items, options) {
+        return;
+    }
+    info!("Creating a System Restore point");
+    if let Err(e) = options.create_restore_point() {
+        error!("Could not create a restore point: {}; items that need one will be skipped", e);
+    }
+}
+
+/// Print what cleaning one item, or with `dry_run` scanning it, came to. Items that had
+/// nothing to clean print nothing.
+fn print_outcome(outcome: &clean_rs::Result<CleanupResult>, dry_run: bool) {
+    let result = match outcome {
+        Ok(result) => result,
+        Err(CleanError::PartialFailure { result, cause }) => {
+            println!("  Stopped: {}", cause);
+            println!(
+                "  Cleaned before stopping: {} files, {} directories ({})",
+                result.files_deleted,
+                result.dirs_deleted,
+                result.bytes_human()
+            );
+            return;
+        }
+        Err(e) => {
+            println!("  Failed: {}", e);
+            return;
+        }
+    };
+    if let Some(reason) = &result.skip_reason {
+        println!("  Skipped: {}", reason);
+    } else if result.report_only {
+        print_report(result);
+    } else if dry_run {
+        if !result.has_data {
+            return;
+        }
+        println!("  [DRY RUN] Would clean: {} files ({})", 
+                result.files, result.size_human());
+        if result.entries > 0 {
+            println!("  [DRY RUN] Would delete {} database rows", result.entries);
+        }
+        if result.size_bytes == 0 {
+            // Items freeing no space, such as the DNS cache, show what they would do instead
+            for entry in &result.breakdown {
+                println!("  [DRY RUN] {}  {}", entry.label, entry.note);
+            }
+        }
+    } else if result.has_data || result.has_errors() {
+        println!("  Cleaned: {} files ({})", 
+                result.files, result.size_human());
+        if result.entries > 0 {
+            println!("  Deleted {} database rows", result.entries);
+        }
+        if result.has_errors() {
+            let classes = error::summarize_classes(result.errors.iter().map(CleanErrorEntry::class));
+            println!("  Failed to delete {} entries ({})", result.errors.len(), classes);
+        }
+        if result.cloud_placeholders > 0 {
+            println!("  Skipped {} online-only cloud files", result.cloud_placeholders);
+        }
+        for skipped in &result.skipped_in_use {
+            let holders: Vec<String> = skipped.holders.iter().map(ToString::to_string).collect();
+            if holders.is_empty() {
+                println!("  Skipped (in use): {}", skipped.path.display());
             } else {
-                println!("  Cleaning: {} files ({:.2} MB)", 
-                        result.files, result.size_mb());
-                // Actually clean
-                let _ = item.clean();
+                println!("  Skipped (in use by {}): {}", holders.join(", "), skipped.path.display());
+            }
+        }
+    }
+}
+
+/// Clean the enabled items, printing each item's result as text or, with `json`, all of them
+/// as one JSON document. Returns the bytes and files freed, or that would be with `dry_run`.
+fn clean_with_items(
+    items: Vec<cleanup_items::CleanupItem>,
+    options: &CleanOptions,
+    dry_run: bool,
+    json: bool,
+) -> (u64, u64) {
+    let mut cleaner = Cleaner::new(items);
+    cleaner.set_options(options.clone());
+    let mut volumes = if json { measure_volumes(cleaner.items()) } else { Vec::new() };
+    prepare_restore_point(cleaner.items(), options, dry_run);
+
+    let report = cleaner.clean_enabled_with(dry_run, |_, outcome| {
+        if !json {
+            print_outcome(outcome, dry_run);
+        }
+    });
+    if report.failed() > 0 {
+        error!("{}", report);
+    }
+    let freed = (report.total_bytes(), report.total_files());
+
+    if json {
+        if !dry_run {
+            for volume in &mut volumes {
+                volume.free_after = platform::disk_usage(&volume.path).ok().map(|usage| usage.available);
             }
-            total_bytes += result.size_bytes;
-            total_files += result.files;
+        }
+        let items = cleaner.items();
+        let reports = report
+            .items
+            .into_iter()
+            .filter_map(|(id, outcome)| {
+                let item = items.iter().find(|item| item.id == id)?;
+                let result = outcome.unwrap_or_else(failed_result);
+                // Items that had nothing to clean are left out
+                if !result.has_data && result.skip_reason.is_none() && !result.has_errors() {
+                    return None;
+                }
+                Some(ItemReport { id: &item.id, name: &item.name, result })
+            })
+            .collect();
+        let report = JsonReport { dry_run, background: options.background, items: reports, volumes };
+        match serde_json::to_string_pretty(&report) {
+            Ok(text) => println!("{}", text),
+            Err(e) => error!("Failed to write JSON report: {}", e),
         }
     }
 
-    (total_bytes, total_files)
+    freed
+}
+
+/// Result an item that failed with `error` is reported with: skipped for the failure, with the
+/// counts of what a clean stopped partway had already deleted
+fn failed_result(error: CleanError) -> CleanupResult {
+    match error.into_partial_result() {
+        (Some(partial), cause) => CleanupResult {
+            files: partial.files_deleted,
+            directories: partial.dirs_deleted,
+            size_bytes: partial.bytes_cleaned,
+            has_data: true,
+            errors: partial.errors,
+            ..CleanupResult::skipped(cause.to_string())
+        },
+        (None, error) => CleanupResult::skipped(error.to_string()),
+    }
+}
+
+/// Apply per-item options (patterns, priorities) saved from the TUI
+fn with_saved_options(mut items: Vec<cleanup_items::CleanupItem>) -> Vec<cleanup_items::CleanupItem> {
+    if let Some(path) = default_state_path() {
+        match load_item_state(&path) {
+            Ok(states) => apply_item_options(&mut items, &states),
+            Err(e) => error!("Ignoring unreadable state file {}: {}", path.display(), e),
+        }
+    }
+    items
+}
+
+/// Clean until `target_bytes` are freed, printing what each item contributed
+fn free_until_target(
+    items: Vec<cleanup_items::CleanupItem>,
+    target_bytes: u64,
+    options: &CleanOptions,
+    dry_run: bool,
+) -> u64 {
+    println!("Target: free {}", format_size(target_bytes));
+    let _background = options.enter_background();
+
+    if dry_run {
+        // Largest items are cleaned first, so the same order shows what would be touched
+        let mut scanned: Vec<_> = cleanup_items::priority_order(&items)
+            .into_iter()
+            .map(|index| &items[index])
+            .filter(|item| item.enabled)
+            .map(|item| (item, item.scan_with(options)))
+            .collect();
+        scanned.sort_by_key(|(_, result)| std::cmp::Reverse(result.size_bytes));
+
+        let mut planned = 0u64;
+        for (item, result) in scanned {
+            if planned >= target_bytes {
+                println!("  [DRY RUN] Untouched: {}", item.name);
+                continue;
+            }
+            let share = result.size_bytes.min(target_bytes - planned);
+            planned += share;
+            println!("  [DRY RUN] Would clean {} from {}", format_size(share), item.name);
+        }
+        return planned;
+    }
+
+    prepare_restore_point(&items, options, false);
+    let report = clean_until_freed(&items, target_bytes, options);
+    for contribution in &report.contributions {
+        let result = &contribution.result;
+        if let Some(reason) = &result.skip_reason {
+            println!("  {}: skipped ({})", contribution.name, reason);
+        } else {
+            println!("  {}: {} files ({})", contribution.name, result.files, result.size_human());
+        }
+    }
+    for id in &report.untouched {
+        println!("  Untouched: {}", id);
+    }
+    if !report.target_reached() {
+        println!("Target not reached: nothing else to clean");
+    }
+    report.freed_bytes
 }
 
 /// Legacy: Clean temporary directory
-fn clean_temp(dry_run: bool) -> Result<u64> {
+fn clean_temp(dry_run: bool, options: &CleanOptions) -> clean_rs::Result<u64> {
+    let mut total = 0;
+    for item in temp_items() {
+        info!("Cleaning temporary directory: {:?}", item.root_paths());
+        item.check_mount_roots(options)?;
+
+        let result = item.scan_with(options);
+        total += if !dry_run && result.has_data { item.clean_with(options).size_bytes } else { result.size_bytes };
+    }
+    Ok(total)
+}
+
+/// Items for `--temp`. `/tmp` and `/var/tmp` are shared by every user and session, so only
+/// old entries of the current user go, keeping sockets and session directories.
+#[cfg(unix)]
+fn temp_items() -> Vec<cleanup_items::CleanupItem> {
+    use clean_rs::linux;
+
     let temp_dir = env::temp_dir();
-    info!("Cleaning temporary directory: {:?}", temp_dir);
+    let mut items = vec![linux::shared_temp_item(
+        "legacy_temp",
+        linux::SharedTemp::for_current_user(&temp_dir),
+        linux::TMP_MIN_AGE_DAYS,
+    )];
+    if temp_dir != Path::new(linux::VAR_TMP) {
+        let var_tmp = linux::SharedTemp::for_current_user(linux::VAR_TMP);
+        items.push(linux::shared_temp_item("legacy_var_tmp", var_tmp, linux::VAR_TMP_MIN_AGE_DAYS));
+    }
+    items
+}
 
-    let item = cleanup_items::CleanupItem {
+/// Items for `--temp`: the user's own temp directory, emptied
+#[cfg(windows)]
+fn temp_items() -> Vec<cleanup_items::CleanupItem> {
+    let temp_dir = env::temp_dir();
+    vec![cleanup_items::CleanupItem {
         id: "legacy_temp".to_string(),
         name: "临时文件目录".to_string(),
         description: "".to_string(),
         cleanup_type: CleanupType::Directory(temp_dir),
+        category: Category::System,
+        risk: RiskLevel::Safe,
         enabled: true,
-    };
-
-    let result = item.scan();
-    if !dry_run && result.has_data {
-        let _ = item.clean();
-    }
-
-    Ok(result.size_bytes)
+        ..Default::default()
+    }]
 }
 
 /// Legacy: Clean custom directory
-fn clean_custom_directory(path: PathBuf, dry_run: bool) -> Result<u64> {
+fn clean_custom_directory(path: PathBuf, dry_run: bool, options: &CleanOptions) -> clean_rs::Result<u64> {
     info!("Cleaning custom directory: {:?}", path);
 
     let item = cleanup_items::CleanupItem {
@@ -148,37 +552,57 @@ fn clean_custom_directory(path: PathBuf, dry_run: bool) -> Result<u64> {
         name: "自定义目录".to_string(),
         description: path.display().to_string(),
         cleanup_type: CleanupType::Directory(path),
+        category: Category::System,
+        risk: RiskLevel::Moderate,
         enabled: true,
+        ..Default::default()
     };
+    item.check_mount_roots(options)?;
 
-    let result = item.scan();
+    let result = item.scan_with(options);
     if !dry_run && result.has_data {
-        let _ = item.clean();
+        return Ok(item.clean_with(options).size_bytes);
     }
 
     Ok(result.size_bytes)
 }
 
 /// Display cleanup summary
-fn display_summary(total_bytes: u64, dry_run: bool) {
+fn display_summary(total_bytes: u64, dry_run: bool, background: bool) {
     if dry_run {
         println!("\n[DRY RUN] Summary:");
-        println!("Would free approximately {:.2} MB", total_bytes as f64 / (1024.0 * 1024.0));
+        println!("Would free approximately {}", format_size(total_bytes));
     } else {
         println!("\nSummary:");
-        println!("Freed {:.2} MB of disk space", total_bytes as f64 / (1024.0 * 1024.0));
+        println!("Freed {} of disk space", format_size(total_bytes));
+    }
+    if background {
+        println!("Ran in background mode (low CPU and I/O priority)");
     }
 }
 
 fn run_cli_mode(cli: &Cli) -> Result<()> {
+    if cli.list {
+        if !cli.json {
+            list_items();
+        }
+        list_recycle_bin(cli.json);
+        return Ok(());
+    }
+
     let mut total_bytes = 0u64;
     let mut has_error = false;
     let directory_provided = cli.directory.is_some();
+    let options = cli.clean_options();
 
     // Clean temporary files
     if cli.temp {
-        match clean_temp(cli.dry_run) {
+        match clean_temp(cli.dry_run, &options) {
             Ok(bytes) => total_bytes += bytes,
+            Err(e @ CleanError::RefusedMountPoint(_)) => {
+                error!("{} (pass --force-roots to clean it anyway)", e);
+                has_error = true;
+            }
             Err(e) => {
                 error!("Failed to clean temporary directory: {}", e);
                 has_error = true;
@@ -188,8 +612,12 @@ fn run_cli_mode(cli: &Cli) -> Result<()> {
 
     // Clean custom directory
     if let Some(dir) = &cli.directory {
-        match clean_custom_directory(dir.clone(), cli.dry_run) {
+        match clean_custom_directory(dir.clone(), cli.dry_run, &options) {
             Ok(bytes) => total_bytes += bytes,
+            Err(e @ CleanError::RefusedMountPoint(_)) => {
+                error!("{} (pass --force-roots to clean it anyway)", e);
+                has_error = true;
+            }
             Err(e) => {
                 error!("Failed to clean custom directory: {}", e);
                 has_error = true;
@@ -197,38 +625,54 @@ fn run_cli_mode(cli: &Cli) -> Result<()> {
         }
     }
 
+    // Clean built-in items only until enough space is free
+    if let Some(target) = cli.free {
+        let items = match cli.risk {
+            Some(risk) => cleanup_items::get_cleanup_items_up_to(risk),
+            None => cleanup_items::get_all_cleanup_items(),
+        };
+        total_bytes += free_until_target(with_saved_options(items), target, &options, cli.dry_run);
+    } else if let Some(risk) = cli.risk {
+        // Clean built-in items up to the requested risk level
+        let items = with_saved_options(cleanup_items::get_cleanup_items_up_to(risk));
+        let (bytes, _) = clean_with_items(items, &options, cli.dry_run, cli.json);
+        total_bytes += bytes;
+    }
+
     // Clean recycle bin
     if cli.recycle {
-        if let Err(e) = clean_recycle_bin(cli.dry_run) {
-            if let CleanError::NotSupported(_) = e {
-                info!("{}", e);
-            } else {
-                error!("Failed to clean recycle bin: {}", e);
-            }
+        match clean_recycle_bin(cli.dry_run) {
+            Ok(bytes) => total_bytes += bytes,
+            Err(e @ CleanError::NotSupported(_)) => info!("{}", e),
+            Err(e) => error!("Failed to clean recycle bin: {}", e),
         }
     }
 
     // If no specific options provided, clean everything
-    if !cli.temp && !cli.recycle && !directory_provided {
-        match clean_temp(cli.dry_run) {
+    if !cli.temp && !cli.recycle && !directory_provided && cli.risk.is_none() && cli.free.is_none() {
+        match clean_temp(cli.dry_run, &options) {
             Ok(bytes) => total_bytes += bytes,
+            Err(e @ CleanError::RefusedMountPoint(_)) => {
+                error!("{} (pass --force-roots to clean it anyway)", e);
+                has_error = true;
+            }
             Err(e) => {
                 error!("Failed to clean temporary directory: {}", e);
                 has_error = true;
             }
         }
 
-        if let Err(e) = clean_recycle_bin(cli.dry_run) {
-            if let CleanError::NotSupported(_) = e {
-                info!("{}", e);
-            } else {
-                error!("Failed to clean recycle bin: {}", e);
-            }
+        match clean_recycle_bin(cli.dry_run) {
+            Ok(bytes) => total_bytes += bytes,
+            Err(e @ CleanError::NotSupported(_)) => info!("{}", e),
+            Err(e) => error!("Failed to clean recycle bin: {}", e),
         }
     }
 
     // Display summary
-    display_summary(total_bytes, cli.dry_run);
+    if !cli.json {
+        display_summary(total_bytes, cli.dry_run, cli.background);
+    }
 
     if has_error {
         std::process::exit(1);
@@ -240,12 +684,17 @@ fn run_cli_mode(cli: &Cli) -> Result<()> {
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    if let Some(Command::Errors) = cli.command {
+        list_error_codes(cli.json);
+        return Ok(());
+    }
     
     // Check if running without arguments (e.g., double-clicked .exe)
     // If no specific options provided, default to TUI mode
     let args: Vec<String> = std::env::args().collect();
     let has_args = args.len() > 1;
-    let has_cli_options = cli.temp || cli.recycle || cli.directory.is_some();
+    let has_cli_options = cli.temp || cli.recycle || cli.list || cli.directory.is_some() || cli.risk.is_some()
+        || cli.free.is_some();
     
     // Auto-detect TUI mode:
     // - If --tui flag is explicitly set, use it
@@ -254,13 +703,14 @@ fn main() -> Result<()> {
     let use_tui = cli.tui || (!has_args && !has_cli_options);
 
     // Initialize logging (silent for TUI)
-    init_logging(cli.log_level(), use_tui);
+    init_logging(cli.log_level(), use_tui, cli.json);
 
     // Check if TUI mode is requested
     if use_tui {
         info!("Starting TUI mode...");
         // Run TUI - no logging output to avoid interference
-        let result = tui::run_tui();
+        let options = cli.clean_options();
+        let result = tui::run_tui(cli.scan_jobs, options);
         
         // Pause before exit if requested
         pause_if_needed(cli.pause);
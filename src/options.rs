@@ -0,0 +1,215 @@
+//! Options controlling how cleanup items are cleaned
+
+use crate::events::{self, ChannelObserver, CleanEvent, CleanObserver};
+use crate::patterns::TempPattern;
+use crate::platform;
+use crate::vfs::{Fs, RealFs};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, OnceLock};
+use tracing::warn;
+
+/// Description of the restore point created before cleaning
+pub const RESTORE_POINT_DESCRIPTION: &str = "clean-rs: before cleaning";
+
+/// Options shared by every item in a clean run
+#[derive(Debug, Clone, Default)]
+pub struct CleanOptions {
+    /// Clean application caches even when the application appears to be running
+    pub ignore_running_apps: bool,
+    /// Order in which `clean_until_freed` visits items
+    pub free_order: FreeOrder,
+    /// Skip items with files on a network share or a removable drive, where scanning is slow
+    /// and deleting risky
+    pub skip_remote: bool,
+    /// Clean items whose target is a volume root or a mount point, which are refused otherwise
+    pub allow_mount_roots: bool,
+    /// Skip files running processes hold open and name those processes. Costs a look at every
+    /// process's open files before a clean and a lookup per file.
+    pub detect_in_use: bool,
+    /// Run scans and cleans at low CPU and I/O priority so other programs stay responsive
+    pub background: bool,
+    /// Create a System Restore point before cleaning aggressive items
+    pub restore_point: bool,
+    /// Clean items that wanted a restore point even though creating it failed, once the user
+    /// has seen the failure
+    pub restore_point_acknowledged: bool,
+    /// Outcome of `create_restore_point`, empty until it finishes. Shared by clones, so the
+    /// restore point is tried at most once a run.
+    pub restore_point_outcome: Arc<OnceLock<Result<(), String>>>,
+    /// Told about every item's progress, deleted entries and result
+    pub observers: Vec<Arc<dyn CleanObserver>>,
+    /// File system items delete through; the real one when unset
+    pub fs: Option<Arc<dyn Fs>>,
+}
+
+/// Item order used when cleaning only until a byte target is reached
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FreeOrder {
+    /// Scan first and clean the items holding the most data first
+    #[default]
+    LargestFirst,
+    /// Clean items in the order they were given
+    AsGiven,
+}
+
+impl CleanOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ignore_running_apps(mut self, ignore: bool) -> Self {
+        self.ignore_running_apps = ignore;
+        self
+    }
+
+    pub fn free_order(mut self, order: FreeOrder) -> Self {
+        self.free_order = order;
+        self
+    }
+
+    pub fn skip_remote(mut self, skip: bool) -> Self {
+        self.skip_remote = skip;
+        self
+    }
+
+    pub fn allow_mount_roots(mut self, allow: bool) -> Self {
+        self.allow_mount_roots = allow;
+        self
+    }
+
+    pub fn detect_in_use(mut self, detect: bool) -> Self {
+        self.detect_in_use = detect;
+        self
+    }
+
+    pub fn background(mut self, background: bool) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// With `background`, lower the priority of the calling thread's work, and of threads it
+    /// starts, until the guard is dropped
+    pub fn enter_background(&self) -> Option<platform::BackgroundGuard> {
+        if !self.background {
+            return None;
+        }
+        platform::enter_background_mode().map_err(|e| warn!("Failed to enter background mode: {}", e)).ok()
+    }
+
+    pub fn restore_point(mut self, create: bool) -> Self {
+        self.restore_point = create;
+        self
+    }
+
+    pub fn restore_point_acknowledged(mut self, acknowledged: bool) -> Self {
+        self.restore_point_acknowledged = acknowledged;
+        self
+    }
+
+    /// Create the restore point on the first call and return that outcome on every later one
+    pub fn create_restore_point(&self) -> Result<(), String> {
+        self.restore_point_outcome
+            .get_or_init(|| platform::create_restore_point(RESTORE_POINT_DESCRIPTION).map_err(|e| e.to_string()))
+            .clone()
+    }
+
+    pub fn observe(mut self, observer: Arc<dyn CleanObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// Send item events to `events`, through a `ChannelObserver`
+    pub fn events(self, events: Sender<CleanEvent>) -> Self {
+        self.observe(Arc::new(ChannelObserver::new(events)))
+    }
+
+    /// Scan and delete through `fs`, such as a `MockFs` in tests, instead of the real file system
+    pub fn fs(mut self, fs: Arc<dyn Fs>) -> Self {
+        self.fs = Some(fs);
+        self
+    }
+
+    pub fn file_system(&self) -> &dyn Fs {
+        self.fs.as_deref().unwrap_or(&RealFs)
+    }
+
+    /// Tell the observers that every item of the batch has finished
+    pub(crate) fn run_done(&self) {
+        events::notify(&self.observers, "on_run_done", |observer| observer.on_run_done());
+    }
+}
+
+/// Per-item settings that override built-in defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ItemOptions {
+    /// File name patterns for `TempFiles` items; `None` uses the defaults
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temp_patterns: Option<Vec<TempPattern>>,
+    /// Replaces the item's built-in priority
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    /// History rows older than this are deleted by `BrowserHistory` items, journal entries by
+    /// the `Journal` item
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u32>,
+    /// Replaces the item's minimum file age in days
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_age_days: Option<u32>,
+    /// Directories searched by `RustTargets`, `NodeModules`, `PythonBytecode`, `FilePattern`, `EmptyDirs` and
+    /// `Duplicates` items
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_roots: Option<Vec<PathBuf>>,
+    /// Project directories that `RustTargets` and `NodeModules` items leave alone, and
+    /// directories `FilePattern` and `Duplicates` items do not search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excluded_paths: Option<Vec<PathBuf>>,
+    /// `PythonBytecode` items also search virtualenvs and `site-packages`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_envs: Option<bool>,
+    /// `MavenRepo` items also delete whole artifact versions unused for this many days
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unused_days: Option<u32>,
+    /// Stop the item's Windows service while cleaning; only done with administrator rights
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_service: Option<bool>,
+    /// Close Explorer while cleaning files it keeps open, then start it again, also when the
+    /// clean fails. Also read as `allow_explorer_restart`.
+    #[serde(default, alias = "allow_explorer_restart", skip_serializing_if = "Option::is_none")]
+    pub restart_explorer: Option<bool>,
+    /// `IisLogs` items compress logs younger than this many days instead of deleting them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compress_until_days: Option<u32>,
+    /// The APT cache item keeps the packages of installed versions instead of deleting every package
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_installed: Option<bool>,
+    /// Versions of each package `PacmanCache` items keep; 2 when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_versions: Option<u32>,
+    /// Size such as `200M` the `Journal` item vacuums the systemd journal down to; 500M when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vacuum_size: Option<String>,
+    /// `FilePattern` items stay on the filesystem of each root, skipping mounted network shares
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub same_filesystem: Option<bool>,
+    /// `Files` items only delete installers, disk images and archives (`*.exe`, `*.msi`,
+    /// `*.dmg`, `*.iso`, `*.zip`, `*.tar.gz`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installers_only: Option<bool>,
+    /// Create a System Restore point before the item is cleaned; aggressive items follow the
+    /// global `restore_point` option when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restore_point: Option<bool>,
+    /// Replaces whether the item moves files to the trash instead of deleting them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_trash: Option<bool>,
+    /// Also delete online-only OneDrive and iCloud files, which deletes them from the cloud;
+    /// they are skipped when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delete_cloud_placeholders: Option<bool>,
+    /// Duplicate groups, each named by the path of the copy that is kept, that `Duplicates`
+    /// items clean; groups not listed are only reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_paths: Option<Vec<PathBuf>>,
+}
@@ -0,0 +1,174 @@
+//! File name patterns used to recognize temporary files and scattered metadata files
+
+use crate::vfs::{EntryType, Fs};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// A single rule matched against a file name
+///
+/// Serialized in the same glob-like notation accepted by `FromStr`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum TempPattern {
+    /// Extension, compared case-insensitively (`*.tmp`); one with a dot such as `tar.gz`
+    /// matches that many trailing extensions
+    Extension(String),
+    /// Name starts with the given text (`~*`)
+    Prefix(String),
+    /// Name ends with the given text (`*~`)
+    Suffix(String),
+    /// Whole name, compared case-insensitively
+    Exact(String),
+}
+
+impl TempPattern {
+    pub fn matches(&self, file_name: &str) -> bool {
+        match self {
+            TempPattern::Extension(ext) if ext.contains('.') => file_name
+                .len()
+                .checked_sub(ext.len() + 1)
+                .and_then(|start| file_name.get(start..))
+                .is_some_and(|tail| tail.starts_with('.') && tail[1..].eq_ignore_ascii_case(ext)),
+            TempPattern::Extension(ext) => Path::new(file_name)
+                .extension()
+                .is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case(ext)),
+            TempPattern::Prefix(prefix) => file_name.starts_with(prefix.as_str()),
+            TempPattern::Suffix(suffix) => file_name.ends_with(suffix.as_str()),
+            TempPattern::Exact(name) => file_name.eq_ignore_ascii_case(name),
+        }
+    }
+}
+
+/// Parses glob-like notation: `*.ext`, `prefix*`, `*suffix`, or an exact name
+impl FromStr for TempPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || s == "*" {
+            return Err(format!("invalid temp pattern: {:?}", s));
+        }
+        if let Some(ext) = s.strip_prefix("*.").filter(|ext| !ext.contains('*')) {
+            Ok(TempPattern::Extension(ext.to_string()))
+        } else if let Some(suffix) = s.strip_prefix('*').filter(|rest| !rest.contains('*')) {
+            Ok(TempPattern::Suffix(suffix.to_string()))
+        } else if let Some(prefix) = s.strip_suffix('*').filter(|rest| !rest.contains('*')) {
+            Ok(TempPattern::Prefix(prefix.to_string()))
+        } else if !s.contains('*') {
+            Ok(TempPattern::Exact(s.to_string()))
+        } else {
+            Err(format!("unsupported temp pattern: {:?}", s))
+        }
+    }
+}
+
+impl fmt::Display for TempPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TempPattern::Extension(ext) => write!(f, "*.{}", ext),
+            TempPattern::Prefix(prefix) => write!(f, "{}*", prefix),
+            TempPattern::Suffix(suffix) => write!(f, "*{}", suffix),
+            TempPattern::Exact(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl TryFrom<String> for TempPattern {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<TempPattern> for String {
+    fn from(pattern: TempPattern) -> Self {
+        pattern.to_string()
+    }
+}
+
+/// Patterns used when an item does not override them
+pub fn default_temp_patterns() -> Vec<TempPattern> {
+    let extensions = ["tmp", "temp", "bak", "old", "log"];
+    let mut patterns: Vec<TempPattern> = extensions
+        .iter()
+        .map(|ext| TempPattern::Extension(ext.to_string()))
+        .collect();
+    patterns.push(TempPattern::Prefix("~".to_string()));
+    patterns.push(TempPattern::Suffix("~".to_string()));
+    // Regenerated folder metadata
+    patterns.push(TempPattern::Exact("Thumbs.db".to_string()));
+    patterns.push(TempPattern::Exact(".DS_Store".to_string()));
+    patterns
+}
+
+/// Installers, disk images and archives, which pile up in the Downloads folder
+pub fn installer_patterns() -> Vec<TempPattern> {
+    ["exe", "msi", "dmg", "iso", "zip", "tar.gz"]
+        .iter()
+        .map(|ext| TempPattern::Extension(ext.to_string()))
+        .collect()
+}
+
+/// Whether any pattern matches the file name
+pub fn matches_any(patterns: &[TempPattern], file_name: &str) -> bool {
+    patterns.iter().any(|pattern| pattern.matches(file_name))
+}
+
+/// Files with one of a fixed set of names, searched for anywhere below a set of roots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePattern {
+    /// Directories searched recursively
+    pub roots: Vec<PathBuf>,
+    /// File names matched exactly, so `.DS_Store` does not match `old.DS_Store`
+    pub names: Vec<String>,
+    /// Directories left alone along with everything below them
+    pub excluded: Vec<PathBuf>,
+    /// Stay on the filesystem of each root, so mounted network shares are not searched
+    pub same_filesystem: bool,
+}
+
+impl FilePattern {
+    /// Whether the file name is one of the pattern's names
+    pub fn matches(&self, file_name: &str) -> bool {
+        self.names.iter().any(|name| name == file_name)
+    }
+
+    /// Matching files below `roots`, read through `fs`, sorted. Symlinks are not followed, and
+    /// with `same_filesystem` directories on another filesystem than their root are skipped.
+    pub fn find(&self, fs: &dyn Fs, roots: &[PathBuf], excluded: &[PathBuf], same_filesystem: bool) -> Vec<PathBuf> {
+        let mut found = Vec::new();
+        for root in roots {
+            let device = fs.symlink_metadata(root).ok().and_then(|metadata| metadata.device);
+            let mut pending = vec![root.clone()];
+            while let Some(dir) = pending.pop() {
+                let Ok(names) = fs.read_dir(&dir) else {
+                    continue;
+                };
+                for name in names {
+                    let path = dir.join(&name);
+                    let Ok(metadata) = fs.symlink_metadata(&path) else {
+                        continue;
+                    };
+                    match metadata.entry_type {
+                        EntryType::Dir => {
+                            if excluded.contains(&path) {
+                                continue;
+                            }
+                            let other_device = same_filesystem && device.is_some() && metadata.device != device;
+                            if !other_device {
+                                pending.push(path);
+                            }
+                        }
+                        EntryType::File if self.matches(&name.to_string_lossy()) => found.push(path),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        found.sort();
+        found.dedup();
+        found
+    }
+}
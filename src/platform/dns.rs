@@ -0,0 +1,63 @@
+//! Commands flushing the DNS resolver caches of Linux and macOS. Which ones apply is decided
+//! from plain data, so the choice is made on every platform.
+
+use crate::error::{CleanError, Result};
+use std::io;
+use std::process::Command;
+use tracing::info;
+
+/// A command line flushing one cache, program first
+pub type FlushCommand = &'static [&'static str];
+
+/// Caching resolvers on Linux: the process running each one and the command flushing its
+/// cache. `/proc` truncates process names to 15 characters, hence `systemd-resolve`.
+pub const LINUX_DNS_CACHES: &[(&str, FlushCommand)] = &[
+    ("systemd-resolve", &["resolvectl", "flush-caches"]),
+    ("nscd", &["nscd", "--invalidate=hosts"]),
+    // SIGHUP makes dnsmasq drop its cache without restarting
+    ("dnsmasq", &["pkill", "-HUP", "-x", "dnsmasq"]),
+];
+
+/// The directory services cache and mDNSResponder, which every macOS release since 10.10 uses
+pub const MACOS_DNS_FLUSH: &[FlushCommand] = &[&["dscacheutil", "-flushcache"], &["killall", "-HUP", "mDNSResponder"]];
+
+/// Commands for the caching resolvers among `running` process names, in `LINUX_DNS_CACHES`
+/// order. Empty when nothing caches DNS, as with plain glibc lookups.
+pub fn linux_flush_commands(running: &[String]) -> Vec<FlushCommand> {
+    LINUX_DNS_CACHES
+        .iter()
+        .filter(|(process, _)| running.iter().any(|name| name.get(..15).unwrap_or(name) == *process))
+        .map(|(_, command)| *command)
+        .collect()
+}
+
+/// `command` as it would be typed in a shell
+pub fn command_line(command: FlushCommand) -> String {
+    command.join(" ")
+}
+
+/// Run each of `commands`, or with `dry_run` only log them, and return their command lines.
+///
+/// Stops at the first command that cannot be started or fails, such as one that needs root.
+pub fn run_flush_commands(commands: &[FlushCommand], dry_run: bool) -> Result<Vec<String>> {
+    let mut flushed = Vec::new();
+    for command in commands {
+        let line = command_line(command);
+        if dry_run {
+            info!("[DRY RUN] Would run: {}", line);
+        } else {
+            let output = Command::new(command[0]).args(&command[1..]).output()?;
+            if !output.status.success() {
+                return Err(CleanError::Io(io::Error::other(format!(
+                    "{} exited with {}: {}",
+                    line,
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ))));
+            }
+            info!("Flushed DNS cache: {}", line);
+        }
+        flushed.push(line);
+    }
+    Ok(flushed)
+}
@@ -0,0 +1,93 @@
+//! The macOS Trash: `~/.Trash` and `.Trashes/<uid>` on every mounted volume
+
+use super::unix::{current_uid, dir_usage, empty_dir};
+use super::RecycleBinInfo;
+use crate::error::{CleanError, Result};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// Shown when privacy protection keeps the Trash from being read
+const FULL_DISK_ACCESS_REQUIRED: &str =
+    "Full Disk Access is required; grant it to the terminal in System Settings > Privacy & Security";
+
+/// Existing Trash directories of the current user: the home Trash first, then one per volume
+pub fn trash_dirs() -> Vec<PathBuf> {
+    let mut trashes: Vec<PathBuf> = dirs::home_dir().map(|home| home.join(".Trash")).into_iter().collect();
+    if let (Some(uid), Ok(volumes)) = (current_uid(), fs::read_dir("/Volumes")) {
+        let mut volume_trashes: Vec<PathBuf> =
+            volumes.flatten().map(|volume| volume.path().join(".Trashes").join(uid.to_string())).collect();
+        volume_trashes.sort();
+        trashes.extend(volume_trashes);
+    }
+    trashes.retain(|trash| trash.is_dir());
+    trashes
+}
+
+fn access_error(trash: &Path, e: io::Error) -> CleanError {
+    if e.kind() == io::ErrorKind::PermissionDenied {
+        CleanError::NotSupported(format!("cannot read {}: {}", trash.display(), FULL_DISK_ACCESS_REQUIRED))
+    } else {
+        CleanError::read_dir(trash, e)
+    }
+}
+
+/// Existing Trash directories, which `query_recycle_bin_at` and `clean_recycle_bin_at`
+/// accept one by one
+pub fn recycle_bin_roots() -> Vec<PathBuf> {
+    trash_dirs()
+}
+
+/// Size and number of items in the Trash.
+///
+/// Fails with `CleanError::NotSupported` when any Trash directory is blocked by privacy
+/// protection, rather than reporting only the part that could be read.
+pub fn query_recycle_bin() -> Result<RecycleBinInfo> {
+    query_recycle_bin_at(None)
+}
+
+/// Size and number of items in the Trash directory `root`, or in all of them with `None`
+pub fn query_recycle_bin_at(root: Option<&Path>) -> Result<RecycleBinInfo> {
+    let trashes = root.map_or_else(trash_dirs, |root| vec![root.to_path_buf()]);
+    let mut total = RecycleBinInfo::default();
+    for trash in &trashes {
+        let usage = dir_usage(trash).map_err(|e| access_error(trash, e))?;
+        debug!("{}: {} items, {} bytes", trash.display(), usage.items, usage.bytes);
+        total.bytes += usage.bytes;
+        total.items += usage.items;
+    }
+    Ok(total)
+}
+
+/// Empty the Trash directory `root`, or all of them with `None`, and return what was freed.
+///
+/// Every directory is measured before anything is deleted, so a missing Full Disk Access
+/// grant fails the whole clean instead of emptying only some volumes.
+pub fn empty_trash(root: Option<&Path>) -> Result<RecycleBinInfo> {
+    query_recycle_bin_at(root)?;
+    let trashes = root.map_or_else(trash_dirs, |root| vec![root.to_path_buf()]);
+    let mut freed = RecycleBinInfo::default();
+    for trash in &trashes {
+        let emptied = empty_dir(trash).map_err(|e| access_error(trash, e))?;
+        info!("Emptied {} items ({} bytes) from {}", emptied.items, emptied.bytes, trash.display());
+        freed.bytes += emptied.bytes;
+        freed.items += emptied.items;
+    }
+    Ok(freed)
+}
+
+/// Empty the Trash and return the bytes freed, or with `dry_run` the bytes that would be
+pub fn clean_recycle_bin(dry_run: bool) -> Result<u64> {
+    clean_recycle_bin_at(None, dry_run)
+}
+
+/// Empty the Trash directory `root`, or all of them with `None`, like `clean_recycle_bin`
+pub fn clean_recycle_bin_at(root: Option<&Path>, dry_run: bool) -> Result<u64> {
+    if dry_run {
+        let usage = query_recycle_bin_at(root)?;
+        info!("[DRY RUN] Would empty {} items ({} bytes) from the Trash", usage.items, usage.bytes);
+        return Ok(usage.bytes);
+    }
+    Ok(empty_trash(root)?.bytes)
+}
@@ -4,8 +4,200 @@ pub mod windows;
 #[cfg(not(windows))]
 pub mod unix;
 
+#[cfg(target_os = "macos")]
+pub mod macos;
+
+pub mod dns;
+pub mod recycle_bin;
+
+use crate::vfs::EntryMetadata;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Contents of the Recycle Bin or trash
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecycleBinInfo {
+    pub bytes: u64,
+    pub items: u64,
+}
+
+/// Space on a volume, in bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DiskUsage {
+    pub total: u64,
+    /// Free space, including what is reserved for the administrator or root
+    pub free: u64,
+    /// Free space the current user can write to
+    pub available: u64,
+}
+
+/// Where a volume's data lives
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VolumeKind {
+    #[default]
+    Local,
+    /// A network share, such as redirected AppData or an NFS home
+    Network,
+    /// A USB drive, memory card or optical disc
+    Removable,
+}
+
+impl VolumeKind {
+    /// Whether scanning and deleting on the volume is slow or risky
+    pub fn is_remote(self) -> bool {
+        self != VolumeKind::Local
+    }
+
+    /// Name shown when an item is skipped for its volume
+    pub fn label(self) -> &'static str {
+        match self {
+            VolumeKind::Local => "本地磁盘",
+            VolumeKind::Network => "网络驱动器",
+            VolumeKind::Removable => "可移动驱动器",
+        }
+    }
+}
+
+/// What moved a file to the trash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashBackend {
+    /// `gio trash`, which follows the desktop's own trash rules
+    Gio,
+    /// `trash-put` from trash-cli
+    TrashCli,
+    /// clean-rs itself: the Recycle Bin shell API on Windows, the trash directory elsewhere
+    Builtin,
+}
+
+/// A running process holding a file open
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileHolder {
+    pub pid: u32,
+    /// Executable name, such as `chrome.exe`
+    pub name: String,
+}
+
+impl fmt::Display for FileHolder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.pid)
+    }
+}
+
+/// Files running processes have open, by `file_id`, read once before a clean
+#[derive(Debug, Default)]
+pub struct OpenFiles {
+    holders: HashMap<(u64, u64), Vec<FileHolder>>,
+}
+
+impl OpenFiles {
+    /// Record that `holder` has the file with id `id` open
+    pub fn insert(&mut self, id: (u64, u64), holder: FileHolder) {
+        let holders = self.holders.entry(id).or_default();
+        if !holders.contains(&holder) {
+            holders.push(holder);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.holders.is_empty()
+    }
+
+    /// Processes holding `path` open; links are not followed
+    pub fn holders_of(&self, path: &Path) -> Vec<FileHolder> {
+        if self.holders.is_empty() {
+            return Vec::new();
+        }
+        file_id(path).and_then(|id| self.holders.get(&id)).cloned().unwrap_or_default()
+    }
+}
+
+/// Whether `file_name` is the stub iCloud Drive leaves for an evicted file, `.Report.pdf.icloud`
+pub fn is_icloud_stub(file_name: &str) -> bool {
+    file_name.strip_prefix('.').and_then(|name| name.strip_suffix(".icloud")).is_some_and(|name| !name.is_empty())
+}
+
+/// Whether an entry, with `metadata` read without following links, is an online-only file of
+/// OneDrive or another sync client. Reading its data would download it, and deleting it
+/// deletes it from the cloud as well.
+pub fn is_cloud_placeholder(file_name: &OsStr, metadata: &impl EntryMetadata) -> bool {
+    metadata.is_online_only() || file_name.to_str().is_some_and(is_icloud_stub)
+}
+
+/// Whether `path` is where a volume is mounted: a drive root such as `D:\`, a folder a volume
+/// is mounted on, or a Unix mount point such as `/mnt/data`. Bind mounts of a directory on the
+/// same device are not detected.
+pub fn is_mount_point(path: &Path) -> bool {
+    path.exists() && volume_root(path).is_some_and(|root| same_path(&root, path))
+}
+
+/// Whether two paths name the same location, ignoring trailing separators and, on Windows, case
+fn same_path(a: &Path, b: &Path) -> bool {
+    if cfg!(windows) {
+        let lower = |path: &Path| PathBuf::from(path.to_string_lossy().to_lowercase());
+        lower(a).components().eq(lower(b).components())
+    } else {
+        a.components().eq(b.components())
+    }
+}
+
+/// Roots `A:\` to `Z:\` of the drives set in a `GetLogicalDrives` bitmask, bit 0 being `A:`
+pub fn drive_roots(mask: u32) -> Vec<PathBuf> {
+    (0..26u8)
+        .filter(|bit| mask & (1 << bit) != 0)
+        .map(|bit| PathBuf::from(format!("{}:\\", char::from(b'A' + bit))))
+        .collect()
+}
+
+/// `path` in the extended-length form Win32 needs for paths past `MAX_PATH`: `\\?\C:\...`,
+/// or `\\?\UNC\server\share\...` for shares. Forward slashes become backslashes, as the prefix
+/// turns off Windows' own normalization.
+///
+/// Relative paths, paths already prefixed and paths with `.` or `..` components are returned
+/// as `None`, since the prefixed form could name a different file.
+pub fn extended_length_path(path: &str) -> Option<String> {
+    if path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+        return None;
+    }
+    let path = path.replace('/', "\\");
+    if path.split('\\').any(|component| component == "." || component == "..") {
+        return None;
+    }
+    if let Some(share) = path.strip_prefix(r"\\") {
+        return Some(format!(r"\\?\UNC\{}", share));
+    }
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\' {
+        return Some(format!(r"\\?\{}", path));
+    }
+    None
+}
+
 #[cfg(windows)]
-pub use windows::clean_recycle_bin;
+pub use windows::{
+    clean_recycle_bin, clean_recycle_bin_at, compress_file, create_restore_point, current_uid, device_id, disk_size,
+    disk_usage, dns_flush_commands, enter_background_mode, file_holders, file_id, file_owner, flush_dns_cache,
+    is_compressed, is_elevated, is_in_use, is_link, is_locked, is_online_only, is_special_file, list_recycle_bin,
+    long_path, make_deletable, move_to_trash, open_files, process_exists, query_recycle_bin, query_recycle_bin_at,
+    recycle_bin_roots, remove_file_or_link, running_process_names, start_explorer, start_service, stop_explorer,
+    stop_service, trash_files, volume_kind, volume_root, with_explorer_stopped, BackgroundGuard,
+};
 
 #[cfg(not(windows))]
-pub use unix::clean_recycle_bin;
\ No newline at end of file
+pub use unix::{
+    compress_file, create_restore_point, current_uid, device_id, disk_size, disk_usage, dns_flush_commands,
+    enter_background_mode, file_holders, file_id, file_owner, flush_dns_cache, is_compressed, is_elevated, is_in_use,
+    is_link, is_locked, is_online_only, is_special_file, list_recycle_bin, long_path, make_deletable, move_to_trash,
+    open_files, open_files_in, process_exists, remove_file_or_link, running_process_names, start_explorer,
+    start_service, stop_explorer, stop_service, trash_files, trash_files_with, trash_program, volume_kind, volume_root,
+    with_explorer_stopped, BackgroundGuard,
+};
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub use unix::{clean_recycle_bin, clean_recycle_bin_at, query_recycle_bin, query_recycle_bin_at, recycle_bin_roots};
+
+#[cfg(target_os = "macos")]
+pub use macos::{clean_recycle_bin, clean_recycle_bin_at, query_recycle_bin, query_recycle_bin_at, recycle_bin_roots};
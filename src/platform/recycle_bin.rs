@@ -0,0 +1,118 @@
+//! The `$I` index files Windows keeps in `$Recycle.Bin\<SID>` for every deleted item. They are
+//! plain data, so they are parsed on every platform.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// `$I` layout of Windows Vista to 8.1: the path is a fixed 260 character buffer
+pub const INDEX_VERSION_FIXED_PATH: u64 = 1;
+
+/// `$I` layout of Windows 10 and later: the path length precedes a path of any length
+pub const INDEX_VERSION_LONG_PATH: u64 = 2;
+
+/// Characters in the path buffer of a version 1 record, `MAX_PATH`
+const FIXED_PATH_CHARS: usize = 260;
+
+/// 100 ns intervals between 1601-01-01, where `FILETIME` starts, and the Unix epoch
+const FILETIME_UNIX_EPOCH: u64 = 116_444_736_000_000_000;
+
+/// An item in the Recycle Bin, as recorded by its `$I` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecycleBinEntry {
+    /// Where the item was before it was deleted
+    pub original_path: PathBuf,
+    /// When it was deleted, if the record holds a valid time
+    pub deleted: Option<SystemTime>,
+    /// Size of the file, or of everything in the folder
+    pub size: u64,
+    /// The `$R` file or folder holding the deleted data
+    pub stored: PathBuf,
+}
+
+impl RecycleBinEntry {
+    /// Whole days since the item was deleted
+    pub fn deleted_days_ago(&self) -> Option<u64> {
+        let age = self.deleted?.elapsed().ok()?;
+        Some(age.as_secs() / (24 * 60 * 60))
+    }
+}
+
+/// Time of a Windows `FILETIME`, or `None` for 0
+pub fn filetime_to_system_time(filetime: u64) -> Option<SystemTime> {
+    if filetime == 0 {
+        return None;
+    }
+    let nanos = |intervals: u64| Duration::from_nanos(intervals.saturating_mul(100));
+    if filetime >= FILETIME_UNIX_EPOCH {
+        UNIX_EPOCH.checked_add(nanos(filetime - FILETIME_UNIX_EPOCH))
+    } else {
+        UNIX_EPOCH.checked_sub(nanos(FILETIME_UNIX_EPOCH - filetime))
+    }
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(bytes.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+/// UTF-16 path up to its first NUL
+fn read_path(bytes: &[u8]) -> PathBuf {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    PathBuf::from(String::from_utf16_lossy(&units))
+}
+
+/// Original path, deletion time and size from the contents of a `$I` file, version 1 or 2.
+///
+/// Returns `None` for unknown versions and truncated records.
+pub fn parse_index_record(bytes: &[u8]) -> Option<(PathBuf, Option<SystemTime>, u64)> {
+    let version = read_u64(bytes, 0)?;
+    let size = read_u64(bytes, 8)?;
+    let deleted = filetime_to_system_time(read_u64(bytes, 16)?);
+    let path = match version {
+        INDEX_VERSION_FIXED_PATH => bytes.get(24..24 + FIXED_PATH_CHARS * 2)?,
+        INDEX_VERSION_LONG_PATH => {
+            let chars = u32::from_le_bytes(bytes.get(24..28)?.try_into().ok()?) as usize;
+            bytes.get(28..28 + chars.checked_mul(2)?)?
+        }
+        _ => return None,
+    };
+    let path = read_path(path);
+    if path.as_os_str().is_empty() {
+        return None;
+    }
+    Some((path, deleted, size))
+}
+
+/// Items recorded by the `$I` files in one `$Recycle.Bin\<SID>` folder, largest first.
+///
+/// Records that cannot be parsed, and those whose `$R` data is already gone, are left out.
+pub fn read_index_dir(dir: &Path) -> io::Result<Vec<RecycleBinEntry>> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some(id) = name.strip_prefix("$I") else {
+            continue;
+        };
+        let stored = dir.join(format!("$R{}", id));
+        if fs::symlink_metadata(&stored).is_err() {
+            continue;
+        }
+        let Some((original_path, deleted, size)) = fs::read(entry.path()).ok().as_deref().and_then(parse_index_record)
+        else {
+            continue;
+        };
+        entries.push(RecycleBinEntry {
+            original_path,
+            deleted,
+            size,
+            stored,
+        });
+    }
+    entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.original_path.cmp(&b.original_path)));
+    Ok(entries)
+}
@@ -1,17 +1,897 @@
-use crate::error::{CleanError, Result};
+use super::dns::{command_line, linux_flush_commands, run_flush_commands, FlushCommand, MACOS_DNS_FLUSH};
+use super::recycle_bin::RecycleBinEntry;
+use super::{DiskUsage, FileHolder, OpenFiles, RecycleBinInfo, TrashBackend, VolumeKind};
+#[cfg(not(target_os = "macos"))]
+use crate::error::Result;
+use std::borrow::Cow;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::debug;
+#[cfg(not(target_os = "macos"))]
 use tracing::info;
 
-/// Clean the Recycle Bin (not supported on Unix/Linux)
-/// 
-/// On Unix-like systems, there is no unified recycle bin. Each desktop
-/// environment may have its own trash implementation.
-pub fn clean_recycle_bin(dry_run: bool) -> Result<()> {
-    if dry_run {
-        info!("[DRY RUN] Recycle Bin cleaning is not supported on this platform");
+/// Trash directories that `query_recycle_bin_at` and `clean_recycle_bin_at` accept one by one
+#[cfg(not(target_os = "macos"))]
+pub fn recycle_bin_roots() -> Vec<PathBuf> {
+    trash_dirs()
+}
+
+/// Size and number of items in the FreeDesktop trash: the home trash and those on writable
+/// mounts. macOS has its own implementation in `platform::macos`.
+#[cfg(not(target_os = "macos"))]
+pub fn query_recycle_bin() -> Result<RecycleBinInfo> {
+    query_recycle_bin_at(None)
+}
+
+/// Size and number of items in the trash directory `root`, or in all of them with `None`
+#[cfg(not(target_os = "macos"))]
+pub fn query_recycle_bin_at(root: Option<&Path>) -> Result<RecycleBinInfo> {
+    let trashes = root.map_or_else(trash_dirs, |root| vec![root.to_path_buf()]);
+    let mut total = RecycleBinInfo::default();
+    for trash in &trashes {
+        let usage = trash_usage(trash);
+        total.bytes += usage.bytes;
+        total.items += usage.items;
+    }
+    Ok(total)
+}
+
+/// Empty the trash and return the bytes freed, or with `dry_run` the bytes that would be.
+///
+/// Uses the FreeDesktop layout: the home trash plus `.Trash/<uid>` and `.Trash-<uid>` on
+/// every writable mount.
+#[cfg(not(target_os = "macos"))]
+pub fn clean_recycle_bin(dry_run: bool) -> Result<u64> {
+    clean_recycle_bin_at(None, dry_run)
+}
+
+/// Empty the trash directory `root`, or all of them with `None`, like `clean_recycle_bin`
+#[cfg(not(target_os = "macos"))]
+pub fn clean_recycle_bin_at(root: Option<&Path>, dry_run: bool) -> Result<u64> {
+    let trashes = root.map_or_else(trash_dirs, |root| vec![root.to_path_buf()]);
+    let mut total = RecycleBinInfo::default();
+    for trash in &trashes {
+        let usage = if dry_run { trash_usage(trash) } else { empty_trash(trash) };
+        let verb = if dry_run { "[DRY RUN] Would empty" } else { "Emptied" };
+        info!("{} {} items ({} bytes) from {}", verb, usage.items, usage.bytes, trash.display());
+        total.bytes += usage.bytes;
+        total.items += usage.items;
+    }
+    Ok(total.bytes)
+}
+
+/// Items in the trash are not listed one by one yet; always returns an empty list
+pub fn list_recycle_bin(_root: Option<&Path>) -> Vec<RecycleBinEntry> {
+    Vec::new()
+}
+
+/// Existing FreeDesktop trash directories of the current user: the home trash first, then
+/// those on writable mounts
+pub fn trash_dirs() -> Vec<PathBuf> {
+    let mut trashes: Vec<PathBuf> = home_trash_dir().into_iter().filter(|trash| trash.is_dir()).collect();
+    let mounts = fs::read_to_string("/proc/self/mounts").map(|text| parse_mounts(&text)).unwrap_or_default();
+    if let Some(uid) = current_uid() {
+        for trash in volume_trash_dirs(&mounts, uid) {
+            if !trashes.contains(&trash) {
+                trashes.push(trash);
+            }
+        }
+    }
+    trashes
+}
+
+/// Mount points in `/proc/self/mounts` with whether each is mounted read-write
+pub fn parse_mounts(text: &str) -> Vec<(PathBuf, bool)> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?;
+            let writable = fields.nth(1)?.split(',').any(|option| option == "rw");
+            // Spaces and other special characters are written as octal escapes such as `\040`
+            Some((PathBuf::from(unescape_mount_point(mount_point)), writable))
+        })
+        .collect()
+}
+
+/// Filesystem types served over the network
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb3", "smbfs", "fuse.sshfs", "9p", "afs", "ceph", "glusterfs", "fuse.glusterfs", "davfs",
+    "fuse.rclone",
+];
+
+/// Directories udisks mounts USB drives and discs below
+const REMOVABLE_MOUNT_DIRS: &[&str] = &["/media", "/run/media"];
+
+/// Mount points in `/proc/self/mounts` with the kind of volume mounted there. Network shares
+/// are told by their filesystem type, removable drives by being mounted where udisks puts them.
+pub fn parse_mount_kinds(text: &str) -> Vec<(PathBuf, VolumeKind)> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = PathBuf::from(unescape_mount_point(fields.nth(1)?));
+            let fstype = fields.next()?;
+            let kind = if NETWORK_FILESYSTEMS.contains(&fstype) {
+                VolumeKind::Network
+            } else if REMOVABLE_MOUNT_DIRS.iter().any(|dir| mount_point.starts_with(dir)) {
+                VolumeKind::Removable
+            } else {
+                VolumeKind::Local
+            };
+            Some((mount_point, kind))
+        })
+        .collect()
+}
+
+/// Kind of the volume holding `path`: that of the deepest mount point containing it
+pub fn volume_kind_in(mounts: &[(PathBuf, VolumeKind)], path: &Path) -> VolumeKind {
+    mounts
+        .iter()
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())
+        .map_or(VolumeKind::Local, |(_, kind)| *kind)
+}
+
+/// Kind of the volume holding `path`, from `/proc/self/mounts`; always local where that
+/// cannot be read, as on macOS
+pub fn volume_kind(path: &Path) -> VolumeKind {
+    match fs::read_to_string("/proc/self/mounts") {
+        Ok(text) => volume_kind_in(&parse_mount_kinds(&text), path),
+        Err(_) => VolumeKind::Local,
+    }
+}
+
+fn unescape_mount_point(escaped: &str) -> String {
+    let bytes = escaped.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let octal = bytes.get(i + 1..i + 4).and_then(|digits| std::str::from_utf8(digits).ok());
+        match octal.filter(|_| bytes[i] == b'\\').and_then(|digits| u8::from_str_radix(digits, 8).ok()) {
+            Some(byte) => {
+                unescaped.push(byte);
+                i += 4;
+            }
+            None => {
+                unescaped.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+/// Trash directories of user `uid` on the writable mounts: `$topdir/.Trash/<uid>` when
+/// `.Trash` is a sticky directory rather than a symlink, as the specification requires, and
+/// `$topdir/.Trash-<uid>`
+pub fn volume_trash_dirs(mounts: &[(PathBuf, bool)], uid: u32) -> Vec<PathBuf> {
+    use std::os::unix::fs::MetadataExt;
+
+    let is_real_dir = |path: &Path| fs::symlink_metadata(path).is_ok_and(|metadata| metadata.is_dir());
+    let mut trashes = Vec::new();
+    for (mount_point, _) in mounts.iter().filter(|(_, writable)| *writable) {
+        let shared = mount_point.join(".Trash");
+        let sticky =
+            fs::symlink_metadata(&shared).is_ok_and(|metadata| metadata.is_dir() && metadata.mode() & 0o1000 != 0);
+        let candidates = [shared.join(uid.to_string()), mount_point.join(format!(".Trash-{}", uid))];
+        for (i, trash) in candidates.into_iter().enumerate() {
+            if (i == 1 || sticky) && is_real_dir(&trash) && !trashes.contains(&trash) {
+                trashes.push(trash);
+            }
+        }
+    }
+    trashes
+}
+
+/// Bytes and number of items in a FreeDesktop trash directory. Each name in `files/` or
+/// `info/` counts once.
+pub fn trash_usage(trash: &Path) -> RecycleBinInfo {
+    let mut usage = RecycleBinInfo::default();
+    for (file, _) in trash_items(trash) {
+        usage.items += 1;
+        usage.bytes += file.as_deref().map_or(0, entry_size);
+    }
+    usage
+}
+
+/// Delete every item in a FreeDesktop trash directory and return what was freed.
+///
+/// Each item's `info/<name>.trashinfo` is removed only once `files/<name>` is gone, so an
+/// item that cannot be deleted stays listed by file managers. Files without an info entry
+/// are deleted as well, and so is the `directorysizes` cache.
+pub fn empty_trash(trash: &Path) -> RecycleBinInfo {
+    let mut freed = RecycleBinInfo::default();
+    for (file, info) in trash_items(trash) {
+        let mut bytes = 0;
+        if let Some(file) = &file {
+            bytes = entry_size(file);
+            let removed = match fs::symlink_metadata(file) {
+                Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(file),
+                Ok(_) => fs::remove_file(file),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = removed {
+                debug!("Failed to delete {} from the trash: {}", file.display(), e);
+                continue;
+            }
+        }
+        if let Some(info) = &info {
+            if let Err(e) = fs::remove_file(info) {
+                debug!("Failed to delete {}: {}", info.display(), e);
+            }
+        }
+        freed.items += 1;
+        freed.bytes += bytes;
+    }
+    let _ = fs::remove_file(trash.join("directorysizes"));
+    freed
+}
+
+/// Bytes and number of entries directly in `dir`, each entry counted with everything below
+/// it. Used for trash directories without the FreeDesktop layout, such as macOS's `~/.Trash`.
+///
+/// Fails when `dir` cannot be listed.
+pub fn dir_usage(dir: &Path) -> std::io::Result<RecycleBinInfo> {
+    let mut usage = RecycleBinInfo::default();
+    for entry in fs::read_dir(dir)?.flatten() {
+        usage.items += 1;
+        usage.bytes += entry_size(&entry.path());
+    }
+    Ok(usage)
+}
+
+/// Delete every entry in `dir`, keeping `dir` itself, and return what was freed. Entries that
+/// cannot be deleted are left in place.
+///
+/// Fails when `dir` cannot be listed.
+pub fn empty_dir(dir: &Path) -> std::io::Result<RecycleBinInfo> {
+    let mut freed = RecycleBinInfo::default();
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        let bytes = entry_size(&path);
+        let removed = match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => fs::remove_dir_all(&path),
+            _ => fs::remove_file(&path),
+        };
+        match removed {
+            Ok(()) => {
+                freed.items += 1;
+                freed.bytes += bytes;
+            }
+            Err(e) => debug!("Failed to delete {}: {}", path.display(), e),
+        }
+    }
+    Ok(freed)
+}
+
+/// Items of a trash directory as the path in `files/` and the `.trashinfo` in `info/`, either
+/// of which may be missing, sorted by name
+fn trash_items(trash: &Path) -> Vec<(Option<PathBuf>, Option<PathBuf>)> {
+    use std::collections::BTreeMap;
+    use std::ffi::OsString;
+
+    let mut items: BTreeMap<OsString, (Option<PathBuf>, Option<PathBuf>)> = BTreeMap::new();
+    for entry in fs::read_dir(trash.join("files")).into_iter().flatten().flatten() {
+        items.entry(entry.file_name()).or_default().0 = Some(entry.path());
+    }
+    for entry in fs::read_dir(trash.join("info")).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|extension| extension == "trashinfo") {
+            if let Some(name) = path.file_stem() {
+                items.entry(name.to_os_string()).or_default().1 = Some(path.clone());
+            }
+        }
+    }
+    items.into_values().collect()
+}
+
+/// Size of a file, or of everything below a directory, without following symlinks
+fn entry_size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry_size(&entry.path()))
+        .sum()
+}
+
+/// List the names of all running processes
+///
+/// Uses `/proc` where available (Linux) and falls back to `ps` elsewhere.
+/// Names from `/proc/<pid>/comm` are truncated to 15 characters by the kernel.
+pub fn running_process_names() -> Vec<String> {
+    let proc_dir = Path::new("/proc");
+    if proc_dir.is_dir() {
+        if let Ok(entries) = fs::read_dir(proc_dir) {
+            return entries
+                .flatten()
+                .filter(|entry| entry.file_name().to_string_lossy().bytes().all(|b| b.is_ascii_digit()))
+                .filter_map(|entry| fs::read_to_string(entry.path().join("comm")).ok())
+                .map(|name| name.trim_end().to_string())
+                .collect();
+        }
+    }
+
+    match Command::new("ps").args(["-A", "-o", "comm="]).output() {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| {
+                // macOS reports the full executable path
+                let line = line.trim();
+                Path::new(line)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| line.to_string())
+            })
+            .collect(),
+        Err(e) => {
+            debug!("Failed to list processes: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// For a read-only `path`, give the owner write permission on the directory containing it,
+/// which is what deleting an entry needs on Unix. Writable entries are left to fail as
+/// before. Returns whether anything was changed.
+pub fn make_deletable(path: &Path) -> bool {
+    let read_only = fs::symlink_metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o200 == 0);
+    let Some(parent) = path.parent().filter(|_| read_only) else {
+        return false;
+    };
+    let Ok(metadata) = fs::metadata(parent) else {
+        return false;
+    };
+    let mode = metadata.permissions().mode();
+    if mode & 0o200 != 0 {
+        return false;
+    }
+    fs::set_permissions(parent, fs::Permissions::from_mode(mode | 0o200)).is_ok()
+}
+
+/// Whether a process with this pid is running
+pub fn process_exists(pid: u32) -> bool {
+    let proc_dir = Path::new("/proc");
+    if proc_dir.is_dir() {
+        return proc_dir.join(pid.to_string()).exists();
+    }
+    Command::new("ps")
+        .args(["-p", &pid.to_string()])
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Whether the process runs as root: its effective uid is 0
+pub fn is_elevated() -> bool {
+    current_uid() == Some(0)
+}
+
+/// Nice value background work runs at, the lowest CPU priority
+const BACKGROUND_NICE: libc::c_int = 19;
+
+/// `ioprio_set` arguments from linux/ioprio.h
+#[cfg(target_os = "linux")]
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+#[cfg(target_os = "linux")]
+const IOPRIO_CLASS_IDLE: libc::c_int = 3;
+#[cfg(target_os = "linux")]
+const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+
+/// Lowered CPU and I/O priority of the calling thread and the threads it starts afterwards,
+/// put back when dropped
+#[derive(Debug)]
+pub struct BackgroundGuard {
+    nice: Option<libc::c_int>,
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    ioprio: Option<libc::c_long>,
+}
+
+fn clear_errno() {
+    // SAFETY: errno is thread-local and always writable
+    unsafe {
+        #[cfg(target_os = "linux")]
+        {
+            *libc::__errno_location() = 0;
+        }
+        #[cfg(target_os = "macos")]
+        {
+            *libc::__error() = 0;
+        }
+    }
+}
+
+/// Nice value of the calling thread, or `None` if it cannot be read
+fn current_nice() -> Option<libc::c_int> {
+    // -1 is both a valid nice value and the error return, so errno tells them apart
+    clear_errno();
+    // SAFETY: getpriority only reads the calling thread's priority
+    let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) };
+    (nice != -1 || std::io::Error::last_os_error().raw_os_error() == Some(0)).then_some(nice)
+}
+
+/// Switch the calling thread to the idle I/O class and return its previous I/O priority
+#[cfg(target_os = "linux")]
+fn lower_io_priority() -> Option<libc::c_long> {
+    // SAFETY: ioprio_get and ioprio_set only touch the calling thread's I/O priority
+    unsafe {
+        let previous = libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, 0);
+        let idle = IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT;
+        if previous < 0 || libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, idle) != 0 {
+            debug!("Failed to lower the I/O priority: {}", std::io::Error::last_os_error());
+            return None;
+        }
+        Some(previous)
+    }
+}
+
+/// macOS has no I/O classes to switch, only the nice value is lowered
+#[cfg(not(target_os = "linux"))]
+fn lower_io_priority() -> Option<libc::c_long> {
+    None
+}
+
+/// Run the calling thread, and threads it starts while the guard lives, at nice 19 and, on
+/// Linux, in the idle I/O class so other programs stay responsive.
+///
+/// Nice values are per thread on Linux. Raising the priority again needs `CAP_SYS_NICE`, so
+/// without it the thread keeps nice 19 after the guard is dropped.
+pub fn enter_background_mode() -> crate::error::Result<BackgroundGuard> {
+    let nice = current_nice();
+    // SAFETY: setpriority only changes the calling thread's priority
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, BACKGROUND_NICE) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    let ioprio = lower_io_priority();
+    debug!("Entered background mode (previous nice {:?}, I/O priority {:?})", nice, ioprio);
+    Ok(BackgroundGuard { nice, ioprio })
+}
+
+impl Drop for BackgroundGuard {
+    fn drop(&mut self) {
+        #[cfg(target_os = "linux")]
+        if let Some(ioprio) = self.ioprio {
+            // SAFETY: restores the I/O priority read in `lower_io_priority`
+            if unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, ioprio) } != 0 {
+                debug!("Failed to restore the I/O priority: {}", std::io::Error::last_os_error());
+            }
+        }
+        if let Some(nice) = self.nice.filter(|&nice| nice != BACKGROUND_NICE) {
+            // SAFETY: setpriority only changes the calling thread's priority
+            if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) } != 0 {
+                debug!("Failed to restore nice value {}: {}", nice, std::io::Error::last_os_error());
+            }
+        }
+    }
+}
+
+/// Commands for the DNS caches of this system: on macOS the directory services cache and
+/// mDNSResponder, elsewhere whichever of systemd-resolved, nscd and dnsmasq is running
+fn dns_caches() -> Vec<FlushCommand> {
+    if cfg!(target_os = "macos") {
+        MACOS_DNS_FLUSH.to_vec()
     } else {
-        info!("Recycle Bin cleaning is not supported on this platform");
+        linux_flush_commands(&running_process_names())
     }
-    Err(CleanError::NotSupported(
-        "Recycle Bin is not available on Unix/Linux systems".to_string(),
+}
+
+/// Command lines `flush_dns_cache` would run; empty when nothing caches DNS
+pub fn dns_flush_commands() -> Vec<String> {
+    dns_caches().into_iter().map(command_line).collect()
+}
+
+/// Flush every DNS cache, or with `dry_run` only log the commands, and return the command
+/// lines. Most of them need root.
+pub fn flush_dns_cache(dry_run: bool) -> crate::error::Result<Vec<String>> {
+    run_flush_commands(&dns_caches(), dry_run)
+}
+
+/// Stopping services is only supported on Windows; always returns false
+pub fn stop_service(name: &str) -> bool {
+    debug!("Not stopping service {}: not supported on this platform", name);
+    false
+}
+
+/// Starting services is only supported on Windows; always returns false
+pub fn start_service(name: &str) -> bool {
+    debug!("Not starting service {}: not supported on this platform", name);
+    false
+}
+
+/// Unix has no `MAX_PATH` to work around; always returns `path` as it is
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
+/// Whether `metadata`, read without following links, is a symlink
+pub fn is_link(metadata: &fs::Metadata) -> bool {
+    metadata.file_type().is_symlink()
+}
+
+/// Only Windows marks files whose data is kept in the cloud; always returns false
+pub fn is_online_only(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// Delete the file or symlink at `path` itself; Unix never follows a link being unlinked
+pub fn remove_file_or_link(path: &Path) -> std::io::Result<()> {
+    fs::remove_file(path)
+}
+
+/// Files are not locked against deletion on Unix; always returns false
+pub fn is_in_use(_error: &std::io::Error) -> bool {
+    false
+}
+
+/// Most open file descriptors `open_files` looks at, so a system with huge numbers of them
+/// does not stall the clean
+pub const MAX_OPEN_FDS: usize = 200_000;
+
+/// Files open in running processes, from the `fd` links below `/proc`. Best effort: processes
+/// of other users are only visible to root, and only `MAX_OPEN_FDS` descriptors are read.
+/// Empty on macOS, which has no `/proc`.
+pub fn open_files() -> OpenFiles {
+    open_files_in(Path::new("/proc"))
+}
+
+/// Like `open_files`, with `proc_root` standing in for `/proc`
+pub fn open_files_in(proc_root: &Path) -> OpenFiles {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut open = OpenFiles::default();
+    let Ok(processes) = fs::read_dir(proc_root) else {
+        return open;
+    };
+    let mut budget = MAX_OPEN_FDS;
+    for process in processes.flatten() {
+        let Some(pid) = process.file_name().to_str().and_then(|pid| pid.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(process.path().join("fd")) else {
+            continue;
+        };
+        let mut name = None;
+        for fd in fds.flatten() {
+            if budget == 0 {
+                debug!("Stopped reading open files after {} descriptors", MAX_OPEN_FDS);
+                return open;
+            }
+            budget -= 1;
+            // Sockets, pipes and anonymous inodes link to names like `socket:[1234]`
+            if !fs::read_link(fd.path()).is_ok_and(|target| target.is_absolute()) {
+                continue;
+            }
+            // metadata() follows the link to the open file
+            let Ok(metadata) = fs::metadata(fd.path()) else {
+                continue;
+            };
+            let name = name.get_or_insert_with(|| {
+                let comm = fs::read_to_string(process.path().join("comm"));
+                comm.map_or_else(|_| pid.to_string(), |comm| comm.trim().to_string())
+            });
+            open.insert((metadata.dev(), metadata.ino()), FileHolder { pid, name: name.clone() });
+        }
+    }
+    open
+}
+
+/// Processes holding each of `paths` open, in the same order
+pub fn file_holders(paths: &[PathBuf]) -> Vec<Vec<FileHolder>> {
+    let open = open_files();
+    paths.iter().map(|path| open.holders_of(path)).collect()
+}
+
+/// Explorer only exists on Windows; always returns false
+pub fn stop_explorer() -> bool {
+    false
+}
+
+/// Explorer only exists on Windows; always returns false
+pub fn start_explorer() -> bool {
+    false
+}
+
+/// Explorer only exists on Windows; always fails without running `f`
+pub fn with_explorer_stopped<R>(_f: impl FnOnce() -> R) -> crate::error::Result<R> {
+    Err(crate::error::CleanError::NotSupported("Explorer only exists on Windows".to_string()))
+}
+
+/// System Restore only exists on Windows; always fails
+pub fn create_restore_point(_description: &str) -> crate::error::Result<()> {
+    Err(crate::error::CleanError::NotSupported("System Restore only exists on Windows".to_string()))
+}
+
+/// Transparent file compression is only supported on Windows; always returns false
+pub fn is_compressed(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// Bytes allocated for a file on disk, from `st_blocks`: less than its length when sparse,
+/// more when it does not fill its last block
+pub fn disk_size(_path: &Path, metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    metadata.blocks() * 512
+}
+
+/// Transparent file compression is only supported on Windows
+pub fn compress_file(path: &Path) -> std::io::Result<u64> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!("cannot compress {}: not supported on this platform", path.display()),
     ))
-}
\ No newline at end of file
+}
+
+/// Whether another process holds a POSIX write lock on `path`, as apt and dpkg do on their
+/// lock files. Files that cannot be opened are reported as unlocked.
+pub fn is_locked(path: &Path) -> bool {
+    use std::os::fd::AsRawFd;
+
+    let Ok(file) = fs::File::open(path) else {
+        return false;
+    };
+    // SAFETY: flock is plain data, and fcntl only writes into the struct passed to it
+    let mut lock: libc::flock = unsafe { std::mem::zeroed() };
+    lock.l_type = libc::F_WRLCK as libc::c_short;
+    lock.l_whence = libc::SEEK_SET as libc::c_short;
+    let status = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETLK, &mut lock) };
+    status == 0 && lock.l_type != libc::F_UNLCK as libc::c_short
+}
+
+/// Owner uid of a file
+pub fn file_owner(metadata: &fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(metadata.uid())
+}
+
+/// Effective uid of the process
+pub fn current_uid() -> Option<u32> {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    Some(unsafe { libc::geteuid() })
+}
+
+/// Whether `file_type` is a socket, FIFO or device node rather than a file, directory or link
+pub fn is_special_file(file_type: &fs::FileType) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    file_type.is_socket() || file_type.is_fifo() || file_type.is_char_device() || file_type.is_block_device()
+}
+
+/// Id of the filesystem holding a file, to tell mount points apart
+pub fn device_id(metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(metadata.dev())
+}
+
+/// Mount point of the file system holding `path`: its highest ancestor on the same device.
+/// A path that does not exist yet belongs to the volume of its nearest existing ancestor.
+pub fn volume_root(path: &Path) -> Option<PathBuf> {
+    let existing = path.ancestors().find(|dir| dir.exists())?;
+    let device = device_id(&fs::metadata(existing).ok()?)?;
+    let mut root = existing;
+    for parent in existing.ancestors().skip(1) {
+        if fs::metadata(parent).ok().and_then(|metadata| device_id(&metadata)) != Some(device) {
+            break;
+        }
+        root = parent;
+    }
+    Some(root.to_path_buf())
+}
+
+/// Space on the file system holding `path`, from `statvfs`
+// The widths of the statvfs fields differ between platforms
+#[allow(clippy::useless_conversion)]
+pub fn disk_usage(path: &Path) -> crate::error::Result<DiskUsage> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let existing = path.ancestors().find(|dir| dir.exists()).unwrap_or(Path::new("/"));
+    let c_path = CString::new(existing.as_os_str().as_bytes()).map_err(std::io::Error::other)?;
+    // SAFETY: statvfs is plain data, and statvfs() only writes into the struct passed to it
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    let block = u64::from(stat.f_frsize);
+    Ok(DiskUsage {
+        total: u64::from(stat.f_blocks) * block,
+        free: u64::from(stat.f_bfree) * block,
+        available: u64::from(stat.f_bavail) * block,
+    })
+}
+
+/// The user's trash: `~/.Trash` on macOS, `$XDG_DATA_HOME/Trash` elsewhere
+pub fn home_trash_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "macos") {
+        dirs::home_dir().map(|home| home.join(".Trash"))
+    } else {
+        dirs::data_dir().map(|data| data.join("Trash"))
+    }
+}
+
+/// Move `path` to the user's trash so it can still be restored
+pub fn move_to_trash(path: &Path) -> std::io::Result<()> {
+    let trash = home_trash_dir()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "cannot locate the home directory"))?;
+    trash_file(path, &trash)
+}
+
+/// A program on `PATH` that moves files to the desktop's trash: `gio`, else trash-cli's
+/// `trash-put`. Always `None` on macOS, where the trash is only handled by clean-rs itself.
+pub fn trash_program() -> Option<(PathBuf, TrashBackend)> {
+    if cfg!(target_os = "macos") {
+        return None;
+    }
+    let find = crate::devtools::find_program;
+    find("gio").map(|gio| (gio, TrashBackend::Gio)).or_else(|| find("trash-put").map(|put| (put, TrashBackend::TrashCli)))
+}
+
+/// Move each of `paths` to the user's trash, returning for each one how it was moved or why
+/// it was not. Uses `trash_program` when there is one, as `trash_files_with` describes.
+pub fn trash_files(paths: &[PathBuf]) -> Vec<std::io::Result<TrashBackend>> {
+    let Some(trash) = home_trash_dir() else {
+        let missing = || std::io::Error::new(std::io::ErrorKind::NotFound, "cannot locate the home directory");
+        return paths.iter().map(|_| Err(missing())).collect();
+    };
+    trash_files_with(trash_program().as_ref(), paths, &trash)
+}
+
+/// Move `paths` to the trash with `program`, all in one run, and the files it did not move,
+/// or all of them without a program, into `trash` with `trash_file`.
+///
+/// The program's exit code is not trusted either way: gio keeps going after a file fails and
+/// only reports the failure in its exit code, so every path still there afterwards is moved
+/// by `trash_file` instead.
+pub fn trash_files_with(
+    program: Option<&(PathBuf, TrashBackend)>,
+    paths: &[PathBuf],
+    trash: &Path,
+) -> Vec<std::io::Result<TrashBackend>> {
+    let delegated = program.filter(|_| !paths.is_empty()).and_then(|(program, backend)| {
+        let mut command = Command::new(program);
+        if *backend == TrashBackend::Gio {
+            command.arg("trash");
+        }
+        match command.arg("--").args(paths).output() {
+            Ok(output) => {
+                if !output.status.success() {
+                    debug!(
+                        "{} exited with {}: {}",
+                        program.display(),
+                        output.status,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                }
+                Some(*backend)
+            }
+            Err(e) => {
+                debug!("Failed to run {}: {}", program.display(), e);
+                None
+            }
+        }
+    });
+    paths
+        .iter()
+        .map(|path| match delegated {
+            Some(backend) if fs::symlink_metadata(path).is_err() => Ok(backend),
+            _ => trash_file(path, trash).map(|()| TrashBackend::Builtin),
+        })
+        .collect()
+}
+
+/// Move `path` into `trash`, adding `.2`, `.3` and so on to its name if the trash already
+/// holds one by that name.
+///
+/// On macOS the file goes straight into the trash directory. Elsewhere the FreeDesktop layout
+/// is used: the file goes to `files/` next to an `info/<name>.trashinfo` recording where it
+/// came from and when, so file managers can restore it. Moving fails with a cross-device
+/// error when the trash is on another filesystem.
+pub fn trash_file(path: &Path, trash: &Path) -> std::io::Result<()> {
+    use std::ffi::OsString;
+    use std::io::Write;
+
+    let path = std::path::absolute(path)?;
+    let name = path.file_name().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("cannot trash {}", path.display()))
+    })?;
+    let candidate = |n: u32| {
+        let mut candidate = name.to_os_string();
+        if n > 1 {
+            candidate.push(format!(".{}", n));
+        }
+        candidate
+    };
+
+    if cfg!(target_os = "macos") {
+        fs::create_dir_all(trash)?;
+        let mut n = 1;
+        while fs::symlink_metadata(trash.join(candidate(n))).is_ok() {
+            n += 1;
+        }
+        return fs::rename(&path, trash.join(candidate(n)));
+    }
+
+    let (files, info) = (trash.join("files"), trash.join("info"));
+    fs::create_dir_all(&files)?;
+    fs::create_dir_all(&info)?;
+    for n in 1.. {
+        let candidate = candidate(n);
+        let mut info_name = OsString::from(&candidate);
+        info_name.push(".trashinfo");
+        let info_path = info.join(info_name);
+        // Creating the info file reserves the name, as the specification asks
+        let mut info_file = match fs::OpenOptions::new().write(true).create_new(true).open(&info_path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        };
+        let target = files.join(&candidate);
+        let moved = if fs::symlink_metadata(&target).is_ok() {
+            Err(std::io::Error::from(std::io::ErrorKind::AlreadyExists))
+        } else {
+            write!(info_file, "[Trash Info]\nPath={}\nDeletionDate={}\n", encode_trash_path(&path), local_timestamp())
+                .and_then(|()| fs::rename(&path, &target))
+        };
+        match moved {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                let _ = fs::remove_file(&info_path);
+                if e.kind() != std::io::ErrorKind::AlreadyExists {
+                    return Err(e);
+                }
+            }
+        }
+    }
+    unreachable!("ran out of trash names for {}", path.display())
+}
+
+/// `path` with every byte but unreserved characters and `/` percent-encoded, as `.trashinfo`
+/// files store it
+fn encode_trash_path(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut encoded = String::new();
+    for &byte in path.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Current local time as `YYYY-MM-DDThh:mm:ss`
+fn local_timestamp() -> String {
+    // SAFETY: time accepts a null pointer, and localtime_r only writes to the tm it is given
+    let tm = unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        libc::localtime_r(&now, &mut tm);
+        tm
+    };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
+
+/// Device and inode of a file, equal for every hard link to it; symlinks are not followed
+pub fn file_id(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    fs::symlink_metadata(path).ok().map(|metadata| (metadata.dev(), metadata.ino()))
+}
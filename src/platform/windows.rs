@@ -1,35 +1,866 @@
-use crate::error::Result;
+use super::dns::run_flush_commands;
+use super::recycle_bin::{read_index_dir, RecycleBinEntry};
+use super::{DiskUsage, FileHolder, OpenFiles, RecycleBinInfo, TrashBackend, VolumeKind};
+use crate::error::{CleanError, Result};
+use std::borrow::Cow;
+use std::ffi::OsString;
+use std::fs;
+use std::mem;
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::os::windows::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::ptr;
-use tracing::{info, warn};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+use winapi::shared::minwindef::{BOOL, DWORD, FARPROC, MAX_PATH, UINT};
+use winapi::um::fileapi::{
+    GetCompressedFileSizeW, GetDiskFreeSpaceExW, GetDriveTypeW, GetFileInformationByHandle, GetLogicalDrives,
+    GetVolumePathNameW, BY_HANDLE_FILE_INFORMATION, INVALID_FILE_SIZE,
+};
+use winapi::um::libloaderapi::{FreeLibrary, GetProcAddress, LoadLibraryW};
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::processthreadsapi::{GetCurrentProcess, OpenProcess, SetPriorityClass};
+use winapi::um::restartmanager::{RmEndSession, RmGetList, RmRegisterResources, RmStartSession, RM_PROCESS_INFO};
 use winapi::um::shellapi::{
-    SHEmptyRecycleBinW, SHERB_NOCONFIRMATION, SHERB_NOPROGRESSUI, SHERB_NOSOUND,
+    SHEmptyRecycleBinW, SHFileOperationW, SHQueryRecycleBinW, FOF_ALLOWUNDO, FOF_NOCONFIRMATION, FOF_NOERRORUI,
+    FOF_SILENT, FO_DELETE, SHERB_NOCONFIRMATION, SHERB_NOPROGRESSUI, SHERB_NOSOUND, SHFILEOPSTRUCTW, SHQUERYRBINFO,
+};
+use winapi::um::securitybaseapi::{AllocateAndInitializeSid, CheckTokenMembership, FreeSid};
+use winapi::um::winbase::{
+    QueryFullProcessImageNameW, DRIVE_CDROM, DRIVE_FIXED, DRIVE_REMOTE, DRIVE_REMOVABLE, PROCESS_MODE_BACKGROUND_BEGIN,
+    PROCESS_MODE_BACKGROUND_END,
 };
+use winapi::um::winnt::{
+    DOMAIN_ALIAS_RID_ADMINS, FILE_ATTRIBUTE_COMPRESSED, FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_REPARSE_POINT,
+    FILE_ATTRIBUTE_SPARSE_FILE, PROCESS_QUERY_LIMITED_INFORMATION, PSID, SECURITY_BUILTIN_DOMAIN_RID,
+    SECURITY_NT_AUTHORITY, SID_IDENTIFIER_AUTHORITY, ULARGE_INTEGER,
+};
+use winapi::um::winuser::{FindWindowW, PostMessageW, WM_USER};
+
+/// `path` in the extended-length form, so it can be read and deleted even when it is longer
+/// than `MAX_PATH`; see [`super::extended_length_path`]. Other paths are returned as they are.
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    match path.to_str().and_then(super::extended_length_path) {
+        Some(extended) => Cow::Owned(PathBuf::from(extended)),
+        None => Cow::Borrowed(path),
+    }
+}
+
+/// Opening the file downloads it; set on online-only folders
+const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x0004_0000;
+
+/// Reading the file's data downloads it; set on OneDrive's online-only files
+const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x0040_0000;
+
+/// Whether `metadata` belongs to a cloud files placeholder whose data is not on disk, such as
+/// an online-only OneDrive file. Checking the attributes does not download it.
+pub fn is_online_only(metadata: &fs::Metadata) -> bool {
+    metadata.file_attributes() & (FILE_ATTRIBUTE_RECALL_ON_OPEN | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS) != 0
+}
+
+/// Whether `metadata`, read without following links, is a reparse point: a symlink, a junction
+/// such as `Documents and Settings`, or a cloud or deduplicated file
+pub fn is_link(metadata: &fs::Metadata) -> bool {
+    metadata.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0
+}
+
+/// Delete the file or reparse point at `path` itself. Directory symlinks and junctions are
+/// removed like empty directories, which `DeleteFileW` refuses, and their target is left alone.
+pub fn remove_file_or_link(path: &Path) -> std::io::Result<()> {
+    let attributes = fs::symlink_metadata(path)?.file_attributes();
+    if attributes & FILE_ATTRIBUTE_REPARSE_POINT != 0 && attributes & FILE_ATTRIBUTE_DIRECTORY != 0 {
+        fs::remove_dir(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// `root` as a NUL-terminated wide string
+fn wide_root(root: &Path) -> Vec<u16> {
+    root.as_os_str().encode_wide().chain(std::iter::once(0)).collect()
+}
+
+/// Roots of the fixed drives, such as `C:\`, skipping removable, network and optical drives
+pub fn fixed_drives() -> Vec<PathBuf> {
+    // SAFETY: GetLogicalDrives has no preconditions
+    let mask = unsafe { GetLogicalDrives() };
+    super::drive_roots(mask)
+        .into_iter()
+        .filter(|root| {
+            let wide = wide_root(root);
+            // SAFETY: `wide` is a NUL-terminated root path that outlives the call
+            unsafe { GetDriveTypeW(wide.as_ptr()) == DRIVE_FIXED }
+        })
+        .collect()
+}
+
+/// Root of the volume holding `path`, such as `C:\` or a mounted folder, from
+/// `GetVolumePathNameW`. A path that does not exist yet belongs to the volume of its nearest
+/// existing ancestor.
+pub fn volume_root(path: &Path) -> Option<PathBuf> {
+    let existing = path.ancestors().find(|dir| dir.exists())?;
+    let wide = wide_root(existing);
+    let mut buffer = [0u16; MAX_PATH + 1];
+    // SAFETY: `wide` is NUL-terminated and `buffer` is writable for the length passed
+    let found = unsafe { GetVolumePathNameW(wide.as_ptr(), buffer.as_mut_ptr(), buffer.len() as DWORD) };
+    if found == 0 {
+        debug!("No volume found for {}: {}", path.display(), std::io::Error::last_os_error());
+        return None;
+    }
+    let len = buffer.iter().position(|&unit| unit == 0).unwrap_or(buffer.len());
+    Some(PathBuf::from(OsString::from_wide(&buffer[..len])))
+}
+
+/// Kind of the volume holding `path`, from `GetDriveTypeW`: mapped drives and UNC shares are
+/// network volumes, USB drives, memory cards and discs removable ones
+pub fn volume_kind(path: &Path) -> VolumeKind {
+    let Some(root) = volume_root(path) else {
+        return VolumeKind::Local;
+    };
+    let wide = wide_root(&root);
+    // SAFETY: `wide` is a NUL-terminated root path that outlives the call
+    match unsafe { GetDriveTypeW(wide.as_ptr()) } {
+        DRIVE_REMOTE => VolumeKind::Network,
+        DRIVE_REMOVABLE | DRIVE_CDROM => VolumeKind::Removable,
+        _ => VolumeKind::Local,
+    }
+}
 
-/// Clean the Windows Recycle Bin
-pub fn clean_recycle_bin(dry_run: bool) -> Result<()> {
-    info!("Checking Windows Recycle Bin...");
-    
+/// Space on the volume holding `path`, from `GetDiskFreeSpaceExW`
+pub fn disk_usage(path: &Path) -> Result<DiskUsage> {
+    let root = volume_root(path)
+        .ok_or_else(|| CleanError::WindowsError(format!("no volume holds {}", path.display())))?;
+    let wide = wide_root(&root);
+    // SAFETY: ULARGE_INTEGER is plain data
+    let (mut available, mut total, mut free): (ULARGE_INTEGER, ULARGE_INTEGER, ULARGE_INTEGER) =
+        unsafe { (mem::zeroed(), mem::zeroed(), mem::zeroed()) };
+    // SAFETY: `wide` is a NUL-terminated root path and the three outputs are writable
+    if unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, &mut total, &mut free) } == 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    // SAFETY: QuadPart spans the whole union
     unsafe {
-        if dry_run {
-            info!("[DRY RUN] Would empty the Recycle Bin");
-            return Ok(());
-        }
-        
-        // Empty the recycle bin
-        info!("Emptying Recycle Bin...");
-        let result = SHEmptyRecycleBinW(
+        Ok(DiskUsage {
+            total: *total.QuadPart(),
+            free: *free.QuadPart(),
+            available: *available.QuadPart(),
+        })
+    }
+}
+
+/// Drives whose Recycle Bin `query_recycle_bin_at` and `clean_recycle_bin_at` accept one by one
+pub fn recycle_bin_roots() -> Vec<PathBuf> {
+    fixed_drives()
+}
+
+/// SID of the current user, such as `S-1-5-21-...-1001`, naming their folder in `$Recycle.Bin`
+fn current_user_sid() -> Option<&'static str> {
+    static SID: OnceLock<Option<String>> = OnceLock::new();
+    SID.get_or_init(|| {
+        // One CSV line: "DOMAIN\user","S-1-5-21-..."
+        let output = Command::new("whoami").args(["/user", "/fo", "csv", "/nh"]).output().ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let sid = text.trim().rsplit(',').next()?.trim_matches('"');
+        sid.starts_with("S-").then(|| sid.to_string())
+    })
+    .as_deref()
+}
+
+/// Items in the current user's Recycle Bin on the drive `root`, or on all fixed drives with
+/// `None`, read from the `$I` index files in `$Recycle.Bin\<SID>`. Largest first per drive.
+pub fn list_recycle_bin(root: Option<&Path>) -> Vec<RecycleBinEntry> {
+    let Some(sid) = current_user_sid() else {
+        debug!("Cannot list the Recycle Bin: the current user's SID is unknown");
+        return Vec::new();
+    };
+    let drives = root.map_or_else(fixed_drives, |root| vec![root.to_path_buf()]);
+    drives
+        .iter()
+        .flat_map(|drive| {
+            let dir = drive.join("$Recycle.Bin").join(sid);
+            read_index_dir(&dir).unwrap_or_else(|e| {
+                debug!("Failed to list {}: {}", dir.display(), e);
+                Vec::new()
+            })
+        })
+        .collect()
+}
+
+/// Size and number of items in the Recycle Bins of all drives
+pub fn query_recycle_bin() -> Result<RecycleBinInfo> {
+    query_recycle_bin_at(None)
+}
+
+/// Size and number of items in the Recycle Bin of the drive `root`, such as `D:\`, or of all
+/// drives with `None`
+pub fn query_recycle_bin_at(root: Option<&Path>) -> Result<RecycleBinInfo> {
+    let wide = root.map(wide_root);
+    // SAFETY: SHQUERYRBINFO is plain data; all zeroes is valid before cbSize is set
+    let mut info: SHQUERYRBINFO = unsafe { mem::zeroed() };
+    info.cbSize = mem::size_of::<SHQUERYRBINFO>() as DWORD;
+    // SAFETY: a null root path queries every drive; `wide` and `info` outlive the call
+    let result = unsafe { SHQueryRecycleBinW(wide.as_ref().map_or(ptr::null(), |wide| wide.as_ptr()), &mut info) };
+    if result != 0 {
+        return Err(CleanError::WindowsError(format!("SHQueryRecycleBinW failed: 0x{:08X}", result)));
+    }
+    Ok(RecycleBinInfo {
+        bytes: info.i64Size.max(0) as u64,
+        items: info.i64NumItems.max(0) as u64,
+    })
+}
+
+/// Clean the Recycle Bins of all drives. Returns the bytes freed, or with `dry_run` the bytes
+/// that would be, as measured by `query_recycle_bin` beforehand.
+pub fn clean_recycle_bin(dry_run: bool) -> Result<u64> {
+    clean_recycle_bin_at(None, dry_run)
+}
+
+/// Clean the Recycle Bin of the drive `root`, or of all drives with `None`, like
+/// `clean_recycle_bin`. A dry run over all drives logs the size of each fixed drive's bin.
+pub fn clean_recycle_bin_at(root: Option<&Path>, dry_run: bool) -> Result<u64> {
+    let name = root.map_or_else(|| "all drives".to_string(), |root| root.display().to_string());
+    info!("Checking Windows Recycle Bin ({})...", name);
+    let usage = query_recycle_bin_at(root).unwrap_or_else(|e| {
+        warn!("Failed to query Recycle Bin: {}", e);
+        RecycleBinInfo::default()
+    });
+    info!("Recycle Bin holds {} items ({} bytes)", usage.items, usage.bytes);
+
+    if dry_run {
+        if root.is_none() {
+            for drive in fixed_drives() {
+                if let Ok(drive_usage) = query_recycle_bin_at(Some(&drive)) {
+                    info!("[DRY RUN] {}: {} items ({} bytes)", drive.display(), drive_usage.items, drive_usage.bytes);
+                }
+            }
+        }
+        info!("[DRY RUN] Would empty the Recycle Bin ({})", name);
+        return Ok(usage.bytes);
+    }
+
+    // Empty the recycle bin
+    info!("Emptying Recycle Bin ({})...", name);
+    let wide = root.map(wide_root);
+    // SAFETY: no owner window; a null root path empties the bins of all drives, and `wide`
+    // outlives the call
+    let result = unsafe {
+        SHEmptyRecycleBinW(
             ptr::null_mut(),
-            ptr::null(),
+            wide.as_ref().map_or(ptr::null(), |wide| wide.as_ptr()),
             SHERB_NOCONFIRMATION | SHERB_NOPROGRESSUI | SHERB_NOSOUND,
-        );
-        
-        if result == 0 {
-            info!("Recycle Bin emptied successfully");
-            Ok(())
+        )
+    };
+
+    if result == 0 {
+        info!("Recycle Bin emptied successfully");
+        Ok(usage.bytes)
+    } else {
+        warn!("Failed to empty Recycle Bin (error: {}). This is not critical.", result);
+        // Don't fail the entire operation if recycle bin fails
+        Ok(0)
+    }
+}
+
+/// List the image names of all running processes (e.g. `chrome.exe`)
+pub fn running_process_names() -> Vec<String> {
+    match Command::new("tasklist").args(["/FO", "CSV", "/NH"]).output() {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split("\",\"").next())
+            .map(|name| name.trim_matches('"').to_string())
+            .filter(|name| !name.is_empty())
+            .collect(),
+        Err(e) => {
+            debug!("Failed to list processes: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Clear the read-only attribute of `path`, which blocks deleting it on Windows.
+/// Returns whether anything was changed.
+#[allow(clippy::permissions_set_readonly_false)]
+pub fn make_deletable(path: &Path) -> bool {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return false;
+    };
+    let mut permissions = metadata.permissions();
+    if !permissions.readonly() {
+        return false;
+    }
+    permissions.set_readonly(false);
+    fs::set_permissions(path, permissions).is_ok()
+}
+
+/// Whether a process with this pid is running
+pub fn process_exists(pid: u32) -> bool {
+    let filter = format!("PID eq {}", pid);
+    match Command::new("tasklist").args(["/FI", &filter, "/FO", "CSV", "/NH"]).output() {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid)),
+        Err(e) => {
+            debug!("Failed to look up process {}: {}", pid, e);
+            false
+        }
+    }
+}
+
+/// What `flush_dns_cache` calls, as shown in logs and in a dry run
+const DNS_FLUSH_CALL: &str = "DnsFlushResolverCache (dnsapi.dll)";
+
+/// Call `DnsFlushResolverCache`, which dnsapi.dll exports without documenting it, so it is
+/// looked up at run time. Returns false when it is missing or fails.
+fn dns_flush_resolver_cache() -> bool {
+    let name: Vec<u16> = "dnsapi.dll".encode_utf16().chain(std::iter::once(0)).collect();
+    // SAFETY: `name` is NUL-terminated; the function pointer is only called while the module
+    // is loaded, and DnsFlushResolverCache takes no arguments and returns a BOOL
+    unsafe {
+        let module = LoadLibraryW(name.as_ptr());
+        if module.is_null() {
+            return false;
+        }
+        let address = GetProcAddress(module, c"DnsFlushResolverCache".as_ptr());
+        let flushed = !address.is_null() && {
+            let flush = mem::transmute::<FARPROC, unsafe extern "system" fn() -> BOOL>(address);
+            flush() != 0
+        };
+        FreeLibrary(module);
+        flushed
+    }
+}
+
+/// Command lines `flush_dns_cache` would run: the resolver cache is always there
+pub fn dns_flush_commands() -> Vec<String> {
+    vec![DNS_FLUSH_CALL.to_string()]
+}
+
+/// Flush the DNS resolver cache, or with `dry_run` only log how, and return what was run.
+/// Falls back to `ipconfig /flushdns` when `DnsFlushResolverCache` is unavailable.
+pub fn flush_dns_cache(dry_run: bool) -> Result<Vec<String>> {
+    if dry_run {
+        info!("[DRY RUN] Would run: {}", DNS_FLUSH_CALL);
+        return Ok(dns_flush_commands());
+    }
+    if dns_flush_resolver_cache() {
+        info!("Flushed DNS cache: {}", DNS_FLUSH_CALL);
+        return Ok(dns_flush_commands());
+    }
+    debug!("{} failed, falling back to ipconfig", DNS_FLUSH_CALL);
+    run_flush_commands(&[&["ipconfig", "/flushdns"]], false)
+}
+
+/// Whether the process runs with administrator rights: its token is a member of the
+/// Administrators group, which under UAC only holds once elevated. Checked once per run.
+pub fn is_elevated() -> bool {
+    static ELEVATED: OnceLock<bool> = OnceLock::new();
+    *ELEVATED.get_or_init(|| {
+        let mut nt_authority = SID_IDENTIFIER_AUTHORITY { Value: SECURITY_NT_AUTHORITY };
+        let mut administrators: PSID = ptr::null_mut();
+        // SAFETY: the authority and the out pointer outlive the call; unused sub-authorities are 0
+        let allocated = unsafe {
+            AllocateAndInitializeSid(
+                &mut nt_authority,
+                2,
+                SECURITY_BUILTIN_DOMAIN_RID,
+                DOMAIN_ALIAS_RID_ADMINS,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                &mut administrators,
+            )
+        };
+        if allocated == 0 {
+            debug!("Failed to build the Administrators SID: {}", std::io::Error::last_os_error());
+            return false;
+        }
+        let mut is_member: BOOL = 0;
+        // SAFETY: a null token checks the calling thread's token; the SID was allocated above
+        let checked = unsafe { CheckTokenMembership(ptr::null_mut(), administrators, &mut is_member) };
+        // SAFETY: allocated by AllocateAndInitializeSid and not used afterwards
+        unsafe { FreeSid(administrators) };
+        checked != 0 && is_member != 0
+    })
+}
+
+/// Guards alive in the process; background mode is process-wide, so the first begins it and
+/// the last ends it
+static BACKGROUND_GUARDS: Mutex<usize> = Mutex::new(0);
+
+/// Background mode of the process, lowering its CPU, I/O and memory priority, until dropped
+#[derive(Debug)]
+pub struct BackgroundGuard(());
+
+/// Put the process in background mode with `PROCESS_MODE_BACKGROUND_BEGIN` so other programs
+/// stay responsive. Nested guards share one background period.
+pub fn enter_background_mode() -> Result<BackgroundGuard> {
+    let mut guards = BACKGROUND_GUARDS.lock().unwrap_or_else(|e| e.into_inner());
+    // SAFETY: the pseudo handle of the current process needs no closing
+    if *guards == 0 && unsafe { SetPriorityClass(GetCurrentProcess(), PROCESS_MODE_BACKGROUND_BEGIN) } == 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    *guards += 1;
+    debug!("Entered background mode");
+    Ok(BackgroundGuard(()))
+}
+
+impl Drop for BackgroundGuard {
+    fn drop(&mut self) {
+        let mut guards = BACKGROUND_GUARDS.lock().unwrap_or_else(|e| e.into_inner());
+        *guards -= 1;
+        // SAFETY: the pseudo handle of the current process needs no closing
+        if *guards == 0 && unsafe { SetPriorityClass(GetCurrentProcess(), PROCESS_MODE_BACKGROUND_END) } == 0 {
+            debug!("Failed to leave background mode: {}", std::io::Error::last_os_error());
+        }
+    }
+}
+
+/// `RESTOREPOINTINFOW` from srrestoreptapi.h, which is packed to 1 byte
+#[repr(C, packed)]
+struct RestorePointInfo {
+    event_type: DWORD,
+    restore_point_type: DWORD,
+    sequence_number: i64,
+    description: [u16; 256],
+}
+
+/// `STATEMGRSTATUS` from srrestoreptapi.h
+#[repr(C, packed)]
+#[derive(Default)]
+struct StateManagerStatus {
+    status: DWORD,
+    sequence_number: i64,
+}
+
+const BEGIN_SYSTEM_CHANGE: DWORD = 100;
+const END_SYSTEM_CHANGE: DWORD = 101;
+const MODIFY_SETTINGS: DWORD = 12;
+/// `nStatus` when System Restore is turned off, `ERROR_SERVICE_DISABLED`
+const SR_DISABLED: DWORD = 1058;
+
+type SrSetRestorePoint = unsafe extern "system" fn(*mut RestorePointInfo, *mut StateManagerStatus) -> BOOL;
+
+/// Create a System Restore point named `description`, as a begin and end pair of
+/// `SRSetRestorePointW` calls. srclient.dll is loaded at run time since Windows Server
+/// editions do not ship it.
+///
+/// Needs administrator rights. Windows skips the point when another was created within the
+/// last 24 hours, which still counts as success.
+pub fn create_restore_point(description: &str) -> Result<()> {
+    if !is_elevated() {
+        return Err(CleanError::WindowsError("administrator rights are required to create a restore point".to_string()));
+    }
+    let mut info = RestorePointInfo {
+        event_type: BEGIN_SYSTEM_CHANGE,
+        restore_point_type: MODIFY_SETTINGS,
+        sequence_number: 0,
+        description: [0; 256],
+    };
+    // Keep the last unit as the terminating NUL
+    for (unit, c) in info.description.iter_mut().take(255).zip(description.encode_utf16()) {
+        *unit = c;
+    }
+
+    let name: Vec<u16> = "srclient.dll".encode_utf16().chain(std::iter::once(0)).collect();
+    // SAFETY: `name` is NUL-terminated; the function pointer is only called while the module
+    // is loaded, with structs laid out as srrestoreptapi.h declares them
+    let status = unsafe {
+        let module = LoadLibraryW(name.as_ptr());
+        if module.is_null() {
+            let missing = "System Restore is not available (srclient.dll is missing)";
+            return Err(CleanError::WindowsError(missing.to_string()));
+        }
+        let address = GetProcAddress(module, c"SRSetRestorePointW".as_ptr());
+        let status = if address.is_null() {
+            None
         } else {
-            warn!("Failed to empty Recycle Bin (error: {}). This is not critical.", result);
-            // Don't fail the entire operation if recycle bin fails
+            let set_restore_point = mem::transmute::<FARPROC, SrSetRestorePoint>(address);
+            let mut status = StateManagerStatus::default();
+            if set_restore_point(&mut info, &mut status) != 0 {
+                info.event_type = END_SYSTEM_CHANGE;
+                info.sequence_number = status.sequence_number;
+                set_restore_point(&mut info, &mut status);
+                Some(0)
+            } else {
+                Some(status.status)
+            }
+        };
+        FreeLibrary(module);
+        status
+    };
+    match status {
+        Some(0) => {
+            info!("Created restore point: {}", description);
             Ok(())
         }
+        Some(SR_DISABLED) => Err(CleanError::WindowsError("System Restore is turned off".to_string())),
+        Some(code) => Err(CleanError::WindowsError(format!("SRSetRestorePointW failed with status {}", code))),
+        None => Err(CleanError::WindowsError("srclient.dll does not export SRSetRestorePointW".to_string())),
     }
 }
+
+/// Stop a Windows service and wait until it has stopped. Returns whether it was stopped.
+pub fn stop_service(name: &str) -> bool {
+    run_net(&["stop", name, "/y"])
+}
+
+/// Start a Windows service. Returns whether it was started.
+pub fn start_service(name: &str) -> bool {
+    run_net(&["start", name])
+}
+
+fn run_net(args: &[&str]) -> bool {
+    match Command::new("net").args(args).output() {
+        Ok(output) if output.status.success() => {
+            info!("net {} succeeded", args.join(" "));
+            true
+        }
+        Ok(output) => {
+            warn!("net {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+            false
+        }
+        Err(e) => {
+            warn!("Failed to run net {}: {}", args.join(" "), e);
+            false
+        }
+    }
+}
+
+/// Whether deleting a file failed because another process has it open
+/// (`ERROR_SHARING_VIOLATION` or `ERROR_LOCK_VIOLATION`)
+pub fn is_in_use(error: &std::io::Error) -> bool {
+    matches!(error.raw_os_error(), Some(32 | 33))
+}
+
+/// Files registered with the Restart Manager in one session
+const RM_BATCH: usize = 64;
+
+/// `ERROR_MORE_DATA`, returned by `RmGetList` when the process array is too small
+const ERROR_MORE_DATA: DWORD = 234;
+
+/// Open files cannot be listed up front on Windows; `file_holders` asks the Restart Manager
+/// about files that failed to delete instead. Always empty.
+pub fn open_files() -> OpenFiles {
+    OpenFiles::default()
+}
+
+/// Executable name of process `pid`, such as `chrome.exe`
+fn process_image_name(pid: DWORD) -> Option<String> {
+    let mut buffer = [0u16; MAX_PATH];
+    let mut len = buffer.len() as DWORD;
+    // SAFETY: the handle is checked and closed; the buffer length is passed along with it
+    let queried = unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            return None;
+        }
+        let queried = QueryFullProcessImageNameW(process, 0, buffer.as_mut_ptr(), &mut len) != 0;
+        CloseHandle(process);
+        queried
+    };
+    if !queried {
+        return None;
+    }
+    let path = PathBuf::from(OsString::from_wide(&buffer[..len as usize]));
+    Some(path.file_name()?.to_string_lossy().into_owned())
+}
+
+/// Processes holding any of `paths` open, from one Restart Manager session
+fn restart_manager_holders(paths: &[PathBuf]) -> Vec<FileHolder> {
+    let wide: Vec<Vec<u16>> = paths.iter().map(|path| wide_root(&long_path(path))).collect();
+    let mut names: Vec<*const u16> = wide.iter().map(|path| path.as_ptr()).collect();
+    let mut session: DWORD = 0;
+    // CCH_RM_SESSION_KEY characters and the terminating NUL
+    let mut key = [0u16; 33];
+    // SAFETY: every pointer outlives the session, and the process array is as long as
+    // `count` says; the session is ended on every path
+    unsafe {
+        if RmStartSession(&mut session, 0, key.as_mut_ptr()) != 0 {
+            return Vec::new();
+        }
+        let mut infos: Vec<RM_PROCESS_INFO> = Vec::new();
+        let mut status = RmRegisterResources(
+            session,
+            names.len() as UINT,
+            names.as_mut_ptr(),
+            0,
+            ptr::null_mut(),
+            0,
+            ptr::null_mut(),
+        );
+        if status == 0 {
+            // Processes can open the files between the calls, so retry while the array is short
+            loop {
+                let mut needed: UINT = 0;
+                let mut count = infos.len() as UINT;
+                let mut reasons: DWORD = 0;
+                status = RmGetList(session, &mut needed, &mut count, infos.as_mut_ptr(), &mut reasons);
+                if status == ERROR_MORE_DATA {
+                    infos = (0..needed).map(|_| mem::zeroed()).collect();
+                    continue;
+                }
+                infos.truncate(count as usize);
+                break;
+            }
+        }
+        RmEndSession(session);
+        if status != 0 {
+            debug!("Restart Manager failed with {}", status);
+            return Vec::new();
+        }
+        infos
+            .iter()
+            .map(|info| {
+                let pid = info.Process.dwProcessId;
+                let app_name = &info.strAppName;
+                let app_len = app_name.iter().position(|&unit| unit == 0).unwrap_or(app_name.len());
+                let name = process_image_name(pid).unwrap_or_else(|| String::from_utf16_lossy(&app_name[..app_len]));
+                FileHolder { pid, name }
+            })
+            .collect()
+    }
+}
+
+/// Processes holding each of `paths` open, in the same order, from the Restart Manager.
+///
+/// Files are registered in batches; only a batch some process holds is asked about file by
+/// file, since a session reports its processes for all of its files together.
+pub fn file_holders(paths: &[PathBuf]) -> Vec<Vec<FileHolder>> {
+    let mut holders = Vec::with_capacity(paths.len());
+    for batch in paths.chunks(RM_BATCH) {
+        if batch.len() > 1 && restart_manager_holders(batch).is_empty() {
+            holders.extend(batch.iter().map(|_| Vec::new()));
+        } else {
+            holders.extend(batch.iter().map(|path| restart_manager_holders(std::slice::from_ref(path))));
+        }
+    }
+    holders
+}
+
+/// Message asking the taskbar to shut Explorer down, as "Exit Explorer" in its
+/// Ctrl+Shift context menu does
+const WM_EXIT_EXPLORER: UINT = WM_USER + 436;
+
+/// How long Explorer gets to exit on its own before it is terminated
+const EXPLORER_EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn explorer_running() -> bool {
+    running_process_names().iter().any(|name| name.eq_ignore_ascii_case("explorer.exe"))
+}
+
+/// Ask the taskbar to exit and wait for every Explorer process to be gone. Returns false when
+/// there is no taskbar or Explorer is still running after `EXPLORER_EXIT_TIMEOUT`.
+fn close_explorer() -> bool {
+    let class: Vec<u16> = "Shell_TrayWnd".encode_utf16().chain(std::iter::once(0)).collect();
+    // SAFETY: `class` is NUL-terminated and outlives the call; a null window name matches any
+    let taskbar = unsafe { FindWindowW(class.as_ptr(), ptr::null()) };
+    // SAFETY: posting to a window handle that may have gone away since only fails
+    if taskbar.is_null() || unsafe { PostMessageW(taskbar, WM_EXIT_EXPLORER, 0, 0) } == 0 {
+        return false;
+    }
+    let deadline = Instant::now() + EXPLORER_EXIT_TIMEOUT;
+    while Instant::now() < deadline {
+        if !explorer_running() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(250));
+    }
+    false
+}
+
+/// Stop Explorer so the files it keeps open can be deleted: the taskbar is asked to exit first
+/// and Explorer is only terminated when it does not. Returns whether it was running and has
+/// been stopped; the desktop and taskbar disappear until [`start_explorer`], which callers
+/// must also run when this fails, as the taskbar may already be gone.
+pub fn stop_explorer() -> bool {
+    if !explorer_running() {
+        return false;
+    }
+    if close_explorer() {
+        info!("Closed Explorer");
+        return true;
+    }
+    debug!("Explorer did not exit on its own, terminating it");
+    match Command::new("taskkill").args(["/F", "/IM", "explorer.exe"]).output() {
+        Ok(output) if output.status.success() => {
+            info!("Stopped Explorer");
+            true
+        }
+        Ok(output) => {
+            debug!("taskkill explorer.exe failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+            false
+        }
+        Err(e) => {
+            warn!("Failed to run taskkill: {}", e);
+            false
+        }
+    }
+}
+
+/// Start Explorer again after [`stop_explorer`]. Nothing is started when Windows has already
+/// restarted the shell itself, as a second `explorer.exe` would only open a folder window.
+pub fn start_explorer() -> bool {
+    if explorer_running() {
+        debug!("Explorer is already running");
+        return true;
+    }
+    match Command::new("explorer.exe").spawn() {
+        Ok(_) => {
+            info!("Started Explorer");
+            true
+        }
+        Err(e) => {
+            warn!("Failed to start Explorer: {}", e);
+            false
+        }
+    }
+}
+
+/// Starts Explorer when dropped, so the shell comes back even if cleaning panics
+struct ExplorerRestart;
+
+impl Drop for ExplorerRestart {
+    fn drop(&mut self) {
+        start_explorer();
+    }
+}
+
+/// Run `f` with Explorer stopped, then start Explorer again, also when `f` panics.
+///
+/// Fails without running `f` when Explorer is not running or cannot be stopped. The shell is
+/// started again on failure too, since the taskbar may have exited before stopping gave up.
+pub fn with_explorer_stopped<R>(f: impl FnOnce() -> R) -> Result<R> {
+    if !explorer_running() {
+        return Err(CleanError::WindowsError("Explorer is not running".to_string()));
+    }
+    let _restart = ExplorerRestart;
+    if !stop_explorer() {
+        return Err(CleanError::WindowsError("Explorer could not be stopped".to_string()));
+    }
+    Ok(f())
+}
+
+/// Whether NTFS compression is enabled for a file
+pub fn is_compressed(metadata: &fs::Metadata) -> bool {
+    metadata.file_attributes() & FILE_ATTRIBUTE_COMPRESSED != 0
+}
+
+/// Bytes a file occupies on disk, which is less than its length when it is compressed
+pub fn compressed_size(path: &Path) -> std::io::Result<u64> {
+    let wide: Vec<u16> = long_path(path).as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut high: DWORD = 0;
+    let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+    if low == INVALID_FILE_SIZE {
+        // INVALID_FILE_SIZE is also a valid low word; only an error code tells them apart
+        let error = std::io::Error::last_os_error();
+        if error.raw_os_error() != Some(0) {
+            return Err(error);
+        }
+    }
+    Ok((u64::from(high) << 32) | u64::from(low))
+}
+
+/// Bytes a file occupies on disk: for NTFS-compressed and sparse files what
+/// `GetCompressedFileSizeW` reports, for others their length
+pub fn disk_size(path: &Path, metadata: &fs::Metadata) -> u64 {
+    if metadata.file_attributes() & (FILE_ATTRIBUTE_COMPRESSED | FILE_ATTRIBUTE_SPARSE_FILE) == 0 {
+        return metadata.len();
+    }
+    compressed_size(path).unwrap_or(metadata.len())
+}
+
+/// Compress a file in place with NTFS compression (`compact /c`), keeping it readable.
+/// Returns the bytes saved on disk.
+pub fn compress_file(path: &Path) -> std::io::Result<u64> {
+    let before = fs::metadata(path)?.len();
+    let output = Command::new("compact").arg("/c").arg("/q").arg(path).output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "compact /c exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stdout).trim()
+        )));
+    }
+    Ok(before.saturating_sub(compressed_size(path)?))
+}
+
+/// POSIX lock files such as apt's do not exist on Windows; always returns false
+pub fn is_locked(_path: &Path) -> bool {
+    false
+}
+
+/// Files have no uid owner on Windows; always returns None
+pub fn file_owner(_metadata: &fs::Metadata) -> Option<u32> {
+    None
+}
+
+/// Processes have no uid on Windows; always returns None
+pub fn current_uid() -> Option<u32> {
+    None
+}
+
+/// Sockets and FIFOs do not appear as files on Windows; always returns false
+pub fn is_special_file(_file_type: &fs::FileType) -> bool {
+    false
+}
+
+/// The volume serial number is not exposed by std on stable; always returns None, so every
+/// directory counts as being on the same filesystem
+pub fn device_id(_metadata: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// Move each of `paths` to the Recycle Bin, returning for each one how it was moved or why
+/// it was not
+pub fn trash_files(paths: &[PathBuf]) -> Vec<std::io::Result<TrashBackend>> {
+    paths.iter().map(|path| move_to_trash(path).map(|()| TrashBackend::Builtin)).collect()
+}
+
+/// Move `path` to the Recycle Bin so it can still be restored
+pub fn move_to_trash(path: &Path) -> std::io::Result<()> {
+    let path = std::path::absolute(path)?;
+    // A list of paths, each ended by a NUL, with another NUL after the last one
+    let from: Vec<u16> = path.as_os_str().encode_wide().chain([0, 0]).collect();
+    let mut operation = SHFILEOPSTRUCTW {
+        hwnd: ptr::null_mut(),
+        wFunc: FO_DELETE,
+        pFrom: from.as_ptr(),
+        pTo: ptr::null(),
+        fFlags: FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_NOERRORUI | FOF_SILENT,
+        fAnyOperationsAborted: 0,
+        hNameMappings: ptr::null_mut(),
+        lpszProgressTitle: ptr::null(),
+    };
+    // SAFETY: `from` is double-NUL terminated and outlives the call
+    let code = unsafe { SHFileOperationW(&mut operation) };
+    if code == 0 && operation.fAnyOperationsAborted == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "moving {} to the Recycle Bin failed with {:#x}",
+            path.display(),
+            code
+        )))
+    }
+}
+
+/// Volume serial number and file index of a file, equal for every hard link to it
+pub fn file_id(path: &Path) -> Option<(u64, u64)> {
+    use std::os::windows::io::AsRawHandle;
+
+    let file = fs::File::open(path).ok()?;
+    // SAFETY: the handle stays open for the call and `info` is a plain struct it fills in
+    let info = unsafe {
+        let mut info: BY_HANDLE_FILE_INFORMATION = std::mem::zeroed();
+        if GetFileInformationByHandle(file.as_raw_handle() as _, &mut info) == 0 {
+            return None;
+        }
+        info
+    };
+    let index = (u64::from(info.nFileIndexHigh) << 32) | u64::from(info.nFileIndexLow);
+    Some((u64::from(info.dwVolumeSerialNumber), index))
+}
@@ -0,0 +1,97 @@
+//! Persistence of the user's item selection between runs
+
+use crate::apps::ElectronApp;
+use crate::cleanup_items::CleanupItem;
+use crate::error::Result;
+use crate::options::ItemOptions;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Saved choices for a single cleanup item, matched by id
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemState {
+    pub id: String,
+    pub enabled: bool,
+    #[serde(default)]
+    pub options: ItemOptions,
+}
+
+impl From<&CleanupItem> for ItemState {
+    fn from(item: &CleanupItem) -> Self {
+        Self {
+            id: item.id.clone(),
+            enabled: item.enabled,
+            options: item.options.clone(),
+        }
+    }
+}
+
+/// On-disk layout of the state file
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StateFile {
+    #[serde(default)]
+    items: Vec<ItemState>,
+    /// User additions to the built-in Electron app table; only ever edited by hand
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    electron_apps: Vec<ElectronApp>,
+}
+
+/// Default location of the state file (`<config dir>/clean-rs/items.json`)
+pub fn default_state_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("clean-rs").join("items.json"))
+}
+
+/// Save the enablement and options of every item, keeping hand-written settings
+pub fn save_item_state(path: &Path, items: &[CleanupItem]) -> Result<()> {
+    let file = StateFile {
+        items: items.iter().map(ItemState::from).collect(),
+        electron_apps: load_electron_apps(path).unwrap_or_default(),
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&file)?)?;
+    debug!("Saved state for {} items to {}", file.items.len(), path.display());
+    Ok(())
+}
+
+/// Load saved item states. A missing file yields an empty list.
+pub fn load_item_state(path: &Path) -> Result<Vec<ItemState>> {
+    Ok(read_state_file(path)?.items)
+}
+
+/// Load the extra Electron apps listed in the state file. A missing file yields an empty list.
+pub fn load_electron_apps(path: &Path) -> Result<Vec<ElectronApp>> {
+    Ok(read_state_file(path)?.electron_apps)
+}
+
+fn read_state_file(path: &Path) -> Result<StateFile> {
+    if !path.exists() {
+        return Ok(StateFile::default());
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Merge saved states into freshly built items by id.
+///
+/// Unknown ids are ignored and items without a saved state keep their defaults.
+/// Items that are currently unavailable stay disabled.
+pub fn apply_item_state(items: &mut [CleanupItem], states: &[ItemState]) {
+    for item in items.iter_mut() {
+        if let Some(state) = states.iter().find(|state| state.id == item.id) {
+            item.enabled = state.enabled && item.status.is_ready();
+            item.options = state.options.clone();
+        }
+    }
+}
+
+/// Merge only the saved per-item options (patterns, priority overrides), leaving selection alone
+pub fn apply_item_options(items: &mut [CleanupItem], states: &[ItemState]) {
+    for item in items.iter_mut() {
+        if let Some(state) = states.iter().find(|state| state.id == item.id) {
+            item.options = state.options.clone();
+        }
+    }
+}
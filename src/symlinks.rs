@@ -0,0 +1,178 @@
+//! Finding symlinks whose target no longer exists, such as those left behind by uninstalled
+//! programs in `~/.local/bin`.
+
+use crate::cleanup_items::{
+    self, BreakdownEntry, Category, CleanErrorEntry, CleanTarget, CleanupItem, CleanupResult, CleanupType, ItemContext,
+    ItemStatus, RiskLevel,
+};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::debug;
+
+/// Links followed before a chain is considered a loop, as the kernel's `SYMLOOP_MAX`
+pub const MAX_LINK_HOPS: usize = 40;
+
+/// Locations owned by the system's package managers; links in them are never reported
+pub const PROTECTED_DIRS: &[&str] = &[
+    "/usr",
+    "/bin",
+    "/sbin",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/etc",
+    "/opt",
+    "/var/lib",
+    "/nix",
+    "/snap",
+    "/System",
+    "/Library",
+    "/Applications",
+];
+
+/// Whether `path` is inside a location managed by a package manager
+pub fn is_protected(path: &Path) -> bool {
+    PROTECTED_DIRS.iter().any(|dir| path.starts_with(dir))
+}
+
+/// Whether `link` is a symlink whose target, after following every link in the chain, does
+/// not exist.
+///
+/// Relative targets are resolved against the directory holding the link. A chain longer than
+/// `MAX_LINK_HOPS` is a loop and counts as broken. Anything that cannot be checked, such as a
+/// target in a directory that cannot be read, is not.
+pub fn is_broken_symlink(link: &Path) -> bool {
+    let mut current = link.to_path_buf();
+    for hop in 0..MAX_LINK_HOPS {
+        match fs::symlink_metadata(&current) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                let Ok(target) = fs::read_link(&current) else {
+                    return false;
+                };
+                // Joining an absolute target replaces the directory
+                current = match current.parent() {
+                    Some(dir) => dir.join(target),
+                    None => target,
+                };
+            }
+            Ok(_) => return false,
+            Err(e) => {
+                return hop > 0 && matches!(e.kind(), io::ErrorKind::NotFound | io::ErrorKind::NotADirectory);
+            }
+        }
+    }
+    true
+}
+
+/// Broken symlinks below `roots`, sorted.
+///
+/// Symlinked directories are not followed, and directories in `excluded` or in
+/// `PROTECTED_DIRS` are not searched.
+pub fn broken_symlinks(roots: &[PathBuf], excluded: &[PathBuf]) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut pending: Vec<PathBuf> = roots.iter().filter(|root| !is_protected(root)).cloned().collect();
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if is_protected(&path) {
+                continue;
+            }
+            if file_type.is_dir() {
+                if !excluded.contains(&path) {
+                    pending.push(path);
+                }
+            } else if file_type.is_symlink() && is_broken_symlink(&path) {
+                found.push(path);
+            }
+        }
+    }
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// Broken symlinks below `roots`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenSymlinks {
+    pub roots: Vec<PathBuf>,
+}
+
+impl BrokenSymlinks {
+    /// Broken symlinks below the roots, outside the user's exclusions
+    fn find(&self, cx: &ItemContext<'_>) -> Vec<PathBuf> {
+        broken_symlinks(cx.roots(&self.roots), cx.excluded_paths())
+    }
+}
+
+impl CleanTarget for BrokenSymlinks {
+    /// One breakdown entry per broken symlink, showing where it pointed
+    fn scan_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for link in self.find(cx) {
+            let target = fs::read_link(&link).map_or_else(|_| "?".to_string(), |target| target.display().to_string());
+            result.entries += 1;
+            result.has_data = true;
+            result.breakdown.push(BreakdownEntry {
+                label: link.display().to_string(),
+                size_bytes: 0,
+                note: format!("指向 {}", target),
+            });
+        }
+        result
+    }
+
+    /// Unlink the broken symlinks. The chain is checked again first, so a link whose target
+    /// came back is kept.
+    fn clean_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for link in self.find(cx) {
+            match fs::remove_file(&link) {
+                Ok(()) => {
+                    result.entries += 1;
+                    result.has_data = true;
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => debug!("Skipping {}: {}", link.display(), e),
+                Err(e) => result.errors.push(CleanErrorEntry::new(&link, &e)),
+            }
+        }
+        result
+    }
+
+    fn estimate_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        self.scan_in(cx)
+    }
+
+    fn validate_in(&self, cx: &ItemContext<'_>) -> ItemStatus {
+        cleanup_items::validate_roots(cx.fs(), cx.roots(&self.roots))
+    }
+}
+
+/// Item unlinking broken symlinks in `roots`, such as `~/.local/bin`, `~/.config` and the
+/// temp directory. Only the links are removed; they take no space of their own, so the result
+/// counts entries.
+///
+/// Roots can be changed with `project_roots` and folders left out with `excluded_paths`.
+/// Disabled by default, since a link may point to a drive that is not mounted right now.
+pub fn broken_symlinks_item(roots: Vec<PathBuf>) -> CleanupItem {
+    CleanupItem {
+        id: "broken_symlinks".to_string(),
+        name: "失效的符号链接".to_string(),
+        description: format!(
+            "目标已不存在的符号链接 (在 {} 中查找), 只删除链接本身",
+            roots.iter().map(|root| root.display().to_string()).collect::<Vec<_>>().join(", ")
+        ),
+        cleanup_type: CleanupType::Custom(Arc::new(BrokenSymlinks { roots })),
+        category: Category::System,
+        risk: RiskLevel::Moderate,
+        enabled: false,
+        ..Default::default()
+    }
+}
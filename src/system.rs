@@ -0,0 +1,1171 @@
+//! Caches, logs and crash data kept by the operating system itself
+
+use crate::cleanup_items::{
+    self, temp_files_item, BreakdownEntry, Category, CleanErrorEntry, CleanTarget, CleanupItem, CleanupResult,
+    CleanupType, ItemContext, ItemStatus, RiskLevel, EXCLUDED_NOTE, TEMP_MIN_AGE_DAYS,
+};
+use crate::browsers::FULL_DISK_ACCESS_HINT;
+use crate::error::CleanError;
+use crate::patterns::FilePattern;
+use crate::platform;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use tracing::debug;
+
+/// `%ProgramData%`, normally `C:\ProgramData`
+pub fn program_data() -> PathBuf {
+    env::var_os("ProgramData")
+        .filter(|path| !path.is_empty())
+        .map_or_else(|| PathBuf::from("C:\\ProgramData"), PathBuf::from)
+}
+
+/// Folders of Windows Error Reporting holding one directory per crash report
+pub const WER_REPORT_DIRS: &[&str] = &["ReportQueue", "ReportArchive"];
+
+/// Crash reports younger than this are kept so recent crashes can still be debugged
+pub const WER_MIN_AGE_DAYS: u32 = 7;
+
+/// `Microsoft\Windows\WER` below `%LOCALAPPDATA%` or `%ProgramData%`
+pub fn wer_dir(root: &Path) -> PathBuf {
+    root.join("Microsoft").join("Windows").join("WER")
+}
+
+/// Item deleting Windows Error Reporting crash reports and dumps older than 7 days, from the
+/// user's `%LOCALAPPDATA%` and the machine-wide `%ProgramData%`.
+///
+/// The machine-wide reports need administrator rights; without them that part is listed but
+/// left alone. The age can be changed with `min_age_days`. Disabled by default.
+pub fn wer_item(local_app_data: &Path, program_data: &Path) -> CleanupItem {
+    let (user, system) = (wer_dir(local_app_data), wer_dir(program_data));
+    let roots = [&user, &system]
+        .iter()
+        .flat_map(|dir| WER_REPORT_DIRS.iter().map(move |name| dir.join(name)))
+        .collect();
+    CleanupItem {
+        id: "windows_error_reporting".to_string(),
+        name: "Windows 错误报告".to_string(),
+        description: format!(
+            "{} 天前的崩溃报告和内存转储 (ReportQueue, ReportArchive); 用户: {}; 系统 (需要管理员权限): {}",
+            WER_MIN_AGE_DAYS,
+            user.display(),
+            system.display()
+        ),
+        // Each report is a directory of its own and is deleted as a whole
+        cleanup_type: CleanupType::UnusedDirs { roots, depth: 1 },
+        category: Category::Logs,
+        risk: RiskLevel::Safe,
+        min_age_days: WER_MIN_AGE_DAYS,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// `%SystemRoot%`, normally `C:\Windows`
+pub fn windows_dir() -> PathBuf {
+    env::var_os("SystemRoot")
+        .filter(|path| !path.is_empty())
+        .map_or_else(|| PathBuf::from("C:\\Windows"), PathBuf::from)
+}
+
+/// Memory dumps younger than this are kept so a recent blue screen can still be analysed
+pub const MEMORY_DUMP_MIN_AGE_DAYS: u32 = 7;
+
+/// The full kernel dump and the minidumps Windows writes after a blue screen
+pub fn memory_dump_files(windows_dir: &Path) -> Vec<PathBuf> {
+    vec![windows_dir.join("MEMORY.DMP"), windows_dir.join("Minidump").join("*.dmp")]
+}
+
+/// Item deleting blue screen memory dumps older than 7 days, each listed with its age.
+///
+/// Only `MEMORY.DMP` and `Minidump\*.dmp` are touched, never the rest of the Windows
+/// directory. Reading them needs administrator rights. Disabled by default.
+pub fn memory_dump_item(windows_dir: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "memory_dumps".to_string(),
+        name: "系统内存转储".to_string(),
+        description: format!(
+            "蓝屏后留下的 {} 和 Minidump 文件, 保留 {} 天内的转储 (需要管理员权限)",
+            windows_dir.join("MEMORY.DMP").display(),
+            MEMORY_DUMP_MIN_AGE_DAYS
+        ),
+        cleanup_type: CleanupType::Files(memory_dump_files(windows_dir)),
+        category: Category::System,
+        risk: RiskLevel::Moderate,
+        min_age_days: MEMORY_DUMP_MIN_AGE_DAYS,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// Group Policy key holding Delivery Optimization settings
+pub const DELIVERY_OPTIMIZATION_POLICY_KEY: &str = "HKLM\\SOFTWARE\\Policies\\Microsoft\\Windows\\DeliveryOptimization";
+
+/// Registry value naming the drive Delivery Optimization caches to when it has been moved
+pub const DELIVERY_OPTIMIZATION_DRIVE_VALUE: &str = "DOModifyCacheDrive";
+
+/// Service owning the Delivery Optimization cache
+pub const DELIVERY_OPTIMIZATION_SERVICE: &str = "dosvc";
+
+/// Data of value `name` in `reg query` output, such as `D:` from
+/// `    DOModifyCacheDrive    REG_SZ    D:`
+pub fn parse_reg_value(output: &str, name: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let line = line.trim_start();
+        if !line.get(..name.len())?.eq_ignore_ascii_case(name) {
+            return None;
+        }
+        let rest = line[name.len()..].strip_prefix(char::is_whitespace)?.trim_start();
+        let (kind, data) = rest.split_once(char::is_whitespace)?;
+        let data = data.trim();
+        (kind.starts_with("REG_") && !data.is_empty()).then(|| data.to_string())
+    })
+}
+
+/// Delivery Optimization cache below the Windows directory, or `DeliveryOptimization\Cache`
+/// on `cache_drive` when a policy has moved it there.
+///
+/// A drive given as an environment variable such as `%SystemDrive%` is expanded.
+pub fn delivery_optimization_cache(windows_dir: &Path, cache_drive: Option<&str>) -> PathBuf {
+    let drive = cache_drive.and_then(|drive| match drive.strip_prefix('%').and_then(|name| name.strip_suffix('%')) {
+        Some(name) => env::var(name).ok().filter(|value| !value.is_empty()),
+        None => Some(drive.to_string()),
+    });
+    match drive {
+        Some(drive) => {
+            let drive = drive.trim_end_matches('\\');
+            PathBuf::from(format!("{}\\", drive)).join("DeliveryOptimization").join("Cache")
+        }
+        None => windows_dir
+            .join("ServiceProfiles")
+            .join("NetworkService")
+            .join("AppData")
+            .join("Local")
+            .join("Microsoft")
+            .join("Windows")
+            .join("DeliveryOptimization")
+            .join("Cache"),
+    }
+}
+
+/// Cache drive configured by policy, read with `reg query`
+fn configured_delivery_optimization_drive() -> Option<String> {
+    let output = Command::new("reg")
+        .args(["query", DELIVERY_OPTIMIZATION_POLICY_KEY, "/v", DELIVERY_OPTIMIZATION_DRIVE_VALUE])
+        .output()
+        .map_err(|e| debug!("Failed to query the Delivery Optimization policy: {}", e))
+        .ok()
+        .filter(|output| output.status.success())?;
+    parse_reg_value(&String::from_utf8_lossy(&output.stdout), DELIVERY_OPTIMIZATION_DRIVE_VALUE)
+}
+
+/// Delivery Optimization cache of this machine
+pub fn current_delivery_optimization_cache() -> PathBuf {
+    delivery_optimization_cache(&windows_dir(), configured_delivery_optimization_drive().as_deref())
+}
+
+/// Item emptying the Delivery Optimization cache of update payloads shared with other PCs.
+///
+/// Windows trims this cache on its own, so the item is disabled by default. Chunks locked by
+/// the `dosvc` service are reported and skipped; with `stop_service` and administrator rights
+/// the service is stopped while cleaning instead.
+pub fn delivery_optimization_item(cache_dir: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "delivery_optimization".to_string(),
+        name: "Windows 传递优化缓存".to_string(),
+        description: format!("与其他电脑共享的 Windows 更新文件 (需要管理员权限): {}", cache_dir.display()),
+        cleanup_type: CleanupType::Directory(cache_dir.to_path_buf()),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        enabled: false,
+        service: Some(DELIVERY_OPTIMIZATION_SERVICE.to_string()),
+        ..Default::default()
+    }
+}
+
+/// Service owning the system font cache
+pub const FONT_CACHE_SERVICE: &str = "FontCache";
+
+/// The machine-wide font cache below the Windows directory, then the per-user one newer
+/// builds keep in `%LOCALAPPDATA%`
+pub fn font_cache_dirs(windows_dir: &Path, local_app_data: &Path) -> [PathBuf; 2] {
+    [
+        windows_dir
+            .join("ServiceProfiles")
+            .join("LocalService")
+            .join("AppData")
+            .join("Local")
+            .join("FontCache"),
+        local_app_data.join("FontCache"),
+    ]
+}
+
+/// Item deleting the `*.dat` and `*.tmp` files of both font caches, which Windows rebuilds
+/// on demand at a small cost to the next font-heavy startup.
+///
+/// The machine-wide cache needs administrator rights; without them only the per-user cache
+/// is cleaned and the skipped part is listed. Stopping the `FontCache` service while cleaning
+/// is opt-in through `stop_service`. Disabled by default.
+pub fn font_cache_item(windows_dir: &Path, local_app_data: &Path) -> CleanupItem {
+    let [system, user] = font_cache_dirs(windows_dir, local_app_data);
+    let patterns = [&system, &user]
+        .iter()
+        .flat_map(|dir| [dir.join("*.dat"), dir.join("*.tmp")])
+        .collect();
+    CleanupItem {
+        id: "font_cache".to_string(),
+        name: "Windows 字体缓存".to_string(),
+        description: format!(
+            "字体缓存文件 (*.dat, *.tmp), 会自动重建; 系统 (需要管理员权限): {}; 用户: {}",
+            system.display(),
+            user.display()
+        ),
+        cleanup_type: CleanupType::Files(patterns),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        enabled: false,
+        service: Some(FONT_CACHE_SERVICE.to_string()),
+        ..Default::default()
+    }
+}
+
+/// `Microsoft\Windows\Explorer` below `%LOCALAPPDATA%`, where Explorer keeps its caches
+pub fn explorer_dir(local_app_data: &Path) -> PathBuf {
+    local_app_data.join("Microsoft").join("Windows").join("Explorer")
+}
+
+/// Item deleting Explorer's thumbnail databases (`thumbcache_*.db`).
+///
+/// Explorer keeps them open, so they are skipped as in use unless `restart_explorer` is set.
+pub fn thumbnail_cache_item(local_app_data: &Path) -> CleanupItem {
+    let explorer = explorer_dir(local_app_data);
+    CleanupItem {
+        id: "thumbnail_cache".to_string(),
+        name: "缩略图缓存".to_string(),
+        description: format!("Windows 文件缩略图缓存: {}", explorer.join("thumbcache_*.db").display()),
+        cleanup_type: CleanupType::Files(vec![explorer.join("thumbcache_*.db")]),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        enabled: false,
+        explorer_lock: true,
+        ..Default::default()
+    }
+}
+
+/// Item deleting the icon cache so Windows rebuilds it, which fixes blank or wrong icons.
+///
+/// Only `IconCache.db` and `Explorer\iconcache_*.db` are touched. Explorer always has them
+/// open: they are skipped as in use unless `restart_explorer` is set, which closes Explorer
+/// while cleaning. Disabled by default.
+pub fn icon_cache_item(local_app_data: &Path) -> CleanupItem {
+    let files = vec![
+        local_app_data.join("IconCache.db"),
+        explorer_dir(local_app_data).join("iconcache_*.db"),
+    ];
+    CleanupItem {
+        id: "icon_cache".to_string(),
+        name: "图标缓存".to_string(),
+        description: format!(
+            "Windows 图标缓存 {} 和 {}, 需要重启资源管理器才能删除",
+            files[0].display(),
+            files[1].display()
+        ),
+        cleanup_type: CleanupType::Files(files),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        enabled: false,
+        explorer_lock: true,
+        ..Default::default()
+    }
+}
+
+/// Shader caches of DirectX and the GPU vendors below `%LOCALAPPDATA%`
+pub fn shader_cache_dirs(local_app_data: &Path) -> Vec<PathBuf> {
+    [
+        &["D3DSCache"][..],
+        &["NVIDIA", "DXCache"],
+        &["NVIDIA", "GLCache"],
+        &["AMD", "DxCache"],
+    ]
+    .iter()
+    .map(|components| components.iter().fold(local_app_data.to_path_buf(), |dir, name| dir.join(name)))
+    .collect()
+}
+
+/// Item emptying the shader caches found on this machine, sized per vendor directory.
+///
+/// Drivers recompile shaders on demand. Files a running game has open are skipped without
+/// an error. Enabled by default.
+pub fn shader_cache_item(local_app_data: &Path) -> CleanupItem {
+    let dirs: Vec<PathBuf> = shader_cache_dirs(local_app_data)
+        .into_iter()
+        .filter(|dir| dir.is_dir())
+        .collect();
+    let description = if dirs.is_empty() {
+        "DirectX 和显卡驱动的着色器缓存 (未找到)".to_string()
+    } else {
+        let list: Vec<String> = dirs.iter().map(|dir| dir.display().to_string()).collect();
+        format!("DirectX 和显卡驱动的着色器缓存, 会自动重新生成: {}", list.join(", "))
+    };
+    CleanupItem {
+        id: "shader_cache".to_string(),
+        name: "DirectX 着色器缓存".to_string(),
+        description,
+        cleanup_type: CleanupType::Directories(dirs),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        enabled: true,
+        skip_in_use: true,
+        ..Default::default()
+    }
+}
+
+/// WinINET's folders below `%LOCALAPPDATA%\Microsoft\Windows` and `%APPDATA%\Microsoft\Windows`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InetDirs {
+    /// The cache, per integrity level: `INetCache\IE` and `INetCache\Low`, or on Windows 7
+    /// `Temporary Internet Files\Content.IE5` and `Temporary Internet Files\Low`
+    pub cache: Vec<PathBuf>,
+    /// `INetCookies`, or on Windows 7 the roaming `Cookies`
+    pub cookies: PathBuf,
+    /// Only the Windows 7 locations exist
+    pub legacy: bool,
+}
+
+/// WinINET's cache and cookie folders. The Windows 8 and later layout is used unless only
+/// `Temporary Internet Files` exists.
+pub fn inet_dirs(local_app_data: &Path, roaming: &Path) -> InetDirs {
+    let local = local_app_data.join("Microsoft").join("Windows");
+    let modern = local.join("INetCache");
+    let old = local.join("Temporary Internet Files");
+    if !modern.is_dir() && old.is_dir() {
+        InetDirs {
+            cache: vec![old.join("Content.IE5"), old.join("Low")],
+            cookies: roaming.join("Microsoft").join("Windows").join("Cookies"),
+            legacy: true,
+        }
+    } else {
+        InetDirs {
+            cache: vec![modern.join("IE"), modern.join("Low")],
+            cookies: local.join("INetCookies"),
+            legacy: false,
+        }
+    }
+}
+
+/// Item emptying the WinINET cache still filled by Office, installers and old WebView
+/// components, listed per integrity level.
+///
+/// The folders carry the hidden and system attributes, which do not stop deletion; read-only
+/// files are made deletable as everywhere else. `container.dat` files WinINET has open are
+/// listed as skipped. Enabled by default.
+pub fn inet_cache_item(local_app_data: &Path) -> CleanupItem {
+    let dirs = inet_dirs(local_app_data, local_app_data);
+    let location = if dirs.legacy { "旧版 Temporary Internet Files" } else { "INetCache" };
+    CleanupItem {
+        id: "inet_cache".to_string(),
+        name: "Internet 临时文件".to_string(),
+        description: format!(
+            "WinINET 缓存 ({}): {}",
+            location,
+            dirs.cache.iter().map(|dir| dir.display().to_string()).collect::<Vec<_>>().join(", ")
+        ),
+        cleanup_type: CleanupType::Directories(dirs.cache),
+        category: Category::Browsers,
+        risk: RiskLevel::Safe,
+        enabled: true,
+        skip_in_use: true,
+        ..Default::default()
+    }
+}
+
+/// Item deleting WinINET's cookies, which signs Office and old WebView logins out.
+/// Aggressive and disabled by default.
+pub fn inet_cookies_item(local_app_data: &Path, roaming: &Path) -> CleanupItem {
+    let dirs = inet_dirs(local_app_data, roaming);
+    CleanupItem {
+        id: "inet_cookies".to_string(),
+        name: "Internet Cookie".to_string(),
+        description: format!("WinINET Cookie, 会退出 Office 等应用的登录: {}", dirs.cookies.display()),
+        cleanup_type: CleanupType::Directory(dirs.cookies),
+        category: Category::Browsers,
+        risk: RiskLevel::Aggressive,
+        enabled: false,
+        skip_in_use: true,
+        ..Default::default()
+    }
+}
+
+/// Servicing and setup logs younger than this are kept while an update may still be investigated
+pub const SETUP_LOG_MIN_AGE_DAYS: u32 = 7;
+
+/// Component servicing (`Logs\CBS\*.log`), DISM, Windows Setup (`Panther`) and media
+/// creation (`Logs\MoSetup`) logs below the Windows directory
+pub fn setup_log_files(windows_dir: &Path) -> Vec<PathBuf> {
+    let logs = windows_dir.join("Logs");
+    vec![
+        logs.join("CBS").join("*.log"),
+        logs.join("DISM").join("*"),
+        windows_dir.join("Panther").join("*"),
+        logs.join("MoSetup").join("*"),
+    ]
+}
+
+/// Item deleting servicing and setup logs older than 7 days, sized per location.
+///
+/// Only files directly in those folders are touched; `CBS.log` is skipped while
+/// TrustedInstaller has it open. Needs administrator rights. The age can be changed with
+/// `min_age_days`. Disabled by default.
+pub fn setup_logs_item(windows_dir: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "windows_setup_logs".to_string(),
+        name: "Windows 更新和安装日志".to_string(),
+        description: format!(
+            "组件服务 (CBS)、DISM、Panther 和 MoSetup 日志, 保留 {} 天内的文件 (需要管理员权限): {}",
+            SETUP_LOG_MIN_AGE_DAYS,
+            windows_dir.display()
+        ),
+        cleanup_type: CleanupType::Files(setup_log_files(windows_dir)),
+        category: Category::Logs,
+        risk: RiskLevel::Moderate,
+        min_age_days: SETUP_LOG_MIN_AGE_DAYS,
+        enabled: false,
+        skip_in_use: true,
+        group_files: true,
+        requires_admin: true,
+        ..Default::default()
+    }
+}
+
+/// IIS logs younger than this are kept
+pub const IIS_LOG_MIN_AGE_DAYS: u32 = 30;
+
+/// Where IIS writes its site logs, `%SystemDrive%\inetpub\logs\LogFiles`
+pub fn iis_log_root() -> PathBuf {
+    let drive = env::var("SystemDrive").ok().filter(|drive| !drive.is_empty());
+    PathBuf::from(format!("{}\\", drive.as_deref().unwrap_or("C:")))
+        .join("inetpub")
+        .join("logs")
+        .join("LogFiles")
+}
+
+/// Item deleting IIS `*.log` files older than 30 days from every `W3SVC*` site folder,
+/// sized per site.
+///
+/// Folders and today's logs are never touched. With `compress_until_days` logs younger than
+/// that are NTFS-compressed instead of deleted. Enabled only on machines where the log
+/// folder exists; needs administrator rights.
+pub fn iis_logs_item(root: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "iis_logs".to_string(),
+        name: "IIS 日志".to_string(),
+        description: format!("IIS 网站日志 (W3SVC*), 保留 {} 天内的日志: {}", IIS_LOG_MIN_AGE_DAYS, root.display()),
+        cleanup_type: CleanupType::IisLogs(root.to_path_buf()),
+        category: Category::Logs,
+        risk: RiskLevel::Moderate,
+        min_age_days: IIS_LOG_MIN_AGE_DAYS,
+        enabled: root.is_dir(),
+        requires_admin: true,
+        ..Default::default()
+    }
+}
+
+/// Whether two paths name the same directory, ignoring case and trailing separators as
+/// Windows does
+fn same_dir(a: &Path, b: &Path) -> bool {
+    if let (Ok(a), Ok(b)) = (a.canonicalize(), b.canonicalize()) {
+        return a == b;
+    }
+    let normalize = |path: &Path| path.to_string_lossy().trim_end_matches(['\\', '/']).to_lowercase();
+    normalize(a) == normalize(b)
+}
+
+/// Temporary directories to clean: the user's `%TEMP%`, the system `Windows\Temp` (which
+/// needs administrator rights) and `AppData\LocalLow\Temp` when it exists, each only once
+/// even when `%TEMP%` points at one of the others.
+pub fn temp_dirs(user_temp: &Path, windows_dir: &Path, local_low_temp: Option<&Path>) -> Vec<PathBuf> {
+    let mut dirs = vec![user_temp.to_path_buf()];
+    let candidates = [Some(windows_dir.join("Temp")), local_low_temp.filter(|dir| dir.is_dir()).map(Path::to_path_buf)];
+    for dir in candidates.into_iter().flatten() {
+        if !dirs.iter().any(|known| same_dir(known, &dir)) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+/// Prefetch entries used within this many days are kept so boot and launches stay fast
+pub const PREFETCH_MIN_AGE_DAYS: u32 = 30;
+
+/// Item deleting stale `*.pf` prefetch entries, reported as stale out of all entries.
+///
+/// Recent entries, `Layout.ini` and the `ReadyBoot` folder are kept, since emptying the
+/// whole folder slows the next boot. The age can be changed with `min_age_days`. Needs
+/// administrator rights.
+pub fn prefetch_item(windows_dir: &Path) -> CleanupItem {
+    let prefetch = windows_dir.join("Prefetch");
+    CleanupItem {
+        id: "prefetch".to_string(),
+        name: "Windows Prefetch".to_string(),
+        description: format!(
+            "{} 天内未使用的预读文件 (*.pf), 保留 Layout.ini 和 ReadyBoot (需要管理员权限): {}",
+            PREFETCH_MIN_AGE_DAYS,
+            prefetch.display()
+        ),
+        cleanup_type: CleanupType::Files(vec![prefetch.join("*.pf")]),
+        category: Category::System,
+        risk: RiskLevel::Moderate,
+        min_age_days: PREFETCH_MIN_AGE_DAYS,
+        enabled: true,
+        group_files: true,
+        requires_admin: true,
+        ..Default::default()
+    }
+}
+
+/// Folders below `C:\Users` that are not real user profiles
+pub const NON_USER_PROFILES: &[&str] = &["Default", "Default User", "Public", "All Users"];
+
+/// Whether someone is signed in to a profile: Windows keeps a loaded `NTUSER.DAT` open
+/// exclusively
+fn has_active_session(profile: &Path) -> bool {
+    match fs::OpenOptions::new().read(true).write(true).open(profile.join("NTUSER.DAT")) {
+        Ok(_) => false,
+        Err(e) => platform::is_in_use(&e),
+    }
+}
+
+/// User profiles below `users_dir` other than `own_profile`, as `(user name, profile)`,
+/// skipping built-in folders and profiles with an active session
+pub fn other_user_profiles(users_dir: &Path, own_profile: Option<&Path>) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(users_dir) else {
+        return Vec::new();
+    };
+    let mut profiles: Vec<(String, PathBuf)> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .map(|entry| (entry.file_name().to_string_lossy().into_owned(), entry.path()))
+        .filter(|(name, _)| !NON_USER_PROFILES.iter().any(|skip| skip.eq_ignore_ascii_case(name)))
+        .filter(|(_, profile)| !own_profile.is_some_and(|own| same_dir(own, profile)))
+        .filter(|(_, profile)| !has_active_session(profile))
+        .collect();
+    profiles.sort();
+    profiles
+}
+
+/// Temp and thumbnail cache items for another user's profile, named after the user so
+/// results show who the space belonged to
+pub fn user_profile_items(user: &str, profile: &Path) -> Vec<CleanupItem> {
+    let local = profile.join("AppData").join("Local");
+    let mut temp = temp_files_item(vec![local.join("Temp")]);
+    let mut thumbnails = thumbnail_cache_item(&local);
+    for item in [&mut temp, &mut thumbnails] {
+        item.id = format!("{}@{}", item.id, user.to_lowercase());
+        item.name = format!("{} ({})", item.name, user);
+    }
+    vec![temp, thumbnails]
+}
+
+/// `Microsoft\Windows\Recent` below `%APPDATA%` (Roaming)
+pub fn recent_dir(roaming: &Path) -> PathBuf {
+    roaming.join("Microsoft").join("Windows").join("Recent")
+}
+
+/// Item deleting the recent documents shortcuts and the jump lists in
+/// `AutomaticDestinations` and `CustomDestinations`, sized per location.
+///
+/// Without the jump lists Explorer would keep showing the same recent files. Disabled by
+/// default; the folders themselves are kept.
+pub fn recent_docs_item(roaming: &Path) -> CleanupItem {
+    let recent = recent_dir(roaming);
+    let files = vec![
+        recent.join("*"),
+        recent.join("AutomaticDestinations").join("*"),
+        recent.join("CustomDestinations").join("*"),
+    ];
+    CleanupItem {
+        id: "recent_docs".to_string(),
+        name: "最近文档".to_string(),
+        description: format!("Windows 最近访问的文档列表和跳转列表: {}", recent.display()),
+        cleanup_type: CleanupType::Files(files),
+        category: Category::System,
+        risk: RiskLevel::Moderate,
+        enabled: false,
+        group_files: true,
+        ..Default::default()
+    }
+}
+
+/// File name of the GTK recent files list
+pub const RECENTLY_USED_FILE: &str = "recently-used.xbel";
+
+/// What GLib writes for a recent files list without any bookmark
+pub const EMPTY_XBEL: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<xbel version=\"1.0\"
+      xmlns:bookmark=\"http://www.freedesktop.org/standards/desktop-bookmarks\"
+      xmlns:mime=\"http://www.freedesktop.org/standards/shared-mime-info\"
+>
+</xbel>
+";
+
+/// `recently-used.xbel` in `$XDG_DATA_HOME` (`~/.local/share`), and the copy older GTK
+/// versions kept in `$XDG_CACHE_HOME`
+pub fn recently_used_files(data: &Path, cache: &Path) -> Vec<PathBuf> {
+    vec![data.join(RECENTLY_USED_FILE), cache.join(RECENTLY_USED_FILE)]
+}
+
+/// Number of `<bookmark>` elements in an xbel document, or `None` if it is not one.
+///
+/// Only opening tags are counted; `<bookmark:applications>` and the other namespaced
+/// children of a bookmark are not bookmarks themselves.
+pub fn count_xbel_bookmarks(xml: &str) -> Option<u64> {
+    if !xml.contains("<xbel") {
+        return None;
+    }
+    let count = xml
+        .match_indices("<bookmark")
+        .filter(|(start, tag)| {
+            xml[start + tag.len()..]
+                .chars()
+                .next()
+                .is_some_and(|next| next.is_whitespace() || next == '>' || next == '/')
+        })
+        .count();
+    Some(count as u64)
+}
+
+/// GTK's lists of recently opened files. Deleting a list makes some applications recreate
+/// it badly, so each one is rewritten as an empty bookmark list instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentlyUsed {
+    pub files: Vec<PathBuf>,
+}
+
+impl RecentlyUsed {
+    pub fn new(files: Vec<PathBuf>) -> Self {
+        Self { files }
+    }
+
+    /// Each existing list with its size and number of bookmarks. Files that cannot be read
+    /// or are not xbel documents are left out.
+    fn lists(&self) -> Vec<(&Path, u64, u64)> {
+        self.files
+            .iter()
+            .filter_map(|path| {
+                let xml = fs::read_to_string(path).ok()?;
+                let bookmarks = count_xbel_bookmarks(&xml)?;
+                Some((path.as_path(), xml.len() as u64, bookmarks))
+            })
+            .collect()
+    }
+
+    /// Moderate item: the recent files shown by file choosers and the GNOME shell are lost.
+    /// Disabled by default, like the Windows recent documents.
+    pub fn item(self) -> CleanupItem {
+        CleanupItem {
+            id: "recently_used".to_string(),
+            name: "最近使用的文件".to_string(),
+            description: format!(
+                "GTK/GNOME 应用记录的最近打开文件列表, 清空为空列表: {}",
+                self.files.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join(", ")
+            ),
+            cleanup_type: CleanupType::Custom(Arc::new(self)),
+            category: Category::System,
+            risk: RiskLevel::Moderate,
+            enabled: false,
+            ..Default::default()
+        }
+    }
+}
+
+impl CleanTarget for RecentlyUsed {
+    /// One breakdown entry per list, with the number of recorded files
+    fn scan(&self) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for (path, size, bookmarks) in self.lists() {
+            if bookmarks == 0 {
+                continue;
+            }
+            result.entries += bookmarks;
+            result.size_bytes += size.saturating_sub(EMPTY_XBEL.len() as u64);
+            result.breakdown.push(BreakdownEntry {
+                label: path.display().to_string(),
+                size_bytes: size,
+                note: format!("{} 条记录", bookmarks),
+            });
+        }
+        result.has_data = result.entries > 0;
+        result
+    }
+
+    /// Overwrite each list that has bookmarks with an empty one. The file is written in
+    /// place, so it keeps its private permissions.
+    fn clean(&self) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for (path, size, bookmarks) in self.lists() {
+            if bookmarks == 0 {
+                continue;
+            }
+            match fs::write(path, EMPTY_XBEL) {
+                Ok(()) => {
+                    result.entries += bookmarks;
+                    result.size_bytes += size.saturating_sub(EMPTY_XBEL.len() as u64);
+                }
+                Err(e) => result.errors.push(CleanErrorEntry::new(path, &e)),
+            }
+        }
+        result.has_data = result.entries > 0;
+        result
+    }
+
+    fn validate(&self) -> ItemStatus {
+        if self.files.iter().any(|path| path.is_file()) {
+            ItemStatus::Ready
+        } else {
+            ItemStatus::PathMissing
+        }
+    }
+}
+
+/// The Recycle Bin of every drive on Windows, the Trash on macOS and the FreeDesktop trash
+/// elsewhere, sized with `platform::query_recycle_bin_at` and emptied with
+/// `platform::clean_recycle_bin_at`.
+///
+/// Each bin, a drive on Windows or a trash directory elsewhere, is listed in the breakdown
+/// and can be excluded on its own.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecycleBin {
+    /// Bins to list; those of `platform::recycle_bin_roots` when unset
+    roots: Option<Vec<PathBuf>>,
+}
+
+impl RecycleBin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only the bins at `roots`, such as `D:\` or a trash directory
+    pub fn with_roots(roots: Vec<PathBuf>) -> Self {
+        Self { roots: Some(roots) }
+    }
+
+    fn roots(&self) -> Vec<PathBuf> {
+        self.roots.clone().unwrap_or_else(platform::recycle_bin_roots)
+    }
+
+    /// Aggressive item: whatever was deleted can no longer be restored. Disabled by default.
+    pub fn item(self) -> CleanupItem {
+        let (name, description) = if cfg!(windows) {
+            ("回收站", "清空所有驱动器的回收站, 可在明细中按驱动器排除")
+        } else if cfg!(target_os = "macos") {
+            ("废纸篓", "清倒 ~/.Trash 及各宗卷上的 .Trashes, 需要完全磁盘访问权限")
+        } else {
+            ("回收站", "清空 ~/.local/share/Trash 及可写卷上的 .Trash-<uid>")
+        };
+        CleanupItem {
+            id: "recycle_bin".to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            cleanup_type: CleanupType::Custom(Arc::new(self)),
+            category: Category::System,
+            risk: RiskLevel::Aggressive,
+            priority: 90,
+            enabled: false,
+            ..Default::default()
+        }
+    }
+
+    /// The bin at `root` failing, as a result: macOS privacy protection is a skip, anything
+    /// else an error
+    fn failed(root: Option<&Path>, error: CleanError) -> CleanupResult {
+        if let CleanError::NotSupported(_) = error {
+            return CleanupResult::skipped(FULL_DISK_ACCESS_HINT);
+        }
+        let mut result = CleanupResult::new();
+        result.errors.push(CleanErrorEntry::from_error(root.unwrap_or(Path::new("Recycle Bin")), &error));
+        result
+    }
+
+    /// Empty one bin, or all of them with `None`. Items are counted by querying the bin again
+    /// afterwards.
+    fn clean_bin(root: Option<&Path>) -> CleanupResult {
+        let before = match platform::query_recycle_bin_at(root) {
+            Ok(usage) => usage,
+            Err(e) => return Self::failed(root, e),
+        };
+        let freed = match platform::clean_recycle_bin_at(root, false) {
+            Ok(freed) => freed,
+            Err(e) => return Self::failed(root, e),
+        };
+        let after = platform::query_recycle_bin_at(root).unwrap_or_default();
+        let entries = before.items.saturating_sub(after.items);
+        CleanupResult {
+            size_bytes: freed,
+            entries,
+            has_data: entries > 0,
+            ..CleanupResult::new()
+        }
+    }
+}
+
+impl CleanTarget for RecycleBin {
+    fn scan(&self) -> CleanupResult {
+        self.scan_excluding(&[])
+    }
+
+    fn clean(&self) -> CleanupResult {
+        self.clean_excluding(&[])
+    }
+
+    fn has_parts(&self) -> bool {
+        true
+    }
+
+    fn is_part(&self, label: &Path) -> bool {
+        self.roots().iter().any(|root| root == label)
+    }
+
+    /// One breakdown entry per bin, followed by the items in it where the platform lists them.
+    /// Excluded bins are listed but not counted.
+    fn scan_excluding(&self, excluded: &[PathBuf]) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for root in self.roots() {
+            let usage = match platform::query_recycle_bin_at(Some(&root)) {
+                Ok(usage) => usage,
+                Err(e) => {
+                    result.merge(Self::failed(Some(&root), e));
+                    continue;
+                }
+            };
+            let mut note = format!("{} 个项目", usage.items);
+            if excluded.contains(&root) {
+                note.push_str(&format!(", {}", EXCLUDED_NOTE));
+            } else {
+                result.entries += usage.items;
+                result.size_bytes += usage.bytes;
+            }
+            result.has_data = result.has_data || usage.items > 0;
+            result.breakdown.push(BreakdownEntry {
+                label: root.display().to_string(),
+                size_bytes: usage.bytes,
+                note,
+            });
+            result.breakdown.extend(platform::list_recycle_bin(Some(&root)).into_iter().map(|entry| BreakdownEntry {
+                label: entry.original_path.display().to_string(),
+                size_bytes: entry.size,
+                note: match entry.deleted_days_ago() {
+                    Some(days) => format!("{} 天前删除", days),
+                    None => "删除时间未知".to_string(),
+                },
+            }));
+        }
+        result
+    }
+
+    /// Without exclusions every bin is emptied at once, as `platform::clean_recycle_bin` does
+    fn clean_excluding(&self, excluded: &[PathBuf]) -> CleanupResult {
+        if excluded.is_empty() && self.roots.is_none() {
+            return Self::clean_bin(None);
+        }
+        let mut result = CleanupResult::new();
+        for root in self.roots().iter().filter(|root| !excluded.contains(root)) {
+            result.merge(Self::clean_bin(Some(root)));
+        }
+        result
+    }
+
+    fn validate(&self) -> ItemStatus {
+        let queried = match &self.roots {
+            Some(roots) => roots.iter().try_for_each(|root| platform::query_recycle_bin_at(Some(root)).map(drop)),
+            None => platform::query_recycle_bin().map(drop),
+        };
+        match queried {
+            Ok(()) => ItemStatus::Ready,
+            Err(CleanError::NotSupported(_)) => ItemStatus::NeedsFullDiskAccess,
+            Err(_) => ItemStatus::ServiceUnavailable,
+        }
+    }
+}
+
+/// The DNS resolver cache, flushed with `platform::flush_dns_cache`. Nothing is deleted from
+/// disk, so results count the caches flushed in `entries` and free no bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DnsCache;
+
+impl DnsCache {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Safe item: names are looked up again on next use. Disabled by default, as it frees no
+    /// space. Flushing needs root outside Windows.
+    pub fn item(self) -> CleanupItem {
+        CleanupItem {
+            id: "dns_cache".to_string(),
+            name: "DNS 缓存".to_string(),
+            description: "刷新 DNS 解析缓存, 不占用磁盘空间".to_string(),
+            cleanup_type: CleanupType::Custom(Arc::new(self)),
+            category: Category::System,
+            risk: RiskLevel::Safe,
+            requires_admin: !cfg!(windows),
+            enabled: false,
+            ..Default::default()
+        }
+    }
+}
+
+impl CleanTarget for DnsCache {
+    /// One breakdown entry per command that would run
+    fn scan(&self) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for command in platform::dns_flush_commands() {
+            result.entries += 1;
+            result.breakdown.push(BreakdownEntry {
+                label: command,
+                size_bytes: 0,
+                note: "将执行".to_string(),
+            });
+        }
+        result.has_data = result.entries > 0;
+        result
+    }
+
+    fn clean(&self) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        match platform::flush_dns_cache(false) {
+            Ok(commands) => result.entries = commands.len() as u64,
+            Err(e) => result.errors.push(CleanErrorEntry::from_error(Path::new("DNS"), &e)),
+        }
+        result.has_data = result.entries > 0;
+        result
+    }
+
+    /// Unavailable when no caching resolver runs, as on Linux with plain glibc lookups
+    fn validate(&self) -> ItemStatus {
+        if platform::dns_flush_commands().is_empty() {
+            ItemStatus::ServiceUnavailable
+        } else {
+            ItemStatus::Ready
+        }
+    }
+}
+
+/// Sizes of freedesktop thumbnails, plus `fail/` for files that could not be thumbnailed
+pub const THUMBNAIL_SIZE_DIRS: &[&str] = &["normal", "large", "x-large", "fail"];
+
+/// Thumbnails not viewed for this many days are deleted, so browsing recent folders stays fast
+pub const THUMBNAIL_MIN_AGE_DAYS: u32 = 30;
+
+/// Freedesktop thumbnail directories below `$XDG_CACHE_HOME/thumbnails` and the legacy
+/// `~/.thumbnails`
+pub fn thumbnail_dirs(cache: &Path, home: &Path) -> Vec<PathBuf> {
+    [cache.join("thumbnails"), home.join(".thumbnails")]
+        .iter()
+        .flat_map(|root| THUMBNAIL_SIZE_DIRS.iter().map(move |size| root.join(size)))
+        .collect()
+}
+
+/// Item deleting freedesktop thumbnails not viewed for 30 days, sized per directory.
+/// Enabled by default.
+pub fn thumbnails_item(cache: &Path, home: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "thumbnails".to_string(),
+        name: "缩略图缓存".to_string(),
+        description: format!(
+            "文件管理器缩略图, 保留 {} 天内查看过的: {}",
+            THUMBNAIL_MIN_AGE_DAYS,
+            cache.join("thumbnails").display()
+        ),
+        cleanup_type: CleanupType::Directories(thumbnail_dirs(cache, home)),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        min_age_days: THUMBNAIL_MIN_AGE_DAYS,
+        age_by_access: true,
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+/// Folder metadata left behind by Finder (`.DS_Store`) and Explorer (`Thumbs.db`, `desktop.ini`)
+pub const DESKTOP_METADATA_FILES: &[&str] = &[".DS_Store", "Thumbs.db", "desktop.ini"];
+
+/// Folders below the home directory holding application data rather than the user's files
+pub const DESKTOP_METADATA_EXCLUDED: &[&str] = &["Library", "AppData"];
+
+/// Item deleting `.DS_Store`, `Thumbs.db` and `desktop.ini` files anywhere in the home
+/// directory except `Library` and `AppData`, staying off mounted network shares. Finder's view
+/// settings and custom folder icons are lost with them.
+///
+/// Roots can be changed with the `project_roots` option. Disabled by default, since searching
+/// the whole home directory takes a while.
+pub fn desktop_metadata_item(home: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "desktop_metadata".to_string(),
+        name: "文件夹元数据".to_string(),
+        description: format!(
+            "Finder 和资源管理器在文件夹中留下的 {} (在 {} 中查找), 删除 desktop.ini 会还原文件夹的自定义图标和名称",
+            DESKTOP_METADATA_FILES.join(" / "),
+            home.display()
+        ),
+        cleanup_type: CleanupType::FilePattern(FilePattern {
+            roots: vec![home.to_path_buf()],
+            names: DESKTOP_METADATA_FILES.iter().map(|name| name.to_string()).collect(),
+            excluded: DESKTOP_METADATA_EXCLUDED.iter().map(|dir| home.join(dir)).collect(),
+            same_filesystem: true,
+        }),
+        category: Category::System,
+        risk: RiskLevel::Moderate,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// Files in the Downloads folder not modified for this many days are offered for cleaning
+pub const DOWNLOADS_MIN_AGE_DAYS: u32 = 90;
+
+/// Item moving files in the Downloads folder not modified for 90 days to the trash, listing
+/// each file so the user can check them before cleaning.
+///
+/// Only files directly in the folder are considered. The age can be changed with
+/// `min_age_days`, `installers_only` leaves everything but installers and archives alone, and
+/// `use_trash: false` deletes the files outright. Aggressive and disabled by default.
+pub fn downloads_item(downloads: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "old_downloads".to_string(),
+        name: "旧的下载文件".to_string(),
+        description: format!(
+            "下载文件夹中 {} 天未修改的文件, 默认移到回收站 ({})",
+            DOWNLOADS_MIN_AGE_DAYS,
+            downloads.display()
+        ),
+        cleanup_type: CleanupType::Files(vec![downloads.join("*")]),
+        category: Category::System,
+        risk: RiskLevel::Aggressive,
+        min_age_days: DOWNLOADS_MIN_AGE_DAYS,
+        use_trash: true,
+        enabled: false,
+        ..Default::default()
+    }
+}
+
+/// Empty directory trees below `roots`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmptyDirs {
+    pub roots: Vec<PathBuf>,
+}
+
+impl EmptyDirs {
+    /// Empty directories below each root, deepest first. Directories younger than the item's
+    /// minimum age count as content, since a program may be about to fill them.
+    fn find<'a>(&'a self, cx: &ItemContext<'a>) -> Vec<(&'a Path, Vec<PathBuf>)> {
+        cx.roots(&self.roots)
+            .iter()
+            .map(|root| {
+                let mut found = Vec::new();
+                collect_empty_dirs(cx, root, &mut found);
+                (root.as_path(), found)
+            })
+            .collect()
+    }
+}
+
+/// Add the empty directories below `dir` to `found`, children before their parents, and
+/// return whether `dir` holds nothing else. Symlinks and junctions count as content and are
+/// never followed.
+fn collect_empty_dirs(cx: &ItemContext<'_>, dir: &Path, found: &mut Vec<PathBuf>) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    let mut empty = true;
+    for entry in entries {
+        let Ok(entry) = entry else {
+            empty = false;
+            continue;
+        };
+        let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+        let path = entry.path();
+        if is_dir
+            && collect_empty_dirs(cx, &path, found)
+            && entry.metadata().is_ok_and(|metadata| !cx.is_too_recent(&metadata))
+        {
+            found.push(path);
+        } else {
+            empty = false;
+        }
+    }
+    empty
+}
+
+impl CleanTarget for EmptyDirs {
+    /// Count of the empty directories below each root, one breakdown entry per root
+    fn scan_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for (root, found) in self.find(cx) {
+            if found.is_empty() {
+                continue;
+            }
+            result.directories += found.len() as u64;
+            result.has_data = true;
+            result.breakdown.push(BreakdownEntry {
+                label: root.display().to_string(),
+                size_bytes: 0,
+                note: format!("{} 个空目录", found.len()),
+            });
+        }
+        result
+    }
+
+    /// Remove the empty directories, deepest first. One that gained content since it was
+    /// found is left alone without an error, and so are its parents.
+    fn clean_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        let mut result = CleanupResult::new();
+        for (_, found) in self.find(cx) {
+            for dir in found {
+                match fs::remove_dir(&dir) {
+                    Ok(()) => {
+                        result.directories += 1;
+                        result.has_data = true;
+                    }
+                    Err(e) if matches!(e.kind(), io::ErrorKind::DirectoryNotEmpty | io::ErrorKind::NotFound) => {
+                        debug!("Skipping {}: {}", dir.display(), e);
+                    }
+                    Err(e) => result.errors.push(CleanErrorEntry::new(&dir, &e)),
+                }
+            }
+        }
+        result
+    }
+
+    fn estimate_in(&self, cx: &ItemContext<'_>) -> CleanupResult {
+        self.scan_in(cx)
+    }
+
+    fn validate_in(&self, cx: &ItemContext<'_>) -> ItemStatus {
+        cleanup_items::validate_roots(cx.fs(), cx.roots(&self.roots))
+    }
+}
+
+/// Item removing empty directory trees left in `roots` by earlier cleanups, such as the temp
+/// and cache directories. Directories created within the last day are kept, since a program
+/// may be about to fill them.
+///
+/// Roots can be changed with the `project_roots` option. Disabled by default.
+pub fn empty_dirs_item(roots: Vec<PathBuf>) -> CleanupItem {
+    CleanupItem {
+        id: "empty_dirs".to_string(),
+        name: "空目录".to_string(),
+        description: format!(
+            "不含任何文件的空目录 (在 {} 中查找), 目录本身保留",
+            roots.iter().map(|root| root.display().to_string()).collect::<Vec<_>>().join(", ")
+        ),
+        cleanup_type: CleanupType::Custom(Arc::new(EmptyDirs { roots })),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        min_age_days: TEMP_MIN_AGE_DAYS,
+        enabled: false,
+        ..Default::default()
+    }
+}
@@ -1,4 +1,14 @@
-use crate::cleanup_items::{CleanupItem, CleanupResult, get_all_cleanup_items};
+use clean_rs::cleaner::Cleaner;
+use clean_rs::cleanup_items::{
+    estimate_ready_parallel, needs_restore_point, toggle_category, CleanupItem, CleanupResult, ItemStatus, Report,
+    RiskLevel, DEFAULT_SCAN_PARALLELISM, EXCLUDED_NOTE, NOT_SELECTED_NOTE,
+};
+use clean_rs::events::{ChannelObserver, CleanEvent};
+use clean_rs::free_space::{clean_until_freed, parse_size};
+use clean_rs::options::CleanOptions;
+use clean_rs::platform::{self, DiskUsage};
+use clean_rs::state::{apply_item_state, default_state_path, load_item_state, save_item_state, ItemState};
+use clean_rs::util::format_size;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
     execute,
@@ -12,8 +22,12 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph, Wrap},
     Frame, Terminal,
 };
-use std::{io, time::{Duration, Instant}};
-use tracing::{debug, info};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::{io, thread, time::{Duration, Instant}};
+use tracing::{debug, info, warn};
 
 /// Application state for the TUI
 pub struct App {
@@ -27,6 +41,43 @@ pub struct App {
     pub is_cleaning: bool,
     /// Track the last key event time to prevent auto-repeat issues
     pub last_key_event_time: Option<Instant>,
+    /// Maximum number of items scanned concurrently
+    pub scan_parallelism: usize,
+    /// Options applied when cleaning
+    pub options: CleanOptions,
+    /// Text typed into the free-space target prompt
+    pub target_input: String,
+    /// State to return to when the target prompt is cancelled
+    pub prompt_return: AppState,
+    /// Target confirmed in the prompt, waiting for the clean to start
+    pub pending_target: Option<u64>,
+    /// Target of the last clean-until-target run, if that is what ran
+    pub free_target: Option<u64>,
+    /// Events from the scan or clean running in the background
+    pub events: Option<Receiver<CleanEvent>>,
+    /// Items in the running job, and how many of them have finished
+    pub job_total: usize,
+    pub job_finished: usize,
+    /// Files and bytes processed so far for items that are in progress
+    pub item_progress: Vec<Option<(u64, u64)>>,
+    /// Breakdown entry under the cursor in the details popup
+    pub detail_index: usize,
+    /// Volume holding the temp directory and its space, measured at start and after cleaning
+    pub temp_volume: Option<(PathBuf, DiskUsage)>,
+}
+
+/// Volume holding the temp directory, where most of what is cleaned lives, and its space
+fn temp_volume() -> Option<(PathBuf, DiskUsage)> {
+    let root = platform::volume_root(&env::temp_dir())?;
+    let usage = platform::disk_usage(&root).ok()?;
+    Some((root, usage))
+}
+
+/// Group items by category (one header each), then order them as they will be processed
+fn sort_for_display(items: &mut [CleanupItem]) {
+    items.sort_by(|a, b| {
+        (a.category, a.effective_priority(), &a.name, &a.id).cmp(&(b.category, b.effective_priority(), &b.name, &b.id))
+    });
 }
 
 /// Cooldown duration between key events (150ms) to prevent auto-repeat
@@ -37,15 +88,27 @@ pub enum AppState {
     Initial,
     Scanning,
     ScanningDone,
+    /// Typing how much space to free before cleaning stops
+    EnteringTarget,
+    /// Waiting for the user to confirm cleaning aggressive items
+    ConfirmingClean,
+    /// Creating the System Restore point the selected items want before cleaning
+    CreatingRestorePoint,
+    /// Waiting for the user to acknowledge that the restore point could not be created
+    ConfirmingRestorePoint,
+    /// Showing the per-part breakdown of the selected item's result
+    ViewingDetails,
     Cleaning,
     CleaningDone,
 }
 
 impl App {
     pub fn new() -> Self {
-        let cleanup_items = get_all_cleanup_items();
+        let mut cleanup_items = Cleaner::with_default_items().into_items();
+        sort_for_display(&mut cleanup_items);
         let scan_results = vec![None; cleanup_items.len()];
         let clean_results = vec![None; cleanup_items.len()];
+        let item_count = cleanup_items.len();
         
         Self {
             cleanup_items,
@@ -53,13 +116,48 @@ impl App {
             clean_results,
             selected_index: 0,
             state: AppState::Initial,
-            status_message: "SPACE 选择 | A 全选 | D 取消 | I 反选 | ENTER 扫描 | C 清理 | Q 退出".to_string(),
+            status_message: "SPACE 选择 | G 分类 | A 全选 | D 取消 | I 反选 | ENTER 扫描 | C 清理 | F 按目标清理 | B 后台模式 | Q 退出"
+                .to_string(),
             is_scanning: false,
             is_cleaning: false,
             last_key_event_time: None,
+            scan_parallelism: DEFAULT_SCAN_PARALLELISM,
+            options: CleanOptions::default(),
+            target_input: String::new(),
+            prompt_return: AppState::Initial,
+            pending_target: None,
+            free_target: None,
+            events: None,
+            job_total: 0,
+            job_finished: 0,
+            item_progress: vec![None; item_count],
+            detail_index: 0,
+            temp_volume: temp_volume(),
         }
     }
 
+    /// Return to the initial state, keeping the selection and user-configured settings
+    pub fn reset(&mut self) {
+        let states: Vec<ItemState> = self.cleanup_items.iter().map(ItemState::from).collect();
+        let scan_parallelism = self.scan_parallelism;
+        let options = std::mem::take(&mut self.options);
+        *self = App::new();
+        apply_item_state(&mut self.cleanup_items, &states);
+        sort_for_display(&mut self.cleanup_items);
+        self.scan_parallelism = scan_parallelism;
+        self.options = options;
+        self.estimate_all();
+    }
+
+    /// Fill the results with quick estimates so empty items are visible before a full scan
+    pub fn estimate_all(&mut self) {
+        let scan_results = &mut self.scan_results;
+        estimate_ready_parallel(&self.cleanup_items, self.scan_parallelism, |i, result| {
+            scan_results[i] = Some(result);
+        });
+        debug!("Estimates ready");
+    }
+
     /// Check if a key event should be processed based on cooldown
     /// Returns true if the key event should be processed
     pub fn should_process_key(&mut self) -> bool {
@@ -82,14 +180,43 @@ impl App {
 
     pub fn toggle_selection(&mut self) {
         if let Some(item) = self.cleanup_items.get_mut(self.selected_index) {
+            if !item.status.is_ready() {
+                self.status_message = format!("{} 不可用: {}", item.name, item.status.reason());
+                return;
+            }
             item.enabled = !item.enabled;
             debug!("Toggled selection for item {}: {}", self.selected_index, item.name);
         }
     }
 
+    /// Toggle every item in the selected item's category.
+    /// Enables the whole category unless it is already fully enabled.
+    pub fn toggle_current_category(&mut self) {
+        let Some(category) = self.cleanup_items.get(self.selected_index).map(|item| item.category) else {
+            return;
+        };
+        let all_enabled = self.cleanup_items
+            .iter()
+            .filter(|item| item.category == category && item.status.is_ready())
+            .all(|item| item.enabled);
+        toggle_category(&mut self.cleanup_items, category, !all_enabled);
+        info!("Toggled category {}", category.as_str());
+    }
+
+    /// Row of the selected item in the rendered list, accounting for category headers
+    pub fn list_row(&self) -> usize {
+        let headers = self.cleanup_items
+            .iter()
+            .take(self.selected_index + 1)
+            .enumerate()
+            .filter(|(i, item)| *i == 0 || self.cleanup_items[i - 1].category != item.category)
+            .count();
+        self.selected_index + headers
+    }
+
     pub fn select_all(&mut self) {
         for item in &mut self.cleanup_items {
-            item.enabled = true;
+            item.enabled = item.status.is_ready();
         }
         info!("Selected all items");
     }
@@ -103,11 +230,38 @@ impl App {
 
     pub fn invert_selection(&mut self) {
         for item in &mut self.cleanup_items {
-            item.enabled = !item.enabled;
+            item.enabled = !item.enabled && item.status.is_ready();
         }
         info!("Inverted selection");
     }
 
+    /// Switch background mode, which scans and cleans at low CPU and I/O priority
+    pub fn toggle_background(&mut self) {
+        self.options.background = !self.options.background;
+        self.status_message = if self.options.background {
+            "后台模式: 开启, 以低 CPU 和 I/O 优先级扫描和清理, 耗时会更长".to_string()
+        } else {
+            "后台模式: 关闭".to_string()
+        };
+        info!("Background mode: {}", self.options.background);
+    }
+
+    /// Prefix for status messages after a job, explaining why it took longer
+    fn background_note(&self) -> &'static str {
+        if self.options.background {
+            "[后台模式] "
+        } else {
+            ""
+        }
+    }
+
+    /// Whether any aggressive item is currently selected for cleaning
+    pub fn has_aggressive_selected(&self) -> bool {
+        self.cleanup_items
+            .iter()
+            .any(|item| item.enabled && item.risk == RiskLevel::Aggressive)
+    }
+
     pub fn next(&mut self) {
         if self.cleanup_items.is_empty() {
             return;
@@ -126,59 +280,352 @@ impl App {
         };
     }
 
-    pub async fn scan_all(&mut self) {
+    pub fn scan_all(&mut self) {
         self.state = AppState::Scanning;
+        self.scan_results = vec![None; self.cleanup_items.len()];
+        self.clean_results = vec![None; self.cleanup_items.len()];
         self.is_scanning = true;
         self.status_message = "正在扫描...".to_string();
         
-        for (i, item) in self.cleanup_items.iter().enumerate() {
-            if item.enabled {
-                let result = item.scan();
-                self.scan_results[i] = Some(result);
-                debug!("Scanned item {}: {:?}", i, self.scan_results[i]);
+        // Items target independent locations, so a slow one must not block the rest
+        self.start_job(|cleaner| {
+            cleaner.scan_all();
+        });
+    }
+
+    /// Run `job` on a worker thread with a `Cleaner` for the current selection and options;
+    /// its events are picked up by `drain_events`
+    fn start_job<F>(&mut self, job: F)
+    where
+        F: FnOnce(Cleaner) + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        let mut cleaner = Cleaner::new(self.cleanup_items.clone());
+        cleaner.set_options(self.options.clone());
+        cleaner.add_observer(Arc::new(ChannelObserver::new(tx)));
+        cleaner.set_parallelism(self.scan_parallelism);
+        thread::spawn(move || job(cleaner));
+
+        self.events = Some(rx);
+        self.job_total = self.cleanup_items.iter().filter(|item| item.enabled).count();
+        self.job_finished = 0;
+        self.item_progress = vec![None; self.cleanup_items.len()];
+    }
+
+    /// Apply all events the running job has sent since the last call
+    pub fn drain_events(&mut self) {
+        let Some(events) = self.events.take() else {
+            return;
+        };
+        loop {
+            match events.try_recv() {
+                Ok(CleanEvent::AllDone) | Err(TryRecvError::Disconnected) => {
+                    self.finish_job();
+                    return;
+                }
+                Ok(event) => self.apply_event(event),
+                Err(TryRecvError::Empty) => break,
             }
         }
-        
-        self.state = AppState::ScanningDone;
-        self.is_scanning = false;
-        self.status_message = "扫描完成! 按 C 执行清理, 或按 Q 退出".to_string();
-        info!("Scanning complete");
+        self.events = Some(events);
+    }
+
+    fn apply_event(&mut self, event: CleanEvent) {
+        let index_of = |id: &str| self.cleanup_items.iter().position(|item| item.id == id);
+        match event {
+            CleanEvent::ItemStarted { id } => {
+                if let Some(i) = index_of(&id) {
+                    self.item_progress[i] = Some((0, 0));
+                }
+            }
+            CleanEvent::Progress { id, files_done, bytes_done } => {
+                if let Some(i) = index_of(&id) {
+                    self.item_progress[i] = Some((files_done, bytes_done));
+                }
+            }
+            CleanEvent::ItemFinished { id, result } => {
+                if let Some(i) = index_of(&id) {
+                    debug!("Finished item {}: {:?}", i, result);
+                    self.item_progress[i] = None;
+                    self.job_finished += 1;
+                    if self.is_scanning {
+                        self.scan_results[i] = Some(result);
+                    } else {
+                        self.clean_results[i] = Some(result);
+                    }
+                }
+            }
+            CleanEvent::AllDone => self.finish_job(),
+        }
     }
 
-    pub async fn clean_selected(&mut self) {
+    /// Share of the running job's items that have finished
+    pub fn job_ratio(&self) -> f64 {
+        if self.job_total == 0 {
+            return 1.0;
+        }
+        (self.job_finished as f64 / self.job_total as f64).min(1.0)
+    }
+
+    fn finish_job(&mut self) {
+        self.events = None;
+        self.item_progress = vec![None; self.cleanup_items.len()];
+        if self.is_scanning {
+            self.state = AppState::ScanningDone;
+            self.is_scanning = false;
+            self.status_message = format!("{}扫描完成! 按 C 执行清理, 或按 Q 退出", self.background_note());
+            info!("Scanning complete");
+        } else if self.is_cleaning {
+            self.state = AppState::CleaningDone;
+            self.is_cleaning = false;
+            self.temp_volume = temp_volume();
+            self.finish_clean();
+        }
+    }
+
+    /// Whether an item was left alone because the free-space target was already reached
+    pub fn is_untouched(&self, index: usize) -> bool {
+        self.free_target.is_some()
+            && *self.shown_state() == AppState::CleaningDone
+            && self.cleanup_items[index].enabled
+            && self.clean_results[index].is_none()
+    }
+
+    /// State whose results are on screen; the details popup keeps showing what is under it
+    pub fn shown_state(&self) -> &AppState {
+        if self.state == AppState::ViewingDetails {
+            &self.prompt_return
+        } else {
+            &self.state
+        }
+    }
+
+    /// Latest result of the selected item: the clean result once cleaning ran, else the scan
+    pub fn selected_result(&self) -> Option<&CleanupResult> {
+        let results = match self.shown_state() {
+            AppState::Cleaning | AppState::CleaningDone => &self.clean_results,
+            _ => &self.scan_results,
+        };
+        results.get(self.selected_index)?.as_ref()
+    }
+
+    /// Expand the selected item into its parts, if its result has any
+    pub fn open_details(&mut self) {
+        if self.selected_result().is_some_and(|result| !result.breakdown.is_empty()) {
+            self.prompt_return = self.state.clone();
+            self.state = AppState::ViewingDetails;
+            self.detail_index = 0;
+            self.status_message = if self.can_exclude_details() {
+                if self.cleanup_items[self.selected_index].has_duplicate_groups() {
+                    "↑/↓ 选择 | SPACE 选择/取消清理该组 | ESC 或 V 返回".to_string()
+                } else {
+                    "↑/↓ 选择 | SPACE 排除/恢复项目 | ESC 或 V 返回".to_string()
+                }
+            } else {
+                "ESC 或 V 返回".to_string()
+            };
+        } else {
+            self.status_message = "该项目没有明细, 请先按 ENTER 扫描".to_string();
+        }
+    }
+
+    /// Whether the open details list projects or duplicate groups that can be left out of the
+    /// next clean
+    fn can_exclude_details(&self) -> bool {
+        let item = &self.cleanup_items[self.selected_index];
+        (item.has_projects() || item.has_duplicate_groups()) && *self.shown_state() != AppState::CleaningDone
+    }
+
+    /// Move the cursor in the details popup
+    pub fn move_detail(&mut self, down: bool) {
+        let count = self.selected_result().map_or(0, |result| result.breakdown.len());
+        self.detail_index = if down {
+            (self.detail_index + 1).min(count.saturating_sub(1))
+        } else {
+            self.detail_index.saturating_sub(1)
+        };
+    }
+
+    /// Exclude the project under the cursor from cleaning, or include it again, and update
+    /// the scanned size to match. Duplicate groups are left out until selected instead.
+    pub fn toggle_detail(&mut self) {
+        if !self.can_exclude_details() {
+            return;
+        }
+        let index = self.selected_index;
+        let Some(Some(result)) = self.scan_results.get_mut(index) else {
+            return;
+        };
+        let Some(entry) = result.breakdown.get_mut(self.detail_index) else {
+            return;
+        };
+        let item = &mut self.cleanup_items[index];
+        if !item.has_duplicate_groups() && !item.can_exclude(Path::new(&entry.label)) {
+            self.status_message = format!("{} 不能单独排除", entry.label);
+            return;
+        }
+        let (left_out, note, message) = if item.has_duplicate_groups() {
+            let left_out = !item.toggle_selected(Path::new(&entry.label));
+            (left_out, NOT_SELECTED_NOTE, if left_out { "已取消选择" } else { "已选择" })
+        } else {
+            let left_out = item.toggle_excluded(Path::new(&entry.label));
+            (left_out, EXCLUDED_NOTE, if left_out { "已排除" } else { "已恢复" })
+        };
+        let suffix = format!(", {}", note);
+        if left_out {
+            entry.note.push_str(&suffix);
+            result.size_bytes = result.size_bytes.saturating_sub(entry.size_bytes);
+        } else {
+            if let Some(note) = entry.note.strip_suffix(&suffix) {
+                entry.note = note.to_string();
+            }
+            result.size_bytes += entry.size_bytes;
+        }
+        self.status_message = format!("{} {}", message, entry.label);
+        result.has_data = result.size_bytes > 0;
+    }
+
+    pub fn close_details(&mut self) {
+        self.state = self.prompt_return.clone();
+        self.status_message = "按 V 查看明细, 按 Q 退出".to_string();
+    }
+
+    /// Open the prompt asking how much space to free
+    pub fn start_target_prompt(&mut self) {
+        self.prompt_return = self.state.clone();
+        self.state = AppState::EnteringTarget;
+        self.target_input.clear();
+        self.status_message = "输入要释放的空间, 如 5G 或 500M | ENTER 确认 | ESC 取消".to_string();
+    }
+
+    /// Leave the target prompt without cleaning
+    pub fn cancel_target_prompt(&mut self) {
+        self.state = self.prompt_return.clone();
+        self.status_message = "已取消。按 F 重新输入目标, 或按 Q 退出".to_string();
+    }
+
+    /// Parse the prompt input. Returns the target, or shows an error and keeps the prompt open.
+    pub fn confirm_target_prompt(&mut self) -> Option<u64> {
+        match parse_size(&self.target_input) {
+            Ok(target) if target > 0 => Some(target),
+            _ => {
+                self.status_message = format!("无效的大小: {} (示例: 5G, 500M)", self.target_input);
+                None
+            }
+        }
+    }
+
+    /// Clean selected items, oldest files first, until `target_bytes` are freed
+    pub fn clean_until(&mut self, target_bytes: u64) {
         self.state = AppState::Cleaning;
         self.is_cleaning = true;
         self.status_message = "正在清理...".to_string();
-        
-        for (i, item) in self.cleanup_items.iter().enumerate() {
-            if item.enabled {
-                let result = item.clean();
-                self.clean_results[i] = Some(result);
-                debug!("Cleaned item {}: {:?}", i, self.clean_results[i]);
-            }
-        }
-        
-        self.state = AppState::CleaningDone;
-        self.is_cleaning = false;
+        self.clean_results = vec![None; self.cleanup_items.len()];
+        self.free_target = Some(target_bytes);
+
+        self.start_job(move |cleaner| {
+            clean_until_freed(cleaner.items(), target_bytes, cleaner.options());
+        });
+    }
+
+    /// Create the restore point on a worker thread; `run_app` starts cleaning once it is done
+    pub fn start_restore_point(&mut self) {
+        self.state = AppState::CreatingRestorePoint;
+        self.is_cleaning = true;
+        self.status_message = "正在创建系统还原点...".to_string();
+        let options = self.options.clone();
+        thread::spawn(move || {
+            let _ = options.create_restore_point();
+        });
+    }
+
+    pub fn clean_selected(&mut self) {
+        self.state = AppState::Cleaning;
+        self.is_cleaning = true;
+        self.status_message = "正在清理...".to_string();
+        self.clean_results = vec![None; self.cleanup_items.len()];
+        self.free_target = None;
+
+        self.start_job(|cleaner| {
+            cleaner.clean_enabled(false);
+        });
+    }
+
+    /// Summarize a finished clean in the status bar
+    fn finish_clean(&mut self) {
         let total_size: u64 = self.clean_results.iter()
             .filter_map(|r| r.as_ref())
+            .filter(|r| !r.report_only)
             .map(|r| r.size_bytes)
             .sum();
-        self.status_message = format!("清理完成! 共释放 {:.2} MB", total_size as f64 / (1024.0 * 1024.0));
-        info!("Cleaning complete: {:.2} MB freed", total_size as f64 / (1024.0 * 1024.0));
+        let failed: usize = self.clean_results.iter()
+            .filter_map(|r| r.as_ref())
+            .map(|r| r.errors.len())
+            .sum();
+        let report: Report = self.cleanup_items.iter()
+            .zip(&self.clean_results)
+            .filter_map(|(item, result)| Some((item.id.clone(), item.outcome(result.clone()?, &self.options))))
+            .collect();
+        let mut problems = String::new();
+        if report.failed() > 0 {
+            warn!("{}", report);
+            let summary = format!(", {} / {} 个项目失败: ", report.failed(), report.items.len());
+            // Items stopped partway also say what they had already deleted
+            let names: Vec<String> = report.items.into_iter()
+                .filter_map(|(id, outcome)| {
+                    let item = self.cleanup_items.iter().find(|item| item.id == id)?;
+                    Some(match outcome.err()?.into_partial_result() {
+                        (Some(partial), _) => format!("{} (中止前已删除 {} 个文件, 释放 {})",
+                                                      item.name, partial.files_deleted, partial.bytes_human()),
+                        (None, _) => item.name.clone(),
+                    })
+                })
+                .collect();
+            problems += &summary;
+            problems += &names.join("、");
+        }
+        if failed > 0 {
+            problems += &format!(", {} 个文件无法删除", failed);
+        }
+
+        self.status_message = if let Some(target) = self.free_target {
+            let untouched = (0..self.cleanup_items.len()).filter(|&i| self.is_untouched(i)).count();
+            if total_size >= target {
+                format!("已达到目标! 释放 {} / {}, {} 个项目未处理 | R 重置 | Q 退出",
+                        format_size(total_size), format_size(target), untouched)
+            } else {
+                format!("未达到目标: 仅释放 {} / {} | R 重置 | Q 退出", format_size(total_size), format_size(target))
+            }
+        } else {
+            format!("清理完成! 共释放 {}{} | R 重置 | Q 退出", format_size(total_size), problems)
+        };
+        self.status_message.insert_str(0, self.background_note());
+        info!("Cleaning complete: {} freed", format_size(total_size));
     }
 
-    pub fn get_total_size(&self, use_clean_results: bool) -> f64 {
+    /// Bytes the scanned or cleaned items hold
+    pub fn get_total_size(&self, use_clean_results: bool) -> u64 {
         let results = if use_clean_results {
             &self.clean_results
         } else {
             &self.scan_results
         };
         
+        // Reports list files that are never deleted
         results.iter()
             .filter_map(|r| r.as_ref())
-            .map(|r| r.size_bytes as f64)
-            .sum::<f64>() / (1024.0 * 1024.0)
+            .filter(|r| !r.report_only)
+            .map(|r| r.size_bytes)
+            .sum()
+    }
+
+    /// Space the scanned files take on disk, in bytes, where items measure it
+    pub fn get_total_disk_size(&self) -> u64 {
+        self.scan_results.iter()
+            .filter_map(|r| r.as_ref())
+            .filter(|r| !r.report_only)
+            .map(|r| r.physical_bytes())
+            .sum()
     }
 
     pub fn get_total_files(&self, use_clean_results: bool) -> u64 {
@@ -190,13 +637,14 @@ impl App {
         
         results.iter()
             .filter_map(|r| r.as_ref())
+            .filter(|r| !r.report_only)
             .map(|r| r.files)
             .sum()
     }
 }
 
 /// Run the TUI application
-pub fn run_tui() -> io::Result<()> {
+pub fn run_tui(scan_parallelism: usize, options: CleanOptions) -> io::Result<()> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
@@ -221,8 +669,26 @@ pub fn run_tui() -> io::Result<()> {
     }
 
     let mut app = App::new();
+    app.scan_parallelism = scan_parallelism;
+    app.options = options;
+
+    // Restore the selection from the previous run
+    let state_path = default_state_path();
+    if let Some(path) = &state_path {
+        match load_item_state(path) {
+            Ok(states) => {
+                apply_item_state(&mut app.cleanup_items, &states);
+                // Saved priority overrides can change the order
+                sort_for_display(&mut app.cleanup_items);
+            }
+            Err(e) => debug!("Ignoring unreadable state file {}: {}", path.display(), e),
+        }
+    }
+
+    app.estimate_all();
+
     let mut list_state = ListState::default();
-    list_state.select(Some(0));
+    list_state.select(Some(app.list_row()));
 
     let res = run_app(&mut terminal, &mut app, &mut list_state);
 
@@ -238,6 +704,12 @@ pub fn run_tui() -> io::Result<()> {
         println!("{err:?}");
     }
 
+    if let Some(path) = &state_path {
+        if let Err(e) = save_item_state(path, &app.cleanup_items) {
+            eprintln!("Warning: Failed to save selection to {}: {}", path.display(), e);
+        }
+    }
+
     Ok(())
 }
 
@@ -247,71 +719,102 @@ fn run_app<B: Backend>(
     list_state: &mut ListState,
 ) -> io::Result<()> {
     loop {
+        app.drain_events();
+        if app.state == AppState::CreatingRestorePoint && app.options.restore_point_outcome.get().is_some() {
+            app.is_cleaning = false;
+            run_clean(app);
+        }
         terminal.draw(|f| ui(f, app, list_state))?;
 
         if event::poll(Duration::from_millis(100))? {
             if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Char('Q') => return Ok(()),
-                    KeyCode::Char(' ') => {
-                        // Apply cooldown for Space key to prevent rapid toggling
-                        if !app.is_scanning && !app.is_cleaning && app.should_process_key() {
-                            app.toggle_selection();
+                let busy = app.is_scanning || app.is_cleaning;
+                if app.state == AppState::EnteringTarget {
+                    match key.code {
+                        KeyCode::Enter => {
+                            if let Some(target) = app.confirm_target_prompt() {
+                                app.pending_target = Some(target);
+                                confirm_or_clean(app);
+                            }
                         }
-                    }
-                    KeyCode::Up | KeyCode::Char('k') => {
-                        // Apply cooldown for navigation to prevent skipping
-                        if !app.is_scanning && !app.is_cleaning && app.should_process_key() {
-                            app.previous();
-                            list_state.select(Some(app.selected_index));
+                        KeyCode::Esc => app.cancel_target_prompt(),
+                        KeyCode::Backspace => {
+                            app.target_input.pop();
                         }
+                        KeyCode::Char(c) if c.is_ascii_alphanumeric() || c == '.' => app.target_input.push(c),
+                        _ => {}
                     }
-                    KeyCode::Down | KeyCode::Char('j') => {
-                        // Apply cooldown for navigation to prevent skipping
-                        if !app.is_scanning && !app.is_cleaning && app.should_process_key() {
-                            app.next();
-                            list_state.select(Some(app.selected_index));
-                        }
+                    continue;
+                }
+                if app.state == AppState::ViewingDetails {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('v') | KeyCode::Char('V') => app.close_details(),
+                        KeyCode::Up | KeyCode::Char('k') if app.should_process_key() => app.move_detail(false),
+                        KeyCode::Down | KeyCode::Char('j') if app.should_process_key() => app.move_detail(true),
+                        KeyCode::Char(' ') if app.should_process_key() => app.toggle_detail(),
+                        _ => {}
                     }
-                    KeyCode::Enter => {
-                        if app.state == AppState::Initial || app.state == AppState::ScanningDone {
-                            tokio::runtime::Runtime::new()
-                                .unwrap()
-                                .block_on(app.scan_all());
-                        }
+                    continue;
+                }
+                if app.state == AppState::ConfirmingClean {
+                    match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') => run_clean(app),
+                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => cancel_clean(app),
+                        _ => {}
                     }
-                    KeyCode::Char('c') | KeyCode::Char('C') => {
-                        if app.state == AppState::ScanningDone {
-                            tokio::runtime::Runtime::new()
-                                .unwrap()
-                                .block_on(app.clean_selected());
-                            
-                            // After cleaning, reset to initial state
-                            *app = App::new();
-                            app.status_message = "清理完成！已重置到初始状态，可选择其他项目或按 Q 退出".to_string();
+                    continue;
+                }
+                if app.state == AppState::ConfirmingRestorePoint {
+                    match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') => {
+                            app.options.restore_point_acknowledged = true;
+                            run_clean(app);
                         }
+                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => cancel_clean(app),
+                        _ => {}
                     }
-                    KeyCode::Char('r') | KeyCode::Char('R') => {
-                        // Reset
-                        *app = App::new();
-                        list_state.select(Some(0));
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Char('Q') => return Ok(()),
+                    // Apply cooldown for Space key to prevent rapid toggling
+                    KeyCode::Char(' ') if !busy && app.should_process_key() => {
+                        app.toggle_selection();
                     }
-                    // Batch selection shortcuts
-                    KeyCode::Char('a') | KeyCode::Char('A') => {
-                        if !app.is_scanning && !app.is_cleaning {
-                            app.select_all();
-                        }
+                    // Apply cooldown for navigation to prevent skipping
+                    KeyCode::Up | KeyCode::Char('k') if !busy && app.should_process_key() => {
+                        app.previous();
+                        list_state.select(Some(app.list_row()));
                     }
-                    KeyCode::Char('d') | KeyCode::Char('D') => {
-                        if !app.is_scanning && !app.is_cleaning {
-                            app.deselect_all();
-                        }
+                    KeyCode::Down | KeyCode::Char('j') if !busy && app.should_process_key() => {
+                        app.next();
+                        list_state.select(Some(app.list_row()));
                     }
-                    KeyCode::Char('i') | KeyCode::Char('I') => {
-                        if !app.is_scanning && !app.is_cleaning {
-                            app.invert_selection();
-                        }
+                    KeyCode::Enter
+                        if matches!(app.state, AppState::Initial | AppState::ScanningDone | AppState::CleaningDone) =>
+                    {
+                        app.scan_all();
+                    }
+                    KeyCode::Char('c') | KeyCode::Char('C') if app.state == AppState::ScanningDone => {
+                        confirm_or_clean(app);
                     }
+                    KeyCode::Char('f') | KeyCode::Char('F')
+                        if matches!(app.state, AppState::Initial | AppState::ScanningDone) =>
+                    {
+                        app.start_target_prompt();
+                    }
+                    KeyCode::Char('v') | KeyCode::Char('V') if !busy => app.open_details(),
+                    KeyCode::Char('r') | KeyCode::Char('R') if !busy => {
+                        // Reset
+                        app.reset();
+                        list_state.select(Some(app.list_row()));
+                    }
+                    // Batch selection shortcuts
+                    KeyCode::Char('g') | KeyCode::Char('G') if !busy => app.toggle_current_category(),
+                    KeyCode::Char('a') | KeyCode::Char('A') if !busy => app.select_all(),
+                    KeyCode::Char('d') | KeyCode::Char('D') if !busy => app.deselect_all(),
+                    KeyCode::Char('i') | KeyCode::Char('I') if !busy => app.invert_selection(),
+                    KeyCode::Char('b') | KeyCode::Char('B') if !busy => app.toggle_background(),
                     _ => {}
                 }
             }
@@ -319,6 +822,70 @@ fn run_app<B: Backend>(
     }
 }
 
+/// Start cleaning, asking for a second confirmation first if aggressive items are selected
+fn confirm_or_clean(app: &mut App) {
+    if app.has_aggressive_selected() {
+        app.state = AppState::ConfirmingClean;
+        app.status_message = "已选择激进项目! 按 Y 确认清理, 按 N 取消".to_string();
+    } else {
+        run_clean(app);
+    }
+}
+
+/// Go back to where cleaning was started from without cleaning
+fn cancel_clean(app: &mut App) {
+    app.state = match app.pending_target.take() {
+        Some(_) => app.prompt_return.clone(),
+        None => AppState::ScanningDone,
+    };
+    app.status_message = "已取消清理。按 C 重新开始, 或按 Q 退出".to_string();
+}
+
+/// Clean the selected items, staying on the results screen until reset.
+///
+/// A restore point the items want is created first; if that fails, the user has to
+/// acknowledge it before anything is cleaned.
+fn run_clean(app: &mut App) {
+    if needs_restore_point(&app.cleanup_items, &app.options) && !app.options.restore_point_acknowledged {
+        match app.options.restore_point_outcome.get() {
+            None => return app.start_restore_point(),
+            Some(Err(e)) => {
+                app.state = AppState::ConfirmingRestorePoint;
+                app.status_message = format!("未能创建系统还原点: {}。按 Y 仍然清理, 按 N 取消", e);
+                return;
+            }
+            Some(Ok(())) => {}
+        }
+    }
+    match app.pending_target.take() {
+        Some(target) => app.clean_until(target),
+        None => app.clean_selected(),
+    }
+}
+
+/// Color used to render an item's risk level
+fn risk_color(risk: RiskLevel) -> Color {
+    match risk {
+        RiskLevel::Safe => Color::Rgb(34, 197, 94),      // Green
+        RiskLevel::Moderate => Color::Rgb(245, 158, 11), // Amber
+        RiskLevel::Aggressive => Color::Rgb(239, 68, 68), // Red
+    }
+}
+
+/// Whether the on-disk size differs from the files' sizes by more than a few percent, as on
+/// NTFS-compressed volumes, so both are worth showing
+fn differs_on_disk(file_bytes: u64, disk_bytes: u64) -> bool {
+    file_bytes.abs_diff(disk_bytes) as f64 > file_bytes as f64 * 0.03
+}
+
+/// Count of online-only cloud files left alone, which explains a size below Explorer's
+fn cloud_note(result: &CleanupResult) -> String {
+    if result.cloud_placeholders == 0 {
+        return String::new();
+    }
+    format!(", 已跳过 {} 个云端文件", result.cloud_placeholders)
+}
+
 fn ui(f: &mut Frame<'_>, app: &mut App, list_state: &mut ListState) {
     // Modern color scheme inspired by CCleaner/BleachBit
     let header_color = Color::Rgb(0, 120, 215);  // Windows blue
@@ -349,35 +916,48 @@ fn ui(f: &mut Frame<'_>, app: &mut App, list_state: &mut ListState) {
         .title(" 🧹 Clean-RS 系统清理工具 v0.3 ")
         .title_style(Style::default().fg(header_color).add_modifier(Modifier::BOLD));
     
-    let header_text = if app.state == AppState::CleaningDone {
+    let mut header_text = if *app.shown_state() == AppState::CleaningDone {
         let total_size = app.get_total_size(true);
         let total_files = app.get_total_files(true);
         vec![
             Line::from(vec![
                 Span::styled("✓ 清理完成! ", Style::default().fg(success_color).add_modifier(Modifier::BOLD)),
-                Span::styled(format!("共释放 {:.2} MB ", total_size), 
+                Span::styled(format!("共释放 {} ", format_size(total_size)), 
                            Style::default().fg(warning_color).add_modifier(Modifier::BOLD)),
                 Span::styled(format!("({} 个文件)", total_files), 
                            Style::default().fg(Color::Rgb(148, 163, 184))),
             ]),
             Line::from(vec![
-                Span::styled("系统已优化，可以选择其他项目继续清理", 
+                Span::styled("按 [ENTER] 重新扫描, [R] 重置, [Q] 退出", 
                            Style::default().fg(Color::Rgb(148, 163, 184))),
             ])
         ]
-    } else if app.state == AppState::ScanningDone {
+    } else if matches!(
+        app.shown_state(),
+        AppState::ScanningDone
+            | AppState::ConfirmingClean
+            | AppState::CreatingRestorePoint
+            | AppState::ConfirmingRestorePoint
+    ) {
         let total_size = app.get_total_size(false);
+        let disk_size = app.get_total_disk_size();
         let total_files = app.get_total_files(false);
+        // Compressed volumes free less than the files' sizes, so lead with what will be freed
+        let (shown_size, file_size_note) = if differs_on_disk(total_size, disk_size) {
+            (disk_size, format!("文件大小 {}, ", format_size(total_size)))
+        } else {
+            (total_size, String::new())
+        };
         vec![
             Line::from(vec![
                 Span::styled("✓ 扫描完成! ", Style::default().fg(accent_color).add_modifier(Modifier::BOLD)),
-                Span::styled(format!("可清理 {:.2} MB ", total_size), 
+                Span::styled(format!("可清理 {} ", format_size(shown_size)), 
                            Style::default().fg(warning_color).add_modifier(Modifier::BOLD)),
-                Span::styled(format!("({} 个文件)", total_files), 
+                Span::styled(format!("({}{} 个文件)", file_size_note, total_files), 
                            Style::default().fg(Color::Rgb(148, 163, 184))),
             ]),
             Line::from(vec![
-                Span::styled("按 [C] 开始清理, [R] 重置, [Q] 退出", 
+                Span::styled("按 [C] 开始清理, [V] 查看明细, [R] 重置, [Q] 退出", 
                            Style::default().fg(warning_color)),
             ])
         ]
@@ -411,6 +991,18 @@ fn ui(f: &mut Frame<'_>, app: &mut App, list_state: &mut ListState) {
         ]
     };
 
+    if let Some((root, usage)) = &app.temp_volume {
+        header_text.push(Line::from(Span::styled(
+            format!(
+                "{} 可用 {} / 共 {}",
+                root.display(),
+                format_size(usage.available),
+                format_size(usage.total)
+            ),
+            Style::default().fg(Color::Rgb(148, 163, 184)),
+        )));
+    }
+
     let header_paragraph = Paragraph::new(header_text)
         .block(header)
         .wrap(Wrap { trim: true })
@@ -418,54 +1010,123 @@ fn ui(f: &mut Frame<'_>, app: &mut App, list_state: &mut ListState) {
     f.render_widget(header_paragraph, chunks[0]);
 
     // Main content - list of cleanup items
-    let items: Vec<ListItem> = app.cleanup_items
-        .iter()
-        .enumerate()
-        .map(|(i, item)| {
-            let status_icon = if item.enabled { "✓" } else { "○" };
-            let style = if item.enabled {
-                Style::default()
-                    .fg(Color::Rgb(34, 197, 94))
-                    .add_modifier(Modifier::BOLD)
+    let mut items: Vec<ListItem> = Vec::new();
+    for (i, item) in app.cleanup_items.iter().enumerate() {
+        if i == 0 || app.cleanup_items[i - 1].category != item.category {
+            items.push(ListItem::new(Line::from(Span::styled(
+                format!("── {} ──", item.category.label()),
+                Style::default().fg(accent_color).add_modifier(Modifier::BOLD),
+            ))));
+        }
+
+        if !item.status.is_ready() {
+            let grey = Style::default().fg(Color::Rgb(100, 116, 139));
+            items.push(ListItem::new(Line::from(vec![
+                Span::styled("[-] ", grey),
+                Span::styled(&item.name, grey),
+                Span::styled(format!(" ({})", item.status.reason()), grey.add_modifier(Modifier::ITALIC)),
+            ])));
+            continue;
+        }
+
+        let status_icon = if item.enabled { "✓" } else { "○" };
+        let style = if item.enabled {
+            Style::default()
+                .fg(Color::Rgb(34, 197, 94))
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Rgb(148, 163, 184))
+        };
+        
+        let result_info = if let Some(Some((files, bytes))) = app.item_progress.get(i) {
+            format!(" → ⏳ {} 文件, {}", files, format_size(*bytes))
+        } else if let (AppState::Initial, Some(Some(result))) = (app.shown_state(), app.scan_results.get(i)) {
+            if result.report_only {
+                " → 仅报告, 扫描后按 V 查看".to_string()
+            } else if result.has_data {
+                format!(" → 约 {} 项", result.total_items())
             } else {
-                Style::default().fg(Color::Rgb(148, 163, 184))
-            };
-            
-            let result_info = if let (AppState::ScanningDone, Some(true)) = 
-                (&app.state, app.scan_results.get(i).map(|r| r.is_some())) {
-                let result = app.scan_results[i].as_ref().unwrap();
-                if result.has_data {
-                    format!(" → {:.2} MB, {} 文件", result.size_mb(), result.files)
-                } else {
-                    " → (无数据)".to_string()
-                }
-            } else if let (AppState::CleaningDone, Some(true)) = 
-                (&app.state, app.clean_results.get(i).map(|r| r.is_some())) {
-                let result = app.clean_results[i].as_ref().unwrap();
-                if result.has_data {
-                    format!(" → ✓ 已清理",)
+                " → (无数据)".to_string()
+            }
+        } else if let (
+            AppState::Scanning
+            | AppState::ScanningDone
+            | AppState::ConfirmingClean
+            | AppState::CreatingRestorePoint
+            | AppState::ConfirmingRestorePoint,
+            Some(true),
+        ) = (app.shown_state(), app.scan_results.get(i).map(|r| r.is_some())) {
+            let result = app.scan_results[i].as_ref().unwrap();
+            let info = if let Some(reason) = &result.skip_reason {
+                format!(" → ⏸ 已跳过: {}", reason)
+            } else if result.report_only && result.has_data {
+                format!(" → 最大的 {} 个文件共 {} (仅报告)", result.files, result.size_human())
+            } else if result.entries > 0 {
+                format!(" → {}, {} 条记录", result.size_human(), result.entries)
+            } else if result.has_data {
+                let disk_bytes = result.physical_bytes();
+                if differs_on_disk(result.size_bytes, disk_bytes) {
+                    format!(" → {}, {} 文件 (文件大小 {})", format_size(disk_bytes), result.files, result.size_human())
                 } else {
-                    " → (无数据)".to_string()
+                    format!(" → {}, {} 文件", result.size_human(), result.files)
                 }
             } else {
-                "".to_string()
+                " → (无数据)".to_string()
             };
-            
-            let icon_style = if item.enabled {
-                Style::default().fg(Color::Rgb(34, 197, 94))
+            info + &cloud_note(result)
+        } else if let (AppState::Cleaning | AppState::CleaningDone, Some(true)) =
+            (app.shown_state(), app.clean_results.get(i).map(|r| r.is_some())) {
+            let result = app.clean_results[i].as_ref().unwrap();
+            let info = if let Some(reason) = &result.skip_reason {
+                format!(" → ⏸ 已跳过: {}", reason)
+            } else if result.report_only {
+                " → 仅报告, 未删除任何文件".to_string()
+            } else if result.has_errors() {
+                format!(" → ⚠ 已清理 {}, {} 个失败", result.size_human(), result.errors.len())
+            } else if result.has_data {
+                format!(" → ✓ 已清理 {}", result.size_human())
             } else {
-                Style::default().fg(Color::Rgb(148, 163, 184))
+                " → (无数据)".to_string()
             };
-            
-            let content = Line::from(vec![
-                Span::styled(format!("[{}] ", status_icon), icon_style),
-                Span::styled(&item.name, Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
-                Span::styled(result_info, Style::default().fg(warning_color)),
-            ]);
-            
-            ListItem::new(content).style(style)
-        })
-        .collect();
+            info + &cloud_note(result)
+        } else if app.is_untouched(i) {
+            " → 未处理 (已达到目标)".to_string()
+        } else {
+            "".to_string()
+        };
+        
+        let icon_style = if item.enabled {
+            Style::default().fg(Color::Rgb(34, 197, 94))
+        } else {
+            Style::default().fg(Color::Rgb(148, 163, 184))
+        };
+        
+        // Selectable, but cleaning will be skipped
+        let elevation_note = if item.status == ItemStatus::NeedsElevation {
+            format!(" ({})", item.status.reason())
+        } else {
+            String::new()
+        };
+        let mount_root_badge = if item.status == ItemStatus::MountRoot {
+            format!(" ⚠ {}", item.status.reason())
+        } else {
+            String::new()
+        };
+
+        let content = Line::from(vec![
+            Span::styled(format!("[{}] ", status_icon), icon_style),
+            Span::styled(&item.name, Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::styled(format!(" [{}]", item.risk.label()), Style::default().fg(risk_color(item.risk))),
+            Span::styled(
+                elevation_note,
+                Style::default().fg(Color::Rgb(100, 116, 139)).add_modifier(Modifier::ITALIC),
+            ),
+            Span::styled(mount_root_badge, Style::default().fg(warning_color).add_modifier(Modifier::BOLD)),
+            Span::styled(result_info, Style::default().fg(warning_color)),
+        ]);
+        
+        items.push(ListItem::new(content).style(style));
+    }
 
     let list = List::new(items)
         .block(Block::default()
@@ -514,15 +1175,148 @@ fn ui(f: &mut Frame<'_>, app: &mut App, list_state: &mut ListState) {
         let progress = Gauge::default()
             .block(progress_block)
             .gauge_style(Style::default().fg(progress_color).bg(Color::Rgb(30, 41, 59)))
-            .ratio(1.0); // Full progress
+            .label(format!("{} / {}", app.job_finished, app.job_total))
+            .ratio(app.job_ratio());
         
+        // Sit at the bottom of the list so per-item progress stays visible
         let popup_area = Rect {
             x: f.size().width / 4,
-            y: f.size().height / 2 - 2,
+            y: (chunks[1].y + chunks[1].height).saturating_sub(4),
             width: f.size().width / 2,
-            height: 5,
+            height: 3,
         };
         f.render_widget(Clear, popup_area);
         f.render_widget(progress, popup_area);
     }
-}
\ No newline at end of file
+
+    // Prompt for the amount of space to free
+    if app.state == AppState::EnteringTarget {
+        let lines = vec![
+            Line::from(Span::styled("要释放多少空间? (如 5G, 500M)", Style::default().fg(Color::White))),
+            Line::from(Span::styled(format!("> {}_", app.target_input), Style::default().fg(warning_color).add_modifier(Modifier::BOLD))),
+            Line::from(Span::styled("最大的项目优先, 每个项目内最旧的文件优先", Style::default().fg(Color::Rgb(148, 163, 184)))),
+        ];
+        let popup_area = Rect {
+            x: f.size().width / 4,
+            y: f.size().height.saturating_sub(5) / 2,
+            width: f.size().width / 2,
+            height: 5,
+        };
+        let popup = Paragraph::new(lines)
+            .block(Block::default()
+                .title(" 🎯 按目标清理 ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(accent_color)))
+            .alignment(Alignment::Center)
+            .style(Style::default().bg(bg_color));
+        f.render_widget(Clear, popup_area);
+        f.render_widget(popup, popup_area);
+    }
+
+    // Breakdown of the selected item, e.g. one line per Rust project
+    if app.state == AppState::ViewingDetails {
+        if let Some(result) = app.selected_result() {
+            let lines: Vec<Line> = result
+                .breakdown
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| {
+                    let line = Line::from(vec![
+                        Span::styled(format!("{:>10}  ", format_size(entry.size_bytes)), Style::default().fg(warning_color)),
+                        Span::styled(entry.label.clone(), Style::default().fg(Color::White)),
+                        Span::styled(format!("  {}", entry.note), Style::default().fg(Color::Rgb(148, 163, 184))),
+                    ]);
+                    if i == app.detail_index && app.can_exclude_details() {
+                        line.style(Style::default().bg(Color::Rgb(51, 65, 85)))
+                    } else {
+                        line
+                    }
+                })
+                .collect();
+            let height = (lines.len() as u16 + 2).min(f.size().height);
+            let popup_area = Rect {
+                x: f.size().width / 8,
+                y: f.size().height.saturating_sub(height) / 2,
+                width: f.size().width * 3 / 4,
+                height,
+            };
+            // Keep the cursor visible when there are more entries than rows
+            let scroll = app.detail_index.saturating_sub(height.saturating_sub(3) as usize) as u16;
+            let popup = Paragraph::new(lines)
+                .scroll((scroll, 0))
+                .block(Block::default()
+                    .title(format!(" 📂 {} ", app.cleanup_items[app.selected_index].name))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(accent_color)))
+                .style(Style::default().bg(bg_color));
+            f.render_widget(Clear, popup_area);
+            f.render_widget(popup, popup_area);
+        }
+    }
+
+    // Confirmation popup for aggressive items
+    if app.state == AppState::ConfirmingClean {
+        let danger_color = risk_color(RiskLevel::Aggressive);
+        let mut lines = vec![Line::from(Span::styled(
+            "以下激进项目可能删除重要数据:",
+            Style::default().fg(danger_color).add_modifier(Modifier::BOLD),
+        ))];
+        for item in app.cleanup_items.iter().filter(|item| item.enabled && item.risk == RiskLevel::Aggressive) {
+            lines.push(Line::from(Span::styled(format!("• {}", item.name), Style::default().fg(Color::White))));
+        }
+        lines.push(Line::from(Span::styled("[Y] 确认清理    [N] 取消", Style::default().fg(warning_color))));
+
+        let height = (lines.len() as u16 + 2).min(f.size().height);
+        let popup_area = Rect {
+            x: f.size().width / 4,
+            y: f.size().height.saturating_sub(height) / 2,
+            width: f.size().width / 2,
+            height,
+        };
+        let popup = Paragraph::new(lines)
+            .block(Block::default()
+                .title(" ⚠ 确认清理 ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(danger_color)))
+            .alignment(Alignment::Center)
+            .style(Style::default().bg(bg_color));
+        f.render_widget(Clear, popup_area);
+        f.render_widget(popup, popup_area);
+    }
+
+    // Warning that cleaning would go ahead without the restore point the items asked for
+    let restore_point_error = app.options.restore_point_outcome.get().and_then(|outcome| outcome.as_ref().err());
+    if let (AppState::ConfirmingRestorePoint, Some(error)) = (&app.state, restore_point_error) {
+        let danger_color = risk_color(RiskLevel::Aggressive);
+        let mut lines = vec![
+            Line::from(Span::styled(
+                "未能创建系统还原点:",
+                Style::default().fg(danger_color).add_modifier(Modifier::BOLD),
+            )),
+            Line::from(Span::styled(error.clone(), Style::default().fg(Color::White))),
+            Line::from(Span::styled("以下项目将在没有还原点的情况下清理:", Style::default().fg(danger_color))),
+        ];
+        for item in app.cleanup_items.iter().filter(|item| item.enabled && item.wants_restore_point(&app.options)) {
+            lines.push(Line::from(Span::styled(format!("• {}", item.name), Style::default().fg(Color::White))));
+        }
+        lines.push(Line::from(Span::styled("[Y] 仍然清理    [N] 取消", Style::default().fg(warning_color))));
+
+        let height = (lines.len() as u16 + 2).min(f.size().height);
+        let popup_area = Rect {
+            x: f.size().width / 4,
+            y: f.size().height.saturating_sub(height) / 2,
+            width: f.size().width / 2,
+            height,
+        };
+        let popup = Paragraph::new(lines)
+            .block(Block::default()
+                .title(" ⚠ 系统还原点 ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(danger_color)))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true })
+            .style(Style::default().bg(bg_color));
+        f.render_widget(Clear, popup_area);
+        f.render_widget(popup, popup_area);
+    }
+}
@@ -0,0 +1,29 @@
+//! Formatting shared by the CLI, the TUI and the library's own log messages
+
+/// Unit names from bytes upwards; anything larger is still given in TB
+const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+/// `bytes` in the largest unit of 1024 that keeps the number at least 1, such as "1023 B",
+/// "1.50 GB" or "3.50 TB". Plain bytes have no decimals.
+pub fn format_size(bytes: u64) -> String {
+    format_in_units(bytes, 1024.0)
+}
+
+/// Like `format_size` with units of 1000, the way disk vendors and macOS count
+pub fn format_size_si(bytes: u64) -> String {
+    format_in_units(bytes, 1000.0)
+}
+
+fn format_in_units(bytes: u64, step: f64) -> String {
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= step && unit < UNITS.len() - 1 {
+        size /= step;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.2} {}", size, UNITS[unit])
+    }
+}
@@ -0,0 +1,439 @@
+//! The file system calls deleting goes through. `RealFs` passes them to `std::fs`; `MockFs`
+//! keeps an in-memory tree that can be told to fail on chosen paths, so the deletion logic
+//! can be tested without touching the disk.
+
+use crate::platform;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// What an entry is, read without following links
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryType {
+    File,
+    Dir,
+    /// A symlink, junction or other reparse point
+    Link,
+}
+
+/// The parts of an entry's metadata cleaning looks at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    pub entry_type: EntryType,
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+    pub accessed: Option<SystemTime>,
+    /// A cloud placeholder whose data is not on disk, see `platform::is_online_only`
+    pub online_only: bool,
+    /// Bytes the entry takes up on disk, which compression and sparse files make smaller
+    /// than `len`, see `platform::disk_size`
+    pub disk_len: u64,
+    /// Compressed by the file system, see `platform::is_compressed`
+    pub compressed: bool,
+    /// The file system holding the entry, see `platform::device_id`
+    pub device: Option<u64>,
+}
+
+impl From<&fs::Metadata> for Metadata {
+    fn from(metadata: &fs::Metadata) -> Self {
+        let entry_type = if platform::is_link(metadata) {
+            EntryType::Link
+        } else if metadata.is_dir() {
+            EntryType::Dir
+        } else {
+            EntryType::File
+        };
+        Metadata {
+            entry_type,
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            accessed: metadata.accessed().ok(),
+            online_only: platform::is_online_only(metadata),
+            disk_len: metadata.len(),
+            compressed: platform::is_compressed(metadata),
+            device: platform::device_id(metadata),
+        }
+    }
+}
+
+/// Metadata as the cleaning helpers read it, from `std::fs` or from an `Fs`
+pub trait EntryMetadata {
+    /// A real directory, not a link to one
+    fn is_real_dir(&self) -> bool;
+    fn is_link(&self) -> bool;
+    fn size(&self) -> u64;
+    fn modified_time(&self) -> io::Result<SystemTime>;
+    fn accessed_time(&self) -> io::Result<SystemTime>;
+    fn is_online_only(&self) -> bool;
+    /// Bytes the entry at `path` takes up on disk
+    fn disk_size(&self, path: &Path) -> u64;
+}
+
+impl EntryMetadata for fs::Metadata {
+    fn is_real_dir(&self) -> bool {
+        self.is_dir() && !platform::is_link(self)
+    }
+
+    fn is_link(&self) -> bool {
+        platform::is_link(self)
+    }
+
+    fn size(&self) -> u64 {
+        self.len()
+    }
+
+    fn modified_time(&self) -> io::Result<SystemTime> {
+        self.modified()
+    }
+
+    fn accessed_time(&self) -> io::Result<SystemTime> {
+        self.accessed()
+    }
+
+    fn is_online_only(&self) -> bool {
+        platform::is_online_only(self)
+    }
+
+    fn disk_size(&self, path: &Path) -> u64 {
+        platform::disk_size(path, self)
+    }
+}
+
+impl EntryMetadata for Metadata {
+    fn is_real_dir(&self) -> bool {
+        self.entry_type == EntryType::Dir
+    }
+
+    fn is_link(&self) -> bool {
+        self.entry_type == EntryType::Link
+    }
+
+    fn size(&self) -> u64 {
+        self.len
+    }
+
+    fn modified_time(&self) -> io::Result<SystemTime> {
+        self.modified.ok_or_else(|| io::Error::from(io::ErrorKind::Unsupported))
+    }
+
+    fn accessed_time(&self) -> io::Result<SystemTime> {
+        self.accessed.ok_or_else(|| io::Error::from(io::ErrorKind::Unsupported))
+    }
+
+    fn is_online_only(&self) -> bool {
+        self.online_only
+    }
+
+    fn disk_size(&self, _path: &Path) -> u64 {
+        self.disk_len
+    }
+}
+
+/// File system operations used while deleting
+pub trait Fs: fmt::Debug + Send + Sync {
+    /// Names of the entries in the directory `path`, sorted
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<OsString>>;
+    fn metadata(&self, path: &Path) -> io::Result<Metadata>;
+    /// Metadata of `path` itself when it is a link
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata>;
+    /// Open the file `path` for reading and close it again, to check it can be read
+    fn open(&self, path: &Path) -> io::Result<()>;
+    /// Delete a file or a link, leaving the link's target alone
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+    /// Delete an empty directory
+    fn remove_dir(&self, path: &Path) -> io::Result<()>;
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+    /// Clear a read-only flag so `path` can be deleted; whether anything changed
+    fn make_deletable(&self, path: &Path) -> bool;
+}
+
+/// The real file system. Paths past `MAX_PATH` work on Windows.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealFs;
+
+impl Fs for RealFs {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<OsString>> {
+        let mut names = fs::read_dir(platform::long_path(path))?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<io::Result<Vec<_>>>()?;
+        names.sort();
+        Ok(names)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        fs::metadata(platform::long_path(path)).map(|metadata| real_metadata(path, &metadata))
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        fs::symlink_metadata(platform::long_path(path)).map(|metadata| real_metadata(path, &metadata))
+    }
+
+    fn open(&self, path: &Path) -> io::Result<()> {
+        fs::File::open(platform::long_path(path)).map(drop)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        platform::remove_file_or_link(&platform::long_path(path))
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir(platform::long_path(path))
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir_all(platform::long_path(path))
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(platform::long_path(from), platform::long_path(to))
+    }
+
+    fn make_deletable(&self, path: &Path) -> bool {
+        platform::make_deletable(&platform::long_path(path))
+    }
+}
+
+/// `Metadata` of the entry at `path`, with its size on disk
+fn real_metadata(path: &Path, metadata: &fs::Metadata) -> Metadata {
+    Metadata {
+        disk_len: platform::disk_size(path, metadata),
+        ..Metadata::from(metadata)
+    }
+}
+
+/// An entry of a `MockFs`
+#[derive(Debug, Clone)]
+struct MockEntry {
+    metadata: Metadata,
+    read_only: bool,
+}
+
+#[derive(Debug, Default)]
+struct MockTree {
+    entries: BTreeMap<PathBuf, MockEntry>,
+    read_failures: HashMap<PathBuf, io::ErrorKind>,
+    remove_failures: HashMap<PathBuf, io::ErrorKind>,
+}
+
+/// An in-memory file system for tests. Parent directories are created as entries are added;
+/// links point nowhere. Read-only entries refuse deletion until `make_deletable` is called.
+#[derive(Debug, Default)]
+pub struct MockFs {
+    tree: Mutex<MockTree>,
+}
+
+impl MockFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file of `len` bytes, last modified at `modified`
+    pub fn file_modified(self, path: impl AsRef<Path>, len: u64, modified: SystemTime) -> Self {
+        self.insert(path.as_ref(), EntryType::File, len, Some(modified))
+    }
+
+    /// Add a file of `len` bytes, modified now
+    pub fn file(self, path: impl AsRef<Path>, len: u64) -> Self {
+        self.file_modified(path, len, SystemTime::now())
+    }
+
+    pub fn dir(self, path: impl AsRef<Path>) -> Self {
+        self.insert(path.as_ref(), EntryType::Dir, 0, Some(SystemTime::now()))
+    }
+
+    pub fn link(self, path: impl AsRef<Path>) -> Self {
+        self.insert(path.as_ref(), EntryType::Link, 0, Some(SystemTime::now()))
+    }
+
+    /// Mark an added entry as an online-only cloud file
+    pub fn online_only(self, path: impl AsRef<Path>) -> Self {
+        self.update(path.as_ref(), |entry| entry.metadata.online_only = true)
+    }
+
+    /// Mark an added entry read-only, so deleting it fails with `PermissionDenied` until
+    /// `make_deletable` clears the flag
+    pub fn read_only(self, path: impl AsRef<Path>) -> Self {
+        self.update(path.as_ref(), |entry| entry.read_only = true)
+    }
+
+    /// Make listing the directory `path` fail with `kind`
+    pub fn fail_read_dir(self, path: impl AsRef<Path>, kind: io::ErrorKind) -> Self {
+        self.tree.lock().unwrap().read_failures.insert(path.as_ref().to_path_buf(), kind);
+        self
+    }
+
+    /// Make opening the file `path` fail with `kind`
+    pub fn fail_open(self, path: impl AsRef<Path>, kind: io::ErrorKind) -> Self {
+        self.fail_read_dir(path, kind)
+    }
+
+    /// Make deleting or renaming `path` fail with `kind`
+    pub fn fail_remove(self, path: impl AsRef<Path>, kind: io::ErrorKind) -> Self {
+        self.tree.lock().unwrap().remove_failures.insert(path.as_ref().to_path_buf(), kind);
+        self
+    }
+
+    pub fn exists(&self, path: impl AsRef<Path>) -> bool {
+        self.tree.lock().unwrap().entries.contains_key(path.as_ref())
+    }
+
+    /// Every entry left, sorted
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.tree.lock().unwrap().entries.keys().cloned().collect()
+    }
+
+    fn insert(self, path: &Path, entry_type: EntryType, len: u64, modified: Option<SystemTime>) -> Self {
+        {
+            let mut tree = self.tree.lock().unwrap();
+            for parent in path.ancestors().skip(1).filter(|parent| !parent.as_os_str().is_empty()) {
+                tree.entries.entry(parent.to_path_buf()).or_insert_with(|| MockEntry {
+                    metadata: Metadata {
+                        entry_type: EntryType::Dir,
+                        len: 0,
+                        modified,
+                        accessed: modified,
+                        online_only: false,
+                        disk_len: 0,
+                        compressed: false,
+                        device: None,
+                    },
+                    read_only: false,
+                });
+            }
+            let metadata = Metadata {
+                entry_type,
+                len,
+                modified,
+                accessed: modified,
+                online_only: false,
+                disk_len: len,
+                compressed: false,
+                device: None,
+            };
+            tree.entries.insert(path.to_path_buf(), MockEntry { metadata, read_only: false });
+        }
+        self
+    }
+
+    fn update(self, path: &Path, change: impl FnOnce(&mut MockEntry)) -> Self {
+        if let Some(entry) = self.tree.lock().unwrap().entries.get_mut(path) {
+            change(entry);
+        }
+        self
+    }
+}
+
+impl MockTree {
+    fn check_remove(&self, path: &Path) -> io::Result<&MockEntry> {
+        if let Some(&kind) = self.remove_failures.get(path) {
+            return Err(kind.into());
+        }
+        let entry = self.entries.get(path).ok_or(io::ErrorKind::NotFound)?;
+        if entry.read_only {
+            return Err(io::ErrorKind::PermissionDenied.into());
+        }
+        Ok(entry)
+    }
+
+    fn children(&self, path: &Path) -> Vec<PathBuf> {
+        self.entries.keys().filter(|child| child.parent() == Some(path)).cloned().collect()
+    }
+}
+
+impl Fs for MockFs {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<OsString>> {
+        let tree = self.tree.lock().unwrap();
+        if let Some(&kind) = tree.read_failures.get(path) {
+            return Err(kind.into());
+        }
+        if tree.entries.get(path).ok_or(io::ErrorKind::NotFound)?.metadata.entry_type != EntryType::Dir {
+            return Err(io::ErrorKind::NotADirectory.into());
+        }
+        Ok(tree.children(path).iter().filter_map(|child| child.file_name().map(OsString::from)).collect())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let metadata = self.symlink_metadata(path)?;
+        if metadata.entry_type == EntryType::Link {
+            // Links lead nowhere in a mock tree
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        Ok(metadata)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let tree = self.tree.lock().unwrap();
+        Ok(tree.entries.get(path).ok_or(io::ErrorKind::NotFound)?.metadata.clone())
+    }
+
+    fn open(&self, path: &Path) -> io::Result<()> {
+        if self.metadata(path)?.entry_type == EntryType::Dir {
+            return Err(io::ErrorKind::IsADirectory.into());
+        }
+        match self.tree.lock().unwrap().read_failures.get(path) {
+            Some(&kind) => Err(kind.into()),
+            None => Ok(()),
+        }
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let mut tree = self.tree.lock().unwrap();
+        if tree.check_remove(path)?.metadata.entry_type == EntryType::Dir {
+            return Err(io::ErrorKind::IsADirectory.into());
+        }
+        tree.entries.remove(path);
+        Ok(())
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        let mut tree = self.tree.lock().unwrap();
+        if tree.check_remove(path)?.metadata.entry_type != EntryType::Dir {
+            return Err(io::ErrorKind::NotADirectory.into());
+        }
+        if !tree.children(path).is_empty() {
+            return Err(io::ErrorKind::DirectoryNotEmpty.into());
+        }
+        tree.entries.remove(path);
+        Ok(())
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut tree = self.tree.lock().unwrap();
+        tree.check_remove(path)?;
+        let below: Vec<PathBuf> = tree.entries.keys().filter(|entry| entry.starts_with(path)).cloned().collect();
+        // Deepest first, stopping at the first failure like std::fs::remove_dir_all
+        for entry in below.iter().rev() {
+            tree.check_remove(entry)?;
+            tree.entries.remove(entry);
+        }
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut tree = self.tree.lock().unwrap();
+        tree.check_remove(from)?;
+        let moved: Vec<PathBuf> = tree.entries.keys().filter(|entry| entry.starts_with(from)).cloned().collect();
+        for entry in moved {
+            let value = tree.entries.remove(&entry).unwrap();
+            let target = to.join(entry.strip_prefix(from).unwrap());
+            tree.entries.insert(target, value);
+        }
+        Ok(())
+    }
+
+    fn make_deletable(&self, path: &Path) -> bool {
+        match self.tree.lock().unwrap().entries.get_mut(path) {
+            Some(entry) if entry.read_only => {
+                entry.read_only = false;
+                true
+            }
+            _ => false,
+        }
+    }
+}
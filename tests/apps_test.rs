@@ -0,0 +1,338 @@
+use clean_rs::apps::{
+    detect_electron_apps, detect_vscode, jetbrains_cache_root, spotify_cache_dirs, spotify_cache_item,
+    store_cache_dirs, store_cache_item, telegram_cache_dirs, telegram_cache_item, telegram_data_dir, ElectronApp, JetBrainsCaches, JetBrainsVersion, VSCODE, VSCODE_PROTECTED,
+    VSCODIUM,
+};
+use clean_rs::browsers::{BaseDirs, Os};
+use clean_rs::{Category, CleanOptions, CleanupType, ItemStatus};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+fn fake_base(root: &Path) -> BaseDirs {
+    BaseDirs {
+        home: root.to_path_buf(),
+        data_local: root.join("AppData/Local"),
+        config: root.join(".config"),
+        cache: root.join(".cache"),
+        data: root.join("Library/Application Support"),
+    }
+}
+
+/// Lay out a VS Code user data directory with caches next to user configuration
+fn fake_code_dir(root: &Path) {
+    for dir in ["Cache/Cache_Data", "CachedData/abc123", "Code Cache/js", "GPUCache", "logs/20240101"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+        fs::write(root.join(dir).join("data"), vec![0u8; 100]).unwrap();
+    }
+    fs::create_dir_all(root.join("User/snippets")).unwrap();
+    fs::write(root.join("User/settings.json"), b"{\"editor.fontSize\": 14}").unwrap();
+    fs::write(root.join("User/keybindings.json"), b"[]").unwrap();
+    fs::create_dir_all(root.join("extensions/rust-lang.rust-analyzer")).unwrap();
+    fs::write(root.join("extensions/rust-lang.rust-analyzer/package.json"), b"{}").unwrap();
+}
+
+#[test]
+fn test_vscode_user_data_per_os() {
+    let base = fake_base(Path::new("/home/user"));
+    assert_eq!(VSCODE.user_data(Os::Windows, &base), PathBuf::from("/home/user/.config/Code"));
+    assert_eq!(VSCODE.user_data(Os::Linux, &base), PathBuf::from("/home/user/.config/Code"));
+    assert_eq!(
+        VSCODIUM.user_data(Os::MacOs, &base),
+        PathBuf::from("/home/user/Library/Application Support/VSCodium")
+    );
+}
+
+#[test]
+fn test_vscode_clean_keeps_user_settings() {
+    let temp_dir = TempDir::new().unwrap();
+    let user_data = temp_dir.path().join("Code");
+    fake_code_dir(&user_data);
+
+    let item = VSCODE.cache_item(&user_data);
+    assert_eq!(item.id, "vscode_cache");
+    let CleanupType::Directories(dirs) = &item.cleanup_type else {
+        panic!("expected a directory list");
+    };
+    assert_eq!(dirs.len(), 5);
+    assert!(dirs.iter().all(|dir| !VSCODE_PROTECTED.iter().any(|name| dir.ends_with(name))));
+
+    let cleaned = item.clean_with(&CleanOptions::new().ignore_running_apps(true));
+    assert_eq!(cleaned.size_bytes, 500);
+    assert!(user_data.join("User/settings.json").exists());
+    assert!(user_data.join("User/keybindings.json").exists());
+    assert!(user_data.join("User/snippets").is_dir());
+    assert!(user_data.join("extensions/rust-lang.rust-analyzer/package.json").exists());
+    assert!(!user_data.join("CachedData/abc123").exists());
+}
+
+#[test]
+fn test_vscode_variants_listed_only_when_present() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = fake_base(temp_dir.path());
+
+    let ids: Vec<String> = detect_vscode(Os::Linux, &base).into_iter().map(|item| item.id).collect();
+    assert_eq!(ids, ["vscode_cache"]);
+
+    fake_code_dir(&base.config.join("VSCodium"));
+    let ids: Vec<String> = detect_vscode(Os::Linux, &base).into_iter().map(|item| item.id).collect();
+    assert_eq!(ids, ["vscode_cache", "vscodium_cache"]);
+}
+
+/// Lay out an Electron data directory with caches and local app state
+fn fake_electron_dir(root: &Path) {
+    for dir in ["Cache/Cache_Data", "Code Cache/js", "GPUCache", "Service Worker/CacheStorage"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+        fs::write(root.join(dir).join("data"), vec![0u8; 10]).unwrap();
+    }
+    fs::create_dir_all(root.join("Local Storage/leveldb")).unwrap();
+    fs::write(root.join("Local Storage/leveldb/000001.log"), b"login token").unwrap();
+}
+
+#[test]
+fn test_electron_apps_detected_when_installed() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = fake_base(temp_dir.path());
+    assert!(detect_electron_apps(Os::Linux, &base, &[]).is_empty());
+
+    fake_electron_dir(&base.config.join("discord"));
+    // Teams keeps its data one level deeper on Windows
+    fake_electron_dir(&base.config.join("Microsoft/Teams"));
+
+    let items = detect_electron_apps(Os::Windows, &base, &[]);
+    let ids: Vec<&str> = items.iter().map(|item| item.id.as_str()).collect();
+    assert_eq!(ids, ["discord_cache", "teams_cache"]);
+    assert!(items.iter().all(|item| !item.enabled));
+
+    let CleanupType::Directories(dirs) = &items[0].cleanup_type else {
+        panic!("expected a directory list");
+    };
+    assert_eq!(dirs.len(), 4);
+    assert!(dirs.iter().all(|dir| !dir.starts_with(base.config.join("discord/Local Storage"))));
+}
+
+#[test]
+fn test_electron_apps_from_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = fake_base(temp_dir.path());
+    fake_electron_dir(&base.config.join("Notion"));
+    fake_electron_dir(&base.config.join("Slack"));
+
+    let extra = [
+        ElectronApp {
+            key: "notion".to_string(),
+            name: "Notion".to_string(),
+            dir_names: vec!["Notion".to_string()],
+            process_names: vec!["Notion".to_string()],
+        },
+        // Replaces the built-in entry with the same key
+        ElectronApp {
+            key: "slack".to_string(),
+            name: "Slack (custom)".to_string(),
+            dir_names: vec!["Slack".to_string()],
+            process_names: Vec::new(),
+        },
+    ];
+    let items = detect_electron_apps(Os::Linux, &base, &extra);
+    let names: Vec<&str> = items.iter().map(|item| item.name.as_str()).collect();
+    assert_eq!(names, ["Notion 缓存", "Slack (custom) 缓存"]);
+}
+
+#[test]
+fn test_running_electron_app_is_skipped() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = fake_base(temp_dir.path());
+    let data_dir = base.config.join("Postman");
+    fake_electron_dir(&data_dir);
+    fs::write(data_dir.join("SingletonLock"), b"").unwrap();
+
+    let item = detect_electron_apps(Os::Linux, &base, &[]).pop().unwrap();
+    let result = item.clean();
+    assert!(result.skip_reason.is_some());
+    assert!(data_dir.join("GPUCache/data").exists());
+
+    fs::remove_file(data_dir.join("SingletonLock")).unwrap();
+    let result = item.clean_with(&CleanOptions::new().ignore_running_apps(true));
+    assert_eq!(result.size_bytes, 40);
+    assert!(data_dir.join("Local Storage/leveldb/000001.log").exists());
+}
+
+/// Write `size` bytes to `path`, last modified `days` ago
+fn write_aged(path: &Path, size: usize, days: u64) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, vec![0u8; size]).unwrap();
+    let modified = SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
+    File::options().write(true).open(path).unwrap().set_modified(modified).unwrap();
+}
+
+#[test]
+fn test_spotify_cache_dirs_per_os() {
+    let base = fake_base(Path::new("/home/user"));
+    assert_eq!(
+        spotify_cache_dirs(Os::Windows, &base),
+        [
+            PathBuf::from("/home/user/AppData/Local/Spotify/Data"),
+            PathBuf::from("/home/user/AppData/Local/Spotify/Storage"),
+        ]
+    );
+    assert_eq!(spotify_cache_dirs(Os::Linux, &base), [PathBuf::from("/home/user/.cache/spotify")]);
+    assert_eq!(
+        spotify_cache_dirs(Os::MacOs, &base),
+        [PathBuf::from("/home/user/.cache/com.spotify.client")]
+    );
+}
+
+#[test]
+fn test_spotify_keeps_recently_streamed_chunks() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = fake_base(temp_dir.path());
+    let data = base.data_local.join("Spotify/Data");
+    write_aged(&data.join("a1/old.file"), 1000, 30);
+    write_aged(&data.join("b2/recent.file"), 200, 1);
+    write_aged(&base.data_local.join("Spotify/Storage/old.file"), 500, 30);
+
+    let item = spotify_cache_item(Os::Windows, &base);
+    assert!(!item.enabled);
+    assert!(item.description.contains(&data.display().to_string()));
+    assert!(item.description.contains("Storage"));
+
+    let scanned = item.scan();
+    assert_eq!(scanned.size_bytes, 1500);
+
+    let cleaned = item.clean_with(&CleanOptions::new().ignore_running_apps(true));
+    assert_eq!(cleaned.size_bytes, 1500);
+    assert!(cleaned.errors.is_empty(), "{:?}", cleaned.errors);
+    assert!(!data.join("a1").exists());
+    assert!(data.join("b2/recent.file").exists());
+}
+
+#[test]
+fn test_telegram_data_dir_per_os() {
+    let base = fake_base(Path::new("/home/user"));
+    let data = PathBuf::from("/home/user/Library/Application Support");
+    assert_eq!(telegram_data_dir(Os::Windows, &base), data.join("Telegram Desktop"));
+    assert_eq!(telegram_data_dir(Os::Linux, &base), data.join("TelegramDesktop"));
+    assert_eq!(telegram_data_dir(Os::MacOs, &base), data.join("Telegram Desktop"));
+}
+
+#[test]
+fn test_telegram_cleans_only_media_caches_of_every_account() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = fake_base(temp_dir.path());
+    let tdata = telegram_data_dir(Os::MacOs, &base).join("tdata");
+    write_aged(&tdata.join("key_datas"), 50, 30);
+    write_aged(&tdata.join("D877F783D5D3EF8C/maps"), 50, 30);
+    write_aged(&tdata.join("user_data/cache/0/photo"), 1000, 30);
+    write_aged(&tdata.join("user_data/media_cache/1/video"), 2000, 30);
+    write_aged(&tdata.join("user_data/media_cache/1/recent"), 300, 1);
+    write_aged(&tdata.join("user_data/emoji/set_0"), 400, 30);
+    write_aged(&tdata.join("user_data#2/cache/0/photo"), 500, 30);
+
+    assert_eq!(
+        telegram_cache_dirs(tdata.parent().unwrap()),
+        [
+            tdata.join("user_data/cache"),
+            tdata.join("user_data/media_cache"),
+            tdata.join("user_data#2/cache"),
+        ]
+    );
+    let item = telegram_cache_item(Os::MacOs, &base);
+    assert!(!item.enabled);
+    assert_eq!(item.category, Category::Applications);
+    assert_eq!(item.scan().size_bytes, 3500);
+
+    let cleaned = item.clean_with(&CleanOptions::new().ignore_running_apps(true));
+    assert!(cleaned.errors.is_empty(), "{:?}", cleaned.errors);
+    assert_eq!(cleaned.size_bytes, 3500);
+    assert!(tdata.join("key_datas").exists());
+    assert!(tdata.join("D877F783D5D3EF8C/maps").exists());
+    assert!(tdata.join("user_data/emoji/set_0").exists());
+    assert!(tdata.join("user_data/media_cache/1/recent").exists());
+    assert!(!tdata.join("user_data#2/cache/0/photo").exists());
+}
+
+#[test]
+fn test_telegram_missing_is_path_missing() {
+    let temp_dir = TempDir::new().unwrap();
+    let item = telegram_cache_item(Os::Linux, &fake_base(temp_dir.path()));
+    assert_eq!(item.validate(), ItemStatus::PathMissing);
+}
+
+#[test]
+fn test_jetbrains_version_parsing() {
+    let version = JetBrainsVersion::parse(PathBuf::from("/cache/JetBrains/IntelliJIdea2023.3")).unwrap();
+    assert_eq!(version.product, "IntelliJIdea");
+    assert_eq!(version.version, [2023, 3]);
+    assert_eq!(version.label(), "IntelliJIdea 2023.3");
+    assert!(JetBrainsVersion::parse(PathBuf::from("/cache/JetBrains/Toolbox")).is_none());
+    assert!(JetBrainsVersion::parse(PathBuf::from("/cache/JetBrains/2023.3")).is_none());
+}
+
+#[test]
+fn test_jetbrains_cleans_superseded_versions_and_current_logs() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = fake_base(temp_dir.path());
+    let root = jetbrains_cache_root(Os::Linux, &base);
+    let files: [(&str, usize); 6] = [
+        ("PyCharm2023.1/caches/content.dat", 1000),
+        ("PyCharm2023.1/log/idea.log", 10),
+        ("PyCharm2024.1/caches/content.dat", 2000),
+        ("PyCharm2024.1/log/idea.log", 20),
+        ("PyCharm2024.1/index/shards.dat", 300),
+        ("CLion2023.2/caches/content.dat", 4000),
+    ];
+    for (relative, size) in files {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![0u8; size]).unwrap();
+    }
+    let options = base.config.join("JetBrains/PyCharm2023.1/options/editor.xml");
+    fs::create_dir_all(options.parent().unwrap()).unwrap();
+    fs::write(&options, b"<application/>").unwrap();
+
+    let item = JetBrainsCaches::new(&root).cache_item();
+    let scan = item.scan();
+    let breakdown: Vec<(&str, u64)> = scan.breakdown.iter().map(|entry| (entry.label.as_str(), entry.size_bytes)).collect();
+    assert_eq!(breakdown, [("CLion 2023.2", 0), ("PyCharm 2023.1", 1010), ("PyCharm 2024.1", 320)]);
+
+    let result = item.clean_with(&CleanOptions {
+        ignore_running_apps: true,
+        ..Default::default()
+    });
+    assert_eq!(result.size_bytes, 1330);
+    assert!(!root.join("PyCharm2023.1").exists());
+    assert!(root.join("PyCharm2024.1/caches/content.dat").exists());
+    assert!(root.join("PyCharm2024.1/log").is_dir());
+    assert!(!root.join("PyCharm2024.1/log/idea.log").exists());
+    assert!(root.join("CLion2023.2/caches/content.dat").exists());
+    assert!(options.exists());
+}
+
+#[test]
+fn test_store_cache_cleans_only_cache_folders_once_per_family() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = fake_base(temp_dir.path());
+    let packages = base.data_local.join("Packages");
+    let store = packages.join("Microsoft.WindowsStore_8wekyb3d8bbwe");
+    write_aged(&store.join("LocalCache/Local/cache.dat"), 400, 3);
+    write_aged(&store.join("TempState/download.tmp"), 100, 3);
+    write_aged(&store.join("Settings/settings.dat"), 50, 3);
+    write_aged(&store.join("SystemAppData/Helium/User.dat"), 60, 3);
+    write_aged(&packages.join("microsoft.windowsstore_8wekyb3d8bbwe/TempState/other.tmp"), 70, 3);
+    write_aged(&packages.join("Microsoft.WindowsCalculator_8wekyb3d8bbwe/LocalCache/calc.dat"), 80, 3);
+
+    assert_eq!(store_cache_dirs(&packages), [store.join("LocalCache"), store.join("TempState")]);
+
+    let item = store_cache_item(&base);
+    assert!(!item.enabled);
+    assert_eq!(item.category, Category::Applications);
+    assert_eq!(item.scan().size_bytes, 500);
+
+    let cleaned = item.clean_with(&CleanOptions::new().ignore_running_apps(true));
+    assert!(cleaned.errors.is_empty(), "{:?}", cleaned.errors);
+    assert!(store.join("LocalCache").is_dir());
+    assert!(!store.join("LocalCache/Local").exists());
+    assert!(store.join("Settings/settings.dat").exists());
+    assert!(store.join("SystemAppData/Helium/User.dat").exists());
+}
@@ -0,0 +1,395 @@
+use clean_rs::browsers::{
+    chromium_cache_dirs, chromium_profiles, detect_chromium_browsers, firefox_install, parse_profiles_ini, BaseDirs,
+    ChromiumInstall, FirefoxProfile, Os, SafariCache, SafariLayout, CHROME, CHROMIUM, CHROMIUM_FAMILY, EDGE,
+};
+use clean_rs::{get_all_cleanup_items, Category, CleanOptions, CleanupType, ItemStatus};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Lay out a fake Chrome `User Data` directory with two profiles and some non-profile dirs
+fn fake_user_data(root: &Path) {
+    for profile in ["Default", "Profile 1"] {
+        let dir = root.join(profile);
+        fs::create_dir_all(dir.join("Cache/Cache_Data")).unwrap();
+        fs::create_dir_all(dir.join("Code Cache/js")).unwrap();
+        fs::create_dir_all(dir.join("Service Worker/CacheStorage")).unwrap();
+        fs::create_dir_all(dir.join("Service Worker/Database")).unwrap();
+        for file in ["Preferences", "History", "Bookmarks", "Login Data"] {
+            fs::write(dir.join(file), b"user data").unwrap();
+        }
+    }
+    fs::create_dir_all(root.join("Profile 1/GPUCache")).unwrap();
+    // Not profiles: no Preferences file
+    fs::create_dir_all(root.join("ShaderCache")).unwrap();
+    fs::create_dir_all(root.join("Crashpad")).unwrap();
+    fs::write(root.join("Local State"), b"{}").unwrap();
+}
+
+#[test]
+fn test_chromium_profiles_require_preferences() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fake_user_data(root);
+
+    let profiles = chromium_profiles(root);
+    assert_eq!(profiles, [root.join("Default"), root.join("Profile 1")]);
+    assert!(chromium_profiles(&root.join("missing")).is_empty());
+}
+
+#[test]
+fn test_chromium_cache_dirs_selects_only_caches() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fake_user_data(root);
+
+    let dirs = chromium_cache_dirs(&chromium_profiles(root));
+    let expected = [
+        root.join("Default/Cache"),
+        root.join("Default/Code Cache"),
+        root.join("Default/Service Worker/CacheStorage"),
+        root.join("Profile 1/Cache"),
+        root.join("Profile 1/Code Cache"),
+        root.join("Profile 1/GPUCache"),
+        root.join("Profile 1/Service Worker/CacheStorage"),
+    ];
+    assert_eq!(dirs, expected);
+
+    for dir in &dirs {
+        let name = dir.file_name().unwrap().to_string_lossy();
+        assert!(!["History", "Bookmarks", "Login Data", "Preferences"].contains(&name.as_ref()));
+    }
+}
+
+fn fake_base(root: &Path) -> BaseDirs {
+    BaseDirs {
+        home: root.to_path_buf(),
+        data_local: root.join("AppData/Local"),
+        config: root.join(".config"),
+        cache: root.join(".cache"),
+        data: root.join("Library/Application Support"),
+    }
+}
+
+#[test]
+fn test_chromium_install_paths_per_os() {
+    let base = fake_base(Path::new("/home/user"));
+
+    assert_eq!(
+        CHROME.install(Os::Windows, &base).unwrap(),
+        ChromiumInstall {
+            user_data: PathBuf::from("/home/user/AppData/Local/Google/Chrome/User Data"),
+            cache_root: None,
+        }
+    );
+    assert_eq!(
+        CHROME.install(Os::Linux, &base).unwrap(),
+        ChromiumInstall {
+            user_data: PathBuf::from("/home/user/.config/google-chrome"),
+            cache_root: Some(PathBuf::from("/home/user/.cache/google-chrome")),
+        }
+    );
+    assert_eq!(
+        CHROME.install(Os::MacOs, &base).unwrap().user_data,
+        PathBuf::from("/home/user/Library/Application Support/Google/Chrome")
+    );
+    assert_eq!(
+        CHROMIUM.install(Os::Linux, &base).unwrap().user_data,
+        PathBuf::from("/home/user/.config/chromium")
+    );
+}
+
+#[test]
+fn test_cache_item_includes_mirrored_cache_root() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = fake_base(temp_dir.path());
+    let install = CHROME.install(Os::Linux, &base).unwrap();
+    fake_user_data(&install.user_data);
+    let cache_root = install.cache_root.clone().unwrap();
+    fs::create_dir_all(cache_root.join("Default/Cache")).unwrap();
+    // Leftover cache of a profile that no longer exists is not touched
+    fs::create_dir_all(cache_root.join("Old Profile/Cache")).unwrap();
+
+    let item = CHROME.cache_item(&install);
+    assert_eq!(item.id, "chrome_cache");
+    assert_eq!(item.category, Category::Browsers);
+    assert!(!item.enabled);
+    assert!(item.description.contains("2 个配置文件"));
+    let CleanupType::Directories(dirs) = &item.cleanup_type else {
+        panic!("expected a directory list");
+    };
+    assert!(dirs.contains(&install.user_data.join("Profile 1/GPUCache")));
+    assert!(dirs.contains(&cache_root.join("Default/Cache")));
+    assert!(!dirs.iter().any(|dir| dir.starts_with(cache_root.join("Old Profile"))));
+    assert!(item.validate().is_ready());
+}
+
+#[test]
+fn test_missing_browsers_are_listed_but_disabled() {
+    let items = get_all_cleanup_items();
+    for id in ["chrome_cache", "chromium_cache", "edge_cache", "firefox_cache"] {
+        let item = items.iter().find(|item| item.id == id).unwrap();
+        if !item.status.is_ready() {
+            assert!(!item.enabled);
+        }
+    }
+}
+
+const SAMPLE_PROFILES_INI: &str = "\
+[Install4F96D1932A9F858E]
+Default=Profiles/abcd1234.default-release
+Locked=1
+
+[Profile1]
+Name=default
+IsRelative=1
+Path=Profiles/wxyz9876.default
+
+[Profile0]
+Name=default-release
+IsRelative=1
+Path=Profiles/abcd1234.default-release
+Default=1
+
+[Profile2]
+Name=elsewhere
+IsRelative=0
+Path=/data/firefox/custom
+
+[General]
+StartWithLastProfile=1
+Version=2
+";
+
+#[test]
+fn test_parse_profiles_ini() {
+    let profiles = parse_profiles_ini(SAMPLE_PROFILES_INI);
+    assert_eq!(
+        profiles,
+        [
+            FirefoxProfile {
+                name: "default".to_string(),
+                path: "Profiles/wxyz9876.default".to_string(),
+                is_relative: true,
+            },
+            FirefoxProfile {
+                name: "default-release".to_string(),
+                path: "Profiles/abcd1234.default-release".to_string(),
+                is_relative: true,
+            },
+            FirefoxProfile {
+                name: "elsewhere".to_string(),
+                path: "/data/firefox/custom".to_string(),
+                is_relative: false,
+            },
+        ]
+    );
+    assert!(parse_profiles_ini("").is_empty());
+}
+
+#[test]
+fn test_firefox_install_paths_per_os() {
+    let base = fake_base(Path::new("/home/user"));
+    let linux = firefox_install(Os::Linux, &base);
+    assert_eq!(linux.root, PathBuf::from("/home/user/.mozilla/firefox"));
+    assert_eq!(linux.cache_root, PathBuf::from("/home/user/.cache/mozilla/firefox"));
+
+    let windows = firefox_install(Os::Windows, &base);
+    assert_eq!(windows.root, PathBuf::from("/home/user/.config/Mozilla/Firefox"));
+    assert_eq!(windows.cache_root, PathBuf::from("/home/user/AppData/Local/Mozilla/Firefox"));
+
+    let macos = firefox_install(Os::MacOs, &base);
+    assert_eq!(macos.root, PathBuf::from("/home/user/Library/Application Support/Firefox"));
+    assert_eq!(macos.cache_root, PathBuf::from("/home/user/.cache/Firefox"));
+}
+
+#[test]
+fn test_firefox_cache_item_selects_only_caches() {
+    let temp_dir = TempDir::new().unwrap();
+    let install = firefox_install(Os::Linux, &fake_base(temp_dir.path()));
+    let profile = install.root.join("Profiles/abcd1234.default-release");
+    fs::create_dir_all(&profile).unwrap();
+    fs::write(profile.join("places.sqlite"), b"history").unwrap();
+    fs::write(install.root.join("profiles.ini"), SAMPLE_PROFILES_INI).unwrap();
+
+    let local = install.cache_root.join("Profiles/abcd1234.default-release");
+    fs::create_dir_all(local.join("cache2/entries")).unwrap();
+    fs::create_dir_all(local.join("startupCache")).unwrap();
+    fs::create_dir_all(local.join("safebrowsing")).unwrap();
+
+    let item = install.cache_item();
+    assert_eq!(item.id, "firefox_cache");
+    assert!(item.description.contains("3 个配置文件"));
+    let CleanupType::Directories(dirs) = &item.cleanup_type else {
+        panic!("expected a directory list");
+    };
+    assert_eq!(dirs, &[local.join("cache2"), local.join("startupCache")]);
+    assert!(!dirs.iter().any(|dir| dir.starts_with(&profile)));
+
+    // A Firefox running on the test machine must not make the result depend on it
+    item.clean_with(&CleanOptions::new().ignore_running_apps(true));
+    assert!(profile.join("places.sqlite").exists());
+    assert!(local.join("safebrowsing").exists());
+    assert!(!local.join("cache2/entries").exists());
+}
+
+#[test]
+fn test_edge_item_aggregates_profiles() {
+    let temp_dir = TempDir::new().unwrap();
+    let install = EDGE.install(Os::Windows, &fake_base(temp_dir.path())).unwrap();
+    assert!(install.user_data.ends_with("Microsoft/Edge/User Data"));
+    fake_user_data(&install.user_data);
+    fs::write(install.user_data.join("Default/Cache/Cache_Data/f_000001"), vec![0u8; 100]).unwrap();
+    fs::write(install.user_data.join("Profile 1/GPUCache/data_0"), vec![0u8; 50]).unwrap();
+
+    let item = EDGE.cache_item(&install);
+    assert_eq!(item.id, "edge_cache");
+    assert!(!item.enabled);
+    assert!(item.description.contains(&install.user_data.display().to_string()));
+    assert_eq!(item.scan().size_bytes, 150);
+
+    // A lock file in User Data means Edge is running
+    let app = item.app_check.as_ref().unwrap();
+    assert!(!app.lock_files.iter().any(|lock| lock.exists()));
+    fs::write(install.user_data.join("lockfile"), b"").unwrap();
+    assert!(app.is_running());
+    assert!(item.clean().skip_reason.is_some());
+    assert!(install.user_data.join("Default/Cache/Cache_Data/f_000001").exists());
+}
+
+#[test]
+fn test_chromium_family_paths_per_os() {
+    let base = fake_base(Path::new("/home/user"));
+    let brave = CHROMIUM_FAMILY.iter().find(|spec| spec.key == "brave").unwrap();
+    assert_eq!(
+        brave.install(Os::Windows, &base).unwrap().user_data,
+        PathBuf::from("/home/user/AppData/Local/BraveSoftware/Brave-Browser/User Data")
+    );
+    assert_eq!(
+        brave.install(Os::Linux, &base).unwrap().user_data,
+        PathBuf::from("/home/user/.config/BraveSoftware/Brave-Browser")
+    );
+
+    // Opera keeps its profile in %APPDATA% and the caches in %LOCALAPPDATA%
+    let opera = CHROMIUM_FAMILY.iter().find(|spec| spec.key == "opera").unwrap();
+    assert_eq!(
+        opera.install(Os::Windows, &base).unwrap(),
+        ChromiumInstall {
+            user_data: PathBuf::from("/home/user/.config/Opera Software/Opera Stable"),
+            cache_root: Some(PathBuf::from("/home/user/AppData/Local/Opera Software/Opera Stable")),
+        }
+    );
+
+    let arc = CHROMIUM_FAMILY.iter().find(|spec| spec.key == "arc").unwrap();
+    assert!(arc.install(Os::Linux, &base).is_none());
+    assert_eq!(
+        arc.install(Os::MacOs, &base).unwrap().cache_root,
+        Some(PathBuf::from("/home/user/.cache/Arc/User Data"))
+    );
+
+    for spec in CHROMIUM_FAMILY {
+        assert!(!spec.key.is_empty() && !spec.process_names.is_empty(), "{}", spec.name);
+    }
+}
+
+#[test]
+fn test_detect_only_installed_chromium_browsers() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = fake_base(temp_dir.path());
+    assert!(detect_chromium_browsers(Os::Linux, &base).is_empty());
+
+    let vivaldi = base.config.join("vivaldi");
+    fake_user_data(&vivaldi);
+    let items = detect_chromium_browsers(Os::Linux, &base);
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].id, "vivaldi_cache");
+    assert!(!items[0].enabled);
+}
+
+#[test]
+fn test_single_profile_browser_caches() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = fake_base(temp_dir.path());
+    let opera = CHROMIUM_FAMILY.iter().find(|spec| spec.key == "opera").unwrap();
+    let install = opera.install(Os::Linux, &base).unwrap();
+    fs::create_dir_all(&install.user_data).unwrap();
+    fs::write(install.user_data.join("Preferences"), b"{}").unwrap();
+    fs::write(install.user_data.join("History"), b"user data").unwrap();
+    let cache_root = install.cache_root.clone().unwrap();
+    fs::create_dir_all(cache_root.join("Cache")).unwrap();
+
+    let item = opera.cache_item(&install);
+    let CleanupType::Directories(dirs) = &item.cleanup_type else {
+        panic!("expected a directory list");
+    };
+    assert!(item.description.contains("1 个配置文件"));
+    assert_eq!(dirs.len(), 1);
+    assert_eq!(dirs[0], cache_root.join("Cache"));
+}
+
+#[test]
+fn test_safari_layout_detection() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let safari = SafariCache::new(home);
+    assert_eq!(safari.layout(), None);
+    assert_eq!(SafariCache::new(home).cache_item().validate(), ItemStatus::PathMissing);
+
+    fs::create_dir_all(home.join("Library/Caches/com.apple.Safari")).unwrap();
+    assert_eq!(safari.layout(), Some(SafariLayout::Legacy));
+
+    fs::create_dir_all(home.join("Library/Containers/com.apple.Safari/Data/Library/Caches")).unwrap();
+    assert_eq!(safari.layout(), Some(SafariLayout::Container));
+    assert_eq!(safari.cache_dirs().len(), 2);
+}
+
+#[test]
+fn test_safari_clean_keeps_bookmarks_and_history() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let container = home.join("Library/Containers/com.apple.Safari/Data/Library/Caches");
+    fs::create_dir_all(container.join("com.apple.Safari/WebKitCache")).unwrap();
+    fs::write(container.join("com.apple.Safari/WebKitCache/blob"), vec![0u8; 300]).unwrap();
+    let data = home.join("Library/Safari");
+    fs::create_dir_all(&data).unwrap();
+    fs::write(data.join("History.db"), b"history").unwrap();
+    fs::write(data.join("Bookmarks.plist"), b"bookmarks").unwrap();
+
+    let item = SafariCache::new(home).cache_item();
+    assert_eq!(item.id, "safari_cache");
+    assert!(!item.enabled);
+    assert!(item.description.contains("沙盒容器"));
+    assert_eq!(item.validate(), ItemStatus::Ready);
+
+    let scanned = item.scan();
+    assert_eq!(scanned.size_bytes, 300);
+
+    let cleaned = item.clean_with(&CleanOptions::new().ignore_running_apps(true));
+    assert_eq!(cleaned.size_bytes, 300);
+    assert!(!container.join("com.apple.Safari").exists());
+    assert!(data.join("History.db").exists());
+    assert!(data.join("Bookmarks.plist").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_safari_unreadable_container_needs_full_disk_access() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let container = temp_dir.path().join("Library/Containers/com.apple.Safari/Data/Library/Caches");
+    fs::create_dir_all(&container).unwrap();
+    fs::write(container.join("cache.db"), vec![0u8; 10]).unwrap();
+    fs::set_permissions(&container, fs::Permissions::from_mode(0o000)).unwrap();
+
+    let privileged = fs::read_dir(&container).is_ok();
+    let item = SafariCache::new(temp_dir.path()).cache_item();
+    let status = item.validate();
+    let scanned = item.scan();
+    fs::set_permissions(&container, fs::Permissions::from_mode(0o755)).unwrap();
+
+    if !privileged {
+        assert_eq!(status, ItemStatus::NeedsFullDiskAccess);
+        assert!(scanned.skip_reason.unwrap().contains("完全磁盘访问权限"));
+    }
+}
@@ -0,0 +1,1105 @@
+use clean_rs::{
+    clean_enabled, estimate_ready_parallel, get_all_cleanup_items, priority_order, get_cleanup_items_up_to, items_by_category, scan_enabled_parallel,
+    toggle_category, AppCheck, Category, CleanOptions, CleanTarget, CleanupItem, CleanupResult,
+    CleanupType, ErrorClass, ItemOptions, ItemStatus, MockFs, RiskLevel, TempPattern,
+};
+use clean_rs::cleanup_items::ADMIN_REQUIRED_NOTE;
+use clean_rs::patterns::FilePattern;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tempfile::TempDir;
+
+/// Target whose scan takes a fixed time and reports a recognizable file count
+#[derive(Debug)]
+struct SleepTarget {
+    delay: Duration,
+    marker: u64,
+}
+
+impl CleanTarget for SleepTarget {
+    fn scan(&self) -> CleanupResult {
+        thread::sleep(self.delay);
+        CleanupResult {
+            files: self.marker,
+            has_data: true,
+            ..CleanupResult::new()
+        }
+    }
+
+    fn clean(&self) -> CleanupResult {
+        self.scan()
+    }
+}
+
+fn sleep_item(delay_ms: u64, marker: u64) -> CleanupItem {
+    CleanupItem {
+        id: format!("sleep_{}", marker),
+        name: format!("Sleep {}", marker),
+        description: String::new(),
+        cleanup_type: CleanupType::Custom(Arc::new(SleepTarget {
+            delay: Duration::from_millis(delay_ms),
+            marker,
+        })),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+fn directory_item(path: &Path) -> CleanupItem {
+    CleanupItem {
+        id: "test_dir".to_string(),
+        name: "Test directory".to_string(),
+        description: String::new(),
+        cleanup_type: CleanupType::Directory(path.to_path_buf()),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_aggressive_items_disabled_by_default() {
+    for item in get_all_cleanup_items() {
+        if item.risk == RiskLevel::Aggressive {
+            assert!(!item.enabled, "{} should be disabled by default", item.id);
+        }
+    }
+}
+
+#[test]
+fn test_get_cleanup_items_up_to() {
+    let safe = get_cleanup_items_up_to(RiskLevel::Safe);
+    assert!(!safe.is_empty());
+    assert!(safe.iter().all(|item| item.risk == RiskLevel::Safe));
+
+    let moderate = get_cleanup_items_up_to(RiskLevel::Moderate);
+    assert!(moderate.iter().all(|item| item.risk <= RiskLevel::Moderate));
+
+    let all = get_cleanup_items_up_to(RiskLevel::Aggressive);
+    assert_eq!(all.len(), get_all_cleanup_items().len());
+}
+
+#[test]
+fn test_risk_level_from_str() {
+    assert_eq!("safe".parse::<RiskLevel>(), Ok(RiskLevel::Safe));
+    assert_eq!("Moderate".parse::<RiskLevel>(), Ok(RiskLevel::Moderate));
+    assert_eq!("AGGRESSIVE".parse::<RiskLevel>(), Ok(RiskLevel::Aggressive));
+    assert!("reckless".parse::<RiskLevel>().is_err());
+}
+
+#[test]
+fn test_items_by_category_groups_all_items() {
+    let items = get_all_cleanup_items();
+    let groups = items_by_category(&items);
+
+    let grouped: usize = groups.values().map(|group| group.len()).sum();
+    assert_eq!(grouped, items.len());
+    for (category, group) in &groups {
+        assert!(group.iter().all(|item| item.category == *category));
+    }
+}
+
+#[test]
+fn test_toggle_category() {
+    let mut items = get_all_cleanup_items();
+    toggle_category(&mut items, Category::System, true);
+    assert!(items
+        .iter()
+        .filter(|item| item.category == Category::System)
+        .all(|item| item.enabled == item.status.is_ready()));
+
+    toggle_category(&mut items, Category::System, false);
+    assert!(items
+        .iter()
+        .filter(|item| item.category == Category::System)
+        .all(|item| !item.enabled));
+}
+
+#[test]
+fn test_category_strings_are_stable() {
+    let expected = ["system", "browsers", "dev_tools", "applications", "logs"];
+    for (category, name) in Category::ALL.iter().zip(expected) {
+        assert_eq!(category.as_str(), name);
+        assert_eq!(name.parse::<Category>(), Ok(*category));
+    }
+}
+
+#[test]
+fn test_clean_reports_deleted_entries() {
+    let fs = Arc::new(
+        MockFs::new()
+            .file("/cache/file1.txt", 13)
+            .file("/cache/file2.txt", 1024)
+            .file("/cache/subdir/file3.txt", 2048),
+    );
+
+    let result = directory_item(Path::new("/cache")).clean_with(&CleanOptions::new().fs(fs.clone()));
+
+    assert_eq!(result.files, 3);
+    assert_eq!(result.directories, 1);
+    assert_eq!(result.size_bytes, 3085);
+    assert!(!result.has_errors());
+    assert_eq!(fs.paths(), vec![Path::new("/"), Path::new("/cache")]);
+}
+
+#[test]
+fn test_clean_records_failed_deletions() {
+    let fs = Arc::new(
+        MockFs::new()
+            .file("/cache/deletable.txt", 100)
+            .file("/cache/locked/stuck.txt", 500)
+            .fail_remove("/cache/locked/stuck.txt", io::ErrorKind::PermissionDenied),
+    );
+
+    let result = directory_item(Path::new("/cache")).clean_with(&CleanOptions::new().fs(fs.clone()));
+
+    assert_eq!(result.files, 1);
+    assert_eq!(result.size_bytes, 100);
+    assert_eq!(result.directories, 0);
+    // An item that does not need elevation keeps the path that failed
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].path, Path::new("/cache/locked/stuck.txt"));
+    assert_eq!(result.errors[0].kind, io::ErrorKind::PermissionDenied);
+    assert!(fs.exists("/cache/locked/stuck.txt"));
+    assert!(!fs.exists("/cache/deletable.txt"));
+}
+
+#[test]
+fn test_clean_collapses_denied_errors_only_for_admin_items() {
+    let fs = Arc::new(
+        MockFs::new()
+            .file("/cache/a.bin", 10)
+            .file("/cache/b.bin", 10)
+            .fail_remove("/cache/a.bin", io::ErrorKind::PermissionDenied)
+            .fail_remove("/cache/b.bin", io::ErrorKind::PermissionDenied),
+    );
+    let item = CleanupItem {
+        requires_admin: true,
+        ..directory_item(Path::new("/cache"))
+    };
+
+    let result = item.clean_with(&CleanOptions::new().fs(fs));
+
+    let paths: Vec<_> = result.errors.iter().map(|error| error.path.as_path()).collect();
+    if clean_rs::platform::is_elevated() {
+        assert_eq!(paths, vec![Path::new("/cache/a.bin"), Path::new("/cache/b.bin")]);
+    } else {
+        // Without elevation the failures are reported once for the item's root
+        assert_eq!(paths, vec![Path::new("/cache")]);
+    }
+}
+
+#[test]
+fn test_clean_retries_read_only_entries() {
+    let fs = Arc::new(
+        MockFs::new()
+            .file("/cache/go/mod/pkg.go", 300)
+            .read_only("/cache/go/mod/pkg.go")
+            .read_only("/cache/go/mod"),
+    );
+
+    let result = directory_item(Path::new("/cache")).clean_with(&CleanOptions::new().fs(fs.clone()));
+
+    assert!(!result.has_errors(), "{:?}", result.errors);
+    assert_eq!(result.files, 1);
+    assert_eq!(result.directories, 2);
+    assert!(!fs.exists("/cache/go"));
+}
+
+#[test]
+fn test_clean_reports_unreadable_and_busy_entries() {
+    let fs = Arc::new(
+        MockFs::new()
+            .file("/cache/sealed/a.bin", 10)
+            .file("/cache/open.log", 20)
+            .file("/cache/old.log", 40)
+            .fail_read_dir("/cache/sealed", io::ErrorKind::Other)
+            .fail_remove("/cache/open.log", io::ErrorKind::ResourceBusy),
+    );
+
+    let result = directory_item(Path::new("/cache")).clean_with(&CleanOptions::new().fs(fs.clone()));
+
+    assert_eq!(result.files, 1);
+    assert_eq!(result.size_bytes, 40);
+    let errors: Vec<_> = result.errors.iter().map(|error| (error.path.as_path(), error.code, error.class())).collect();
+    assert_eq!(
+        errors,
+        vec![
+            (Path::new("/cache/open.log"), "E_IO", ErrorClass::Transient),
+            (Path::new("/cache/sealed"), "E_IO", ErrorClass::Fatal),
+        ]
+    );
+    // A directory whose contents could not be listed is kept
+    assert!(fs.exists("/cache/sealed/a.bin"));
+}
+
+#[test]
+fn test_clean_keeps_files_younger_than_min_age() {
+    let old = SystemTime::now() - Duration::from_secs(10 * 24 * 60 * 60);
+    let fs = Arc::new(
+        MockFs::new()
+            .file_modified("/cache/old.tmp", 50, old)
+            .file_modified("/cache/logs/old.log", 60, old)
+            .file("/cache/logs/today.log", 70),
+    );
+    let item = CleanupItem {
+        min_age_days: 3,
+        ..directory_item(Path::new("/cache"))
+    };
+
+    let result = item.clean_with(&CleanOptions::new().fs(fs.clone()));
+
+    assert_eq!(result.files, 2);
+    assert_eq!(result.size_bytes, 110);
+    // The directory holding a recent file stays
+    assert_eq!(result.directories, 0);
+    assert!(fs.exists("/cache/logs/today.log"));
+    assert!(!fs.exists("/cache/logs/old.log"));
+}
+
+#[test]
+fn test_scan_estimate_and_clean_read_only_the_mock_fs() {
+    let fs = Arc::new(
+        MockFs::new()
+            .file("/mock-temp/a.tmp", 100)
+            .file("/mock-temp/keep.txt", 200)
+            .file("/mock-temp/build/b.log", 300)
+            .file("/mock-temp/stuck.bak", 400)
+            .fail_remove("/mock-temp/stuck.bak", io::ErrorKind::PermissionDenied),
+    );
+    let options = CleanOptions::new().fs(fs.clone());
+    let item = CleanupItem {
+        cleanup_type: CleanupType::TempFiles(PathBuf::from("/mock-temp")),
+        ..directory_item(Path::new("/mock-temp"))
+    };
+
+    let estimate = item.estimate_with(&options);
+    assert_eq!(estimate.files, 3);
+    let scan = item.scan_with(&options);
+    assert_eq!(scan.files, 3);
+    assert_eq!(scan.size_bytes, 800);
+
+    let result = item.clean_with(&options);
+    assert_eq!(result.files, 2);
+    assert_eq!(result.size_bytes, 400);
+    // The check after cleaning sees the file that could not be deleted
+    assert_eq!(result.files_remaining, 1);
+    assert_eq!(result.size_remaining, 400);
+    assert!(fs.exists("/mock-temp/keep.txt"));
+    assert!(!fs.exists("/mock-temp/build/b.log"));
+}
+
+#[test]
+fn test_files_item_reads_only_the_mock_fs() {
+    let fs = Arc::new(
+        MockFs::new()
+            .file("/mock-dumps/a.dmp", 100)
+            .file("/mock-dumps/b.DMP", 200)
+            .file("/mock-dumps/notes.txt", 50)
+            .file("/mock-logs/locked.log", 300)
+            .fail_open("/mock-logs/locked.log", io::ErrorKind::PermissionDenied),
+    );
+    let options = CleanOptions::new().fs(fs.clone());
+    let item = CleanupItem {
+        cleanup_type: CleanupType::Files(vec![
+            PathBuf::from("/mock-dumps/*.dmp"),
+            PathBuf::from("/mock-logs/locked.log"),
+        ]),
+        ..directory_item(Path::new("/mock-dumps"))
+    };
+
+    assert_eq!(item.validate_with(&options), ItemStatus::Ready);
+    // The estimate does not open files, so it counts the unreadable one too
+    assert_eq!(item.estimate_with(&options).files, 3);
+    let scan = item.scan_with(&options);
+    assert_eq!(scan.files, 2);
+    assert_eq!(scan.size_bytes, 300);
+    assert!(scan.breakdown.iter().any(|entry| entry.label == "/mock-logs/locked.log"));
+
+    let result = item.clean_with(&options);
+    assert_eq!(result.files, 2);
+    assert_eq!(result.size_bytes, 300);
+    assert!(!fs.exists("/mock-dumps/a.dmp"));
+    assert!(!fs.exists("/mock-dumps/b.DMP"));
+    assert!(fs.exists("/mock-dumps/notes.txt"));
+    assert!(fs.exists("/mock-logs/locked.log"));
+}
+
+#[test]
+fn test_file_pattern_item_reads_only_the_mock_fs() {
+    let fs = Arc::new(
+        MockFs::new()
+            .file("/mock-home/.DS_Store", 10)
+            .file("/mock-home/photos/.DS_Store", 20)
+            .file("/mock-home/photos/keep.jpg", 5)
+            .file("/mock-home/backup/.DS_Store", 30),
+    );
+    let options = CleanOptions::new().fs(fs.clone());
+    let item = CleanupItem {
+        cleanup_type: CleanupType::FilePattern(FilePattern {
+            roots: vec![PathBuf::from("/mock-home")],
+            names: vec![".DS_Store".to_string()],
+            excluded: vec![PathBuf::from("/mock-home/backup")],
+            same_filesystem: true,
+        }),
+        ..directory_item(Path::new("/mock-home"))
+    };
+
+    assert_eq!(item.validate_with(&options), ItemStatus::Ready);
+    assert_eq!(item.estimate_with(&options).files, 2);
+    let scan = item.scan_with(&options);
+    assert_eq!(scan.files, 2);
+    assert_eq!(scan.size_bytes, 30);
+
+    let result = item.clean_with(&options);
+    assert_eq!(result.files, 2);
+    assert_eq!(result.files_remaining, 0);
+    assert!(!fs.exists("/mock-home/.DS_Store"));
+    assert!(!fs.exists("/mock-home/photos/.DS_Store"));
+    assert!(fs.exists("/mock-home/photos/keep.jpg"));
+    assert!(fs.exists("/mock-home/backup/.DS_Store"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_clean_unlinks_symlinked_dirs_without_following_them() {
+    use std::os::unix::fs::symlink;
+
+    let temp_dir = TempDir::new().unwrap();
+    let target = temp_dir.path().join("Documents");
+    let cache = temp_dir.path().join("cache");
+    fs::create_dir_all(&target).unwrap();
+    fs::write(target.join("thesis.docx"), vec![0u8; 4096]).unwrap();
+    fs::create_dir_all(cache.join("app")).unwrap();
+    fs::write(cache.join("app/blob"), vec![0u8; 100]).unwrap();
+    symlink(&target, cache.join("app/Documents")).unwrap();
+    // A link back to its own parent would loop a walk that followed links
+    symlink(&cache, cache.join("app/loop")).unwrap();
+
+    let item = directory_item(&cache);
+    let scan = item.scan();
+    assert_eq!(scan.files, 3);
+    assert_eq!(scan.size_bytes, 100);
+
+    let result = item.clean();
+    assert!(!result.has_errors(), "{:?}", result.errors);
+    assert_eq!(result.files, 3);
+    assert_eq!(result.size_bytes, 100);
+    assert_eq!(fs::read_dir(&cache).unwrap().count(), 0);
+    assert_eq!(fs::read(target.join("thesis.docx")).unwrap().len(), 4096);
+}
+
+#[cfg(windows)]
+#[test]
+fn test_clean_removes_junctions_but_not_their_target() {
+    use std::process::Command;
+
+    let temp_dir = TempDir::new().unwrap();
+    let target = temp_dir.path().join("Documents");
+    let cache = temp_dir.path().join("cache");
+    fs::create_dir_all(&target).unwrap();
+    fs::write(target.join("thesis.docx"), vec![0u8; 4096]).unwrap();
+    fs::create_dir_all(cache.join("app")).unwrap();
+    fs::write(cache.join("app/blob"), vec![0u8; 100]).unwrap();
+    let junction = |link: &Path, to: &Path| {
+        let status = Command::new("cmd").arg("/C").arg("mklink").arg("/J").arg(link).arg(to).status().unwrap();
+        assert!(status.success());
+    };
+    junction(&cache.join("app\\Application Data"), &target);
+    // Like the `Application Data` loops inside some profiles
+    junction(&cache.join("app\\loop"), &cache);
+
+    let item = directory_item(&cache);
+    let scan = item.scan();
+    assert_eq!(scan.files, 3);
+    assert_eq!(scan.size_bytes, 100);
+
+    let result = item.clean();
+    assert!(!result.has_errors(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 100);
+    assert_eq!(fs::read_dir(&cache).unwrap().count(), 0);
+    assert_eq!(fs::read(target.join("thesis.docx")).unwrap().len(), 4096);
+}
+
+#[test]
+fn test_cloud_placeholders_are_counted_but_not_deleted() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_path = temp_dir.path();
+    fs::create_dir_all(dir_path.join("Documents")).unwrap();
+    fs::write(dir_path.join("Documents/local.txt"), vec![0u8; 100]).unwrap();
+    fs::write(dir_path.join("Documents/.Report.pdf.icloud"), vec![0u8; 200]).unwrap();
+    fs::write(dir_path.join(".Photo.heic.icloud"), vec![0u8; 200]).unwrap();
+
+    let item = directory_item(dir_path);
+    let scan = item.scan();
+    assert_eq!(scan.files, 1);
+    assert_eq!(scan.size_bytes, 100);
+    assert_eq!(scan.cloud_placeholders, 2);
+
+    let result = item.clean();
+    assert!(!result.has_errors(), "{:?}", result.errors);
+    assert_eq!(result.files, 1);
+    assert_eq!(result.cloud_placeholders, 2);
+    // The folder holding a placeholder stays
+    assert!(dir_path.join("Documents/.Report.pdf.icloud").exists());
+    assert!(dir_path.join(".Photo.heic.icloud").exists());
+
+    let mut item = directory_item(dir_path);
+    item.options.delete_cloud_placeholders = Some(true);
+    let result = item.clean();
+    assert!(!result.has_errors(), "{:?}", result.errors);
+    assert_eq!(result.files, 2);
+    assert_eq!(result.cloud_placeholders, 0);
+    assert_eq!(fs::read_dir(dir_path).unwrap().count(), 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_scan_reports_on_disk_size_of_sparse_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_path = temp_dir.path();
+    // Set to 64 MB without writing anything, so almost nothing is allocated
+    fs::File::create(dir_path.join("sparse.img")).unwrap().set_len(64 * 1024 * 1024).unwrap();
+    fs::write(dir_path.join("data.bin"), vec![1u8; 64 * 1024]).unwrap();
+
+    let scan = directory_item(dir_path).scan();
+    assert_eq!(scan.size_bytes, 64 * 1024 * 1024 + 64 * 1024);
+    assert!(scan.disk_bytes >= 64 * 1024, "{}", scan.disk_bytes);
+    assert!(scan.disk_bytes < 1024 * 1024, "{}", scan.disk_bytes);
+    assert_eq!(scan.physical_bytes(), scan.disk_bytes);
+
+    let unmeasured = CleanupResult { size_bytes: 100, ..CleanupResult::new() };
+    assert_eq!(unmeasured.physical_bytes(), 100);
+}
+
+#[test]
+fn test_skip_remote_keeps_local_items() {
+    use clean_rs::platform::{volume_kind, VolumeKind};
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("file.txt"), vec![0u8; 100]).unwrap();
+    // Only meaningful where the temp directory itself is local
+    if volume_kind(temp_dir.path()) != VolumeKind::Local {
+        return;
+    }
+
+    let item = directory_item(temp_dir.path());
+    assert!(item.remote_root().is_none());
+    let options = CleanOptions::new().skip_remote(true);
+    let scan = item.scan_with(&options);
+    assert!(scan.skip_reason.is_none());
+    assert_eq!(scan.size_bytes, 100);
+    assert_eq!(item.clean_with(&options).size_bytes, 100);
+}
+
+#[test]
+fn test_restore_point_is_wanted_by_aggressive_items_or_per_item() {
+    use clean_rs::needs_restore_point;
+
+    let temp_dir = TempDir::new().unwrap();
+    let mut aggressive = directory_item(temp_dir.path());
+    aggressive.risk = RiskLevel::Aggressive;
+    let mut safe = directory_item(temp_dir.path());
+
+    let options = CleanOptions::new().restore_point(true);
+    assert!(aggressive.wants_restore_point(&options));
+    assert!(!aggressive.wants_restore_point(&CleanOptions::new()));
+    assert!(!safe.wants_restore_point(&options));
+
+    safe.options.restore_point = Some(true);
+    assert!(safe.wants_restore_point(&CleanOptions::new()));
+    aggressive.options.restore_point = Some(false);
+    assert!(!aggressive.wants_restore_point(&options));
+
+    safe.enabled = false;
+    assert!(!needs_restore_point(&[aggressive, safe.clone()], &options));
+    safe.enabled = true;
+    assert!(needs_restore_point(&[safe], &options));
+}
+
+#[test]
+fn test_mount_points_are_refused_unless_allowed() {
+    use clean_rs::error::CleanError;
+
+    let temp_dir = TempDir::new().unwrap();
+    let mounted = temp_dir.path().join("mnt");
+    fs::create_dir_all(&mounted).unwrap();
+    let is_mount = |path: &Path| path == mounted;
+
+    let item = directory_item(&mounted);
+    assert_eq!(item.mount_root_with(is_mount), Some(mounted.as_path()));
+    let refused = item.check_mount_roots_with(&CleanOptions::new(), is_mount);
+    assert!(matches!(refused, Err(CleanError::RefusedMountPoint(path)) if path == mounted));
+    assert!(item.check_mount_roots_with(&CleanOptions::new().allow_mount_roots(true), is_mount).is_ok());
+
+    let mut both = directory_item(temp_dir.path());
+    both.cleanup_type = CleanupType::Directories(vec![temp_dir.path().to_path_buf(), mounted.clone()]);
+    assert_eq!(both.mount_root_with(is_mount), Some(mounted.as_path()));
+    assert!(directory_item(temp_dir.path()).mount_root_with(is_mount).is_none());
+}
+
+#[test]
+fn test_temp_files_on_a_mount_point_are_not_refused() {
+    let temp_dir = TempDir::new().unwrap();
+    let tmp = temp_dir.path().to_path_buf();
+    // A tmpfs /tmp is its own mount
+    let is_mount = |path: &Path| path == tmp;
+
+    let mut item = directory_item(&tmp);
+    item.cleanup_type = CleanupType::TempFiles(tmp.clone());
+    assert!(item.mount_root_with(is_mount).is_none());
+    assert!(item.check_mount_roots_with(&CleanOptions::new(), is_mount).is_ok());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_volume_root_is_flagged_without_a_detector() {
+    let root = directory_item(Path::new("/"));
+    assert_eq!(root.mount_root_with(|_| false), Some(Path::new("/")));
+    assert_eq!(root.validate(), ItemStatus::MountRoot);
+    assert!(ItemStatus::MountRoot.is_ready());
+    assert!(root.check_mount_roots(&CleanOptions::new()).is_err());
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_failed_restore_point_skips_items_until_acknowledged() {
+    use clean_rs::cleanup_items::RESTORE_POINT_FAILED_NOTE;
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("file.txt"), vec![0u8; 100]).unwrap();
+    let mut item = directory_item(temp_dir.path());
+    item.risk = RiskLevel::Aggressive;
+
+    let options = CleanOptions::new().restore_point(true);
+    assert!(options.restore_point_outcome.get().is_none());
+    let result = item.clean_with(&options);
+    assert!(result.skip_reason.as_deref().unwrap().starts_with(RESTORE_POINT_FAILED_NOTE));
+    assert!(temp_dir.path().join("file.txt").exists());
+    // Clones share the outcome, so the restore point is only tried once a run
+    assert!(matches!(options.clone().restore_point_outcome.get(), Some(Err(_))));
+
+    let acknowledged = options.restore_point_acknowledged(true);
+    let result = item.clean_with(&acknowledged);
+    assert!(result.skip_reason.is_none());
+    assert_eq!(result.size_bytes, 100);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_detect_in_use_skips_files_held_open() {
+    use clean_rs::cleanup_items::{in_use_note, IN_USE_NOTE};
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("held.log"), vec![0u8; 100]).unwrap();
+    fs::write(temp_dir.path().join("free.log"), vec![0u8; 50]).unwrap();
+    let held = fs::File::open(temp_dir.path().join("held.log")).unwrap();
+
+    let item = directory_item(temp_dir.path());
+    let result = item.clean_with(&CleanOptions::new().detect_in_use(true));
+    assert_eq!(result.size_bytes, 50);
+    assert!(temp_dir.path().join("held.log").exists());
+    assert!(!temp_dir.path().join("free.log").exists());
+    assert_eq!(result.skipped_in_use.len(), 1);
+    let skipped = &result.skipped_in_use[0];
+    assert_eq!(skipped.path, temp_dir.path().join("held.log"));
+    assert_eq!(skipped.size_bytes, 100);
+    assert!(skipped.holders.iter().any(|holder| holder.pid == std::process::id()));
+    assert_eq!(result.breakdown[0].note, in_use_note(&skipped.holders));
+    assert!(result.breakdown[0].note.starts_with("被 "));
+    assert_eq!(in_use_note(&[]), IN_USE_NOTE);
+
+    // Without detection, Unix deletes files that are still open
+    let result = item.clean_with(&CleanOptions::new());
+    assert_eq!(result.size_bytes, 100);
+    assert!(result.skipped_in_use.is_empty());
+    drop(held);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_detect_in_use_skips_held_files_of_a_files_item() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("held.dmp"), vec![0u8; 100]).unwrap();
+    fs::write(temp_dir.path().join("free.dmp"), vec![0u8; 50]).unwrap();
+    let held = fs::File::open(temp_dir.path().join("held.dmp")).unwrap();
+    let item = CleanupItem {
+        cleanup_type: CleanupType::Files(vec![temp_dir.path().join("*.dmp")]),
+        ..directory_item(temp_dir.path())
+    };
+
+    let result = item.clean_with(&CleanOptions::new().detect_in_use(true));
+    assert_eq!(result.files, 1);
+    assert_eq!(result.size_bytes, 50);
+    assert!(temp_dir.path().join("held.dmp").exists());
+    assert_eq!(result.skipped_in_use.len(), 1);
+    assert_eq!(result.skipped_in_use[0].path, temp_dir.path().join("held.dmp"));
+    assert_eq!(result.skipped_in_use[0].size_bytes, 100);
+    drop(held);
+}
+
+#[test]
+fn test_scan_enabled_parallel_runs_concurrently() {
+    // Later items finish first, so completion order differs from index order
+    let mut items: Vec<CleanupItem> = (0..4u64).map(|i| sleep_item(400 - i * 50, i + 1)).collect();
+    items.push(sleep_item(10, 99));
+    items[4].enabled = false;
+
+    let mut results: Vec<Option<CleanupResult>> = vec![None; items.len()];
+    let mut completion_order = Vec::new();
+    let start = Instant::now();
+    scan_enabled_parallel(&items, 4, |i, result| {
+        completion_order.push(i);
+        results[i] = Some(result);
+    });
+    let elapsed = start.elapsed();
+
+    // Sequential scanning would take 1.1s; concurrent scanning is bounded by the slowest item
+    assert!(elapsed < Duration::from_millis(900), "scan took {:?}", elapsed);
+    for (i, result) in results.iter().take(4).enumerate() {
+        assert_eq!(result.as_ref().unwrap().files, i as u64 + 1);
+    }
+    assert!(results[4].is_none());
+    assert_eq!(completion_order, vec![3, 2, 1, 0]);
+}
+
+#[test]
+fn test_scan_enabled_parallel_respects_cap() {
+    let items: Vec<CleanupItem> = (0..4u64).map(|i| sleep_item(150, i)).collect();
+
+    let start = Instant::now();
+    let mut count = 0;
+    scan_enabled_parallel(&items, 2, |_, _| count += 1);
+
+    // Two workers need two rounds of 150ms each
+    assert_eq!(count, 4);
+    assert!(start.elapsed() >= Duration::from_millis(300));
+}
+
+#[test]
+fn test_clean_skips_item_while_app_running() {
+    let temp_dir = TempDir::new().unwrap();
+    let cache = temp_dir.path().join("Cache");
+    fs::create_dir(&cache).unwrap();
+    fs::write(cache.join("data_0"), vec![0u8; 64]).unwrap();
+    let lock = temp_dir.path().join("SingletonLock");
+    fs::write(&lock, b"").unwrap();
+
+    let mut item = directory_item(&cache);
+    item.app_check = Some(AppCheck {
+        app_name: "Browser".to_string(),
+        process_names: Vec::new(),
+        lock_files: vec![lock.clone()],
+        pid_file_dirs: Vec::new(),
+    });
+
+    let skipped = item.clean();
+    assert!(skipped.skip_reason.as_deref().unwrap().contains("Browser"));
+    assert_eq!(skipped.files, 0);
+    assert!(cache.join("data_0").exists());
+
+    let forced = item.clean_with(&CleanOptions::new().ignore_running_apps(true));
+    assert!(forced.skip_reason.is_none());
+    assert_eq!(forced.files, 1);
+    assert!(!cache.join("data_0").exists());
+
+    fs::remove_file(&lock).unwrap();
+    assert!(!item.app_check.as_ref().unwrap().is_running());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_app_check_detects_running_process() {
+    let own_name = fs::read_to_string("/proc/self/comm").unwrap();
+    let check = AppCheck {
+        app_name: "self".to_string(),
+        process_names: vec![own_name.trim().to_uppercase()],
+        lock_files: Vec::new(),
+        pid_file_dirs: Vec::new(),
+    };
+    assert!(check.is_running());
+
+    let absent = AppCheck {
+        app_name: "absent".to_string(),
+        process_names: vec!["no-such-process-xyz".to_string()],
+        lock_files: Vec::new(),
+        pid_file_dirs: Vec::new(),
+    };
+    assert!(!absent.is_running());
+}
+
+#[test]
+fn test_validate_reports_missing_path() {
+    let temp_dir = TempDir::new().unwrap();
+    assert_eq!(directory_item(temp_dir.path()).validate(), ItemStatus::Ready);
+
+    let missing = directory_item(&temp_dir.path().join("not-installed"));
+    assert_eq!(missing.validate(), ItemStatus::PathMissing);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_validate_reports_permission_denied() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let protected = temp_dir.path().join("protected");
+    fs::create_dir(&protected).unwrap();
+    fs::set_permissions(&protected, fs::Permissions::from_mode(0o000)).unwrap();
+
+    let privileged = fs::read_dir(&protected).is_ok();
+    let status = directory_item(&protected).validate();
+    fs::set_permissions(&protected, fs::Permissions::from_mode(0o755)).unwrap();
+
+    if !privileged {
+        assert_eq!(status, ItemStatus::PermissionDenied);
+    }
+}
+
+#[test]
+fn test_unavailable_builtin_items_are_disabled() {
+    for item in get_all_cleanup_items() {
+        if item.status == ItemStatus::NeedsElevation {
+            assert!(item.requires_admin, "{} is not tagged as requiring admin", item.id);
+            assert_eq!(item.validate(), ItemStatus::Ready);
+        } else {
+            assert_eq!(item.status, item.validate());
+        }
+        if !item.status.is_ready() {
+            assert!(!item.enabled, "{} should be disabled", item.id);
+        }
+    }
+}
+
+#[test]
+fn test_items_needing_elevation_stay_selectable() {
+    assert!(ItemStatus::NeedsElevation.is_ready());
+    assert_eq!(ItemStatus::NeedsElevation.reason(), ADMIN_REQUIRED_NOTE);
+    assert!(!ItemStatus::PermissionDenied.is_ready());
+}
+
+#[test]
+fn test_permission_errors_collapse_to_one_entry_per_root() {
+    use clean_rs::cleanup_items::collapse_permission_errors;
+    use clean_rs::CleanErrorEntry;
+    use std::io;
+
+    let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+    let in_use = io::Error::from(io::ErrorKind::NotFound);
+    let root = Path::new("/var/cache/apt");
+    let mut errors: Vec<CleanErrorEntry> =
+        (0..300).map(|i| CleanErrorEntry::new(&root.join(format!("archives/{}.deb", i)), &denied)).collect();
+    errors.push(CleanErrorEntry::new(&root.join("lock"), &in_use));
+    errors.push(CleanErrorEntry::new(Path::new("/elsewhere/file"), &denied));
+
+    let collapsed = collapse_permission_errors(errors, &[root]);
+    assert_eq!(collapsed.len(), 3);
+    assert_eq!(collapsed[0].path, root.join("lock"));
+    assert_eq!(collapsed[1].path, Path::new("/elsewhere/file"));
+    assert_eq!(collapsed[2].path, root);
+    assert_eq!(collapsed[2].kind, io::ErrorKind::PermissionDenied);
+    assert_eq!(collapsed[2].message, format!("300 个文件无法删除, {}", ADMIN_REQUIRED_NOTE));
+}
+
+#[test]
+fn test_temp_files_item_uses_precise_patterns() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_path = temp_dir.path();
+    fs::write(dir_path.join("template_report.xlsx"), vec![0u8; 10]).unwrap();
+    fs::write(dir_path.join("cache_policy.md"), vec![0u8; 10]).unwrap();
+    fs::write(dir_path.join("setup.tmp"), vec![0u8; 100]).unwrap();
+    fs::write(dir_path.join("~$report.docx"), vec![0u8; 20]).unwrap();
+
+    let mut item = directory_item(dir_path);
+    item.cleanup_type = CleanupType::TempFiles(dir_path.to_path_buf());
+    let scanned = item.scan();
+    assert_eq!(scanned.files, 2);
+    assert_eq!(scanned.size_bytes, 120);
+
+    item.clean();
+    assert!(dir_path.join("template_report.xlsx").exists());
+    assert!(dir_path.join("cache_policy.md").exists());
+    assert!(!dir_path.join("setup.tmp").exists());
+    assert!(!dir_path.join("~$report.docx").exists());
+}
+
+#[test]
+fn test_temp_patterns_overridable_per_item() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_path = temp_dir.path();
+    fs::write(dir_path.join("setup.tmp"), vec![0u8; 100]).unwrap();
+    fs::write(dir_path.join("blob.dat"), vec![0u8; 50]).unwrap();
+
+    let item = CleanupItem {
+        cleanup_type: CleanupType::TempFiles(dir_path.to_path_buf()),
+        options: ItemOptions {
+            temp_patterns: Some(vec![TempPattern::Extension("dat".to_string())]),
+            ..Default::default()
+        },
+        ..directory_item(dir_path)
+    };
+
+    let scanned = item.scan();
+    assert_eq!(scanned.files, 1);
+    assert_eq!(scanned.size_bytes, 50);
+}
+
+/// Tree mixing temp files, temp-named directories, and regular content
+fn build_temp_fixture(root: &Path) {
+    fs::write(root.join("keep.txt"), vec![0u8; 7]).unwrap();
+    fs::write(root.join("a.tmp"), vec![0u8; 10]).unwrap();
+
+    // Matching directory: removed whole, including non-temp names inside
+    let matched = root.join("build.tmp");
+    fs::create_dir_all(matched.join("nested")).unwrap();
+    fs::write(matched.join("data.bin"), vec![0u8; 100]).unwrap();
+    fs::write(matched.join("nested/more.bin"), vec![0u8; 200]).unwrap();
+
+    // Non-matching directory: only matching files inside are removed
+    let project = root.join("project");
+    fs::create_dir_all(project.join("~scratch")).unwrap();
+    fs::write(project.join("report.docx"), vec![0u8; 5]).unwrap();
+    fs::write(project.join("report.docx.bak"), vec![0u8; 30]).unwrap();
+    fs::write(project.join("~scratch/x"), vec![0u8; 40]).unwrap();
+}
+
+#[test]
+fn test_temp_files_scan_counts_matched_directories() {
+    let temp_dir = TempDir::new().unwrap();
+    build_temp_fixture(temp_dir.path());
+
+    let item = CleanupItem {
+        cleanup_type: CleanupType::TempFiles(temp_dir.path().to_path_buf()),
+        ..directory_item(temp_dir.path())
+    };
+    let scanned = item.scan();
+
+    // a.tmp, data.bin, more.bin, report.docx.bak, x
+    assert_eq!(scanned.files, 5);
+    // build.tmp, build.tmp/nested, project/~scratch
+    assert_eq!(scanned.directories, 3);
+    assert_eq!(scanned.size_bytes, 10 + 100 + 200 + 30 + 40);
+}
+
+#[test]
+fn test_temp_files_scan_and_clean_agree() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    build_temp_fixture(root);
+
+    let item = CleanupItem {
+        cleanup_type: CleanupType::TempFiles(root.to_path_buf()),
+        ..directory_item(root)
+    };
+    let scanned = item.scan();
+    let cleaned = item.clean();
+
+    assert_eq!(cleaned.files, scanned.files);
+    assert_eq!(cleaned.directories, scanned.directories);
+    assert_eq!(cleaned.size_bytes, scanned.size_bytes);
+    assert_eq!(cleaned.files_remaining, 0);
+
+    assert!(root.join("keep.txt").exists());
+    assert!(root.join("project/report.docx").exists());
+    assert!(!root.join("build.tmp").exists());
+    assert!(!root.join("project/~scratch").exists());
+    assert!(!root.join("project/report.docx.bak").exists());
+}
+
+#[test]
+fn test_temp_files_clean_reports_only_deleted_bytes() {
+    let fs = Arc::new(
+        MockFs::new()
+            .file("/temp/free.tmp", 100)
+            .file("/temp/keep.txt", 5)
+            .file("/temp/busy/stuck.tmp", 1000)
+            .fail_remove("/temp/busy/stuck.tmp", io::ErrorKind::ResourceBusy),
+    );
+    let item = CleanupItem {
+        cleanup_type: CleanupType::TempFiles(PathBuf::from("/temp")),
+        ..directory_item(Path::new("/temp"))
+    };
+
+    let cleaned = item.clean_with(&CleanOptions::new().fs(fs.clone()));
+
+    assert_eq!(cleaned.files, 1);
+    assert_eq!(cleaned.size_bytes, 100);
+    assert_eq!(cleaned.errors.len(), 1);
+    assert_eq!(cleaned.errors[0].path, Path::new("/temp/busy/stuck.tmp"));
+    assert_eq!(cleaned.errors[0].class(), ErrorClass::Transient);
+    assert!(fs.exists("/temp/keep.txt"));
+    assert!(fs.exists("/temp/busy/stuck.tmp"));
+}
+
+#[test]
+fn test_estimate_samples_two_levels() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::write(root.join("top.bin"), vec![0u8; 100]).unwrap();
+    fs::create_dir_all(root.join("a/b/c")).unwrap();
+    fs::write(root.join("a/mid.bin"), vec![0u8; 100]).unwrap();
+    fs::write(root.join("a/b/deep.bin"), vec![0u8; 100]).unwrap();
+
+    let item = directory_item(root);
+    let estimate = item.estimate();
+    assert!(estimate.is_estimate);
+    assert!(estimate.has_data);
+    assert_eq!(estimate.size_bytes, 0);
+    // top.bin, a/, a/mid.bin, a/b/ -- nothing below the second level
+    assert_eq!(estimate.files, 2);
+    assert_eq!(estimate.directories, 2);
+
+    let scan = item.scan();
+    assert!(!scan.is_estimate);
+    assert_eq!(scan.files, 3);
+}
+
+#[test]
+fn test_estimate_empty_and_temp_items() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    assert!(!directory_item(root).estimate().has_data);
+
+    build_temp_fixture(root);
+    let item = CleanupItem {
+        cleanup_type: CleanupType::TempFiles(root.to_path_buf()),
+        ..directory_item(root)
+    };
+    let estimate = item.estimate();
+    // a.tmp and project/report.docx.bak; build.tmp and project/~scratch count as one entry each
+    assert_eq!(estimate.files, 2);
+    assert_eq!(estimate.directories, 2);
+}
+
+#[test]
+fn test_estimate_ready_parallel_covers_unselected_items() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("file.bin"), b"data").unwrap();
+    let mut items = vec![directory_item(temp_dir.path()), directory_item(temp_dir.path())];
+    items[1].enabled = false;
+
+    let mut results = vec![None; items.len()];
+    estimate_ready_parallel(&items, 2, |i, result| results[i] = Some(result));
+
+    assert!(results.iter().all(|r| r.as_ref().is_some_and(|r| r.is_estimate && r.has_data)));
+}
+
+fn prioritized(id: &str, name: &str, priority: u8) -> CleanupItem {
+    CleanupItem {
+        id: id.to_string(),
+        name: name.to_string(),
+        priority,
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_priority_order_breaks_ties_deterministically() {
+    let items = vec![
+        prioritized("late", "A", 90),
+        prioritized("b_second", "Same", 50),
+        prioritized("a_first", "Same", 50),
+        prioritized("named_b", "B", 50),
+        prioritized("early", "Z", 10),
+    ];
+    let ids: Vec<&str> = priority_order(&items).into_iter().map(|i| items[i].id.as_str()).collect();
+    assert_eq!(ids, ["early", "named_b", "a_first", "b_second", "late"]);
+}
+
+#[test]
+fn test_priority_override_from_options() {
+    let mut items = vec![prioritized("first", "First", 10), prioritized("second", "Second", 20)];
+    items[0].options.priority = Some(30);
+    assert_eq!(items[0].effective_priority(), 30);
+    assert_eq!(priority_order(&items), [1, 0]);
+}
+
+#[test]
+fn test_app_caches_run_before_generic_temp_item() {
+    let items = get_all_cleanup_items();
+    let temp = items.iter().find(|item| item.id == "temp_files").unwrap();
+    // Aggressive items such as browser history also check for running apps but come last
+    for item in items.iter().filter(|item| item.app_check.is_some() && item.risk != RiskLevel::Aggressive) {
+        assert!(item.priority < temp.priority, "{} should run before temp_files", item.id);
+    }
+}
+
+#[test]
+fn test_clean_enabled_follows_priority() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut items: Vec<CleanupItem> = [("c", 30), ("a", 10), ("b", 20)]
+        .into_iter()
+        .map(|(id, priority)| CleanupItem {
+            cleanup_type: CleanupType::Directory(temp_dir.path().join(id)),
+            ..prioritized(id, id, priority)
+        })
+        .collect();
+    items[2].enabled = false;
+
+    let mut cleaned = Vec::new();
+    let report = clean_enabled(&items, &CleanOptions::default(), |i, _| cleaned.push(items[i].id.clone()));
+    assert_eq!(cleaned, ["a", "c"]);
+    let ids: Vec<&str> = report.results().map(|(id, _)| id).collect();
+    assert_eq!(ids, ["a", "c"]);
+    assert_eq!(report.failed(), 0);
+}
+
+#[test]
+fn test_report_lists_the_items_that_failed() {
+    use clean_rs::error::CleanError;
+    use clean_rs::Report;
+    use std::path::PathBuf;
+
+    let report: Report = [
+        ("temp".to_string(), Ok(CleanupResult::new())),
+        ("mnt".to_string(), Err(CleanError::RefusedMountPoint(PathBuf::from("/mnt")))),
+        ("logs".to_string(), Ok(CleanupResult::new())),
+        ("cache".to_string(), Err(CleanError::Cancelled)),
+        ("docs".to_string(), Ok(CleanupResult::new())),
+    ]
+    .into_iter()
+    .collect();
+
+    assert_eq!(report.failed(), 2);
+    assert_eq!(
+        report.to_string(),
+        "2 of 5 items failed\n  mnt: Refusing to clean a volume root or mount point: /mnt\n  cache: Cancelled"
+    );
+    assert_eq!(report.results().count(), 3);
+
+    let Err(CleanError::Multiple(failures)) = report.into_result() else {
+        panic!("expected CleanError::Multiple");
+    };
+    let ids: Vec<&str> = failures.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(ids, ["mnt", "cache"]);
+    let multiple = CleanError::Multiple(failures);
+    assert!(multiple.to_string().starts_with("2 items failed:\n  mnt: "));
+    assert_eq!(multiple.code(), "E_MULTIPLE");
+
+    let fine: Report = [("temp".to_string(), Ok(CleanupResult::new()))].into_iter().collect();
+    assert_eq!(fine.into_result().unwrap().len(), 1);
+}
+
+#[test]
+fn test_min_age_override_from_options() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("fresh.tmp"), b"just written").unwrap();
+
+    let mut item = CleanupItem {
+        min_age_days: 3,
+        ..directory_item(temp_dir.path())
+    };
+    assert!(!item.scan().has_data);
+
+    item.options.min_age_days = Some(0);
+    assert_eq!(item.effective_min_age_days(), 0);
+    assert_eq!(item.clean().files, 1);
+}
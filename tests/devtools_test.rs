@@ -0,0 +1,900 @@
+use clean_rs::browsers::{BaseDirs, Os};
+use clean_rs::devtools::{
+    cargo_home, cargo_items, composer_cache_item, composer_caches, conda_cache_item, condarc_pkgs_dirs, find_conda, find_node_projects, find_python_bytecode, find_rust_projects, go_cache_item, gradle_cache_item, gradle_user_home,
+    homebrew_cache_dir, homebrew_cache_item, homebrew_items, maven_repo_item, nuget_items, NuGetLocals, parse_brew_freed, resolve_go_env, node_modules_item, node_store_items, npm_cache_item, npmrc_cache, pip_cache_dir,
+    pip_cache_item, pnpm_store_dir, python_bytecode_item,
+    resolve_npm_cache, rust_targets_item, yarn_cache_dir, ComposerCacheSource, NpmCacheSource, PNPM_STORE_MIN_AGE_DAYS,
+};
+use clean_rs::{CleanupType, ItemOptions};
+use std::fs::{self, File, FileTimes};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Lay out a `CARGO_HOME` with every cache directory plus installed binaries and config
+fn fake_cargo_home(root: &Path) {
+    let files: [(&str, usize); 6] = [
+        ("registry/cache/index.crates.io-6f17d22bba15001f/serde-1.0.0.crate", 100),
+        ("registry/src/index.crates.io-6f17d22bba15001f/serde-1.0.0/src/lib.rs", 200),
+        ("git/db/tokio-abc/HEAD", 30),
+        ("git/checkouts/tokio-abc/def/Cargo.toml", 40),
+        ("bin/cargo-watch", 500),
+        ("config.toml", 10),
+    ];
+    for (relative, size) in files {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![0u8; size]).unwrap();
+    }
+}
+
+#[test]
+fn test_cargo_home_resolution() {
+    let home = Path::new("/home/user");
+    assert_eq!(cargo_home(None, home), PathBuf::from("/home/user/.cargo"));
+    assert_eq!(cargo_home(Some(PathBuf::new()), home), PathBuf::from("/home/user/.cargo"));
+    assert_eq!(cargo_home(Some(PathBuf::from("/opt/cargo")), home), PathBuf::from("/opt/cargo"));
+}
+
+#[test]
+fn test_cargo_subtargets_are_sized_separately() {
+    let temp_dir = TempDir::new().unwrap();
+    fake_cargo_home(temp_dir.path());
+
+    let items = cargo_items(temp_dir.path());
+    let sizes: Vec<(&str, u64)> = items.iter().map(|item| (item.id.as_str(), item.scan().size_bytes)).collect();
+    assert_eq!(
+        sizes,
+        [("cargo_cache", 100), ("cargo_registry_src", 200), ("cargo_git_db", 30), ("cargo_git_checkouts", 40)]
+    );
+    for item in &items {
+        let CleanupType::Directory(path) = &item.cleanup_type else {
+            panic!("expected a single directory");
+        };
+        assert!(!path.starts_with(temp_dir.path().join("bin")));
+    }
+}
+
+#[test]
+fn test_dropping_tarballs_keeps_sources_and_binaries() {
+    let temp_dir = TempDir::new().unwrap();
+    fake_cargo_home(temp_dir.path());
+
+    let items = cargo_items(temp_dir.path());
+    let tarballs = items.iter().find(|item| item.id == "cargo_cache").unwrap();
+    assert_eq!(tarballs.clean().size_bytes, 100);
+
+    assert!(temp_dir.path().join("registry/cache").is_dir());
+    assert!(temp_dir
+        .path()
+        .join("registry/src/index.crates.io-6f17d22bba15001f/serde-1.0.0/src/lib.rs")
+        .exists());
+    assert!(temp_dir.path().join("bin/cargo-watch").exists());
+    assert!(temp_dir.path().join("config.toml").exists());
+}
+
+/// Create a Cargo project with a built `target/` whose files were all last written `age_days` ago
+fn fake_rust_project(root: &Path, age_days: u64) {
+    let files: [(&str, usize); 3] = [("Cargo.toml", 10), ("src/main.rs", 20), ("target/debug/app", 1000)];
+    let modified = SystemTime::now() - DAY * age_days as u32;
+    for (relative, size) in files {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, vec![0u8; size]).unwrap();
+        File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+    }
+}
+
+#[test]
+fn test_find_rust_projects_needs_manifest_and_target() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fake_rust_project(&root.join("app"), 0);
+    fake_rust_project(&root.join("group/lib"), 0);
+    fake_rust_project(&root.join(".hidden/app"), 0);
+    fake_rust_project(&root.join("app/target/package/app-0.1.0"), 0);
+    fs::create_dir_all(root.join("unbuilt/src")).unwrap();
+    fs::write(root.join("unbuilt/Cargo.toml"), "").unwrap();
+    fs::create_dir_all(root.join("not_rust/target")).unwrap();
+
+    let found: Vec<PathBuf> = find_rust_projects(&[root.to_path_buf()])
+        .into_iter()
+        .map(|project| project.root)
+        .collect();
+    assert_eq!(found, [root.join("app"), root.join("group/lib")]);
+}
+
+#[test]
+fn test_only_idle_projects_lose_their_target() {
+    let temp_dir = TempDir::new().unwrap();
+    fake_rust_project(&temp_dir.path().join("projects/old"), 60);
+    fake_rust_project(&temp_dir.path().join("projects/recent"), 2);
+
+    let item = rust_targets_item(temp_dir.path());
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 1000);
+    let sizes: Vec<u64> = scan.breakdown.iter().map(|entry| entry.size_bytes).collect();
+    assert_eq!(sizes, [1000, 0]);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 1000);
+    let old = temp_dir.path().join("projects/old");
+    assert!(!old.join("target").exists());
+    assert!(old.join("Cargo.toml").exists());
+    assert!(old.join("src/main.rs").exists());
+    assert!(temp_dir.path().join("projects/recent/target/debug/app").exists());
+}
+
+#[test]
+fn test_project_roots_and_idle_days_can_be_overridden() {
+    let temp_dir = TempDir::new().unwrap();
+    fake_rust_project(&temp_dir.path().join("code/app"), 2);
+
+    let mut item = rust_targets_item(temp_dir.path());
+    assert!(!item.scan().has_data);
+
+    item.options = ItemOptions {
+        project_roots: Some(vec![temp_dir.path().join("code")]),
+        min_age_days: Some(1),
+        ..Default::default()
+    };
+    assert_eq!(item.scan().size_bytes, 1000);
+}
+
+/// Create a JavaScript project with installed dependencies, sources last written `age_days` ago
+fn fake_node_project(root: &Path, age_days: u32) {
+    let files: [(&str, usize); 4] = [
+        ("package.json", 10),
+        ("src/index.js", 20),
+        ("node_modules/left-pad/package.json", 300),
+        ("node_modules/left-pad/node_modules/dep/package.json", 200),
+    ];
+    let modified = SystemTime::now() - DAY * age_days;
+    for (relative, size) in files {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, vec![0u8; size]).unwrap();
+        File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+    }
+}
+
+#[test]
+fn test_nested_node_modules_are_counted_once() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fake_node_project(&root.join("web"), 0);
+    fake_node_project(&root.join("monorepo/packages/ui"), 0);
+    fs::create_dir_all(root.join("not_installed")).unwrap();
+    fs::write(root.join("not_installed/package.json"), "{}").unwrap();
+
+    let found: Vec<PathBuf> = find_node_projects(&[root.to_path_buf()])
+        .into_iter()
+        .map(|project| project.root)
+        .collect();
+    assert_eq!(found, [root.join("monorepo/packages/ui"), root.join("web")]);
+
+    let mut item = node_modules_item(root);
+    item.options.project_roots = Some(vec![root.to_path_buf()]);
+    item.options.min_age_days = Some(0);
+    assert_eq!(item.scan().size_bytes, 1000);
+}
+
+#[test]
+fn test_idle_node_modules_are_removed_and_sources_kept() {
+    let temp_dir = TempDir::new().unwrap();
+    fake_node_project(&temp_dir.path().join("projects/old"), 90);
+    fake_node_project(&temp_dir.path().join("projects/recent"), 10);
+
+    let item = node_modules_item(temp_dir.path());
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 500);
+    let notes: Vec<&str> = scan.breakdown.iter().map(|entry| entry.note.as_str()).collect();
+    assert_eq!(notes, ["90 天前修改", "10 天前修改, 跳过"]);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 500);
+    let old = temp_dir.path().join("projects/old");
+    assert!(!old.join("node_modules").exists());
+    assert!(old.join("package.json").exists());
+    assert!(old.join("src/index.js").exists());
+    assert!(temp_dir.path().join("projects/recent/node_modules").exists());
+}
+
+#[test]
+fn test_excluded_projects_are_listed_but_kept() {
+    let temp_dir = TempDir::new().unwrap();
+    let keep = temp_dir.path().join("projects/keep");
+    fake_node_project(&keep, 90);
+    fake_node_project(&temp_dir.path().join("projects/drop"), 90);
+
+    let mut item = node_modules_item(temp_dir.path());
+    assert!(item.toggle_excluded(&keep));
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 500);
+    let excluded = scan.breakdown.iter().find(|entry| entry.label == keep.display().to_string()).unwrap();
+    assert_eq!(excluded.size_bytes, 500);
+    assert!(excluded.note.ends_with("已排除"));
+
+    item.clean();
+    assert!(keep.join("node_modules").exists());
+    assert!(!temp_dir.path().join("projects/drop/node_modules").exists());
+
+    assert!(!item.toggle_excluded(&keep));
+    assert_eq!(item.options.excluded_paths, None);
+}
+
+#[test]
+fn test_npmrc_cache_entry() {
+    let home = Path::new("/home/user");
+    assert_eq!(npmrc_cache("registry=https://example.com\n", home), None);
+    assert_eq!(
+        npmrc_cache("# cache=/commented\ncache = \"/data/npm\"\n", home),
+        Some(PathBuf::from("/data/npm"))
+    );
+    assert_eq!(
+        npmrc_cache("cache=/first\ncache=~/.cache/npm\n", home),
+        Some(PathBuf::from("/home/user/.cache/npm"))
+    );
+}
+
+#[test]
+fn test_npm_cache_resolution_order() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = BaseDirs {
+        home: temp_dir.path().join("home"),
+        data_local: temp_dir.path().join("local"),
+        ..Default::default()
+    };
+    let npmrc = temp_dir.path().join(".npmrc");
+
+    let cache = resolve_npm_cache(Os::Linux, &base, &npmrc, || None);
+    assert_eq!(cache.dir, base.home.join(".npm"));
+    assert_eq!(cache.source, NpmCacheSource::Default);
+    let cache = resolve_npm_cache(Os::Windows, &base, &npmrc, || None);
+    assert_eq!(cache.dir, base.data_local.join("npm-cache"));
+
+    let cache = resolve_npm_cache(Os::Linux, &base, &npmrc, || Some(PathBuf::from("/from/npm")));
+    assert_eq!(cache.dir, PathBuf::from("/from/npm"));
+    assert_eq!(cache.source, NpmCacheSource::NpmConfig);
+
+    fs::write(&npmrc, "cache=/from/npmrc\n").unwrap();
+    let cache = resolve_npm_cache(Os::Linux, &base, &npmrc, || panic!("npm should not run"));
+    assert_eq!(cache.dir, PathBuf::from("/from/npmrc"));
+    assert_eq!(cache.source, NpmCacheSource::Npmrc(npmrc.clone()));
+    assert!(npm_cache_item(&cache).description.contains(".npmrc"));
+}
+
+#[test]
+fn test_npm_cache_item_keeps_npx() {
+    let temp_dir = TempDir::new().unwrap();
+    let files: [(&str, usize); 3] = [("_cacache/index-v5/ab/cd", 100), ("_logs/debug.log", 20), ("_npx/1234/package.json", 5)];
+    for (relative, size) in files {
+        let path = temp_dir.path().join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![0u8; size]).unwrap();
+    }
+    let base = BaseDirs {
+        home: temp_dir.path().to_path_buf(),
+        ..Default::default()
+    };
+    let npmrc = temp_dir.path().join("missing.npmrc");
+    let cache = resolve_npm_cache(Os::Linux, &base, &npmrc, || Some(temp_dir.path().to_path_buf()));
+
+    let result = npm_cache_item(&cache).clean();
+    assert_eq!(result.size_bytes, 120);
+    assert!(temp_dir.path().join("_npx/1234/package.json").exists());
+}
+
+fn write_files(root: &Path, files: &[(&str, usize)]) {
+    for (relative, size) in files {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![0u8; *size]).unwrap();
+    }
+}
+
+fn linux_base(root: &Path) -> BaseDirs {
+    BaseDirs {
+        home: root.to_path_buf(),
+        cache: root.join(".cache"),
+        data: root.join(".local/share"),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_node_stores_are_listed_only_when_present() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = linux_base(temp_dir.path());
+    assert!(node_store_items(Os::Linux, &base, None).is_empty());
+
+    fs::create_dir_all(yarn_cache_dir(Os::Linux, &base)).unwrap();
+    fs::create_dir_all(pnpm_store_dir(Os::Linux, &base)).unwrap();
+    let items = node_store_items(Os::Linux, &base, None);
+    let ids: Vec<&str> = items.iter().map(|item| item.id.as_str()).collect();
+    assert_eq!(ids, ["yarn_cache", "pnpm_store"]);
+    assert!(items.iter().all(|item| !item.enabled));
+    // Without pnpm only old files may go
+    assert_eq!(items[1].min_age_days, PNPM_STORE_MIN_AGE_DAYS);
+}
+
+#[test]
+fn test_store_scan_is_broken_down_by_version() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = linux_base(temp_dir.path());
+    write_files(
+        &yarn_cache_dir(Os::Linux, &base),
+        &[("v6/npm-left-pad-1.3.0/package.json", 100), ("v4/npm-left-pad-1.1.0/package.json", 30)],
+    );
+
+    let items = node_store_items(Os::Linux, &base, None);
+    let scan = items[0].scan();
+    assert_eq!(scan.size_bytes, 130);
+    let mut breakdown: Vec<(&str, u64)> = scan.breakdown.iter().map(|entry| (entry.label.as_str(), entry.size_bytes)).collect();
+    breakdown.sort();
+    assert_eq!(breakdown, [("v4", 30), ("v6", 100)]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pnpm_store_is_pruned_by_pnpm() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let base = linux_base(temp_dir.path());
+    let store = pnpm_store_dir(Os::Linux, &base);
+    write_files(&store, &[("v3/files/00/unused", 100), ("v3/files/01/used", 50)]);
+
+    // Stand-in for pnpm that removes the unreferenced package
+    let pnpm = temp_dir.path().join("pnpm");
+    fs::write(&pnpm, format!("#!/bin/sh\n[ \"$*\" = \"store prune\" ] && rm -r '{}'\n", store.join("v3/files/00").display())).unwrap();
+    fs::set_permissions(&pnpm, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let items = node_store_items(Os::Linux, &base, Some(pnpm));
+    assert_eq!(items[0].min_age_days, 0);
+    let result = items[0].clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 100);
+    assert!(store.join("v3/files/01/used").exists());
+}
+
+#[test]
+fn test_pip_cache_dir_resolution() {
+    let base = BaseDirs {
+        cache: PathBuf::from("/home/user/.cache"),
+        data_local: PathBuf::from("C:/Users/user/AppData/Local"),
+        ..Default::default()
+    };
+    assert_eq!(pip_cache_dir(None, Os::Linux, &base), PathBuf::from("/home/user/.cache/pip"));
+    assert_eq!(pip_cache_dir(None, Os::Windows, &base), PathBuf::from("C:/Users/user/AppData/Local/pip/Cache"));
+    assert_eq!(pip_cache_dir(Some(PathBuf::from("/tmp/pip")), Os::Linux, &base), PathBuf::from("/tmp/pip"));
+}
+
+#[test]
+fn test_pip_cache_keeps_directory_layout() {
+    let temp_dir = TempDir::new().unwrap();
+    write_files(
+        temp_dir.path(),
+        &[("http-v2/a/b/c/0123", 300), ("wheels/ab/cd/numpy-2.0-cp312.whl", 700), ("selfcheck/state.json", 10)],
+    );
+
+    let item = pip_cache_item(temp_dir.path());
+    let scan = item.scan();
+    let breakdown: Vec<u64> = scan.breakdown.iter().map(|entry| entry.size_bytes).collect();
+    assert_eq!(breakdown, [300, 700]);
+    assert_eq!(scan.directories, 0);
+
+    let result = item.clean();
+    assert_eq!(result.size_bytes, 1000);
+    assert!(temp_dir.path().join("http-v2/a/b/c").is_dir());
+    assert!(temp_dir.path().join("wheels/ab/cd").is_dir());
+    assert!(!temp_dir.path().join("wheels/ab/cd/numpy-2.0-cp312.whl").exists());
+    assert!(temp_dir.path().join("selfcheck/state.json").exists());
+}
+
+#[test]
+fn test_go_env_resolution() {
+    let base = BaseDirs {
+        home: PathBuf::from("/home/user"),
+        cache: PathBuf::from("/home/user/.cache"),
+        ..Default::default()
+    };
+
+    let go = resolve_go_env(None, None, Os::Linux, &base, || None);
+    assert_eq!(go.gopath, PathBuf::from("/home/user/go"));
+    assert_eq!(go.gocache, Some(PathBuf::from("/home/user/.cache/go-build")));
+
+    let go = resolve_go_env(Some("/opt/go:/srv/go".into()), None, Os::Linux, &base, || {
+        Some(("/ignored".to_string(), "/from/go/env".to_string()))
+    });
+    assert_eq!(go.gopath, PathBuf::from("/opt/go"));
+    assert_eq!(go.gocache, Some(PathBuf::from("/from/go/env")));
+
+    let go = resolve_go_env(Some("/opt/go".into()), Some("off".into()), Os::Linux, &base, || {
+        panic!("go should not run")
+    });
+    assert_eq!(go.gocache, None);
+    assert_eq!(go.download_cache(), PathBuf::from("/opt/go/pkg/mod/cache/download"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_read_only_module_cache_is_deleted() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let base = BaseDirs {
+        home: temp_dir.path().to_path_buf(),
+        ..Default::default()
+    };
+    let go = resolve_go_env(None, Some("off".into()), Os::Linux, &base, || None);
+    let module = go.download_cache().join("golang.org/x/text/@v");
+    write_files(&module, &[("v0.3.0.zip", 400), ("v0.3.0.mod", 20)]);
+    // Go writes the module cache read-only
+    for path in [module.join("v0.3.0.zip"), module.join("v0.3.0.mod"), module.clone()] {
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o555)).unwrap();
+    }
+
+    let result = go_cache_item(&go).clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 420);
+    assert!(!module.exists());
+}
+
+#[test]
+fn test_gradle_user_home_resolution() {
+    let home = Path::new("/home/user");
+    assert_eq!(gradle_user_home(None, home), PathBuf::from("/home/user/.gradle"));
+    assert_eq!(gradle_user_home(Some(PathBuf::from("/opt/gradle")), home), PathBuf::from("/opt/gradle"));
+}
+
+/// Set both timestamps of `path` to `days` ago, then mark it read `read_days` ago
+fn age_file(path: &Path, days: u32, read_days: u32) {
+    let now = SystemTime::now();
+    let times = FileTimes::new().set_modified(now - DAY * days).set_accessed(now - DAY * read_days);
+    File::options().write(true).open(path).unwrap().set_times(times).unwrap();
+}
+
+#[test]
+fn test_gradle_keeps_recently_used_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    write_files(
+        root,
+        &[
+            ("caches/modules-2/files-2.1/old.jar", 100),
+            ("caches/modules-2/files-2.1/in-use.jar", 200),
+            ("wrapper/dists/gradle-7.0-bin/gradle-7.0-bin.zip", 400),
+            ("caches/8.5/kotlin-dsl/cache.bin", 800),
+        ],
+    );
+    age_file(&root.join("caches/modules-2/files-2.1/old.jar"), 90, 90);
+    // Downloaded long ago but read by yesterday's build
+    age_file(&root.join("caches/modules-2/files-2.1/in-use.jar"), 90, 1);
+    age_file(&root.join("wrapper/dists/gradle-7.0-bin/gradle-7.0-bin.zip"), 400, 400);
+
+    let item = gradle_cache_item(root);
+    let scan = item.scan();
+    let breakdown: Vec<u64> = scan.breakdown.iter().map(|entry| entry.size_bytes).collect();
+    assert_eq!(breakdown, [100, 400]);
+
+    let result = item.clean();
+    assert_eq!(result.size_bytes, 500);
+    assert!(root.join("caches/modules-2/files-2.1/in-use.jar").exists());
+    assert!(root.join("caches/8.5/kotlin-dsl/cache.bin").exists());
+}
+
+#[test]
+fn test_gradle_is_skipped_while_a_daemon_runs() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    write_files(root, &[("daemon/8.5/daemon-4000000000.out.log", 10)]);
+    assert!(!gradle_cache_item(root).app_check.unwrap().is_running());
+
+    // This test process stands in for the daemon
+    write_files(root, &[(&format!("daemon/8.5/daemon-{}.out.log", std::process::id()), 10)]);
+    let result = gradle_cache_item(root).clean();
+    assert!(result.skip_reason.is_some());
+    assert!(root.join("daemon/8.5").is_dir());
+}
+
+/// A repository with one release and one snapshot version built three times
+fn fake_maven_repo(root: &Path) -> PathBuf {
+    let snapshot = root.join("org/example/lib/1.0-SNAPSHOT");
+    let mut files: Vec<(String, usize)> = Vec::new();
+    for (build, size) in [("20240101.100000-1", 100), ("20240102.100000-2", 200), ("20240103.100000-3", 400)] {
+        files.push((format!("org/example/lib/1.0-SNAPSHOT/lib-1.0-{}.jar", build), size));
+        files.push((format!("org/example/lib/1.0-SNAPSHOT/lib-1.0-{}.pom", build), 10));
+        files.push((format!("org/example/lib/1.0-SNAPSHOT/lib-1.0-{}-sources.jar", build), 50));
+    }
+    files.push(("org/example/lib/1.0-SNAPSHOT/lib-1.0-SNAPSHOT.jar".to_string(), 400));
+    files.push(("org/example/lib/1.0-SNAPSHOT/maven-metadata-remote.xml".to_string(), 5));
+    files.push(("org/example/lib/0.9/lib-0.9.jar".to_string(), 300));
+    files.push(("org/example/lib/0.9/lib-0.9.pom".to_string(), 10));
+    let files: Vec<(&str, usize)> = files.iter().map(|(path, size)| (path.as_str(), *size)).collect();
+    write_files(root, &files);
+    snapshot
+}
+
+#[test]
+fn test_maven_keeps_only_the_newest_snapshot_build() {
+    let temp_dir = TempDir::new().unwrap();
+    let snapshot = fake_maven_repo(temp_dir.path());
+
+    let item = maven_repo_item(temp_dir.path());
+    let scan = item.scan();
+    // Builds 1 and 2: two jars, two poms and two source jars
+    assert_eq!(scan.files, 6);
+    assert_eq!(scan.size_bytes, 100 + 200 + 2 * 10 + 2 * 50);
+    assert_eq!(scan.breakdown.len(), 1);
+
+    let result = item.clean();
+    assert_eq!(result.size_bytes, scan.size_bytes);
+    let mut kept: Vec<String> = fs::read_dir(&snapshot)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    kept.sort();
+    assert_eq!(
+        kept,
+        [
+            "lib-1.0-20240103.100000-3-sources.jar",
+            "lib-1.0-20240103.100000-3.jar",
+            "lib-1.0-20240103.100000-3.pom",
+            "lib-1.0-SNAPSHOT.jar",
+            "maven-metadata-remote.xml",
+        ]
+    );
+    assert!(temp_dir.path().join("org/example/lib/0.9/lib-0.9.jar").exists());
+}
+
+#[test]
+fn test_maven_unused_versions_option() {
+    let temp_dir = TempDir::new().unwrap();
+    fake_maven_repo(temp_dir.path());
+    let release = temp_dir.path().join("org/example/lib/0.9");
+    for name in ["lib-0.9.jar", "lib-0.9.pom"] {
+        age_file(&release.join(name), 200, 200);
+    }
+
+    let mut item = maven_repo_item(temp_dir.path());
+    item.options = ItemOptions {
+        unused_days: Some(180),
+        ..Default::default()
+    };
+    assert_eq!(item.scan().size_bytes, 310 + 100 + 200 + 2 * 10 + 2 * 50);
+    item.clean();
+    assert!(!release.exists());
+    assert!(temp_dir.path().join("org/example/lib/1.0-SNAPSHOT/lib-1.0-SNAPSHOT.jar").exists());
+}
+
+#[test]
+fn test_nuget_locals_prefer_dotnet_output() {
+    let base = BaseDirs {
+        home: PathBuf::from("/home/user"),
+        ..Default::default()
+    };
+    let defaults = NuGetLocals::defaults(Os::Linux, &base, Path::new("/tmp"), "user");
+    assert_eq!(defaults.http_cache, PathBuf::from("/home/user/.local/share/NuGet/v3-cache"));
+    assert_eq!(defaults.temp, PathBuf::from("/tmp/NuGetScratchuser"));
+
+    let locals = defaults.clone().resolve(Some("/data/nuget".into()), None);
+    assert_eq!(locals.global_packages, PathBuf::from("/data/nuget"));
+    assert!(!locals.from_dotnet);
+
+    let output = "info : http-cache: /home/user/.local/share/NuGet/http-cache\n\
+                  info : global-packages: /home/user/.nuget/packages/\n\
+                  info : temp: /tmp/NuGetScratchuser\n\
+                  info : plugins-cache: /home/user/.local/share/NuGet/plugin-cache\n";
+    let locals = defaults.resolve(Some("/data/nuget".into()), Some(output));
+    assert!(locals.from_dotnet);
+    assert_eq!(locals.http_cache, PathBuf::from("/home/user/.local/share/NuGet/http-cache"));
+    assert_eq!(locals.global_packages, PathBuf::from("/home/user/.nuget/packages/"));
+    assert_eq!(locals.plugins_cache, PathBuf::from("/home/user/.local/share/NuGet/plugin-cache"));
+}
+
+#[test]
+fn test_nuget_packages_are_deleted_per_unused_version() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = BaseDirs {
+        home: temp_dir.path().to_path_buf(),
+        ..Default::default()
+    };
+    let locals = NuGetLocals::defaults(Os::Linux, &base, temp_dir.path(), "user");
+    let packages = &locals.global_packages;
+    write_files(
+        packages,
+        &[
+            ("newtonsoft.json/12.0.1/lib/net45/Newtonsoft.Json.dll", 500),
+            ("newtonsoft.json/12.0.1/.nupkg.metadata", 10),
+            ("newtonsoft.json/13.0.3/lib/net45/Newtonsoft.Json.dll", 600),
+            ("newtonsoft.json/13.0.3/.nupkg.metadata", 10),
+        ],
+    );
+    for file in ["lib/net45/Newtonsoft.Json.dll", ".nupkg.metadata"] {
+        age_file(&packages.join("newtonsoft.json/12.0.1").join(file), 90, 90);
+        age_file(&packages.join("newtonsoft.json/13.0.3").join(file), 90, 90);
+    }
+    // Read by a recent build
+    age_file(&packages.join("newtonsoft.json/13.0.3/lib/net45/Newtonsoft.Json.dll"), 90, 2);
+
+    let items = nuget_items(&locals);
+    let item = items.iter().find(|item| item.id == "nuget_packages").unwrap();
+    assert_eq!(item.scan().size_bytes, 510);
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert!(!packages.join("newtonsoft.json/12.0.1").exists());
+    assert!(packages.join("newtonsoft.json/13.0.3/.nupkg.metadata").exists());
+}
+
+/// A miniconda install whose base environment uses `python-3.11.5-h0_0` and whose `ml`
+/// environment uses `numpy-1.26.0-py311_0`; `numpy-1.24.0-py311_0` and `requests-2.31.0-py_0`
+/// are only in the cache
+fn fake_conda(home: &Path) -> PathBuf {
+    let root = home.join("miniconda3");
+    let files: [(&str, usize); 10] = [
+        ("conda-meta/python-3.11.5-h0_0.json", 10),
+        ("envs/ml/conda-meta/numpy-1.26.0-py311_0.json", 10),
+        ("pkgs/python-3.11.5-h0_0.conda", 300),
+        ("pkgs/python-3.11.5-h0_0/info/index.json", 30),
+        ("pkgs/numpy-1.26.0-py311_0.tar.bz2", 200),
+        ("pkgs/numpy-1.26.0-py311_0/info/index.json", 20),
+        ("pkgs/numpy-1.24.0-py311_0.tar.bz2", 100),
+        ("pkgs/numpy-1.24.0-py311_0/info/index.json", 40),
+        ("pkgs/requests-2.31.0-py_0.conda", 50),
+        ("pkgs/cache/repodata.json", 1000),
+    ];
+    write_files(&root, &files);
+    root
+}
+
+#[test]
+fn test_condarc_pkgs_dirs() {
+    let home = Path::new("/home/user");
+    let block = "channels:\n  - defaults\npkgs_dirs:\n  - ~/conda-pkgs  # moved\n  - \"/data/pkgs\"\nauto_activate_base: false\n";
+    assert_eq!(
+        condarc_pkgs_dirs(block, home),
+        [PathBuf::from("/home/user/conda-pkgs"), PathBuf::from("/data/pkgs")]
+    );
+    assert_eq!(condarc_pkgs_dirs("pkgs_dirs: [/a, ~/b]\n", home), [PathBuf::from("/a"), PathBuf::from("/home/user/b")]);
+    assert!(condarc_pkgs_dirs("channels: [defaults]\n", home).is_empty());
+}
+
+#[test]
+fn test_conda_referenced_packages_are_never_removed() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = fake_conda(temp_dir.path());
+
+    let conda = find_conda(temp_dir.path(), None, "");
+    assert_eq!(conda.pkgs_dirs, [root.join("pkgs")]);
+    let names: Vec<String> = conda.unused_packages().into_iter().map(|package| package.name).collect();
+    assert_eq!(names, ["numpy-1.24.0-py311_0", "requests-2.31.0-py_0"]);
+
+    let item = conda_cache_item(&conda);
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 190);
+    assert_eq!(scan.breakdown.len(), 2);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 190);
+    for kept in [
+        "python-3.11.5-h0_0.conda",
+        "python-3.11.5-h0_0/info/index.json",
+        "numpy-1.26.0-py311_0.tar.bz2",
+        "numpy-1.26.0-py311_0/info/index.json",
+        "cache/repodata.json",
+    ] {
+        assert!(root.join("pkgs").join(kept).exists(), "{} was removed", kept);
+    }
+    assert!(!root.join("pkgs/numpy-1.24.0-py311_0").exists());
+    assert!(!root.join("pkgs/requests-2.31.0-py_0.conda").exists());
+}
+
+#[test]
+fn test_conda_prefix_envs_and_listed_envs_count_as_users() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let root = home.join("opt/conda");
+    write_files(&root, &[("conda-meta/python-3.11.5-h0_0.json", 10), ("pkgs/requests-2.31.0-py_0.conda", 50)]);
+    // An environment created with `--prefix` somewhere else
+    let project_env = home.join("work/.env");
+    write_files(&project_env, &[("conda-meta/requests-2.31.0-py_0.json", 10)]);
+
+    let conda = find_conda(home, Some(root.join("envs/dev")), "");
+    assert_eq!(conda.pkgs_dirs, [root.join("pkgs")]);
+    assert_eq!(conda.unused_packages().len(), 1);
+
+    fs::create_dir_all(home.join(".conda")).unwrap();
+    fs::write(home.join(".conda/environments.txt"), format!("{}\n", project_env.display())).unwrap();
+    let conda = find_conda(home, Some(root.join("envs/dev")), "");
+    assert!(conda.unused_packages().is_empty());
+}
+
+#[test]
+fn test_conda_without_environments_reports_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = fake_conda(temp_dir.path());
+    fs::remove_dir_all(root.join("conda-meta")).unwrap();
+    fs::remove_dir_all(root.join("envs")).unwrap();
+
+    let conda = find_conda(temp_dir.path(), None, "");
+    assert!(conda.unused_packages().is_empty());
+    assert!(!conda_cache_item(&conda).scan().has_data);
+}
+
+/// A project with bytecode in the sources, a virtualenv and decoys that only look similar
+fn fake_python_project(root: &Path) {
+    write_files(
+        root,
+        &[
+            ("app/main.py", 10),
+            ("app/__pycache__/main.cpython-311.pyc", 100),
+            ("app/legacy.pyc", 50),
+            ("app/kept.py", 10),
+            ("app/kept.pyc", 40),
+            ("app/my__pycache__notes.txt", 5),
+            ("app/my__pycache__/notes.pyc.txt", 5),
+            (".venv/pyvenv.cfg", 5),
+            (".venv/lib/python3.11/site-packages/six/__pycache__/six.cpython-311.pyc", 200),
+            ("vendor/site-packages/requests/__pycache__/api.cpython-311.pyc", 300),
+        ],
+    );
+}
+
+#[test]
+fn test_python_bytecode_matches_exact_names_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fake_python_project(root);
+
+    assert_eq!(find_python_bytecode(root, false), [root.join("app/__pycache__"), root.join("app/legacy.pyc")]);
+    assert_eq!(find_python_bytecode(root, true).len(), 4);
+}
+
+#[test]
+fn test_python_bytecode_clean_keeps_envs_and_sources() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("projects");
+    fake_python_project(&root);
+
+    let item = python_bytecode_item(temp_dir.path());
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 150);
+    assert_eq!(scan.breakdown.len(), 1);
+    assert_eq!(scan.breakdown[0].note, "1 个 __pycache__ 目录, 1 个 .pyc 文件");
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 150);
+    assert!(!root.join("app/__pycache__").exists());
+    assert!(!root.join("app/legacy.pyc").exists());
+    for kept in ["app/main.py", "app/kept.pyc", "app/my__pycache__notes.txt", "app/my__pycache__/notes.pyc.txt"] {
+        assert!(root.join(kept).exists(), "{} was removed", kept);
+    }
+    assert!(root.join(".venv/lib/python3.11/site-packages/six/__pycache__").exists());
+    assert!(root.join("vendor/site-packages/requests/__pycache__").exists());
+}
+
+#[test]
+fn test_composer_cache_locations() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = linux_base(temp_dir.path());
+
+    // Nothing installed: the location current Composer uses
+    let sources: Vec<ComposerCacheSource> = composer_caches(None, Os::Linux, &base).iter().map(|c| c.source).collect();
+    assert_eq!(sources, [ComposerCacheSource::XdgCache]);
+
+    fs::create_dir_all(base.home.join(".composer/cache")).unwrap();
+    let caches = composer_caches(None, Os::Linux, &base);
+    assert_eq!(caches.len(), 1);
+    assert_eq!(caches[0].source, ComposerCacheSource::LegacyHome);
+    assert!(composer_cache_item(&caches).description.contains("~/.composer/cache"));
+
+    let custom = composer_caches(Some(PathBuf::from("/data/composer")), Os::Linux, &base);
+    assert_eq!(custom[0].dir, PathBuf::from("/data/composer"));
+    assert_eq!(custom[0].source, ComposerCacheSource::EnvVar);
+}
+
+#[test]
+fn test_composer_clean_keeps_auth_and_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = linux_base(temp_dir.path());
+    let composer_home = base.home.join(".composer");
+    write_files(
+        &composer_home,
+        &[
+            ("auth.json", 10),
+            ("config.json", 10),
+            ("cache/files/monolog/monolog/abc.zip", 300),
+            ("cache/repo/https---repo.packagist.org/provider.json", 100),
+        ],
+    );
+    write_files(&base.cache.join("composer"), &[("files/psr/log/def.zip", 50)]);
+
+    let item = composer_cache_item(&composer_caches(None, Os::Linux, &base));
+    assert_eq!(item.scan().size_bytes, 450);
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 450);
+    assert!(composer_home.join("auth.json").exists());
+    assert!(composer_home.join("config.json").exists());
+}
+
+#[test]
+fn test_homebrew_cache_cleans_downloads_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = linux_base(temp_dir.path());
+    let cache = homebrew_cache_dir(None, &base);
+    assert_eq!(cache, temp_dir.path().join(".cache/Homebrew"));
+    assert_eq!(homebrew_cache_dir(Some(PathBuf::from("/opt/brew-cache")), &base), PathBuf::from("/opt/brew-cache"));
+
+    let items = homebrew_items(&cache, None);
+    assert_eq!(items.len(), 1);
+    assert!(!items[0].enabled);
+
+    let downloads = cache.join("downloads");
+    fs::create_dir_all(&downloads).unwrap();
+    fs::create_dir_all(cache.join("api")).unwrap();
+    fs::write(downloads.join("1f2e--wget--1.24.5.arm64_sonoma.bottle.tar.gz"), vec![0u8; 300]).unwrap();
+    fs::write(downloads.join("9a8b--jq--1.7.1.tar.gz.incomplete"), vec![0u8; 50]).unwrap();
+    fs::write(cache.join("api/formula.jws.json"), vec![0u8; 1000]).unwrap();
+
+    let item = homebrew_cache_item(&cache);
+    assert!(item.enabled);
+    assert_eq!(item.scan().size_bytes, 350);
+    item.clean();
+    assert!(downloads.is_dir());
+    assert_eq!(fs::read_dir(&downloads).unwrap().count(), 0);
+    assert!(cache.join("api/formula.jws.json").exists());
+}
+
+#[test]
+fn test_parse_brew_freed() {
+    let dry_run = "Would remove: /opt/homebrew/Cellar/wget/1.21.4 (91 files, 4.4MB)\n\
+                   ==> This operation would free approximately 1.5GB of disk space.\n";
+    assert_eq!(parse_brew_freed(dry_run), Some(1536 * 1024 * 1024));
+    assert_eq!(parse_brew_freed("==> This operation has freed approximately 512KB of disk space.\n"), Some(512 * 1024));
+    assert_eq!(parse_brew_freed(""), None);
+}
+
+/// Stand-in for brew printing a dry-run summary, or recording a real cleanup in `cleaned`
+#[cfg(unix)]
+fn fake_brew(dir: &Path) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let program = dir.join("brew");
+    let script = format!(
+        "#!/bin/sh\n\
+         case \"$3\" in\n\
+         --dry-run) echo '==> This operation would free approximately 20MB of disk space.' ;;\n\
+         *) echo \"$*\" > '{}'; echo '==> This operation has freed approximately 19.5MB of disk space.' ;;\n\
+         esac\n",
+        dir.join("cleaned").display()
+    );
+    fs::write(&program, script).unwrap();
+    fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+    program
+}
+
+#[cfg(unix)]
+#[test]
+fn test_brew_cleanup_is_opt_in_and_reports_freed_space() {
+    let temp_dir = TempDir::new().unwrap();
+    let items = homebrew_items(&temp_dir.path().join("cache"), Some(fake_brew(temp_dir.path())));
+    assert_eq!(items.len(), 2);
+    let prune = &items[1];
+    assert_eq!(prune.id, "homebrew_cleanup");
+    assert!(!prune.enabled);
+
+    assert_eq!(prune.scan().size_bytes, 20 * 1024 * 1024);
+    assert!(!temp_dir.path().join("cleaned").exists());
+    let result = prune.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, (19.5 * 1024.0 * 1024.0) as u64);
+    assert_eq!(fs::read_to_string(temp_dir.path().join("cleaned")).unwrap().trim(), "cleanup --prune=all");
+}
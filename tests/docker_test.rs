@@ -0,0 +1,115 @@
+use clean_rs::docker::{docker_items, parse_disk_usage, parse_reclaimed, parse_size, DockerTarget};
+use clean_rs::{CleanTarget, ItemStatus, RiskLevel};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const DF_OUTPUT: &str = r#"{"Active":"2","Reclaimable":"1.2GB (52%)","Size":"2.3GB","TotalCount":"5","Type":"Images"}
+{"Active":"1","Reclaimable":"0B (0%)","Size":"12kB","TotalCount":"1","Type":"Containers"}
+{"Active":"0","Reclaimable":"350MB","Size":"350MB","TotalCount":"3","Type":"Local Volumes"}
+{"Active":"0","Reclaimable":"1.5kB","Size":"1.5kB","TotalCount":"7","Type":"Build Cache"}
+"#;
+
+#[test]
+fn test_parse_size_uses_decimal_units() {
+    assert_eq!(parse_size("0B"), Some(0));
+    assert_eq!(parse_size("512B"), Some(512));
+    assert_eq!(parse_size("1.5kB"), Some(1_500));
+    assert_eq!(parse_size("2.3GB"), Some(2_300_000_000));
+    assert_eq!(parse_size("1TB"), Some(1_000_000_000_000));
+    assert_eq!(parse_size("12 parsecs"), None);
+    assert_eq!(parse_size(""), None);
+}
+
+#[test]
+fn test_parse_disk_usage_and_reclaimed() {
+    let usage = parse_disk_usage(&format!("WARNING: not json\n{}", DF_OUTPUT));
+    assert_eq!(usage.len(), 4);
+    assert_eq!(usage[0].kind, "Images");
+    assert_eq!(usage[0].reclaimable_bytes(), Some(1_200_000_000));
+    assert_eq!(usage[2].reclaimable_bytes(), Some(350_000_000));
+
+    let prune = "Deleted Images:\nuntagged: alpine:3.18\n\nTotal reclaimed space: 1.234GB\n";
+    assert_eq!(parse_reclaimed(prune), Some(1_234_000_000));
+    assert_eq!(parse_reclaimed("nothing here"), None);
+}
+
+#[test]
+fn test_docker_items_split_volumes_into_aggressive_item() {
+    let items = docker_items(Some(PathBuf::from("/nonexistent/docker")));
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].id, "docker");
+    assert_eq!(items[0].risk, RiskLevel::Moderate);
+    assert_eq!(items[1].id, "docker_volumes");
+    assert_eq!(items[1].risk, RiskLevel::Aggressive);
+    assert_eq!(items[0].validate(), ItemStatus::PathMissing);
+}
+
+/// Stand-in for docker: `info` fails unless `daemon` is set, `df` prints `DF_OUTPUT`,
+/// and prune records its arguments in `pruned`
+#[cfg(unix)]
+fn fake_docker(dir: &Path, daemon: bool) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let df = dir.join("df.json");
+    fs::write(&df, DF_OUTPUT).unwrap();
+    let program = dir.join("docker");
+    let script = format!(
+        "#!/bin/sh\n\
+         case \"$1 $2\" in\n\
+         \"info \"*) exit {} ;;\n\
+         \"system df\") cat '{}' ;;\n\
+         *\" prune\") echo \"$*\" > '{}'; echo 'Total reclaimed space: 1.2GB' ;;\n\
+         esac\n",
+        if daemon { 0 } else { 1 },
+        df.display(),
+        dir.join("pruned").display()
+    );
+    fs::write(&program, script).unwrap();
+    fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+    program
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unreachable_daemon_marks_item_unavailable() {
+    let temp_dir = TempDir::new().unwrap();
+    let target = DockerTarget::new(fake_docker(temp_dir.path(), false), false);
+    assert_eq!(target.validate(), ItemStatus::ServiceUnavailable);
+
+    let target = DockerTarget::new(fake_docker(temp_dir.path(), true), false);
+    assert_eq!(target.validate(), ItemStatus::Ready);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_scan_reports_reclaimable_space_without_pruning() {
+    let temp_dir = TempDir::new().unwrap();
+    let program = fake_docker(temp_dir.path(), true);
+
+    let result = DockerTarget::new(program.clone(), false).scan();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 1_200_001_500);
+    let labels: Vec<_> = result.breakdown.iter().map(|entry| entry.label.as_str()).collect();
+    assert_eq!(labels, ["Images", "Build Cache"]);
+
+    let volumes = DockerTarget::new(program, true).scan();
+    assert_eq!(volumes.size_bytes, 350_000_000);
+    assert!(!temp_dir.path().join("pruned").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_clean_prunes_and_reports_reclaimed_space() {
+    let temp_dir = TempDir::new().unwrap();
+    let program = fake_docker(temp_dir.path(), true);
+    let pruned = temp_dir.path().join("pruned");
+
+    let result = DockerTarget::new(program.clone(), false).clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 1_200_000_000);
+    assert_eq!(fs::read_to_string(&pruned).unwrap().trim(), "system prune -f");
+
+    DockerTarget::new(program, true).clean();
+    assert_eq!(fs::read_to_string(&pruned).unwrap().trim(), "volume prune -f");
+}
@@ -0,0 +1,122 @@
+use clean_rs::duplicates::{duplicates_item, find_duplicates, same_size_files, DUPLICATE_MIN_SIZE};
+use clean_rs::cleanup_items::NOT_SELECTED_NOTE;
+use clean_rs::{ItemStatus, RiskLevel};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Larger than the two 64 KiB samples together, so a change in the middle is only seen by
+/// the full hash
+const SIZE: usize = 200 * 1024;
+
+/// Content of `size` bytes derived from `seed`
+fn content(seed: u8, size: usize) -> Vec<u8> {
+    (0..size).map(|i| (i % 251) as u8 ^ seed).collect()
+}
+
+fn write(root: &Path, relative: &str, data: &[u8]) -> PathBuf {
+    let path = root.join(relative);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, data).unwrap();
+    path
+}
+
+#[test]
+fn test_duplicates_are_confirmed_by_full_contents() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let original = content(1, SIZE);
+    let mut middle_changed = original.clone();
+    middle_changed[SIZE / 2] ^= 0xff;
+    let mut start_changed = original.clone();
+    start_changed[0] ^= 0xff;
+
+    write(root, "iso/ubuntu.iso", &original);
+    write(root, "Downloads/ubuntu.iso", &original);
+    write(root, "Downloads/ubuntu (1).iso", &original);
+    write(root, "Downloads/middle.bin", &middle_changed);
+    write(root, "Downloads/start.bin", &start_changed);
+    write(root, ".hidden/ubuntu.iso", &original);
+    write(root, "small/a.txt", b"same");
+    write(root, "small/b.txt", b"same");
+
+    let groups = find_duplicates(&[root.to_path_buf()], &[], 1024);
+    assert_eq!(groups.len(), 1);
+    let group = &groups[0];
+    assert_eq!(
+        group.paths,
+        [root.join("Downloads/ubuntu (1).iso"), root.join("Downloads/ubuntu.iso"), root.join("iso/ubuntu.iso")]
+    );
+    assert_eq!(group.kept(), root.join("Downloads/ubuntu (1).iso"));
+    assert_eq!(group.reclaimable(), 2 * SIZE as u64);
+
+    // Size alone cannot tell the changed copies apart
+    let same_size = same_size_files(&[root.to_path_buf()], &[], 1024);
+    assert_eq!(same_size.len(), 1);
+    assert_eq!(same_size[0].1.len(), 5);
+
+    let excluded = find_duplicates(&[root.to_path_buf()], &[root.join("iso")], 1024);
+    assert_eq!(excluded[0].paths.len(), 2);
+}
+
+#[test]
+fn test_hard_links_are_not_duplicates() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let original = write(root, "a/video.mp4", &content(2, SIZE));
+    fs::create_dir(root.join("b")).unwrap();
+    fs::hard_link(&original, root.join("b/video.mp4")).unwrap();
+    assert!(find_duplicates(&[root.to_path_buf()], &[], 1024).is_empty());
+
+    write(root, "c/video.mp4", &content(2, SIZE));
+    let groups = find_duplicates(&[root.to_path_buf()], &[], 1024);
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].paths.len(), 2);
+    assert_eq!(groups[0].reclaimable(), SIZE as u64);
+}
+
+#[test]
+fn test_only_selected_groups_are_cleaned_keeping_one_copy() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let size = DUPLICATE_MIN_SIZE as usize;
+    write(home, "Videos/trip.mp4", &content(3, size));
+    write(home, "Downloads/trip.mp4", &content(3, size));
+    write(home, "Music/album.zip", &content(4, size));
+    write(home, "Downloads/album.zip", &content(4, size));
+    write(home, "Library/Caches/album.zip", &content(4, size));
+
+    let mut item = duplicates_item(home);
+    assert!(!item.enabled);
+    assert_eq!(item.risk, RiskLevel::Moderate);
+    assert_eq!(item.validate(), ItemStatus::Ready);
+    // The estimate only compares sizes, so every file of the same size counts
+    assert_eq!(item.estimate().files, 3);
+
+    let scan = item.scan();
+    assert!(scan.has_data);
+    assert_eq!(scan.size_bytes, 0);
+    assert_eq!(scan.breakdown.len(), 2);
+    assert!(scan.breakdown.iter().all(|entry| entry.note.ends_with(NOT_SELECTED_NOTE)));
+    assert_eq!(scan.breakdown[0].size_bytes, size as u64);
+
+    // Nothing is deleted before a group is selected
+    assert_eq!(item.clean().files, 0);
+
+    let kept = home.join("Downloads/trip.mp4");
+    assert_eq!(scan.breakdown[1].label, kept.display().to_string());
+    assert!(item.toggle_selected(&kept));
+    assert_eq!(item.scan().size_bytes, size as u64);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files, 1);
+    assert!(kept.exists());
+    assert!(!home.join("Videos/trip.mp4").exists());
+    assert!(home.join("Music/album.zip").exists());
+    assert!(home.join("Downloads/album.zip").exists());
+    assert!(home.join("Library/Caches/album.zip").exists());
+
+    assert!(!item.toggle_selected(&kept));
+    assert_eq!(item.options.selected_paths, None);
+}
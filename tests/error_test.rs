@@ -0,0 +1,196 @@
+use clean_rs::error::{error_codes, io_code, summarize_classes};
+use clean_rs::{CleanError, CleanErrorEntry, CleanResult, EntryKind, ErrorClass};
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
+#[test]
+fn test_messages_name_the_path_and_operation() {
+    let denied = || io::Error::new(io::ErrorKind::PermissionDenied, "access denied");
+    let path = Path::new("/tmp/cache");
+
+    assert_eq!(
+        CleanError::read_dir(path, denied()).to_string(),
+        "Failed to read directory /tmp/cache: access denied"
+    );
+    assert_eq!(
+        CleanError::metadata(path, denied()).to_string(),
+        "Failed to read metadata of /tmp/cache: access denied"
+    );
+    assert_eq!(
+        CleanError::remove(path, EntryKind::Directory, denied()).to_string(),
+        "Failed to delete directory /tmp/cache: access denied"
+    );
+    assert_eq!(
+        CleanError::remove(Path::new("/tmp/cache/a.log"), EntryKind::Link, denied()).to_string(),
+        "Failed to delete link /tmp/cache/a.log: access denied"
+    );
+    assert_eq!(CleanError::Cancelled.to_string(), "Cancelled");
+}
+
+#[test]
+fn test_errors_keep_their_path_and_io_kind() {
+    let path = Path::new("/tmp/cache/a.log");
+    let error = CleanError::remove(path, EntryKind::File, io::Error::from(io::ErrorKind::NotFound));
+    assert_eq!(error.path(), Some(path));
+    assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    assert!(std::error::Error::source(&error).is_some());
+
+    let coarse = CleanError::from(io::Error::from(io::ErrorKind::PermissionDenied));
+    assert_eq!(coarse.path(), None);
+    assert_eq!(coarse.kind(), io::ErrorKind::PermissionDenied);
+    assert_eq!(CleanError::NotSupported("no".to_string()).kind(), io::ErrorKind::Other);
+
+    let entry = CleanErrorEntry::from_error(Path::new("Recycle Bin"), &error);
+    assert_eq!(entry.path, path);
+    assert_eq!(entry.kind, io::ErrorKind::NotFound);
+    assert_eq!(entry.message, error.to_string());
+    let fallback = CleanErrorEntry::from_error(Path::new("DNS"), &CleanError::Cancelled);
+    assert_eq!(fallback.path, Path::new("DNS"));
+}
+
+#[test]
+fn test_partial_failure_keeps_what_was_already_deleted() {
+    let cause = || CleanError::read_dir(Path::new("/tmp/cache"), io::Error::from(io::ErrorKind::PermissionDenied));
+    let done = CleanResult {
+        files_deleted: 3,
+        dirs_deleted: 1,
+        bytes_cleaned: 2048,
+        errors: Vec::new(),
+    };
+
+    let error = CleanError::partial(done, cause());
+    assert!(error.to_string().starts_with(&cause().to_string()));
+    assert!(error.to_string().ends_with("(stopped after deleting 3 files and 1 directories, 2048 bytes freed)"));
+    assert_eq!(error.path(), Some(Path::new("/tmp/cache")));
+    assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+
+    let (result, cause) = error.into_partial_result();
+    assert_eq!(result.unwrap().files_deleted, 3);
+    assert!(matches!(cause, CleanError::ReadDir { .. }));
+
+    // Nothing deleted yet is a plain failure
+    let nothing = CleanResult {
+        files_deleted: 0,
+        dirs_deleted: 0,
+        bytes_cleaned: 0,
+        errors: Vec::new(),
+    };
+    let (result, cause) = CleanError::partial(nothing, CleanError::Cancelled).into_partial_result();
+    assert!(result.is_none());
+    assert!(matches!(cause, CleanError::Cancelled));
+}
+
+#[test]
+fn test_error_codes_are_unique_and_stable() {
+    let codes: Vec<&str> = error_codes().iter().map(|(code, _)| *code).collect();
+    let unique: HashSet<&str> = codes.iter().copied().collect();
+    assert_eq!(unique.len(), codes.len(), "duplicate code in {:?}", codes);
+    assert!(error_codes().iter().all(|(code, description)| code.starts_with("E_") && !description.is_empty()));
+
+    // Scripts depend on these exact strings; changing one is a breaking change
+    let path = Path::new("/mnt");
+    let io_error = || io::Error::from(io::ErrorKind::Other);
+    let errors = [
+        (CleanError::Io(io_error()), "E_IO"),
+        (CleanError::read_dir(path, io_error()), "E_READ_DIR"),
+        (CleanError::metadata(path, io_error()), "E_METADATA"),
+        (CleanError::remove(path, EntryKind::File, io_error()), "E_REMOVE"),
+        (CleanError::Cancelled, "E_CANCELLED"),
+        (
+            CleanError::partial(
+                CleanResult { files_deleted: 1, dirs_deleted: 0, bytes_cleaned: 1, errors: Vec::new() },
+                CleanError::Cancelled,
+            ),
+            "E_PARTIAL",
+        ),
+        (CleanError::Multiple(vec![("temp".to_string(), CleanError::Cancelled)]), "E_MULTIPLE"),
+        (CleanError::DeleteFailed { path: "/mnt/a".to_string() }, "E_DELETE_FAILED"),
+        (CleanError::NotSupported(String::new()), "E_NOT_SUPPORTED"),
+        (CleanError::WindowsError(String::new()), "E_WINDOWS_API"),
+        (CleanError::RefusedMountPoint(path.to_path_buf()), "E_PROTECTED_PATH"),
+        (CleanError::InvalidState(serde_json::from_str::<u8>("x").unwrap_err()), "E_INVALID_STATE"),
+    ];
+    for (error, code) in &errors {
+        assert_eq!(error.code(), *code);
+        assert!(unique.contains(code), "{} is not listed", code);
+    }
+
+    assert_eq!(io_code(io::ErrorKind::PermissionDenied, false), "E_PERMISSION");
+    assert_eq!(io_code(io::ErrorKind::PermissionDenied, true), "E_IN_USE");
+    assert_eq!(io_code(io::ErrorKind::NotFound, false), "E_NOT_FOUND");
+    assert_eq!(io_code(io::ErrorKind::TimedOut, false), "E_IO");
+    for code in ["E_PERMISSION", "E_IN_USE", "E_NOT_FOUND"] {
+        assert!(unique.contains(code), "{} is not listed", code);
+    }
+}
+
+#[test]
+fn test_codes_are_serialized_next_to_the_message() {
+    let error = CleanError::RefusedMountPoint(Path::new("/mnt").to_path_buf());
+    let json = serde_json::to_value(&error).unwrap();
+    assert_eq!(json["code"], "E_PROTECTED_PATH");
+    assert_eq!(json["message"], error.to_string());
+
+    let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+    let entry = CleanErrorEntry::from_error(Path::new("/"), &CleanError::read_dir(Path::new("/mnt"), denied));
+    let json = serde_json::to_value(&entry).unwrap();
+    assert_eq!(json["code"], "E_PERMISSION");
+    assert_eq!(json["path"], "/mnt");
+    assert_eq!(CleanErrorEntry::from_error(Path::new("/"), &CleanError::Cancelled).code, "E_CANCELLED");
+}
+
+#[test]
+fn test_io_kinds_map_to_classes() {
+    use io::ErrorKind::*;
+
+    let cases = [
+        (PermissionDenied, ErrorClass::Permission),
+        (ReadOnlyFilesystem, ErrorClass::Permission),
+        (NotFound, ErrorClass::Missing),
+        (Interrupted, ErrorClass::Transient),
+        (TimedOut, ErrorClass::Transient),
+        (WouldBlock, ErrorClass::Transient),
+        (ResourceBusy, ErrorClass::Transient),
+        (InvalidData, ErrorClass::Fatal),
+        (Other, ErrorClass::Fatal),
+    ];
+    for (kind, class) in cases {
+        assert_eq!(ErrorClass::of_io(kind, false), class, "{:?}", kind);
+        assert_eq!(ErrorClass::of(&io::Error::from(kind)), class, "{:?}", kind);
+    }
+    // A file another program holds is worth retrying whatever the kind says
+    assert_eq!(ErrorClass::of_io(PermissionDenied, true), ErrorClass::Transient);
+}
+
+#[test]
+fn test_errors_keep_their_io_kind_through_wrapping() {
+    let path = Path::new("/tmp/cache");
+    let missing = CleanError::remove(path, EntryKind::File, io::Error::from(io::ErrorKind::NotFound));
+    assert_eq!(missing.io_kind(), Some(io::ErrorKind::NotFound));
+    assert_eq!(missing.classification(), ErrorClass::Missing);
+
+    let denied = CleanError::read_dir(path, io::Error::from(io::ErrorKind::PermissionDenied));
+    let source = std::error::Error::source(&denied).unwrap().downcast_ref::<io::Error>().unwrap();
+    assert_eq!(source.kind(), io::ErrorKind::PermissionDenied);
+    assert_eq!(denied.classification(), ErrorClass::Permission);
+    let timed_out = CleanError::metadata(path, io::Error::from(io::ErrorKind::TimedOut));
+    assert_eq!(timed_out.classification(), ErrorClass::Transient);
+    assert_eq!(CleanError::Io(io::Error::from(io::ErrorKind::InvalidData)).classification(), ErrorClass::Fatal);
+
+    let done = CleanResult { files_deleted: 1, dirs_deleted: 0, bytes_cleaned: 1, errors: Vec::new() };
+    assert_eq!(CleanError::partial(done, denied).classification(), ErrorClass::Permission);
+    assert_eq!(CleanError::Cancelled.io_kind(), None);
+    assert_eq!(CleanError::Cancelled.classification(), ErrorClass::Fatal);
+    assert_eq!(CleanError::NotSupported(String::new()).classification(), ErrorClass::Fatal);
+}
+
+#[test]
+fn test_classes_are_counted_in_declaration_order() {
+    let classes = [ErrorClass::Missing, ErrorClass::Permission, ErrorClass::Permission, ErrorClass::Transient];
+    assert_eq!(summarize_classes(classes), "2 permission, 1 transient, 1 missing");
+    assert_eq!(summarize_classes([]), "");
+
+    let entry = CleanErrorEntry::new(Path::new("a"), &io::Error::from(io::ErrorKind::NotFound));
+    assert_eq!(entry.class(), ErrorClass::Missing);
+}
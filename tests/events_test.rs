@@ -0,0 +1,198 @@
+use clean_rs::{
+    clean_enabled, scan_enabled_parallel_with, Category, CleanErrorEntry, CleanEvent, CleanObserver, CleanOptions,
+    Cleaner, CleanupItem, CleanupResult, CleanupType, MockFs, RiskLevel, TracingObserver,
+};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex};
+use tempfile::TempDir;
+
+fn directory_item(id: &str, path: &Path) -> CleanupItem {
+    CleanupItem {
+        id: id.to_string(),
+        name: id.to_string(),
+        description: String::new(),
+        cleanup_type: CleanupType::Directory(path.to_path_buf()),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_scan_emits_item_events_then_all_done() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("a.bin"), vec![0u8; 10]).unwrap();
+    let mut items = vec![
+        directory_item("first", temp_dir.path()),
+        directory_item("second", temp_dir.path()),
+        directory_item("disabled", temp_dir.path()),
+    ];
+    items[2].enabled = false;
+
+    let (tx, rx) = mpsc::channel();
+    let options = CleanOptions::new().events(tx);
+    scan_enabled_parallel_with(&items, 2, &options, |_, _| {});
+    drop(options);
+
+    let events: Vec<CleanEvent> = rx.into_iter().collect();
+    assert!(matches!(events.last(), Some(CleanEvent::AllDone)));
+    for id in ["first", "second"] {
+        let started = events
+            .iter()
+            .position(|e| matches!(e, CleanEvent::ItemStarted { id: started } if started == id))
+            .unwrap();
+        let finished = events
+            .iter()
+            .position(|e| matches!(e, CleanEvent::ItemFinished { id: done, .. } if done == id))
+            .unwrap();
+        assert!(started < finished);
+    }
+    assert!(!events.iter().any(|e| matches!(e, CleanEvent::ItemStarted { id } if id == "disabled")));
+}
+
+#[test]
+fn test_clean_reports_progress_and_results() {
+    let temp_dir = TempDir::new().unwrap();
+    for i in 0..600 {
+        fs::write(temp_dir.path().join(format!("{i}.bin")), b"x").unwrap();
+    }
+    let items = vec![directory_item("dir", temp_dir.path())];
+
+    let (tx, rx) = mpsc::channel();
+    let options = CleanOptions::new().events(tx);
+    clean_enabled(&items, &options, |_, _| {});
+    drop(options);
+
+    let events: Vec<CleanEvent> = rx.into_iter().collect();
+    let progress: Vec<(u64, u64)> = events
+        .iter()
+        .filter_map(|e| match e {
+            CleanEvent::Progress { files_done, bytes_done, .. } => Some((*files_done, *bytes_done)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(progress, [(256, 256), (512, 512)]);
+
+    let finished = events.iter().find_map(|e| match e {
+        CleanEvent::ItemFinished { result, .. } => Some(result),
+        _ => None,
+    });
+    assert_eq!(finished.unwrap().files, 600);
+    assert!(matches!(events.last(), Some(CleanEvent::AllDone)));
+}
+
+#[test]
+fn test_events_are_optional() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("a.bin"), b"x").unwrap();
+    let item = directory_item("dir", temp_dir.path());
+    assert_eq!(item.scan_with(&CleanOptions::default()).files, 1);
+}
+
+/// Records every callback as a line of text
+#[derive(Debug, Default)]
+struct Recorder {
+    lines: Mutex<Vec<String>>,
+}
+
+impl Recorder {
+    fn record(&self, line: String) {
+        self.lines.lock().unwrap().push(line);
+    }
+
+    fn lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap().clone()
+    }
+}
+
+impl CleanObserver for Recorder {
+    fn on_item_start(&self, id: &str, name: &str) {
+        self.record(format!("start {} {}", id, name));
+    }
+
+    fn on_entry_deleted(&self, _id: &str, path: &Path, bytes: u64) {
+        self.record(format!("deleted {} {}", path.file_name().unwrap().to_string_lossy(), bytes));
+    }
+
+    fn on_entry_failed(&self, _id: &str, error: &CleanErrorEntry) {
+        self.record(format!("failed {} {:?}", error.path.file_name().unwrap().to_string_lossy(), error.kind));
+    }
+
+    fn on_item_done(&self, id: &str, result: &CleanupResult) {
+        self.record(format!("done {} {}", id, result.files));
+    }
+
+    fn on_run_done(&self) {
+        self.record("run done".to_string());
+    }
+}
+
+/// Panics in every callback
+#[derive(Debug)]
+struct Faulty;
+
+impl CleanObserver for Faulty {
+    fn on_item_start(&self, _id: &str, _name: &str) {
+        panic!("observer bug");
+    }
+
+    fn on_entry_deleted(&self, _id: &str, _path: &Path, _bytes: u64) {
+        panic!("observer bug");
+    }
+
+    fn on_item_done(&self, _id: &str, _result: &CleanupResult) {
+        panic!("observer bug");
+    }
+}
+
+fn mock_cleaner(fs: MockFs) -> Cleaner {
+    let mut cleaner = Cleaner::new(vec![directory_item("cache", Path::new("/cache"))]);
+    cleaner.set_options(CleanOptions::new().fs(Arc::new(fs)));
+    cleaner
+}
+
+#[test]
+fn test_observers_see_deleted_and_failed_entries() {
+    let fs = MockFs::new()
+        .file("/cache/a.bin", 10)
+        .file("/cache/sub/b.bin", 20)
+        .fail_remove("/cache/sub/b.bin", io::ErrorKind::ResourceBusy);
+    let mut cleaner = mock_cleaner(fs);
+    let recorder = Arc::new(Recorder::default());
+    cleaner.add_observer(recorder.clone());
+
+    let report = cleaner.clean_enabled(false);
+
+    assert_eq!(report.total_files(), 1);
+    assert_eq!(
+        recorder.lines(),
+        vec![
+            "start cache cache",
+            "deleted a.bin 10",
+            "failed b.bin ResourceBusy",
+            "done cache 1",
+            "run done",
+        ]
+    );
+}
+
+#[test]
+fn test_panicking_observer_does_not_stop_the_clean() {
+    let fs = MockFs::new().file("/cache/a.bin", 10).file("/cache/b.bin", 20);
+    let mut cleaner = mock_cleaner(fs);
+    let recorder = Arc::new(Recorder::default());
+    cleaner.add_observer(Arc::new(Faulty));
+    cleaner.add_observer(Arc::new(TracingObserver));
+    cleaner.add_observer(recorder.clone());
+
+    let report = cleaner.clean_enabled(false);
+
+    assert_eq!(report.failed(), 0);
+    assert_eq!(report.total_files(), 2);
+    assert_eq!(report.total_bytes(), 30);
+    // Observers after the faulty one still hear about everything
+    assert_eq!(recorder.lines().len(), 5);
+}
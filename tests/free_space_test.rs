@@ -0,0 +1,128 @@
+use clean_rs::free_space::parse_size;
+use clean_rs::{
+    clean_until_freed, Category, CleanOptions, CleanupItem, CleanupType, FreeOrder, RiskLevel,
+};
+use std::fs::{self, File};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+fn directory_item(id: &str, path: &Path) -> CleanupItem {
+    CleanupItem {
+        id: id.to_string(),
+        name: id.to_string(),
+        description: String::new(),
+        cleanup_type: CleanupType::Directory(path.to_path_buf()),
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+/// Write a file of `size` bytes whose modification time is `age_days` in the past
+fn write_aged(path: &Path, size: usize, age_days: u64) {
+    fs::write(path, vec![0u8; size]).unwrap();
+    let modified = SystemTime::now() - Duration::from_secs(age_days * 24 * 3600);
+    File::options().write(true).open(path).unwrap().set_modified(modified).unwrap();
+}
+
+#[test]
+fn test_clean_at_most_deletes_oldest_first() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir(root.join("sub")).unwrap();
+    write_aged(&root.join("new.bin"), 100, 1);
+    write_aged(&root.join("sub/oldest.bin"), 100, 30);
+    write_aged(&root.join("older.bin"), 100, 10);
+
+    let result = directory_item("dir", root).clean_at_most(150, &CleanOptions::default());
+
+    assert_eq!(result.files, 2);
+    assert_eq!(result.size_bytes, 200);
+    assert!(!root.join("sub/oldest.bin").exists());
+    assert!(!root.join("older.bin").exists());
+    assert!(root.join("new.bin").exists());
+    // The target was reached before the end, so emptied directories are left alone
+    assert!(root.join("sub").exists());
+    assert_eq!(result.size_remaining, 100);
+}
+
+#[test]
+fn test_clean_until_freed_stops_at_target() {
+    let temp_dir = TempDir::new().unwrap();
+    let small = temp_dir.path().join("small");
+    let large = temp_dir.path().join("large");
+    let untouched = temp_dir.path().join("untouched");
+    for dir in [&small, &large, &untouched] {
+        fs::create_dir(dir).unwrap();
+    }
+    write_aged(&small.join("a.bin"), 200, 1);
+    write_aged(&large.join("a.bin"), 400, 2);
+    write_aged(&large.join("b.bin"), 400, 1);
+    write_aged(&untouched.join("a.bin"), 50, 1);
+
+    let items = vec![
+        directory_item("small", &small),
+        directory_item("large", &large),
+        directory_item("untouched", &untouched),
+    ];
+    let report = clean_until_freed(&items, 900, &CleanOptions::default());
+
+    // Largest first: all of "large", then just enough of "small"
+    assert!(report.target_reached());
+    assert_eq!(report.freed_bytes, 1000);
+    let order: Vec<&str> = report.contributions.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(order, ["large", "small"]);
+    assert_eq!(report.contributions[0].result.size_bytes, 800);
+    assert_eq!(report.contributions[1].result.size_bytes, 200);
+    assert_eq!(report.untouched, ["untouched"]);
+    assert!(untouched.join("a.bin").exists());
+}
+
+#[test]
+fn test_clean_until_freed_in_given_order() {
+    let temp_dir = TempDir::new().unwrap();
+    let first = temp_dir.path().join("first");
+    let second = temp_dir.path().join("second");
+    fs::create_dir(&first).unwrap();
+    fs::create_dir(&second).unwrap();
+    write_aged(&first.join("a.bin"), 100, 1);
+    write_aged(&second.join("a.bin"), 1000, 1);
+
+    let items = vec![directory_item("first", &first), directory_item("second", &second)];
+    let options = CleanOptions::new().free_order(FreeOrder::AsGiven);
+    let report = clean_until_freed(&items, 100, &options);
+
+    assert_eq!(report.freed_bytes, 100);
+    assert_eq!(report.untouched, ["second"]);
+    assert!(second.join("a.bin").exists());
+}
+
+#[test]
+fn test_clean_until_freed_reports_shortfall() {
+    let temp_dir = TempDir::new().unwrap();
+    write_aged(&temp_dir.path().join("a.bin"), 100, 1);
+    fs::create_dir(temp_dir.path().join("empty")).unwrap();
+
+    let items = vec![directory_item("dir", temp_dir.path())];
+    let report = clean_until_freed(&items, 1 << 30, &CleanOptions::default());
+
+    assert!(!report.target_reached());
+    assert_eq!(report.freed_bytes, 100);
+    assert!(report.untouched.is_empty());
+    // Everything was processed, so emptied directories are removed too
+    assert!(!temp_dir.path().join("empty").exists());
+}
+
+#[test]
+fn test_parse_size() {
+    assert_eq!(parse_size("4096"), Ok(4096));
+    assert_eq!(parse_size("5G"), Ok(5 << 30));
+    assert_eq!(parse_size("500mb"), Ok(500 << 20));
+    assert_eq!(parse_size("1.5GiB"), Ok(3 << 29));
+    assert_eq!(parse_size(" 2 K "), Ok(2048));
+    assert!(parse_size("").is_err());
+    assert!(parse_size("5X").is_err());
+    assert!(parse_size("G").is_err());
+}
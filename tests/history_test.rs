@@ -0,0 +1,153 @@
+use clean_rs::history::{clean_history, scan_history, HistoryDb, HistoryDbKind};
+use clean_rs::{Category, CleanupItem, CleanupType, ItemOptions, RiskLevel};
+use rusqlite::Connection;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Chromium `History` with one visit 200 days old and one from yesterday
+fn fake_chromium_history(path: &Path) {
+    let conn = Connection::open(path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE urls (id INTEGER PRIMARY KEY, url TEXT, last_visit_time INTEGER);
+         CREATE TABLE visits (id INTEGER PRIMARY KEY, url INTEGER, visit_time INTEGER);
+         CREATE TABLE keyword_search_terms (keyword_id INTEGER, url_id INTEGER, term TEXT);",
+    )
+    .unwrap();
+    let now = SystemTime::now();
+    for (id, age, padding) in [(1, 200, 20_000), (2, 1, 10)] {
+        let time = HistoryDbKind::ChromiumHistory.cutoff(now, age);
+        conn.execute(
+            "INSERT INTO urls VALUES (?1, ?2, ?3)",
+            rusqlite::params![id, "x".repeat(padding), time],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO visits VALUES (?1, ?1, ?2)", rusqlite::params![id, time]).unwrap();
+        conn.execute("INSERT INTO keyword_search_terms VALUES (1, ?1, 'term')", [id]).unwrap();
+    }
+}
+
+fn count(path: &Path, table: &str) -> i64 {
+    let conn = Connection::open(path).unwrap();
+    conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0)).unwrap()
+}
+
+#[test]
+fn test_scan_reports_old_rows_without_changing_the_database() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("History");
+    fake_chromium_history(&path);
+    let before = fs::read(&path).unwrap();
+
+    let databases = [HistoryDb::new(path.clone(), HistoryDbKind::ChromiumHistory)];
+    let result = scan_history(&databases, 90);
+    assert!(result.has_data);
+    // One visit, its url and its search term
+    assert_eq!(result.entries, 3);
+    assert!(result.size_bytes > 0);
+    assert_eq!(fs::read(&path).unwrap(), before);
+}
+
+#[test]
+fn test_clean_deletes_old_rows_and_vacuums() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("History");
+    fake_chromium_history(&path);
+    let size_before = fs::metadata(&path).unwrap().len();
+
+    let databases = [HistoryDb::new(path.clone(), HistoryDbKind::ChromiumHistory)];
+    let result = clean_history(&databases, 90);
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.entries, 3);
+    assert!(result.size_bytes > 0);
+    assert_eq!(fs::metadata(&path).unwrap().len(), size_before - result.size_bytes);
+    assert_eq!(count(&path, "urls"), 1);
+    assert_eq!(count(&path, "visits"), 1);
+    assert_eq!(count(&path, "keyword_search_terms"), 1);
+}
+
+#[test]
+fn test_firefox_bookmarked_places_are_kept() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("places.sqlite");
+    let conn = Connection::open(&path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url TEXT, last_visit_date INTEGER);
+         CREATE TABLE moz_historyvisits (id INTEGER PRIMARY KEY, place_id INTEGER, visit_date INTEGER);
+         CREATE TABLE moz_bookmarks (id INTEGER PRIMARY KEY, fk INTEGER);
+         INSERT INTO moz_bookmarks VALUES (1, 2);",
+    )
+    .unwrap();
+    let old = HistoryDbKind::FirefoxPlaces.cutoff(SystemTime::now(), 365);
+    for id in [1, 2] {
+        conn.execute("INSERT INTO moz_places VALUES (?1, 'https://example.com', ?2)", rusqlite::params![id, old])
+            .unwrap();
+        conn.execute("INSERT INTO moz_historyvisits VALUES (?1, ?1, ?2)", rusqlite::params![id, old]).unwrap();
+    }
+    drop(conn);
+
+    let result = clean_history(&[HistoryDb::new(path.clone(), HistoryDbKind::FirefoxPlaces)], 90);
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(count(&path, "moz_historyvisits"), 0);
+    let conn = Connection::open(&path).unwrap();
+    let kept: Vec<i64> = conn
+        .prepare("SELECT id FROM moz_places")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(kept, vec![2]);
+}
+
+#[test]
+fn test_clean_refuses_locked_database() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("History");
+    fake_chromium_history(&path);
+
+    // A running browser keeps its history database locked
+    let browser = Connection::open(&path).unwrap();
+    browser.execute_batch("BEGIN EXCLUSIVE").unwrap();
+
+    let databases = [HistoryDb::new(path.clone(), HistoryDbKind::ChromiumHistory)];
+    let result = clean_history(&databases, 90);
+    assert!(result.skip_reason.is_some());
+    assert_eq!(result.entries, 0);
+
+    browser.execute_batch("ROLLBACK").unwrap();
+    drop(browser);
+    assert_eq!(count(&path, "visits"), 2);
+}
+
+#[test]
+fn test_item_uses_max_age_option() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("Cookies");
+    let conn = Connection::open(&path).unwrap();
+    conn.execute_batch("CREATE TABLE cookies (name TEXT, last_access_utc INTEGER)").unwrap();
+    let accessed = HistoryDbKind::ChromiumCookies.cutoff(SystemTime::now() - 30 * DAY, 0);
+    conn.execute("INSERT INTO cookies VALUES ('session', ?1)", [accessed]).unwrap();
+    drop(conn);
+
+    let mut item = CleanupItem {
+        id: "test_history".to_string(),
+        name: "Test history".to_string(),
+        cleanup_type: CleanupType::BrowserHistory(vec![HistoryDb::new(path.clone(), HistoryDbKind::ChromiumCookies)]),
+        category: Category::Browsers,
+        risk: RiskLevel::Aggressive,
+        ..Default::default()
+    };
+    assert_eq!(item.scan().entries, 0);
+
+    item.options = ItemOptions {
+        max_age_days: Some(7),
+        ..Default::default()
+    };
+    assert_eq!(item.scan().entries, 1);
+    assert_eq!(item.clean().entries, 1);
+    assert_eq!(count(&path, "cookies"), 0);
+}
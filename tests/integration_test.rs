@@ -1,7 +1,20 @@
-use clean_rs::{clean_directory, get_dir_size, CleanResult};
+use clean_rs::{
+    clean_directory, clean_directory_in, get_dir_size, get_dir_size_in, Category, CleanErrorEntry, CleanOptions,
+    CleanResult, Cleaner, CleanupItem, CleanupType, ErrorClass, ItemStatus, MockFs, RiskLevel,
+};
 use std::fs;
+use std::io;
+use std::path::Path;
 use tempfile::TempDir;
 
+/// The tree the directory tests clean: 13 + 1024 + 2048 = 3085 bytes
+fn mock_tree() -> MockFs {
+    MockFs::new()
+        .file("/data/file1.txt", 13)
+        .file("/data/file2.txt", 1024)
+        .file("/data/subdir/file3.txt", 2048)
+}
+
 #[test]
 fn test_get_dir_size() {
     let temp_dir = TempDir::new().unwrap();
@@ -23,55 +36,155 @@ fn test_get_dir_size() {
 }
 
 #[test]
-fn test_clean_directory_dry_run() {
-    let temp_dir = TempDir::new().unwrap();
-    let dir_path = temp_dir.path();
+fn test_get_dir_size_skips_links_and_placeholders() {
+    let fs = mock_tree().link("/data/shortcut").file("/data/.Photo.heic.icloud", 500).file("/data/cloud.docx", 700);
+    let fs = fs.online_only("/data/cloud.docx");
 
-    // Create test structure
-    fs::write(dir_path.join("file1.txt"), b"Hello, World!").unwrap();
-    fs::write(dir_path.join("file2.txt"), vec![0u8; 1024]).unwrap();
-    
-    let subdir = dir_path.join("subdir");
-    fs::create_dir(&subdir).unwrap();
-    fs::write(subdir.join("file3.txt"), vec![0u8; 2048]).unwrap();
+    assert_eq!(get_dir_size_in(&fs, Path::new("/data")).unwrap(), 3085);
+    assert_eq!(get_dir_size_in(&fs, Path::new("/data/subdir/file3.txt")).unwrap(), 2048);
+    assert_eq!(get_dir_size_in(&fs, Path::new("/missing")).unwrap(), 0);
+}
+
+#[test]
+fn test_clean_directory_dry_run() {
+    let fs = mock_tree();
 
     // Dry run should not delete files
-    let result = clean_directory(dir_path, true).unwrap();
+    let result = clean_directory_in(&fs, Path::new("/data"), true).unwrap();
 
-    assert!(result.files_deleted > 0);
-    assert!(result.dirs_deleted > 0);
+    assert_eq!(result.files_deleted, 2);
+    assert_eq!(result.dirs_deleted, 1);
     assert_eq!(result.bytes_cleaned, 3085);
 
     // Verify files still exist
-    assert!(dir_path.join("file1.txt").exists());
-    assert!(dir_path.join("file2.txt").exists());
-    assert!(dir_path.join("subdir/file3.txt").exists());
+    assert!(fs.exists("/data/file1.txt"));
+    assert!(fs.exists("/data/file2.txt"));
+    assert!(fs.exists("/data/subdir/file3.txt"));
 }
 
 #[test]
 fn test_clean_directory_real() {
+    let fs = mock_tree();
+
+    // Real clean should delete files
+    let result = clean_directory_in(&fs, Path::new("/data"), false).unwrap();
+
+    assert_eq!(result.files_deleted, 2);
+    assert_eq!(result.dirs_deleted, 1);
+    assert_eq!(result.bytes_cleaned, 3085);
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+    // Verify files are deleted, leaving the directory itself
+    assert!(fs.exists("/data"));
+    assert!(!fs.exists("/data/file1.txt"));
+    assert!(!fs.exists("/data/file2.txt"));
+    assert!(!fs.exists("/data/subdir"));
+}
+
+#[test]
+fn test_clean_directory_on_disk() {
     let temp_dir = TempDir::new().unwrap();
     let dir_path = temp_dir.path();
-
-    // Create test structure
     fs::write(dir_path.join("file1.txt"), b"Hello, World!").unwrap();
-    fs::write(dir_path.join("file2.txt"), vec![0u8; 1024]).unwrap();
-    
-    let subdir = dir_path.join("subdir");
-    fs::create_dir(&subdir).unwrap();
-    fs::write(subdir.join("file3.txt"), vec![0u8; 2048]).unwrap();
+    fs::create_dir(dir_path.join("subdir")).unwrap();
+    fs::write(dir_path.join("subdir/file3.txt"), vec![0u8; 2048]).unwrap();
 
-    // Real clean should delete files
     let result = clean_directory(dir_path, false).unwrap();
 
-    assert!(result.files_deleted > 0);
-    assert!(result.dirs_deleted >= 1);
-    assert!(result.bytes_cleaned > 0);
+    assert_eq!(result.files_deleted, 1);
+    assert_eq!(result.dirs_deleted, 1);
+    assert_eq!(result.bytes_cleaned, 2061);
+    assert_eq!(fs::read_dir(dir_path).unwrap().count(), 0);
+}
+
+#[test]
+fn test_clean_directory_records_failed_removals() {
+    let fs = mock_tree()
+        .fail_remove("/data/file2.txt", io::ErrorKind::PermissionDenied)
+        .fail_remove("/data/subdir/file3.txt", io::ErrorKind::ResourceBusy);
+
+    let result = clean_directory_in(&fs, Path::new("/data"), false).unwrap();
+
+    assert_eq!(result.files_deleted, 1);
+    assert_eq!(result.dirs_deleted, 0);
+    assert_eq!(result.bytes_cleaned, 13);
+    let failed: Vec<_> = result.errors.iter().map(|error| (error.path.clone(), error.class())).collect();
+    assert_eq!(
+        failed,
+        vec![
+            (Path::new("/data/file2.txt").to_path_buf(), ErrorClass::Permission),
+            // remove_dir_all stops at the busy file
+            (Path::new("/data/subdir").to_path_buf(), ErrorClass::Transient),
+        ]
+    );
+    assert!(result.display_status().contains("Errors encountered: 2 (1 permission, 1 transient)"));
+    assert!(fs.exists("/data/file2.txt"));
+    assert!(fs.exists("/data/subdir/file3.txt"));
+}
+
+#[test]
+fn test_clean_directory_stops_on_fatal_removal_failure() {
+    let fs = mock_tree().fail_remove("/data/file2.txt", io::ErrorKind::Other);
+
+    let error = clean_directory_in(&fs, Path::new("/data"), false).unwrap_err();
+
+    assert_eq!(error.code(), "E_PARTIAL");
+    let (result, cause) = error.into_partial_result();
+    let result = result.unwrap();
+    assert_eq!(result.files_deleted, 1);
+    assert_eq!(result.dirs_deleted, 0);
+    assert_eq!(result.bytes_cleaned, 13);
+    assert_eq!(cause.code(), "E_REMOVE");
+    assert_eq!(cause.path(), Some(Path::new("/data/file2.txt")));
+    // Nothing after the failing entry was touched
+    assert!(fs.exists("/data/subdir/file3.txt"));
+}
+
+#[test]
+fn test_clean_directory_stopped_before_deleting_is_the_plain_cause() {
+    let fs = mock_tree().fail_remove("/data/file1.txt", io::ErrorKind::Other);
+
+    let error = clean_directory_in(&fs, Path::new("/data"), false).unwrap_err();
+
+    assert_eq!(error.code(), "E_REMOVE");
+    assert!(fs.exists("/data/file2.txt"));
+}
+
+#[test]
+fn test_clean_directory_fails_when_unreadable() {
+    let fs = mock_tree().fail_read_dir("/data", io::ErrorKind::PermissionDenied);
+
+    let error = clean_directory_in(&fs, Path::new("/data"), false).unwrap_err();
 
-    // Verify files are deleted
-    assert!(!dir_path.join("file1.txt").exists());
-    assert!(!dir_path.join("file2.txt").exists());
-    assert!(!dir_path.join("subdir").exists());
+    assert_eq!(error.code(), "E_READ_DIR");
+    assert_eq!(error.path(), Some(Path::new("/data")));
+    assert_eq!(error.io_kind(), Some(io::ErrorKind::PermissionDenied));
+    assert!(fs.exists("/data/file1.txt"));
+}
+
+#[test]
+fn test_clean_directory_missing_is_empty() {
+    let result = clean_directory_in(&MockFs::new(), Path::new("/missing"), false).unwrap();
+    assert!(result.is_empty());
+    assert_eq!(result.bytes_cleaned, 0);
+}
+
+#[test]
+fn test_clean_directory_dry_run_reports_what_a_real_clean_frees() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir_path = temp_dir.path();
+
+    fs::write(dir_path.join("file1.txt"), vec![0u8; 512]).unwrap();
+    let subdir = dir_path.join("subdir");
+    fs::create_dir(&subdir).unwrap();
+    fs::write(subdir.join("file2.txt"), vec![0u8; 256]).unwrap();
+
+    let preview = clean_directory(dir_path, true).unwrap();
+    assert_eq!(preview.bytes_cleaned, 768);
+    assert_eq!(get_dir_size(dir_path).unwrap(), 768);
+
+    let cleaned = clean_directory(dir_path, false).unwrap();
+    assert_eq!(cleaned.bytes_cleaned, preview.bytes_cleaned);
 }
 
 #[test]
@@ -80,7 +193,7 @@ fn test_clean_result_methods() {
         files_deleted: 10,
         dirs_deleted: 2,
         bytes_cleaned: 1024,
-        errors: vec!["Error1".to_string()],
+        errors: vec![CleanErrorEntry::new(Path::new("a"), &io::Error::from(io::ErrorKind::PermissionDenied))],
     };
 
     assert!(!result.is_empty());
@@ -103,12 +216,192 @@ fn test_clean_result_display_status() {
         files_deleted: 10,
         dirs_deleted: 2,
         bytes_cleaned: 1024000,
-        errors: vec!["Error1".to_string(), "Error2".to_string()],
+        errors: [io::ErrorKind::PermissionDenied, io::ErrorKind::NotFound, io::ErrorKind::PermissionDenied]
+            .into_iter()
+            .map(|kind| CleanErrorEntry::new(Path::new("a"), &io::Error::from(kind)))
+            .collect(),
     };
 
     let status = result.display_status();
     assert!(status.contains("Files deleted: 10"));
     assert!(status.contains("Directories deleted: 2"));
-    assert!(status.contains("0.98")); // Should be approximately 0.98 MB
-    assert!(status.contains("Errors encountered: 2"));
-}
\ No newline at end of file
+    assert!(status.contains("Space freed: 1000.00 KB"));
+    assert!(status.contains("Errors encountered: 3 (2 permission, 1 missing)"));
+}
+/// Directory below `root` whose path is longer than `MAX_PATH`, holding a 100 byte file
+#[cfg(windows)]
+fn long_path_tree(root: &std::path::Path) -> std::path::PathBuf {
+    let mut deep = root.to_path_buf();
+    while deep.as_os_str().len() <= 300 {
+        deep.push("node_modules_with_a_long_name");
+    }
+    fs::create_dir_all(&deep).unwrap();
+    fs::write(deep.join("index.js"), vec![0u8; 100]).unwrap();
+    deep
+}
+
+#[cfg(windows)]
+#[test]
+fn test_get_dir_size_reaches_past_max_path() {
+    let temp_dir = TempDir::new().unwrap();
+    let deep = long_path_tree(temp_dir.path());
+    assert!(deep.join("index.js").as_os_str().len() > 260);
+
+    assert_eq!(get_dir_size(temp_dir.path()).unwrap(), 100);
+}
+
+#[cfg(windows)]
+#[test]
+fn test_clean_directory_deletes_past_max_path() {
+    let temp_dir = TempDir::new().unwrap();
+    long_path_tree(temp_dir.path());
+
+    let result = clean_directory(temp_dir.path(), false).unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.dirs_deleted, 1);
+    assert_eq!(result.bytes_cleaned, 100);
+    assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+}
+
+#[cfg(windows)]
+#[test]
+fn test_clean_directory_leaves_junction_targets() {
+    use std::process::Command;
+
+    let temp_dir = TempDir::new().unwrap();
+    let target = temp_dir.path().join("target");
+    let dir = temp_dir.path().join("dir");
+    fs::create_dir_all(&target).unwrap();
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(target.join("keep.txt"), vec![0u8; 100]).unwrap();
+    fs::write(dir.join("file.txt"), vec![0u8; 10]).unwrap();
+    let status = Command::new("cmd").arg("/C").arg("mklink").arg("/J").arg(dir.join("link")).arg(&target).status().unwrap();
+    assert!(status.success());
+
+    assert_eq!(get_dir_size(&dir).unwrap(), 10);
+    let result = clean_directory(&dir, false).unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files_deleted, 2);
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    assert!(target.join("keep.txt").exists());
+}
+
+fn item(id: &str, cleanup_type: CleanupType) -> CleanupItem {
+    CleanupItem {
+        id: id.to_string(),
+        name: id.to_string(),
+        description: String::new(),
+        cleanup_type,
+        category: Category::System,
+        risk: RiskLevel::Safe,
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+/// A cache directory, a temp directory with one temp file among other files, and a
+/// deselected directory, each in its own tempdir
+fn cycle_fixture(root: &Path) -> Cleaner {
+    let (cache, temp, kept) = (root.join("cache"), root.join("temp"), root.join("kept"));
+    fs::create_dir_all(cache.join("nested")).unwrap();
+    fs::write(cache.join("a.bin"), vec![0u8; 100]).unwrap();
+    fs::write(cache.join("nested/b.bin"), vec![0u8; 200]).unwrap();
+    fs::create_dir_all(&temp).unwrap();
+    fs::write(temp.join("setup.tmp"), vec![0u8; 50]).unwrap();
+    fs::write(temp.join("notes.txt"), vec![0u8; 10]).unwrap();
+    fs::create_dir_all(&kept).unwrap();
+    fs::write(kept.join("c.bin"), vec![0u8; 400]).unwrap();
+
+    let mut unselected = item("kept", CleanupType::Directory(kept));
+    unselected.enabled = false;
+    Cleaner::new(vec![
+        item("cache", CleanupType::Directory(cache)),
+        item("temp", CleanupType::TempFiles(temp)),
+        unselected,
+    ])
+}
+
+#[test]
+fn test_cleaner_scans_then_cleans_enabled_items() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let cleaner = cycle_fixture(root);
+
+    let scanned = cleaner.scan_all();
+    let ids: Vec<&str> = scanned.items.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(ids, vec!["cache", "temp"]);
+    assert_eq!(scanned.total_files(), 3);
+    assert_eq!(scanned.total_bytes(), 350);
+
+    let cleaned = cleaner.clean_enabled(false);
+    assert_eq!(cleaned.failed(), 0);
+    assert_eq!(cleaned.errors().count(), 0);
+    assert_eq!(cleaned.total_files(), scanned.total_files());
+    assert_eq!(cleaned.total_bytes(), scanned.total_bytes());
+
+    assert_eq!(fs::read_dir(root.join("cache")).unwrap().count(), 0);
+    assert!(!root.join("temp/setup.tmp").exists());
+    assert!(root.join("temp/notes.txt").exists());
+    assert!(root.join("kept/c.bin").exists());
+
+    // Everything is gone, so a second round finds nothing
+    assert_eq!(cleaner.scan_all().total_bytes(), 0);
+}
+
+#[test]
+fn test_cleaner_dry_run_deletes_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let cleaner = cycle_fixture(root);
+
+    let mut seen = Vec::new();
+    let report = cleaner.clean_enabled_with(true, |index, outcome| {
+        seen.push((cleaner.items()[index].id.clone(), outcome.as_ref().map(|result| result.size_bytes).ok()));
+    });
+
+    assert_eq!(seen, vec![("cache".to_string(), Some(300)), ("temp".to_string(), Some(50))]);
+    assert_eq!(report.total_bytes(), 350);
+    assert!(root.join("cache/nested/b.bin").exists());
+    assert!(root.join("temp/setup.tmp").exists());
+}
+
+#[test]
+fn test_cleaner_enable_and_disable() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut cleaner = cycle_fixture(temp_dir.path());
+    cleaner.items_mut()[0].status = ItemStatus::PathMissing;
+
+    assert!(cleaner.enable("kept"));
+    assert!(cleaner.disable("temp"));
+    assert!(!cleaner.enable("cache"), "unavailable items stay unselected");
+    assert!(!cleaner.enable("no_such_item"));
+    assert!(!cleaner.disable("no_such_item"));
+
+    let enabled: Vec<&str> = cleaner.items().iter().filter(|item| item.enabled).map(|item| item.id.as_str()).collect();
+    assert_eq!(enabled, vec!["cache", "kept"]);
+    cleaner.disable("cache");
+    assert_eq!(cleaner.scan_all().total_bytes(), 400);
+}
+
+#[test]
+fn test_cleaner_report_serializes_totals_and_failures() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut cleaner = cycle_fixture(temp_dir.path());
+    cleaner.items_mut()[2] = item("root", CleanupType::Directory(Path::new("/").to_path_buf()));
+    cleaner.set_options(CleanOptions::new());
+
+    // A volume root is refused before it is scanned
+    let report = cleaner.clean_enabled(true);
+    assert_eq!(report.failed(), 1);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["total_files"], 3);
+    assert_eq!(json["total_bytes"], 350);
+    assert_eq!(json["failed_items"], 1);
+    assert_eq!(json["failed_entries"], 0);
+    assert!(json["duration_ms"].is_u64());
+    let root = json["items"].as_array().unwrap().iter().find(|item| item["id"] == "root").unwrap();
+    assert_eq!(root["error"]["code"], "E_PROTECTED_PATH");
+    assert!(root.get("result").is_none());
+    assert_eq!(json["items"][0]["result"]["files"], 2);
+}
@@ -0,0 +1,135 @@
+use clean_rs::cleanup_items::UNREADABLE_DIRS_LABEL;
+use clean_rs::large_files::{large_files_item, largest_files, LARGE_FILES_LIMIT};
+use clean_rs::{ItemStatus, RiskLevel};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn write(root: &Path, relative: &str, size: usize) -> PathBuf {
+    let path = root.join(relative);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, vec![0u8; size]).unwrap();
+    path
+}
+
+#[test]
+fn test_largest_files_keeps_the_largest_in_order() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    for i in 1..=LARGE_FILES_LIMIT + 10 {
+        write(root, &format!("dir{}/file{}.bin", i % 7, i), i * 100);
+    }
+
+    let report = largest_files(&[root.to_path_buf()], &[], LARGE_FILES_LIMIT);
+    assert_eq!(report.files.len(), LARGE_FILES_LIMIT);
+    assert_eq!(report.unreadable_dirs, 0);
+    assert_eq!(report.files[0].size, (LARGE_FILES_LIMIT as u64 + 10) * 100);
+    assert_eq!(report.files[LARGE_FILES_LIMIT - 1].size, 1100);
+    assert!(report.files.windows(2).all(|pair| pair[0].size >= pair[1].size));
+    assert!(report.files[0].accessed.is_some());
+    assert_eq!(report.files[0].idle_days(), Some(0));
+
+    let few = largest_files(&[root.to_path_buf()], &[], 3);
+    assert_eq!(few.files.len(), 3);
+    assert_eq!(few.files[2].size, (LARGE_FILES_LIMIT as u64 + 8) * 100);
+}
+
+#[test]
+fn test_largest_files_skips_excluded_dirs_and_missing_roots() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    write(root, "Videos/movie.mkv", 4000);
+    write(root, "Library/Caches/huge.bin", 9000);
+    write(root, ".cache/hidden.bin", 2000);
+
+    let roots = [root.to_path_buf(), root.join("missing")];
+    let report = largest_files(&roots, &[root.join("Library")], 10);
+    let paths: Vec<&Path> = report.files.iter().map(|file| file.path.as_path()).collect();
+    assert_eq!(paths, [root.join("Videos/movie.mkv"), root.join(".cache/hidden.bin")]);
+    assert_eq!(report.unreadable_dirs, 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_largest_files_does_not_follow_symlinks() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("home");
+    let outside = write(temp_dir.path(), "outside/big.bin", 5000);
+    write(&root, "small.bin", 10);
+    std::os::unix::fs::symlink(&outside, root.join("link.bin")).unwrap();
+    std::os::unix::fs::symlink(outside.parent().unwrap(), root.join("linked_dir")).unwrap();
+
+    let report = largest_files(std::slice::from_ref(&root), &[], 10);
+    assert_eq!(report.files.len(), 1);
+    assert_eq!(report.files[0].path, root.join("small.bin"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unreadable_dirs_are_counted() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    write(root, "open/file.bin", 100);
+    let locked = root.join("locked");
+    write(root, "locked/secret.bin", 5000);
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+    // Root can read the directory anyway
+    let readable = fs::read_dir(&locked).is_ok();
+
+    let item = large_files_item(root);
+    let result = item.scan();
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+    if readable {
+        return;
+    }
+
+    assert_eq!(result.files, 1);
+    let last = result.breakdown.last().unwrap();
+    assert_eq!(last.label, UNREADABLE_DIRS_LABEL);
+    assert_eq!(last.note, "跳过 1 个目录");
+}
+
+#[test]
+fn test_large_files_item_never_deletes() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let big = write(home, "Downloads/installer.dmg", 3000);
+    let small = write(home, "Documents/notes.txt", 100);
+    write(home, "Projects/target/build.bin", 2000);
+
+    let mut item = large_files_item(home);
+    assert_eq!(item.id, "large_files");
+    assert!(!item.enabled);
+    assert_eq!(item.risk, RiskLevel::Safe);
+    assert_eq!(item.validate(), ItemStatus::Ready);
+    item.options.excluded_paths = Some(vec![home.join("Projects")]);
+
+    let estimate = item.estimate();
+    assert!(estimate.has_data);
+    assert!(estimate.report_only);
+
+    let scan = item.scan();
+    assert!(scan.report_only);
+    assert_eq!(scan.files, 2);
+    assert_eq!(scan.size_bytes, 3100);
+    assert_eq!(scan.breakdown.len(), 2);
+    assert_eq!(scan.breakdown[0].label, big.display().to_string());
+    assert_eq!(scan.breakdown[0].note, "0 天前访问");
+
+    let cleaned = item.clean();
+    assert!(cleaned.report_only);
+    assert_eq!(cleaned.files, scan.files);
+    assert_eq!(cleaned.size_bytes, scan.size_bytes);
+    assert_eq!(cleaned.breakdown, scan.breakdown);
+    assert!(big.exists());
+    assert!(small.exists());
+
+    let json = serde_json::to_value(&cleaned).unwrap();
+    assert_eq!(json["report_only"], true);
+    assert_eq!(json["breakdown"][0]["size_bytes"], 3000);
+
+    item.options.project_roots = Some(vec![home.join("missing")]);
+    assert_eq!(item.validate(), ItemStatus::PathMissing);
+}
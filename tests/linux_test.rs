@@ -0,0 +1,644 @@
+use clean_rs::linux::{
+    apt_cache_item, deb_file_name, dnf_cache_item, flatpak_cache_dirs, flatpak_cache_item, installed_deb_names,
+    journal_item, pacman_cache_item, pacman_vercmp, parse_flatpak_list, parse_flatpak_size, parse_journal_usage,
+    parse_pacman_file_name, parse_snap_list, parse_vacuumed, rpm_cache_repos, rpm_cache_root, shared_temp_item,
+    snap_item, unused_flatpak_runtimes, AptArchives, FlatpakRuntimes, Journal, PacmanCache, PacmanPackage, SharedTemp,
+    SnapRevision,
+};
+use clean_rs::{ItemOptions, ItemStatus, RiskLevel};
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const DPKG_STATUS: &str = "\
+Package: bash
+Status: install ok installed
+Architecture: amd64
+Version: 5.2.15-2+b2
+
+Package: libc6
+Status: install ok installed
+Architecture: amd64
+Version: 2.36-9+deb12u4
+Description: GNU C Library
+ multi-line description
+
+Package: vim
+Status: deinstall ok config-files
+Architecture: amd64
+Version: 2:9.0.1378-2
+";
+
+/// apt archives in `root` with two bash versions, libc, vim and a partial download
+fn apt_archives(root: &Path) -> AptArchives {
+    let dir = root.join("archives");
+    fs::create_dir_all(dir.join("partial")).unwrap();
+    for (name, size) in [
+        ("bash_5.2.15-2+b2_amd64.deb", 100),
+        ("bash_5.2.15-2_amd64.deb", 200),
+        ("libc6_2.36-9+deb12u4_amd64.deb", 400),
+        ("vim_2%3a9.0.1378-2_amd64.deb", 800),
+        ("lock", 0),
+        ("partial/git_1%3a2.39.2-1.1_amd64.deb", 50),
+    ] {
+        fs::write(dir.join(name), vec![0u8; size]).unwrap();
+    }
+    let status = root.join("status");
+    fs::write(&status, DPKG_STATUS).unwrap();
+    AptArchives::new(dir, status)
+}
+
+#[test]
+fn test_installed_deb_names_skip_removed_packages() {
+    assert_eq!(deb_file_name("vim", "2:9.0.1378-2", "amd64"), "vim_2%3a9.0.1378-2_amd64.deb");
+    let mut names: Vec<_> = installed_deb_names(DPKG_STATUS).into_iter().collect();
+    names.sort();
+    assert_eq!(names, ["bash_5.2.15-2+b2_amd64.deb", "libc6_2.36-9+deb12u4_amd64.deb"]);
+}
+
+#[test]
+fn test_apt_cache_scan_reports_packages_and_partial_downloads() {
+    let temp_dir = TempDir::new().unwrap();
+    let item = apt_cache_item(apt_archives(temp_dir.path()), &[]);
+    assert_eq!(item.validate(), ItemStatus::Ready);
+
+    let result = item.scan();
+    assert_eq!(result.files, 5);
+    assert_eq!(result.size_bytes, 1550);
+    let sizes: Vec<_> = result.breakdown.iter().map(|entry| entry.size_bytes).collect();
+    assert_eq!(sizes, [1500, 50]);
+}
+
+#[test]
+fn test_apt_cache_keeps_installed_versions() {
+    let temp_dir = TempDir::new().unwrap();
+    let archives = apt_archives(temp_dir.path());
+    let dir = archives.dir.clone();
+    let mut item = apt_cache_item(archives, &[]);
+    item.options = ItemOptions {
+        keep_installed: Some(true),
+        ..Default::default()
+    };
+
+    assert_eq!(item.scan().size_bytes, 1050);
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files, 3);
+    assert!(dir.join("bash_5.2.15-2+b2_amd64.deb").exists());
+    assert!(dir.join("libc6_2.36-9+deb12u4_amd64.deb").exists());
+    assert!(!dir.join("bash_5.2.15-2_amd64.deb").exists());
+    assert!(!dir.join("vim_2%3a9.0.1378-2_amd64.deb").exists());
+    assert!(dir.join("partial").is_dir());
+    assert!(dir.join("lock").exists());
+}
+
+#[test]
+fn test_apt_cache_clean_ignores_unheld_locks() {
+    let temp_dir = TempDir::new().unwrap();
+    let archives = apt_archives(temp_dir.path());
+    let dir = archives.dir.clone();
+    let lock = dir.join("lock");
+    let item = apt_cache_item(archives, &[lock.to_str().unwrap()]);
+
+    let result = item.clean();
+    assert_eq!(result.skip_reason, None);
+    assert_eq!(result.files, 5);
+    assert!(lock.exists());
+    assert_eq!(fs::read_dir(dir.join("partial")).unwrap().count(), 0);
+}
+
+/// Write `files` below `root`
+fn write_files(root: &Path, files: &[(&str, usize)]) {
+    for (relative, size) in files {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, vec![0u8; *size]).unwrap();
+    }
+}
+
+#[test]
+fn test_rpm_cache_repos_in_dnf_and_yum_layouts() {
+    let temp_dir = TempDir::new().unwrap();
+    let dnf = temp_dir.path().join("dnf");
+    let yum = temp_dir.path().join("yum");
+    write_files(
+        &dnf,
+        &[
+            ("fedora-1a2b/packages/bash-5.2.26-3.fc40.x86_64.rpm", 10),
+            ("updates-3c4d/repodata/repomd.xml", 10),
+            ("fedora.solv", 10),
+        ],
+    );
+    write_files(&yum, &[("x86_64/7/base/packages/bash.rpm", 10), ("x86_64/7/timedhosts", 10)]);
+
+    let roots = [dnf.join("missing"), dnf.clone(), yum.clone()].map(|root| root.to_string_lossy().into_owned());
+    let roots: Vec<&str> = roots.iter().map(String::as_str).collect();
+    assert_eq!(rpm_cache_root(&roots), Some(dnf.clone()));
+    assert_eq!(rpm_cache_repos(&dnf), [dnf.join("fedora-1a2b"), dnf.join("updates-3c4d")]);
+    assert_eq!(rpm_cache_repos(&yum), [yum.join("x86_64/7/base")]);
+}
+
+#[test]
+fn test_dnf_cache_lists_repos_and_keeps_layout() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    write_files(
+        root,
+        &[
+            ("fedora-1a2b/packages/bash-5.2.26-3.fc40.x86_64.rpm", 300),
+            ("fedora-1a2b/repodata/primary.xml.zst", 100),
+            ("updates-3c4d/repodata/repomd.xml", 50),
+            ("metadata_lock.pid", 5),
+        ],
+    );
+    let item = dnf_cache_item(root, &[]);
+
+    let scan = item.scan();
+    let sizes: Vec<_> = scan.breakdown.iter().map(|entry| (entry.label.clone(), entry.size_bytes)).collect();
+    assert_eq!(
+        sizes,
+        [
+            (root.join("fedora-1a2b").display().to_string(), 400),
+            (root.join("updates-3c4d").display().to_string(), 50),
+        ]
+    );
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files, 3);
+    assert!(root.join("fedora-1a2b/packages").is_dir());
+    assert!(root.join("updates-3c4d/repodata").is_dir());
+    assert!(!root.join("fedora-1a2b/repodata/primary.xml.zst").exists());
+    assert!(root.join("metadata_lock.pid").exists());
+}
+
+#[test]
+fn test_dnf_cache_is_not_cleaned_while_pid_lock_is_held() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("cache");
+    write_files(&root, &[("fedora-1a2b/packages/bash.rpm", 300)]);
+    let lock = temp_dir.path().join("download_lock.pid");
+    fs::write(&lock, format!("{}\n", std::process::id())).unwrap();
+    let item = dnf_cache_item(&root, &[lock.to_str().unwrap()]);
+
+    assert_eq!(item.scan().size_bytes, 300);
+    let result = item.clean();
+    assert!(result.skip_reason.is_some());
+    assert!(root.join("fedora-1a2b/packages/bash.rpm").exists());
+
+    // A pid file left behind by a process that is gone does not block cleaning
+    fs::write(&lock, "4294967295").unwrap();
+    assert_eq!(item.clean().files, 1);
+}
+
+#[test]
+fn test_parse_pacman_file_names() {
+    let package = |name: &str, version: &str, arch: &str| PacmanPackage {
+        name: name.to_string(),
+        version: version.to_string(),
+        arch: arch.to_string(),
+    };
+    assert_eq!(
+        parse_pacman_file_name("bash-5.2.026-2-x86_64.pkg.tar.zst"),
+        Some(package("bash", "5.2.026-2", "x86_64"))
+    );
+    assert_eq!(
+        parse_pacman_file_name("python-setuptools-1:69.1.0-1-any.pkg.tar.zst"),
+        Some(package("python-setuptools", "1:69.1.0-1", "any"))
+    );
+    assert_eq!(
+        parse_pacman_file_name("lib32-gcc-libs-13.2.1-5-x86_64.pkg.tar.xz"),
+        Some(package("lib32-gcc-libs", "13.2.1-5", "x86_64"))
+    );
+    assert_eq!(package("bash", "5.2.026-2", "x86_64").local_name(), "bash-5.2.026-2");
+    assert_eq!(parse_pacman_file_name("bash-5.2.026-2-x86_64.pkg.tar.zst.sig"), None);
+    assert_eq!(parse_pacman_file_name("bash-5.2.026-x86_64.pkg.tar.zst"), None);
+    assert_eq!(parse_pacman_file_name("download-XYZ.part"), None);
+}
+
+#[test]
+fn test_pacman_vercmp_orders_like_pacman() {
+    assert_eq!(pacman_vercmp("1.10-1", "1.9-1"), Ordering::Greater);
+    assert_eq!(pacman_vercmp("1.0-2", "1.0-10"), Ordering::Less);
+    assert_eq!(pacman_vercmp("1:1.0-1", "2.0-1"), Ordering::Greater);
+    assert_eq!(pacman_vercmp("1.0.1-1", "1.0-1"), Ordering::Greater);
+    assert_eq!(pacman_vercmp("1.0a-1", "1.0-1"), Ordering::Less);
+    assert_eq!(pacman_vercmp("1.0-1", "1.0-1"), Ordering::Equal);
+}
+
+#[test]
+fn test_pacman_cache_keeps_newest_and_installed_versions() {
+    let temp_dir = TempDir::new().unwrap();
+    let pkg = temp_dir.path().join("pkg");
+    write_files(
+        &pkg,
+        &[
+            ("bash-5.1.016-1-x86_64.pkg.tar.zst", 100),
+            ("bash-5.1.016-1-x86_64.pkg.tar.zst.sig", 1),
+            ("bash-5.2.015-1-x86_64.pkg.tar.zst", 200),
+            ("bash-5.2.026-2-x86_64.pkg.tar.zst", 300),
+            ("bash-5.2.026-1-x86_64.pkg.tar.zst", 400),
+            ("python-setuptools-1:69.1.0-1-any.pkg.tar.zst", 10),
+            ("python-setuptools-70.0.0-1-any.pkg.tar.zst", 20),
+            ("python-setuptools-68.0.0-1-any.pkg.tar.zst", 30),
+        ],
+    );
+    let local = temp_dir.path().join("local");
+    fs::create_dir_all(local.join("bash-5.1.016-1")).unwrap();
+    let item = pacman_cache_item(PacmanCache::new(&pkg, &local), "/nonexistent/db.lck");
+
+    // bash 5.1.016-1 is installed, setuptools 69 carries an epoch and is the newest
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 230);
+    let labels: Vec<_> = scan.breakdown.iter().map(|entry| (entry.label.as_str(), entry.size_bytes)).collect();
+    assert_eq!(labels, [("bash", 200), ("python-setuptools", 30)]);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files, 2);
+    assert!(pkg.join("bash-5.1.016-1-x86_64.pkg.tar.zst.sig").exists());
+    assert!(!pkg.join("bash-5.2.015-1-x86_64.pkg.tar.zst").exists());
+    assert!(!pkg.join("python-setuptools-68.0.0-1-any.pkg.tar.zst").exists());
+}
+
+#[test]
+fn test_pacman_cache_keep_versions_option_and_lock() {
+    let temp_dir = TempDir::new().unwrap();
+    let pkg = temp_dir.path().join("pkg");
+    write_files(
+        &pkg,
+        &[
+            ("zstd-1.5.5-1-x86_64.pkg.tar.zst", 100),
+            ("zstd-1.5.5-1-x86_64.pkg.tar.zst.sig", 1),
+            ("zstd-1.5.6-1-x86_64.pkg.tar.zst", 200),
+        ],
+    );
+    let lock = temp_dir.path().join("db.lck");
+    fs::write(&lock, "").unwrap();
+    let mut item = pacman_cache_item(PacmanCache::new(&pkg, temp_dir.path().join("local")), lock.to_str().unwrap());
+    item.options = ItemOptions {
+        keep_versions: Some(1),
+        ..Default::default()
+    };
+
+    assert_eq!(item.scan().size_bytes, 101);
+    assert!(item.clean().skip_reason.is_some());
+    assert!(pkg.join("zstd-1.5.5-1-x86_64.pkg.tar.zst").exists());
+
+    fs::remove_file(&lock).unwrap();
+    assert_eq!(item.clean().files, 2);
+    assert_eq!(fs::read_dir(&pkg).unwrap().count(), 1);
+}
+
+#[test]
+fn test_parse_journalctl_output() {
+    let usage = "Archived and active journals take up 1.5G in the file system.\n";
+    assert_eq!(parse_journal_usage(usage), Some(1536 * 1024 * 1024));
+    assert_eq!(parse_journal_usage("No journal files were found.\n"), None);
+
+    let vacuum = "Deleted archived journal /var/log/journal/abc/system@1.journal (8.0M).\n\
+                  Vacuuming done, freed 8.0M of archived journals from /var/log/journal/abc.\n\
+                  Vacuuming done, freed 0B of archived journals from /run/log/journal.\n";
+    assert_eq!(parse_vacuumed(vacuum), 8 * 1024 * 1024);
+}
+
+/// Stand-in for journalctl reporting 800M of journals; vacuuming records its arguments in `vacuumed`
+#[cfg(unix)]
+fn fake_journalctl(dir: &Path) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let program = dir.join("journalctl");
+    let script = format!(
+        "#!/bin/sh\n\
+         case \"$1\" in\n\
+         --disk-usage) echo 'Archived and active journals take up 800.0M in the file system.' ;;\n\
+         *) echo \"$*\" > '{}'; echo 'Vacuuming done, freed 296.0M of archived journals from /var/log/journal.' >&2 ;;\n\
+         esac\n",
+        dir.join("vacuumed").display()
+    );
+    fs::write(&program, script).unwrap();
+    fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+    program
+}
+
+#[cfg(unix)]
+#[test]
+fn test_journal_scan_and_vacuum_through_journalctl() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut item = journal_item(Journal::new(fake_journalctl(temp_dir.path())));
+    assert_eq!(item.validate(), ItemStatus::Ready);
+
+    let scan = item.scan();
+    assert!(scan.errors.is_empty(), "{:?}", scan.errors);
+    assert_eq!(scan.size_bytes, 300 * 1024 * 1024);
+
+    item.options = ItemOptions {
+        vacuum_size: Some("200M".to_string()),
+        max_age_days: Some(30),
+        ..Default::default()
+    };
+    assert_eq!(item.scan().size_bytes, 600 * 1024 * 1024);
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 296 * 1024 * 1024);
+    let args = fs::read_to_string(temp_dir.path().join("vacuumed")).unwrap();
+    assert_eq!(args.trim(), "--vacuum-size=200M --vacuum-time=30d");
+}
+
+#[test]
+fn test_journal_without_journalctl_is_missing() {
+    let item = journal_item(Journal::new("/nonexistent/journalctl"));
+    assert_eq!(item.validate(), ItemStatus::PathMissing);
+    assert!(!item.scan().errors.is_empty());
+}
+
+/// Date `path` (a file or directory) `age_days` back
+#[cfg(unix)]
+fn set_age(path: &Path, age_days: u32) {
+    use std::fs::{File, FileTimes};
+    use std::time::{Duration, SystemTime};
+
+    let time = SystemTime::now() - Duration::from_secs(24 * 60 * 60) * age_days;
+    let times = FileTimes::new().set_modified(time).set_accessed(time);
+    File::open(path).unwrap().set_times(times).unwrap();
+}
+
+/// A shared temp fixture with old files, a socket, a FIFO and session directories, returning the uid owning it
+#[cfg(unix)]
+fn shared_temp_fixture(tmp: &Path) -> u32 {
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::net::UnixListener;
+    use std::process::Command;
+
+    write_files(
+        tmp,
+        &[
+            ("old.txt", 10),
+            ("new.txt", 20),
+            ("build-1234/out.o", 30),
+            ("fresh-dir/old.o", 40),
+            ("tmux-dir/keep", 1),
+            (".X11-unix/X0", 0),
+            ("systemd-private-abc-colord.service-x/tmp/data", 50),
+            ("gradle.lock", 1),
+        ],
+    );
+    UnixListener::bind(tmp.join("ssh-agent.sock")).unwrap();
+    UnixListener::bind(tmp.join("tmux-dir/default")).unwrap();
+    assert!(Command::new("mkfifo").arg(tmp.join("pipe")).status().unwrap().success());
+    for path in [
+        "old.txt",
+        "build-1234/out.o",
+        "build-1234",
+        "fresh-dir/old.o",
+        "tmux-dir/keep",
+        "tmux-dir",
+        ".X11-unix",
+        "systemd-private-abc-colord.service-x/tmp/data",
+        "systemd-private-abc-colord.service-x",
+        "gradle.lock",
+    ] {
+        set_age(&tmp.join(path), 30);
+    }
+    fs::metadata(tmp).unwrap().uid()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shared_temp_keeps_sockets_sessions_and_recent_entries() {
+    let temp_dir = TempDir::new().unwrap();
+    let tmp = temp_dir.path();
+    let uid = shared_temp_fixture(tmp);
+    let item = shared_temp_item("temp_files", SharedTemp::new(tmp, Some(uid)), 2);
+
+    let temp = SharedTemp::new(tmp, Some(uid));
+    assert_eq!(temp.stale_entries(2), [tmp.join("build-1234"), tmp.join("old.txt")]);
+    assert_eq!(item.scan().size_bytes, 40);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files, 2);
+    for kept in [
+        "new.txt",
+        "fresh-dir/old.o",
+        "tmux-dir/default",
+        ".X11-unix/X0",
+        "systemd-private-abc-colord.service-x/tmp/data",
+        "gradle.lock",
+        "ssh-agent.sock",
+        "pipe",
+    ] {
+        assert!(tmp.join(kept).symlink_metadata().is_ok(), "{} was deleted", kept);
+    }
+    assert!(!tmp.join("build-1234").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shared_temp_leaves_other_users_entries() {
+    let temp_dir = TempDir::new().unwrap();
+    let tmp = temp_dir.path();
+    let uid = shared_temp_fixture(tmp);
+
+    // Entries all belong to `uid`, so another user sees nothing to clean
+    let other = shared_temp_item("var_tmp", SharedTemp::new(tmp, Some(uid.wrapping_add(1))), 2);
+    assert_eq!(other.scan().size_bytes, 0);
+    assert_eq!(other.clean().files, 0);
+    assert!(tmp.join("old.txt").exists());
+
+    // Root cleans every user's old entries, with the same exclusions
+    let everyone = SharedTemp::new(tmp, None);
+    assert_eq!(everyone.stale_entries(2), [tmp.join("build-1234"), tmp.join("old.txt")]);
+    assert_eq!(everyone.stale_entries(60), Vec::<PathBuf>::new());
+}
+
+const FLATPAK_RUNTIMES: &str = "org.gnome.Platform/x86_64/45\t1.2 GB\n\
+org.gnome.Platform.Locale/x86_64/45\t12.1 kB\n\
+org.gnome.Platform/x86_64/44\t1.1 GB\n\
+org.gnome.Platform.Locale/x86_64/44\t10.0 kB\n\
+org.freedesktop.Platform.GL.default/x86_64/23.08\t350.5 MB\n\
+org.kde.Platform/x86_64/5.15-23.08\t400 MB\n";
+
+const FLATPAK_APPS: &str = "org.gnome.Platform/x86_64/45\norg.gnome.Platform/x86_64/45\n";
+
+#[test]
+fn test_parse_flatpak_sizes_and_lists() {
+    assert_eq!(parse_flatpak_size("1.2 GB"), Some(1_200_000_000));
+    assert_eq!(parse_flatpak_size("12.1 kB"), Some(12_100));
+    assert_eq!(parse_flatpak_size("512 bytes"), Some(512));
+    assert_eq!(parse_flatpak_size("lots"), None);
+
+    let runtimes = parse_flatpak_list(FLATPAK_RUNTIMES);
+    assert_eq!(runtimes.len(), 6);
+    assert_eq!(runtimes[4], ("org.freedesktop.Platform.GL.default/x86_64/23.08".to_string(), 350_500_000));
+    assert_eq!(parse_flatpak_list(FLATPAK_APPS)[0], ("org.gnome.Platform/x86_64/45".to_string(), 0));
+}
+
+#[test]
+fn test_unused_flatpak_runtimes_keep_used_runtimes_and_drivers() {
+    let app_runtimes = vec!["org.gnome.Platform/x86_64/45".to_string()];
+    let unused = unused_flatpak_runtimes(&parse_flatpak_list(FLATPAK_RUNTIMES), &app_runtimes);
+    let references: Vec<_> = unused.iter().map(|(reference, _)| reference.as_str()).collect();
+    assert_eq!(references, ["org.gnome.Platform/x86_64/44", "org.kde.Platform/x86_64/5.15-23.08"]);
+}
+
+#[test]
+fn test_flatpak_cache_dirs_per_app() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    write_files(
+        home,
+        &[
+            (".var/app/org.gimp.GIMP/cache/gegl/swap", 100),
+            (".var/app/org.gimp.GIMP/config/GIMP/gimprc", 5),
+            (".var/app/com.spotify.Client/cache/data/a", 200),
+            (".var/app/org.example.NoCache/data/file", 5),
+        ],
+    );
+    let app = home.join(".var/app");
+    assert_eq!(flatpak_cache_dirs(home), [app.join("com.spotify.Client/cache"), app.join("org.gimp.GIMP/cache")]);
+
+    let item = flatpak_cache_item(home);
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 300);
+    assert_eq!(scan.breakdown.len(), 2);
+    item.clean();
+    assert!(app.join("org.gimp.GIMP/config/GIMP/gimprc").exists());
+    assert!(!app.join("org.gimp.GIMP/cache/gegl/swap").exists());
+}
+
+/// Stand-in for flatpak listing `FLATPAK_RUNTIMES`; `uninstall` drops the unused ones and
+/// records its arguments in `uninstalled`
+#[cfg(unix)]
+fn fake_flatpak(dir: &Path) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let runtimes = dir.join("runtimes");
+    fs::write(&runtimes, FLATPAK_RUNTIMES).unwrap();
+    fs::write(dir.join("apps"), FLATPAK_APPS).unwrap();
+    let program = dir.join("flatpak");
+    let script = format!(
+        "#!/bin/sh\n\
+         cd '{}'\n\
+         case \"$1 $2\" in\n\
+         \"list --runtime\") cat runtimes ;;\n\
+         \"list --app\") cat apps ;;\n\
+         \"uninstall \"*) echo \"$*\" > uninstalled; grep -v -e /44 -e kde runtimes > kept; mv kept runtimes ;;\n\
+         *) exit 1 ;;\n\
+         esac\n",
+        dir.display()
+    );
+    fs::write(&program, script).unwrap();
+    fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+    program
+}
+
+#[cfg(unix)]
+#[test]
+fn test_flatpak_runtimes_scan_and_uninstall_unused() {
+    let temp_dir = TempDir::new().unwrap();
+    let item = FlatpakRuntimes::new(fake_flatpak(temp_dir.path())).item();
+    assert_eq!(item.risk, RiskLevel::Aggressive);
+    assert!(!item.enabled);
+    assert_eq!(item.validate(), ItemStatus::Ready);
+
+    let scan = item.scan();
+    assert!(scan.errors.is_empty(), "{:?}", scan.errors);
+    assert_eq!(scan.size_bytes, 1_500_000_000);
+    assert_eq!(scan.breakdown.len(), 2);
+    assert!(!temp_dir.path().join("uninstalled").exists());
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 1_500_010_000);
+    let args = fs::read_to_string(temp_dir.path().join("uninstalled")).unwrap();
+    assert_eq!(args.trim(), "uninstall --unused --noninteractive -y");
+
+    let missing = FlatpakRuntimes::new(temp_dir.path().join("missing")).item();
+    assert_eq!(missing.validate(), ItemStatus::PathMissing);
+}
+
+const SNAP_LIST: &str = "\
+Name     Version          Rev    Tracking         Publisher   Notes
+core22   20240111         1122   latest/stable    canonical✓  base,disabled
+core22   20240408         1380   latest/stable    canonical✓  base
+firefox  124.0.2-1        4090   latest/stable/…  mozilla✓    disabled
+firefox  125.0.1-1        4173   latest/stable/…  mozilla✓    -
+";
+
+#[test]
+fn test_parse_snap_list_finds_disabled_revisions() {
+    let revisions = parse_snap_list(SNAP_LIST);
+    assert_eq!(revisions.len(), 4);
+    let disabled: Vec<_> = revisions.iter().filter(|revision| revision.disabled).map(SnapRevision::file_name).collect();
+    assert_eq!(disabled, ["core22_1122.snap", "firefox_4090.snap"]);
+}
+
+/// Stand-in for snap listing `SNAP_LIST`; `remove` appends its arguments to `removed`
+#[cfg(unix)]
+fn fake_snap(dir: &Path) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::write(dir.join("list"), SNAP_LIST).unwrap();
+    let program = dir.join("snap");
+    let script = format!(
+        "#!/bin/sh\n\
+         cd '{}'\n\
+         case \"$1\" in\n\
+         list) cat list ;;\n\
+         remove) echo \"$*\" >> removed ;;\n\
+         version) echo 'snap 2.61' ;;\n\
+         *) exit 1 ;;\n\
+         esac\n",
+        dir.display()
+    );
+    fs::write(&program, script).unwrap();
+    fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+    program
+}
+
+#[cfg(unix)]
+#[test]
+fn test_snap_item_removes_disabled_revisions_and_cache() {
+    let temp_dir = TempDir::new().unwrap();
+    let snapd = temp_dir.path().join("snapd");
+    write_files(
+        &snapd,
+        &[
+            ("snaps/core22_1122.snap", 100),
+            ("snaps/core22_1380.snap", 200),
+            ("snaps/firefox_4090.snap", 300),
+            ("snaps/firefox_4173.snap", 400),
+            ("cache/5f2a", 50),
+        ],
+    );
+    let item = snap_item(Some(fake_snap(temp_dir.path())), &snapd);
+    assert!(item.enabled);
+    assert!(item.requires_admin);
+    assert_eq!(item.validate(), ItemStatus::Ready);
+
+    let scan = item.scan();
+    assert!(scan.errors.is_empty(), "{:?}", scan.errors);
+    assert_eq!(scan.size_bytes, 450);
+    let labels: Vec<_> = scan.breakdown.iter().map(|entry| entry.label.clone()).collect();
+    assert_eq!(labels, ["core22 (1122)", "firefox (4090)", &snapd.join("cache").display().to_string()]);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 450);
+    let removed = fs::read_to_string(temp_dir.path().join("removed")).unwrap();
+    assert_eq!(removed, "remove core22 --revision 1122\nremove firefox --revision 4090\n");
+    assert!(!snapd.join("cache/5f2a").exists());
+    assert!(snapd.join("cache").is_dir());
+}
+
+#[test]
+fn test_snap_item_without_snap_is_disabled() {
+    let temp_dir = TempDir::new().unwrap();
+    let item = snap_item(None, temp_dir.path());
+    assert!(!item.enabled);
+
+    let missing = snap_item(Some(temp_dir.path().join("snap")), temp_dir.path());
+    assert_eq!(missing.validate(), ItemStatus::PathMissing);
+}
@@ -0,0 +1,233 @@
+use clean_rs::macos::{
+    device_support_item, device_support_version, logs_item, parse_unavailable_simulators, simulator_cache_dirs,
+    stale_device_support, user_cache_dirs, user_caches_item, xcode_items, CACHES_MIN_AGE_DAYS, LOGS_MIN_AGE_DAYS,
+};
+use clean_rs::{Category, ItemOptions, ItemStatus, RiskLevel};
+use std::fs::{self, File, FileTimes};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Create `files` below `root`, each last written `age_days` ago
+fn write_aged(root: &Path, files: &[(&str, usize)], age_days: u32) {
+    let time = SystemTime::now() - DAY * age_days;
+    for (relative, size) in files {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, vec![0u8; *size]).unwrap();
+        let times = FileTimes::new().set_modified(time).set_accessed(time);
+        File::options().write(true).open(&path).unwrap().set_times(times).unwrap();
+    }
+}
+
+#[test]
+fn test_user_caches_per_bundle_without_excluded_caches() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let caches = home.join("Library/Caches");
+    write_aged(
+        home,
+        &[
+            ("Library/Caches/com.spotify.client/Data/a", 100),
+            ("Library/Caches/com.microsoft.VSCode/b", 200),
+            ("Library/Caches/com.apple.Safari/Cache.db", 400),
+            ("Library/Caches/CloudKit/state", 800),
+            ("Library/Caches/stray.log", 5),
+        ],
+        3,
+    );
+    write_aged(home, &[("Library/Caches/com.spotify.client/Data/new", 50)], 0);
+    assert_eq!(user_cache_dirs(home), [caches.join("com.microsoft.VSCode"), caches.join("com.spotify.client")]);
+
+    let mut item = user_caches_item(home);
+    assert_eq!(item.category, Category::System);
+    assert!(item.enabled);
+    assert_eq!(item.min_age_days, CACHES_MIN_AGE_DAYS);
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 300);
+    assert_eq!(scan.breakdown.len(), 2);
+
+    item.options = ItemOptions {
+        min_age_days: Some(0),
+        ..Default::default()
+    };
+    assert_eq!(item.scan().size_bytes, 350);
+    item.clean();
+    assert!(caches.join("com.apple.Safari/Cache.db").exists());
+    assert!(caches.join("CloudKit/state").exists());
+    assert!(!caches.join("com.spotify.client/Data/a").exists());
+}
+
+#[test]
+fn test_logs_include_readable_diagnostic_reports_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path().join("home");
+    let reports = temp_dir.path().join("DiagnosticReports");
+    write_aged(&home, &[("Library/Logs/app/old.log", 100)], 10);
+    write_aged(&home, &[("Library/Logs/app/new.log", 200)], 1);
+    write_aged(&reports, &[("kernel.panic", 1000)], 30);
+
+    let missing = logs_item(&home, &temp_dir.path().join("missing"));
+    assert!(!missing.enabled);
+    assert_eq!(missing.scan().size_bytes, 100);
+
+    let item = logs_item(&home, &reports);
+    assert_eq!(item.min_age_days, LOGS_MIN_AGE_DAYS);
+    assert_eq!(item.validate(), ItemStatus::Ready);
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 1100);
+    assert_eq!(scan.breakdown.len(), 2);
+}
+
+#[test]
+fn test_denied_status_asks_for_full_disk_access_on_macos() {
+    let expected = if cfg!(target_os = "macos") {
+        ItemStatus::NeedsFullDiskAccess
+    } else {
+        ItemStatus::PermissionDenied
+    };
+    assert_eq!(ItemStatus::denied(), expected);
+}
+
+#[test]
+fn test_device_support_keeps_newest_version_per_platform() {
+    assert_eq!(device_support_version("17.4 (21E219)"), [17, 4]);
+    assert_eq!(device_support_version("iPhone15,2 17.4.1 (21E236)"), [17, 4, 1]);
+
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let xcode = home.join("Library/Developer/Xcode");
+    write_aged(
+        home,
+        &[
+            ("Library/Developer/Xcode/iOS DeviceSupport/16.7 (20H19)/Symbols/a", 100),
+            ("Library/Developer/Xcode/iOS DeviceSupport/iPhone14,2 17.4 (21E219)/Symbols/a", 200),
+            ("Library/Developer/Xcode/iOS DeviceSupport/iPhone15,2 17.4 (21E219)/Symbols/a", 300),
+            ("Library/Developer/Xcode/iOS DeviceSupport/17.0 (21A329)/Symbols/a", 400),
+            ("Library/Developer/Xcode/watchOS DeviceSupport/Watch6,1 10.4 (21T216)/Symbols/a", 500),
+            ("Library/Developer/Xcode/Archives/2024-03-01/App.xcarchive/dSYMs/App.dSYM", 600),
+        ],
+        3,
+    );
+    let ios = xcode.join("iOS DeviceSupport");
+    assert_eq!(stale_device_support(home), [ios.join("16.7 (20H19)"), ios.join("17.0 (21A329)")]);
+
+    let item = device_support_item(home);
+    assert_eq!(item.risk, RiskLevel::Moderate);
+    assert!(!item.enabled);
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 500);
+    assert_eq!(scan.breakdown.len(), 2);
+}
+
+#[test]
+fn test_xcode_items_never_touch_archives() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let developer = home.join("Library/Developer");
+    write_aged(
+        home,
+        &[
+            ("Library/Developer/Xcode/DerivedData/App-abcdef/Build/Products/app", 100),
+            ("Library/Developer/Xcode/DerivedData/Lib-123456/Index.noindex/db", 200),
+            ("Library/Developer/Xcode/Archives/2024-03-01/App.xcarchive/dSYMs/App.dSYM", 600),
+            ("Library/Developer/CoreSimulator/Caches/dyld/21E213/com.apple.CoreSimulator.SimRuntime.iOS-17-4/a", 30),
+            ("Library/Developer/CoreSimulator/Caches/dyld/20H10/com.apple.CoreSimulator.SimRuntime.iOS-16-4/a", 20),
+        ],
+        3,
+    );
+    let items = xcode_items(home, None);
+    let ids: Vec<_> = items.iter().map(|item| item.id.as_str()).collect();
+    assert_eq!(ids, ["xcode_derived_data", "xcode_device_support", "simulator_caches"]);
+    assert!(items[0].enabled);
+
+    let derived = items[0].scan();
+    assert_eq!(derived.size_bytes, 300);
+    assert_eq!(derived.breakdown.len(), 2);
+    let dyld = developer.join("CoreSimulator/Caches/dyld");
+    assert_eq!(simulator_cache_dirs(home), [dyld.join("20H10"), dyld.join("21E213")]);
+
+    for item in &items {
+        item.clean();
+    }
+    assert!(developer.join("Xcode/Archives/2024-03-01/App.xcarchive/dSYMs/App.dSYM").exists());
+    assert!(!developer.join("Xcode/DerivedData/App-abcdef/Build/Products/app").exists());
+}
+
+const SIMCTL_UNAVAILABLE: &str = r#"{
+  "devices" : {
+    "com.apple.CoreSimulator.SimRuntime.iOS-15-5" : [
+      {
+        "dataPath" : "DEVICES/1111/data",
+        "udid" : "1111",
+        "isAvailable" : false,
+        "name" : "iPhone 13",
+        "availabilityError" : "runtime profile not found"
+      }
+    ],
+    "com.apple.CoreSimulator.SimRuntime.iOS-17-4" : [
+      { "udid" : "2222", "isAvailable" : true, "name" : "iPhone 15", "dataPath" : "DEVICES/2222/data" }
+    ]
+  }
+}"#;
+
+#[test]
+fn test_parse_unavailable_simulators() {
+    let devices = parse_unavailable_simulators(SIMCTL_UNAVAILABLE);
+    assert_eq!(devices.len(), 1);
+    assert_eq!(devices[0].udid, "1111");
+    assert_eq!(devices[0].name, "iPhone 13");
+    assert!(parse_unavailable_simulators("not json").is_empty());
+}
+
+/// Stand-in for xcrun listing `SIMCTL_UNAVAILABLE` below `dir`; `delete` records its arguments
+#[cfg(unix)]
+fn fake_xcrun(dir: &Path) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let devices = dir.join("Devices");
+    fs::write(dir.join("list.json"), SIMCTL_UNAVAILABLE.replace("DEVICES", &devices.display().to_string())).unwrap();
+    let program = dir.join("xcrun");
+    let script = format!(
+        "#!/bin/sh\n\
+         cd '{}'\n\
+         case \"$2\" in\n\
+         list) cat list.json ;;\n\
+         delete) echo \"$*\" > deleted ;;\n\
+         help) ;;\n\
+         *) exit 1 ;;\n\
+         esac\n",
+        dir.display()
+    );
+    fs::write(&program, script).unwrap();
+    fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+    program
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unavailable_simulators_are_deleted_through_simctl() {
+    let temp_dir = TempDir::new().unwrap();
+    write_aged(
+        temp_dir.path(),
+        &[("Devices/1111/data/Containers/a", 700), ("Devices/1111/device.plist", 10), ("Devices/2222/data/a", 5)],
+        3,
+    );
+    let items = xcode_items(temp_dir.path(), Some(fake_xcrun(temp_dir.path())));
+    let item = items.last().unwrap();
+    assert_eq!(item.id, "unavailable_simulators");
+    assert_eq!(item.validate(), ItemStatus::Ready);
+
+    let scan = item.scan();
+    assert!(scan.errors.is_empty(), "{:?}", scan.errors);
+    assert_eq!(scan.size_bytes, 710);
+    assert_eq!(scan.breakdown[0].label, "iPhone 13");
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 710);
+    let deleted = fs::read_to_string(temp_dir.path().join("deleted")).unwrap();
+    assert_eq!(deleted.trim(), "simctl delete unavailable");
+}
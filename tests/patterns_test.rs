@@ -0,0 +1,73 @@
+use clean_rs::patterns::{default_temp_patterns, installer_patterns, matches_any};
+use clean_rs::TempPattern;
+
+/// Names that merely contain "temp"/"cache"/"tmp" and must never be treated as temp files
+const FALSE_POSITIVES: &[&str] = &[
+    "template_report.xlsx",
+    "cache_policy.md",
+    "temperature.csv",
+    "contemporary.docx",
+    "tmp_notes.txt",
+    "backup.tmpl",
+    "logbook.pdf",
+    "catalog.db",
+    "old_photos.zip",
+    "Cache",
+];
+
+const TEMP_NAMES: &[&str] = &[
+    "setup.tmp",
+    "UPPER.TMP",
+    "download.temp",
+    "settings.json.bak",
+    "config.old",
+    "install.log",
+    "~$report.docx",
+    "draft.txt~",
+    "Thumbs.db",
+    ".DS_Store",
+];
+
+#[test]
+fn test_default_patterns_reject_false_positives() {
+    let patterns = default_temp_patterns();
+    for name in FALSE_POSITIVES {
+        assert!(!matches_any(&patterns, name), "{} must not match", name);
+    }
+}
+
+#[test]
+fn test_default_patterns_match_temp_names() {
+    let patterns = default_temp_patterns();
+    for name in TEMP_NAMES {
+        assert!(matches_any(&patterns, name), "{} should match", name);
+    }
+}
+
+#[test]
+fn test_pattern_parsing_round_trip() {
+    let cases = [
+        ("*.tmp", TempPattern::Extension("tmp".to_string())),
+        ("~*", TempPattern::Prefix("~".to_string())),
+        ("*~", TempPattern::Suffix("~".to_string())),
+        ("Thumbs.db", TempPattern::Exact("Thumbs.db".to_string())),
+    ];
+    for (text, pattern) in cases {
+        assert_eq!(text.parse::<TempPattern>().unwrap(), pattern);
+        assert_eq!(pattern.to_string(), text);
+    }
+
+    assert!("*".parse::<TempPattern>().is_err());
+    assert!("a*b*c".parse::<TempPattern>().is_err());
+}
+
+#[test]
+fn test_installer_patterns_match_multi_part_extensions() {
+    let patterns = installer_patterns();
+    for name in ["setup.exe", "Tool.MSI", "Xcode_15.dmg", "ubuntu-24.04.iso", "photos.zip", "linux-6.8.TAR.GZ"] {
+        assert!(matches_any(&patterns, name), "{} should match", name);
+    }
+    for name in ["notes.txt", "backup.gz", "tar.gz", "archive.tar", "exe"] {
+        assert!(!matches_any(&patterns, name), "{} should not match", name);
+    }
+}
@@ -0,0 +1,482 @@
+#[cfg(all(unix, not(target_os = "macos")))]
+use std::fs;
+#[cfg(all(unix, not(target_os = "macos")))]
+use std::path::Path;
+#[cfg(all(unix, not(target_os = "macos")))]
+use tempfile::TempDir;
+
+/// Put `name` with `size` bytes into a FreeDesktop `trash`, with or without its info entry
+#[cfg(all(unix, not(target_os = "macos")))]
+fn trash_item(trash: &Path, name: &str, size: usize, with_info: bool) {
+    let file = trash.join("files").join(name);
+    fs::create_dir_all(file.parent().unwrap()).unwrap();
+    fs::write(&file, vec![0u8; size]).unwrap();
+    if with_info {
+        fs::create_dir_all(trash.join("info")).unwrap();
+        let info = format!("[Trash Info]\nPath=/home/user/{}\nDeletionDate=2026-01-01T10:00:00\n", name);
+        fs::write(trash.join("info").join(format!("{}.trashinfo", name)), info).unwrap();
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn test_empty_trash_removes_files_with_their_info() {
+    use clean_rs::platform::unix::{empty_trash, trash_usage};
+    use clean_rs::platform::RecycleBinInfo;
+
+    let temp_dir = TempDir::new().unwrap();
+    let trash = temp_dir.path().join("Trash");
+    trash_item(&trash, "report.pdf", 100, true);
+    trash_item(&trash, "notes.trashinfo", 30, true);
+    trash_item(&trash, "project/src/main.rs", 50, false);
+    fs::write(trash.join("info/project.trashinfo"), "[Trash Info]\nPath=/home/user/project\n").unwrap();
+    // Info left behind by an item whose file is already gone, and a file nobody recorded
+    fs::write(trash.join("info/gone.txt.trashinfo"), "[Trash Info]\nPath=/home/user/gone.txt\n").unwrap();
+    trash_item(&trash, "orphan.bin", 20, false);
+    fs::write(trash.join("info/README"), "not an info file").unwrap();
+    fs::write(trash.join("directorysizes"), "50 1767261600 project\n").unwrap();
+
+    assert_eq!(trash_usage(&trash), RecycleBinInfo { bytes: 200, items: 5 });
+
+    let freed = empty_trash(&trash);
+    assert_eq!(freed, RecycleBinInfo { bytes: 200, items: 5 });
+    assert_eq!(fs::read_dir(trash.join("files")).unwrap().count(), 0);
+    let left: Vec<_> = fs::read_dir(trash.join("info")).unwrap().flatten().map(|entry| entry.file_name()).collect();
+    assert_eq!(left, ["README"]);
+    assert!(!trash.join("directorysizes").exists());
+    assert_eq!(trash_usage(&trash), RecycleBinInfo::default());
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn test_info_is_kept_while_its_file_cannot_be_deleted() {
+    use clean_rs::platform::unix::empty_trash;
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let trash = temp_dir.path().join("Trash");
+    trash_item(&trash, "locked/data.bin", 10, false);
+    fs::create_dir_all(trash.join("info")).unwrap();
+    fs::write(trash.join("info/locked.trashinfo"), "[Trash Info]\nPath=/home/user/locked\n").unwrap();
+    let locked = trash.join("files/locked");
+    fs::set_permissions(trash.join("files"), fs::Permissions::from_mode(0o555)).unwrap();
+
+    let freed = empty_trash(&trash);
+    let file_left = locked.exists();
+    fs::set_permissions(trash.join("files"), fs::Permissions::from_mode(0o755)).unwrap();
+    // Root deletes the file anyway; the pairing only shows for other users
+    assert_eq!(freed.items, u64::from(!file_left));
+    assert_eq!(trash.join("info/locked.trashinfo").exists(), file_left);
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn test_mounts_are_parsed_with_escapes() {
+    use clean_rs::platform::unix::parse_mounts;
+    use std::path::PathBuf;
+
+    let mounts = parse_mounts(
+        "/dev/sda2 / ext4 rw,relatime 0 0\n\
+         proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0\n\
+         /dev/sdb1 /media/user/USB\\040Stick vfat rw,nosuid,uid=1000 0 0\n\
+         /dev/sr0 /media/user/CDROM iso9660 ro,nosuid 0 0\n",
+    );
+    assert_eq!(
+        mounts,
+        [
+            (PathBuf::from("/"), true),
+            (PathBuf::from("/proc"), true),
+            (PathBuf::from("/media/user/USB Stick"), true),
+            (PathBuf::from("/media/user/CDROM"), false),
+        ]
+    );
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn test_mount_kinds_tell_network_and_removable_volumes() {
+    use clean_rs::platform::unix::{parse_mount_kinds, volume_kind_in};
+    use clean_rs::platform::VolumeKind;
+    use std::path::PathBuf;
+
+    let mounts = parse_mount_kinds(
+        "/dev/nvme0n1p2 / ext4 rw,relatime 0 0\n\
+         /dev/nvme0n1p1 /boot/efi vfat rw,relatime 0 0\n\
+         fileserver:/export/home /home nfs4 rw,relatime,vers=4.2 0 0\n\
+         /dev/nvme0n1p3 /home/local ext4 rw,relatime 0 0\n\
+         //nas/profiles /mnt/profiles cifs rw,relatime,vers=3.1.1 0 0\n\
+         me@host:/srv /mnt/remote\\040dir fuse.sshfs rw,nosuid,nodev 0 0\n\
+         /dev/sdb1 /media/me/USB\\040Stick exfat rw,nosuid,nodev 0 0\n\
+         /dev/sdc1 /run/media/me/BACKUP ext4 rw,nosuid,nodev 0 0\n",
+    );
+    assert_eq!(mounts.len(), 8);
+    assert_eq!(mounts[6], (PathBuf::from("/media/me/USB Stick"), VolumeKind::Removable));
+
+    let kind = |path: &str| volume_kind_in(&mounts, Path::new(path));
+    assert_eq!(kind("/var/tmp"), VolumeKind::Local);
+    assert_eq!(kind("/boot/efi/EFI"), VolumeKind::Local);
+    assert_eq!(kind("/home/me/.cache"), VolumeKind::Network);
+    // The deepest mount point wins
+    assert_eq!(kind("/home/local/me/.cache"), VolumeKind::Local);
+    assert_eq!(kind("/mnt/profiles/me/AppData"), VolumeKind::Network);
+    assert_eq!(kind("/mnt/remote dir/cache"), VolumeKind::Network);
+    assert_eq!(kind("/media/me/USB Stick/DCIM"), VolumeKind::Removable);
+    assert_eq!(kind("/run/media/me/BACKUP"), VolumeKind::Removable);
+    assert_eq!(volume_kind_in(&[], Path::new("/home")), VolumeKind::Local);
+    assert!(VolumeKind::Removable.is_remote());
+    assert!(!VolumeKind::Local.is_remote());
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn test_volume_trashes_only_on_writable_mounts() {
+    use clean_rs::platform::unix::volume_trash_dirs;
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let volume = |name: &str, dirs: &[&str]| {
+        let mount = root.join(name);
+        fs::create_dir_all(&mount).unwrap();
+        for dir in dirs {
+            fs::create_dir_all(mount.join(dir)).unwrap();
+        }
+        mount
+    };
+    let usb = volume("usb", &[".Trash-1000", ".Trash-1001"]);
+    let cdrom = volume("cdrom", &[".Trash-1000"]);
+    let shared = volume("shared", &[".Trash/1000"]);
+    fs::set_permissions(shared.join(".Trash"), fs::Permissions::from_mode(0o1777)).unwrap();
+    let not_sticky = volume("not_sticky", &[".Trash/1000"]);
+    fs::set_permissions(not_sticky.join(".Trash"), fs::Permissions::from_mode(0o777)).unwrap();
+    let linked = volume("linked", &[]);
+    std::os::unix::fs::symlink(usb.join(".Trash-1000"), linked.join(".Trash-1000")).unwrap();
+
+    let mounts = [(usb.clone(), true), (cdrom, false), (shared.clone(), true), (not_sticky, true), (linked, true)];
+    assert_eq!(volume_trash_dirs(&mounts, 1000), [usb.join(".Trash-1000"), shared.join(".Trash/1000")]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_flat_trash_is_measured_and_emptied() {
+    use clean_rs::platform::unix::{dir_usage, empty_dir};
+    use clean_rs::platform::RecycleBinInfo;
+    use std::fs;
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let trash = temp_dir.path().join(".Trash");
+    fs::create_dir_all(trash.join("Old Project/src")).unwrap();
+    fs::write(trash.join("Old Project/src/main.rs"), vec![0u8; 40]).unwrap();
+    fs::write(trash.join("Old Project/README"), vec![0u8; 10]).unwrap();
+    fs::write(trash.join("photo.jpg"), vec![0u8; 100]).unwrap();
+    // An alias is counted by its own size and its target is left alone
+    fs::create_dir_all(temp_dir.path().join("kept")).unwrap();
+    fs::write(temp_dir.path().join("kept/data.bin"), vec![0u8; 500]).unwrap();
+    std::os::unix::fs::symlink("../kept", trash.join("alias")).unwrap();
+
+    assert_eq!(dir_usage(&trash).unwrap(), RecycleBinInfo { bytes: 157, items: 3 });
+    assert_eq!(empty_dir(&trash).unwrap(), RecycleBinInfo { bytes: 157, items: 3 });
+    assert!(trash.is_dir());
+    assert_eq!(fs::read_dir(&trash).unwrap().count(), 0);
+    assert!(temp_dir.path().join("kept/data.bin").exists());
+    assert!(dir_usage(&temp_dir.path().join("missing")).is_err());
+}
+
+#[test]
+fn test_drive_roots_follow_the_logical_drive_mask() {
+    use clean_rs::platform::drive_roots;
+    use std::path::PathBuf;
+
+    // C:, D: and Z:
+    let mask = (1 << 2) | (1 << 3) | (1 << 25);
+    assert_eq!(drive_roots(mask), [PathBuf::from("C:\\"), PathBuf::from("D:\\"), PathBuf::from("Z:\\")]);
+    assert!(drive_roots(0).is_empty());
+    assert_eq!(drive_roots(u32::MAX).len(), 26);
+}
+
+/// Header of a `$I` record deleted at 2026-01-01 00:00:00 UTC: version, size (1 234 567
+/// bytes) and `FILETIME`, as written by Explorer
+const INDEX_HEADER_V1: [u8; 24] = [
+    0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x87, 0xD6, 0x12, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x81, 0x92, 0xB1, 0x7A, 0xDC, 0x01,
+];
+
+/// UTF-16LE `path`, with its NUL
+fn utf16(path: &str) -> Vec<u8> {
+    path.encode_utf16().chain(std::iter::once(0)).flat_map(u16::to_le_bytes).collect()
+}
+
+/// Version 1 record: the path padded to 260 characters, 544 bytes in all
+fn index_record_v1(path: &str) -> Vec<u8> {
+    let mut record = INDEX_HEADER_V1.to_vec();
+    let mut name = utf16(path);
+    name.resize(520, 0);
+    record.extend(name);
+    record
+}
+
+/// Version 2 record: the path length in characters, NUL included, then the path
+fn index_record_v2(path: &str) -> Vec<u8> {
+    let mut record = INDEX_HEADER_V1.to_vec();
+    record[0] = 2;
+    let name = utf16(path);
+    record.extend(((name.len() / 2) as u32).to_le_bytes());
+    record.extend(name);
+    record
+}
+
+#[test]
+fn test_index_records_of_both_versions_are_parsed() {
+    use clean_rs::platform::recycle_bin::parse_index_record;
+    use std::path::PathBuf;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let deleted = UNIX_EPOCH + Duration::from_secs(1_767_225_600);
+    let v1 = index_record_v1("C:\\Users\\alice\\Documents\\report.docx");
+    assert_eq!(v1.len(), 544);
+    assert_eq!(
+        parse_index_record(&v1),
+        Some((PathBuf::from("C:\\Users\\alice\\Documents\\report.docx"), Some(deleted), 1_234_567))
+    );
+
+    // Windows 10 records paths longer than MAX_PATH
+    let long = format!("D:\\VMs\\{}disk.vhdx", "nested\\".repeat(40));
+    let v2 = index_record_v2(&long);
+    assert_eq!(parse_index_record(&v2), Some((PathBuf::from(&long), Some(deleted), 1_234_567)));
+
+    assert_eq!(parse_index_record(&v1[..300]), None);
+    assert_eq!(parse_index_record(&v2[..v2.len() - 2]), None);
+    let mut unknown = v2.clone();
+    unknown[0] = 3;
+    assert_eq!(parse_index_record(&unknown), None);
+    let mut no_time = index_record_v2("C:\\a.txt");
+    no_time[16..24].fill(0);
+    assert_eq!(parse_index_record(&no_time).unwrap().1, None);
+}
+
+#[test]
+fn test_index_dir_lists_items_whose_data_is_still_there() {
+    use clean_rs::platform::recycle_bin::read_index_dir;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let bin = temp_dir.path();
+    let mut small = index_record_v2("C:\\notes.txt");
+    small[8..16].copy_from_slice(&10u64.to_le_bytes());
+    fs::write(bin.join("$IAB12CD.txt"), small).unwrap();
+    fs::write(bin.join("$RAB12CD.txt"), "0123456789").unwrap();
+    fs::write(bin.join("$IEF34GH.docx"), index_record_v1("C:\\report.docx")).unwrap();
+    fs::create_dir_all(bin.join("$REF34GH.docx")).unwrap();
+    // Data already gone, a damaged record and the folder's own settings
+    fs::write(bin.join("$IGONE12.bin"), index_record_v2("C:\\gone.bin")).unwrap();
+    fs::write(bin.join("$IBROKEN.bin"), [2, 0, 0]).unwrap();
+    fs::write(bin.join("$RBROKEN.bin"), "data").unwrap();
+    fs::write(bin.join("desktop.ini"), "[.ShellClassInfo]").unwrap();
+
+    let entries = read_index_dir(bin).unwrap();
+    let paths: Vec<_> = entries.iter().map(|entry| entry.original_path.clone()).collect();
+    assert_eq!(paths, [PathBuf::from("C:\\report.docx"), PathBuf::from("C:\\notes.txt")]);
+    assert_eq!(entries[0].size, 1_234_567);
+    assert_eq!(entries[0].stored, bin.join("$REF34GH.docx"));
+    assert!(entries[0].deleted_days_ago().is_some());
+    assert!(read_index_dir(&bin.join("missing")).is_err());
+}
+
+#[test]
+fn test_dns_caches_are_chosen_from_running_resolvers() {
+    use clean_rs::platform::dns::{command_line, linux_flush_commands, run_flush_commands};
+
+    let running = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+    let lines = |names: &[&str]| {
+        linux_flush_commands(&running(names)).into_iter().map(command_line).collect::<Vec<_>>()
+    };
+    assert!(lines(&["systemd", "bash", "sshd"]).is_empty());
+    // Truncated by /proc and in full from ps
+    assert_eq!(lines(&["systemd-resolve"]), ["resolvectl flush-caches"]);
+    assert_eq!(lines(&["systemd-resolved"]), ["resolvectl flush-caches"]);
+    assert_eq!(
+        lines(&["dnsmasq", "nscd", "systemd-resolve"]),
+        ["resolvectl flush-caches", "nscd --invalidate=hosts", "pkill -HUP -x dnsmasq"]
+    );
+    assert!(lines(&["nscd-helper", "dnsmasq2"]).is_empty());
+
+    // A dry run only reports the commands
+    let commands = linux_flush_commands(&running(&["nscd"]));
+    assert_eq!(run_flush_commands(&commands, true).unwrap(), ["nscd --invalidate=hosts"]);
+}
+
+#[test]
+fn test_extended_length_paths_keep_naming_the_same_file() {
+    use clean_rs::platform::extended_length_path;
+
+    assert_eq!(extended_length_path(r"C:\Users\me\node_modules").as_deref(), Some(r"\\?\C:\Users\me\node_modules"));
+    assert_eq!(extended_length_path("D:/temp/cache").as_deref(), Some(r"\\?\D:\temp\cache"));
+    assert_eq!(extended_length_path(r"\\server\share\dir").as_deref(), Some(r"\\?\UNC\server\share\dir"));
+    // Already extended, relative, or resolved differently once prefixed
+    assert_eq!(extended_length_path(r"\\?\C:\dir"), None);
+    assert_eq!(extended_length_path(r"\\.\pipe\name"), None);
+    assert_eq!(extended_length_path(r"relative\dir"), None);
+    assert_eq!(extended_length_path(r"C:relative"), None);
+    assert_eq!(extended_length_path(r"C:\dir\..\other"), None);
+    assert_eq!(extended_length_path("/home/me"), None);
+}
+
+#[test]
+fn test_icloud_stubs_are_recognized_by_name() {
+    use clean_rs::platform::is_icloud_stub;
+
+    assert!(is_icloud_stub(".Report.pdf.icloud"));
+    assert!(is_icloud_stub(".notes.icloud"));
+    assert!(!is_icloud_stub(".icloud"));
+    assert!(!is_icloud_stub("Report.pdf.icloud"));
+    assert!(!is_icloud_stub(".Report.pdf"));
+}
+
+#[test]
+fn test_disk_usage_measures_the_containing_volume() {
+    use clean_rs::platform::{disk_usage, volume_root};
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let usage = disk_usage(temp_dir.path()).unwrap();
+    assert!(usage.total > 0);
+    assert!(usage.free <= usage.total);
+    assert!(usage.available <= usage.free);
+
+    // A path that does not exist yet is on its nearest existing ancestor's volume
+    let missing = temp_dir.path().join("not/created/yet");
+    assert_eq!(disk_usage(&missing).unwrap().total, usage.total);
+
+    let root = volume_root(temp_dir.path()).unwrap();
+    assert!(temp_dir.path().starts_with(&root));
+    assert_eq!(volume_root(&missing), Some(root.clone()));
+    assert_eq!(disk_usage(&root).unwrap().total, usage.total);
+}
+
+#[test]
+fn test_mount_points_are_the_roots_of_their_volumes() {
+    use clean_rs::platform::{is_mount_point, volume_root};
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = volume_root(temp_dir.path()).unwrap();
+    assert!(is_mount_point(&root));
+    assert!(!is_mount_point(temp_dir.path()));
+    assert!(!is_mount_point(&temp_dir.path().join("missing")));
+}
+
+/// Executable shell script `name` in `dir` running `body`
+#[cfg(all(unix, not(target_os = "macos")))]
+fn fake_program(dir: &Path, name: &str, body: &str) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join(name);
+    fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn test_trash_programs_are_preferred_with_a_fallback_per_file() {
+    use clean_rs::platform::{trash_files_with, TrashBackend};
+
+    let temp_dir = TempDir::new().unwrap();
+    let (bin, trash, downloads) = (temp_dir.path().join("bin"), temp_dir.path().join("Trash"), temp_dir.path().join("dl"));
+    fs::create_dir_all(&bin).unwrap();
+    let files = |names: &[&str]| -> Vec<std::path::PathBuf> {
+        fs::create_dir_all(&downloads).unwrap();
+        names
+            .iter()
+            .map(|name| {
+                fs::write(downloads.join(name), name).unwrap();
+                downloads.join(name)
+            })
+            .collect()
+    };
+
+    // trash-put moves everything and succeeds
+    let trash_put = fake_program(&bin, "trash-put", r#"shift; rm -f "$@""#);
+    let paths = files(&["a.zip", "b.zip"]);
+    let trashed = trash_files_with(Some(&(trash_put, TrashBackend::TrashCli)), &paths, &trash);
+    assert_eq!(trashed.into_iter().map(Result::unwrap).collect::<Vec<_>>(), [TrashBackend::TrashCli; 2]);
+    assert!(!trash.exists());
+
+    // gio moves only the first file and fails; the second is trashed by clean-rs itself
+    let gio = fake_program(&bin, "gio", r#"shift 2; rm -f "$1"; exit 1"#);
+    let paths = files(&["c.iso", "d.iso"]);
+    let trashed = trash_files_with(Some(&(gio, TrashBackend::Gio)), &paths, &trash);
+    assert_eq!(trashed.into_iter().map(Result::unwrap).collect::<Vec<_>>(), [TrashBackend::Gio, TrashBackend::Builtin]);
+    assert_eq!(fs::read_to_string(trash.join("files/d.iso")).unwrap(), "d.iso");
+    assert!(trash.join("info/d.iso.trashinfo").is_file());
+
+    // A program that cannot be started, or none at all
+    let paths = files(&["e.msi"]);
+    let missing = (bin.join("missing"), TrashBackend::Gio);
+    assert_eq!(trash_files_with(Some(&missing), &paths, &trash)[0].as_ref().unwrap(), &TrashBackend::Builtin);
+    let paths = files(&["f.msi"]);
+    assert_eq!(trash_files_with(None, &paths, &trash)[0].as_ref().unwrap(), &TrashBackend::Builtin);
+    assert!(trash.join("files/f.msi").is_file());
+    assert!(!downloads.join("f.msi").exists());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_background_mode_lowers_the_thread_and_its_children() {
+    use clean_rs::platform::{enter_background_mode, is_elevated};
+    use std::thread;
+
+    // Nice values are per thread, so the test thread keeps its own
+    thread::spawn(|| {
+        // SAFETY: getpriority only reads the calling thread's priority
+        let nice = || unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) };
+        let before = nice();
+        let guard = enter_background_mode().unwrap();
+        assert_eq!(nice(), 19);
+        assert_eq!(thread::spawn(nice).join().unwrap(), 19);
+        drop(guard);
+        // Raising the priority again needs CAP_SYS_NICE
+        if is_elevated() {
+            assert_eq!(nice(), before);
+        }
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn test_open_files_are_read_from_proc_fd_links() {
+    use clean_rs::platform::{open_files, open_files_in, FileHolder};
+    use std::os::unix::fs::symlink;
+
+    let temp_dir = TempDir::new().unwrap();
+    let held = temp_dir.path().join("held.log");
+    let free = temp_dir.path().join("free.log");
+    fs::write(&held, "log").unwrap();
+    fs::write(&free, "log").unwrap();
+
+    // A fake /proc: one process with the file, a socket and a pipe open
+    let proc_root = temp_dir.path().join("proc");
+    fs::create_dir_all(proc_root.join("123/fd")).unwrap();
+    fs::create_dir_all(proc_root.join("self")).unwrap();
+    fs::write(proc_root.join("123/comm"), "chrome\n").unwrap();
+    symlink(&held, proc_root.join("123/fd/3")).unwrap();
+    symlink("socket:[4242]", proc_root.join("123/fd/4")).unwrap();
+    symlink("pipe:[4243]", proc_root.join("123/fd/5")).unwrap();
+
+    let open = open_files_in(&proc_root);
+    assert_eq!(open.holders_of(&held), [FileHolder { pid: 123, name: "chrome".to_string() }]);
+    assert!(open.holders_of(&free).is_empty());
+    assert!(open_files_in(&temp_dir.path().join("missing")).is_empty());
+
+    // This process shows up in the real /proc while it has the file open
+    let file = fs::File::open(&free).unwrap();
+    let holders = open_files().holders_of(&free);
+    assert!(holders.iter().any(|holder| holder.pid == std::process::id()), "{:?}", holders);
+    drop(file);
+}
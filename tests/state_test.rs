@@ -0,0 +1,158 @@
+use clean_rs::state::{load_electron_apps, ItemState};
+use clean_rs::{
+    apply_item_options, apply_item_state, get_all_cleanup_items, load_item_state, save_item_state, Category,
+    CleanupItem, ItemOptions, TempPattern,
+};
+use std::collections::HashSet;
+use std::fs;
+use tempfile::TempDir;
+
+fn ready_item(id: &str, enabled: bool) -> CleanupItem {
+    CleanupItem {
+        id: id.to_string(),
+        name: id.to_string(),
+        enabled,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_save_and_load_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("nested/items.json");
+
+    let mut items = vec![ready_item("temp_files", false), ready_item("log_files", true)];
+    items[1].options = ItemOptions {
+        temp_patterns: Some(vec![TempPattern::Extension("log".to_string())]),
+        ..Default::default()
+    };
+    save_item_state(&path, &items).unwrap();
+
+    let states = load_item_state(&path).unwrap();
+    assert_eq!(states.len(), 2);
+    assert_eq!(states[0], ItemState::from(&items[0]));
+    assert_eq!(states[1].options, items[1].options);
+    assert!(fs::read_to_string(&path).unwrap().contains("\"*.log\""));
+}
+
+#[test]
+fn test_load_missing_file_is_empty() {
+    let temp_dir = TempDir::new().unwrap();
+    let states = load_item_state(&temp_dir.path().join("absent.json")).unwrap();
+    assert!(states.is_empty());
+}
+
+#[test]
+fn test_load_rejects_corrupt_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("items.json");
+    fs::write(&path, "{ not json").unwrap();
+    assert!(load_item_state(&path).is_err());
+}
+
+#[test]
+fn test_apply_state_merges_by_id() {
+    let mut items = vec![
+        ready_item("temp_files", true),
+        ready_item("brand_new_item", true),
+        ready_item("chrome_cache", false),
+    ];
+    let states = vec![
+        ItemState {
+            id: "temp_files".to_string(),
+            enabled: false,
+            options: ItemOptions::default(),
+        },
+        ItemState {
+            id: "chrome_cache".to_string(),
+            enabled: true,
+            options: ItemOptions::default(),
+        },
+        ItemState {
+            id: "removed_in_this_release".to_string(),
+            enabled: true,
+            options: ItemOptions::default(),
+        },
+    ];
+
+    apply_item_state(&mut items, &states);
+
+    assert!(!items[0].enabled);
+    // Items without saved state keep their defaults
+    assert!(items[1].enabled);
+    assert!(items[2].enabled);
+    assert_eq!(items.len(), 3);
+}
+
+#[test]
+fn test_builtin_ids_are_unique_and_stable() {
+    let items = get_all_cleanup_items();
+    let mut seen = HashSet::new();
+    for item in &items {
+        assert!(seen.insert(item.id.clone()), "duplicate id {}", item.id);
+        assert!(
+            item.id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'),
+            "id {} must be snake_case",
+            item.id
+        );
+    }
+    // Ids present on every platform must never change
+    assert!(seen.contains("temp_files"));
+    assert!(seen.contains("log_files"));
+}
+
+#[test]
+fn test_item_serialization_skips_runtime_state() {
+    let item = CleanupItem {
+        category: Category::DevTools,
+        ..ready_item("vscode_cache", true)
+    };
+    let json = serde_json::to_value(&item).unwrap();
+
+    assert_eq!(json["id"], "vscode_cache");
+    assert_eq!(json["category"], "dev_tools");
+    assert_eq!(json["risk"], "safe");
+    assert!(json.get("cleanup_type").is_none());
+    assert!(json.get("status").is_none());
+
+    let restored: CleanupItem = serde_json::from_value(json).unwrap();
+    assert_eq!(restored.id, "vscode_cache");
+    assert_eq!(restored.category, Category::DevTools);
+}
+
+#[test]
+fn test_apply_options_keeps_selection() {
+    let mut items = vec![ready_item("temp_files", true)];
+    let states = vec![ItemState {
+        id: "temp_files".to_string(),
+        enabled: false,
+        options: ItemOptions {
+            priority: Some(5),
+            ..Default::default()
+        },
+    }];
+
+    apply_item_options(&mut items, &states);
+
+    assert!(items[0].enabled);
+    assert_eq!(items[0].effective_priority(), 5);
+}
+
+#[test]
+fn test_electron_apps_survive_saving_items() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("items.json");
+    fs::write(
+        &path,
+        r#"{"items": [], "electron_apps": [{"key": "notion", "name": "Notion", "dir_names": ["Notion"]}]}"#,
+    )
+    .unwrap();
+
+    save_item_state(&path, &[ready_item("temp_files", true)]).unwrap();
+
+    let apps = load_electron_apps(&path).unwrap();
+    assert_eq!(apps.len(), 1);
+    assert_eq!(apps[0].key, "notion");
+    assert!(apps[0].process_names.is_empty());
+    assert_eq!(load_item_state(&path).unwrap().len(), 1);
+}
@@ -0,0 +1,137 @@
+use clean_rs::symlinks::{broken_symlinks_item, is_protected, MAX_LINK_HOPS};
+use clean_rs::{ItemStatus, RiskLevel};
+use std::path::Path;
+
+#[test]
+fn test_package_managed_locations_are_protected() {
+    assert!(is_protected(Path::new("/usr/lib/libfoo.so.1")));
+    assert!(is_protected(Path::new("/usr/local/bin/tool")));
+    assert!(is_protected(Path::new("/etc/alternatives/editor")));
+    assert!(!is_protected(Path::new("/home/user/.local/bin/tool")));
+    assert!(!is_protected(Path::new("/usr-data/link")));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_broken_links_are_found_with_relative_targets_and_chains() {
+    use clean_rs::symlinks::{broken_symlinks, is_broken_symlink};
+    use std::fs;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("bin")).unwrap();
+    fs::create_dir_all(root.join("lib")).unwrap();
+    fs::write(root.join("lib/tool"), "#!/bin/sh").unwrap();
+
+    // Relative targets resolve against the link's own directory
+    symlink("../lib/tool", root.join("bin/tool")).unwrap();
+    symlink("../lib/removed", root.join("bin/removed")).unwrap();
+    symlink(root.join("lib/gone"), root.join("bin/absolute")).unwrap();
+    // A chain ending at a file that exists, and one ending at nothing
+    symlink("tool", root.join("bin/alias")).unwrap();
+    symlink("alias", root.join("bin/alias2")).unwrap();
+    symlink("removed", root.join("bin/chained")).unwrap();
+    // A target below a regular file can never exist
+    symlink("../lib/tool/inner", root.join("bin/through_file")).unwrap();
+    // Links pointing at each other never resolve
+    symlink("loop_b", root.join("bin/loop_a")).unwrap();
+    symlink("loop_a", root.join("bin/loop_b")).unwrap();
+
+    assert!(!is_broken_symlink(&root.join("bin/tool")));
+    assert!(!is_broken_symlink(&root.join("bin/alias2")));
+    assert!(!is_broken_symlink(&root.join("lib/tool")));
+    assert!(!is_broken_symlink(&root.join("bin/missing")));
+    assert!(is_broken_symlink(&root.join("bin/removed")));
+    assert!(is_broken_symlink(&root.join("bin/chained")));
+    assert!(is_broken_symlink(&root.join("bin/loop_a")));
+
+    let found = broken_symlinks(&[root.to_path_buf()], &[]);
+    let names: Vec<String> = found.iter().map(|path| path.strip_prefix(root).unwrap().display().to_string()).collect();
+    assert_eq!(
+        names,
+        ["bin/absolute", "bin/chained", "bin/loop_a", "bin/loop_b", "bin/removed", "bin/through_file"]
+    );
+    assert!(broken_symlinks(&[root.to_path_buf()], &[root.join("bin")]).is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_long_chains_stop_at_the_hop_limit() {
+    use clean_rs::symlinks::is_broken_symlink;
+    use std::fs;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::write(root.join("link0"), "target").unwrap();
+    for i in 1..=MAX_LINK_HOPS + 1 {
+        symlink(format!("link{}", i - 1), root.join(format!("link{}", i))).unwrap();
+    }
+
+    assert!(!is_broken_symlink(&root.join(format!("link{}", MAX_LINK_HOPS - 1))));
+    assert!(is_broken_symlink(&root.join(format!("link{}", MAX_LINK_HOPS + 1))));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlinked_dirs_are_not_followed() {
+    use clean_rs::symlinks::broken_symlinks;
+    use std::fs;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let outside = temp_dir.path().join("outside");
+    let root = temp_dir.path().join("config");
+    fs::create_dir_all(&outside).unwrap();
+    fs::create_dir_all(&root).unwrap();
+    symlink("missing", outside.join("dangling")).unwrap();
+    symlink(&outside, root.join("linked_dir")).unwrap();
+
+    assert!(broken_symlinks(&[root], &[]).is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_broken_symlinks_item_unlinks_only_broken_links() {
+    use std::fs;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join(".local/bin")).unwrap();
+    fs::write(root.join("tool"), "#!/bin/sh").unwrap();
+    symlink("../../tool", root.join(".local/bin/tool")).unwrap();
+    symlink("../../old-tool", root.join(".local/bin/old-tool")).unwrap();
+    symlink("/nonexistent/app", root.join(".local/bin/app")).unwrap();
+
+    let item = broken_symlinks_item(vec![root.join(".local/bin"), root.join(".config")]);
+    assert_eq!(item.id, "broken_symlinks");
+    assert!(!item.enabled);
+    assert_eq!(item.risk, RiskLevel::Moderate);
+    assert_eq!(item.validate(), ItemStatus::Ready);
+
+    let scan = item.scan();
+    assert!(scan.has_data);
+    assert_eq!(scan.entries, 2);
+    assert_eq!(scan.size_bytes, 0);
+    assert_eq!(scan.breakdown[0].label, root.join(".local/bin/app").display().to_string());
+    assert_eq!(scan.breakdown[0].note, "指向 /nonexistent/app");
+    assert_eq!(item.estimate().entries, 2);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.entries, 2);
+    assert!(fs::symlink_metadata(root.join(".local/bin/app")).is_err());
+    assert!(fs::symlink_metadata(root.join(".local/bin/old-tool")).is_err());
+    assert!(root.join(".local/bin/tool").exists());
+    assert!(root.join("tool").exists());
+    assert!(!item.scan().has_data);
+
+    let missing = broken_symlinks_item(vec![root.join("missing")]);
+    assert_eq!(missing.validate(), ItemStatus::PathMissing);
+}
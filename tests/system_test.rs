@@ -0,0 +1,927 @@
+use clean_rs::system::{
+    count_xbel_bookmarks, delivery_optimization_cache, delivery_optimization_item, desktop_metadata_item,
+    downloads_item, empty_dirs_item, explorer_dir, font_cache_dirs, font_cache_item,
+    icon_cache_item, iis_logs_item, inet_cache_item, inet_cookies_item, inet_dirs, memory_dump_item, other_user_profiles, parse_reg_value, prefetch_item, recent_dir,
+    recent_docs_item, recently_used_files, setup_logs_item, shader_cache_dirs, shader_cache_item, temp_dirs, thumbnail_cache_item,
+    thumbnail_dirs, thumbnails_item, user_profile_items, wer_dir, wer_item, DnsCache, RecentlyUsed, RecycleBin, EMPTY_XBEL,
+    WER_MIN_AGE_DAYS,
+};
+use clean_rs::cleanup_items::{temp_files_item, TEMP_MIN_AGE_DAYS};
+use clean_rs::{CleanupType, ItemOptions, ItemStatus, RiskLevel};
+use std::fs::{self, File, FileTimes};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Create `files` below `root`, each last written and read `age_days` ago
+fn write_aged(root: &Path, files: &[(&str, usize)], age_days: u32) {
+    let time = SystemTime::now() - DAY * age_days;
+    for (relative, size) in files {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, vec![0u8; *size]).unwrap();
+        let times = FileTimes::new().set_modified(time).set_accessed(time);
+        File::options().write(true).open(&path).unwrap().set_times(times).unwrap();
+    }
+}
+
+#[test]
+fn test_wer_keeps_recent_reports_in_both_locations() {
+    let temp_dir = TempDir::new().unwrap();
+    let (local, program_data) = (temp_dir.path().join("Local"), temp_dir.path().join("ProgramData"));
+    let user = wer_dir(&local);
+    let system = wer_dir(&program_data);
+    write_aged(&user, &[("ReportArchive/AppCrash_old/memory.hdmp", 500)], 30);
+    write_aged(&user, &[("ReportQueue/AppCrash_new/memory.hdmp", 400)], 1);
+    write_aged(&system, &[("ReportQueue/Kernel_old/Report.wer", 200)], 10);
+
+    let item = wer_item(&local, &program_data);
+    assert_eq!(item.min_age_days, WER_MIN_AGE_DAYS);
+    assert!(item.description.contains(&user.display().to_string()));
+    assert!(item.description.contains(&system.display().to_string()));
+
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 700);
+    let labels: Vec<String> = scan.breakdown.iter().map(|entry| entry.label.clone()).collect();
+    assert_eq!(
+        labels,
+        [user.join("ReportArchive").display().to_string(), system.join("ReportQueue").display().to_string()]
+    );
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 700);
+    assert!(!user.join("ReportArchive/AppCrash_old").exists());
+    assert!(!system.join("ReportQueue/Kernel_old").exists());
+    assert!(user.join("ReportQueue/AppCrash_new/memory.hdmp").exists());
+}
+
+#[test]
+fn test_wer_age_can_be_overridden() {
+    let temp_dir = TempDir::new().unwrap();
+    let local = temp_dir.path().join("Local");
+    write_aged(&wer_dir(&local), &[("ReportQueue/AppCrash_new/memory.hdmp", 400)], 1);
+
+    let mut item = wer_item(&local, &temp_dir.path().join("ProgramData"));
+    assert!(!item.scan().has_data);
+    item.options = ItemOptions {
+        min_age_days: Some(0),
+        ..Default::default()
+    };
+    assert_eq!(item.scan().size_bytes, 400);
+}
+
+#[test]
+fn test_memory_dumps_are_listed_with_their_age() {
+    let temp_dir = TempDir::new().unwrap();
+    let windows = temp_dir.path();
+    write_aged(windows, &[("MEMORY.DMP", 5000), ("Minidump/010124-01.dmp", 300)], 40);
+    write_aged(windows, &[("Minidump/101626-02.dmp", 200), ("Minidump/notes.txt", 10)], 2);
+    write_aged(windows, &[("System32/config/SYSTEM", 700)], 40);
+
+    let item = memory_dump_item(windows);
+    assert_eq!(item.validate(), ItemStatus::Ready);
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 5300);
+    let notes: Vec<(u64, &str)> = scan.breakdown.iter().map(|entry| (entry.size_bytes, entry.note.as_str())).collect();
+    assert_eq!(notes, [(5000, "40 天前"), (300, "40 天前"), (200, "2 天前, 保留")]);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files, 2);
+    assert!(!windows.join("MEMORY.DMP").exists());
+    assert!(windows.join("Minidump/101626-02.dmp").exists());
+    assert!(windows.join("Minidump/notes.txt").exists());
+    assert!(windows.join("System32/config/SYSTEM").exists());
+}
+
+#[test]
+fn test_memory_dumps_missing() {
+    let temp_dir = TempDir::new().unwrap();
+    assert_eq!(memory_dump_item(temp_dir.path()).validate(), ItemStatus::PathMissing);
+}
+
+#[test]
+fn test_parse_reg_value() {
+    let output = "\r\nHKEY_LOCAL_MACHINE\\SOFTWARE\\Policies\\Microsoft\\Windows\\DeliveryOptimization\r\n    \
+                  DOModifyCacheDriveOld    REG_SZ    E:\r\n    \
+                  DOModifyCacheDrive    REG_EXPAND_SZ    %SystemDrive%\r\n\r\n";
+    assert_eq!(parse_reg_value(output, "DOModifyCacheDrive").as_deref(), Some("%SystemDrive%"));
+    assert_eq!(parse_reg_value(output, "domodifycachedrive").as_deref(), Some("%SystemDrive%"));
+    assert_eq!(parse_reg_value(output, "DOMaxCacheAge"), None);
+}
+
+#[test]
+fn test_delivery_optimization_cache_location() {
+    let windows = Path::new("C:\\Windows");
+    let default = delivery_optimization_cache(windows, None);
+    assert!(default.starts_with(windows.join("ServiceProfiles").join("NetworkService")));
+    assert!(default.ends_with(Path::new("DeliveryOptimization").join("Cache")));
+
+    let moved = delivery_optimization_cache(windows, Some("D:"));
+    assert_eq!(moved, Path::new("D:\\").join("DeliveryOptimization").join("Cache"));
+}
+
+#[test]
+fn test_delivery_optimization_item_is_cleaned_without_stopping_service() {
+    let temp_dir = TempDir::new().unwrap();
+    let cache = delivery_optimization_cache(temp_dir.path(), None);
+    write_aged(&cache, &[("0a/1b2c3d", 300), ("ff/0011", 200)], 1);
+
+    let mut item = delivery_optimization_item(&cache);
+    assert!(!item.enabled);
+    assert_eq!(item.service.as_deref(), Some("dosvc"));
+    item.options = ItemOptions {
+        stop_service: Some(true),
+        ..Default::default()
+    };
+    assert_eq!(item.scan().size_bytes, 500);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 500);
+    assert!(cache.exists());
+    assert!(!cache.join("0a/1b2c3d").exists());
+}
+
+#[test]
+fn test_font_cache_deletes_only_cache_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let (windows, local) = (temp_dir.path().join("Windows"), temp_dir.path().join("Local"));
+    let [system, user] = font_cache_dirs(&windows, &local);
+    write_aged(&system, &[("FontCache-S-1-5-21.dat", 400), ("~FontCache-System.tmp", 50)], 1);
+    write_aged(&user, &[("FontCache-S-1-5-21-100.dat", 300), ("desktop.ini", 10)], 1);
+
+    let item = font_cache_item(&windows, &local);
+    assert!(!item.enabled);
+    assert_eq!(item.service.as_deref(), Some("FontCache"));
+    assert_eq!(item.scan().size_bytes, 750);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files, 3);
+    assert!(user.join("desktop.ini").exists());
+    assert!(!system.join("FontCache-S-1-5-21.dat").exists());
+}
+
+#[test]
+fn test_icon_cache_targets_only_icon_databases() {
+    let temp_dir = TempDir::new().unwrap();
+    let local = temp_dir.path();
+    let explorer = explorer_dir(local);
+    write_aged(local, &[("IconCache.db", 100)], 1);
+    write_aged(&explorer, &[("iconcache_32.db", 200), ("IconCache_idx.DB", 50), ("thumbcache_96.db", 400)], 1);
+    write_aged(&explorer, &[("ExplorerStartupLog.etl", 30)], 1);
+
+    let item = icon_cache_item(local);
+    assert!(item.explorer_lock);
+    assert_eq!(item.scan().size_bytes, 350);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files, 3);
+    assert!(!local.join("IconCache.db").exists());
+    assert!(explorer.join("thumbcache_96.db").exists());
+    assert!(explorer.join("ExplorerStartupLog.etl").exists());
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_explorer_restart_option_falls_back_to_a_plain_clean() {
+    let temp_dir = TempDir::new().unwrap();
+    let explorer = explorer_dir(temp_dir.path());
+    write_aged(&explorer, &[("thumbcache_256.db", 400)], 1);
+
+    let mut item = thumbnail_cache_item(temp_dir.path());
+    item.options = serde_json::from_str(r#"{"allow_explorer_restart": true}"#).unwrap();
+    assert_eq!(item.options.restart_explorer, Some(true));
+
+    // There is no Explorer to stop here, so the files are cleaned as without the option
+    let result = item.clean();
+    assert_eq!(result.files, 1);
+    assert!(!explorer.join("thumbcache_256.db").exists());
+}
+
+#[test]
+fn test_thumbnail_cache_leaves_rest_of_explorer_dir() {
+    let temp_dir = TempDir::new().unwrap();
+    let explorer = explorer_dir(temp_dir.path());
+    write_aged(&explorer, &[("thumbcache_256.db", 400), ("thumbcache_idx.db", 20), ("iconcache_32.db", 200)], 1);
+
+    let item = thumbnail_cache_item(temp_dir.path());
+    assert_eq!(item.scan().size_bytes, 420);
+    item.clean();
+    assert!(!explorer.join("thumbcache_256.db").exists());
+    assert!(explorer.join("iconcache_32.db").exists());
+}
+
+#[test]
+fn test_shader_cache_lists_each_vendor_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let local = temp_dir.path();
+    write_aged(&local.join("D3DSCache"), &[("a1b2/shader.idx", 300)], 1);
+    write_aged(&local.join("NVIDIA/DXCache"), &[("nvshader_0001.toc", 700)], 1);
+
+    let item = shader_cache_item(local);
+    assert!(item.enabled);
+    assert_eq!(item.risk, RiskLevel::Safe);
+    assert!(matches!(&item.cleanup_type, CleanupType::Directories(dirs) if dirs.len() == 2));
+    let scan = item.scan();
+    let parts: Vec<(String, u64)> = scan.breakdown.iter().map(|entry| (entry.label.clone(), entry.size_bytes)).collect();
+    assert_eq!(
+        parts,
+        [
+            (local.join("D3DSCache").display().to_string(), 300),
+            (local.join("NVIDIA/DXCache").display().to_string(), 700)
+        ]
+    );
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 1000);
+}
+
+#[test]
+fn test_shader_cache_missing_everywhere() {
+    let temp_dir = TempDir::new().unwrap();
+    assert_eq!(shader_cache_dirs(temp_dir.path()).len(), 4);
+    assert_eq!(shader_cache_item(temp_dir.path()).validate(), ItemStatus::PathMissing);
+}
+
+#[test]
+fn test_inet_cache_uses_the_modern_layout_per_integrity_level() {
+    let temp_dir = TempDir::new().unwrap();
+    let local = temp_dir.path().join("Local");
+    let roaming = temp_dir.path().join("Roaming");
+    let windows = local.join("Microsoft/Windows");
+    write_aged(&windows.join("INetCache/IE"), &[("ABCD1234/page[1].htm", 400), ("container.dat", 100)], 1);
+    write_aged(&windows.join("INetCache/Low"), &[("EFGH5678/script[1].js", 200)], 1);
+    write_aged(&windows.join("INetCookies"), &[("cookie.txt", 50)], 1);
+
+    let dirs = inet_dirs(&local, &roaming);
+    assert!(!dirs.legacy);
+    assert_eq!(dirs.cookies, windows.join("INetCookies"));
+
+    let item = inet_cache_item(&local);
+    assert_eq!(item.id, "inet_cache");
+    assert!(item.enabled);
+    assert!(item.skip_in_use);
+    assert_eq!(item.risk, RiskLevel::Safe);
+    assert!(item.description.contains("INetCache"));
+    let scan = item.scan();
+    let parts: Vec<(String, u64)> = scan.breakdown.iter().map(|entry| (entry.label.clone(), entry.size_bytes)).collect();
+    assert_eq!(
+        parts,
+        [
+            (windows.join("INetCache/IE").display().to_string(), 500),
+            (windows.join("INetCache/Low").display().to_string(), 200)
+        ]
+    );
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.size_bytes, 700);
+    assert!(windows.join("INetCookies/cookie.txt").exists());
+
+    let cookies = inet_cookies_item(&local, &roaming);
+    assert!(!cookies.enabled);
+    assert_eq!(cookies.risk, RiskLevel::Aggressive);
+    assert_eq!(cookies.validate(), ItemStatus::Ready);
+}
+
+#[test]
+fn test_inet_cache_falls_back_to_temporary_internet_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let local = temp_dir.path().join("Local");
+    let roaming = temp_dir.path().join("Roaming");
+    let old = local.join("Microsoft/Windows/Temporary Internet Files");
+    write_aged(&old.join("Content.IE5"), &[("index.dat", 300)], 1);
+
+    let dirs = inet_dirs(&local, &roaming);
+    assert!(dirs.legacy);
+    assert_eq!(dirs.cache, [old.join("Content.IE5"), old.join("Low")]);
+    assert_eq!(dirs.cookies, roaming.join("Microsoft/Windows/Cookies"));
+
+    let item = inet_cache_item(&local);
+    assert!(item.description.contains("Temporary Internet Files"));
+    assert_eq!(item.validate(), ItemStatus::Ready);
+    assert_eq!(item.scan().size_bytes, 300);
+    assert_eq!(inet_cookies_item(&local, &roaming).validate(), ItemStatus::PathMissing);
+}
+
+#[test]
+fn test_inet_cache_missing() {
+    let temp_dir = TempDir::new().unwrap();
+    assert!(!inet_dirs(temp_dir.path(), temp_dir.path()).legacy);
+    assert_eq!(inet_cache_item(temp_dir.path()).validate(), ItemStatus::PathMissing);
+}
+
+#[test]
+fn test_setup_logs_are_sized_per_location() {
+    let temp_dir = TempDir::new().unwrap();
+    let windows = temp_dir.path();
+    write_aged(windows, &[("Logs/CBS/CbsPersist_20260101.log", 900), ("Logs/CBS/FilterList.cab", 50)], 30);
+    write_aged(windows, &[("Logs/CBS/CBS.log", 400), ("Logs/DISM/dism.log", 10)], 1);
+    write_aged(windows, &[("Logs/DISM/dism.log.bak", 200), ("Panther/setupact.log", 300)], 30);
+    write_aged(windows, &[("Panther/UnattendGC/setupact.log", 70)], 30);
+
+    let item = setup_logs_item(windows);
+    assert_eq!(item.validate(), ItemStatus::Ready);
+    assert_eq!(item.min_age_days, 7);
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 1400);
+    let parts: Vec<(u64, &str)> = scan.breakdown.iter().map(|entry| (entry.size_bytes, entry.note.as_str())).collect();
+    assert_eq!(parts, [(900, "1 / 2 个文件已过期"), (200, "1 / 2 个文件已过期"), (300, "1 / 1 个文件已过期")]);
+    assert!(scan.breakdown[0].label.ends_with("*.log"));
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files, 3);
+    assert!(windows.join("Logs/CBS/CBS.log").exists());
+    assert!(windows.join("Logs/CBS/FilterList.cab").exists());
+    assert!(windows.join("Panther/UnattendGC/setupact.log").exists());
+}
+
+#[test]
+fn test_iis_logs_are_deleted_per_site() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    write_aged(root, &[("W3SVC1/u_ex260101.log", 500), ("W3SVC1/u_ex260801.log", 300)], 60);
+    write_aged(root, &[("W3SVC2/u_ex250101.log", 800), ("W3SVC2/notes.txt", 20)], 120);
+    write_aged(root, &[("W3SVC2/u_ex261016.log", 100)], 0);
+    write_aged(root, &[("FTPSVC3/u_ex250101.log", 900)], 120);
+
+    let item = iis_logs_item(root);
+    assert!(item.enabled);
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 1600);
+    let sites: Vec<(String, u64)> = scan.breakdown.iter().map(|entry| (entry.label.clone(), entry.size_bytes)).collect();
+    assert_eq!(
+        sites,
+        [(root.join("W3SVC1").display().to_string(), 800), (root.join("W3SVC2").display().to_string(), 800)]
+    );
+
+    let mut item = item;
+    item.options.min_age_days = Some(0);
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files, 3);
+    // Today's log, other files and the site folders stay
+    assert!(root.join("W3SVC2/u_ex261016.log").exists());
+    assert!(root.join("W3SVC2/notes.txt").exists());
+    assert!(root.join("W3SVC1").is_dir());
+    assert!(root.join("FTPSVC3/u_ex250101.log").exists());
+}
+
+#[test]
+fn test_iis_logs_can_be_compressed_instead() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    write_aged(root, &[("W3SVC1/u_ex260601.log", 500)], 60);
+    write_aged(root, &[("W3SVC1/u_ex250101.log", 800)], 120);
+
+    let mut item = iis_logs_item(root);
+    item.options.compress_until_days = Some(90);
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 800);
+    assert_eq!(scan.breakdown[0].note, "1 个文件将压缩");
+    assert!(!iis_logs_item(&root.join("missing")).enabled);
+}
+
+#[test]
+fn test_temp_dirs_cover_system_and_local_low_once() {
+    let temp_dir = TempDir::new().unwrap();
+    let windows = temp_dir.path().join("Windows");
+    let user = temp_dir.path().join("Local/Temp");
+    let local_low = temp_dir.path().join("LocalLow/Temp");
+    fs::create_dir_all(&local_low).unwrap();
+
+    assert_eq!(temp_dirs(&user, &windows, Some(&local_low)), [user.clone(), windows.join("Temp"), local_low.clone()]);
+    // LocalLow without a Temp folder is left out
+    assert_eq!(temp_dirs(&user, &windows, Some(&temp_dir.path().join("missing"))), [user.clone(), windows.join("Temp")]);
+    // %TEMP% pointing at the system temp is not listed twice
+    let system_temp = windows.join("Temp");
+    assert_eq!(temp_dirs(&system_temp, &windows, None), [system_temp]);
+}
+
+#[test]
+fn test_temp_item_sizes_each_location_and_keeps_recent_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let (user, system) = (temp_dir.path().join("user"), temp_dir.path().join("system"));
+    write_aged(&user, &[("old.tmp", 300), ("session/new.tmp", 50)], 3);
+    write_aged(&user, &[("fresh.tmp", 70)], 0);
+    write_aged(&system, &[("MpCmdRun.log", 200)], 5);
+
+    let item = temp_files_item(vec![user.clone(), system.clone()]);
+    assert_eq!(item.min_age_days, TEMP_MIN_AGE_DAYS);
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 550);
+    let parts: Vec<(String, u64)> = scan.breakdown.iter().map(|entry| (entry.label.clone(), entry.size_bytes)).collect();
+    assert_eq!(parts, [(user.display().to_string(), 350), (system.display().to_string(), 200)]);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert!(user.join("fresh.tmp").exists());
+    assert!(!system.join("MpCmdRun.log").exists());
+}
+
+#[test]
+fn test_prefetch_keeps_recent_entries_and_boot_layout() {
+    let temp_dir = TempDir::new().unwrap();
+    let windows = temp_dir.path();
+    write_aged(windows, &[("Prefetch/OLDAPP.EXE-1A2B3C4D.pf", 300), ("Prefetch/SETUP.EXE-00000001.pf", 100)], 90);
+    write_aged(windows, &[("Prefetch/CHROME.EXE-5E6F7A8B.pf", 200)], 2);
+    write_aged(windows, &[("Prefetch/Layout.ini", 500), ("Prefetch/ReadyBoot/Trace1.fx", 700)], 90);
+
+    let item = prefetch_item(windows);
+    assert_eq!(item.min_age_days, 30);
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 400);
+    assert_eq!(scan.breakdown.len(), 1);
+    assert_eq!(scan.breakdown[0].note, "2 / 3 个文件已过期");
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files, 2);
+    assert!(windows.join("Prefetch/CHROME.EXE-5E6F7A8B.pf").exists());
+    assert!(windows.join("Prefetch/Layout.ini").exists());
+    assert!(windows.join("Prefetch/ReadyBoot/Trace1.fx").exists());
+}
+
+#[test]
+fn test_other_user_profiles_skip_builtin_and_own_profile() {
+    let temp_dir = TempDir::new().unwrap();
+    let users = temp_dir.path();
+    for name in ["alice", "bob", "Default", "Public", "admin"] {
+        fs::create_dir_all(users.join(name).join("AppData/Local/Temp")).unwrap();
+    }
+    fs::write(users.join("desktop.ini"), "").unwrap();
+
+    let profiles = other_user_profiles(users, Some(&users.join("admin")));
+    assert_eq!(profiles, [("alice".to_string(), users.join("alice")), ("bob".to_string(), users.join("bob"))]);
+}
+
+#[test]
+fn test_user_profile_items_are_named_after_the_user() {
+    let temp_dir = TempDir::new().unwrap();
+    let profile = temp_dir.path().join("Alice");
+    write_aged(&profile, &[("AppData/Local/Temp/setup.log", 300)], 3);
+
+    let items = user_profile_items("Alice", &profile);
+    let ids: Vec<&str> = items.iter().map(|item| item.id.as_str()).collect();
+    assert_eq!(ids, ["temp_files@alice", "thumbnail_cache@alice"]);
+    assert_eq!(items[0].name, "临时文件目录 (Alice)");
+    assert_eq!(items[0].scan().size_bytes, 300);
+}
+
+#[test]
+fn test_recent_docs_include_jump_lists() {
+    let temp_dir = TempDir::new().unwrap();
+    let roaming = temp_dir.path();
+    let recent = recent_dir(roaming);
+    write_aged(&recent, &[("report.docx.lnk", 100), ("photos.lnk", 50)], 1);
+    write_aged(&recent, &[("AutomaticDestinations/5f7b5f1e01b83767.automaticDestinations-ms", 400)], 1);
+    write_aged(&recent, &[("CustomDestinations/28c8b86deab549a1.customDestinations-ms", 200)], 1);
+
+    let item = recent_docs_item(roaming);
+    assert_eq!(item.validate(), ItemStatus::Ready);
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 750);
+    let parts: Vec<(u64, &str)> = scan.breakdown.iter().map(|entry| (entry.size_bytes, entry.note.as_str())).collect();
+    assert_eq!(parts, [(150, ""), (400, ""), (200, "")]);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files, 4);
+    assert!(recent.join("AutomaticDestinations").is_dir());
+    assert!(!recent.join("photos.lnk").exists());
+
+    assert_eq!(recent_docs_item(&temp_dir.path().join("missing")).validate(), ItemStatus::PathMissing);
+}
+
+/// A recent files list as GTK writes it, with two bookmarks
+const SAMPLE_XBEL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/user/Documents/report.pdf" added="2026-01-02T10:00:00.000000Z" modified="2026-01-02T10:00:00.000000Z" visited="2026-01-02T10:00:00.000000Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="application/pdf"/>
+        <bookmark:applications>
+          <bookmark:application name="Document Viewer" exec="&apos;evince %u&apos;" modified="2026-01-02T10:00:00.000000Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/user/Pictures/trip.jpg" added="2026-01-03T09:00:00.000000Z" modified="2026-01-03T09:00:00.000000Z" visited="2026-01-03T09:00:00.000000Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="image/jpeg"/>
+        <bookmark:groups>
+          <bookmark:group>Image Viewer</bookmark:group>
+        </bookmark:groups>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+
+#[test]
+fn test_xbel_bookmarks_are_counted() {
+    assert_eq!(count_xbel_bookmarks(SAMPLE_XBEL), Some(2));
+    assert_eq!(count_xbel_bookmarks(EMPTY_XBEL), Some(0));
+    assert_eq!(count_xbel_bookmarks("<xbel><bookmark/></xbel>"), Some(1));
+    assert_eq!(count_xbel_bookmarks("not a bookmark list"), None);
+}
+
+#[test]
+fn test_recently_used_lists_are_emptied_not_deleted() {
+    let temp_dir = TempDir::new().unwrap();
+    let (data, cache) = (temp_dir.path().join(".local/share"), temp_dir.path().join(".cache"));
+    fs::create_dir_all(&data).unwrap();
+    fs::create_dir_all(&cache).unwrap();
+    let files = recently_used_files(&data, &cache);
+    fs::write(&files[0], SAMPLE_XBEL).unwrap();
+    fs::write(&files[1], "<xbel version=\"1.0\"><bookmark href=\"file:///tmp/a\"></bookmark></xbel>").unwrap();
+
+    let item = RecentlyUsed::new(files.clone()).item();
+    assert_eq!(item.id, "recently_used");
+    assert!(!item.enabled);
+    assert_eq!(item.risk, RiskLevel::Moderate);
+    assert_eq!(item.validate(), ItemStatus::Ready);
+
+    let scan = item.scan();
+    assert_eq!(scan.entries, 3);
+    assert_eq!(scan.breakdown.len(), 2);
+    assert_eq!(scan.breakdown[0].note, "2 条记录");
+    assert_eq!(scan.size_bytes, (SAMPLE_XBEL.len() - EMPTY_XBEL.len()) as u64);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.entries, 3);
+    for file in &files {
+        assert_eq!(fs::read_to_string(file).unwrap(), EMPTY_XBEL);
+    }
+    assert!(!item.scan().has_data);
+
+    // Only lists that exist are checked
+    fs::remove_file(&files[1]).unwrap();
+    assert_eq!(item.validate(), ItemStatus::Ready);
+    fs::remove_file(&files[0]).unwrap();
+    assert_eq!(item.validate(), ItemStatus::PathMissing);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_recently_used_list_keeps_its_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let files = recently_used_files(temp_dir.path(), &temp_dir.path().join("missing"));
+    fs::write(&files[0], SAMPLE_XBEL).unwrap();
+    fs::set_permissions(&files[0], fs::Permissions::from_mode(0o600)).unwrap();
+
+    RecentlyUsed::new(files.clone()).item().clean();
+    assert_eq!(fs::metadata(&files[0]).unwrap().permissions().mode() & 0o777, 0o600);
+    assert_eq!(fs::read_to_string(&files[0]).unwrap(), EMPTY_XBEL);
+}
+
+#[test]
+fn test_thumbnails_keep_recently_viewed_ones() {
+    let temp_dir = TempDir::new().unwrap();
+    let (cache, home) = (temp_dir.path().join(".cache"), temp_dir.path().to_path_buf());
+    write_aged(&cache.join("thumbnails"), &[("normal/0a1b.png", 300), ("fail/gnome-thumbnail-factory/9f.png", 20)], 60);
+    write_aged(&cache.join("thumbnails"), &[("large/77aa.png", 500)], 2);
+    write_aged(&home.join(".thumbnails"), &[("normal/legacy.png", 100)], 90);
+    write_aged(&cache.join("thumbnails"), &[("other/keep.png", 40)], 90);
+
+    let item = thumbnails_item(&cache, &home);
+    assert!(item.enabled);
+    assert_eq!(thumbnail_dirs(&cache, &home).len(), 8);
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 420);
+    assert_eq!(scan.breakdown.len(), 3);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert!(cache.join("thumbnails/large/77aa.png").exists());
+    assert!(cache.join("thumbnails/other/keep.png").exists());
+    assert!(!home.join(".thumbnails/normal/legacy.png").exists());
+}
+
+#[test]
+fn test_desktop_metadata_matches_exact_names_outside_app_data() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let metadata = [
+        (".DS_Store", 10),
+        ("Pictures/.DS_Store", 20),
+        ("Pictures/2023/trip/.DS_Store", 30),
+        ("Pictures/2023/Thumbs.db", 40),
+        ("Music/desktop.ini", 5),
+    ];
+    write_aged(home, &metadata, 0);
+    let kept = [
+        ("Pictures/old.DS_Store", 1),
+        ("Pictures/.DS_Store.bak", 1),
+        ("Documents/Thumbs.db.txt", 1),
+        ("Documents/thumbs.db", 1),
+        ("Library/Caches/.DS_Store", 1),
+        ("AppData/Roaming/desktop.ini", 1),
+        ("Projects/.DS_Store/notes.txt", 1),
+    ];
+    write_aged(home, &kept, 0);
+
+    let item = desktop_metadata_item(home);
+    assert!(!item.enabled);
+    assert_eq!(item.validate(), ItemStatus::Ready);
+    let scan = item.scan();
+    assert_eq!(scan.files, 5);
+    assert_eq!(scan.size_bytes, 105);
+    let parts: Vec<(&str, u64, &str)> =
+        scan.breakdown.iter().map(|entry| (entry.label.as_str(), entry.size_bytes, entry.note.as_str())).collect();
+    assert_eq!(parts, [(".DS_Store", 60, "3 个文件"), ("Thumbs.db", 40, "1 个文件"), ("desktop.ini", 5, "1 个文件")]);
+    assert_eq!(item.estimate().files, 5);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files, 5);
+    for (relative, _) in metadata {
+        assert!(!home.join(relative).exists(), "{} was not deleted", relative);
+    }
+    for (relative, _) in kept {
+        assert!(home.join(relative).exists(), "{} was deleted", relative);
+    }
+}
+
+#[test]
+fn test_desktop_metadata_roots_and_exclusions_can_be_configured() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    write_aged(home, &[("Pictures/.DS_Store", 20), ("Music/.DS_Store", 30), ("Music/Live/Thumbs.db", 40)], 0);
+
+    let mut item = desktop_metadata_item(home);
+    item.options = ItemOptions {
+        project_roots: Some(vec![home.join("Music")]),
+        excluded_paths: Some(vec![home.join("Music/Live")]),
+        same_filesystem: Some(false),
+        ..Default::default()
+    };
+    assert_eq!(item.scan().size_bytes, 30);
+    item.clean();
+    assert!(home.join("Pictures/.DS_Store").exists());
+    assert!(home.join("Music/Live/Thumbs.db").exists());
+    assert!(!home.join("Music/.DS_Store").exists());
+
+    item.options.project_roots = Some(vec![home.join("missing")]);
+    assert_eq!(item.validate(), ItemStatus::PathMissing);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_desktop_metadata_does_not_follow_symlinks() {
+    let temp_dir = TempDir::new().unwrap();
+    let (home, outside) = (temp_dir.path().join("home"), temp_dir.path().join("outside"));
+    write_aged(&outside, &[(".DS_Store", 10)], 0);
+    fs::create_dir_all(&home).unwrap();
+    std::os::unix::fs::symlink(&outside, home.join("linked")).unwrap();
+
+    let item = desktop_metadata_item(&home);
+    assert!(!item.scan().has_data);
+    item.clean();
+    assert!(outside.join(".DS_Store").exists());
+}
+
+#[test]
+fn test_old_downloads_lists_each_file_and_keeps_recent_ones() {
+    let temp_dir = TempDir::new().unwrap();
+    let downloads = temp_dir.path();
+    write_aged(downloads, &[("setup.exe", 500), ("report.pdf", 300), ("linux.tar.gz", 200)], 120);
+    write_aged(downloads, &[("new.msi", 100)], 10);
+    write_aged(downloads, &[("folder/old.iso", 1000)], 200);
+
+    let mut item = downloads_item(downloads);
+    assert!(!item.enabled);
+    assert!(item.use_trash);
+    assert_eq!(item.risk, RiskLevel::Aggressive);
+    let scan = item.scan();
+    assert_eq!(scan.size_bytes, 1000);
+    let notes: Vec<(String, &str)> = scan
+        .breakdown
+        .iter()
+        .map(|entry| (Path::new(&entry.label).file_name().unwrap().to_string_lossy().into_owned(), entry.note.as_str()))
+        .collect();
+    assert_eq!(
+        notes,
+        [
+            ("linux.tar.gz".to_string(), "120 天前"),
+            ("new.msi".to_string(), "10 天前, 保留"),
+            ("report.pdf".to_string(), "120 天前"),
+            ("setup.exe".to_string(), "120 天前"),
+        ]
+    );
+
+    item.options = ItemOptions {
+        installers_only: Some(true),
+        use_trash: Some(false),
+        ..Default::default()
+    };
+    assert_eq!(item.scan().size_bytes, 700);
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files, 2);
+    assert!(!downloads.join("setup.exe").exists());
+    assert!(!downloads.join("linux.tar.gz").exists());
+    assert!(downloads.join("report.pdf").exists());
+    assert!(downloads.join("new.msi").exists());
+    assert!(downloads.join("folder/old.iso").exists());
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn test_trash_file_records_where_files_came_from() {
+    use clean_rs::platform::unix::trash_file;
+
+    let temp_dir = TempDir::new().unwrap();
+    let trash = temp_dir.path().join("Trash");
+    let downloads = temp_dir.path().join("My Downloads");
+    write_aged(&downloads, &[("setup.exe", 10)], 0);
+    trash_file(&downloads.join("setup.exe"), &trash).unwrap();
+    write_aged(&downloads, &[("setup.exe", 20)], 0);
+    trash_file(&downloads.join("setup.exe"), &trash).unwrap();
+
+    assert!(!downloads.join("setup.exe").exists());
+    assert_eq!(fs::metadata(trash.join("files/setup.exe")).unwrap().len(), 10);
+    assert_eq!(fs::metadata(trash.join("files/setup.exe.2")).unwrap().len(), 20);
+    let info = fs::read_to_string(trash.join("info/setup.exe.2.trashinfo")).unwrap();
+    let mut lines = info.lines();
+    assert_eq!(lines.next(), Some("[Trash Info]"));
+    assert_eq!(lines.next().unwrap(), format!("Path={}/My%20Downloads/setup.exe", temp_dir.path().display()));
+    assert!(lines.next().unwrap().starts_with("DeletionDate=20"));
+}
+
+/// Create empty directories below `root`, each last modified `age_days` ago
+fn make_dirs_aged(root: &Path, dirs: &[&str], age_days: u32) {
+    for dir in dirs {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    // Deepest first, so setting a child's time does not touch its parent's again
+    let mut paths: Vec<PathBuf> = dirs
+        .iter()
+        .flat_map(|dir| Path::new(dir).ancestors().filter(|path| !path.as_os_str().is_empty()))
+        .map(|path| root.join(path))
+        .collect();
+    paths.sort_by_key(|path| std::cmp::Reverse(path.components().count()));
+    let time = SystemTime::now() - DAY * age_days;
+    for path in paths {
+        File::open(&path).unwrap().set_modified(time).unwrap();
+    }
+}
+
+#[test]
+fn test_empty_dirs_are_removed_deepest_first_keeping_roots() {
+    let temp_dir = TempDir::new().unwrap();
+    let (temp, cache) = (temp_dir.path().join("tmp"), temp_dir.path().join("cache"));
+    make_dirs_aged(&temp, &["a/b/c", "a/d", "full/empty"], 10);
+    write_aged(&temp, &[("full/keep.txt", 10)], 10);
+    make_dirs_aged(&cache, &["app/v1/shaders"], 10);
+    make_dirs_aged(&cache, &["fresh/new"], 0);
+
+    let item = empty_dirs_item(vec![temp.clone(), cache.clone()]);
+    assert!(!item.enabled);
+    let scan = item.scan();
+    assert_eq!(scan.directories, 8);
+    assert_eq!(scan.size_bytes, 0);
+    let notes: Vec<&str> = scan.breakdown.iter().map(|entry| entry.note.as_str()).collect();
+    assert_eq!(notes, ["5 个空目录", "3 个空目录"]);
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.directories, 8);
+    assert!(temp.is_dir() && cache.is_dir());
+    assert!(!temp.join("a").exists());
+    assert!(!temp.join("full/empty").exists());
+    assert!(temp.join("full/keep.txt").exists());
+    assert!(!cache.join("app").exists());
+    assert!(cache.join("fresh/new").is_dir());
+}
+
+#[test]
+fn test_empty_dir_filled_after_scan_is_skipped() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    make_dirs_aged(root, &["a/b", "c"], 10);
+
+    let mut item = empty_dirs_item(vec![root.to_path_buf()]);
+    item.options.min_age_days = Some(0);
+    assert_eq!(item.scan().directories, 3);
+    fs::write(root.join("a/b/new.txt"), b"x").unwrap();
+
+    let result = item.clean();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.directories, 1);
+    assert!(root.join("a/b/new.txt").exists());
+    assert!(!root.join("c").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_empty_dirs_do_not_follow_symlinks() {
+    let temp_dir = TempDir::new().unwrap();
+    let (root, outside) = (temp_dir.path().join("root"), temp_dir.path().join("outside"));
+    make_dirs_aged(&outside, &["empty"], 10);
+    make_dirs_aged(&root, &["holder"], 10);
+    std::os::unix::fs::symlink(&outside, root.join("holder/link")).unwrap();
+
+    let mut item = empty_dirs_item(vec![root.clone()]);
+    item.options.min_age_days = Some(0);
+    assert!(!item.scan().has_data);
+    item.clean();
+    assert!(outside.join("empty").is_dir());
+    assert!(root.join("holder/link").exists());
+}
+
+#[test]
+fn test_recycle_bin_item_scans_with_the_platform_query() {
+    let item = RecycleBin::new().item();
+    assert_eq!(item.id, "recycle_bin");
+    assert_eq!(item.risk, RiskLevel::Aggressive);
+    assert!(!item.enabled);
+    assert!(matches!(item.cleanup_type, CleanupType::Custom(_)));
+
+    // Only read: the size shown before cleaning is what the query reports
+    let Ok(usage) = clean_rs::platform::query_recycle_bin() else {
+        return;
+    };
+    assert_eq!(item.validate(), ItemStatus::Ready);
+    let scan = item.scan();
+    assert_eq!(scan.entries, usage.items);
+    assert_eq!(scan.size_bytes, usage.bytes);
+    assert_eq!(scan.has_data, usage.items > 0);
+    assert_eq!(item.estimate().size_bytes, usage.bytes);
+}
+
+#[test]
+fn test_dns_cache_item_lists_the_commands_it_would_run() {
+    let item = DnsCache::new().item();
+    assert_eq!(item.id, "dns_cache");
+    assert_eq!(item.risk, RiskLevel::Safe);
+    assert!(!item.enabled);
+    assert_eq!(item.requires_admin, !cfg!(windows));
+
+    // Scanning never flushes anything
+    let commands = clean_rs::platform::dns_flush_commands();
+    let scan = item.scan();
+    assert_eq!(scan.entries, commands.len() as u64);
+    assert_eq!(scan.size_bytes, 0);
+    assert_eq!(scan.has_data, !commands.is_empty());
+    let labels: Vec<_> = scan.breakdown.iter().map(|entry| entry.label.clone()).collect();
+    assert_eq!(labels, commands);
+    let expected = if commands.is_empty() { ItemStatus::ServiceUnavailable } else { ItemStatus::Ready };
+    assert_eq!(item.validate(), expected);
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn test_recycle_bins_can_be_excluded_one_by_one() {
+    use clean_rs::cleanup_items::EXCLUDED_NOTE;
+
+    let temp_dir = TempDir::new().unwrap();
+    let bin = |name: &str, size: usize| {
+        let trash = temp_dir.path().join(name);
+        fs::create_dir_all(trash.join("files")).unwrap();
+        fs::create_dir_all(trash.join("info")).unwrap();
+        fs::write(trash.join("files/vm.img"), vec![0u8; size]).unwrap();
+        fs::write(trash.join("info/vm.img.trashinfo"), "[Trash Info]\nPath=/data/vm.img\n").unwrap();
+        trash
+    };
+    let home = bin("home-trash", 100);
+    let data = bin("data-trash", 4000);
+
+    let mut item = RecycleBin::with_roots(vec![home.clone(), data.clone()]).item();
+    assert!(item.has_projects());
+    assert!(item.can_exclude(&data));
+    assert!(!item.can_exclude(&data.join("files/vm.img")));
+    assert_eq!(item.validate(), ItemStatus::Ready);
+    let scan = item.scan();
+    assert_eq!((scan.entries, scan.size_bytes), (2, 4100));
+    assert_eq!(scan.breakdown.len(), 2);
+    assert_eq!(scan.breakdown[1].label, data.display().to_string());
+    assert_eq!(scan.breakdown[1].note, "1 个项目");
+
+    assert!(item.toggle_excluded(&home));
+    let scan = item.scan();
+    assert_eq!((scan.entries, scan.size_bytes), (1, 4000));
+    assert_eq!(scan.breakdown[0].note, format!("1 个项目, {}", EXCLUDED_NOTE));
+
+    let cleaned = item.clean();
+    assert!(cleaned.errors.is_empty(), "{:?}", cleaned.errors);
+    assert_eq!((cleaned.entries, cleaned.size_bytes), (1, 4000));
+    assert!(!data.join("files/vm.img").exists());
+    assert!(home.join("files/vm.img").exists());
+    assert!(home.join("info/vm.img.trashinfo").exists());
+}
@@ -0,0 +1,29 @@
+use clean_rs::util::{format_size, format_size_si};
+use clean_rs::CleanupResult;
+
+#[test]
+fn test_sizes_switch_units_at_each_power_of_1024() {
+    assert_eq!(format_size(0), "0 B");
+    assert_eq!(format_size(1023), "1023 B");
+    assert_eq!(format_size(1024), "1.00 KB");
+    assert_eq!(format_size(1024 * 1024), "1.00 MB");
+    assert_eq!(format_size(3 * 1024 * 1024 * 1024 + 512 * 1024 * 1024), "3.50 GB");
+    assert_eq!(format_size(1536 * 1024 * 1024), "1.50 GB");
+    assert_eq!(format_size(1 << 40), "1.00 TB");
+    // Nothing is larger than TB, so the largest possible size stays in it
+    assert_eq!(format_size(u64::MAX), "16777216.00 TB");
+}
+
+#[test]
+fn test_si_sizes_use_powers_of_1000() {
+    assert_eq!(format_size_si(999), "999 B");
+    assert_eq!(format_size_si(1000), "1.00 KB");
+    assert_eq!(format_size_si(1_500_000_000), "1.50 GB");
+    assert_eq!(format_size_si(u64::MAX), "18446744.07 TB");
+}
+
+#[test]
+fn test_results_format_their_own_size() {
+    let result = CleanupResult { size_bytes: 1536, ..CleanupResult::new() };
+    assert_eq!(result.size_human(), "1.50 KB");
+}
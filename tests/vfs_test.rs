@@ -0,0 +1,67 @@
+use clean_rs::vfs::{EntryType, Fs, MockFs, RealFs};
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[test]
+fn test_mock_fs_lists_and_removes_like_a_disk() {
+    let fs = MockFs::new().file("/root/b.txt", 2).file("/root/a/c.txt", 3).link("/root/link");
+
+    assert_eq!(fs.read_dir(Path::new("/root")).unwrap(), vec![OsString::from("a"), "b.txt".into(), "link".into()]);
+    assert_eq!(fs.symlink_metadata(Path::new("/root/a")).unwrap().entry_type, EntryType::Dir);
+    assert_eq!(fs.symlink_metadata(Path::new("/root/link")).unwrap().entry_type, EntryType::Link);
+    assert_eq!(fs.metadata(Path::new("/root/link")).unwrap_err().kind(), io::ErrorKind::NotFound);
+
+    assert_eq!(fs.remove_dir(Path::new("/root/a")).unwrap_err().kind(), io::ErrorKind::DirectoryNotEmpty);
+    assert_eq!(fs.remove_file(Path::new("/root/a")).unwrap_err().kind(), io::ErrorKind::IsADirectory);
+    fs.remove_dir_all(Path::new("/root/a")).unwrap();
+    assert!(!fs.exists("/root/a/c.txt"));
+    assert_eq!(fs.remove_file(Path::new("/root/a")).unwrap_err().kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_mock_fs_injected_failures() {
+    let fs = MockFs::new()
+        .file("/root/busy.dll", 1)
+        .file("/root/sealed/x", 1)
+        .file("/root/ro.txt", 1)
+        .fail_remove("/root/busy.dll", io::ErrorKind::ResourceBusy)
+        .fail_read_dir("/root/sealed", io::ErrorKind::PermissionDenied)
+        .read_only("/root/ro.txt");
+
+    assert_eq!(fs.remove_file(Path::new("/root/busy.dll")).unwrap_err().kind(), io::ErrorKind::ResourceBusy);
+    assert_eq!(fs.read_dir(Path::new("/root/sealed")).unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+    assert_eq!(fs.symlink_metadata(Path::new("/root/sealed")).unwrap().entry_type, EntryType::Dir);
+
+    assert_eq!(fs.remove_file(Path::new("/root/ro.txt")).unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+    assert!(fs.make_deletable(Path::new("/root/ro.txt")));
+    assert!(!fs.make_deletable(Path::new("/root/ro.txt")));
+    fs.remove_file(Path::new("/root/ro.txt")).unwrap();
+}
+
+#[test]
+fn test_mock_fs_rename_moves_subtree() {
+    let fs = MockFs::new().file("/root/old/deep/f", 4);
+
+    fs.rename(Path::new("/root/old"), Path::new("/root/new")).unwrap();
+
+    assert!(!fs.exists("/root/old"));
+    assert_eq!(fs.metadata(Path::new("/root/new/deep/f")).unwrap().len, 4);
+}
+
+#[test]
+fn test_real_fs_matches_std() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::write(root.join("b.txt"), b"12345").unwrap();
+    fs::create_dir(root.join("a")).unwrap();
+
+    assert_eq!(RealFs.read_dir(root).unwrap(), vec![OsString::from("a"), "b.txt".into()]);
+    assert_eq!(RealFs.metadata(&root.join("b.txt")).unwrap().len, 5);
+    RealFs.rename(&root.join("b.txt"), &root.join("c.txt")).unwrap();
+    RealFs.remove_file(&root.join("c.txt")).unwrap();
+    RealFs.remove_dir(&root.join("a")).unwrap();
+    assert!(RealFs.read_dir(root).unwrap().is_empty());
+}